@@ -7,6 +7,8 @@ fn main() -> Result<(), Box<dyn Error>> {
     // Change detection of source shaders
     println!("cargo:rerun-if-changed=res/shaders/glsl");
 
+    let out_dir = std::env::var("OUT_DIR")?;
+
     // Compile each shader at source
     for entry in std::fs::read_dir("res/shaders/glsl")? {
         let entry = entry?;
@@ -33,8 +35,10 @@ fn main() -> Result<(), Box<dyn Error>> {
                 let mut compiled_bytes = Vec::new();
                 compiled_file.read_to_end(&mut compiled_bytes)?;
 
+                // Embedded into the crate by `include_bytes!`
                 let out_path = format!(
-                    "res/shaders/spirv/{}.spv",
+                    "{}/{}.spv",
+                    out_dir,
                     in_path.file_name().unwrap().to_string_lossy()
                 );
 
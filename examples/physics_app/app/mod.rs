@@ -1,238 +1,229 @@
 #![allow(unused)]
 
-// std
-use std::time::Instant;
-
 // extern
 extern crate nalgebra_glm as glm;
 use anyhow::Result;
-use rand::{self, Rng, RngCore};
+use rand::{self, rngs::ThreadRng, Rng, RngCore};
 use winit::{
     dpi::{PhysicalPosition, PhysicalSize},
-    event::{DeviceEvent, ElementState, Event, MouseButton, VirtualKeyCode, WindowEvent},
-    event_loop::{ControlFlow, EventLoop, EventLoopBuilder},
-    window::{CursorIcon, WindowBuilder},
+    event::{ElementState, MouseButton, VirtualKeyCode, WindowEvent},
+    event_loop::EventLoop,
+    window::{CursorIcon, Window, WindowBuilder},
 };
 
 const WINDOW_HEIGHT: u32 = 600;
 const WINDOW_WIDTH: u32 = 800;
 
 // intern
-use crate::physics::{ModelType, PhysicsSystem};
-use lavapond::{self, AnchorType, Renderer};
+use lavapond::{
+    self, AnchorType, Body, Collider, FixedTimestep, FrameLimit, FullscreenMode, LoopHandler,
+    LoopRunner, PhysicsSystem, Renderer,
+};
+
+/// Radius of the circle object drawn with a scale of 1.0
+const CIRCLE_RADIUS: f32 = 0.1;
+
+/// Updates of the physics system every second
+const UPDATES_PER_SEC: f32 = 120.0;
 
 /// Runs application
 pub fn run() -> Result<()> {
     // Window
-    let event_loop = EventLoop::new();
+    let mut event_loop = EventLoop::new();
 
-    let mut window_size = PhysicalSize::new(WINDOW_WIDTH, WINDOW_HEIGHT);
     let window = WindowBuilder::new()
         .with_title("lavapond")
-        .with_inner_size(window_size)
+        .with_inner_size(PhysicalSize::new(WINDOW_WIDTH, WINDOW_HEIGHT))
         .build(&event_loop)?;
 
+    // Vulkan Renderer: Frames are limited to the update rate
+    let mut renderer = Renderer::builder()
+        .frame_limit(FrameLimit::Fps(UPDATES_PER_SEC))
+        .build(&window)?;
+
+    // Physics App
+    let mut app = PhysicsApp::new();
+
+    LoopRunner::new(FixedTimestep::new(UPDATES_PER_SEC)).run(
+        &mut event_loop,
+        &window,
+        &mut renderer,
+        &mut app,
+    )
+}
+
+//==================================================
+//=== Physics App
+//==================================================
+
+struct PhysicsApp {
+    physics_system: PhysicsSystem,
+    colors: Vec<glm::Vec3>,
+    rng: ThreadRng,
+
     // Input Handling
-    let mut lmb_down = false;
-    let mut last_mouse_pos: Option<PhysicalPosition<f64>> = None;
-    let mut mouse_pos: PhysicalPosition<f64> = PhysicalPosition::new(0.0, 0.0);
-    let mut center_pos: PhysicalPosition<f64> = PhysicalPosition::new(0.0, 0.0);
-
-    // Physics System
-    let mut physics_system = PhysicsSystem::new();
-
-    // Vulkan Renderer
-    let mut renderer = Renderer::new(&window)?;
-    let mut res: Result<()> = Ok(());
-
-    // Random Generator
-    let mut rng = rand::thread_rng();
-
-    ///////////////// DEBUG /////////////////
-    let mut last_creation_pos: PhysicalPosition<f64> = PhysicalPosition::new(0.0, 0.0);
-
-    physics_system.arena(
-        glm::vec2(10.0, 10.0),
-        glm::vec2(0.0, 0.0),
-        glm::vec2(0.0, 0.0),
-        glm::vec3(0.2, 0.2, 0.2),
-    );
-
-    physics_system.circle(
-        0.1,
-        glm::vec2(0.0, 0.0),
-        glm::vec2(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0)),
-        glm::vec3(
-            rng.gen_range(0.0..1.0),
-            rng.gen_range(0.0..1.0),
-            rng.gen_range(0.0..1.0),
-        ),
-    );
-
-    physics_system.circle(
-        0.1,
-        glm::vec2(-0.8, -0.8),
-        glm::vec2(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0)),
-        glm::vec3(
-            rng.gen_range(0.0..1.0),
-            rng.gen_range(0.0..1.0),
-            rng.gen_range(0.0..1.0),
-        ),
-    );
-
-    physics_system.circle(
-        0.1,
-        glm::vec2(0.8, 0.8),
-        glm::vec2(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0)),
-        glm::vec3(
-            rng.gen_range(0.0..1.0),
-            rng.gen_range(0.0..1.0),
-            rng.gen_range(0.0..1.0),
-        ),
-    );
-
-    ///////////////// DEBUG /////////////////
-
-    event_loop.run(move |event, _, control_flow| {
-        control_flow.set_poll();
+    lmb_down: bool,
+    last_mouse_pos: Option<PhysicalPosition<f64>>,
+    mouse_pos: PhysicalPosition<f64>,
+}
+
+impl PhysicsApp {
+    fn new() -> Self {
+        // Physics System
+        let mut physics_system = PhysicsSystem::new().with_timestep(1.0 / UPDATES_PER_SEC);
+        physics_system.bounds = Some((glm::vec2(-1.0, -1.0), glm::vec2(1.0, 1.0)));
+        physics_system.set_paused(true);
+
+        let mut app = Self {
+            physics_system,
+            colors: Vec::new(),
+            rng: rand::thread_rng(),
+            lmb_down: false,
+            last_mouse_pos: None,
+            mouse_pos: PhysicalPosition::new(0.0, 0.0),
+        };
+
+        for position in [
+            glm::vec2(0.0, 0.0),
+            glm::vec2(-0.8, -0.8),
+            glm::vec2(0.8, 0.8),
+        ] {
+            app.add_circle(0.05, position);
+        }
+
+        app
+    }
+
+    /// Adds a circle with a random velocity and color
+    fn add_circle(&mut self, radius: f32, position: glm::Vec2) {
+        self.physics_system.add(Body::circle(
+            radius,
+            position,
+            glm::vec2(self.rng.gen_range(-1.0..1.0), self.rng.gen_range(-1.0..1.0)),
+        ));
+
+        self.colors.push(glm::vec3(
+            self.rng.gen_range(0.0..1.0),
+            self.rng.gen_range(0.0..1.0),
+            self.rng.gen_range(0.0..1.0),
+        ));
+    }
+}
+
+impl LoopHandler for PhysicsApp {
+    fn update(&mut self, dt: f32) -> Result<()> {
+        self.physics_system.step(dt);
+
+        Ok(())
+    }
+
+    fn render(&mut self, renderer: &mut Renderer, _alpha: f32) -> Result<()> {
+        // Arena
+        renderer.rectangle(
+            10.0,
+            10.0,
+            0.0,
+            0.0,
+            0.0,
+            glm::vec3(0.2, 0.2, 0.2),
+            AnchorType::Locked,
+        )?;
+
+        // Draw Objects From Physics System Bodies
+        for (body, color) in self.physics_system.bodies().iter().zip(&self.colors) {
+            if let Collider::Circle { radius } = body.collider {
+                renderer.circle(
+                    radius / CIRCLE_RADIUS,
+                    body.position.x,
+                    body.position.y,
+                    *color,
+                    AnchorType::Unlocked,
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn event(
+        &mut self,
+        event: &WindowEvent,
+        window: &Window,
+        renderer: &mut Renderer,
+    ) -> Result<()> {
         match event {
-            Event::MainEventsCleared => {
-                // Physics System
-                physics_system.update();
-
-                // Draw Objects From Physics System Models
-                for model in &physics_system.models {
-                    match model.model_type {
-                        ModelType::Circle(radius, color) => {
-                            renderer.circle(
-                                radius * 2.0,
-                                model.position.x,
-                                model.position.y,
-                                color,
-                                AnchorType::Unlocked,
-                            );
+            WindowEvent::KeyboardInput { input, .. } => {
+                if let Some(key) = input.virtual_keycode {
+                    match key {
+                        VirtualKeyCode::C if input.state == ElementState::Released => {
+                            let position = renderer.scene.screen_to_world(glm::vec2(
+                                self.mouse_pos.x as f32,
+                                self.mouse_pos.y as f32,
+                            ));
+
+                            let radius = self.rng.gen_range(0.01..0.05);
+                            self.add_circle(radius, position);
+                        }
+                        VirtualKeyCode::Space if input.state == ElementState::Released => {
+                            self.physics_system.switch_state()
                         }
-                        ModelType::Arena(x, y, color) => {
-                            renderer.rectangle(
-                                x,
-                                y,
-                                0.0,
-                                model.position.x,
-                                model.position.y,
-                                color,
-                                AnchorType::Locked,
-                            );
+                        VirtualKeyCode::F11 if input.state == ElementState::Released => {
+                            let mode = if renderer.fullscreen_mode() == FullscreenMode::Windowed {
+                                FullscreenMode::Borderless
+                            } else {
+                                FullscreenMode::Windowed
+                            };
+
+                            renderer.set_fullscreen(window, mode)?;
                         }
+                        _ => (),
                     }
                 }
-
-                // Renderer
-                res = control_flow.check_result(renderer.draw_request(&window));
             }
-            Event::WindowEvent { event, .. } => match event {
-                WindowEvent::CloseRequested => control_flow.set_exit(),
-                WindowEvent::Resized(new_size) => {
-                    if new_size == window.inner_size() {
-                        window_size = new_size;
-                        res = control_flow.check_result(renderer.recreate_swapchain(new_size));
-                    }
+            WindowEvent::MouseWheel { delta, .. } => {
+                if let winit::event::MouseScrollDelta::LineDelta(_, dir) = delta {
+                    renderer.scene.zoom_at(
+                        glm::vec2(self.mouse_pos.x as f32, self.mouse_pos.y as f32),
+                        dir * 0.1,
+                    );
                 }
-                WindowEvent::KeyboardInput { input, .. } => {
-                    if let Some(key) = input.virtual_keycode {
-                        match key {
-                            VirtualKeyCode::C if input.state == ElementState::Released => {
-                                let window_width = window_size.width as f64;
-                                let window_height = window_size.height as f64;
-
-                                let x = ((2.0 * (mouse_pos.x - center_pos.x) - window_width)
-                                    / window_width) as f32;
-                                let y = -((2.0 * (mouse_pos.y - center_pos.y) - window_height)
-                                    / window_height) as f32;
-
-                                physics_system.circle(
-                                    rng.gen_range(0.1..0.5),
-                                    glm::vec2(x, y),
-                                    glm::vec2(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0)),
-                                    glm::vec3(
-                                        rng.gen_range(0.0..1.0),
-                                        rng.gen_range(0.0..1.0),
-                                        rng.gen_range(0.0..1.0),
-                                    ),
-                                );
-                            }
-                            VirtualKeyCode::Space if input.state == ElementState::Released => {
-                                physics_system.switch_state()
-                            }
-                            _ => (),
+            }
+            WindowEvent::MouseInput { button, state, .. } => {
+                if let MouseButton::Left = button {
+                    match state {
+                        ElementState::Pressed => {
+                            self.lmb_down = true;
+                            window.set_cursor_icon(CursorIcon::Grabbing)
                         }
-                    }
-                }
-                WindowEvent::MouseWheel { delta, .. } => {
-                    if let winit::event::MouseScrollDelta::LineDelta(_, dir) = delta {
-                        renderer.scene.zoom(dir * 0.1);
-                    }
-                }
-                WindowEvent::MouseInput { button, state, .. } => {
-                    if let MouseButton::Left = button {
-                        match state {
-                            ElementState::Pressed => {
-                                lmb_down = true;
-                                window.set_cursor_icon(CursorIcon::Grabbing)
-                            }
-                            ElementState::Released => {
-                                lmb_down = false;
-                                window.set_cursor_icon(CursorIcon::Default);
-                            }
+                        ElementState::Released => {
+                            self.lmb_down = false;
+                            window.set_cursor_icon(CursorIcon::Default);
                         }
                     }
                 }
-                WindowEvent::CursorMoved { position, .. } => {
-                    mouse_pos = position;
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                self.mouse_pos = *position;
 
-                    if lmb_down {
-                        if let Some(last_position) = last_mouse_pos {
-                            let window_width = window_size.width as f64;
-                            let window_height = window_size.height as f64;
+                if self.lmb_down {
+                    if let Some(last_position) = self.last_mouse_pos {
+                        let window_size = window.inner_size();
+                        let window_width = window_size.width as f64;
+                        let window_height = window_size.height as f64;
 
-                            let x = ((last_position.x - mouse_pos.x) / window_width) as f32;
-                            let y = ((last_position.y - mouse_pos.y) / window_height) as f32;
+                        let x = ((last_position.x - self.mouse_pos.x) / window_width) as f32;
+                        let y = ((last_position.y - self.mouse_pos.y) / window_height) as f32;
 
-                            renderer.scene.pan_view_xy(x, y);
-                            center_pos.x += x as f64;
-                            center_pos.y += y as f64;
-                        }
-
-                        last_mouse_pos = Some(position);
-                    } else {
-                        last_mouse_pos = None;
+                        renderer.scene.pan_view_xy(x, y);
                     }
-                }
 
-                _ => (),
-            },
-            // Event::DeviceEvent { event, .. } => match event {
-            //     DeviceEvent::MouseMotion { delta } => {}
-            //     _ => (),
-            // },
+                    self.last_mouse_pos = Some(*position);
+                } else {
+                    self.last_mouse_pos = None;
+                }
+            }
             _ => (),
         }
-    });
-
-    res
-}
-
-trait EventResult {
-    fn check_result(&mut self, result: Result<()>) -> Result<()> {
-        Ok(())
-    }
-}
-
-impl EventResult for ControlFlow {
-    fn check_result(&mut self, result: Result<()>) -> Result<()> {
-        if let Err(e) = result {
-            self.set_exit();
-            return Err(e);
-        }
 
         Ok(())
     }
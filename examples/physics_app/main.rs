@@ -1,5 +1,4 @@
 mod app;
-mod physics;
 
 fn main() -> () {
     let app = app::run();
@@ -0,0 +1,35 @@
+#version 450
+
+layout(set = 0, binding = 0) uniform sampler2D frame_sampler;
+
+// Params: x -> Threshold, y -> Intensity
+layout(push_constant) uniform post_params {
+    vec4 params;
+    vec2 texel_size;
+} post;
+
+layout(location = 0) in vec2 fragment_uv;
+
+layout(location = 0) out vec4 out_color;
+
+void main() {
+    vec4 color = texture(frame_sampler, fragment_uv);
+
+    // Gaussian blur of the pixels above the threshold, sampled every second texel
+    vec3 glow = vec3(0.0);
+    float total_weight = 0.0;
+
+    for (int x = -4; x <= 4; x++) {
+        for (int y = -4; y <= 4; y++) {
+            vec2 offset = vec2(x, y) * post.texel_size * 2.0;
+            vec3 sample_color = texture(frame_sampler, fragment_uv + offset).rgb;
+            float brightness = max(sample_color.r, max(sample_color.g, sample_color.b));
+            float weight = exp(-float(x * x + y * y) / 8.0);
+
+            glow += sample_color * step(post.params.x, brightness) * weight;
+            total_weight += weight;
+        }
+    }
+
+    out_color = vec4(color.rgb + glow / total_weight * post.params.y, color.a);
+}
@@ -0,0 +1,30 @@
+#version 450
+
+layout(push_constant) uniform EguiParams {
+    vec2 screen_size;
+    uint srgb_target;
+} params;
+
+layout(set = 0, binding = 0) uniform sampler2D texture_sampler;
+
+layout(location = 0) in vec4 fragment_color;
+layout(location = 1) in vec2 fragment_uv;
+
+layout(location = 0) out vec4 out_color;
+
+vec3 srgb_from_linear(vec3 linear) {
+    vec3 lower = linear * 12.92;
+    vec3 higher = 1.055 * pow(linear, vec3(1.0 / 2.4)) - 0.055;
+    return mix(higher, lower, vec3(lessThan(linear, vec3(0.0031308))));
+}
+
+void main() {
+    vec4 color = fragment_color * texture(texture_sampler, fragment_uv);
+
+    // UNORM Target -> No conversion by the hardware
+    if (params.srgb_target == 0) {
+        color.rgb = srgb_from_linear(color.rgb);
+    }
+
+    out_color = color;
+}
@@ -0,0 +1,28 @@
+#version 450
+
+layout(push_constant) uniform EguiParams {
+    vec2 screen_size;
+    uint srgb_target;
+} params;
+
+layout(location = 0) in vec2 vertex_position;
+layout(location = 1) in vec2 vertex_uv;
+layout(location = 2) in vec4 vertex_color;
+
+layout(location = 0) out vec4 fragment_color;
+layout(location = 1) out vec2 fragment_uv;
+
+vec3 linear_from_srgb(vec3 srgb) {
+    vec3 lower = srgb / 12.92;
+    vec3 higher = pow((srgb + 0.055) / 1.055, vec3(2.4));
+    return mix(higher, lower, vec3(lessThan(srgb, vec3(0.04045))));
+}
+
+// Egui points are placed from the top left corner of the screen
+void main() {
+    gl_Position = vec4(2.0 * vertex_position / params.screen_size - 1.0, 0.0, 1.0);
+
+    // Premultiplied sRGB color, blended in linear space
+    fragment_color = vec4(linear_from_srgb(vertex_color.rgb), vertex_color.a);
+    fragment_uv = vertex_uv;
+}
@@ -0,0 +1,19 @@
+#version 450
+
+layout(set = 0, binding = 0) uniform sampler2D frame_sampler;
+
+// Params: x -> Gamma
+layout(push_constant) uniform post_params {
+    vec4 params;
+    vec2 texel_size;
+} post;
+
+layout(location = 0) in vec2 fragment_uv;
+
+layout(location = 0) out vec4 out_color;
+
+void main() {
+    vec4 color = texture(frame_sampler, fragment_uv);
+
+    out_color = vec4(pow(color.rgb, vec3(1.0 / max(post.params.x, 0.0001))), color.a);
+}
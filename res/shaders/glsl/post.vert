@@ -0,0 +1,9 @@
+#version 450
+
+layout(location = 0) out vec2 fragment_uv;
+
+// Full-screen triangle generated from the vertex index, no vertex buffer is bound
+void main() {
+    fragment_uv = vec2(gl_VertexIndex & 2, (gl_VertexIndex << 1) & 2);
+    gl_Position = vec4(fragment_uv * 2.0 - 1.0, 0.0, 1.0);
+}
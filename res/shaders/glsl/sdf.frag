@@ -0,0 +1,63 @@
+#version 450
+
+layout(location = 0) in vec3 fragment_color;
+layout(location = 1) in vec3 fragment_gradient_color;
+layout(location = 2) in vec2 fragment_position;
+layout(location = 3) flat in vec4 fragment_gradient_points;
+layout(location = 4) flat in float fragment_gradient_mode;
+layout(location = 5) in vec2 fragment_shape_position;
+layout(location = 6) flat in vec4 fragment_shape;
+
+layout(location = 0) out vec4 out_color;
+
+// Gradient: 0 -> Flat, 1 -> Linear, 2 -> Radial
+vec3 gradient(vec3 color, vec3 gradient_color, vec2 position, vec4 points, float mode) {
+    vec2 start = points.xy;
+    vec2 end = points.zw;
+    vec2 delta = end - start;
+    float t = 0.0;
+
+    if (mode > 1.5) {
+        t = length(position - start) / max(length(delta), 0.0001);
+    } else if (mode > 0.5) {
+        t = dot(position - start, delta) / max(dot(delta, delta), 0.0001);
+    } else {
+        return color;
+    }
+
+    return mix(color, gradient_color, clamp(t, 0.0, 1.0));
+}
+
+// Signed distance to the edge of a rounded rectangle, negative inside
+// Corner radius of half the shorter side -> Circle or capsule
+float rounded_rectangle(vec2 position, vec2 half_size, float corner_radius) {
+    float radius = clamp(corner_radius, 0.0, min(half_size.x, half_size.y));
+    vec2 q = abs(position) - half_size + radius;
+
+    return length(max(q, 0.0)) + min(max(q.x, q.y), 0.0) - radius;
+}
+
+void main() {
+    vec3 color = gradient(
+        fragment_color,
+        fragment_gradient_color,
+        fragment_position,
+        fragment_gradient_points,
+        fragment_gradient_mode
+    );
+
+    float distance = rounded_rectangle(fragment_shape_position, fragment_shape.xy, fragment_shape.z);
+
+    // Border: Only the band of its thickness inside the edge, zero fills the shape
+    if (fragment_shape.w > 0.0) {
+        distance = max(distance, -(distance + fragment_shape.w));
+    }
+
+    // Anti-Aliasing: Coverage of the pixel across the edge in screen space
+    float coverage = clamp(0.5 - distance / max(fwidth(distance), 0.0001), 0.0, 1.0);
+    if (coverage <= 0.0) {
+        discard;
+    }
+
+    out_color = vec4(color, coverage);
+}
@@ -6,19 +6,39 @@ layout(binding = 0) uniform camera_vp {
     mat4 proj;
 } camera;
 
-// Model Data: Transform & Color
-layout( push_constant ) uniform model_data {
-	mat4 transform;
+// Model Data: Transform, Color, Atlas Region & Gradient
+struct ModelData {
+    mat4 transform;
     vec3 color;
-} model;
+    vec4 uv_rect;
+    vec4 gradient_color;
+    vec4 gradient_points;
+};
+
+// Draw Instances: gl_InstanceIndex includes the first instance of the batch
+layout(std430, binding = 1) readonly buffer instance_data {
+    ModelData instances[];
+};
 
 // Vertex Properties
 layout(location = 0) in vec3 vertex_position;
 layout(location = 1) in vec3 vertex_color;
 
 layout(location = 0) out vec3 out_color;
+layout(location = 1) out vec3 out_gradient_color;
+layout(location = 2) out vec2 out_position;
+layout(location = 3) flat out vec4 out_gradient_points;
+layout(location = 4) flat out float out_gradient_mode;
 
 void main() {
+    ModelData model = instances[gl_InstanceIndex];
+
     gl_Position = camera.proj * camera.view * model.transform * vec4(vertex_position, 1.0);
     out_color = vertex_color * model.color;
+
+    // Gradient: Interpolated in the fragment shader based on the local position
+    out_gradient_color = vertex_color * model.gradient_color.rgb;
+    out_position = vertex_position.xy;
+    out_gradient_points = model.gradient_points;
+    out_gradient_mode = model.gradient_color.w;
 }
\ No newline at end of file
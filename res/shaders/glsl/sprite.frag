@@ -0,0 +1,42 @@
+#version 450
+
+layout(set = 1, binding = 0) uniform sampler2D texture_sampler;
+
+layout(location = 0) in vec3 fragment_color;
+layout(location = 1) in vec2 fragment_uv;
+layout(location = 2) in vec3 fragment_gradient_color;
+layout(location = 3) in vec2 fragment_position;
+layout(location = 4) flat in vec4 fragment_gradient_points;
+layout(location = 5) flat in float fragment_gradient_mode;
+
+layout(location = 0) out vec4 out_color;
+
+// Gradient: 0 -> Flat, 1 -> Linear, 2 -> Radial
+vec3 gradient(vec3 color, vec3 gradient_color, vec2 position, vec4 points, float mode) {
+    vec2 start = points.xy;
+    vec2 end = points.zw;
+    vec2 delta = end - start;
+    float t = 0.0;
+
+    if (mode > 1.5) {
+        t = length(position - start) / max(length(delta), 0.0001);
+    } else if (mode > 0.5) {
+        t = dot(position - start, delta) / max(dot(delta, delta), 0.0001);
+    } else {
+        return color;
+    }
+
+    return mix(color, gradient_color, clamp(t, 0.0, 1.0));
+}
+
+void main() {
+    vec3 color = gradient(
+        fragment_color,
+        fragment_gradient_color,
+        fragment_position,
+        fragment_gradient_points,
+        fragment_gradient_mode
+    );
+
+    out_color = texture(texture_sampler, fragment_uv) * vec4(color, 1.0);
+}
@@ -0,0 +1,51 @@
+#version 450
+
+// Camera: View & Projection
+layout(binding = 0) uniform camera_vp {
+    mat4 view;
+    mat4 proj;
+} camera;
+
+// Model Data: Transform, Color, Atlas Region & Gradient
+struct ModelData {
+    mat4 transform;
+    vec3 color;
+    vec4 uv_rect;
+    vec4 gradient_color;
+    vec4 gradient_points;
+};
+
+// Draw Instances: gl_InstanceIndex includes the first instance of the batch
+layout(std430, binding = 1) readonly buffer instance_data {
+    ModelData instances[];
+};
+
+// Vertex Properties
+layout(location = 0) in vec3 vertex_position;
+layout(location = 1) in vec3 vertex_color;
+
+layout(location = 0) out vec3 out_color;
+layout(location = 1) out vec2 out_uv;
+layout(location = 2) out vec3 out_gradient_color;
+layout(location = 3) out vec2 out_position;
+layout(location = 4) flat out vec4 out_gradient_points;
+layout(location = 5) flat out float out_gradient_mode;
+
+void main() {
+    ModelData model = instances[gl_InstanceIndex];
+
+    gl_Position = camera.proj * camera.view * model.transform * vec4(vertex_position, 1.0);
+    out_color = vertex_color * model.color;
+
+    // Gradient: Interpolated in the fragment shader based on the local position
+    out_gradient_color = vertex_color * model.gradient_color.rgb;
+    out_position = vertex_position.xy;
+    out_gradient_points = model.gradient_points;
+    out_gradient_mode = model.gradient_color.w;
+
+    // Rectangle object spans [-0.1, 0.1] -> UV [0, 1] with the image top at the rectangle top
+    vec2 uv = vec2(vertex_position.x * 5.0 + 0.5, 0.5 - vertex_position.y * 5.0);
+
+    // Atlas Region: Offset & Scale
+    out_uv = model.uv_rect.xy + uv * model.uv_rect.zw;
+}
@@ -0,0 +1,23 @@
+#version 450
+
+layout(set = 0, binding = 0) uniform sampler2D frame_sampler;
+
+// Params: x -> Strength, y -> Radius
+layout(push_constant) uniform post_params {
+    vec4 params;
+    vec2 texel_size;
+} post;
+
+layout(location = 0) in vec2 fragment_uv;
+
+layout(location = 0) out vec4 out_color;
+
+void main() {
+    vec4 color = texture(frame_sampler, fragment_uv);
+
+    // Distance: 0 -> Center, 1 -> Corner
+    float distance = length(fragment_uv - 0.5) * 1.41421356;
+    float vignette = 1.0 - post.params.x * smoothstep(post.params.y, 1.0, distance);
+
+    out_color = vec4(color.rgb * vignette, color.a);
+}
@@ -0,0 +1,267 @@
+// std
+use std::ffi::c_void;
+
+// extern
+use anyhow::{anyhow, Result};
+use ash::vk;
+
+//==================================================
+//=== Memory Allocator
+//==================================================
+
+/// Part of a memory block handed out by the [`MemoryAllocator`]
+#[derive(Debug, Clone, Copy)]
+pub struct Allocation {
+    pub memory: vk::DeviceMemory,
+    pub offset: u64,
+    pub size: u64,
+    /// Host address of the allocation, null when the memory is not host visible
+    pub mapped: *mut c_void,
+    block_index: usize,
+}
+
+/// Large `vk::DeviceMemory` allocation shared by multiple resources
+struct MemoryBlock {
+    memory: vk::DeviceMemory,
+    memory_type_index: u32,
+    /// Buffers and images never share a block, so the buffer-image granularity can be ignored
+    linear: bool,
+    /// Host visible blocks stay mapped for their whole lifetime
+    mapped: *mut c_void,
+    free_list: FreeList,
+}
+
+/// Suballocates buffer and image memory from a few large blocks
+///
+/// Devices only guarantee a low number of allocations (`maxMemoryAllocationCount`),
+/// so resources must not get their own `vk::DeviceMemory` each
+pub struct MemoryAllocator {
+    memory_properties: vk::PhysicalDeviceMemoryProperties,
+    blocks: Vec<MemoryBlock>,
+}
+
+impl MemoryAllocator {
+    /// Size of a new memory block, larger requests get a block of their own size
+    const BLOCK_SIZE: u64 = 64 * 1024 * 1024;
+
+    /// Creates a new [`MemoryAllocator`] for the memory types in `memory_properties`
+    pub fn new(memory_properties: vk::PhysicalDeviceMemoryProperties) -> Self {
+        Self {
+            memory_properties,
+            blocks: Vec::new(),
+        }
+    }
+
+    /// Allocates memory for a buffer (`linear`) or an image with `property_flags`
+    ///
+    /// A new block is allocated only if none of the existing ones has enough free space
+    pub fn allocate(
+        &mut self,
+        logical_device: &ash::Device,
+        requirements: vk::MemoryRequirements,
+        property_flags: vk::MemoryPropertyFlags,
+        linear: bool,
+    ) -> Result<Allocation> {
+        let memory_type_index =
+            self.find_memory_type(requirements.memory_type_bits, property_flags)?;
+
+        for (block_index, block) in self.blocks.iter_mut().enumerate() {
+            if block.memory_type_index != memory_type_index || block.linear != linear {
+                continue;
+            }
+
+            if let Some(offset) = block
+                .free_list
+                .allocate(requirements.size, requirements.alignment)
+            {
+                return Ok(block.allocation(block_index, offset, requirements.size));
+            }
+        }
+
+        /* New Block */
+
+        let block_size = requirements.size.max(Self::BLOCK_SIZE);
+
+        let memory = {
+            let allocate_info = vk::MemoryAllocateInfo::builder()
+                .allocation_size(block_size)
+                .memory_type_index(memory_type_index);
+
+            unsafe { logical_device.allocate_memory(&allocate_info, None) }?
+        };
+
+        let mapped = if property_flags.contains(vk::MemoryPropertyFlags::HOST_VISIBLE) {
+            unsafe {
+                logical_device.map_memory(memory, 0, vk::WHOLE_SIZE, vk::MemoryMapFlags::empty())
+            }?
+        } else {
+            std::ptr::null_mut()
+        };
+
+        let mut block = MemoryBlock {
+            memory,
+            memory_type_index,
+            linear,
+            mapped,
+            free_list: FreeList::new(block_size),
+        };
+
+        let offset = block
+            .free_list
+            .allocate(requirements.size, requirements.alignment)
+            .ok_or_else(|| anyhow!("Memory: Block is too small for the allocation"))?;
+
+        let allocation = block.allocation(self.blocks.len(), offset, requirements.size);
+        self.blocks.push(block);
+
+        Ok(allocation)
+    }
+
+    /// Gives back the memory of `allocation` to its block
+    ///
+    /// Blocks are kept for later allocations even when they become empty
+    pub fn free(&mut self, allocation: &Allocation) {
+        if let Some(block) = self.blocks.get_mut(allocation.block_index) {
+            block.free_list.free(allocation.offset, allocation.size);
+        }
+    }
+
+    /// Frees every memory block, the resources using them must already be destroyed
+    pub fn destroy(&mut self, logical_device: &ash::Device) {
+        for block in self.blocks.drain(..) {
+            unsafe { logical_device.free_memory(block.memory, None) };
+        }
+    }
+
+    /// Gives back the index of the first memory type allowed by `memory_type_bits`
+    /// which has every `property_flags`
+    fn find_memory_type(
+        &self,
+        memory_type_bits: u32,
+        property_flags: vk::MemoryPropertyFlags,
+    ) -> Result<u32> {
+        self.memory_properties.memory_types[..self.memory_properties.memory_type_count as usize]
+            .iter()
+            .enumerate()
+            .find(|(index, memory_type)| {
+                memory_type_bits & (1 << index) != 0
+                    && memory_type.property_flags.contains(property_flags)
+            })
+            .map(|(index, _)| index as u32)
+            .ok_or_else(|| anyhow!("Memory: No memory type with {:?}", property_flags))
+    }
+}
+
+impl MemoryBlock {
+    fn allocation(&self, block_index: usize, offset: u64, size: u64) -> Allocation {
+        let mapped = if self.mapped.is_null() {
+            std::ptr::null_mut()
+        } else {
+            unsafe { self.mapped.add(offset as usize) }
+        };
+
+        Allocation {
+            memory: self.memory,
+            offset,
+            size,
+            mapped,
+            block_index,
+        }
+    }
+}
+
+//==================================================
+//=== Free List
+//==================================================
+
+/// Free `(offset, size)` ranges of a memory block, sorted by offset
+#[derive(Debug)]
+struct FreeList {
+    ranges: Vec<(u64, u64)>,
+}
+
+impl FreeList {
+    fn new(size: u64) -> Self {
+        Self {
+            ranges: vec![(0, size)],
+        }
+    }
+
+    /// Takes the first free range which fits `size` bytes at `alignment`
+    fn allocate(&mut self, size: u64, alignment: u64) -> Option<u64> {
+        let alignment = alignment.max(1);
+
+        for index in 0..self.ranges.len() {
+            let (offset, range_size) = self.ranges[index];
+            let aligned_offset = offset.next_multiple_of(alignment);
+            let padding = aligned_offset - offset;
+
+            if padding + size > range_size {
+                continue;
+            }
+
+            // Padding before and space after the allocation stays free
+            let end = aligned_offset + size;
+            let range_end = offset + range_size;
+
+            self.ranges.remove(index);
+            if range_end > end {
+                self.ranges.insert(index, (end, range_end - end));
+            }
+            if padding > 0 {
+                self.ranges.insert(index, (offset, padding));
+            }
+
+            return Some(aligned_offset);
+        }
+
+        None
+    }
+
+    /// Returns a range, merging it with its free neighbours
+    fn free(&mut self, offset: u64, size: u64) {
+        let mut index = self.ranges.partition_point(|&(o, _)| o < offset);
+        self.ranges.insert(index, (offset, size));
+
+        if index > 0 && self.ranges[index - 1].0 + self.ranges[index - 1].1 == offset {
+            self.ranges[index - 1].1 += size;
+            self.ranges.remove(index);
+            index -= 1;
+        }
+
+        if index + 1 < self.ranges.len()
+            && self.ranges[index].0 + self.ranges[index].1 == self.ranges[index + 1].0
+        {
+            self.ranges[index].1 += self.ranges[index + 1].1;
+            self.ranges.remove(index + 1);
+        }
+    }
+}
+
+//==================================================
+//=== Unit Testing
+//==================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_free_list() {
+        let mut free_list = FreeList::new(256);
+
+        let a = free_list.allocate(10, 1).unwrap();
+        let b = free_list.allocate(64, 64).unwrap();
+        let c = free_list.allocate(100, 4).unwrap();
+        assert_eq!((a, b, c), (0, 64, 128));
+
+        // No range left for the request
+        assert_eq!(free_list.allocate(200, 1), None);
+
+        // Freed ranges merge back into a single block
+        free_list.free(b, 64);
+        free_list.free(a, 10);
+        free_list.free(c, 100);
+        assert_eq!(free_list.ranges, vec![(0, 256)]);
+    }
+}
@@ -0,0 +1,514 @@
+// std
+use std::time::Instant;
+
+// extern
+extern crate nalgebra_glm as glm;
+
+// intern
+use crate::{AtlasRegionId, ObjectInstance, PersistentId, Renderer, RendererResult};
+
+//==================================================
+//=== Easing
+//==================================================
+
+/// Curve of the progress of a [`Tween`] over its duration
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Easing {
+    #[default]
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    SineInOut,
+    /// Overshoots the end a little before settling
+    BackOut,
+    /// Bounces against the end like a dropped ball
+    BounceOut,
+    /// Springs around the end before settling
+    ElasticOut,
+}
+
+impl Easing {
+    /// Gives back the eased progress of the linear progress `t` between 0.0 and 1.0
+    ///
+    /// Every curve starts at 0.0 and ends at 1.0, the ones overshooting go beyond in between
+    pub fn apply(&self, t: f32) -> f32 {
+        use std::f32::consts::PI;
+
+        let t = t.clamp(0.0, 1.0);
+
+        match self {
+            Easing::Linear => t,
+            Easing::QuadIn => t * t,
+            Easing::QuadOut => 1.0 - (1.0 - t) * (1.0 - t),
+            Easing::QuadInOut if t < 0.5 => 2.0 * t * t,
+            Easing::QuadInOut => 1.0 - (-2.0 * t + 2.0).powi(2) / 2.0,
+            Easing::CubicIn => t * t * t,
+            Easing::CubicOut => 1.0 - (1.0 - t).powi(3),
+            Easing::CubicInOut if t < 0.5 => 4.0 * t * t * t,
+            Easing::CubicInOut => 1.0 - (-2.0 * t + 2.0).powi(3) / 2.0,
+            Easing::SineInOut => -((PI * t).cos() - 1.0) / 2.0,
+            Easing::BackOut => {
+                const OVERSHOOT: f32 = 1.70158;
+                1.0 + (OVERSHOOT + 1.0) * (t - 1.0).powi(3) + OVERSHOOT * (t - 1.0).powi(2)
+            }
+            Easing::BounceOut => bounce_out(t),
+            Easing::ElasticOut if t == 0.0 || t == 1.0 => t,
+            Easing::ElasticOut => {
+                2f32.powf(-10.0 * t) * ((t * 10.0 - 0.75) * (2.0 * PI / 3.0)).sin() + 1.0
+            }
+        }
+    }
+}
+
+/// Four bounces of shrinking height, the last one ends at 1.0
+fn bounce_out(t: f32) -> f32 {
+    const N: f32 = 7.5625;
+    const D: f32 = 2.75;
+
+    if t < 1.0 / D {
+        N * t * t
+    } else if t < 2.0 / D {
+        let t = t - 1.5 / D;
+        N * t * t + 0.75
+    } else if t < 2.5 / D {
+        let t = t - 2.25 / D;
+        N * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / D;
+        N * t * t + 0.984375
+    }
+}
+
+//==================================================
+//=== Tween
+//==================================================
+
+/// Property of the instances of a target changed by a [`Tween`], from the first value to the
+/// second one
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TweenProperty {
+    Position(glm::Vec2, glm::Vec2),
+    Scale(glm::Vec2, glm::Vec2),
+    /// Rotation in degrees
+    Rotation(f32, f32),
+    Color(glm::Vec3, glm::Vec3),
+}
+
+impl TweenProperty {
+    /// Sets the property of `instance` to its value at the eased progress `t`
+    fn apply(&self, t: f32, instance: &mut ObjectInstance) {
+        match self {
+            TweenProperty::Position(from, to) => {
+                let position = glm::lerp(from, to, t);
+                instance.position = glm::vec3(position.x, position.y, instance.position.z);
+            }
+            TweenProperty::Scale(from, to) => {
+                let scale = glm::lerp(from, to, t);
+                instance.scale = glm::vec3(scale.x, scale.y, instance.scale.z);
+            }
+            TweenProperty::Rotation(from, to) => instance.rotation = from + (to - from) * t,
+            TweenProperty::Color(from, to) => instance.color = glm::lerp(from, to, t),
+        }
+    }
+}
+
+/// Playback of a [`Tween`] after its first run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Repeat {
+    /// Stops at the end value
+    #[default]
+    Once,
+    /// Starts again from the first value
+    Loop,
+    /// Runs back and forth between the values
+    PingPong,
+}
+
+/// Change of a property over time, played by an [`Animator`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Tween {
+    pub property: TweenProperty,
+    /// Seconds of a single run
+    pub duration: f32,
+    pub easing: Easing,
+    /// Seconds before the tween starts, the property is left unchanged until then
+    pub delay: f32,
+    pub repeat: Repeat,
+}
+
+impl Tween {
+    /// Creates a new linear [`Tween`] of `property` running once for `duration` seconds
+    pub fn new(property: TweenProperty, duration: f32) -> Self {
+        Self {
+            property,
+            duration,
+            easing: Easing::Linear,
+            delay: 0.0,
+            repeat: Repeat::Once,
+        }
+    }
+
+    /// Sets the curve of the progress
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    /// Starts the tween `delay` seconds after it was added, eg. to chain tweens one after another
+    pub fn with_delay(mut self, delay: f32) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Sets the playback after the first run
+    pub fn with_repeat(mut self, repeat: Repeat) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    /// Gives back the eased progress `elapsed` seconds after the tween was added, `None` during
+    /// the delay
+    pub fn progress(&self, elapsed: f32) -> Option<f32> {
+        let time = elapsed - self.delay;
+        if time < 0.0 {
+            return None;
+        }
+
+        // Zero duration -> Jumps to the end
+        let runs = time / self.duration.max(f32::EPSILON);
+        let t = match self.repeat {
+            Repeat::Once => runs.min(1.0),
+            Repeat::Loop => runs.fract(),
+            Repeat::PingPong if runs % 2.0 < 1.0 => runs.fract(),
+            Repeat::PingPong => 1.0 - runs.fract(),
+        };
+
+        Some(self.easing.apply(t))
+    }
+
+    /// Checks whether the tween reached its end value `elapsed` seconds after it was added,
+    /// repeating tweens never finish
+    pub fn is_finished(&self, elapsed: f32) -> bool {
+        self.repeat == Repeat::Once && elapsed >= self.delay + self.duration
+    }
+
+    /// Sets the property of `instance` to its value `elapsed` seconds after the tween was added
+    pub fn apply(&self, elapsed: f32, instance: &mut ObjectInstance) {
+        if let Some(t) = self.progress(elapsed) {
+            self.property.apply(t, instance);
+        }
+    }
+}
+
+//==================================================
+//=== Flipbook
+//==================================================
+
+/// Sprite animation stepping through atlas regions at a fixed rate
+///
+/// Played on persistent sprites by [`Renderer::play_flipbook`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Flipbook {
+    pub frames: Vec<AtlasRegionId>,
+    /// Frames per second
+    pub fps: f32,
+    pub repeat: Repeat,
+}
+
+impl Flipbook {
+    /// Creates a new [`Flipbook`] showing `frames` at `fps` frames per second, played once
+    pub fn new(frames: Vec<AtlasRegionId>, fps: f32) -> Self {
+        Self {
+            frames,
+            fps,
+            repeat: Repeat::Once,
+        }
+    }
+
+    pub fn with_repeat(mut self, repeat: Repeat) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    /// Gives back the index of the frame shown `elapsed` seconds after the start
+    pub fn frame_index(&self, elapsed: f32) -> usize {
+        let count = self.frames.len();
+        if count <= 1 || self.fps <= 0.0 {
+            return 0;
+        }
+
+        let step = (elapsed.max(0.0) * self.fps) as usize;
+        match self.repeat {
+            Repeat::Once => step.min(count - 1),
+            Repeat::Loop => step % count,
+            Repeat::PingPong => {
+                // The first and last frames are not doubled at the turns
+                let period = 2 * count - 2;
+                let step = step % period;
+                if step < count {
+                    step
+                } else {
+                    period - step
+                }
+            }
+        }
+    }
+
+    /// Gives back the frame shown `elapsed` seconds after the start
+    pub fn frame(&self, elapsed: f32) -> Option<AtlasRegionId> {
+        self.frames.get(self.frame_index(elapsed)).copied()
+    }
+
+    /// `true` if the last frame of a flipbook played once is reached
+    pub fn is_finished(&self, elapsed: f32) -> bool {
+        self.repeat == Repeat::Once && self.frame_index(elapsed) + 1 >= self.frames.len()
+    }
+}
+
+/// Flipbook playing on the instances of a target, advanced by the renderer in `draw_request`
+#[derive(Debug, Clone)]
+pub(crate) struct FlipbookPlayback {
+    pub target: PersistentId,
+    pub flipbook: Flipbook,
+    pub start: Instant,
+}
+
+//==================================================
+//=== Animator
+//==================================================
+
+/// Handle of a [`Tween`] played by an [`Animator`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TweenId(usize);
+
+/// Tween playing on the instances of a target
+#[derive(Debug, Clone)]
+struct Track {
+    id: TweenId,
+    target: PersistentId,
+    tween: Tween,
+    /// Time of the animator when the tween was added
+    start: f32,
+}
+
+/// Timeline of the tweens of persistent instances, see [`Renderer::add_persistent`]
+///
+/// Every instance kept under a target gets the same value, so tweens of position and scale
+/// suit targets of a single shape or sprite. Call [`Animator::advance`] once per frame
+#[derive(Debug, Clone, Default)]
+pub struct Animator {
+    tracks: Vec<Track>,
+    /// Seconds advanced since the animator was created
+    time: f32,
+    next_id: usize,
+}
+
+impl Animator {
+    /// Creates a new empty [`Animator`]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Plays `tween` on the instances kept under `target`, starting now
+    ///
+    /// Tweens added later change the same property after the earlier ones
+    pub fn add(&mut self, target: PersistentId, tween: Tween) -> TweenId {
+        let id = TweenId(self.next_id);
+        self.next_id += 1;
+
+        self.tracks.push(Track {
+            id,
+            target,
+            tween,
+            start: self.time,
+        });
+
+        id
+    }
+
+    /// Plays the `tweens` on `target` one after another, the delay of each one is counted from
+    /// the end of the previous one
+    pub fn add_sequence(
+        &mut self,
+        target: PersistentId,
+        tweens: impl IntoIterator<Item = Tween>,
+    ) -> Vec<TweenId> {
+        let mut offset = 0.0;
+
+        tweens
+            .into_iter()
+            .map(|tween| {
+                let tween = tween.with_delay(offset + tween.delay);
+                offset = tween.delay + tween.duration;
+                self.add(target, tween)
+            })
+            .collect()
+    }
+
+    /// Stops the tween of `id`, the property keeps its current value
+    pub fn remove(&mut self, id: TweenId) {
+        self.tracks.retain(|track| track.id != id);
+    }
+
+    /// Stops every tween of `target`
+    pub fn remove_target(&mut self, target: PersistentId) {
+        self.tracks.retain(|track| track.target != target);
+    }
+
+    /// Checks whether the tween of `id` is still playing or waiting for its delay
+    pub fn is_playing(&self, id: TweenId) -> bool {
+        self.tracks.iter().any(|track| track.id == id)
+    }
+
+    /// Checks whether no tween is playing anymore
+    pub fn is_idle(&self) -> bool {
+        self.tracks.is_empty()
+    }
+
+    /// Moves the timeline forward by `dt` seconds and updates the instances of the targets
+    ///
+    /// Finished tweens are set to their end value and removed. A target removed from the
+    /// renderer stops its tweens and gives back the error of [`Renderer::update`]
+    pub fn advance(&mut self, renderer: &mut Renderer, dt: f32) -> RendererResult<()> {
+        self.time += dt.max(0.0);
+
+        let time = self.time;
+        let mut first_error = None;
+
+        self.tracks.retain(|track| {
+            let elapsed = time - track.start;
+
+            match renderer.update(track.target, |instance| {
+                track.tween.apply(elapsed, instance)
+            }) {
+                Ok(()) => !track.tween.is_finished(elapsed),
+                Err(error) => {
+                    first_error.get_or_insert(error);
+                    false
+                }
+            }
+        });
+
+        match first_error {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+}
+
+//==================================================
+//=== Unit Testing
+//==================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_easing() {
+        let easings = [
+            Easing::Linear,
+            Easing::QuadIn,
+            Easing::QuadOut,
+            Easing::QuadInOut,
+            Easing::CubicIn,
+            Easing::CubicOut,
+            Easing::CubicInOut,
+            Easing::SineInOut,
+            Easing::BackOut,
+            Easing::BounceOut,
+            Easing::ElasticOut,
+        ];
+
+        for easing in easings {
+            assert!(easing.apply(0.0).abs() < 1e-5, "{:?}", easing);
+            assert!((easing.apply(1.0) - 1.0).abs() < 1e-5, "{:?}", easing);
+        }
+
+        assert_eq!(Easing::QuadIn.apply(0.5), 0.25);
+        assert_eq!(Easing::QuadInOut.apply(0.5), 0.5);
+        assert!(Easing::BackOut.apply(0.7) > 1.0);
+    }
+
+    #[test]
+    fn test_tween() {
+        let tween = Tween::new(
+            TweenProperty::Position(glm::vec2(0.0, 0.0), glm::vec2(2.0, 4.0)),
+            2.0,
+        )
+        .with_delay(1.0);
+        let mut instance = ObjectInstance {
+            position: glm::vec3(5.0, 5.0, 0.5),
+            ..ObjectInstance::default()
+        };
+
+        // Delayed -> Unchanged
+        tween.apply(0.5, &mut instance);
+        assert_eq!(instance.position, glm::vec3(5.0, 5.0, 0.5));
+
+        tween.apply(2.0, &mut instance);
+        assert_eq!(instance.position, glm::vec3(1.0, 2.0, 0.5));
+        assert!(!tween.is_finished(2.0));
+
+        // Past the end -> End value
+        tween.apply(10.0, &mut instance);
+        assert_eq!(instance.position, glm::vec3(2.0, 4.0, 0.5));
+        assert!(tween.is_finished(3.0));
+
+        // Ping pong -> Back at the start after two runs
+        let tween =
+            Tween::new(TweenProperty::Rotation(0.0, 90.0), 1.0).with_repeat(Repeat::PingPong);
+        assert_eq!(tween.progress(0.25), Some(0.25));
+        assert_eq!(tween.progress(1.25), Some(0.75));
+        assert_eq!(tween.progress(2.25), Some(0.25));
+        assert!(!tween.is_finished(100.0));
+
+        let tween = tween.with_repeat(Repeat::Loop);
+        assert_eq!(tween.progress(1.25), Some(0.25));
+    }
+
+    #[test]
+    fn test_flipbook() {
+        let frames: Vec<AtlasRegionId> = (0..4)
+            .map(|region| AtlasRegionId { atlas: 0, region })
+            .collect();
+        let flipbook = Flipbook::new(frames, 10.0);
+
+        // Once -> Stays on the last frame
+        assert_eq!(flipbook.frame_index(0.0), 0);
+        assert_eq!(flipbook.frame_index(0.15), 1);
+        assert!(!flipbook.is_finished(0.25));
+        assert_eq!(flipbook.frame_index(5.0), 3);
+        assert!(flipbook.is_finished(0.35));
+        assert_eq!(
+            flipbook.frame(5.0),
+            Some(AtlasRegionId {
+                atlas: 0,
+                region: 3
+            })
+        );
+
+        let flipbook = flipbook.with_repeat(Repeat::Loop);
+        assert_eq!(flipbook.frame_index(0.45), 0);
+        assert_eq!(flipbook.frame_index(0.55), 1);
+        assert!(!flipbook.is_finished(5.0));
+
+        // Ping pong -> 0 1 2 3 2 1 0 1 ..
+        let flipbook = flipbook.with_repeat(Repeat::PingPong);
+        let indices: Vec<usize> = (0..8)
+            .map(|step| flipbook.frame_index(step as f32 * 0.1 + 0.05))
+            .collect();
+        assert_eq!(indices, [0, 1, 2, 3, 2, 1, 0, 1]);
+
+        // No frames -> Nothing to show
+        assert_eq!(Flipbook::new(Vec::new(), 10.0).frame(1.0), None);
+    }
+}
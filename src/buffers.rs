@@ -2,6 +2,9 @@
 use anyhow::{anyhow, Result};
 use ash::{util, vk};
 
+// intern
+use crate::allocator::{Allocation, MemoryAllocator};
+
 //==================================================
 //=== Commad Buffer
 //==================================================
@@ -43,10 +46,12 @@ impl CommandBuffer {
     /// Using:
     /// * Transient Command Pool (Buffers with short lifetime)
     /// * Onetime Submit Command Buffers
+    ///
+    /// On a dedicated transfer queue the ownership of the destination buffers is released to
+    /// the graphics queue after the copy
     pub fn buffer_copy(
         logical_device: &ash::Device,
-        queue: &vk::Queue,
-        queue_family_index: &u32,
+        queues: &UploadQueues,
         data_sizes: &[u64],
         src_buffers: &[&vk::Buffer],
         dst_buffers: &[&vk::Buffer],
@@ -55,59 +60,382 @@ impl CommandBuffer {
             return Err(anyhow!("Length of input vectors must match!"));
         }
 
+        let ownership_barriers =
+            |transfer_index: u32,
+             src_access_mask: vk::AccessFlags,
+             dst_access_mask: vk::AccessFlags| {
+                dst_buffers
+                    .iter()
+                    .zip(data_sizes)
+                    .map(|(buffer, size)| {
+                        vk::BufferMemoryBarrier::builder()
+                            .src_access_mask(src_access_mask)
+                            .dst_access_mask(dst_access_mask)
+                            .src_queue_family_index(transfer_index)
+                            .dst_queue_family_index(queues.graphics_index)
+                            .buffer(**buffer)
+                            .size(*size)
+                            .build()
+                    })
+                    .collect::<Vec<_>>()
+            };
+
+        queues.submit(
+            logical_device,
+            vk::PipelineStageFlags::VERTEX_INPUT,
+            |command_buffer| unsafe {
+                for i in 0..data_sizes.len() {
+                    logical_device.cmd_copy_buffer(
+                        command_buffer,
+                        *src_buffers[i],
+                        *dst_buffers[i],
+                        &[vk::BufferCopy::builder().size(data_sizes[i]).build()],
+                    );
+                }
+            },
+            |command_buffer, transfer_index| unsafe {
+                logical_device.cmd_pipeline_barrier(
+                    command_buffer,
+                    vk::PipelineStageFlags::TRANSFER,
+                    vk::PipelineStageFlags::BOTTOM_OF_PIPE,
+                    vk::DependencyFlags::empty(),
+                    &[],
+                    &ownership_barriers(
+                        transfer_index,
+                        vk::AccessFlags::TRANSFER_WRITE,
+                        vk::AccessFlags::empty(),
+                    ),
+                    &[],
+                );
+            },
+            |command_buffer, transfer_index| unsafe {
+                logical_device.cmd_pipeline_barrier(
+                    command_buffer,
+                    vk::PipelineStageFlags::TOP_OF_PIPE,
+                    vk::PipelineStageFlags::VERTEX_INPUT,
+                    vk::DependencyFlags::empty(),
+                    &[],
+                    &ownership_barriers(
+                        transfer_index,
+                        vk::AccessFlags::empty(),
+                        vk::AccessFlags::VERTEX_ATTRIBUTE_READ | vk::AccessFlags::INDEX_READ,
+                    ),
+                    &[],
+                );
+            },
+        )
+    }
+
+    /// Copy the data of a buffer into a 2D color image
+    ///
+    /// The image is transitioned from `UNDEFINED` to `TRANSFER_DST_OPTIMAL` before the copy
+    /// and to `SHADER_READ_ONLY_OPTIMAL` after the copy, on a dedicated transfer queue the
+    /// second transition is also the ownership transfer to the graphics queue
+    pub fn image_copy(
+        logical_device: &ash::Device,
+        queues: &UploadQueues,
+        src_buffer: &vk::Buffer,
+        dst_image: &vk::Image,
+        extent: vk::Extent3D,
+    ) -> Result<()> {
+        let subresource_range = vk::ImageSubresourceRange::builder()
+            .aspect_mask(vk::ImageAspectFlags::COLOR)
+            .level_count(1)
+            .layer_count(1)
+            .build();
+
+        let barrier_shader = |src_access_mask: vk::AccessFlags,
+                              dst_access_mask: vk::AccessFlags,
+                              src_queue_family_index: u32,
+                              dst_queue_family_index: u32| {
+            vk::ImageMemoryBarrier::builder()
+                .old_layout(vk::ImageLayout::TRANSFER_DST_OPTIMAL)
+                .new_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
+                .src_queue_family_index(src_queue_family_index)
+                .dst_queue_family_index(dst_queue_family_index)
+                .image(*dst_image)
+                .subresource_range(subresource_range)
+                .src_access_mask(src_access_mask)
+                .dst_access_mask(dst_access_mask)
+                .build()
+        };
+
+        queues.submit(
+            logical_device,
+            vk::PipelineStageFlags::FRAGMENT_SHADER,
+            |command_buffer| unsafe {
+                let barrier_transfer = vk::ImageMemoryBarrier::builder()
+                    .old_layout(vk::ImageLayout::UNDEFINED)
+                    .new_layout(vk::ImageLayout::TRANSFER_DST_OPTIMAL)
+                    .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                    .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                    .image(*dst_image)
+                    .subresource_range(subresource_range)
+                    .src_access_mask(vk::AccessFlags::empty())
+                    .dst_access_mask(vk::AccessFlags::TRANSFER_WRITE);
+
+                logical_device.cmd_pipeline_barrier(
+                    command_buffer,
+                    vk::PipelineStageFlags::TOP_OF_PIPE,
+                    vk::PipelineStageFlags::TRANSFER,
+                    vk::DependencyFlags::empty(),
+                    &[],
+                    &[],
+                    std::slice::from_ref(&barrier_transfer),
+                );
+
+                let region = vk::BufferImageCopy::builder()
+                    .image_subresource(
+                        vk::ImageSubresourceLayers::builder()
+                            .aspect_mask(vk::ImageAspectFlags::COLOR)
+                            .layer_count(1)
+                            .build(),
+                    )
+                    .image_extent(extent);
+
+                logical_device.cmd_copy_buffer_to_image(
+                    command_buffer,
+                    *src_buffer,
+                    *dst_image,
+                    vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                    std::slice::from_ref(&region),
+                );
+
+                if queues.transfer.is_none() {
+                    logical_device.cmd_pipeline_barrier(
+                        command_buffer,
+                        vk::PipelineStageFlags::TRANSFER,
+                        vk::PipelineStageFlags::FRAGMENT_SHADER,
+                        vk::DependencyFlags::empty(),
+                        &[],
+                        &[],
+                        &[barrier_shader(
+                            vk::AccessFlags::TRANSFER_WRITE,
+                            vk::AccessFlags::SHADER_READ,
+                            vk::QUEUE_FAMILY_IGNORED,
+                            vk::QUEUE_FAMILY_IGNORED,
+                        )],
+                    );
+                }
+            },
+            |command_buffer, transfer_index| unsafe {
+                logical_device.cmd_pipeline_barrier(
+                    command_buffer,
+                    vk::PipelineStageFlags::TRANSFER,
+                    vk::PipelineStageFlags::BOTTOM_OF_PIPE,
+                    vk::DependencyFlags::empty(),
+                    &[],
+                    &[],
+                    &[barrier_shader(
+                        vk::AccessFlags::TRANSFER_WRITE,
+                        vk::AccessFlags::empty(),
+                        transfer_index,
+                        queues.graphics_index,
+                    )],
+                );
+            },
+            |command_buffer, transfer_index| unsafe {
+                logical_device.cmd_pipeline_barrier(
+                    command_buffer,
+                    vk::PipelineStageFlags::TOP_OF_PIPE,
+                    vk::PipelineStageFlags::FRAGMENT_SHADER,
+                    vk::DependencyFlags::empty(),
+                    &[],
+                    &[],
+                    &[barrier_shader(
+                        vk::AccessFlags::empty(),
+                        vk::AccessFlags::SHADER_READ,
+                        transfer_index,
+                        queues.graphics_index,
+                    )],
+                );
+            },
+        )
+    }
+}
+
+//==================================================
+//=== Upload Queues
+//==================================================
+
+/// Queues running the staging copies of the uploads
+///
+/// With a dedicated `transfer` queue the copies don't occupy the graphics queue, which only
+/// acquires the ownership of the written resources after waiting on a semaphore
+#[derive(Debug, Clone, Copy)]
+pub struct UploadQueues {
+    pub graphics: vk::Queue,
+    pub graphics_index: u32,
+    /// Dedicated transfer queue with its family index
+    pub transfer: Option<(vk::Queue, u32)>,
+}
+
+impl UploadQueues {
+    /// Same queues without the transfer queue, for resources whose content must be kept
+    pub fn graphics_only(&self) -> Self {
+        Self {
+            transfer: None,
+            ..*self
+        }
+    }
+
+    /// Records and submits the copy commands of `record_copy`, waits until they are done
+    ///
+    /// On a dedicated transfer queue `record_release` is recorded after the copy and
+    /// `record_acquire` is submitted to the graphics queue, waiting at `acquire_stage`, both
+    /// getting the transfer family index
+    fn submit(
+        &self,
+        logical_device: &ash::Device,
+        acquire_stage: vk::PipelineStageFlags,
+        record_copy: impl FnOnce(vk::CommandBuffer),
+        record_release: impl FnOnce(vk::CommandBuffer, u32),
+        record_acquire: impl FnOnce(vk::CommandBuffer, u32),
+    ) -> Result<()> {
+        let Some((transfer, transfer_index)) = self.transfer else {
+            let copy = OneTimeSubmit::new(logical_device, self.graphics_index, record_copy)?;
+            let result = copy.submit_and_wait(logical_device, &self.graphics, None);
+            copy.destroy(logical_device);
+
+            return result;
+        };
+
+        let copy = OneTimeSubmit::new(logical_device, transfer_index, |command_buffer| {
+            record_copy(command_buffer);
+            record_release(command_buffer, transfer_index);
+        })?;
+        let acquire = OneTimeSubmit::new(logical_device, self.graphics_index, |command_buffer| {
+            record_acquire(command_buffer, transfer_index)
+        });
+        let acquire = match acquire {
+            Ok(acquire) => acquire,
+            Err(error) => {
+                copy.destroy(logical_device);
+                return Err(error);
+            }
+        };
+
+        let semaphore =
+            unsafe { logical_device.create_semaphore(&vk::SemaphoreCreateInfo::default(), None) };
+
+        let result = semaphore
+            .map_err(anyhow::Error::from)
+            .and_then(|semaphore| {
+                let copy_info = vk::SubmitInfo::builder()
+                    .command_buffers(std::slice::from_ref(&copy.buffer))
+                    .signal_semaphores(std::slice::from_ref(&semaphore));
+
+                unsafe {
+                    logical_device.queue_submit(
+                        transfer,
+                        std::slice::from_ref(&copy_info),
+                        vk::Fence::null(),
+                    )
+                }?;
+
+                // The graphics queue only waits for the copy with the acquire barriers
+                let result = acquire.submit_and_wait(
+                    logical_device,
+                    &self.graphics,
+                    Some((semaphore, acquire_stage)),
+                );
+                if result.is_err() {
+                    let _ = unsafe { logical_device.queue_wait_idle(transfer) };
+                }
+
+                unsafe { logical_device.destroy_semaphore(semaphore, None) };
+
+                result
+            });
+
+        copy.destroy(logical_device);
+        acquire.destroy(logical_device);
+
+        result
+    }
+}
+
+/// Transient command buffer recorded once
+struct OneTimeSubmit {
+    pool: vk::CommandPool,
+    buffer: vk::CommandBuffer,
+}
+
+impl OneTimeSubmit {
+    /// Records the commands of `record` into a new command buffer of the queue family
+    fn new(
+        logical_device: &ash::Device,
+        queue_family_index: u32,
+        record: impl FnOnce(vk::CommandBuffer),
+    ) -> Result<Self> {
         let pool = {
             let create_info = vk::CommandPoolCreateInfo::builder()
                 .flags(vk::CommandPoolCreateFlags::TRANSIENT)
-                .queue_family_index(*queue_family_index);
+                .queue_family_index(queue_family_index);
 
             unsafe { logical_device.create_command_pool(&create_info, None) }?
         };
 
-        let buffers = {
+        let record = || -> Result<vk::CommandBuffer> {
             let allocate_info = vk::CommandBufferAllocateInfo::builder()
                 .command_pool(pool)
                 .level(vk::CommandBufferLevel::PRIMARY)
                 .command_buffer_count(1);
 
-            unsafe { logical_device.allocate_command_buffers(&allocate_info) }?
-        };
+            let buffer = unsafe { logical_device.allocate_command_buffers(&allocate_info) }?[0];
 
-        unsafe {
-            /* Start Recording */
-            logical_device.begin_command_buffer(
-                buffers[0],
-                &vk::CommandBufferBeginInfo::builder()
-                    .flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT),
-            )?;
+            unsafe {
+                logical_device.begin_command_buffer(
+                    buffer,
+                    &vk::CommandBufferBeginInfo::builder()
+                        .flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT),
+                )?;
+                record(buffer);
+                logical_device.end_command_buffer(buffer)?;
+            }
 
-            /* Commands */
-            for i in 0..data_sizes.len() {
-                logical_device.cmd_copy_buffer(
-                    buffers[0],
-                    *src_buffers[i],
-                    *dst_buffers[i],
-                    &[vk::BufferCopy::builder().size(data_sizes[i]).build()],
-                );
+            Ok(buffer)
+        };
+
+        match record() {
+            Ok(buffer) => Ok(Self { pool, buffer }),
+            Err(error) => {
+                unsafe { logical_device.destroy_command_pool(pool, None) };
+                Err(error)
             }
+        }
+    }
 
-            /* End Recording */
-            logical_device.end_command_buffer(buffers[0])?;
+    /// Submits the command buffer to `queue` and waits for it with a fence, instead of
+    /// waiting for the whole queue to be idle
+    fn submit_and_wait(
+        &self,
+        logical_device: &ash::Device,
+        queue: &vk::Queue,
+        wait: Option<(vk::Semaphore, vk::PipelineStageFlags)>,
+    ) -> Result<()> {
+        let (wait_semaphores, wait_stages): (Vec<_>, Vec<_>) = wait.into_iter().unzip();
 
-            /* Submit To Queue */
-            let submit_info = vk::SubmitInfo::builder().command_buffers(&buffers);
+        let submit_info = vk::SubmitInfo::builder()
+            .wait_semaphores(&wait_semaphores)
+            .wait_dst_stage_mask(&wait_stages)
+            .command_buffers(std::slice::from_ref(&self.buffer));
 
-            logical_device.queue_submit(
-                *queue,
-                std::slice::from_ref(&submit_info),
-                vk::Fence::null(),
-            )?;
+        let fence = unsafe { logical_device.create_fence(&vk::FenceCreateInfo::default(), None) }?;
 
-            /* Cleanup*/
-            logical_device.queue_wait_idle(*queue);
-            logical_device.destroy_command_pool(pool, None);
-        }
+        let result = unsafe {
+            logical_device
+                .queue_submit(*queue, std::slice::from_ref(&submit_info), fence)
+                .and_then(|_| logical_device.wait_for_fences(&[fence], true, u64::MAX))
+        };
 
-        Ok(())
+        unsafe { logical_device.destroy_fence(fence, None) };
+
+        Ok(result?)
+    }
+
+    fn destroy(self, logical_device: &ash::Device) {
+        unsafe { logical_device.destroy_command_pool(self.pool, None) };
     }
 }
 
@@ -121,21 +449,30 @@ pub struct FrameBuffer {
 
 impl FrameBuffer {
     /// Creates a new [`FrameBuffer`]
+    ///
+    /// With a `color_target` the swapchain image views are used as resolve attachments, the
+    /// `stencil_target` is attached after them
     pub fn new(
         logical_device: &ash::Device,
         image_views: &Vec<vk::ImageView>,
         render_pass: &vk::RenderPass,
         width: u32,
         height: u32,
+        color_target: Option<&ColorTarget>,
+        stencil_target: Option<&StencilTarget>,
     ) -> Result<Self> {
         let mut buffers = Vec::new();
 
         for iv in image_views {
-            let iv = [*iv];
+            let mut attachments = match color_target {
+                Some(color_target) => vec![color_target.image_view, *iv],
+                None => vec![*iv],
+            };
+            attachments.extend(stencil_target.map(|stencil_target| stencil_target.image_view));
 
             let create_info = vk::FramebufferCreateInfo::builder()
                 .render_pass(*render_pass)
-                .attachments(&iv)
+                .attachments(&attachments)
                 .width(width)
                 .height(height)
                 .layers(1);
@@ -147,6 +484,204 @@ impl FrameBuffer {
     }
 }
 
+//==================================================
+//=== Color Target
+//==================================================
+
+/// Multisampled color attachment, resolved into the swapchain image
+pub struct ColorTarget {
+    pub image: vk::Image,
+    pub image_allocation: Allocation,
+    pub image_view: vk::ImageView,
+}
+
+impl ColorTarget {
+    /// Creates a new [`ColorTarget`] in the swapchain `format`
+    pub fn new(
+        logical_device: &ash::Device,
+        allocator: &mut MemoryAllocator,
+        width: u32,
+        height: u32,
+        format: vk::Format,
+        samples: vk::SampleCountFlags,
+    ) -> Result<Self> {
+        let image = {
+            let create_info = vk::ImageCreateInfo::builder()
+                .image_type(vk::ImageType::TYPE_2D)
+                .format(format)
+                .extent(vk::Extent3D {
+                    width,
+                    height,
+                    depth: 1,
+                })
+                .mip_levels(1)
+                .array_layers(1)
+                .samples(samples)
+                .tiling(vk::ImageTiling::OPTIMAL)
+                .usage(
+                    vk::ImageUsageFlags::COLOR_ATTACHMENT
+                        | vk::ImageUsageFlags::TRANSIENT_ATTACHMENT,
+                )
+                .sharing_mode(vk::SharingMode::EXCLUSIVE)
+                .initial_layout(vk::ImageLayout::UNDEFINED);
+
+            unsafe { logical_device.create_image(&create_info, None) }?
+        };
+
+        let image_mem_requirements = unsafe { logical_device.get_image_memory_requirements(image) };
+
+        let image_allocation = allocator.allocate(
+            logical_device,
+            image_mem_requirements,
+            vk::MemoryPropertyFlags::DEVICE_LOCAL,
+            false,
+        )?;
+
+        unsafe {
+            logical_device.bind_image_memory(
+                image,
+                image_allocation.memory,
+                image_allocation.offset,
+            )
+        }?;
+
+        let image_view = {
+            let subresource_range = vk::ImageSubresourceRange::builder()
+                .aspect_mask(vk::ImageAspectFlags::COLOR)
+                .level_count(1)
+                .layer_count(1)
+                .build();
+
+            let create_info = vk::ImageViewCreateInfo::builder()
+                .image(image)
+                .view_type(vk::ImageViewType::TYPE_2D)
+                .format(format)
+                .subresource_range(subresource_range);
+
+            unsafe { logical_device.create_image_view(&create_info, None) }?
+        };
+
+        Ok(Self {
+            image,
+            image_allocation,
+            image_view,
+        })
+    }
+
+    /// Destroys every Vulkan object owned by the [`ColorTarget`]
+    pub fn destroy(&self, logical_device: &ash::Device, allocator: &mut MemoryAllocator) {
+        unsafe {
+            logical_device.destroy_image_view(self.image_view, None);
+            logical_device.destroy_image(self.image, None);
+        }
+        allocator.free(&self.image_allocation);
+    }
+}
+
+//==================================================
+//=== Stencil Target
+//==================================================
+
+/// Stencil attachment of the masks, cleared at the start of every frame
+pub struct StencilTarget {
+    pub image: vk::Image,
+    pub image_allocation: Allocation,
+    pub image_view: vk::ImageView,
+    pub format: vk::Format,
+}
+
+impl StencilTarget {
+    /// Creates a new [`StencilTarget`] of `format` with the `samples` of the color attachment
+    pub fn new(
+        logical_device: &ash::Device,
+        allocator: &mut MemoryAllocator,
+        width: u32,
+        height: u32,
+        format: vk::Format,
+        samples: vk::SampleCountFlags,
+    ) -> Result<Self> {
+        let image = {
+            let create_info = vk::ImageCreateInfo::builder()
+                .image_type(vk::ImageType::TYPE_2D)
+                .format(format)
+                .extent(vk::Extent3D {
+                    width,
+                    height,
+                    depth: 1,
+                })
+                .mip_levels(1)
+                .array_layers(1)
+                .samples(samples)
+                .tiling(vk::ImageTiling::OPTIMAL)
+                .usage(
+                    vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT
+                        | vk::ImageUsageFlags::TRANSIENT_ATTACHMENT,
+                )
+                .sharing_mode(vk::SharingMode::EXCLUSIVE)
+                .initial_layout(vk::ImageLayout::UNDEFINED);
+
+            unsafe { logical_device.create_image(&create_info, None) }?
+        };
+
+        let image_mem_requirements = unsafe { logical_device.get_image_memory_requirements(image) };
+
+        let image_allocation = allocator.allocate(
+            logical_device,
+            image_mem_requirements,
+            vk::MemoryPropertyFlags::DEVICE_LOCAL,
+            false,
+        )?;
+
+        unsafe {
+            logical_device.bind_image_memory(
+                image,
+                image_allocation.memory,
+                image_allocation.offset,
+            )
+        }?;
+
+        let image_view = {
+            let subresource_range = vk::ImageSubresourceRange::builder()
+                .aspect_mask(Self::aspect_mask(format))
+                .level_count(1)
+                .layer_count(1)
+                .build();
+
+            let create_info = vk::ImageViewCreateInfo::builder()
+                .image(image)
+                .view_type(vk::ImageViewType::TYPE_2D)
+                .format(format)
+                .subresource_range(subresource_range);
+
+            unsafe { logical_device.create_image_view(&create_info, None) }?
+        };
+
+        Ok(Self {
+            image,
+            image_allocation,
+            image_view,
+            format,
+        })
+    }
+
+    /// Aspects of the attachment, combined formats have to be attached with their depth
+    pub fn aspect_mask(format: vk::Format) -> vk::ImageAspectFlags {
+        match format {
+            vk::Format::S8_UINT => vk::ImageAspectFlags::STENCIL,
+            _ => vk::ImageAspectFlags::DEPTH | vk::ImageAspectFlags::STENCIL,
+        }
+    }
+
+    /// Destroys every Vulkan object owned by the [`StencilTarget`]
+    pub fn destroy(&self, logical_device: &ash::Device, allocator: &mut MemoryAllocator) {
+        unsafe {
+            logical_device.destroy_image_view(self.image_view, None);
+            logical_device.destroy_image(self.image, None);
+        }
+        allocator.free(&self.image_allocation);
+    }
+}
+
 //==================================================
 //=== Storage Buffer
 //==================================================
@@ -158,7 +693,7 @@ pub enum DataUsage {
 
 pub struct StorageBuffer {
     pub buffer: vk::Buffer,
-    pub buffer_memory: vk::DeviceMemory,
+    pub buffer_allocation: Allocation,
 }
 
 impl StorageBuffer {
@@ -170,9 +705,8 @@ impl StorageBuffer {
     /// 3. Copy data from staging buffer to storage buffer
     pub fn new<T: Copy>(
         logical_device: &ash::Device,
-        device_mem_properties: &vk::PhysicalDeviceMemoryProperties,
-        queue: &vk::Queue,
-        queue_family_index: &u32,
+        allocator: &mut MemoryAllocator,
+        queues: &UploadQueues,
         data_size: u64,
         data_usage: DataUsage,
         data: &[T],
@@ -180,58 +714,8 @@ impl StorageBuffer {
     ) -> Result<Self> {
         /* Staging Buffer */
 
-        let staging_buffer = {
-            let create_info = vk::BufferCreateInfo::builder()
-                .size(data_size)
-                .usage(vk::BufferUsageFlags::TRANSFER_SRC)
-                .sharing_mode(vk::SharingMode::EXCLUSIVE);
-
-            unsafe { logical_device.create_buffer(&create_info, None) }?
-        };
-
-        let staging_buffer_mem_requirements =
-            unsafe { logical_device.get_buffer_memory_requirements(staging_buffer) };
-
-        let staging_buffer_memory = {
-            let mut memory_type_index: u32 = 0;
-            for mt in device_mem_properties.memory_types {
-                if (staging_buffer_mem_requirements.memory_type_bits & (1 << memory_type_index)
-                    != 0)
-                    && mt.property_flags.contains(
-                        vk::MemoryPropertyFlags::HOST_VISIBLE
-                            | vk::MemoryPropertyFlags::HOST_COHERENT,
-                    )
-                {
-                    break;
-                }
-
-                memory_type_index += 1;
-            }
-
-            let allocate_info = vk::MemoryAllocateInfo::builder()
-                .allocation_size(staging_buffer_mem_requirements.size)
-                .memory_type_index(memory_type_index);
-
-            unsafe { logical_device.allocate_memory(&allocate_info, None) }?
-        };
-
-        unsafe { logical_device.bind_buffer_memory(staging_buffer, staging_buffer_memory, 0) }?;
-
-        let data_ptr = unsafe {
-            logical_device.map_memory(
-                staging_buffer_memory,
-                0,
-                staging_buffer_mem_requirements.size,
-                vk::MemoryMapFlags::empty(),
-            )
-        }?;
-
-        let mut staging_align =
-            unsafe { util::Align::new(data_ptr, data_align, staging_buffer_mem_requirements.size) };
-
-        staging_align.copy_from_slice(&data);
-
-        unsafe { logical_device.unmap_memory(staging_buffer_memory) };
+        let staging_buffer =
+            StagingBuffer::new(logical_device, allocator, data_size, data, data_align)?;
 
         /* Storage Buffer */
 
@@ -252,136 +736,392 @@ impl StorageBuffer {
         let buffer_mem_requirements =
             unsafe { logical_device.get_buffer_memory_requirements(buffer) };
 
-        let buffer_memory = {
-            let mut memory_type_index: u32 = 0;
-            for mt in device_mem_properties.memory_types {
-                if (buffer_mem_requirements.memory_type_bits & (1 << memory_type_index) != 0)
-                    && mt
-                        .property_flags
-                        .contains(vk::MemoryPropertyFlags::DEVICE_LOCAL)
-                {
-                    break;
-                }
-
-                memory_type_index += 1;
-            }
-
-            let allocate_info = vk::MemoryAllocateInfo::builder()
-                .allocation_size(buffer_mem_requirements.size)
-                .memory_type_index(memory_type_index);
-
-            unsafe { logical_device.allocate_memory(&allocate_info, None) }?
-        };
+        let buffer_allocation = allocator.allocate(
+            logical_device,
+            buffer_mem_requirements,
+            vk::MemoryPropertyFlags::DEVICE_LOCAL,
+            true,
+        )?;
 
-        unsafe { logical_device.bind_buffer_memory(buffer, buffer_memory, 0) }?;
+        unsafe {
+            logical_device.bind_buffer_memory(
+                buffer,
+                buffer_allocation.memory,
+                buffer_allocation.offset,
+            )
+        }?;
 
         self::CommandBuffer::buffer_copy(
             logical_device,
-            queue,
-            queue_family_index,
+            queues,
             &[data_size],
-            &[&staging_buffer],
+            &[&staging_buffer.buffer],
             &[&buffer],
         )?;
 
         /* Cleanup */
-        unsafe {
-            logical_device.destroy_buffer(staging_buffer, None);
-            logical_device.free_memory(staging_buffer_memory, None);
-        }
+        staging_buffer.destroy(logical_device, allocator);
 
         Ok(Self {
             buffer,
-            buffer_memory,
+            buffer_allocation,
         })
     }
 
     /// Load new data into an existing [`StorageBuffer`]
     ///
-    /// Similar to creation, but without storage buffer creation
+    /// Similar to creation, but without storage buffer creation. The copy stays on the graphics
+    /// queue owning the buffer, so the content past `data_size` is kept
     #[allow(dead_code)]
     pub fn load<T: Copy>(
         &self,
         logical_device: &ash::Device,
-        device_mem_properties: &vk::PhysicalDeviceMemoryProperties,
-        queue: &vk::Queue,
-        queue_family_index: &u32,
+        allocator: &mut MemoryAllocator,
+        queues: &UploadQueues,
         data_size: u64,
         data: &[T],
         data_align: u64,
     ) -> Result<()> {
         /* Staging Buffer */
 
-        let staging_buffer = {
+        let staging_buffer =
+            StagingBuffer::new(logical_device, allocator, data_size, data, data_align)?;
+
+        self::CommandBuffer::buffer_copy(
+            logical_device,
+            &queues.graphics_only(),
+            &[data_size],
+            &[&staging_buffer.buffer],
+            &[&self.buffer],
+        )?;
+
+        /* Cleanup */
+        staging_buffer.destroy(logical_device, allocator);
+
+        Ok(())
+    }
+}
+
+//==================================================
+//=== Streaming Buffer
+//==================================================
+
+/// Host visible vertex and index buffer, rewritten every frame without staging copies
+pub struct StreamingBuffer {
+    pub buffer: vk::Buffer,
+    pub allocation: Allocation,
+    pub capacity: u64,
+}
+
+impl StreamingBuffer {
+    /// Creates a new [`StreamingBuffer`] holding up to `capacity` bytes of vertices and indices
+    pub fn new(
+        logical_device: &ash::Device,
+        allocator: &mut MemoryAllocator,
+        capacity: u64,
+    ) -> Result<Self> {
+        let buffer = {
             let create_info = vk::BufferCreateInfo::builder()
-                .size(data_size)
-                .usage(vk::BufferUsageFlags::TRANSFER_SRC)
+                .size(capacity)
+                .usage(vk::BufferUsageFlags::VERTEX_BUFFER | vk::BufferUsageFlags::INDEX_BUFFER)
                 .sharing_mode(vk::SharingMode::EXCLUSIVE);
 
             unsafe { logical_device.create_buffer(&create_info, None) }?
         };
 
-        let staging_buffer_mem_requirements =
-            unsafe { logical_device.get_buffer_memory_requirements(staging_buffer) };
-
-        let staging_buffer_memory = {
-            let mut memory_type_index: u32 = 0;
-            for mt in device_mem_properties.memory_types {
-                if (staging_buffer_mem_requirements.memory_type_bits & (1 << memory_type_index)
-                    != 0)
-                    && mt.property_flags.contains(
-                        vk::MemoryPropertyFlags::HOST_VISIBLE
-                            | vk::MemoryPropertyFlags::HOST_COHERENT,
-                    )
-                {
-                    break;
-                }
+        let buffer_mem_requirements =
+            unsafe { logical_device.get_buffer_memory_requirements(buffer) };
 
-                memory_type_index += 1;
-            }
+        let allocation = allocator.allocate(
+            logical_device,
+            buffer_mem_requirements,
+            vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT,
+            true,
+        )?;
 
-            let allocate_info = vk::MemoryAllocateInfo::builder()
-                .allocation_size(staging_buffer_mem_requirements.size)
-                .memory_type_index(memory_type_index);
+        unsafe { logical_device.bind_buffer_memory(buffer, allocation.memory, allocation.offset) }?;
 
-            unsafe { logical_device.allocate_memory(&allocate_info, None) }?
-        };
+        Ok(Self {
+            buffer,
+            allocation,
+            capacity,
+        })
+    }
 
-        unsafe { logical_device.bind_buffer_memory(staging_buffer, staging_buffer_memory, 0) }?;
+    /// Copies `vertices` and right after them `indices` to the start of the buffer,
+    /// gives back the offset of the indices
+    ///
+    /// The buffer must not be in use by the GPU while writing
+    pub fn write<V: Copy>(&self, vertices: &[V], indices: &[u32]) -> Result<u64> {
+        let vertices_size = std::mem::size_of_val(vertices);
+        let indices_size = std::mem::size_of_val(indices);
+
+        if (vertices_size + indices_size) as u64 > self.capacity {
+            return Err(anyhow!(
+                "Streaming Buffer: {} bytes do not fit into {} bytes",
+                vertices_size + indices_size,
+                self.capacity
+            ));
+        }
 
-        let data_ptr = unsafe {
-            logical_device.map_memory(
-                staging_buffer_memory,
-                0,
-                staging_buffer_mem_requirements.size,
-                vk::MemoryMapFlags::empty(),
-            )
-        }?;
+        // Vertices are made of 4 byte fields, so the indices stay 4 byte aligned
+        unsafe {
+            let mapped = self.allocation.mapped as *mut u8;
+
+            std::ptr::copy_nonoverlapping(vertices.as_ptr() as *const u8, mapped, vertices_size);
+            std::ptr::copy_nonoverlapping(
+                indices.as_ptr() as *const u8,
+                mapped.add(vertices_size),
+                indices_size,
+            );
+        }
 
-        let mut staging_align =
-            unsafe { util::Align::new(data_ptr, data_align, staging_buffer_mem_requirements.size) };
+        Ok(vertices_size as u64)
+    }
 
-        staging_align.copy_from_slice(&data);
+    /// Destroys the buffer and gives back its memory to the `allocator`
+    pub fn destroy(&self, logical_device: &ash::Device, allocator: &mut MemoryAllocator) {
+        unsafe { logical_device.destroy_buffer(self.buffer, None) };
+        allocator.free(&self.allocation);
+    }
+}
 
-        unsafe { logical_device.unmap_memory(staging_buffer_memory) };
+//==================================================
+//=== Instance Buffer
+//==================================================
 
-        self::CommandBuffer::buffer_copy(
+/// Host visible storage buffer of the per instance data rewritten every frame, also used
+/// by the compute dispatches
+pub struct InstanceBuffer {
+    pub buffer: vk::Buffer,
+    pub allocation: Allocation,
+    pub capacity: u64,
+}
+
+impl InstanceBuffer {
+    /// Creates a new [`InstanceBuffer`] holding up to `capacity` bytes
+    pub fn new(
+        logical_device: &ash::Device,
+        allocator: &mut MemoryAllocator,
+        capacity: u64,
+    ) -> Result<Self> {
+        let buffer = {
+            let create_info = vk::BufferCreateInfo::builder()
+                .size(capacity)
+                .usage(vk::BufferUsageFlags::STORAGE_BUFFER)
+                .sharing_mode(vk::SharingMode::EXCLUSIVE);
+
+            unsafe { logical_device.create_buffer(&create_info, None) }?
+        };
+
+        let buffer_mem_requirements =
+            unsafe { logical_device.get_buffer_memory_requirements(buffer) };
+
+        let allocation = allocator.allocate(
             logical_device,
-            queue,
-            queue_family_index,
-            &[data_size],
-            &[&staging_buffer],
-            &[&self.buffer],
+            buffer_mem_requirements,
+            vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT,
+            true,
         )?;
 
-        /* Cleanup */
+        unsafe { logical_device.bind_buffer_memory(buffer, allocation.memory, allocation.offset) }?;
+
+        Ok(Self {
+            buffer,
+            allocation,
+            capacity,
+        })
+    }
+
+    /// Copies `data` to the start of the buffer
+    ///
+    /// The buffer must not be in use by the GPU while writing
+    pub fn write<T: bytemuck::Pod>(&self, data: &[T]) -> Result<()> {
+        let bytes: &[u8] = bytemuck::cast_slice(data);
+        let data_size = bytes.len();
+
+        if data_size as u64 > self.capacity {
+            return Err(anyhow!(
+                "Instance Buffer: {} bytes do not fit into {} bytes",
+                data_size,
+                self.capacity
+            ));
+        }
+
         unsafe {
-            logical_device.destroy_buffer(staging_buffer, None);
-            logical_device.free_memory(staging_buffer_memory, None);
+            std::ptr::copy_nonoverlapping(
+                bytes.as_ptr(),
+                self.allocation.mapped as *mut u8,
+                data_size,
+            );
         }
 
         Ok(())
     }
+
+    /// Copies the first `count` elements out of the buffer
+    ///
+    /// The GPU must have finished writing the buffer
+    pub fn read<T: bytemuck::Pod>(&self, count: usize) -> Result<Vec<T>> {
+        let data_size = count * std::mem::size_of::<T>();
+
+        if data_size as u64 > self.capacity {
+            return Err(anyhow!(
+                "Instance Buffer: {} bytes can't be read from {} bytes",
+                data_size,
+                self.capacity
+            ));
+        }
+
+        let mut data = vec![T::zeroed(); count];
+        unsafe {
+            std::ptr::copy_nonoverlapping(
+                self.allocation.mapped as *const u8,
+                bytemuck::cast_slice_mut::<T, u8>(&mut data).as_mut_ptr(),
+                data_size,
+            );
+        }
+
+        Ok(data)
+    }
+
+    /// Destroys the buffer and gives back its memory to the `allocator`
+    pub fn destroy(&self, logical_device: &ash::Device, allocator: &mut MemoryAllocator) {
+        unsafe { logical_device.destroy_buffer(self.buffer, None) };
+        allocator.free(&self.allocation);
+    }
+}
+
+//==================================================
+//=== Readback Buffer
+//==================================================
+
+/// Host visible buffer the GPU copies images into, read back by the CPU
+pub struct ReadbackBuffer {
+    pub buffer: vk::Buffer,
+    pub allocation: Allocation,
+    pub capacity: u64,
+}
+
+impl ReadbackBuffer {
+    /// Creates a new [`ReadbackBuffer`] holding up to `capacity` bytes
+    pub fn new(
+        logical_device: &ash::Device,
+        allocator: &mut MemoryAllocator,
+        capacity: u64,
+    ) -> Result<Self> {
+        let buffer = {
+            let create_info = vk::BufferCreateInfo::builder()
+                .size(capacity)
+                .usage(vk::BufferUsageFlags::TRANSFER_DST)
+                .sharing_mode(vk::SharingMode::EXCLUSIVE);
+
+            unsafe { logical_device.create_buffer(&create_info, None) }?
+        };
+
+        let buffer_mem_requirements =
+            unsafe { logical_device.get_buffer_memory_requirements(buffer) };
+
+        let allocation = allocator.allocate(
+            logical_device,
+            buffer_mem_requirements,
+            vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT,
+            true,
+        )?;
+
+        unsafe { logical_device.bind_buffer_memory(buffer, allocation.memory, allocation.offset) }?;
+
+        Ok(Self {
+            buffer,
+            allocation,
+            capacity,
+        })
+    }
+
+    /// Copies the first `size` bytes out of the buffer
+    ///
+    /// The GPU must have finished writing the buffer
+    pub fn read(&self, size: usize) -> Result<Vec<u8>> {
+        if size as u64 > self.capacity {
+            return Err(anyhow!(
+                "Readback Buffer: {} bytes can't be read from {} bytes",
+                size,
+                self.capacity
+            ));
+        }
+
+        let mut data = vec![0; size];
+        unsafe {
+            std::ptr::copy_nonoverlapping(
+                self.allocation.mapped as *const u8,
+                data.as_mut_ptr(),
+                size,
+            );
+        }
+
+        Ok(data)
+    }
+
+    /// Destroys the buffer and gives back its memory to the `allocator`
+    pub fn destroy(&self, logical_device: &ash::Device, allocator: &mut MemoryAllocator) {
+        unsafe { logical_device.destroy_buffer(self.buffer, None) };
+        allocator.free(&self.allocation);
+    }
+}
+
+//==================================================
+//=== Staging Buffer
+//==================================================
+
+/// Host visible buffer, source of the copies into device local buffers and images
+struct StagingBuffer {
+    buffer: vk::Buffer,
+    allocation: Allocation,
+}
+
+impl StagingBuffer {
+    /// Creates a new [`StagingBuffer`] holding `data`
+    fn new<T: Copy>(
+        logical_device: &ash::Device,
+        allocator: &mut MemoryAllocator,
+        data_size: u64,
+        data: &[T],
+        data_align: u64,
+    ) -> Result<Self> {
+        let buffer = {
+            let create_info = vk::BufferCreateInfo::builder()
+                .size(data_size)
+                .usage(vk::BufferUsageFlags::TRANSFER_SRC)
+                .sharing_mode(vk::SharingMode::EXCLUSIVE);
+
+            unsafe { logical_device.create_buffer(&create_info, None) }?
+        };
+
+        let buffer_mem_requirements =
+            unsafe { logical_device.get_buffer_memory_requirements(buffer) };
+
+        let allocation = allocator.allocate(
+            logical_device,
+            buffer_mem_requirements,
+            vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT,
+            true,
+        )?;
+
+        unsafe { logical_device.bind_buffer_memory(buffer, allocation.memory, allocation.offset) }?;
+
+        let mut staging_align =
+            unsafe { util::Align::new(allocation.mapped, data_align, allocation.size) };
+
+        staging_align.copy_from_slice(data);
+
+        Ok(Self { buffer, allocation })
+    }
+
+    /// Destroys the buffer and gives back its memory to the `allocator`
+    fn destroy(&self, logical_device: &ash::Device, allocator: &mut MemoryAllocator) {
+        unsafe { logical_device.destroy_buffer(self.buffer, None) };
+        allocator.free(&self.allocation);
+    }
 }
 
 //==================================================
@@ -390,7 +1130,7 @@ impl StorageBuffer {
 
 pub struct UniformBuffer {
     pub buffers: Vec<vk::Buffer>,
-    pub buffers_memory: Vec<vk::DeviceMemory>,
+    pub buffers_allocation: Vec<Allocation>,
     pub buffers_mem_req: Vec<vk::MemoryRequirements>,
     pub buffers_mapped: Vec<*mut std::ffi::c_void>,
 }
@@ -398,12 +1138,12 @@ pub struct UniformBuffer {
 impl UniformBuffer {
     pub fn new(
         logical_device: &ash::Device,
-        device_mem_properties: &vk::PhysicalDeviceMemoryProperties,
+        allocator: &mut MemoryAllocator,
         buffer_count: usize,
         buffer_size: u64,
     ) -> Result<Self> {
         let mut buffers: Vec<vk::Buffer> = Vec::with_capacity(buffer_count);
-        let mut buffers_memory: Vec<vk::DeviceMemory> = Vec::with_capacity(buffer_count);
+        let mut buffers_allocation: Vec<Allocation> = Vec::with_capacity(buffer_count);
         let mut buffers_mem_req: Vec<vk::MemoryRequirements> = Vec::with_capacity(buffer_count);
         let mut buffers_mapped: Vec<*mut std::ffi::c_void> = Vec::with_capacity(buffer_count);
 
@@ -420,50 +1160,228 @@ impl UniformBuffer {
             let uniform_mem_requirements =
                 unsafe { logical_device.get_buffer_memory_requirements(uniform_buffer) };
 
-            let uniform_buffer_memory = {
-                let mut memory_type_index: u32 = 0;
-                for mt in device_mem_properties.memory_types {
-                    if (uniform_mem_requirements.memory_type_bits & (1 << memory_type_index) != 0)
-                        && mt.property_flags.contains(
-                            vk::MemoryPropertyFlags::HOST_VISIBLE
-                                | vk::MemoryPropertyFlags::HOST_COHERENT,
-                        )
-                    {
-                        break;
-                    }
-
-                    memory_type_index += 1;
-                }
-
-                let allocate_info = vk::MemoryAllocateInfo::builder()
-                    .allocation_size(uniform_mem_requirements.size)
-                    .memory_type_index(memory_type_index);
-
-                unsafe { logical_device.allocate_memory(&allocate_info, None) }?
-            };
-
-            unsafe { logical_device.bind_buffer_memory(uniform_buffer, uniform_buffer_memory, 0) }?;
+            // Host visible memory is mapped by the allocator
+            let uniform_allocation = allocator.allocate(
+                logical_device,
+                uniform_mem_requirements,
+                vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT,
+                true,
+            )?;
 
-            let uniform_mapped = unsafe {
-                logical_device.map_memory(
-                    uniform_buffer_memory,
-                    0,
-                    uniform_mem_requirements.size,
-                    vk::MemoryMapFlags::empty(),
+            unsafe {
+                logical_device.bind_buffer_memory(
+                    uniform_buffer,
+                    uniform_allocation.memory,
+                    uniform_allocation.offset,
                 )
             }?;
 
             buffers.push(uniform_buffer);
-            buffers_memory.push(uniform_buffer_memory);
+            buffers_allocation.push(uniform_allocation);
             buffers_mem_req.push(uniform_mem_requirements);
-            buffers_mapped.push(uniform_mapped);
+            buffers_mapped.push(uniform_allocation.mapped);
         }
 
         Ok(Self {
             buffers,
-            buffers_memory,
+            buffers_allocation,
             buffers_mem_req,
             buffers_mapped,
         })
     }
 }
+
+//==================================================
+//=== Texture
+//==================================================
+
+pub struct Texture {
+    pub image: vk::Image,
+    pub image_allocation: Allocation,
+    pub image_view: vk::ImageView,
+    pub sampler: vk::Sampler,
+}
+
+impl Texture {
+    /// Creates a new [`Texture`] from RGBA8 `pixels`
+    ///
+    /// Texture Creation Steps:
+    /// 1. Stage pixels using staging buffer
+    /// 2. Create device local image
+    /// 3. Copy pixels from staging buffer to image with layout transitions
+    /// 4. Create image view and sampler
+    pub fn new(
+        logical_device: &ash::Device,
+        allocator: &mut MemoryAllocator,
+        queues: &UploadQueues,
+        width: u32,
+        height: u32,
+        pixels: &[u8],
+    ) -> Result<Self> {
+        let data_size = (width * height * 4) as u64;
+
+        if pixels.len() as u64 != data_size {
+            return Err(anyhow!(
+                "Texture data must be {}x{} RGBA8 pixels!",
+                width,
+                height
+            ));
+        }
+
+        /* Staging Buffer */
+
+        let staging_buffer = StagingBuffer::new(
+            logical_device,
+            allocator,
+            pixels.len() as u64,
+            pixels,
+            std::mem::align_of::<u8>() as u64,
+        )?;
+
+        /* Image */
+
+        let extent = vk::Extent3D {
+            width,
+            height,
+            depth: 1,
+        };
+
+        let image = {
+            let create_info = vk::ImageCreateInfo::builder()
+                .image_type(vk::ImageType::TYPE_2D)
+                .format(vk::Format::R8G8B8A8_SRGB)
+                .extent(extent)
+                .mip_levels(1)
+                .array_layers(1)
+                .samples(vk::SampleCountFlags::TYPE_1)
+                .tiling(vk::ImageTiling::OPTIMAL)
+                .usage(vk::ImageUsageFlags::TRANSFER_DST | vk::ImageUsageFlags::SAMPLED)
+                .sharing_mode(vk::SharingMode::EXCLUSIVE)
+                .initial_layout(vk::ImageLayout::UNDEFINED);
+
+            unsafe { logical_device.create_image(&create_info, None) }?
+        };
+
+        let image_mem_requirements = unsafe { logical_device.get_image_memory_requirements(image) };
+
+        let image_allocation = allocator.allocate(
+            logical_device,
+            image_mem_requirements,
+            vk::MemoryPropertyFlags::DEVICE_LOCAL,
+            false,
+        )?;
+
+        unsafe {
+            logical_device.bind_image_memory(
+                image,
+                image_allocation.memory,
+                image_allocation.offset,
+            )
+        }?;
+
+        self::CommandBuffer::image_copy(
+            logical_device,
+            queues,
+            &staging_buffer.buffer,
+            &image,
+            extent,
+        )?;
+
+        /* Cleanup */
+        staging_buffer.destroy(logical_device, allocator);
+
+        /* Image View & Sampler */
+
+        let image_view = {
+            let subresource_range = vk::ImageSubresourceRange::builder()
+                .aspect_mask(vk::ImageAspectFlags::COLOR)
+                .level_count(1)
+                .layer_count(1)
+                .build();
+
+            let create_info = vk::ImageViewCreateInfo::builder()
+                .image(image)
+                .view_type(vk::ImageViewType::TYPE_2D)
+                .format(vk::Format::R8G8B8A8_SRGB)
+                .subresource_range(subresource_range);
+
+            unsafe { logical_device.create_image_view(&create_info, None) }?
+        };
+
+        let sampler = {
+            let create_info = vk::SamplerCreateInfo::builder()
+                .mag_filter(vk::Filter::LINEAR)
+                .min_filter(vk::Filter::LINEAR)
+                .mipmap_mode(vk::SamplerMipmapMode::NEAREST)
+                .address_mode_u(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+                .address_mode_v(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+                .address_mode_w(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+                .max_lod(0.0)
+                .border_color(vk::BorderColor::INT_OPAQUE_BLACK);
+
+            unsafe { logical_device.create_sampler(&create_info, None) }?
+        };
+
+        Ok(Self {
+            image,
+            image_allocation,
+            image_view,
+            sampler,
+        })
+    }
+
+    /// Overwrites the whole content of the [`Texture`] with RGBA8 `pixels`
+    ///
+    /// The texture must not be in use by the GPU while updating
+    pub fn update(
+        &self,
+        logical_device: &ash::Device,
+        allocator: &mut MemoryAllocator,
+        queues: &UploadQueues,
+        width: u32,
+        height: u32,
+        pixels: &[u8],
+    ) -> Result<()> {
+        if pixels.len() as u64 != (width * height * 4) as u64 {
+            return Err(anyhow!(
+                "Texture data must be {}x{} RGBA8 pixels!",
+                width,
+                height
+            ));
+        }
+
+        let staging_buffer = StagingBuffer::new(
+            logical_device,
+            allocator,
+            pixels.len() as u64,
+            pixels,
+            std::mem::align_of::<u8>() as u64,
+        )?;
+
+        self::CommandBuffer::image_copy(
+            logical_device,
+            queues,
+            &staging_buffer.buffer,
+            &self.image,
+            vk::Extent3D {
+                width,
+                height,
+                depth: 1,
+            },
+        )?;
+
+        staging_buffer.destroy(logical_device, allocator);
+
+        Ok(())
+    }
+
+    /// Destroys every Vulkan object owned by the [`Texture`]
+    pub fn destroy(&self, logical_device: &ash::Device, allocator: &mut MemoryAllocator) {
+        unsafe {
+            logical_device.destroy_sampler(self.sampler, None);
+            logical_device.destroy_image_view(self.image_view, None);
+            logical_device.destroy_image(self.image, None);
+        }
+        allocator.free(&self.image_allocation);
+    }
+}
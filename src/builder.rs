@@ -0,0 +1,489 @@
+// std
+use std::path::PathBuf;
+
+// extern
+use ash::vk;
+use raw_window_handle::{HasRawDisplayHandle, HasRawWindowHandle};
+
+// intern
+use crate::resources::ResourceConfig;
+use crate::{FrameLimit, Renderer, RendererResult};
+
+//==================================================
+//=== Renderer Builder
+//==================================================
+
+/// Decides which physical device is picked when there are multiple suitable ones
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GpuPreference {
+    /// Prefer discrete GPUs, fall back to integrated or any other
+    Discrete,
+    /// Prefer integrated GPUs, fall back to discrete or any other
+    Integrated,
+    /// Pick the first GPU whose name contains the given text (case insensitive)
+    Name(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PresentMode {
+    /// VSync, always supported
+    Fifo,
+    /// VSync without blocking
+    Mailbox,
+    /// No VSync, may tear
+    Immediate,
+}
+
+impl PresentMode {
+    pub(crate) fn as_vk(&self) -> vk::PresentModeKHR {
+        match self {
+            PresentMode::Fifo => vk::PresentModeKHR::FIFO,
+            PresentMode::Mailbox => vk::PresentModeKHR::MAILBOX,
+            PresentMode::Immediate => vk::PresentModeKHR::IMMEDIATE,
+        }
+    }
+}
+
+/// Format and color space of the swapchain images
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorMode {
+    /// 8 bit sRGB, always supported
+    #[default]
+    Srgb,
+    /// 10 bit HDR10 with the ST 2084 (PQ) transfer function, colors are written as they are
+    Hdr10,
+    /// 16 bit float extended sRGB with linear colors, values above 1.0 are brighter than white
+    ScRgb,
+}
+
+impl ColorMode {
+    /// Formats of the mode, preferred first
+    fn formats(&self) -> &'static [(vk::Format, vk::ColorSpaceKHR)] {
+        match self {
+            ColorMode::Srgb => &[
+                (vk::Format::B8G8R8A8_SRGB, vk::ColorSpaceKHR::SRGB_NONLINEAR),
+                (vk::Format::R8G8B8A8_SRGB, vk::ColorSpaceKHR::SRGB_NONLINEAR),
+            ],
+            ColorMode::Hdr10 => &[
+                (
+                    vk::Format::A2B10G10R10_UNORM_PACK32,
+                    vk::ColorSpaceKHR::HDR10_ST2084_EXT,
+                ),
+                (
+                    vk::Format::A2R10G10B10_UNORM_PACK32,
+                    vk::ColorSpaceKHR::HDR10_ST2084_EXT,
+                ),
+            ],
+            ColorMode::ScRgb => &[(
+                vk::Format::R16G16B16A16_SFLOAT,
+                vk::ColorSpaceKHR::EXTENDED_SRGB_LINEAR_EXT,
+            )],
+        }
+    }
+}
+
+/// Gives back the surface format of `color_mode` if it is `supported`, otherwise an sRGB one
+pub(crate) fn surface_format(
+    color_mode: ColorMode,
+    supported: &[vk::SurfaceFormatKHR],
+) -> Option<vk::SurfaceFormatKHR> {
+    color_mode
+        .formats()
+        .iter()
+        .chain(ColorMode::Srgb.formats())
+        .find_map(|&(format, color_space)| {
+            supported
+                .iter()
+                .find(|f| f.format == format && f.color_space == color_space)
+                .copied()
+        })
+}
+
+/// Window mode set with `Renderer::set_fullscreen`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FullscreenMode {
+    #[default]
+    Windowed,
+    /// Borderless window covering the current monitor
+    Borderless,
+    /// Largest video mode of the current monitor, presented with exclusive access through
+    /// VK_EXT_full_screen_exclusive on Windows when it is available
+    Exclusive,
+}
+
+/// Options of a [`Renderer`], created with `Renderer::builder`
+///
+/// Unsupported options fall back to a supported value (eg. FIFO present mode, lower MSAA samples)
+#[derive(Debug, Clone)]
+pub struct RendererBuilder {
+    pub(crate) gpu_preference: GpuPreference,
+    pub(crate) portability_enumeration: bool,
+    pub(crate) present_mode: PresentMode,
+    pub(crate) msaa_samples: u32,
+    pub(crate) max_frames_inflight: usize,
+    pub(crate) color_mode: ColorMode,
+    pub(crate) dynamic_rendering: bool,
+    pub(crate) frame_limit: Option<FrameLimit>,
+    pub(crate) low_latency: bool,
+    pub(crate) clear_color: [f32; 4],
+    pub(crate) shader_dir: Option<PathBuf>,
+    pub(crate) hot_reload_dir: Option<PathBuf>,
+    pub(crate) resource_config: ResourceConfig,
+    pub(crate) recording_threads: usize,
+}
+
+impl Default for RendererBuilder {
+    fn default() -> Self {
+        Self {
+            gpu_preference: GpuPreference::Discrete,
+            portability_enumeration: false,
+            present_mode: PresentMode::Mailbox,
+            msaa_samples: 1,
+            max_frames_inflight: 2,
+            color_mode: ColorMode::Srgb,
+            dynamic_rendering: false,
+            frame_limit: None,
+            low_latency: false,
+            clear_color: [0.0, 0.0, 0.0, 1.0],
+            shader_dir: None,
+            hot_reload_dir: None,
+            resource_config: ResourceConfig::default(),
+            recording_threads: 1,
+        }
+    }
+}
+
+impl RendererBuilder {
+    /// Most frames recorded ahead of the GPU, eg. 3 for triple buffering
+    pub const MAX_FRAMES_INFLIGHT: usize = 3;
+
+    /// Most threads recording the draw commands of a frame
+    pub const MAX_RECORDING_THREADS: usize = 16;
+
+    /// Creates a new [`RendererBuilder`] with the default options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets which GPU is picked
+    pub fn gpu_preference(mut self, gpu_preference: GpuPreference) -> Self {
+        self.gpu_preference = gpu_preference;
+        self
+    }
+
+    /// Enables `VK_KHR_portability_enumeration`, needed to find devices of portability
+    /// implementations like MoltenVK
+    pub fn portability_enumeration(mut self, enabled: bool) -> Self {
+        self.portability_enumeration = enabled;
+        self
+    }
+
+    /// Sets the preferred present mode, FIFO is used when it is not supported
+    pub fn present_mode(mut self, present_mode: PresentMode) -> Self {
+        self.present_mode = present_mode;
+        self
+    }
+
+    /// Records the draw commands of large frames on `threads` threads into secondary command
+    /// buffers, clamped to 1 - [`RendererBuilder::MAX_RECORDING_THREADS`]
+    ///
+    /// Frames of a few hundred draw batches or less are still recorded on the calling thread,
+    /// 1 never starts any threads
+    pub fn recording_threads(mut self, threads: usize) -> Self {
+        self.recording_threads = threads.clamp(1, Self::MAX_RECORDING_THREADS);
+        self
+    }
+
+    /// Sets the number of MSAA samples, clamped to the highest count supported by the device
+    pub fn msaa_samples(mut self, samples: u32) -> Self {
+        self.msaa_samples = samples.max(1);
+        self
+    }
+
+    /// Sets the number of frames recorded while the GPU is still working on previous ones,
+    /// clamped to 1 - [`RendererBuilder::MAX_FRAMES_INFLIGHT`]
+    ///
+    /// The swapchain is created with at least one image for each frame in flight, if the surface
+    /// allows less images the frames in flight are lowered to the image count
+    pub fn max_frames_inflight(mut self, frames: usize) -> Self {
+        self.max_frames_inflight = frames.clamp(1, Self::MAX_FRAMES_INFLIGHT);
+        self
+    }
+
+    /// Sets the format and color space of the swapchain, sRGB is used when the surface does not
+    /// support `color_mode`
+    pub fn color_mode(mut self, color_mode: ColorMode) -> Self {
+        self.color_mode = color_mode;
+        self
+    }
+
+    /// Draws with Vulkan 1.3 dynamic rendering instead of render pass and framebuffer objects,
+    /// render passes are used when the device does not support it
+    ///
+    /// Post-processing effects need the render passes and can't be set with dynamic rendering
+    pub fn dynamic_rendering(mut self, enabled: bool) -> Self {
+        self.dynamic_rendering = enabled;
+        self
+    }
+
+    /// Limits the frame rate, eg. to keep an uncapped mailbox present from busy drawing frames
+    /// which are never shown, see `Renderer::set_frame_limit`
+    pub fn frame_limit(mut self, limit: FrameLimit) -> Self {
+        self.frame_limit = Some(limit);
+        self
+    }
+
+    /// Waits for the previous frame to be shown before drawing the next one, so input is at most
+    /// a frame old when it reaches the screen. Presentation times are reported in the render
+    /// statistics
+    ///
+    /// Needs VK_KHR_present_wait, ignored when the device does not support it
+    pub fn low_latency(mut self, enabled: bool) -> Self {
+        self.low_latency = enabled;
+        self
+    }
+
+    /// Sets the RGBA color the frames are cleared with
+    pub fn clear_color(mut self, clear_color: [f32; 4]) -> Self {
+        self.clear_color = clear_color;
+        self
+    }
+
+    /// Loads the built-in shaders as `{name}.vert.spv` and `{name}.frag.spv` from `shader_dir`
+    /// instead of the SPIR-V embedded into the binary
+    pub fn shader_dir(mut self, shader_dir: impl Into<PathBuf>) -> Self {
+        self.shader_dir = Some(shader_dir.into());
+        self
+    }
+
+    /// Development mode, the built-in shaders are loaded from `shader_dir` and their pipelines
+    /// are rebuilt whenever the files change
+    ///
+    /// Each stage is read from `{name}.{stage}.spv` or compiled from the GLSL source
+    /// `{name}.{stage}` with `glslc`, eg. `res/shaders/glsl`
+    pub fn hot_reload_shaders(mut self, shader_dir: impl Into<PathBuf>) -> Self {
+        self.hot_reload_dir = Some(shader_dir.into());
+        self
+    }
+
+    /// Sets where the default resources are loaded from, by default they are embedded into the binary
+    pub fn resource_config(mut self, resource_config: ResourceConfig) -> Self {
+        self.resource_config = resource_config;
+        self
+    }
+
+    /// Creates a new [`Renderer`] using `window`
+    #[cfg(feature = "winit")]
+    pub fn build(self, window: &winit::window::Window) -> RendererResult<Renderer> {
+        let size = window.inner_size();
+        self.build_with_handle(window, size.width, size.height)
+    }
+
+    /// Creates a new [`Renderer`] drawing into any native window, eg. one of sdl2 or tao
+    ///
+    /// The window is `width` x `height` pixels large, later sizes are set with
+    /// [`Renderer::set_surface_extent`]
+    pub fn build_with_handle(
+        self,
+        window: &(impl HasRawDisplayHandle + HasRawWindowHandle),
+        width: u32,
+        height: u32,
+    ) -> RendererResult<Renderer> {
+        Ok(Renderer::from_builder(
+            window,
+            vk::Extent2D { width, height },
+            &self,
+        )?)
+    }
+}
+
+/// Gives back the highest sample count in `supported` which is not above `requested`
+pub(crate) fn sample_count(
+    requested: u32,
+    supported: vk::SampleCountFlags,
+) -> vk::SampleCountFlags {
+    [
+        (64, vk::SampleCountFlags::TYPE_64),
+        (32, vk::SampleCountFlags::TYPE_32),
+        (16, vk::SampleCountFlags::TYPE_16),
+        (8, vk::SampleCountFlags::TYPE_8),
+        (4, vk::SampleCountFlags::TYPE_4),
+        (2, vk::SampleCountFlags::TYPE_2),
+    ]
+    .into_iter()
+    .find(|(count, flag)| *count <= requested && supported.contains(*flag))
+    .map_or(vk::SampleCountFlags::TYPE_1, |(_, flag)| flag)
+}
+
+/// Gives back the number of swapchain images to request from a surface with `caps`, one more
+/// than the minimum but at least one for each of the `frames_inflight`
+pub(crate) fn swapchain_image_count(
+    caps: &vk::SurfaceCapabilitiesKHR,
+    frames_inflight: usize,
+) -> u32 {
+    let count = (caps.min_image_count + 1).max(frames_inflight as u32);
+
+    if caps.max_image_count > 0 {
+        count.min(caps.max_image_count)
+    } else {
+        count
+    }
+}
+
+/// Gives back the swapchain extent for a window of `width` and `height`
+///
+/// The `current_extent` of the surface is used unless it is the special 0xFFFFFFFF value, in
+/// which case the size is determined by the swapchain and clamped to the supported extents
+pub(crate) fn swapchain_extent(
+    caps: &vk::SurfaceCapabilitiesKHR,
+    width: u32,
+    height: u32,
+) -> vk::Extent2D {
+    if caps.current_extent.width != u32::MAX && caps.current_extent.height != u32::MAX {
+        return caps.current_extent;
+    }
+
+    // Not `clamp`, some drivers report a smaller max than min extent while minimized
+    vk::Extent2D {
+        width: width
+            .max(caps.min_image_extent.width)
+            .min(caps.max_image_extent.width),
+        height: height
+            .max(caps.min_image_extent.height)
+            .min(caps.max_image_extent.height),
+    }
+}
+
+/// Gives back the usage of the swapchain images, they are also copied from for the frame
+/// capture when the surface supports it
+pub(crate) fn swapchain_usage(caps: &vk::SurfaceCapabilitiesKHR) -> vk::ImageUsageFlags {
+    let usage = vk::ImageUsageFlags::COLOR_ATTACHMENT;
+
+    if caps
+        .supported_usage_flags
+        .contains(vk::ImageUsageFlags::TRANSFER_SRC)
+    {
+        usage | vk::ImageUsageFlags::TRANSFER_SRC
+    } else {
+        usage
+    }
+}
+
+/// Gives back the format of the stencil attachment of the masks out of the `supported` ones,
+/// the smallest one with a stencil aspect
+pub(crate) fn stencil_format(supported: impl Fn(vk::Format) -> bool) -> Option<vk::Format> {
+    [
+        vk::Format::S8_UINT,
+        vk::Format::D16_UNORM_S8_UINT,
+        vk::Format::D24_UNORM_S8_UINT,
+        vk::Format::D32_SFLOAT_S8_UINT,
+    ]
+    .into_iter()
+    .find(|format| supported(*format))
+}
+
+//==================================================
+//=== Unit Testing
+//==================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_count() {
+        let supported = vk::SampleCountFlags::TYPE_1
+            | vk::SampleCountFlags::TYPE_2
+            | vk::SampleCountFlags::TYPE_4;
+
+        assert_eq!(sample_count(1, supported), vk::SampleCountFlags::TYPE_1);
+        assert_eq!(sample_count(4, supported), vk::SampleCountFlags::TYPE_4);
+        assert_eq!(sample_count(8, supported), vk::SampleCountFlags::TYPE_4);
+        assert_eq!(sample_count(3, supported), vk::SampleCountFlags::TYPE_2);
+    }
+
+    #[test]
+    fn test_swapchain_image_count() {
+        let caps = vk::SurfaceCapabilitiesKHR {
+            min_image_count: 2,
+            max_image_count: 3,
+            ..Default::default()
+        };
+
+        assert_eq!(swapchain_image_count(&caps, 1), 3);
+        assert_eq!(swapchain_image_count(&caps, 3), 3);
+
+        // No upper limit
+        let caps = vk::SurfaceCapabilitiesKHR {
+            min_image_count: 1,
+            max_image_count: 0,
+            ..Default::default()
+        };
+        assert_eq!(swapchain_image_count(&caps, 3), 3);
+        assert_eq!(swapchain_image_count(&caps, 1), 2);
+    }
+
+    #[test]
+    fn test_swapchain_extent() {
+        let extent = |width, height| vk::Extent2D { width, height };
+        let caps = vk::SurfaceCapabilitiesKHR {
+            current_extent: extent(u32::MAX, u32::MAX),
+            min_image_extent: extent(1, 1),
+            max_image_extent: extent(1920, 1080),
+            ..Default::default()
+        };
+
+        assert_eq!(swapchain_extent(&caps, 800, 600), extent(800, 600));
+        assert_eq!(swapchain_extent(&caps, 2560, 1440), extent(1920, 1080));
+        assert_eq!(swapchain_extent(&caps, 0, 0), extent(1, 1));
+
+        // Invalid limits don't panic
+        let minimized = vk::SurfaceCapabilitiesKHR {
+            max_image_extent: extent(0, 0),
+            ..caps
+        };
+        assert_eq!(swapchain_extent(&minimized, 800, 600), extent(0, 0));
+
+        // Fixed by the surface
+        let caps = vk::SurfaceCapabilitiesKHR {
+            current_extent: extent(640, 480),
+            ..caps
+        };
+        assert_eq!(swapchain_extent(&caps, 800, 600), extent(640, 480));
+    }
+
+    #[test]
+    fn test_surface_format() {
+        let format = |format, color_space| vk::SurfaceFormatKHR {
+            format,
+            color_space,
+        };
+        let srgb = format(vk::Format::R8G8B8A8_SRGB, vk::ColorSpaceKHR::SRGB_NONLINEAR);
+        let hdr10 = format(
+            vk::Format::A2B10G10R10_UNORM_PACK32,
+            vk::ColorSpaceKHR::HDR10_ST2084_EXT,
+        );
+
+        assert_eq!(
+            surface_format(ColorMode::Hdr10, &[srgb, hdr10]),
+            Some(hdr10)
+        );
+
+        // Unsupported modes fall back to sRGB
+        assert_eq!(surface_format(ColorMode::ScRgb, &[srgb, hdr10]), Some(srgb));
+        assert_eq!(surface_format(ColorMode::Srgb, &[hdr10]), None);
+    }
+
+    #[test]
+    fn test_stencil_format() {
+        // Stencil only formats are preferred
+        assert_eq!(stencil_format(|_| true), Some(vk::Format::S8_UINT));
+        assert_eq!(
+            stencil_format(
+                |format| format != vk::Format::S8_UINT && format != vk::Format::D16_UNORM_S8_UINT
+            ),
+            Some(vk::Format::D24_UNORM_S8_UINT)
+        );
+        assert_eq!(stencil_format(|_| false), None);
+    }
+}
@@ -0,0 +1,200 @@
+// extern
+extern crate nalgebra_glm as glm;
+#[cfg(feature = "winit")]
+use winit::event::VirtualKeyCode;
+
+// intern
+use crate::ui::Inputs;
+use crate::Scene;
+
+//==================================================
+//=== Camera Controller
+//==================================================
+
+/// Moves the camera of a [`Scene`] from the [`Inputs`] of a frame
+///
+/// Dragging with the left mouse button pans the view, the wheel zooms around the cursor and
+/// the arrow or WASD keys move the camera, which speeds up and glides to a stop
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraController {
+    /// Zoom change of a scrolled line
+    pub zoom_speed: f32,
+    /// Keyboard movement in world units per second at a zoom level of 1.0
+    pub move_speed: f32,
+    /// How fast the keyboard movement reaches its speed and stops, higher is snappier
+    pub damping: f32,
+    /// Keyboard movement in world units per second
+    velocity: glm::Vec2,
+    /// Cursor position of the last frame while dragging
+    drag_position: Option<glm::Vec2>,
+}
+
+impl Default for CameraController {
+    fn default() -> Self {
+        Self {
+            zoom_speed: 0.1,
+            move_speed: 1.5,
+            damping: 8.0,
+            velocity: glm::Vec2::zeros(),
+            drag_position: None,
+        }
+    }
+}
+
+impl CameraController {
+    /// Below this speed the camera stops gliding
+    const REST_SPEED: f32 = 1e-3;
+
+    /// Creates a new [`CameraController`] with the default speeds
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Moves the camera of the `scene` by the `inputs` of a frame lasting `dt` seconds
+    ///
+    /// Call this before [`Inputs::end_frame`], the scroll of the frame is cleared there
+    pub fn update(&mut self, inputs: &Inputs, scene: &mut Scene, dt: f32) {
+        self.drag(inputs, scene);
+        self.zoom(inputs, scene);
+
+        // Held keys are only tracked with winit
+        #[cfg(feature = "winit")]
+        let direction = Self::key_direction(inputs);
+        #[cfg(not(feature = "winit"))]
+        let direction = glm::Vec2::zeros();
+        let speed = self.move_speed / scene.zoom_level();
+        self.accelerate(glm::rotate_vec2(&direction, scene.rotation()) * speed, dt);
+
+        if self.velocity != glm::Vec2::zeros() {
+            scene.set_position(scene.position() + self.velocity * dt);
+        }
+    }
+
+    /// Stops the gliding of the camera
+    pub fn stop(&mut self) {
+        self.velocity = glm::Vec2::zeros();
+    }
+
+    /// Keeps the world point grabbed at the start of a drag under the cursor
+    fn drag(&mut self, inputs: &Inputs, scene: &mut Scene) {
+        if !inputs.mouse_down {
+            self.drag_position = None;
+            return;
+        }
+
+        if let Some(last_position) = self.drag_position {
+            let grabbed = scene.screen_to_world(last_position);
+            let current = scene.screen_to_world(inputs.mouse_position);
+            scene.set_position(scene.position() + grabbed - current);
+        }
+
+        self.drag_position = Some(inputs.mouse_position);
+    }
+
+    /// Zooms with the wheel, the world point under the cursor stays in place
+    fn zoom(&mut self, inputs: &Inputs, scene: &mut Scene) {
+        let scroll = inputs.scroll_delta().y;
+        if scroll == 0.0 {
+            return;
+        }
+
+        scene.zoom_at(inputs.mouse_position, scroll * self.zoom_speed);
+    }
+
+    /// Eases the velocity towards `target`, the same amount every second regardless of `dt`
+    fn accelerate(&mut self, target: glm::Vec2, dt: f32) {
+        let blend = 1.0 - (-self.damping * dt.max(0.0)).exp();
+        self.velocity += (target - self.velocity) * blend;
+
+        if target == glm::Vec2::zeros() && self.velocity.norm() < Self::REST_SPEED {
+            self.velocity = glm::Vec2::zeros();
+        }
+    }
+
+    /// Direction of the held arrow and WASD keys relative to the view, up is positive y
+    #[cfg(feature = "winit")]
+    fn key_direction(inputs: &Inputs) -> glm::Vec2 {
+        let axis = |negative: [VirtualKeyCode; 2], positive: [VirtualKeyCode; 2]| -> f32 {
+            let held = |keys: [VirtualKeyCode; 2]| keys.iter().any(|key| inputs.key_held(*key));
+            held(positive) as i32 as f32 - held(negative) as i32 as f32
+        };
+
+        let direction = glm::vec2(
+            axis(
+                [VirtualKeyCode::Left, VirtualKeyCode::A],
+                [VirtualKeyCode::Right, VirtualKeyCode::D],
+            ),
+            axis(
+                [VirtualKeyCode::Down, VirtualKeyCode::S],
+                [VirtualKeyCode::Up, VirtualKeyCode::W],
+            ),
+        );
+
+        // Diagonals are not faster
+        if direction != glm::Vec2::zeros() {
+            direction.normalize()
+        } else {
+            direction
+        }
+    }
+}
+
+//==================================================
+//=== Unit Testing
+//==================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_camera_inertia() {
+        let mut controller = CameraController::new();
+        let target = glm::vec2(1.0, 0.0);
+
+        // Speeds up the same way with any frame rate
+        controller.accelerate(target, 0.1);
+        let coarse = controller.velocity;
+        controller.stop();
+        for _ in 0..10 {
+            controller.accelerate(target, 0.01);
+        }
+        assert!((controller.velocity - coarse).norm() < 1e-4);
+        assert!(controller.velocity.x > 0.0 && controller.velocity.x < 1.0);
+
+        // Glides to a full stop after the keys are released
+        for _ in 0..200 {
+            controller.accelerate(glm::Vec2::zeros(), 0.01);
+        }
+        assert_eq!(controller.velocity, glm::Vec2::zeros());
+    }
+
+    #[cfg(feature = "winit")]
+    #[test]
+    fn test_key_direction() {
+        let mut inputs = Inputs::default();
+        let press = |inputs: &mut Inputs, key| {
+            #[allow(deprecated)]
+            inputs.handle_event(&winit::event::WindowEvent::KeyboardInput {
+                device_id: unsafe { winit::event::DeviceId::dummy() },
+                input: winit::event::KeyboardInput {
+                    scancode: 0,
+                    state: winit::event::ElementState::Pressed,
+                    virtual_keycode: Some(key),
+                    modifiers: Default::default(),
+                },
+                is_synthetic: false,
+            });
+        };
+
+        press(&mut inputs, VirtualKeyCode::W);
+        assert_eq!(
+            CameraController::key_direction(&inputs),
+            glm::vec2(0.0, 1.0)
+        );
+
+        press(&mut inputs, VirtualKeyCode::Right);
+        let direction = CameraController::key_direction(&inputs);
+        assert!((direction.norm() - 1.0).abs() < 1e-6 && direction.x > 0.0);
+    }
+}
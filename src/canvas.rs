@@ -0,0 +1,236 @@
+// extern
+extern crate nalgebra_glm as glm;
+use anyhow::Result;
+use ash::vk;
+
+// intern
+use crate::pipeline::{GraphicsPipeline, PipelineStates, RenderTarget, ShaderCode, StencilMode};
+
+//==================================================
+//=== Fullscreen Effect
+//==================================================
+
+/// Where a fullscreen effect is drawn in the main pass, see `Renderer::fullscreen_effect`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EffectOrder {
+    /// Drawn before the shapes, eg. as a procedural background
+    #[default]
+    Background,
+    /// Drawn over the shapes, before the post-processing
+    Overlay,
+}
+
+/// Push constant block of the fullscreen effects
+///
+/// `vec4 params; vec2 resolution; vec2 mouse; float time;` in the fragment shader
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct EffectConstants {
+    pub params: glm::Vec4,
+    /// Size of the window in pixels
+    pub resolution: glm::Vec2,
+    /// Cursor in window pixels from the top left, the same as `gl_FragCoord`
+    pub mouse: glm::Vec2,
+    /// Seconds since the renderer was created
+    pub time: f32,
+}
+
+/// Fullscreen effect drawn in the current frame
+#[derive(Debug, Clone, Copy)]
+struct QueuedEffect {
+    pipeline: usize,
+    constants: EffectConstants,
+    order: EffectOrder,
+}
+
+//==================================================
+//=== Effect Canvas
+//==================================================
+
+/// Pipelines and queued draws of the fullscreen effects
+///
+/// The pipelines are kept across frames, looked up by the code of their fragment shader
+#[derive(Default)]
+pub(crate) struct EffectCanvas {
+    pipelines: Vec<(Vec<u8>, GraphicsPipeline)>,
+    queued: Vec<QueuedEffect>,
+}
+
+impl EffectCanvas {
+    /// Gives back the index of the pipeline of the `frag` shader, creating it on the first use
+    pub fn pipeline(
+        &mut self,
+        logical_device: &ash::Device,
+        target: &RenderTarget,
+        viewport: &vk::Viewport,
+        scissor: &vk::Rect2D,
+        samples: vk::SampleCountFlags,
+        frag: &[u8],
+    ) -> Result<usize> {
+        if let Some(pipeline) = self.pipelines.iter().position(|(code, _)| code == frag) {
+            return Ok(pipeline);
+        }
+
+        let pipeline =
+            Self::create_pipeline(logical_device, target, viewport, scissor, samples, frag)?;
+        self.pipelines.push((frag.to_vec(), pipeline));
+
+        Ok(self.pipelines.len() - 1)
+    }
+
+    /// Queues a draw of the effect of `pipeline` in the current frame
+    pub fn queue(&mut self, pipeline: usize, constants: EffectConstants, order: EffectOrder) {
+        self.queued.push(QueuedEffect {
+            pipeline,
+            constants,
+            order,
+        });
+    }
+
+    /// `true` if no effect is drawn in the current frame
+    pub fn is_empty(&self) -> bool {
+        self.queued.is_empty()
+    }
+
+    /// Records the effects of `order` in the order they were queued, covering the `scissor`
+    ///
+    /// Expects a begun main pass, leaves its own pipeline bound
+    pub fn record(
+        &self,
+        logical_device: &ash::Device,
+        command_buffer: vk::CommandBuffer,
+        viewport: &vk::Viewport,
+        scissor: &vk::Rect2D,
+        order: EffectOrder,
+    ) {
+        for effect in self.queued.iter().filter(|effect| effect.order == order) {
+            let pipeline = &self.pipelines[effect.pipeline].1;
+
+            unsafe {
+                logical_device.cmd_bind_pipeline(
+                    command_buffer,
+                    vk::PipelineBindPoint::GRAPHICS,
+                    pipeline.pipeline,
+                );
+
+                logical_device.cmd_set_viewport(command_buffer, 0, std::slice::from_ref(viewport));
+                logical_device.cmd_set_scissor(command_buffer, 0, std::slice::from_ref(scissor));
+
+                // Unmasked: A zero compare mask passes the stencil test everywhere
+                logical_device.cmd_set_stencil_compare_mask(
+                    command_buffer,
+                    vk::StencilFaceFlags::FRONT_AND_BACK,
+                    0,
+                );
+                logical_device.cmd_set_stencil_reference(
+                    command_buffer,
+                    vk::StencilFaceFlags::FRONT_AND_BACK,
+                    0,
+                );
+
+                logical_device.cmd_push_constants(
+                    command_buffer,
+                    pipeline.layout,
+                    vk::ShaderStageFlags::FRAGMENT,
+                    0,
+                    std::slice::from_raw_parts(
+                        (&effect.constants as *const EffectConstants).cast::<u8>(),
+                        std::mem::size_of::<EffectConstants>(),
+                    ),
+                );
+
+                // Full-screen triangle
+                logical_device.cmd_draw(command_buffer, 3, 1, 0, 0);
+            }
+        }
+    }
+
+    /// Forgets the effects of the submitted frame, the pipelines are kept
+    pub fn clear(&mut self) {
+        self.queued.clear();
+    }
+
+    /// Destroys every pipeline, the device must not use them anymore
+    pub fn destroy(&mut self, logical_device: &ash::Device) {
+        self.queued.clear();
+        self.pipelines
+            .drain(..)
+            .for_each(|(_, pipeline)| pipeline.destroy(logical_device));
+    }
+
+    /// Creates the pipeline of the `frag` shader drawing the full-screen triangle of the
+    /// post-processing, blended over the frame
+    fn create_pipeline(
+        logical_device: &ash::Device,
+        target: &RenderTarget,
+        viewport: &vk::Viewport,
+        scissor: &vk::Rect2D,
+        samples: vk::SampleCountFlags,
+        frag: &[u8],
+    ) -> Result<GraphicsPipeline> {
+        let push_constant_range = vk::PushConstantRange::builder()
+            .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+            .size(std::mem::size_of::<EffectConstants>() as u32)
+            .build();
+
+        let blend = vk::PipelineColorBlendAttachmentState::builder()
+            .color_write_mask(vk::ColorComponentFlags::RGBA)
+            .blend_enable(true)
+            .src_color_blend_factor(vk::BlendFactor::SRC_ALPHA)
+            .dst_color_blend_factor(vk::BlendFactor::ONE_MINUS_SRC_ALPHA)
+            .color_blend_op(vk::BlendOp::ADD)
+            .src_alpha_blend_factor(vk::BlendFactor::ONE)
+            .dst_alpha_blend_factor(vk::BlendFactor::ZERO)
+            .alpha_blend_op(vk::BlendOp::ADD)
+            .build();
+
+        GraphicsPipeline::with_states(
+            logical_device,
+            target,
+            &[],
+            std::slice::from_ref(&push_constant_range),
+            &ShaderCode::post_fragment(frag)?,
+            &PipelineStates {
+                viewport: *viewport,
+                scissor: *scissor,
+                vertex_stride: 0,
+                vertex_attributes: &[],
+                blend,
+                cull_mode: vk::CullModeFlags::NONE,
+                samples,
+                polygon_mode: vk::PolygonMode::FILL,
+                topology: vk::PrimitiveTopology::TRIANGLE_LIST,
+                stencil: StencilMode::Test,
+            },
+        )
+    }
+}
+
+//==================================================
+//=== Unit Testing
+//==================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_effect_constants() {
+        // Matches the std430 push constant block of the shaders, without trailing padding
+        assert_eq!(std::mem::size_of::<EffectConstants>(), 36);
+
+        let constants = EffectConstants {
+            params: glm::Vec4::zeros(),
+            resolution: glm::Vec2::zeros(),
+            mouse: glm::Vec2::zeros(),
+            time: 0.0,
+        };
+        let base = &constants as *const EffectConstants as usize;
+        assert_eq!(
+            &constants.resolution as *const glm::Vec2 as usize - base,
+            16
+        );
+        assert_eq!(&constants.mouse as *const glm::Vec2 as usize - base, 24);
+        assert_eq!(&constants.time as *const f32 as usize - base, 32);
+    }
+}
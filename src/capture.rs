@@ -0,0 +1,527 @@
+// std
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+// extern
+use anyhow::{anyhow, Context, Result};
+use ash::vk;
+
+// intern
+use crate::allocator::MemoryAllocator;
+use crate::buffers::ReadbackBuffer;
+
+//==================================================
+//=== Record Format
+//==================================================
+
+/// Output of a recording, picked from the path given to `Renderer::start_recording`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordFormat {
+    /// Animated PNG, for paths ending with `.png` or `.apng`
+    Apng,
+    /// Numbered PNG files (`frame_00000.png`, ...) in the directory at the path
+    ImageSequence,
+}
+
+impl RecordFormat {
+    /// Gives back the format of a recording written to `path`
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("png") || ext.eq_ignore_ascii_case("apng") => {
+                RecordFormat::Apng
+            }
+            _ => RecordFormat::ImageSequence,
+        }
+    }
+}
+
+//==================================================
+//=== Encoder
+//==================================================
+
+/// Frame read back from the swapchain, encoded on the encoder thread
+struct CapturedFrame {
+    width: u32,
+    height: u32,
+    /// Pixels in the order of the swapchain format
+    pixels: Vec<u8>,
+    bgra: bool,
+    captured: Instant,
+}
+
+/// Converts the `pixels` of a swapchain image into opaque RGBA8
+fn to_rgba(pixels: &mut [u8], bgra: bool) {
+    for pixel in pixels.chunks_exact_mut(4) {
+        if bgra {
+            pixel.swap(0, 2);
+        }
+        // Presented with opaque composition, the alpha is meaningless
+        pixel[3] = u8::MAX;
+    }
+}
+
+/// Time every frame stays on the screen, measured until the capture of the next one
+///
+/// The last frame keeps the delay of the one before it
+fn frame_delays(captured: &[Instant]) -> Vec<Duration> {
+    const DEFAULT_DELAY: Duration = Duration::from_micros(16_667);
+
+    let mut delays: Vec<Duration> = captured
+        .windows(2)
+        .map(|pair| pair[1].duration_since(pair[0]))
+        .collect();
+
+    if !captured.is_empty() {
+        delays.push(delays.last().copied().unwrap_or(DEFAULT_DELAY));
+    }
+
+    delays
+}
+
+/// Writes a single RGBA8 PNG image
+fn write_png(path: &Path, width: u32, height: u32, pixels: &[u8]) -> Result<()> {
+    let file = std::fs::File::create(path)
+        .with_context(|| format!("Recorder: Can't create {}", path.display()))?;
+
+    let mut encoder = png::Encoder::new(std::io::BufWriter::new(file), width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(pixels)?;
+    writer.finish()?;
+
+    Ok(())
+}
+
+/// Writes the `frames` as an endlessly looping animated PNG
+fn write_apng(path: &Path, frames: &[CapturedFrame]) -> Result<()> {
+    let Some(first) = frames.first() else {
+        return Err(anyhow!(
+            "Recorder: No frames captured for {}",
+            path.display()
+        ));
+    };
+
+    if let Some((index, frame)) = frames
+        .iter()
+        .enumerate()
+        .find(|(_, frame)| (frame.width, frame.height) != (first.width, first.height))
+    {
+        return Err(anyhow!(
+            "Recorder: Frame {} is {}x{}, the animation is {}x{}",
+            index,
+            frame.width,
+            frame.height,
+            first.width,
+            first.height
+        ));
+    }
+
+    let file = std::fs::File::create(path)
+        .with_context(|| format!("Recorder: Can't create {}", path.display()))?;
+
+    let mut encoder = png::Encoder::new(std::io::BufWriter::new(file), first.width, first.height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_animated(frames.len() as u32, 0)?;
+
+    let captured: Vec<Instant> = frames.iter().map(|frame| frame.captured).collect();
+
+    let mut writer = encoder.write_header()?;
+    for (frame, delay) in frames.iter().zip(frame_delays(&captured)) {
+        let millis = delay.as_millis().clamp(1, u16::MAX as u128) as u16;
+        writer.set_frame_delay(millis, 1000)?;
+        writer.write_image_data(&frame.pixels)?;
+    }
+    writer.finish()?;
+
+    Ok(())
+}
+
+/// Encodes the frames sent to the encoder thread into `path` until the channel is closed,
+/// gives back the number of frames written
+///
+/// The animation is written at once after the last frame, its frame count is part of its header
+fn encode(
+    path: &Path,
+    format: RecordFormat,
+    frames: mpsc::Receiver<CapturedFrame>,
+) -> Result<usize> {
+    let mut animation = Vec::new();
+    let mut count = 0;
+
+    for mut frame in frames {
+        to_rgba(&mut frame.pixels, frame.bgra);
+
+        match format {
+            RecordFormat::ImageSequence => write_png(
+                &path.join(format!("frame_{:05}.png", count)),
+                frame.width,
+                frame.height,
+                &frame.pixels,
+            )?,
+            RecordFormat::Apng => animation.push(frame),
+        }
+
+        count += 1;
+    }
+
+    if format == RecordFormat::Apng {
+        write_apng(path, &animation)?;
+    }
+
+    Ok(count)
+}
+
+/// Starts the encoder thread writing into `path`
+fn spawn_encoder(
+    path: PathBuf,
+    format: RecordFormat,
+) -> (mpsc::Sender<CapturedFrame>, JoinHandle<Result<usize>>) {
+    let (sender, receiver) = mpsc::channel();
+
+    let handle = std::thread::Builder::new()
+        .name("lavapond-recorder".to_string())
+        .spawn(move || encode(&path, format, receiver))
+        .expect("Recorder: Can't spawn the encoder thread");
+
+    (sender, handle)
+}
+
+//==================================================
+//=== Recorder
+//==================================================
+
+/// Readback buffer of a frame in flight
+#[derive(Default)]
+struct CaptureSlot {
+    buffer: Option<ReadbackBuffer>,
+    /// Extent of the frame copied into the buffer and not read yet
+    pending: Option<(vk::Extent2D, Instant)>,
+}
+
+/// Captures consecutive frames from the swapchain and encodes them on a background thread,
+/// created by `Renderer::start_recording`
+///
+/// Every frame is copied into a readback buffer of its frame in flight, which is read when
+/// the frame is done on the GPU
+pub(crate) struct Recorder {
+    frames_left: usize,
+    /// Swapchain images are B8G8R8A8
+    bgra: bool,
+    slots: Vec<CaptureSlot>,
+    sender: Option<mpsc::Sender<CapturedFrame>>,
+    encoder: JoinHandle<Result<usize>>,
+}
+
+impl Recorder {
+    /// Starts recording the next `frame_count` frames of the `swapchain_format` into `path`
+    pub fn start(
+        path: &Path,
+        frame_count: usize,
+        swapchain_format: vk::Format,
+        max_frames_inflight: usize,
+    ) -> Result<Self> {
+        let bgra = match swapchain_format {
+            vk::Format::B8G8R8A8_SRGB | vk::Format::B8G8R8A8_UNORM => true,
+            vk::Format::R8G8B8A8_SRGB | vk::Format::R8G8B8A8_UNORM => false,
+            format => return Err(anyhow!("Recorder: Can't capture {:?} frames", format)),
+        };
+
+        let format = RecordFormat::from_path(path);
+        if format == RecordFormat::ImageSequence {
+            std::fs::create_dir_all(path)
+                .with_context(|| format!("Recorder: Can't create {}", path.display()))?;
+        }
+
+        let (sender, encoder) = spawn_encoder(path.to_path_buf(), format);
+
+        Ok(Self {
+            frames_left: frame_count,
+            bgra,
+            slots: (0..max_frames_inflight)
+                .map(|_| CaptureSlot::default())
+                .collect(),
+            sender: Some(sender),
+            encoder,
+        })
+    }
+
+    /// Gives back true while frames are captured or not yet read back
+    pub fn is_capturing(&self) -> bool {
+        self.frames_left > 0 || self.slots.iter().any(|slot| slot.pending.is_some())
+    }
+
+    /// Records the copy of the presentable swapchain `image` at the end of the frame
+    ///
+    /// The readback buffer of `current_frame` is replaced by a larger one when the frame does
+    /// not fit, it must not be in use by the GPU
+    pub fn record(
+        &mut self,
+        logical_device: &ash::Device,
+        allocator: &mut MemoryAllocator,
+        command_buffer: vk::CommandBuffer,
+        image: vk::Image,
+        extent: vk::Extent2D,
+        current_frame: usize,
+    ) -> Result<()> {
+        if self.frames_left == 0 {
+            return Ok(());
+        }
+
+        let slot = self
+            .slots
+            .get_mut(current_frame)
+            .context("Recorder: Slot index out of bounds")?;
+
+        let frame_size = extent.width as u64 * extent.height as u64 * 4;
+        if slot
+            .buffer
+            .as_ref()
+            .is_none_or(|buffer| buffer.capacity < frame_size)
+        {
+            let buffer = ReadbackBuffer::new(logical_device, allocator, frame_size)?;
+
+            if let Some(old_buffer) = slot.buffer.replace(buffer) {
+                old_buffer.destroy(logical_device, allocator);
+            }
+        }
+
+        let buffer = slot.buffer.as_ref().map(|buffer| buffer.buffer);
+        record_copy(
+            logical_device,
+            command_buffer,
+            image,
+            extent,
+            buffer.context("Recorder: No readback buffer")?,
+        );
+
+        slot.pending = Some((extent, Instant::now()));
+        self.frames_left -= 1;
+
+        Ok(())
+    }
+
+    /// Hands over the frame captured in the slot of `current_frame` to the encoder, the GPU
+    /// must have finished that frame
+    ///
+    /// After the last frame the encoder is left to finish the file
+    pub fn collect(&mut self, current_frame: usize) -> Result<()> {
+        let slot = self
+            .slots
+            .get_mut(current_frame)
+            .context("Recorder: Slot index out of bounds")?;
+
+        if let (Some((extent, captured)), Some(buffer)) = (slot.pending.take(), &slot.buffer) {
+            let pixels = buffer.read(extent.width as usize * extent.height as usize * 4)?;
+
+            if let Some(sender) = &self.sender {
+                // A failed encoder gives back its error when stopped
+                let _ = sender.send(CapturedFrame {
+                    width: extent.width,
+                    height: extent.height,
+                    pixels,
+                    bgra: self.bgra,
+                    captured,
+                });
+            }
+        }
+
+        if !self.is_capturing() {
+            self.sender = None;
+        }
+
+        Ok(())
+    }
+
+    /// Stops capturing, hands over the frames not yet read and waits for the encoder
+    ///
+    /// Gives back the number of frames written, the device must be idle
+    pub fn stop(
+        mut self,
+        logical_device: &ash::Device,
+        allocator: &mut MemoryAllocator,
+    ) -> Result<usize> {
+        self.frames_left = 0;
+
+        // Frames are read in the order they were captured
+        let mut slot_order: Vec<usize> = (0..self.slots.len()).collect();
+        slot_order.sort_by_key(|index| self.slots[*index].pending.map(|(_, captured)| captured));
+
+        let collected = slot_order
+            .into_iter()
+            .try_for_each(|index| self.collect(index));
+
+        self.slots
+            .drain(..)
+            .filter_map(|slot| slot.buffer)
+            .for_each(|buffer| buffer.destroy(logical_device, allocator));
+
+        self.sender = None;
+        let encoded = match self.encoder.join() {
+            Ok(encoded) => encoded,
+            Err(_) => Err(anyhow!("Recorder: The encoder thread panicked")),
+        };
+
+        collected.and(encoded)
+    }
+}
+
+/// Records the copy of the presentable `image` into the `buffer`, leaving the image
+/// presentable again
+fn record_copy(
+    logical_device: &ash::Device,
+    command_buffer: vk::CommandBuffer,
+    image: vk::Image,
+    extent: vk::Extent2D,
+    buffer: vk::Buffer,
+) {
+    let subresource_range = vk::ImageSubresourceRange::builder()
+        .aspect_mask(vk::ImageAspectFlags::COLOR)
+        .level_count(1)
+        .layer_count(1)
+        .build();
+
+    let image_barrier = |old_layout, new_layout, src_access, dst_access| {
+        vk::ImageMemoryBarrier::builder()
+            .src_access_mask(src_access)
+            .dst_access_mask(dst_access)
+            .old_layout(old_layout)
+            .new_layout(new_layout)
+            .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+            .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+            .image(image)
+            .subresource_range(subresource_range)
+            .build()
+    };
+
+    let to_transfer = image_barrier(
+        vk::ImageLayout::PRESENT_SRC_KHR,
+        vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+        vk::AccessFlags::COLOR_ATTACHMENT_WRITE,
+        vk::AccessFlags::TRANSFER_READ,
+    );
+    let to_present = image_barrier(
+        vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+        vk::ImageLayout::PRESENT_SRC_KHR,
+        vk::AccessFlags::TRANSFER_READ,
+        vk::AccessFlags::empty(),
+    );
+
+    // Read on the host after the fence of the frame
+    let buffer_barrier = vk::BufferMemoryBarrier::builder()
+        .src_access_mask(vk::AccessFlags::TRANSFER_WRITE)
+        .dst_access_mask(vk::AccessFlags::HOST_READ)
+        .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+        .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+        .buffer(buffer)
+        .size(vk::WHOLE_SIZE)
+        .build();
+
+    let region = vk::BufferImageCopy::builder()
+        .image_subresource(
+            vk::ImageSubresourceLayers::builder()
+                .aspect_mask(vk::ImageAspectFlags::COLOR)
+                .layer_count(1)
+                .build(),
+        )
+        .image_extent(vk::Extent3D {
+            width: extent.width,
+            height: extent.height,
+            depth: 1,
+        });
+
+    unsafe {
+        logical_device.cmd_pipeline_barrier(
+            command_buffer,
+            vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+            vk::PipelineStageFlags::TRANSFER,
+            vk::DependencyFlags::empty(),
+            &[],
+            &[],
+            std::slice::from_ref(&to_transfer),
+        );
+
+        logical_device.cmd_copy_image_to_buffer(
+            command_buffer,
+            image,
+            vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+            buffer,
+            std::slice::from_ref(&region),
+        );
+
+        logical_device.cmd_pipeline_barrier(
+            command_buffer,
+            vk::PipelineStageFlags::TRANSFER,
+            vk::PipelineStageFlags::BOTTOM_OF_PIPE | vk::PipelineStageFlags::HOST,
+            vk::DependencyFlags::empty(),
+            &[],
+            std::slice::from_ref(&buffer_barrier),
+            std::slice::from_ref(&to_present),
+        );
+    }
+}
+
+//==================================================
+//=== Unit Testing
+//==================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_delays() {
+        let start = Instant::now();
+        let at = |millis| start + Duration::from_millis(millis);
+
+        assert!(frame_delays(&[]).is_empty());
+        assert_eq!(frame_delays(&[start]), [Duration::from_micros(16_667)]);
+        assert_eq!(
+            frame_delays(&[at(0), at(20), at(50)]),
+            [20, 30, 30].map(Duration::from_millis)
+        );
+    }
+
+    #[test]
+    fn test_encode_apng() {
+        let dir = std::env::temp_dir().join(format!("lavapond_capture_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("clip.png");
+        assert_eq!(RecordFormat::from_path(&path), RecordFormat::Apng);
+        assert_eq!(RecordFormat::from_path(&dir), RecordFormat::ImageSequence);
+
+        let (sender, encoder) = spawn_encoder(path.clone(), RecordFormat::Apng);
+        let start = Instant::now();
+        for index in 0..3u64 {
+            // BGRA blue pixels
+            let pixels = [255, 0, 0, 0].repeat(2 * 2);
+            sender
+                .send(CapturedFrame {
+                    width: 2,
+                    height: 2,
+                    pixels,
+                    bgra: true,
+                    captured: start + Duration::from_millis(40 * index),
+                })
+                .unwrap();
+        }
+        drop(sender);
+        assert_eq!(encoder.join().unwrap().unwrap(), 3);
+
+        let decoder = png::Decoder::new(std::fs::File::open(&path).unwrap());
+        let mut reader = decoder.read_info().unwrap();
+        let animation = reader.info().animation_control.unwrap();
+        assert_eq!(animation.num_frames, 3);
+
+        let mut frame = vec![0; reader.output_buffer_size()];
+        reader.next_frame(&mut frame).unwrap();
+        assert_eq!(frame[..4], [0, 0, 255, 255]);
+        assert_eq!(reader.info().frame_control.unwrap().delay_num, 40);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,396 @@
+// extern
+use anyhow::{anyhow, Result};
+use ash::vk;
+
+// intern
+use crate::buffers::InstanceBuffer;
+use crate::pipeline::ShaderCode;
+
+//==================================================
+//=== Compute Pipeline
+//==================================================
+
+/// Handle of a compute pipeline created with `Renderer::create_compute_pipeline`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ComputeHandle(pub(crate) usize);
+
+/// Handle of a storage buffer created with `Renderer::create_compute_buffer`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ComputeBufferHandle(pub(crate) usize);
+
+/// Storage buffer bound to a compute dispatch, the bindings are numbered from `binding = 0`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComputeBinding {
+    Buffer(ComputeBufferHandle),
+    /// Instance buffer of the frame, one std430 `ModelData` per drawn instance
+    ///
+    /// Instances are stored in drawing order, instances of the same layer, pipeline, texture
+    /// and object keep the order they were submitted in
+    Instances,
+}
+
+/// Compute pipeline reading and writing `binding_count` storage buffers at `set = 0`
+pub(crate) struct ComputePipeline {
+    pub set_layout: vk::DescriptorSetLayout,
+    pub layout: vk::PipelineLayout,
+    pub pipeline: vk::Pipeline,
+    pub binding_count: u32,
+    pub push_constant_size: u32,
+}
+
+impl ComputePipeline {
+    /// Most storage buffers a single compute pipeline can bind
+    pub const MAX_BINDINGS: u32 = 8;
+
+    /// Creates a new [`ComputePipeline`] from the SPIR-V `code`
+    pub fn new(
+        logical_device: &ash::Device,
+        code: &[u8],
+        binding_count: u32,
+        push_constant_size: u32,
+    ) -> Result<Self> {
+        if binding_count > Self::MAX_BINDINGS {
+            return Err(anyhow!(
+                "Compute: {} bindings are more than the maximum of {}",
+                binding_count,
+                Self::MAX_BINDINGS
+            ));
+        }
+
+        let code = ShaderCode::from_bytes(code)?;
+
+        let set_layout = {
+            let layout_bindings: Vec<vk::DescriptorSetLayoutBinding> = (0..binding_count)
+                .map(|binding| {
+                    vk::DescriptorSetLayoutBinding::builder()
+                        .binding(binding)
+                        .descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
+                        .descriptor_count(1)
+                        .stage_flags(vk::ShaderStageFlags::COMPUTE)
+                        .build()
+                })
+                .collect();
+
+            let create_info =
+                vk::DescriptorSetLayoutCreateInfo::builder().bindings(&layout_bindings);
+
+            unsafe { logical_device.create_descriptor_set_layout(&create_info, None) }?
+        };
+
+        let layout = {
+            let push_constant_range = vk::PushConstantRange::builder()
+                .stage_flags(vk::ShaderStageFlags::COMPUTE)
+                .size(push_constant_size)
+                .build();
+
+            let push_constant_ranges = if push_constant_size > 0 {
+                std::slice::from_ref(&push_constant_range)
+            } else {
+                &[]
+            };
+
+            let create_info = vk::PipelineLayoutCreateInfo::builder()
+                .set_layouts(std::slice::from_ref(&set_layout))
+                .push_constant_ranges(push_constant_ranges);
+
+            unsafe { logical_device.create_pipeline_layout(&create_info, None) }?
+        };
+
+        let shader_module = {
+            let create_info = vk::ShaderModuleCreateInfo::builder().code(&code);
+
+            unsafe { logical_device.create_shader_module(&create_info, None) }?
+        };
+
+        let stage = vk::PipelineShaderStageCreateInfo::builder()
+            .stage(vk::ShaderStageFlags::COMPUTE)
+            .module(shader_module)
+            .name(unsafe { std::ffi::CStr::from_bytes_with_nul_unchecked(b"main\0") })
+            .build();
+
+        let create_info = vk::ComputePipelineCreateInfo::builder()
+            .stage(stage)
+            .layout(layout)
+            .build();
+
+        let pipeline = unsafe {
+            logical_device.create_compute_pipelines(
+                vk::PipelineCache::null(),
+                std::slice::from_ref(&create_info),
+                None,
+            )
+        };
+
+        unsafe { logical_device.destroy_shader_module(shader_module, None) };
+
+        let pipeline = match pipeline {
+            Ok(pipelines) => pipelines[0],
+            Err((_, err)) => {
+                unsafe {
+                    logical_device.destroy_pipeline_layout(layout, None);
+                    logical_device.destroy_descriptor_set_layout(set_layout, None);
+                }
+                return Err(anyhow!("Compute: Pipeline creation failed, {}", err));
+            }
+        };
+
+        Ok(Self {
+            set_layout,
+            layout,
+            pipeline,
+            binding_count,
+            push_constant_size,
+        })
+    }
+
+    /// Checks whether a dispatch with `bindings` and `push_constants` fits the pipeline
+    pub fn validate(&self, bindings: &[ComputeBinding], push_constants: &[u8]) -> Result<()> {
+        if bindings.len() != self.binding_count as usize {
+            return Err(anyhow!(
+                "Compute: Pipeline expects {} bindings, got {}",
+                self.binding_count,
+                bindings.len()
+            ));
+        }
+
+        if push_constants.len() > self.push_constant_size as usize || push_constants.len() % 4 != 0
+        {
+            return Err(anyhow!(
+                "Compute: {} bytes of push constants do not fit the {} byte block",
+                push_constants.len(),
+                self.push_constant_size
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Destroys the pipeline, its layout and set layout, the device must not use them anymore
+    pub fn destroy(&self, logical_device: &ash::Device) {
+        unsafe {
+            logical_device.destroy_pipeline(self.pipeline, None);
+            logical_device.destroy_pipeline_layout(self.layout, None);
+            logical_device.destroy_descriptor_set_layout(self.set_layout, None);
+        }
+    }
+}
+
+//==================================================
+//=== Compute Dispatch
+//==================================================
+
+/// Dispatch queued by `Renderer::dispatch_compute`, recorded in the next frame
+pub(crate) struct ComputeDispatch {
+    pub pipeline: ComputeHandle,
+    pub bindings: Vec<ComputeBinding>,
+    pub push_constants: Vec<u8>,
+    pub group_count: [u32; 3],
+}
+
+/// Most dispatches recorded in a single frame
+pub(crate) const MAX_DISPATCHES: u32 = 32;
+
+/// Creates the descriptor pool of the dispatch sets of a frame, reset before every frame
+pub(crate) fn create_dispatch_pool(logical_device: &ash::Device) -> Result<vk::DescriptorPool> {
+    let pool_size = vk::DescriptorPoolSize::builder()
+        .ty(vk::DescriptorType::STORAGE_BUFFER)
+        .descriptor_count(MAX_DISPATCHES * ComputePipeline::MAX_BINDINGS);
+
+    let create_info = vk::DescriptorPoolCreateInfo::builder()
+        .pool_sizes(std::slice::from_ref(&pool_size))
+        .max_sets(MAX_DISPATCHES);
+
+    Ok(unsafe { logical_device.create_descriptor_pool(&create_info, None) }?)
+}
+
+/// Records the `dispatches` in order with barriers between them
+///
+/// * Dispatches wait for the previous frames reading or writing the buffers
+/// * Every dispatch sees the writes of the previous one
+/// * The vertex shaders and the host see the writes of the last one
+pub(crate) fn record_dispatches(
+    logical_device: &ash::Device,
+    command_buffer: vk::CommandBuffer,
+    dispatch_pool: vk::DescriptorPool,
+    pipelines: &[ComputePipeline],
+    buffers: &[InstanceBuffer],
+    instance_buffer: &InstanceBuffer,
+    dispatches: &[ComputeDispatch],
+) -> Result<()> {
+    if dispatches.is_empty() {
+        return Ok(());
+    }
+
+    unsafe {
+        logical_device.reset_descriptor_pool(dispatch_pool, vk::DescriptorPoolResetFlags::empty())
+    }?;
+
+    memory_barrier(
+        logical_device,
+        command_buffer,
+        vk::PipelineStageFlags::VERTEX_SHADER | vk::PipelineStageFlags::COMPUTE_SHADER,
+        vk::PipelineStageFlags::COMPUTE_SHADER,
+        vk::AccessFlags::SHADER_WRITE,
+        vk::AccessFlags::SHADER_READ | vk::AccessFlags::SHADER_WRITE,
+    );
+
+    for (index, dispatch) in dispatches.iter().enumerate() {
+        let pipeline = pipelines
+            .get(dispatch.pipeline.0)
+            .ok_or_else(|| anyhow!("Compute: Unknown pipeline {:?}", dispatch.pipeline))?;
+
+        let set = {
+            let allocate_info = vk::DescriptorSetAllocateInfo::builder()
+                .descriptor_pool(dispatch_pool)
+                .set_layouts(std::slice::from_ref(&pipeline.set_layout));
+
+            unsafe { logical_device.allocate_descriptor_sets(&allocate_info) }?[0]
+        };
+
+        let buffer_infos = dispatch
+            .bindings
+            .iter()
+            .map(|binding| {
+                let buffer = match binding {
+                    ComputeBinding::Buffer(handle) => {
+                        buffers
+                            .get(handle.0)
+                            .ok_or_else(|| anyhow!("Compute: Unknown buffer {:?}", handle))?
+                            .buffer
+                    }
+                    ComputeBinding::Instances => instance_buffer.buffer,
+                };
+
+                Ok(vk::DescriptorBufferInfo::builder()
+                    .buffer(buffer)
+                    .offset(0)
+                    .range(vk::WHOLE_SIZE)
+                    .build())
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let descriptor_writes: Vec<vk::WriteDescriptorSet> = buffer_infos
+            .iter()
+            .enumerate()
+            .map(|(binding, buffer_info)| {
+                vk::WriteDescriptorSet::builder()
+                    .dst_set(set)
+                    .dst_binding(binding as u32)
+                    .descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
+                    .buffer_info(std::slice::from_ref(buffer_info))
+                    .build()
+            })
+            .collect();
+
+        if index > 0 {
+            memory_barrier(
+                logical_device,
+                command_buffer,
+                vk::PipelineStageFlags::COMPUTE_SHADER,
+                vk::PipelineStageFlags::COMPUTE_SHADER,
+                vk::AccessFlags::SHADER_WRITE,
+                vk::AccessFlags::SHADER_READ | vk::AccessFlags::SHADER_WRITE,
+            );
+        }
+
+        unsafe {
+            logical_device.update_descriptor_sets(&descriptor_writes, &[]);
+
+            logical_device.cmd_bind_pipeline(
+                command_buffer,
+                vk::PipelineBindPoint::COMPUTE,
+                pipeline.pipeline,
+            );
+
+            logical_device.cmd_bind_descriptor_sets(
+                command_buffer,
+                vk::PipelineBindPoint::COMPUTE,
+                pipeline.layout,
+                0,
+                std::slice::from_ref(&set),
+                &[],
+            );
+
+            if !dispatch.push_constants.is_empty() {
+                logical_device.cmd_push_constants(
+                    command_buffer,
+                    pipeline.layout,
+                    vk::ShaderStageFlags::COMPUTE,
+                    0,
+                    &dispatch.push_constants,
+                );
+            }
+
+            let [x, y, z] = dispatch.group_count;
+            logical_device.cmd_dispatch(command_buffer, x, y, z);
+        }
+    }
+
+    memory_barrier(
+        logical_device,
+        command_buffer,
+        vk::PipelineStageFlags::COMPUTE_SHADER,
+        vk::PipelineStageFlags::VERTEX_SHADER | vk::PipelineStageFlags::HOST,
+        vk::AccessFlags::SHADER_WRITE,
+        vk::AccessFlags::SHADER_READ | vk::AccessFlags::HOST_READ,
+    );
+
+    Ok(())
+}
+
+fn memory_barrier(
+    logical_device: &ash::Device,
+    command_buffer: vk::CommandBuffer,
+    src_stage_mask: vk::PipelineStageFlags,
+    dst_stage_mask: vk::PipelineStageFlags,
+    src_access_mask: vk::AccessFlags,
+    dst_access_mask: vk::AccessFlags,
+) {
+    let memory_barrier = vk::MemoryBarrier::builder()
+        .src_access_mask(src_access_mask)
+        .dst_access_mask(dst_access_mask);
+
+    unsafe {
+        logical_device.cmd_pipeline_barrier(
+            command_buffer,
+            src_stage_mask,
+            dst_stage_mask,
+            vk::DependencyFlags::empty(),
+            std::slice::from_ref(&memory_barrier),
+            &[],
+            &[],
+        )
+    };
+}
+
+//==================================================
+//=== Unit Testing
+//==================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_dispatch() {
+        let pipeline = ComputePipeline {
+            set_layout: vk::DescriptorSetLayout::null(),
+            layout: vk::PipelineLayout::null(),
+            pipeline: vk::Pipeline::null(),
+            binding_count: 2,
+            push_constant_size: 16,
+        };
+
+        let bindings = [
+            ComputeBinding::Buffer(ComputeBufferHandle(0)),
+            ComputeBinding::Instances,
+        ];
+        assert!(pipeline.validate(&bindings, &[0; 16]).is_ok());
+        assert!(pipeline.validate(&bindings, &[]).is_ok());
+
+        // Missing binding, push constants above the block or not in whole words
+        assert!(pipeline.validate(&bindings[..1], &[]).is_err());
+        assert!(pipeline.validate(&bindings, &[0; 20]).is_err());
+        assert!(pipeline.validate(&bindings, &[0; 6]).is_err());
+    }
+}
@@ -10,30 +10,53 @@ pub struct Descriptor {
     pub set_layout: vk::DescriptorSetLayout,
     pub pool: vk::DescriptorPool,
     pub sets: Vec<vk::DescriptorSet>,
+    pub texture_set_layout: vk::DescriptorSetLayout,
+    pub texture_pool: vk::DescriptorPool,
 }
 
 impl Descriptor {
+    /// Maximum number of textures which can be allocated from the texture pool
+    pub const MAX_TEXTURES: u32 = 64;
+
     /// Creates a new [`Descriptor`]
     pub fn new(logical_device: &ash::Device, max_frames_inflight: usize) -> Result<Self> {
         let set_layout = {
-            let layout_binding = vk::DescriptorSetLayoutBinding::builder()
-                .binding(0)
-                .descriptor_type(vk::DescriptorType::UNIFORM_BUFFER)
-                .descriptor_count(1)
-                .stage_flags(vk::ShaderStageFlags::VERTEX);
-
-            let create_info = vk::DescriptorSetLayoutCreateInfo::builder()
-                .bindings(std::slice::from_ref(&layout_binding));
+            // Camera & Draw Instances
+            let layout_bindings = [
+                vk::DescriptorSetLayoutBinding::builder()
+                    .binding(0)
+                    .descriptor_type(vk::DescriptorType::UNIFORM_BUFFER_DYNAMIC)
+                    .descriptor_count(1)
+                    .stage_flags(vk::ShaderStageFlags::VERTEX)
+                    .build(),
+                vk::DescriptorSetLayoutBinding::builder()
+                    .binding(1)
+                    .descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
+                    .descriptor_count(1)
+                    .stage_flags(vk::ShaderStageFlags::VERTEX)
+                    .build(),
+            ];
+
+            let create_info =
+                vk::DescriptorSetLayoutCreateInfo::builder().bindings(&layout_bindings);
 
             unsafe { logical_device.create_descriptor_set_layout(&create_info, None) }?
         };
 
         let pool = {
-            let pool_size =
-                vk::DescriptorPoolSize::builder().descriptor_count(max_frames_inflight as u32);
+            let pool_sizes = [
+                vk::DescriptorPoolSize::builder()
+                    .ty(vk::DescriptorType::UNIFORM_BUFFER_DYNAMIC)
+                    .descriptor_count(max_frames_inflight as u32)
+                    .build(),
+                vk::DescriptorPoolSize::builder()
+                    .ty(vk::DescriptorType::STORAGE_BUFFER)
+                    .descriptor_count(max_frames_inflight as u32)
+                    .build(),
+            ];
 
             let create_info = vk::DescriptorPoolCreateInfo::builder()
-                .pool_sizes(std::slice::from_ref(&pool_size))
+                .pool_sizes(&pool_sizes)
                 .max_sets(max_frames_inflight as u32);
 
             unsafe { logical_device.create_descriptor_pool(&create_info, None) }?
@@ -49,14 +72,43 @@ impl Descriptor {
             unsafe { logical_device.allocate_descriptor_sets(&allocate_info) }?
         };
 
+        let texture_set_layout = {
+            let layout_binding = vk::DescriptorSetLayoutBinding::builder()
+                .binding(0)
+                .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+                .descriptor_count(1)
+                .stage_flags(vk::ShaderStageFlags::FRAGMENT);
+
+            let create_info = vk::DescriptorSetLayoutCreateInfo::builder()
+                .bindings(std::slice::from_ref(&layout_binding));
+
+            unsafe { logical_device.create_descriptor_set_layout(&create_info, None) }?
+        };
+
+        let texture_pool = {
+            let pool_size = vk::DescriptorPoolSize::builder()
+                .ty(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+                .descriptor_count(Self::MAX_TEXTURES);
+
+            let create_info = vk::DescriptorPoolCreateInfo::builder()
+                .pool_sizes(std::slice::from_ref(&pool_size))
+                .max_sets(Self::MAX_TEXTURES);
+
+            unsafe { logical_device.create_descriptor_pool(&create_info, None) }?
+        };
+
         Ok(Self {
             set_layout,
             pool,
             sets,
+            texture_set_layout,
+            texture_pool,
         })
     }
 
     /// Updates the current descriptor sets with buffer data
+    ///
+    /// The uniform buffer is dynamic, `data_size` bytes are visible from the offset given at binding
     pub fn update_descriptor_sets(
         &self,
         logical_device: &ash::Device,
@@ -83,7 +135,7 @@ impl Descriptor {
                 )
                 .dst_binding(0)
                 .dst_array_element(0)
-                .descriptor_type(vk::DescriptorType::UNIFORM_BUFFER)
+                .descriptor_type(vk::DescriptorType::UNIFORM_BUFFER_DYNAMIC)
                 .buffer_info(std::slice::from_ref(&buffer_info));
 
             unsafe {
@@ -94,3 +146,72 @@ impl Descriptor {
         Ok(())
     }
 }
+
+/// Writes the instance storage `buffer` into the binding 1 of `set`
+///
+/// The set must not be in use by the GPU while updating
+pub fn write_instance_buffer(
+    logical_device: &ash::Device,
+    set: vk::DescriptorSet,
+    buffer: vk::Buffer,
+) {
+    let buffer_info = vk::DescriptorBufferInfo::builder()
+        .buffer(buffer)
+        .offset(0)
+        .range(vk::WHOLE_SIZE);
+
+    let descriptor_write = vk::WriteDescriptorSet::builder()
+        .dst_set(set)
+        .dst_binding(1)
+        .dst_array_element(0)
+        .descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
+        .buffer_info(std::slice::from_ref(&buffer_info));
+
+    unsafe { logical_device.update_descriptor_sets(std::slice::from_ref(&descriptor_write), &[]) };
+}
+
+/// Allocates a texture descriptor set from `texture_pool` and writes the `image_view` and `sampler` into it
+pub fn allocate_texture_set(
+    logical_device: &ash::Device,
+    texture_pool: &vk::DescriptorPool,
+    texture_set_layout: &vk::DescriptorSetLayout,
+    image_view: &vk::ImageView,
+    sampler: &vk::Sampler,
+) -> Result<vk::DescriptorSet> {
+    let set = {
+        let allocate_info = vk::DescriptorSetAllocateInfo::builder()
+            .descriptor_pool(*texture_pool)
+            .set_layouts(std::slice::from_ref(texture_set_layout));
+
+        unsafe { logical_device.allocate_descriptor_sets(&allocate_info) }?
+            .into_iter()
+            .next()
+            .context("Texture Descriptor Set: allocation returned no set")?
+    };
+
+    write_texture_set(logical_device, set, image_view, sampler);
+
+    Ok(set)
+}
+
+/// Writes the `image_view` and `sampler` into the texture descriptor `set`
+pub fn write_texture_set(
+    logical_device: &ash::Device,
+    set: vk::DescriptorSet,
+    image_view: &vk::ImageView,
+    sampler: &vk::Sampler,
+) {
+    let image_info = vk::DescriptorImageInfo::builder()
+        .image_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
+        .image_view(*image_view)
+        .sampler(*sampler);
+
+    let descriptor_write = vk::WriteDescriptorSet::builder()
+        .dst_set(set)
+        .dst_binding(0)
+        .dst_array_element(0)
+        .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+        .image_info(std::slice::from_ref(&image_info));
+
+    unsafe { logical_device.update_descriptor_sets(std::slice::from_ref(&descriptor_write), &[]) };
+}
@@ -0,0 +1,431 @@
+//! Vulkan setup of the machine, to be attached to bug reports
+//!
+//! ```no_run
+//! match lavapond::diagnostics::report() {
+//!     Ok(report) => println!("{}", report),
+//!     Err(error) => println!("Vulkan is not available: {}", error),
+//! }
+//! ```
+
+// std
+use std::ffi::CStr;
+use std::fmt;
+
+// extern
+use anyhow::Result;
+use ash::{extensions::khr, vk};
+use raw_window_handle::{HasRawDisplayHandle, HasRawWindowHandle};
+
+// intern
+use crate::extensions::SurfaceExtension;
+use crate::{select_physical_device, GpuPreference, RendererResult};
+
+//==================================================
+//=== Report
+//==================================================
+
+/// Instance layers and extensions, physical devices and the device the renderer would pick
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Report {
+    /// Highest Vulkan version of the instance, eg. `1.3.250`
+    pub instance_version: String,
+    pub instance_layers: Vec<String>,
+    pub instance_extensions: Vec<String>,
+    pub devices: Vec<DeviceReport>,
+    /// Name of the device picked by the [`GpuPreference`], [`None`] when none is suitable
+    pub selected_device: Option<String>,
+}
+
+/// Physical device of a [`Report`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DeviceReport {
+    pub name: String,
+    /// Type of the device, eg. `DISCRETE_GPU`
+    pub device_type: String,
+    pub api_version: String,
+    /// Driver version as reported, its encoding depends on the vendor
+    pub driver_version: u32,
+    pub vendor_id: u32,
+    pub device_id: u32,
+    pub extensions: Vec<String>,
+    pub queue_families: Vec<QueueFamilyReport>,
+    /// Surface formats as `FORMAT COLOR_SPACE`, empty without a window
+    pub surface_formats: Vec<String>,
+    /// Present modes, empty without a window
+    pub present_modes: Vec<String>,
+    /// Why the renderer can't use the device, empty when suitable
+    pub unsupported: Vec<String>,
+}
+
+/// Queue family of a [`DeviceReport`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QueueFamilyReport {
+    pub index: u32,
+    /// Supported operations, eg. `GRAPHICS | COMPUTE | TRANSFER`
+    pub flags: String,
+    pub queue_count: u32,
+    /// Presents to the surface of the window, [`None`] without a window
+    pub present_support: Option<bool>,
+}
+
+impl Report {
+    /// Device picked by the renderer
+    pub fn selected(&self) -> Option<&DeviceReport> {
+        let name = self.selected_device.as_ref()?;
+        self.devices.iter().find(|device| &device.name == name)
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Vulkan Instance: {}", self.instance_version)?;
+        writeln!(f, "Layers: {}", list(&self.instance_layers))?;
+        writeln!(f, "Extensions: {}", list(&self.instance_extensions))?;
+        writeln!(
+            f,
+            "Selected Device: {}",
+            self.selected_device.as_deref().unwrap_or("none")
+        )?;
+
+        for (index, device) in self.devices.iter().enumerate() {
+            writeln!(f)?;
+            writeln!(
+                f,
+                "Device {}: {} ({}, Vulkan {}, Driver {:#x}, {:04x}:{:04x})",
+                index,
+                device.name,
+                device.device_type,
+                device.api_version,
+                device.driver_version,
+                device.vendor_id,
+                device.device_id
+            )?;
+
+            match device.unsupported.is_empty() {
+                true => writeln!(f, "  Suitable")?,
+                false => writeln!(f, "  Unsupported: {}", device.unsupported.join(", "))?,
+            }
+
+            for queue_family in &device.queue_families {
+                let present = match queue_family.present_support {
+                    Some(true) => ", present",
+                    _ => "",
+                };
+                writeln!(
+                    f,
+                    "  Queue Family {}: {} x{}{}",
+                    queue_family.index, queue_family.flags, queue_family.queue_count, present
+                )?;
+            }
+
+            writeln!(f, "  Surface Formats: {}", list(&device.surface_formats))?;
+            writeln!(f, "  Present Modes: {}", list(&device.present_modes))?;
+            writeln!(f, "  Extensions: {}", list(&device.extensions))?;
+        }
+
+        Ok(())
+    }
+}
+
+fn list(names: &[String]) -> String {
+    match names.is_empty() {
+        true => "none".to_string(),
+        false => names.join(", "),
+    }
+}
+
+//==================================================
+//=== Queries
+//==================================================
+
+/// Creates a [`Report`] without a window, the surface formats and present modes stay empty
+///
+/// The selected device follows the default [`GpuPreference::Discrete`]
+pub fn report() -> RendererResult<Report> {
+    Ok(query(None::<&NoWindow>, &GpuPreference::Discrete)?)
+}
+
+/// Creates a [`Report`] including the surface of `window`, picking the device like a renderer
+/// built with `gpu_preference`
+pub fn report_for_window(
+    window: &(impl HasRawDisplayHandle + HasRawWindowHandle),
+    gpu_preference: &GpuPreference,
+) -> RendererResult<Report> {
+    Ok(query(Some(window), gpu_preference)?)
+}
+
+/// Stands in for the window type of a report without one
+enum NoWindow {}
+
+unsafe impl HasRawDisplayHandle for NoWindow {
+    fn raw_display_handle(&self) -> raw_window_handle::RawDisplayHandle {
+        match *self {}
+    }
+}
+
+unsafe impl HasRawWindowHandle for NoWindow {
+    fn raw_window_handle(&self) -> raw_window_handle::RawWindowHandle {
+        match *self {}
+    }
+}
+
+fn query(
+    window: Option<&(impl HasRawDisplayHandle + HasRawWindowHandle)>,
+    gpu_preference: &GpuPreference,
+) -> Result<Report> {
+    let entry = unsafe { ash::Entry::load() }?;
+
+    let instance_version = entry
+        .try_enumerate_instance_version()?
+        .unwrap_or(vk::API_VERSION_1_0);
+    let instance_layers = entry
+        .enumerate_instance_layer_properties()?
+        .iter()
+        .map(|lp| name(&lp.layer_name))
+        .collect::<Vec<_>>();
+    let instance_extensions = entry
+        .enumerate_instance_extension_properties(None)?
+        .iter()
+        .map(|ep| name(&ep.extension_name))
+        .collect::<Vec<_>>();
+
+    /* Instance */
+    let mut enabled_extension_names = Vec::new();
+    if let Some(window) = window {
+        enabled_extension_names.extend_from_slice(ash_window::enumerate_required_extensions(
+            window.raw_display_handle(),
+        )?);
+        enabled_extension_names.push(khr::Surface::name().as_ptr());
+    }
+
+    // Portability implementations are only listed when asked for, eg. MoltenVK
+    let portability_name = vk::KhrPortabilityEnumerationFn::name();
+    let create_flags = if instance_extensions
+        .iter()
+        .any(|extension| extension.as_bytes() == portability_name.to_bytes())
+    {
+        enabled_extension_names.push(portability_name.as_ptr());
+        vk::InstanceCreateFlags::ENUMERATE_PORTABILITY_KHR
+    } else {
+        vk::InstanceCreateFlags::empty()
+    };
+
+    let application_info = vk::ApplicationInfo::builder().api_version(instance_version);
+
+    let create_info = vk::InstanceCreateInfo::builder()
+        .application_info(&application_info)
+        .enabled_extension_names(&enabled_extension_names)
+        .flags(create_flags);
+
+    let instance = unsafe { entry.create_instance(&create_info, None) }?;
+
+    let surface_ext = match window {
+        Some(window) => match SurfaceExtension::new(&entry, &instance, window) {
+            Ok(surface_ext) => Some(surface_ext),
+            Err(error) => {
+                unsafe { instance.destroy_instance(None) };
+                return Err(error);
+            }
+        },
+        None => None,
+    };
+
+    let devices = query_devices(&instance, surface_ext.as_ref());
+
+    unsafe {
+        if let Some(surface_ext) = &surface_ext {
+            surface_ext
+                .loader
+                .destroy_surface(surface_ext.surface, None);
+        }
+        instance.destroy_instance(None);
+    }
+
+    let (devices, properties) = devices?.into_iter().unzip::<_, _, Vec<_>, Vec<_>>();
+    let suitable = devices
+        .iter()
+        .zip(&properties)
+        .filter(|(device, _)| device.unsupported.is_empty())
+        .collect::<Vec<_>>();
+    let selected_device =
+        select_physical_device(suitable, gpu_preference, |(_, properties)| *properties)
+            .map(|(device, _)| device.name.clone());
+
+    Ok(Report {
+        instance_version: version(instance_version),
+        instance_layers,
+        instance_extensions,
+        devices,
+        selected_device,
+    })
+}
+
+/// Describes the physical devices, checking them like the device selection of the renderer
+fn query_devices(
+    instance: &ash::Instance,
+    surface_ext: Option<&SurfaceExtension>,
+) -> Result<Vec<(DeviceReport, vk::PhysicalDeviceProperties)>> {
+    let mut devices = Vec::new();
+
+    for pd in unsafe { instance.enumerate_physical_devices() }? {
+        let properties = unsafe { instance.get_physical_device_properties(pd) };
+        let mut unsupported = Vec::new();
+
+        let extensions = unsafe { instance.enumerate_device_extension_properties(pd) }?
+            .iter()
+            .map(|ep| name(&ep.extension_name))
+            .collect::<Vec<_>>();
+        let swapchain_name = khr::Swapchain::name().to_string_lossy();
+        if !extensions
+            .iter()
+            .any(|extension| *extension == swapchain_name)
+        {
+            unsupported.push(swapchain_name.into_owned());
+        }
+
+        let queue_families = unsafe { instance.get_physical_device_queue_family_properties(pd) }
+            .iter()
+            .enumerate()
+            .map(|(index, qf)| QueueFamilyReport {
+                index: index as u32,
+                flags: format!("{:?}", qf.queue_flags),
+                queue_count: qf.queue_count,
+                present_support: surface_ext.map(|surface_ext| {
+                    unsafe {
+                        surface_ext.loader.get_physical_device_surface_support(
+                            pd,
+                            index as u32,
+                            surface_ext.surface,
+                        )
+                    }
+                    .unwrap_or(false)
+                }),
+            })
+            .collect::<Vec<_>>();
+
+        if !queue_families
+            .iter()
+            .any(|qf| qf.flags.contains("GRAPHICS"))
+        {
+            unsupported.push("graphics queue".to_string());
+        }
+        if surface_ext.is_some()
+            && !queue_families
+                .iter()
+                .any(|qf| qf.present_support == Some(true))
+        {
+            unsupported.push("present queue".to_string());
+        }
+
+        let (surface_formats, present_modes) = match surface_ext {
+            Some(surface_ext) => {
+                let surface_formats = unsafe {
+                    surface_ext
+                        .loader
+                        .get_physical_device_surface_formats(pd, surface_ext.surface)
+                }
+                .unwrap_or_default();
+                let present_modes = unsafe {
+                    surface_ext
+                        .loader
+                        .get_physical_device_surface_present_modes(pd, surface_ext.surface)
+                }
+                .unwrap_or_default();
+
+                if !surface_formats.iter().any(|sf| {
+                    crate::builder::surface_format(crate::ColorMode::Srgb, &[*sf]).is_some()
+                }) {
+                    unsupported.push("sRGB surface format".to_string());
+                }
+
+                (
+                    surface_formats
+                        .iter()
+                        .map(|sf| format!("{:?} {:?}", sf.format, sf.color_space))
+                        .collect(),
+                    present_modes
+                        .iter()
+                        .map(|present_mode| format!("{:?}", present_mode))
+                        .collect(),
+                )
+            }
+            None => (Vec::new(), Vec::new()),
+        };
+
+        devices.push((
+            DeviceReport {
+                name: name(&properties.device_name),
+                device_type: format!("{:?}", properties.device_type),
+                api_version: version(properties.api_version),
+                driver_version: properties.driver_version,
+                vendor_id: properties.vendor_id,
+                device_id: properties.device_id,
+                extensions,
+                queue_families,
+                surface_formats,
+                present_modes,
+                unsupported,
+            },
+            properties,
+        ));
+    }
+
+    Ok(devices)
+}
+
+/// Reads a fixed size, null terminated Vulkan name
+fn name(raw: &[std::os::raw::c_char]) -> String {
+    unsafe { CStr::from_ptr(raw.as_ptr()) }
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn version(version: u32) -> String {
+    format!(
+        "{}.{}.{}",
+        vk::api_version_major(version),
+        vk::api_version_minor(version),
+        vk::api_version_patch(version)
+    )
+}
+
+//==================================================
+//=== Unit Testing
+//==================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_display() {
+        let report = Report {
+            instance_version: version(vk::make_api_version(0, 1, 3, 250)),
+            instance_layers: Vec::new(),
+            instance_extensions: vec!["VK_KHR_surface".to_string()],
+            devices: vec![DeviceReport {
+                name: "Test GPU".to_string(),
+                device_type: format!("{:?}", vk::PhysicalDeviceType::DISCRETE_GPU),
+                api_version: version(vk::API_VERSION_1_3),
+                queue_families: vec![QueueFamilyReport {
+                    index: 0,
+                    flags: format!("{:?}", vk::QueueFlags::GRAPHICS),
+                    queue_count: 16,
+                    present_support: Some(true),
+                }],
+                ..DeviceReport::default()
+            }],
+            selected_device: Some("Test GPU".to_string()),
+        };
+
+        assert_eq!(report.selected().unwrap().name, "Test GPU");
+
+        let text = report.to_string();
+        assert!(text.starts_with("Vulkan Instance: 1.3.250\nLayers: none\n"));
+        assert!(text.contains("Device 0: Test GPU (DISCRETE_GPU, Vulkan 1.3.0"));
+        assert!(text.contains("  Suitable\n  Queue Family 0: GRAPHICS x16, present\n"));
+    }
+}
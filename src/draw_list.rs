@@ -0,0 +1,246 @@
+// extern
+use anyhow::{anyhow, Result};
+
+// intern
+use crate::resources::ObjectInstance;
+use crate::RendererResult;
+
+//==================================================
+//=== Draw List
+//==================================================
+
+/// Sizes of the pools the indices of a draw instance point into
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct DrawLimits {
+    pub objects: usize,
+    pub streamed_objects: usize,
+    pub textures: usize,
+    pub pipelines: usize,
+}
+
+impl DrawLimits {
+    /// Checks whether every index of the `draw_instance` points into its pool
+    pub fn validate(&self, draw_instance: &ObjectInstance) -> Result<()> {
+        let objects = match draw_instance.streamed {
+            true => self.streamed_objects,
+            false => self.objects,
+        };
+
+        if draw_instance.object.0 >= objects {
+            return Err(anyhow!(
+                "Draw List: Object index {} out of bounds for {} objects",
+                draw_instance.object.0,
+                objects
+            ));
+        }
+
+        if let Some(texture) = draw_instance
+            .texture
+            .filter(|texture| texture.0 >= self.textures)
+        {
+            return Err(anyhow!(
+                "Draw List: Texture index {} out of bounds for {} textures",
+                texture.0,
+                self.textures
+            ));
+        }
+
+        if let Some(pipeline) = draw_instance
+            .pipeline
+            .filter(|pipeline| pipeline.0 >= self.pipelines)
+        {
+            return Err(anyhow!(
+                "Draw List: Pipeline index {} out of bounds for {} pipelines",
+                pipeline.0,
+                self.pipelines
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Removes the instances of `draw_pool` pointing past their pools
+    ///
+    /// Gives back the error of the first removed instance with the number of removed ones
+    pub fn retain_valid(&self, draw_pool: &mut Vec<ObjectInstance>) -> Result<()> {
+        let mut first_error = None;
+        let mut dropped = 0;
+
+        draw_pool.retain(|draw_instance| match self.validate(draw_instance) {
+            Ok(()) => true,
+            Err(error) => {
+                dropped += 1;
+                first_error.get_or_insert(error);
+                false
+            }
+        });
+
+        match first_error {
+            Some(error) => Err(error.context(format!("Draw List: Dropped {} instances", dropped))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Instances drawn by the next draw request, see `Renderer::draw_list`
+///
+/// Instances are checked against the objects, textures and pipelines of the renderer when they
+/// are pushed, so a bad index fails here instead of panicking during the draw
+pub struct DrawList<'a> {
+    instances: &'a mut Vec<ObjectInstance>,
+    limits: DrawLimits,
+}
+
+impl<'a> DrawList<'a> {
+    pub(crate) fn new(instances: &'a mut Vec<ObjectInstance>, limits: DrawLimits) -> Self {
+        Self { instances, limits }
+    }
+
+    /// Pushes the `draw_instance` after checking its indices
+    pub fn push(&mut self, draw_instance: ObjectInstance) -> RendererResult<()> {
+        self.limits.validate(&draw_instance)?;
+        self.instances.push(draw_instance);
+
+        Ok(())
+    }
+
+    /// Pushes every instance of `draw_instances`, stops at the first invalid one
+    pub fn extend(
+        &mut self,
+        draw_instances: impl IntoIterator<Item = ObjectInstance>,
+    ) -> RendererResult<()> {
+        let draw_instances = draw_instances.into_iter();
+        self.instances.reserve(draw_instances.size_hint().0);
+
+        for draw_instance in draw_instances {
+            self.push(draw_instance)?;
+        }
+
+        Ok(())
+    }
+
+    /// Reserves room for `additional` instances, the capacity is kept across frames
+    pub fn reserve(&mut self, additional: usize) {
+        self.instances.reserve(additional);
+    }
+
+    /// Instances pushed so far in the order they were pushed
+    pub fn instances(&self) -> &[ObjectInstance] {
+        self.instances
+    }
+
+    pub fn len(&self) -> usize {
+        self.instances.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.instances.is_empty()
+    }
+
+    /// Removes every instance pushed in the current frame
+    pub fn clear(&mut self) {
+        self.instances.clear();
+    }
+
+    /// Gives back the instances without any checks, eg. to edit them in place
+    ///
+    /// Instances with bad indices are left out of the next draw request, which reports them
+    pub fn as_mut_vec_unchecked(&mut self) -> &mut Vec<ObjectInstance> {
+        self.instances
+    }
+}
+
+//==================================================
+//=== Unit Testing
+//==================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::{ObjectHandle, TextureHandle};
+
+    #[test]
+    fn test_draw_list() {
+        let mut instances = Vec::new();
+        let mut draw_list = DrawList::new(
+            &mut instances,
+            DrawLimits {
+                objects: 2,
+                streamed_objects: 1,
+                textures: 1,
+                pipelines: 0,
+            },
+        );
+
+        assert!(draw_list
+            .push(ObjectInstance {
+                object: ObjectHandle(1),
+                texture: Some(TextureHandle(0)),
+                ..ObjectInstance::default()
+            })
+            .is_ok());
+
+        // Indices past the end of their pools are rejected
+        assert!(draw_list
+            .push(ObjectInstance {
+                object: ObjectHandle(2),
+                ..ObjectInstance::default()
+            })
+            .is_err());
+        assert!(draw_list
+            .push(ObjectInstance {
+                object: ObjectHandle(1),
+                streamed: true,
+                ..ObjectInstance::default()
+            })
+            .is_err());
+        assert!(draw_list
+            .push(ObjectInstance {
+                texture: Some(TextureHandle(1)),
+                ..ObjectInstance::default()
+            })
+            .is_err());
+
+        // Stops at the first invalid instance, the ones before it are kept
+        let result = draw_list.extend([
+            ObjectInstance::default(),
+            ObjectInstance {
+                object: ObjectHandle(5),
+                ..ObjectInstance::default()
+            },
+            ObjectInstance::default(),
+        ]);
+        assert!(result.is_err());
+        assert_eq!(draw_list.len(), 2);
+    }
+
+    #[test]
+    fn test_retain_valid() {
+        let limits = DrawLimits {
+            objects: 2,
+            ..DrawLimits::default()
+        };
+        let stale = ObjectInstance {
+            object: ObjectHandle(7),
+            ..ObjectInstance::default()
+        };
+
+        // Stale handle pushed past the checks -> Dropped and reported
+        let mut draw_pool = vec![ObjectInstance::default(), stale.clone(), stale];
+        let error = limits.retain_valid(&mut draw_pool).unwrap_err();
+        assert_eq!(error.to_string(), "Draw List: Dropped 2 instances");
+        assert_eq!(draw_pool.len(), 1);
+
+        // The rest of the frame and the next one are drawn
+        assert!(draw_pool
+            .iter()
+            .all(|draw_instance| limits.validate(draw_instance).is_ok()));
+        draw_pool.clear();
+        draw_pool.push(ObjectInstance {
+            object: ObjectHandle(1),
+            ..ObjectInstance::default()
+        });
+        assert!(limits.retain_valid(&mut draw_pool).is_ok());
+        assert_eq!(draw_pool.len(), 1);
+    }
+}
@@ -0,0 +1,805 @@
+// std
+use std::collections::HashMap;
+
+// extern
+use anyhow::{anyhow, Context, Result};
+use ash::vk;
+use egui::epaint::{ImageData, Primitive, Vertex};
+use egui::{ClippedPrimitive, TextureId, TexturesDelta};
+#[cfg(feature = "egui-winit")]
+use winit::{event::WindowEvent, event_loop::EventLoopWindowTarget, window::Window};
+
+// intern
+use crate::allocator::MemoryAllocator;
+use crate::buffers::{FrameBuffer, StreamingBuffer, Texture, UploadQueues};
+use crate::descriptor;
+use crate::pipeline::{GraphicsPipeline, PipelineStates, RenderTarget, ShaderCode, StencilMode};
+#[cfg(feature = "egui-winit")]
+use crate::{Renderer, RendererResult};
+
+//==================================================
+//=== Egui Draw
+//==================================================
+
+/// Shaders of the egui pass, compiled by the build script
+const EMBEDDED_EGUI_VERT: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/egui.vert.spv"));
+const EMBEDDED_EGUI_FRAG: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/egui.frag.spv"));
+
+/// Push constant block of the egui shaders
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+struct EguiParams {
+    /// Size of the surface in points
+    screen_size: [f32; 2],
+    /// 1 when the target converts the linear colors to sRGB
+    srgb_target: u32,
+}
+
+/// Indexed draw of a single egui mesh
+#[derive(Debug, Clone, Copy)]
+struct EguiDraw {
+    texture_id: TextureId,
+    scissor: vk::Rect2D,
+    first_index: u32,
+    index_count: u32,
+    vertex_offset: i32,
+}
+
+/// Converts the egui `clip_rect` in points into a scissor in pixels clamped to `extent`,
+/// gives back None when nothing is visible
+fn clip_to_scissor(
+    clip_rect: egui::Rect,
+    pixels_per_point: f32,
+    extent: vk::Extent2D,
+) -> Option<vk::Rect2D> {
+    let min_x = (clip_rect.min.x * pixels_per_point).round().max(0.0) as u32;
+    let min_y = (clip_rect.min.y * pixels_per_point).round().max(0.0) as u32;
+    let max_x = ((clip_rect.max.x * pixels_per_point).round().max(0.0) as u32).min(extent.width);
+    let max_y = ((clip_rect.max.y * pixels_per_point).round().max(0.0) as u32).min(extent.height);
+
+    if min_x >= max_x || min_y >= max_y {
+        return None;
+    }
+
+    Some(vk::Rect2D {
+        offset: vk::Offset2D {
+            x: min_x as i32,
+            y: min_y as i32,
+        },
+        extent: vk::Extent2D {
+            width: max_x - min_x,
+            height: max_y - min_y,
+        },
+    })
+}
+
+/// Merges the meshes of the `primitives` into one vertex and index list, gives back the draws
+/// of the visible meshes
+///
+/// Paint callbacks are not supported and skipped
+fn batch_meshes(
+    primitives: &[ClippedPrimitive],
+    pixels_per_point: f32,
+    extent: vk::Extent2D,
+) -> (Vec<Vertex>, Vec<u32>, Vec<EguiDraw>) {
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+    let mut draws = Vec::new();
+
+    for clipped in primitives {
+        let Primitive::Mesh(mesh) = &clipped.primitive else {
+            continue;
+        };
+
+        if mesh.indices.is_empty() {
+            continue;
+        }
+
+        let Some(scissor) = clip_to_scissor(clipped.clip_rect, pixels_per_point, extent) else {
+            continue;
+        };
+
+        draws.push(EguiDraw {
+            texture_id: mesh.texture_id,
+            scissor,
+            first_index: indices.len() as u32,
+            index_count: mesh.indices.len() as u32,
+            vertex_offset: vertices.len() as i32,
+        });
+
+        vertices.extend_from_slice(&mesh.vertices);
+        indices.extend_from_slice(&mesh.indices);
+    }
+
+    (vertices, indices, draws)
+}
+
+//==================================================
+//=== Egui Texture
+//==================================================
+
+/// Texture of egui, the pixels are kept to apply the partial updates
+struct EguiTexture {
+    texture: Texture,
+    set: vk::DescriptorSet,
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+/// RGBA8 pixels of an egui image
+fn image_pixels(image: &ImageData) -> Vec<u8> {
+    match image {
+        ImageData::Color(image) => image.pixels.iter().flat_map(|c| c.to_array()).collect(),
+        ImageData::Font(image) => image
+            .srgba_pixels(None)
+            .flat_map(|c| c.to_array())
+            .collect(),
+    }
+}
+
+/// Copies the `region_width` wide RGBA8 `region` into the `width` wide RGBA8 `pixels` at `pos`
+fn write_region(
+    pixels: &mut [u8],
+    width: usize,
+    pos: [usize; 2],
+    region_width: usize,
+    region: &[u8],
+) -> Result<()> {
+    let height = pixels.len() / (width * 4);
+    let region_height = region.len() / (region_width * 4).max(1);
+
+    if pos[0] + region_width > width || pos[1] + region_height > height {
+        return Err(anyhow!(
+            "Egui: Region of {}x{} at {:?} is outside of the {}x{} texture",
+            region_width,
+            region_height,
+            pos,
+            width,
+            height
+        ));
+    }
+
+    for (row, region_row) in region.chunks_exact(region_width * 4).enumerate() {
+        let start = ((pos[1] + row) * width + pos[0]) * 4;
+        pixels[start..start + region_row.len()].copy_from_slice(region_row);
+    }
+
+    Ok(())
+}
+
+//==================================================
+//=== Egui Renderer
+//==================================================
+
+/// Draws the output of egui in its own pass over the finished frame, after the scene and the
+/// post-processing
+///
+/// The frame is handed over with [`EguiRenderer::set_frame`] and drawn by the next draw request,
+/// textures are sampled linearly whatever their egui options
+pub struct EguiRenderer {
+    render_pass: vk::RenderPass,
+    frame_buffers: Vec<vk::Framebuffer>,
+    pipeline: GraphicsPipeline,
+    texture_set_layout: vk::DescriptorSetLayout,
+    set_pool: vk::DescriptorPool,
+    textures: HashMap<TextureId, EguiTexture>,
+    /// Vertices and indices of every frame in flight
+    mesh_buffers: Vec<StreamingBuffer>,
+    srgb_target: bool,
+    primitives: Vec<ClippedPrimitive>,
+    textures_delta: TexturesDelta,
+    pixels_per_point: f32,
+    /// Textures freed once the frame using them last is done
+    pending_free: Vec<TextureId>,
+    draws: Vec<EguiDraw>,
+    index_offset: u64,
+}
+
+impl EguiRenderer {
+    /// Most textures alive at the same time, the font atlas counts as one
+    pub const MAX_TEXTURES: u32 = 64;
+
+    /// Initial size of the mesh buffers in bytes, grown when a frame does not fit
+    const MESH_BUFFER_SIZE: u64 = 1 << 16;
+
+    /// Creates a new [`EguiRenderer`] drawing into the swapchain `image_views`
+    pub(crate) fn new(
+        logical_device: &ash::Device,
+        allocator: &mut MemoryAllocator,
+        texture_set_layout: vk::DescriptorSetLayout,
+        format: vk::Format,
+        extent: vk::Extent2D,
+        image_views: &Vec<vk::ImageView>,
+        max_frames_inflight: usize,
+    ) -> Result<Self> {
+        let render_pass = create_overlay_render_pass(logical_device, format)?;
+
+        let frame_buffers = FrameBuffer::new(
+            logical_device,
+            image_views,
+            &render_pass,
+            extent.width,
+            extent.height,
+            None,
+            None,
+        )?
+        .buffers;
+
+        let vertex_attributes = [
+            vk::VertexInputAttributeDescription::builder()
+                .binding(0)
+                .location(0)
+                .format(vk::Format::R32G32_SFLOAT)
+                .offset(0)
+                .build(),
+            vk::VertexInputAttributeDescription::builder()
+                .binding(0)
+                .location(1)
+                .format(vk::Format::R32G32_SFLOAT)
+                .offset(std::mem::size_of::<[f32; 2]>() as u32)
+                .build(),
+            vk::VertexInputAttributeDescription::builder()
+                .binding(0)
+                .location(2)
+                .format(vk::Format::R8G8B8A8_UNORM)
+                .offset(std::mem::size_of::<[f32; 4]>() as u32)
+                .build(),
+        ];
+
+        // Premultiplied Alpha
+        let blend = vk::PipelineColorBlendAttachmentState::builder()
+            .color_write_mask(vk::ColorComponentFlags::RGBA)
+            .blend_enable(true)
+            .src_color_blend_factor(vk::BlendFactor::ONE)
+            .dst_color_blend_factor(vk::BlendFactor::ONE_MINUS_SRC_ALPHA)
+            .color_blend_op(vk::BlendOp::ADD)
+            .src_alpha_blend_factor(vk::BlendFactor::ONE_MINUS_DST_ALPHA)
+            .dst_alpha_blend_factor(vk::BlendFactor::ONE)
+            .alpha_blend_op(vk::BlendOp::ADD)
+            .build();
+
+        let push_constant_range = vk::PushConstantRange::builder()
+            .stage_flags(vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT)
+            .size(std::mem::size_of::<EguiParams>() as u32)
+            .build();
+
+        let shaders = ShaderCode {
+            vert: ShaderCode::from_bytes(EMBEDDED_EGUI_VERT)?,
+            frag: ShaderCode::from_bytes(EMBEDDED_EGUI_FRAG)?,
+        };
+
+        // Egui meshes have no consistent winding
+        let pipeline = GraphicsPipeline::with_states(
+            logical_device,
+            &RenderTarget::RenderPass(render_pass),
+            std::slice::from_ref(&texture_set_layout),
+            std::slice::from_ref(&push_constant_range),
+            &shaders,
+            &PipelineStates {
+                viewport: vk::Viewport::builder()
+                    .width(extent.width as f32)
+                    .height(extent.height as f32)
+                    .max_depth(1.0)
+                    .build(),
+                scissor: vk::Rect2D::builder().extent(extent).build(),
+                vertex_stride: std::mem::size_of::<Vertex>() as u32,
+                vertex_attributes: &vertex_attributes,
+                blend,
+                cull_mode: vk::CullModeFlags::NONE,
+                samples: vk::SampleCountFlags::TYPE_1,
+                polygon_mode: vk::PolygonMode::FILL,
+                topology: vk::PrimitiveTopology::TRIANGLE_LIST,
+                stencil: StencilMode::Test,
+            },
+        )?;
+
+        // Freed textures give back their sets
+        let set_pool = {
+            let pool_size = vk::DescriptorPoolSize::builder()
+                .ty(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+                .descriptor_count(Self::MAX_TEXTURES);
+
+            let create_info = vk::DescriptorPoolCreateInfo::builder()
+                .flags(vk::DescriptorPoolCreateFlags::FREE_DESCRIPTOR_SET)
+                .pool_sizes(std::slice::from_ref(&pool_size))
+                .max_sets(Self::MAX_TEXTURES);
+
+            unsafe { logical_device.create_descriptor_pool(&create_info, None) }?
+        };
+
+        let mut mesh_buffers = Vec::with_capacity(max_frames_inflight);
+        for _ in 0..max_frames_inflight {
+            mesh_buffers.push(StreamingBuffer::new(
+                logical_device,
+                allocator,
+                Self::MESH_BUFFER_SIZE,
+            )?);
+        }
+
+        let srgb_target = matches!(
+            format,
+            vk::Format::B8G8R8A8_SRGB
+                | vk::Format::R8G8B8A8_SRGB
+                | vk::Format::A8B8G8R8_SRGB_PACK32
+        );
+
+        Ok(Self {
+            render_pass,
+            frame_buffers,
+            pipeline,
+            texture_set_layout,
+            set_pool,
+            textures: HashMap::new(),
+            mesh_buffers,
+            srgb_target,
+            primitives: Vec::new(),
+            textures_delta: TexturesDelta::default(),
+            pixels_per_point: 1.0,
+            pending_free: Vec::new(),
+            draws: Vec::new(),
+            index_offset: 0,
+        })
+    }
+
+    /// Hands over the tessellated `primitives` and the texture changes of an egui frame,
+    /// drawn by the next draw request
+    ///
+    /// The texture changes of frames never drawn are kept for the next one
+    pub fn set_frame(
+        &mut self,
+        primitives: Vec<ClippedPrimitive>,
+        textures_delta: TexturesDelta,
+        pixels_per_point: f32,
+    ) {
+        self.primitives = primitives;
+        self.textures_delta.append(textures_delta);
+        self.pixels_per_point = pixels_per_point;
+    }
+
+    /// Number of textures uploaded for egui
+    pub fn texture_count(&self) -> usize {
+        self.textures.len()
+    }
+
+    /// Uploads the texture changes and the meshes of the frame, the mesh buffer of
+    /// `current_frame` must not be in use by the GPU
+    ///
+    /// Changing or freeing textures waits for the device, which only happens when egui
+    /// adds glyphs to its font atlas or an image is replaced
+    pub(crate) fn prepare(
+        &mut self,
+        logical_device: &ash::Device,
+        allocator: &mut MemoryAllocator,
+        queues: &UploadQueues,
+        current_frame: usize,
+        extent: vk::Extent2D,
+    ) -> Result<()> {
+        let textures_delta = std::mem::take(&mut self.textures_delta);
+
+        let in_use = !self.pending_free.is_empty()
+            || textures_delta
+                .set
+                .iter()
+                .any(|(id, _)| self.textures.contains_key(id));
+        if in_use {
+            unsafe { logical_device.device_wait_idle() }?;
+        }
+
+        for id in std::mem::take(&mut self.pending_free) {
+            if let Some(texture) = self.textures.remove(&id) {
+                texture.destroy(logical_device, allocator, self.set_pool)?;
+            }
+        }
+
+        for (id, delta) in &textures_delta.set {
+            let [region_width, region_height] = delta.image.size();
+            let region = image_pixels(&delta.image);
+
+            match (self.textures.get_mut(id), delta.pos) {
+                // Partial Update -> Written into the kept pixels, the whole texture is uploaded
+                (Some(texture), Some(pos)) => {
+                    write_region(
+                        &mut texture.pixels,
+                        texture.width,
+                        pos,
+                        region_width,
+                        &region,
+                    )?;
+                    texture.texture.update(
+                        logical_device,
+                        allocator,
+                        queues,
+                        texture.width as u32,
+                        texture.height as u32,
+                        &texture.pixels,
+                    )?;
+                }
+                (None, Some(_)) => {
+                    return Err(anyhow!("Egui: Partial update of unknown texture {:?}", id));
+                }
+                // Whole Image -> New texture, a replaced one is destroyed
+                (_, None) => {
+                    if self.textures.len() >= Self::MAX_TEXTURES as usize
+                        && !self.textures.contains_key(id)
+                    {
+                        return Err(anyhow!(
+                            "Egui: Texture limit of {} reached",
+                            Self::MAX_TEXTURES
+                        ));
+                    }
+
+                    let texture = Texture::new(
+                        logical_device,
+                        allocator,
+                        queues,
+                        region_width as u32,
+                        region_height as u32,
+                        &region,
+                    )?;
+
+                    let set = descriptor::allocate_texture_set(
+                        logical_device,
+                        &self.set_pool,
+                        &self.texture_set_layout,
+                        &texture.image_view,
+                        &texture.sampler,
+                    )?;
+
+                    let texture = EguiTexture {
+                        texture,
+                        set,
+                        width: region_width,
+                        height: region_height,
+                        pixels: region,
+                    };
+
+                    if let Some(replaced) = self.textures.insert(*id, texture) {
+                        replaced.destroy(logical_device, allocator, self.set_pool)?;
+                    }
+                }
+            }
+        }
+
+        // Freed after the frame drawn with them
+        self.pending_free = textures_delta.free;
+
+        let (vertices, indices, draws) =
+            batch_meshes(&self.primitives, self.pixels_per_point, extent);
+
+        let mesh_size = (std::mem::size_of_val(vertices.as_slice())
+            + std::mem::size_of_val(indices.as_slice())) as u64;
+
+        let mesh_buffer = self
+            .mesh_buffers
+            .get_mut(current_frame)
+            .context("Egui Mesh Buffer: Index out of bounds")?;
+        if mesh_size > mesh_buffer.capacity {
+            let new_mesh_buffer =
+                StreamingBuffer::new(logical_device, allocator, mesh_size.next_power_of_two())?;
+
+            std::mem::replace(mesh_buffer, new_mesh_buffer).destroy(logical_device, allocator);
+        }
+
+        self.index_offset = self.mesh_buffers[current_frame].write(&vertices, &indices)?;
+        self.draws = draws;
+
+        Ok(())
+    }
+
+    /// Records the egui pass over the swapchain image of `image_index`, which is in the
+    /// present layout after the scene
+    pub(crate) fn record(
+        &self,
+        logical_device: &ash::Device,
+        command_buffer: vk::CommandBuffer,
+        image_index: usize,
+        current_frame: usize,
+        extent: vk::Extent2D,
+    ) -> Result<()> {
+        if self.draws.is_empty() {
+            return Ok(());
+        }
+
+        let frame_buffer = *self
+            .frame_buffers
+            .get(image_index)
+            .context("Egui Frame Buffer: Index out of bounds")?;
+        let mesh_buffer = self.mesh_buffers[current_frame].buffer;
+
+        let render_pass_begin = vk::RenderPassBeginInfo::builder()
+            .render_pass(self.render_pass)
+            .framebuffer(frame_buffer)
+            .render_area(vk::Rect2D::builder().extent(extent).build());
+
+        let viewport = vk::Viewport::builder()
+            .width(extent.width as f32)
+            .height(extent.height as f32)
+            .max_depth(1.0)
+            .build();
+
+        let egui_params = EguiParams {
+            screen_size: [
+                extent.width as f32 / self.pixels_per_point,
+                extent.height as f32 / self.pixels_per_point,
+            ],
+            srgb_target: self.srgb_target as u32,
+        };
+
+        unsafe {
+            logical_device.cmd_begin_render_pass(
+                command_buffer,
+                &render_pass_begin,
+                vk::SubpassContents::INLINE,
+            );
+
+            logical_device.cmd_bind_pipeline(
+                command_buffer,
+                vk::PipelineBindPoint::GRAPHICS,
+                self.pipeline.pipeline,
+            );
+
+            logical_device.cmd_set_viewport(command_buffer, 0, std::slice::from_ref(&viewport));
+
+            logical_device.cmd_bind_vertex_buffers(command_buffer, 0, &[mesh_buffer], &[0]);
+            logical_device.cmd_bind_index_buffer(
+                command_buffer,
+                mesh_buffer,
+                self.index_offset,
+                vk::IndexType::UINT32,
+            );
+
+            logical_device.cmd_push_constants(
+                command_buffer,
+                self.pipeline.layout,
+                vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT,
+                0,
+                std::slice::from_raw_parts(
+                    (&egui_params as *const EguiParams).cast::<u8>(),
+                    std::mem::size_of::<EguiParams>(),
+                ),
+            );
+
+            for draw in &self.draws {
+                // Texture freed or never uploaded -> No Draw
+                let Some(texture) = self.textures.get(&draw.texture_id) else {
+                    continue;
+                };
+
+                logical_device.cmd_set_scissor(
+                    command_buffer,
+                    0,
+                    std::slice::from_ref(&draw.scissor),
+                );
+
+                logical_device.cmd_bind_descriptor_sets(
+                    command_buffer,
+                    vk::PipelineBindPoint::GRAPHICS,
+                    self.pipeline.layout,
+                    0,
+                    std::slice::from_ref(&texture.set),
+                    &[],
+                );
+
+                logical_device.cmd_draw_indexed(
+                    command_buffer,
+                    draw.index_count,
+                    1,
+                    draw.first_index,
+                    draw.vertex_offset,
+                    0,
+                );
+            }
+
+            logical_device.cmd_end_render_pass(command_buffer);
+        }
+
+        Ok(())
+    }
+
+    /// Creates the framebuffers for the new swapchain `image_views`, gives back the old ones
+    /// to be destroyed with their swapchain
+    pub(crate) fn recreate(
+        &mut self,
+        logical_device: &ash::Device,
+        extent: vk::Extent2D,
+        image_views: &Vec<vk::ImageView>,
+    ) -> Result<Vec<vk::Framebuffer>> {
+        let frame_buffers = FrameBuffer::new(
+            logical_device,
+            image_views,
+            &self.render_pass,
+            extent.width,
+            extent.height,
+            None,
+            None,
+        )?
+        .buffers;
+
+        Ok(std::mem::replace(&mut self.frame_buffers, frame_buffers))
+    }
+
+    /// Destroys every resource, the device must not use them anymore
+    pub(crate) fn destroy(
+        &mut self,
+        logical_device: &ash::Device,
+        allocator: &mut MemoryAllocator,
+    ) {
+        self.textures.drain().for_each(|(_, texture)| {
+            texture.texture.destroy(logical_device, allocator);
+        });
+
+        self.mesh_buffers
+            .drain(..)
+            .for_each(|mesh_buffer| mesh_buffer.destroy(logical_device, allocator));
+
+        self.pipeline.destroy(logical_device);
+
+        unsafe {
+            self.frame_buffers
+                .drain(..)
+                .for_each(|fb| logical_device.destroy_framebuffer(fb, None));
+            logical_device.destroy_descriptor_pool(self.set_pool, None);
+            logical_device.destroy_render_pass(self.render_pass, None);
+        }
+    }
+}
+
+impl EguiTexture {
+    fn destroy(
+        self,
+        logical_device: &ash::Device,
+        allocator: &mut MemoryAllocator,
+        set_pool: vk::DescriptorPool,
+    ) -> Result<()> {
+        unsafe { logical_device.free_descriptor_sets(set_pool, std::slice::from_ref(&self.set)) }?;
+        self.texture.destroy(logical_device, allocator);
+
+        Ok(())
+    }
+}
+
+/// Creates the pass drawing over the presentable swapchain image, keeping its content
+fn create_overlay_render_pass(
+    logical_device: &ash::Device,
+    format: vk::Format,
+) -> Result<vk::RenderPass> {
+    let color_attachment = vk::AttachmentDescription::builder()
+        .format(format)
+        .samples(vk::SampleCountFlags::TYPE_1)
+        .load_op(vk::AttachmentLoadOp::LOAD)
+        .store_op(vk::AttachmentStoreOp::STORE)
+        .stencil_load_op(vk::AttachmentLoadOp::DONT_CARE)
+        .stencil_store_op(vk::AttachmentStoreOp::DONT_CARE)
+        .initial_layout(vk::ImageLayout::PRESENT_SRC_KHR)
+        .final_layout(vk::ImageLayout::PRESENT_SRC_KHR);
+
+    let color_attachment_ref = vk::AttachmentReference::builder()
+        .attachment(0)
+        .layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL);
+
+    let subpass = vk::SubpassDescription::builder()
+        .pipeline_bind_point(vk::PipelineBindPoint::GRAPHICS)
+        .color_attachments(std::slice::from_ref(&color_attachment_ref));
+
+    // Scene Pass -> Blended over only after its writes
+    let subpass_dependency = vk::SubpassDependency::builder()
+        .src_subpass(vk::SUBPASS_EXTERNAL)
+        .dst_subpass(0)
+        .src_stage_mask(vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT)
+        .dst_stage_mask(vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT)
+        .src_access_mask(vk::AccessFlags::COLOR_ATTACHMENT_WRITE)
+        .dst_access_mask(
+            vk::AccessFlags::COLOR_ATTACHMENT_READ | vk::AccessFlags::COLOR_ATTACHMENT_WRITE,
+        );
+
+    let create_info = vk::RenderPassCreateInfo::builder()
+        .attachments(std::slice::from_ref(&color_attachment))
+        .subpasses(std::slice::from_ref(&subpass))
+        .dependencies(std::slice::from_ref(&subpass_dependency));
+
+    Ok(unsafe { logical_device.create_render_pass(&create_info, None) }?)
+}
+
+//==================================================
+//=== Winit Integration
+//==================================================
+
+/// Egui context fed with the events of a winit window, its frames are drawn by the
+/// [`EguiRenderer`] of a [`Renderer`]
+#[cfg(feature = "egui-winit")]
+pub struct EguiWinit {
+    pub context: egui::Context,
+    state: egui_winit::State,
+}
+
+#[cfg(feature = "egui-winit")]
+impl EguiWinit {
+    /// Creates a new [`EguiWinit`] for the `window`
+    pub fn new<T>(event_loop: &EventLoopWindowTarget<T>, window: &Window) -> Self {
+        let mut state = egui_winit::State::new(event_loop);
+        state.set_pixels_per_point(window.scale_factor() as f32);
+
+        Self {
+            context: egui::Context::default(),
+            state,
+        }
+    }
+
+    /// Passes the window `event` to egui, gives back true when egui used it and the
+    /// application should ignore it
+    pub fn handle_event(&mut self, event: &WindowEvent) -> bool {
+        self.state.on_event(&self.context, event).consumed
+    }
+
+    /// Runs the `ui` of a frame and hands its output to the egui renderer of the `renderer`,
+    /// drawn by its next draw request
+    pub fn run(
+        &mut self,
+        window: &Window,
+        renderer: &mut Renderer,
+        ui: impl FnOnce(&egui::Context),
+    ) -> RendererResult<()> {
+        let raw_input = self.state.take_egui_input(window);
+        let full_output = self.context.run(raw_input, ui);
+
+        self.state
+            .handle_platform_output(window, &self.context, full_output.platform_output);
+
+        let primitives = self.context.tessellate(full_output.shapes);
+        renderer.egui()?.set_frame(
+            primitives,
+            full_output.textures_delta,
+            self.context.pixels_per_point(),
+        );
+
+        Ok(())
+    }
+}
+
+//==================================================
+//=== Unit Testing
+//==================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clip_to_scissor() {
+        let extent = vk::Extent2D {
+            width: 200,
+            height: 100,
+        };
+        let rect = |min: (f32, f32), max: (f32, f32)| {
+            egui::Rect::from_min_max(egui::pos2(min.0, min.1), egui::pos2(max.0, max.1))
+        };
+
+        // Points -> Pixels
+        let scissor = clip_to_scissor(rect((10.0, 5.0), (30.0, 25.0)), 2.0, extent).unwrap();
+        assert_eq!((scissor.offset.x, scissor.offset.y), (20, 10));
+        assert_eq!((scissor.extent.width, scissor.extent.height), (40, 40));
+
+        // Clamped to the surface
+        let scissor = clip_to_scissor(rect((-10.0, -10.0), (1e6, 1e6)), 1.0, extent).unwrap();
+        assert_eq!((scissor.offset.x, scissor.offset.y), (0, 0));
+        assert_eq!((scissor.extent.width, scissor.extent.height), (200, 100));
+
+        assert!(clip_to_scissor(rect((250.0, 0.0), (300.0, 50.0)), 1.0, extent).is_none());
+    }
+
+    #[test]
+    fn test_write_region() {
+        let mut pixels = vec![0u8; 4 * 3 * 4];
+
+        write_region(&mut pixels, 4, [1, 1], 2, &[7u8; 2 * 2 * 4]).unwrap();
+        assert_eq!(
+            pixels.iter().filter(|value| **value == 7).count(),
+            2 * 2 * 4
+        );
+        assert_eq!(pixels[(4 + 1) * 4], 7);
+        assert_eq!(pixels[(4 + 3) * 4], 0);
+
+        assert!(write_region(&mut pixels, 4, [3, 0], 2, &[7u8; 2 * 4]).is_err());
+    }
+}
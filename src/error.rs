@@ -0,0 +1,103 @@
+// std
+use std::path::PathBuf;
+
+// extern
+use ash::vk;
+
+//==================================================
+//=== Renderer Error
+//==================================================
+
+/// Result of the public [`crate::Renderer`] API
+pub type RendererResult<T> = std::result::Result<T, RendererError>;
+
+/// Error of the public [`crate::Renderer`] API, telling apart the failures a caller can react to
+#[derive(Debug, thiserror::Error)]
+pub enum RendererError {
+    /// None of the physical devices fits the renderer or the [`crate::GpuPreference`], see
+    /// [`crate::diagnostics::report_for_window`] for the details
+    #[error("Device Selection: {0}")]
+    DeviceSelection(String),
+    /// Surface of the window is no longer usable, the renderer has to be recreated
+    #[error("Surface lost")]
+    SurfaceLost,
+    /// Swapchain no longer matches the surface, eg. after a resize
+    #[error("Swapchain out of date")]
+    OutOfDate,
+    /// File of a model, texture, font or shader can't be read or parsed
+    #[error("Resource Load: Can't load {path}")]
+    ResourceLoad {
+        path: PathBuf,
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+    /// Any other failed Vulkan call
+    #[error("Vulkan: {0}")]
+    Vulkan(vk::Result),
+    /// Misuse of the API, eg. an unknown handle, or an internal failure
+    #[error(transparent)]
+    Other(Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl RendererError {
+    /// Creates a [`RendererError::ResourceLoad`] of the file at `path`
+    pub(crate) fn resource_load(path: impl Into<PathBuf>, error: anyhow::Error) -> Self {
+        Self::ResourceLoad {
+            path: path.into(),
+            source: error.into(),
+        }
+    }
+}
+
+impl From<vk::Result> for RendererError {
+    fn from(result: vk::Result) -> Self {
+        match result {
+            vk::Result::ERROR_SURFACE_LOST_KHR => Self::SurfaceLost,
+            vk::Result::ERROR_OUT_OF_DATE_KHR => Self::OutOfDate,
+            result => Self::Vulkan(result),
+        }
+    }
+}
+
+/// Keeps the typed errors raised inside the internal `anyhow` code
+impl From<anyhow::Error> for RendererError {
+    fn from(error: anyhow::Error) -> Self {
+        let error = match error.downcast::<RendererError>() {
+            Ok(error) => return error,
+            Err(error) => error,
+        };
+
+        match error.downcast::<vk::Result>() {
+            Ok(result) => result.into(),
+            Err(error) => Self::Other(error.into()),
+        }
+    }
+}
+
+//==================================================
+//=== Unit Testing
+//==================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_renderer_error_from_anyhow() {
+        // Typed errors survive the internal anyhow code
+        let error = anyhow::Error::new(RendererError::DeviceSelection("none".to_string()));
+        assert!(matches!(
+            RendererError::from(error),
+            RendererError::DeviceSelection(_)
+        ));
+
+        let error = anyhow::Error::new(vk::Result::ERROR_SURFACE_LOST_KHR);
+        assert!(matches!(
+            RendererError::from(error),
+            RendererError::SurfaceLost
+        ));
+
+        let error = RendererError::from(anyhow::anyhow!("Font: Unknown font handle"));
+        assert_eq!(error.to_string(), "Font: Unknown font handle");
+    }
+}
@@ -1,5 +1,5 @@
 // std
-use std::{borrow::Cow, ffi::CStr};
+use std::{borrow::Cow, ffi::CStr, sync::Mutex};
 
 // extern
 use anyhow::Result;
@@ -8,7 +8,9 @@ use ash::{
     vk,
 };
 use raw_window_handle::{HasRawDisplayHandle, HasRawWindowHandle};
-use winit::window;
+
+// intern
+use crate::builder;
 
 //==================================================
 //=== Debug Messenger
@@ -20,8 +22,14 @@ pub struct DebugExtension {
 }
 
 impl DebugExtension {
-    /// Creates a new [`DebugExtension`]
-    pub fn new(entry: &ash::Entry, instance: &ash::Instance) -> Result<Self> {
+    /// Creates a new [`DebugExtension`] which routes the messages into `sink`
+    ///
+    /// `sink` must outlive the messenger
+    pub fn new(
+        entry: &ash::Entry,
+        instance: &ash::Instance,
+        sink: &Mutex<DebugSink>,
+    ) -> Result<Self> {
         let loader = ext::DebugUtils::new(entry, instance);
 
         let messenger = {
@@ -29,14 +37,16 @@ impl DebugExtension {
                 .message_severity(
                     vk::DebugUtilsMessageSeverityFlagsEXT::ERROR
                         | vk::DebugUtilsMessageSeverityFlagsEXT::WARNING
-                        | vk::DebugUtilsMessageSeverityFlagsEXT::INFO,
+                        | vk::DebugUtilsMessageSeverityFlagsEXT::INFO
+                        | vk::DebugUtilsMessageSeverityFlagsEXT::VERBOSE,
                 )
                 .message_type(
                     vk::DebugUtilsMessageTypeFlagsEXT::GENERAL
                         | vk::DebugUtilsMessageTypeFlagsEXT::VALIDATION
                         | vk::DebugUtilsMessageTypeFlagsEXT::PERFORMANCE,
                 )
-                .pfn_user_callback(Some(vulkan_debug_callback));
+                .pfn_user_callback(Some(vulkan_debug_callback))
+                .user_data(sink as *const Mutex<DebugSink> as *mut std::os::raw::c_void);
 
             unsafe { loader.create_debug_utils_messenger(&debug_info, None) }?
         };
@@ -45,13 +55,106 @@ impl DebugExtension {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DebugSeverity {
+    Verbose,
+    Info,
+    Warning,
+    Error,
+}
+
+impl DebugSeverity {
+    fn from_vk(severity: vk::DebugUtilsMessageSeverityFlagsEXT) -> Self {
+        if severity.contains(vk::DebugUtilsMessageSeverityFlagsEXT::ERROR) {
+            DebugSeverity::Error
+        } else if severity.contains(vk::DebugUtilsMessageSeverityFlagsEXT::WARNING) {
+            DebugSeverity::Warning
+        } else if severity.contains(vk::DebugUtilsMessageSeverityFlagsEXT::INFO) {
+            DebugSeverity::Info
+        } else {
+            DebugSeverity::Verbose
+        }
+    }
+}
+
+/// Message of the validation layers
+#[derive(Debug, Clone)]
+pub struct DebugMessage {
+    pub severity: DebugSeverity,
+    /// General, validation or performance message
+    pub message_type: String,
+    pub id_name: String,
+    pub id_number: i32,
+    pub message: String,
+}
+
+/// Receiver of the [`DebugMessage`]s at or above `min_severity`
+pub struct DebugSink {
+    pub min_severity: DebugSeverity,
+    pub callback: Box<dyn Fn(&DebugMessage) + Send + Sync>,
+}
+
+impl Default for DebugSink {
+    /// Prints the messages to stdout, or forwards them to the `log` crate with the `log` feature
+    fn default() -> Self {
+        Self {
+            min_severity: DebugSeverity::Info,
+            callback: Box::new(default_debug_sink),
+        }
+    }
+}
+
+#[cfg(not(feature = "log"))]
+fn default_debug_sink(message: &DebugMessage) {
+    println!(
+        "{:?}:\n{} [{} ({})] : {}\n",
+        message.severity, message.message_type, message.id_name, message.id_number, message.message
+    );
+}
+
+#[cfg(feature = "log")]
+fn default_debug_sink(message: &DebugMessage) {
+    let level = match message.severity {
+        DebugSeverity::Verbose => log::Level::Trace,
+        DebugSeverity::Info => log::Level::Info,
+        DebugSeverity::Warning => log::Level::Warn,
+        DebugSeverity::Error => log::Level::Error,
+    };
+
+    log::log!(
+        target: "lavapond::vulkan",
+        level,
+        "{} [{} ({})] : {}",
+        message.message_type,
+        message.id_name,
+        message.id_number,
+        message.message
+    );
+}
+
 /// Callback function for debug messenger
+///
+/// `user_data` points to the [`DebugSink`] of the renderer
 unsafe extern "system" fn vulkan_debug_callback(
     message_severity: vk::DebugUtilsMessageSeverityFlagsEXT,
     message_type: vk::DebugUtilsMessageTypeFlagsEXT,
     p_callback_data: *const vk::DebugUtilsMessengerCallbackDataEXT,
-    _user_data: *mut std::os::raw::c_void,
+    user_data: *mut std::os::raw::c_void,
 ) -> vk::Bool32 {
+    let severity = DebugSeverity::from_vk(message_severity);
+
+    let Some(sink) = (user_data as *const Mutex<DebugSink>).as_ref() else {
+        return vk::FALSE;
+    };
+
+    let Ok(sink) = sink.lock() else {
+        return vk::FALSE;
+    };
+
+    if severity < sink.min_severity {
+        return vk::FALSE;
+    }
+
     let callback_data = *p_callback_data;
     let message_id_number = callback_data.message_id_number;
 
@@ -67,9 +170,13 @@ unsafe extern "system" fn vulkan_debug_callback(
         CStr::from_ptr(callback_data.p_message).to_string_lossy()
     };
 
-    println!(
-        "{message_severity:?}:\n{message_type:?} [{message_id_name} ({message_id_number})] : {message}\n",
-    );
+    (sink.callback)(&DebugMessage {
+        severity,
+        message_type: format!("{message_type:?}"),
+        id_name: message_id_name.to_string(),
+        id_number: message_id_number,
+        message: message.to_string(),
+    });
 
     vk::FALSE
 }
@@ -84,11 +191,11 @@ pub struct SurfaceExtension {
 }
 
 impl SurfaceExtension {
-    /// Creates a new [`SurfaceExtension`]
+    /// Creates a new [`SurfaceExtension`] for the native `window`
     pub fn new(
         entry: &ash::Entry,
         instance: &ash::Instance,
-        window: &window::Window,
+        window: &(impl HasRawDisplayHandle + HasRawWindowHandle),
     ) -> Result<Self> {
         let loader = khr::Surface::new(&entry, &instance);
 
@@ -113,72 +220,70 @@ impl SurfaceExtension {
 pub struct SwapchainExtension {
     pub loader: khr::Swapchain,
     pub swapchain: vk::SwapchainKHR,
+    /// Size of the swapchain images, clamped to the surface capabilities
+    pub extent: vk::Extent2D,
 }
 
 impl SwapchainExtension {
-    /// Creates a new [`SwapchainExtension`]
+    /// Creates a new [`SwapchainExtension`] with images of `surface_extent` if the surface
+    /// leaves the size to the application
     pub fn new(
         entry: &ash::Entry,
         instance: &ash::Instance,
         logical_device: &ash::Device,
         physical_device: &vk::PhysicalDevice,
         surface_ext: &SurfaceExtension,
-        window: &winit::window::Window,
+        surface_extent: vk::Extent2D,
+        present_mode: vk::PresentModeKHR,
+        surface_format: vk::SurfaceFormatKHR,
+        queue_family_indices: &[u32; 2],
+        frames_inflight: usize,
     ) -> Result<Self> {
         let loader = khr::Swapchain::new_from_instance(&entry, &instance, logical_device.handle());
 
-        let swapchain = {
-            let (min_image_count, pre_transform) = {
-                let caps = unsafe {
-                    surface_ext.loader.get_physical_device_surface_capabilities(
-                        *physical_device,
-                        surface_ext.surface,
-                    )
-                }?;
-                let mut count = caps.min_image_count + 1;
-
-                if caps.max_image_count > 0 && count > caps.max_image_count {
-                    count = caps.max_image_count;
-                }
-
-                (count, caps.current_transform)
-            };
+        let caps = unsafe {
+            surface_ext
+                .loader
+                .get_physical_device_surface_capabilities(*physical_device, surface_ext.surface)
+        }?;
 
-            let image_extent = vk::Extent2D {
-                width: window.inner_size().width,
-                height: window.inner_size().height,
-            };
+        let extent = builder::swapchain_extent(&caps, surface_extent.width, surface_extent.height);
+
+        let swapchain = {
+            let min_image_count = builder::swapchain_image_count(&caps, frames_inflight);
 
             // TODO! -> This is too strict/error prone right now, better to supplement with queried data
             // TODO! -> Check for defaults
             let create_info = vk::SwapchainCreateInfoKHR::builder()
                 .surface(surface_ext.surface)
                 .min_image_count(min_image_count)
-                .image_format(vk::Format::B8G8R8A8_SRGB)
-                .image_color_space(vk::ColorSpaceKHR::SRGB_NONLINEAR)
-                .image_extent(image_extent)
+                .image_format(surface_format.format)
+                .image_color_space(surface_format.color_space)
+                .image_extent(extent)
                 .image_array_layers(1)
-                .image_usage(vk::ImageUsageFlags::COLOR_ATTACHMENT)
-                .pre_transform(pre_transform)
+                .image_usage(builder::swapchain_usage(&caps))
+                .pre_transform(caps.current_transform)
                 .composite_alpha(vk::CompositeAlphaFlagsKHR::OPAQUE)
-                .present_mode(vk::PresentModeKHR::MAILBOX)
-                .image_sharing_mode(vk::SharingMode::EXCLUSIVE)
+                .present_mode(present_mode)
                 .clipped(true);
 
-            // TODO! -> Fix me!
-            // let create_info = if !queue_family_indices.eq_indicies()? {
-            //     create_info
-            //         .image_sharing_mode(vk::SharingMode::CONCURRENT)
-            //         .queue_family_indices(queue_family_indices.to_vec()?)
-            //     //.queue_family_indices(&queue_family_indices.to_vec()?.as_slice());
-            // } else {
-            //     create_info.image_sharing_mode(vk::SharingMode::EXCLUSIVE)
-            // };
+            // Images are used by both queue families when they are separate
+            let create_info = if queue_family_indices[0] != queue_family_indices[1] {
+                create_info
+                    .image_sharing_mode(vk::SharingMode::CONCURRENT)
+                    .queue_family_indices(queue_family_indices)
+            } else {
+                create_info.image_sharing_mode(vk::SharingMode::EXCLUSIVE)
+            };
 
             // TODO! -> STATUS_STACK_BUFFER_OVERRUN Error
             unsafe { loader.create_swapchain(&create_info, None) }?
         };
 
-        Ok(Self { loader, swapchain })
+        Ok(Self {
+            loader,
+            swapchain,
+            extent,
+        })
     }
 }
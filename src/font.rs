@@ -0,0 +1,404 @@
+// std
+use std::collections::HashMap;
+
+// extern
+extern crate nalgebra_glm as glm;
+use ab_glyph::{Font as _, FontVec, PxScale, ScaleFont};
+use anyhow::{anyhow, Context, Result};
+
+// intern
+use crate::resources::TextureHandle;
+use crate::text;
+
+//==================================================
+//=== Font
+//==================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FontHandle(pub(crate) usize);
+
+/// Metrics of a rasterized character at scale 1.0
+#[derive(Debug, Clone, Copy)]
+pub struct FontGlyph {
+    /// Region of the glyph inside the atlas, `None` for characters without outline like space
+    pub uv_rect: Option<glm::Vec4>,
+    /// Size of the glyph quad
+    pub size: glm::Vec2,
+    /// Distance between the cursor on the baseline and the quad center
+    pub offset: glm::Vec2,
+    /// Horizontal distance to the next character
+    pub advance: f32,
+}
+
+/// TrueType/OpenType font rasterized on demand into a glyph atlas
+///
+/// Glyphs are rasterized the first time they are used, so any Unicode character
+/// covered by the font can be drawn. The atlas doubles its size when it is full
+pub struct Font {
+    font: FontVec,
+    px_size: f32,
+    /// Size of a pixel at scale 1.0, a line of the font is [`text::LINE_HEIGHT`] high
+    pixel_size: f32,
+    pub texture: TextureHandle,
+    atlas_size: u32,
+    pixels: Vec<u8>,
+    cursor_x: u32,
+    cursor_y: u32,
+    shelf_height: u32,
+    glyphs: HashMap<char, FontGlyph>,
+    /// Atlas pixels changed since the last upload
+    pub dirty: bool,
+    /// Atlas size changed since the last upload, the texture has to be recreated
+    pub resized: bool,
+}
+
+impl Font {
+    /// Width and height of the glyph atlas in pixels when the font is loaded
+    pub const INITIAL_ATLAS_SIZE: u32 = 256;
+
+    /// Width and height the glyph atlas may grow to in pixels
+    pub const MAX_ATLAS_SIZE: u32 = 4096;
+
+    /// Empty space around glyphs in the atlas, avoids bleeding with linear filtering
+    const PADDING: u32 = 1;
+
+    /// Parses the font `data` which is rasterized with `px_size` pixel high lines into `texture`
+    pub fn new(data: Vec<u8>, px_size: f32, texture: TextureHandle) -> Result<Self> {
+        if px_size <= 0.0 {
+            return Err(anyhow!("Font: Pixel size must be positive"));
+        }
+
+        let font = FontVec::try_from_vec(data).context("Font: Invalid font data")?;
+
+        let scaled_font = font.as_scaled(PxScale::from(px_size));
+        let line_height = scaled_font.ascent() - scaled_font.descent();
+
+        Ok(Self {
+            font,
+            px_size,
+            pixel_size: text::LINE_HEIGHT / line_height,
+            texture,
+            atlas_size: Self::INITIAL_ATLAS_SIZE,
+            pixels: Self::empty_atlas(Self::INITIAL_ATLAS_SIZE),
+            cursor_x: Self::PADDING,
+            cursor_y: Self::PADDING,
+            shelf_height: 0,
+            glyphs: HashMap::new(),
+            dirty: false,
+            resized: false,
+        })
+    }
+
+    /// Transparent white RGBA8 pixels of an empty atlas of `size` x `size`
+    pub fn empty_atlas(size: u32) -> Vec<u8> {
+        [255, 255, 255, 0].repeat((size * size) as usize)
+    }
+
+    /// Width and height of the glyph atlas in pixels
+    pub fn atlas_size(&self) -> u32 {
+        self.atlas_size
+    }
+
+    /// RGBA8 pixels of the atlas, coverage is stored in the alpha channel
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Gives back the metrics of `c` if it was already rasterized by [`Font::prepare`]
+    pub fn glyph(&self, c: char) -> Option<&FontGlyph> {
+        self.glyphs.get(&c)
+    }
+
+    /// Metrics of `c` for [`text::layout_text`], identified by the character itself
+    pub fn layout_glyph(&self, c: char) -> Option<text::Glyph> {
+        let glyph = self.glyph(c)?;
+
+        Some(text::Glyph {
+            id: glyph.uv_rect.map(|_| c as usize),
+            offset_x: glyph.offset.x,
+            offset_y: glyph.offset.y,
+            advance: glyph.advance,
+        })
+    }
+
+    /// Rasterizes every character of `text` which is not in the atlas yet
+    pub fn prepare(&mut self, text: &str) -> Result<()> {
+        for c in text.chars() {
+            if c == '\n' || self.glyphs.contains_key(&c) {
+                continue;
+            }
+
+            let glyph = self.rasterize(c)?;
+            self.glyphs.insert(c, glyph);
+        }
+
+        Ok(())
+    }
+
+    /// Rasterizes the outline of `c` into the atlas
+    fn rasterize(&mut self, c: char) -> Result<FontGlyph> {
+        let scale = PxScale::from(self.px_size);
+        let glyph_id = self.font.glyph_id(c);
+        let advance = self.font.as_scaled(scale).h_advance(glyph_id) * self.pixel_size;
+
+        let outlined = match self
+            .font
+            .outline_glyph(glyph_id.with_scale_and_position(scale, ab_glyph::point(0.0, 0.0)))
+        {
+            Some(outlined) => outlined,
+            None => {
+                return Ok(FontGlyph {
+                    uv_rect: None,
+                    size: glm::vec2(0.0, 0.0),
+                    offset: glm::vec2(0.0, 0.0),
+                    advance,
+                })
+            }
+        };
+
+        let bounds = outlined.px_bounds();
+        let width = bounds.width() as u32;
+        let height = bounds.height() as u32;
+
+        /* Find Place On Shelf */
+
+        let (x, y) = loop {
+            if let Some(place) = self.place(width, height) {
+                break place;
+            }
+
+            if self.atlas_size >= Self::MAX_ATLAS_SIZE {
+                return Err(anyhow!(
+                    "Font: Glyph atlas is full, can't rasterize {:?}",
+                    c
+                ));
+            }
+
+            self.grow();
+        };
+
+        /* Rasterize */
+
+        let atlas_size = self.atlas_size;
+        let pixels = &mut self.pixels;
+        outlined.draw(|px, py, coverage| {
+            let index = (((y + py) * atlas_size + x + px) * 4 + 3) as usize;
+            pixels[index] = (coverage.clamp(0.0, 1.0) * 255.0) as u8;
+        });
+
+        self.dirty = true;
+
+        let atlas_size = atlas_size as f32;
+
+        Ok(FontGlyph {
+            uv_rect: Some(glm::vec4(
+                x as f32 / atlas_size,
+                y as f32 / atlas_size,
+                width as f32 / atlas_size,
+                height as f32 / atlas_size,
+            )),
+            size: glm::vec2(width as f32, height as f32) * self.pixel_size,
+            // Pixel bounds are y-down relative to the baseline
+            offset: glm::vec2(
+                (bounds.min.x + bounds.max.x) * 0.5,
+                -(bounds.min.y + bounds.max.y) * 0.5,
+            ) * self.pixel_size,
+            advance,
+        })
+    }
+
+    /// Finds the top left corner of a `width` x `height` glyph on the shelves of the atlas,
+    /// opens a new shelf when the current one is out of room
+    fn place(&mut self, width: u32, height: u32) -> Option<(u32, u32)> {
+        if self.cursor_x + width + Self::PADDING > self.atlas_size {
+            self.cursor_x = Self::PADDING;
+            self.cursor_y += self.shelf_height + Self::PADDING;
+            self.shelf_height = 0;
+        }
+
+        if self.cursor_x + width + Self::PADDING > self.atlas_size
+            || self.cursor_y + height + Self::PADDING > self.atlas_size
+        {
+            return None;
+        }
+
+        let place = (self.cursor_x, self.cursor_y);
+        self.cursor_x += width + Self::PADDING;
+        self.shelf_height = self.shelf_height.max(height);
+
+        Some(place)
+    }
+
+    /// Doubles the size of the atlas, the glyphs keep their pixels in the top left corner
+    fn grow(&mut self) {
+        let old_size = self.atlas_size;
+        let new_size = old_size * 2;
+
+        let mut pixels = Self::empty_atlas(new_size);
+        for (row, old_row) in self.pixels.chunks_exact(old_size as usize * 4).enumerate() {
+            let start = row * new_size as usize * 4;
+            pixels[start..start + old_row.len()].copy_from_slice(old_row);
+        }
+
+        // Regions are relative to the atlas size
+        for glyph in self.glyphs.values_mut() {
+            if let Some(uv_rect) = &mut glyph.uv_rect {
+                *uv_rect *= old_size as f32 / new_size as f32;
+            }
+        }
+
+        self.atlas_size = new_size;
+        self.pixels = pixels;
+        self.dirty = true;
+        self.resized = true;
+    }
+}
+
+//==================================================
+//=== Unit Testing
+//==================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::text::TextParams;
+
+    fn load_font(px_size: f32) -> Font {
+        let data = std::fs::read("res/fonts/Hack-Regular.ttf").unwrap();
+        Font::new(data, px_size, TextureHandle(0)).unwrap()
+    }
+
+    /// Alpha of the atlas pixel at `x`, `y`
+    fn coverage(font: &Font, x: u32, y: u32) -> u8 {
+        font.pixels()[((y * font.atlas_size() + x) * 4 + 3) as usize]
+    }
+
+    #[test]
+    fn test_rasterize() {
+        let mut font = load_font(32.0);
+        assert!(Font::new(vec![0; 16], 32.0, TextureHandle(0)).is_err());
+        assert!(Font::new(Vec::new(), 0.0, TextureHandle(0)).is_err());
+
+        // Only the missing characters are rasterized, new lines are handled by the layout
+        font.prepare("Hi \n").unwrap();
+        assert!(font.dirty);
+        assert!(font.glyph('\n').is_none());
+        font.dirty = false;
+        font.prepare("iH").unwrap();
+        assert!(!font.dirty);
+
+        // Space has no outline, only an advance
+        let space = font.glyph(' ').unwrap();
+        assert!(space.uv_rect.is_none());
+        assert!(space.advance > 0.0);
+
+        // Covered pixels only inside the regions of the glyphs
+        let inside = |c: char, x: u32, y: u32| {
+            let region = font.glyph(c).unwrap().uv_rect.unwrap() * font.atlas_size() as f32;
+            (region.x as u32..(region.x + region.z) as u32).contains(&x)
+                && (region.y as u32..(region.y + region.w) as u32).contains(&y)
+        };
+        let covered = (0..font.atlas_size())
+            .flat_map(|y| (0..font.atlas_size()).map(move |x| (x, y)))
+            .filter(|&(x, y)| coverage(&font, x, y) > 0)
+            .collect::<Vec<_>>();
+        assert!(covered.iter().any(|&(x, y)| inside('H', x, y)));
+        assert!(covered
+            .iter()
+            .all(|&(x, y)| inside('H', x, y) || inside('i', x, y)));
+
+        let glyph = font.glyph('H').unwrap();
+        assert!(glyph.size.y > 0.0 && glyph.size.y < text::LINE_HEIGHT);
+    }
+
+    #[test]
+    fn test_non_ascii() {
+        let mut font = load_font(32.0);
+        let text = "Árvíztűrő tükörfúrógép Ωλ Жж";
+        font.prepare(text).unwrap();
+
+        // Every character gets its own region, the glyphs are not mixed up with ASCII ones
+        for c in text.chars().filter(|c| *c != ' ') {
+            assert!(font.glyph(c).unwrap().uv_rect.is_some(), "{:?}", c);
+        }
+        font.prepare("A").unwrap();
+        assert_ne!(
+            font.glyph('Á').unwrap().uv_rect,
+            font.glyph('A').unwrap().uv_rect
+        );
+    }
+
+    #[test]
+    fn test_atlas_packing() {
+        let mut font = load_font(32.0);
+        let text = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+        font.prepare(text).unwrap();
+
+        // Regions stay inside the atlas and don't overlap
+        let regions = text
+            .chars()
+            .map(|c| font.glyph(c).unwrap().uv_rect.unwrap())
+            .collect::<Vec<_>>();
+        for (index, a) in regions.iter().enumerate() {
+            assert!(a.x >= 0.0 && a.y >= 0.0 && a.x + a.z <= 1.0 && a.y + a.w <= 1.0);
+
+            for b in &regions[index + 1..] {
+                let apart =
+                    a.x + a.z <= b.x || b.x + b.z <= a.x || a.y + a.w <= b.y || b.y + b.w <= a.y;
+                assert!(apart, "{:?} overlaps {:?}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_atlas_growth() {
+        let mut font = load_font(64.0);
+        assert_eq!(font.atlas_size(), Font::INITIAL_ATLAS_SIZE);
+
+        font.prepare("A").unwrap();
+        let region = font.glyph('A').unwrap().uv_rect.unwrap();
+        let pixels = font.pixels().to_vec();
+
+        // Large glyphs don't fit the initial atlas -> Doubled, the old glyphs are kept
+        let text = ('a'..='z').chain('A'..='Z').collect::<String>();
+        font.prepare(&text).unwrap();
+        assert!(font.resized);
+        assert!(font.atlas_size() > Font::INITIAL_ATLAS_SIZE);
+        assert_eq!(font.pixels().len() as u32, font.atlas_size().pow(2) * 4);
+
+        let scale = Font::INITIAL_ATLAS_SIZE as f32 / font.atlas_size() as f32;
+        assert_eq!(font.glyph('A').unwrap().uv_rect.unwrap(), region * scale);
+        let region = region * Font::INITIAL_ATLAS_SIZE as f32;
+        for y in region.y as u32..(region.y + region.w) as u32 {
+            for x in region.x as u32..(region.x + region.z) as u32 {
+                let old = pixels[((y * Font::INITIAL_ATLAS_SIZE + x) * 4 + 3) as usize];
+                assert_eq!(coverage(&font, x, y), old);
+            }
+        }
+
+        // The atlas can't grow past its maximum
+        let mut font = load_font(8192.0);
+        assert!(font.prepare("W").is_err());
+    }
+
+    #[test]
+    fn test_font_layout() {
+        let mut font = load_font(32.0);
+        let text = "Hé\nŐ";
+        font.prepare(text).unwrap();
+
+        let placements = text::layout_text(text, &TextParams::default(), |c| font.layout_glyph(c));
+
+        // Characters are identified by themselves, the second line is one line lower
+        assert_eq!(placements.len(), 3);
+        assert_eq!(placements[0].id, 'H' as usize);
+        assert_eq!(placements[1].id, 'é' as usize);
+        assert_eq!(placements[2].id, 'Ő' as usize);
+        assert!(placements[1].position.x > placements[0].position.x);
+
+        let baseline =
+            |index: usize, c: char| placements[index].position.y - font.glyph(c).unwrap().offset.y;
+        assert!((baseline(0, 'H') - baseline(2, 'Ő') - text::LINE_HEIGHT).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,143 @@
+// extern
+use anyhow::{anyhow, Context, Result};
+
+//==================================================
+//=== Layers
+//==================================================
+
+/// Named layer of draw instances, selected with `Renderer::use_layer`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Layer {
+    pub name: String,
+    /// Value of `draw_params.layer` of the instances, higher orders are drawn above lower ones
+    pub order: i32,
+    /// Hidden layers are neither drawn nor picked
+    pub visible: bool,
+    /// Instances are placed relative to the screen and drawn ignoring the camera position
+    /// and rotation
+    pub screen_space: bool,
+}
+
+impl Layer {
+    fn new(name: &str, order: i32) -> Self {
+        Self {
+            name: name.to_string(),
+            order,
+            visible: true,
+            screen_space: false,
+        }
+    }
+}
+
+/// Named layers of the renderer, layers without a name are visible and follow the camera
+#[derive(Debug, Clone)]
+pub(crate) struct Layers {
+    layers: Vec<Layer>,
+}
+
+impl Default for Layers {
+    /// Built-in "background", "world" and "ui" layers, "world" is the default `draw_params.layer`
+    fn default() -> Self {
+        Self {
+            layers: vec![
+                Layer::new("background", -1000),
+                Layer::new("world", 0),
+                Layer::new("ui", 1000),
+            ],
+        }
+    }
+}
+
+impl Layers {
+    /// Adds a new layer called `name`, names and orders are unique
+    pub fn add(&mut self, name: &str, order: i32) -> Result<()> {
+        if self.layers.iter().any(|layer| layer.name == name) {
+            return Err(anyhow!("Layers: Layer {} already exists", name));
+        }
+
+        self.check_order(order)?;
+        self.layers.push(Layer::new(name, order));
+
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Result<&Layer> {
+        self.layers
+            .iter()
+            .find(|layer| layer.name == name)
+            .with_context(|| format!("Layers: Unknown layer {}", name))
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Result<&mut Layer> {
+        self.layers
+            .iter_mut()
+            .find(|layer| layer.name == name)
+            .with_context(|| format!("Layers: Unknown layer {}", name))
+    }
+
+    /// Moves the layer called `name` to `order` and gives back its previous order
+    pub fn set_order(&mut self, name: &str, order: i32) -> Result<i32> {
+        if self.get(name)?.order == order {
+            return Ok(order);
+        }
+
+        self.check_order(order)?;
+
+        Ok(std::mem::replace(&mut self.get_mut(name)?.order, order))
+    }
+
+    pub fn is_visible(&self, order: i32) -> bool {
+        self.by_order(order).is_none_or(|layer| layer.visible)
+    }
+
+    pub fn is_screen_space(&self, order: i32) -> bool {
+        self.by_order(order).is_some_and(|layer| layer.screen_space)
+    }
+
+    pub fn as_slice(&self) -> &[Layer] {
+        &self.layers
+    }
+
+    fn by_order(&self, order: i32) -> Option<&Layer> {
+        self.layers.iter().find(|layer| layer.order == order)
+    }
+
+    fn check_order(&self, order: i32) -> Result<()> {
+        match self.by_order(order) {
+            Some(layer) => Err(anyhow!(
+                "Layers: Order {} is already used by layer {}",
+                order,
+                layer.name
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+//==================================================
+//=== Unit Testing
+//==================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layers() {
+        let mut layers = Layers::default();
+        layers.add("overlay", 2000).unwrap();
+        layers.get_mut("overlay").unwrap().screen_space = true;
+        layers.get_mut("background").unwrap().visible = false;
+
+        // Names and orders are unique
+        assert!(layers.add("overlay", 3000).is_err());
+        assert!(layers.add("debug", 0).is_err());
+        assert!(layers.set_order("overlay", 1000).is_err());
+
+        assert_eq!(layers.set_order("overlay", 3000).unwrap(), 2000);
+        assert!(layers.is_screen_space(3000));
+        assert!(!layers.is_screen_space(2000));
+        assert!(!layers.is_visible(-1000));
+        assert!(layers.is_visible(42));
+    }
+}
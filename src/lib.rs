@@ -2,7 +2,11 @@
 
 // std
 use std::{
+    cell::OnceCell,
+    collections::{HashMap, VecDeque},
     ffi::CStr,
+    ops::Range,
+    path::Path,
     time::{Duration, Instant},
 };
 
@@ -14,21 +18,115 @@ use ash::{
     util,
     vk::{self, DescriptorSet},
 };
-use raw_window_handle::HasRawDisplayHandle;
-use winit::dpi::PhysicalSize;
+use bytemuck::{Pod, Zeroable};
+use raw_window_handle::{HasRawDisplayHandle, HasRawWindowHandle};
+#[cfg(feature = "winit")]
+use winit::{
+    dpi::PhysicalSize,
+    event::{ElementState, KeyboardInput, VirtualKeyCode, WindowEvent},
+    window::Fullscreen,
+};
 
 // intern
+mod allocator;
+mod animation;
 mod buffers;
+mod builder;
+mod camera;
+mod canvas;
+mod capture;
+mod compute;
 mod descriptor;
+pub mod diagnostics;
+mod draw_list;
+#[cfg(feature = "egui")]
+mod egui_backend;
+mod error;
 mod extensions;
+mod font;
+mod layers;
+mod loader;
+mod loop_runner;
+mod pacing;
+#[cfg(feature = "physics")]
+mod physics;
 mod pipeline;
+mod plot;
+mod post;
+#[cfg(test)]
+mod raster;
+mod recording;
+mod render_thread;
 mod resources;
-
+mod scene;
+mod shapes;
+mod spatial;
+mod svg;
+mod text;
+mod ui;
+mod watch;
+
+use allocator::{Allocation, MemoryAllocator};
+use animation::FlipbookPlayback;
 use buffers::*;
+use canvas::{EffectCanvas, EffectConstants};
+use capture::Recorder;
+use compute::{ComputeDispatch, ComputePipeline};
 use descriptor::*;
+use draw_list::DrawLimits;
 use extensions::*;
+use font::Font;
+use layers::Layers;
+use loader::{LoadRequest, Loaded, ResourceLoader};
+use pacing::FramePacer;
 use pipeline::*;
+use post::PostProcess;
+use recording::{
+    CommandRecorder, DrawBackend, DrawContext, ParallelRecorder, RegionCommands, SecondaryTarget,
+};
 use resources::*;
+use watch::ObjectWatcher;
+
+pub use animation::{Animator, Easing, Flipbook, Repeat, Tween, TweenId, TweenProperty};
+pub use builder::{ColorMode, FullscreenMode, GpuPreference, PresentMode, RendererBuilder};
+pub use camera::CameraController;
+pub use canvas::EffectOrder;
+pub use capture::RecordFormat;
+pub use compute::{ComputeBinding, ComputeBufferHandle, ComputeHandle};
+pub use draw_list::DrawList;
+#[cfg(feature = "egui")]
+pub use egui_backend::EguiRenderer;
+#[cfg(feature = "egui-winit")]
+pub use egui_backend::EguiWinit;
+pub use error::{RendererError, RendererResult};
+pub use extensions::{DebugMessage, DebugSeverity};
+pub use font::FontHandle;
+pub use layers::Layer;
+pub use loop_runner::FixedTimestep;
+#[cfg(feature = "winit")]
+pub use loop_runner::{LoopHandler, LoopRunner};
+pub use pacing::FrameLimit;
+#[cfg(feature = "physics")]
+pub use physics::{Body, BodyId, Collider, PhysicsSystem};
+pub use pipeline::{PipelineDesc, PipelineHandle, Topology};
+pub use plot::{Plot, PlotStyle};
+pub use post::PostEffect;
+pub use render_thread::{DrawCommand, RenderHandle};
+pub use text::{HorizontalAlign, TextParams, TextSpan, VerticalAlign};
+pub use ui::{
+    Gamepad, GamepadAxis, GamepadButton, GamepadEvent, InputEvent, Inputs, Modifiers, Ui, UiRect,
+    UiStyle, WidgetState,
+};
+
+pub use loader::{LoadEvent, LoadId, LoadedResource};
+pub use scene::{NodeId, NodeShape, SceneGraph, SceneNode, Transform};
+pub use shapes::{Border, LineCap, LineJoint, LineStyle, Shape};
+pub use spatial::SpatialGrid;
+
+pub use resources::{
+    AtlasHandle, AtlasRegionId, DrawInstanceId, ObjectHandle, ObjectInstance, PersistentId,
+    PoolWarning, ResourceConfig, TextureHandle, Vertex,
+};
 
 //==================================================
 //=== Renderer
@@ -41,94 +139,286 @@ pub struct Renderer {
     instance: ash::Instance,
     device: ash::Device,
     physical_device: vk::PhysicalDevice,
+    allocator: MemoryAllocator,
+    swapchain_images: Vec<vk::Image>,
     image_views: Vec<vk::ImageView>,
 
     // Vulkan: Extensions
     debug_utils_loader: Option<ext::DebugUtils>,
     debug_messenger: Option<vk::DebugUtilsMessengerEXT>,
+    debug_sink: Box<std::sync::Mutex<DebugSink>>,
     surface_loader: khr::Surface,
     surface: vk::SurfaceKHR,
     swapchain_loader: khr::Swapchain,
     swapchain: vk::SwapchainKHR,
+    /// Only loaded on Windows when VK_EXT_full_screen_exclusive is supported
+    full_screen_exclusive_loader: Option<ext::FullScreenExclusive>,
+    fullscreen_mode: FullscreenMode,
+    /// Only loaded with [`RendererBuilder::low_latency`] when VK_KHR_present_wait is supported
+    present_wait_loader: Option<khr::PresentWait>,
+    /// Id of the last presented frame
+    present_id: u64,
+    /// Id and present time of the frame which is not waited on yet
+    pending_present: Option<(u64, Instant)>,
+    /// Monitor of the window in [`FullscreenMode::Exclusive`]
+    #[cfg(feature = "winit")]
+    exclusive_monitor: Option<winit::monitor::MonitorHandle>,
 
     // Vulkan: Descriptor
     descriptor_set_layout: vk::DescriptorSetLayout,
     descriptor_pool: vk::DescriptorPool,
     descriptor_sets: Vec<DescriptorSet>,
+    texture_set_layout: vk::DescriptorSetLayout,
+    texture_pool: vk::DescriptorPool,
 
     // Vulkan: Graphics Pipeline
     pipeline_layout: vk::PipelineLayout,
     render_pass: vk::RenderPass,
     graphics_pipeline: vk::Pipeline,
+    sprite_pipeline_layout: vk::PipelineLayout,
+    sprite_pipeline: vk::Pipeline,
+    /// Draws the shapes of the masks into the stencil attachment, see [`Renderer::begin_mask`]
+    mask_pipeline: GraphicsPipeline,
+    /// Draws the [`SdfShape`]s of the instances, see [`DrawParams::sdf`]
+    sdf_pipeline: GraphicsPipeline,
+    wireframe_pipeline: Option<GraphicsPipeline>,
+    debug_wireframe: bool,
+    /// Draws the [`DebugInfo`] of every frame, see [`Renderer::set_debug_overlay`]
+    debug_overlay: bool,
+    custom_pipelines: Vec<GraphicsPipeline>,
+    /// Pipelines and queued draws of [`Renderer::fullscreen_effect`]
+    effect_canvas: EffectCanvas,
+    compute_pipelines: Vec<ComputePipeline>,
+    compute_buffers: Vec<InstanceBuffer>,
+    compute_pools: Vec<vk::DescriptorPool>,
+    compute_dispatches: Vec<ComputeDispatch>,
+    shader_watcher: Option<ShaderWatcher>,
     graphics_queue: vk::Queue,
+    graphics_queue_index: u32,
     present_queue: vk::Queue,
+    present_queue_index: u32,
+    /// Queues of the staging copies, with the transfer queue when the device has one
+    upload_queues: UploadQueues,
+    /// Size of the window in pixels, see [`Renderer::set_surface_extent`]
+    surface_extent: vk::Extent2D,
     viewport: vk::Viewport,
     scissor: vk::Rect2D,
-    #[allow(dead_code)]
-    push_constant_range: vk::PushConstantRange,
+    present_mode: vk::PresentModeKHR,
+    surface_format: vk::SurfaceFormatKHR,
+    /// Frames are drawn with `cmd_begin_rendering`, without render pass and framebuffers
+    dynamic_rendering: bool,
+    msaa_samples: vk::SampleCountFlags,
+    /// Clear values of the attachments of the main pass, the stencil attachment last
+    clear_values: Vec<vk::ClearValue>,
+
+    /// Replaced swapchains, destroyed once the frames in flight using them are done
+    retired_swapchains: Vec<RetiredSwapchain>,
 
     // Vulkan: Buffers
     frame_buffers: Vec<vk::Framebuffer>,
+    color_target: Option<ColorTarget>,
+    stencil_target: StencilTarget,
+    post_process: Option<PostProcess>,
+    /// Egui pass over the finished frame, created by [`Renderer::egui`]
+    #[cfg(feature = "egui")]
+    egui_renderer: Option<EguiRenderer>,
     command_pool: vk::CommandPool,
     draw_command_buffers: Vec<vk::CommandBuffer>,
+    /// Records large frames on several threads, see [`RendererBuilder::recording_threads`]
+    parallel_recorder: Option<ParallelRecorder>,
     vertex_buffer: vk::Buffer,
-    vertex_buffer_memory: vk::DeviceMemory,
+    vertex_buffer_allocation: Allocation,
     index_buffer: vk::Buffer,
-    index_buffer_memory: vk::DeviceMemory,
+    index_buffer_allocation: Allocation,
+    index_type: vk::IndexType,
+    stream_buffers: Vec<StreamingBuffer>,
+    instance_buffers: Vec<InstanceBuffer>,
     uniform_buffers: Vec<vk::Buffer>,
-    uniform_buffers_memory: Vec<vk::DeviceMemory>,
+    uniform_buffers_allocation: Vec<Allocation>,
     uniform_buffers_mem_req: Vec<vk::MemoryRequirements>,
     uniform_buffers_mapped: Vec<*mut std::ffi::c_void>,
+    /// Distance of the cameras of the viewport regions in the uniform buffers
+    uniform_stride: u64,
+
+    // Vulkan: Textures
+    textures: Vec<Texture>,
+    texture_sets: Vec<DescriptorSet>,
+
+    // Fonts
+    fonts: Vec<Font>,
+    font: Option<FontHandle>,
+
+    // Resource Loading
+    resource_loader: ResourceLoader,
+    /// Textures drawn as placeholders until their load is done
+    pending_textures: HashMap<LoadId, TextureHandle>,
+    /// .obj files reloaded by [`Renderer::reload_changed_objects`]
+    object_watcher: ObjectWatcher,
 
     // Vulkan: Syncronization
+    /// Signaled when the swapchain image of a frame in flight is acquired
     semaphores_acquire: Vec<vk::Semaphore>,
+    /// Signaled when the rendering to a swapchain image is done, one for each image since
+    /// the presentation of an image may still wait on it when its frame comes around again
     semaphores_release: Vec<vk::Semaphore>,
     fences_inflight: Vec<vk::Fence>,
 
+    /// Frame capture started by [`Renderer::start_recording`]
+    recorder: Option<Recorder>,
+
     // Render Loop Data
+    max_frames_inflight: usize,
     current_frame: usize,
+    /// Creation time, the time of the fullscreen effects is counted from it
+    created: Instant,
+    /// Cursor in window pixels from the top left, tracked by `handle_window_event`
+    cursor_position: glm::Vec2,
     pub scene: Scene,
+    viewports: Vec<ViewportRegion>,
+    /// Instances outside of the view of every camera are not drawn
+    culling: bool,
     object_pool: ObjectPool,
-    pub draw_pool: Vec<ObjectInstance>,
+    /// Default objects drawn by the shape functions, looked up by name at creation
+    rectangle_object: ObjectHandle,
+    circle_object: ObjectHandle,
+    stream_pool: ObjectPool,
+    /// Instances of the current frame, see [`Renderer::draw_list`]
+    draw_pool: Vec<ObjectInstance>,
+    persistent_pool: PersistentPool,
+    /// Flipbooks of persistent sprites, see [`Renderer::play_flipbook`]
+    flipbooks: Vec<FlipbookPlayback>,
+    layers: Layers,
+    /// Draw instances of the last submitted frame, used for picking
+    pick_targets: Vec<PickTarget>,
+    /// Grid of the `pick_targets`, built by the first pick after a draw request
+    pick_index: OnceCell<PickIndex>,
+    pub draw_params: DrawParams,
+    /// Clip rectangles of [`Renderer::push_clip`], each one inside the previous
+    clip_stack: Vec<ClipRect>,
+    /// Masks begun in the current frame, the id of the last one
+    mask_count: u8,
     render_stats: RenderStats,
+    frame_pacer: FramePacer,
 }
 
+// Only the host addresses of the mapped buffers keep the renderer from being `Send`, they are
+// used by the thread owning the renderer alone
+unsafe impl Send for Renderer {}
+
 impl Renderer {
-    const MAX_FRAMES_INFLIGHT: usize = 2;
+    /// Layer of the render statistics, drawn above everything else
+    const STATS_LAYER: i32 = i32::MAX;
 
-    const CLEAR_VALUES: [vk::ClearValue; 1] = [vk::ClearValue {
-        color: vk::ClearColorValue {
-            float32: [0.0, 0.0, 0.0, 1.0],
-        },
-    }];
+    /// Pixels of a unit of the render statistics objects
+    const STATS_SCALE: f32 = 200.0;
+
+    /// Window pixels of the color swatch of the debug overlay
+    const DEBUG_SWATCH_SIZE: f32 = 24.0;
+
+    /// Key toggling the debug overlay in `handle_window_event`
+    #[cfg(feature = "winit")]
+    pub const DEBUG_OVERLAY_KEY: VirtualKeyCode = VirtualKeyCode::F3;
+
+    /// Initial size of the per frame streaming buffers in bytes, they grow when needed
+    const STREAM_BUFFER_CAPACITY: u64 = 64 * 1024;
+
+    /// Maximum number of viewport regions drawn in a frame
+    pub const MAX_VIEWPORTS: usize = 4;
+
+    /// Initial number of instances of the per frame instance buffers, they grow when needed
+    const INSTANCE_BUFFER_CAPACITY: u64 = 1024;
 
-    /// Creates a new [`Renderer`] using `window`
-    pub fn new(window: &winit::window::Window) -> Result<Renderer> {
+    /// Longest wait for the previous frame to be presented in nanoseconds
+    const PRESENT_WAIT_TIMEOUT: u64 = 100_000_000;
+
+    /// Creates a new [`Renderer`] using `window` with the default [`RendererBuilder`] options
+    #[cfg(feature = "winit")]
+    pub fn new(window: &winit::window::Window) -> RendererResult<Renderer> {
+        RendererBuilder::new().build(window)
+    }
+
+    /// Creates a new [`RendererBuilder`] to configure the [`Renderer`] before creation
+    pub fn builder() -> RendererBuilder {
+        RendererBuilder::new()
+    }
+
+    /// Creates a new [`Renderer`] using `window` of `surface_extent` and the `options` of the
+    /// builder
+    pub(crate) fn from_builder(
+        window: &(impl HasRawDisplayHandle + HasRawWindowHandle),
+        surface_extent: vk::Extent2D,
+        options: &RendererBuilder,
+    ) -> Result<Renderer> {
         // Pre Load Object Pool
-        let object_pool = resources::preload()?;
+        let object_pool = resources::preload(&options.resource_config)?;
+        let rectangle_object = object_pool.lookup("rectangle")?;
+        let circle_object = object_pool.lookup("circle")?;
+
+        #[cfg(feature = "log")]
+        for warning in object_pool.validate() {
+            log::warn!(target: "lavapond::resources", "{}", warning);
+        }
 
-        let window_size =
-            winit::dpi::PhysicalSize::new(window.inner_size().width, window.inner_size().height);
+        let mut resource_loader = ResourceLoader::default();
+        let mut object_watcher = ObjectWatcher::default();
+        if let Some(obj_dir) = &options.resource_config.obj_dir {
+            if options.resource_config.load_in_background {
+                resource_loader.request(LoadRequest::DefaultObjects(obj_dir.clone()));
+            } else {
+                for (path, handles) in resources::default_object_files(obj_dir)? {
+                    object_watcher.watch(&path, false, handles);
+                }
+            }
+        }
 
         // Base: Entry & Instance
         let entry = unsafe { ash::Entry::load() }?;
 
-        let instance = create_instance(&entry, &window)?;
+        let instance = create_instance(&entry, window, options.portability_enumeration)?;
 
         // Extensions: Debug & Surface
+        let debug_sink = Box::new(std::sync::Mutex::new(DebugSink::default()));
+
         #[cfg(not(feature = "render_dbg"))]
         let (debug_ext_loader, debug_ext_messenger) = (None, None);
 
         #[cfg(feature = "render_dbg")]
         let (debug_ext_loader, debug_ext_messenger) = {
-            let debug_ext = DebugExtension::new(&entry, &instance)?;
+            let debug_ext = DebugExtension::new(&entry, &instance, &debug_sink)?;
             (Some(debug_ext.loader), Some(debug_ext.messenger))
         };
 
-        let surface_ext = SurfaceExtension::new(&entry, &instance, &window)?;
+        let surface_ext = SurfaceExtension::new(&entry, &instance, window)?;
 
         // Device
-        let device = Device::new(&instance, &surface_ext)?;
+        let device = Device::new(&instance, &surface_ext, &options.gpu_preference)?;
+
+        let msaa_samples =
+            builder::sample_count(options.msaa_samples, device.supported_sample_counts);
+
+        // Present Mode: Fall back to FIFO which is always supported
+        let present_mode = if unsafe {
+            surface_ext
+                .loader
+                .get_physical_device_surface_present_modes(
+                    device.physical_device,
+                    surface_ext.surface,
+                )
+        }?
+        .contains(&options.present_mode.as_vk())
+        {
+            options.present_mode.as_vk()
+        } else {
+            vk::PresentModeKHR::FIFO
+        };
+
+        // Surface Format: Fall back to sRGB, which is checked by the device selection
+        let surface_format = builder::surface_format(options.color_mode, &unsafe {
+            surface_ext
+                .loader
+                .get_physical_device_surface_formats(device.physical_device, surface_ext.surface)
+        }?)
+        .context("Surface Format: No sRGB surface format")?;
 
         // Queue Families
         let graphics_queue = unsafe {
@@ -143,6 +433,17 @@ impl Renderer {
                 .get_device_queue(device.present_queue_index, 0)
         };
 
+        let upload_queues = UploadQueues {
+            graphics: graphics_queue,
+            graphics_index: device.graphics_queue_index,
+            transfer: device.transfer_queue_index.map(|index| {
+                (
+                    unsafe { device.logical_device.get_device_queue(index, 0) },
+                    index,
+                )
+            }),
+        };
+
         // Extension: Swapchain
         let mut swapchain_ext = SwapchainExtension::new(
             &entry,
@@ -150,15 +451,37 @@ impl Renderer {
             &device.logical_device,
             &device.physical_device,
             &surface_ext,
-            &window,
+            surface_extent,
+            present_mode,
+            surface_format,
+            &[device.graphics_queue_index, device.present_queue_index],
+            options.max_frames_inflight,
         )?;
 
+        let full_screen_exclusive_loader = if device.full_screen_exclusive_supported {
+            Some(ext::FullScreenExclusive::new(
+                &instance,
+                &device.logical_device,
+            ))
+        } else {
+            None
+        };
+
+        let present_wait_loader = if options.low_latency && device.present_wait_supported {
+            Some(khr::PresentWait::new(&instance, &device.logical_device))
+        } else {
+            None
+        };
+
         let swapchain_images = unsafe {
             swapchain_ext
                 .loader
                 .get_swapchain_images(swapchain_ext.swapchain)
         }?;
 
+        // Frames In Flight: Never more than the swapchain images
+        let max_frames_inflight = options.max_frames_inflight.min(swapchain_images.len());
+
         // Image views
         let mut image_views: Vec<vk::ImageView> = {
             let subresource_range = vk::ImageSubresourceRange::builder()
@@ -168,11 +491,11 @@ impl Renderer {
                 .build();
 
             let mut image_views = Vec::new();
-            for img in swapchain_images {
+            for img in &swapchain_images {
                 let create_info = vk::ImageViewCreateInfo::builder()
-                    .image(img)
+                    .image(*img)
                     .view_type(vk::ImageViewType::TYPE_2D)
-                    .format(vk::Format::B8G8R8A8_SRGB)
+                    .format(surface_format.format)
                     .subresource_range(subresource_range);
 
                 image_views
@@ -182,118 +505,263 @@ impl Renderer {
         };
 
         // Descriptor
-        let descriptor = Descriptor::new(&device.logical_device, Self::MAX_FRAMES_INFLIGHT)?;
-
-        // Push Constants
-        let push_constant_range = vk::PushConstantRange::builder()
-            .stage_flags(vk::ShaderStageFlags::VERTEX)
-            .size(std::mem::size_of::<DrawInstanceData>() as u32)
-            .offset(0)
-            .build();
+        let descriptor = Descriptor::new(&device.logical_device, max_frames_inflight)?;
 
         // Viewport & Scissor
         let mut viewport = vk::Viewport {
-            width: window.inner_size().width as f32,
-            height: window.inner_size().height as f32,
+            width: swapchain_ext.extent.width as f32,
+            height: swapchain_ext.extent.height as f32,
             max_depth: 1.0,
             ..Default::default()
         };
 
         let mut scissor = vk::Rect2D {
-            extent: vk::Extent2D {
-                width: window.inner_size().width,
-                height: window.inner_size().height,
-            },
+            extent: swapchain_ext.extent,
             ..Default::default()
         };
 
         // Graphics Pipeline
+        let shader_dir = options.shader_dir.as_deref();
+        let (shape_shaders, sprite_shaders, sdf_shaders) = match &options.hot_reload_dir {
+            Some(hot_reload_dir) => (
+                ShaderCode::load_source(hot_reload_dir, "shader")?,
+                ShaderCode::load_source(hot_reload_dir, "sprite")?,
+                ShaderCode::load_source(hot_reload_dir, "sdf")?,
+            ),
+            None => (
+                ShaderCode::load(shader_dir, "shader")?,
+                ShaderCode::load(shader_dir, "sprite")?,
+                ShaderCode::load(shader_dir, "sdf")?,
+            ),
+        };
+        let shader_watcher = options.hot_reload_dir.clone().map(ShaderWatcher::new);
+
+        let render_pass = pipeline::create_render_pass(
+            &device.logical_device,
+            surface_format.format,
+            msaa_samples,
+            vk::ImageLayout::PRESENT_SRC_KHR,
+            Some(device.stencil_format),
+        )?;
+
+        // Dynamic Rendering: Fall back to the render pass
+        let dynamic_rendering = options.dynamic_rendering && device.dynamic_rendering_supported;
+        let pipeline_target = if dynamic_rendering {
+            RenderTarget::Dynamic {
+                format: surface_format.format,
+                stencil_format: device.stencil_format,
+            }
+        } else {
+            RenderTarget::StencilRenderPass(render_pass)
+        };
+
         let graphics_pipeline = GraphicsPipeline::new(
             &device.logical_device,
-            &descriptor.set_layout,
+            &pipeline_target,
+            std::slice::from_ref(&descriptor.set_layout),
+            &viewport,
+            &scissor,
+            std::mem::size_of::<Vertex>() as u32,
+            &[],
+            &shape_shaders,
+            false,
+            msaa_samples,
+            vk::PolygonMode::FILL,
+            vk::PrimitiveTopology::TRIANGLE_LIST,
+        )?;
+
+        let sprite_pipeline = GraphicsPipeline::new(
+            &device.logical_device,
+            &pipeline_target,
+            &[descriptor.set_layout, descriptor.texture_set_layout],
+            &viewport,
+            &scissor,
+            std::mem::size_of::<Vertex>() as u32,
+            &[],
+            &sprite_shaders,
+            true,
+            msaa_samples,
+            vk::PolygonMode::FILL,
+            vk::PrimitiveTopology::TRIANGLE_LIST,
+        )?;
+
+        let mask_pipeline = GraphicsPipeline::mask(
+            &device.logical_device,
+            &pipeline_target,
+            std::slice::from_ref(&descriptor.set_layout),
+            &viewport,
+            &scissor,
+            &shape_shaders,
+            msaa_samples,
+        )?;
+
+        let sdf_pipeline = GraphicsPipeline::new(
+            &device.logical_device,
+            &pipeline_target,
+            std::slice::from_ref(&descriptor.set_layout),
             &viewport,
             &scissor,
             std::mem::size_of::<Vertex>() as u32,
-            &push_constant_range,
+            &[],
+            &sdf_shaders,
+            true,
+            msaa_samples,
+            vk::PolygonMode::FILL,
+            vk::PrimitiveTopology::TRIANGLE_LIST,
         )?;
 
+        // Wireframe pipeline needs the non solid fill mode device feature
+        let wireframe_pipeline = if device.wireframe_supported {
+            Some(GraphicsPipeline::new(
+                &device.logical_device,
+                &pipeline_target,
+                std::slice::from_ref(&descriptor.set_layout),
+                &viewport,
+                &scissor,
+                std::mem::size_of::<Vertex>() as u32,
+                &[],
+                &shape_shaders,
+                false,
+                msaa_samples,
+                vk::PolygonMode::LINE,
+                vk::PrimitiveTopology::TRIANGLE_LIST,
+            )?)
+        } else {
+            None
+        };
+
         // Buffers
         let draw_command_buffer = buffers::CommandBuffer::new_draw_cmd_buffer(
             &device.logical_device,
             device.graphics_queue_index,
-            Self::MAX_FRAMES_INFLIGHT as u32,
+            max_frames_inflight as u32,
         )?;
 
-        let mut frame_buffer = buffers::FrameBuffer::new(
+        let parallel_recorder = if options.recording_threads > 1 {
+            Some(ParallelRecorder::new(
+                &device.logical_device,
+                device.graphics_queue_index,
+                options.recording_threads,
+                max_frames_inflight,
+                Renderer::MAX_VIEWPORTS,
+            )?)
+        } else {
+            None
+        };
+
+        let mut allocator = MemoryAllocator::new(device.memory_properties);
+
+        let color_target = if msaa_samples != vk::SampleCountFlags::TYPE_1 {
+            Some(buffers::ColorTarget::new(
+                &device.logical_device,
+                &mut allocator,
+                swapchain_ext.extent.width,
+                swapchain_ext.extent.height,
+                surface_format.format,
+                msaa_samples,
+            )?)
+        } else {
+            None
+        };
+
+        let stencil_target = buffers::StencilTarget::new(
             &device.logical_device,
-            &image_views,
-            &graphics_pipeline.render_pass,
-            window_size.width,
-            window_size.height,
+            &mut allocator,
+            swapchain_ext.extent.width,
+            swapchain_ext.extent.height,
+            device.stencil_format,
+            msaa_samples,
         )?;
 
+        let frame_buffers = if dynamic_rendering {
+            Vec::new()
+        } else {
+            buffers::FrameBuffer::new(
+                &device.logical_device,
+                &image_views,
+                &render_pass,
+                swapchain_ext.extent.width,
+                swapchain_ext.extent.height,
+                color_target.as_ref(),
+                Some(&stencil_target),
+            )?
+            .buffers
+        };
+
         let vertices_size = (std::mem::size_of::<Vertex>() * object_pool.vertices.len()) as u64;
 
         let vertex_buffer = buffers::StorageBuffer::new(
             &device.logical_device,
-            &device.memory_properties,
-            &graphics_queue,
-            &device.graphics_queue_index,
+            &mut allocator,
+            &upload_queues,
             vertices_size,
             DataUsage::VERTEX,
             &object_pool.vertices,
             std::mem::align_of::<f32>() as u64,
         )?;
 
-        let indices_size = (std::mem::size_of::<u16>() * object_pool.indices.len()) as u64;
-
-        let index_buffer = buffers::StorageBuffer::new(
+        let (index_buffer, index_type) = create_index_buffer(
             &device.logical_device,
-            &device.memory_properties,
-            &graphics_queue,
-            &device.graphics_queue_index,
-            indices_size,
-            DataUsage::INDEX,
-            &object_pool.indices,
-            (std::mem::align_of::<u16>()) as u64,
+            &mut allocator,
+            &upload_queues,
+            &object_pool,
         )?;
 
+        let stream_buffers = (0..max_frames_inflight)
+            .map(|_| {
+                StreamingBuffer::new(
+                    &device.logical_device,
+                    &mut allocator,
+                    Self::STREAM_BUFFER_CAPACITY,
+                )
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        // A world, a resting and a pixel camera for each viewport region, bound with dynamic offsets
+        let uniform_stride = (std::mem::size_of::<CameraVP>() as u64)
+            .next_multiple_of(device.min_uniform_buffer_offset_alignment.max(1));
+
         let uniform_buffer = buffers::UniformBuffer::new(
             &device.logical_device,
-            &device.memory_properties,
-            Self::MAX_FRAMES_INFLIGHT,
-            (std::mem::size_of::<CameraVP>()) as u64,
+            &mut allocator,
+            max_frames_inflight,
+            uniform_stride * 3 * Self::MAX_VIEWPORTS as u64,
         )?;
 
         descriptor.update_descriptor_sets(
             &device.logical_device,
-            Self::MAX_FRAMES_INFLIGHT,
+            max_frames_inflight,
             &uniform_buffer.buffers,
             std::mem::size_of::<CameraVP>() as u64,
         )?;
 
-        // Syncronization
-        let mut semaphores_release: Vec<vk::Semaphore> =
-            Vec::with_capacity(Self::MAX_FRAMES_INFLIGHT);
+        let instance_buffers = (0..max_frames_inflight)
+            .map(|_| {
+                InstanceBuffer::new(
+                    &device.logical_device,
+                    &mut allocator,
+                    Self::INSTANCE_BUFFER_CAPACITY * std::mem::size_of::<DrawInstanceData>() as u64,
+                )
+            })
+            .collect::<Result<Vec<_>>>()?;
 
-        let mut semaphores_acquire: Vec<vk::Semaphore> =
-            Vec::with_capacity(Self::MAX_FRAMES_INFLIGHT);
+        for (set, instance_buffer) in descriptor.sets.iter().zip(&instance_buffers) {
+            descriptor::write_instance_buffer(&device.logical_device, *set, instance_buffer.buffer);
+        }
 
-        let mut fences_inflight: Vec<vk::Fence> = Vec::with_capacity(Self::MAX_FRAMES_INFLIGHT);
+        let compute_pools = (0..max_frames_inflight)
+            .map(|_| compute::create_dispatch_pool(&device.logical_device))
+            .collect::<Result<Vec<_>>>()?;
 
-        for _ in 0..Self::MAX_FRAMES_INFLIGHT {
-            semaphores_release.push(unsafe {
-                device
-                    .logical_device
-                    .create_semaphore(&vk::SemaphoreCreateInfo::default(), None)
-            }?);
+        // Syncronization: Acquire semaphores & fences per frame, release semaphores per image
+        let sync_counts = SyncCounts::new(max_frames_inflight, image_views.len());
+        let semaphores_acquire = create_semaphores(&device.logical_device, sync_counts.acquire)?;
 
-            semaphores_acquire.push(unsafe {
-                device
-                    .logical_device
-                    .create_semaphore(&vk::SemaphoreCreateInfo::default(), None)
-            }?);
+        let semaphores_release = create_semaphores(&device.logical_device, sync_counts.release)?;
+
+        let mut fences_inflight: Vec<vk::Fence> = Vec::with_capacity(max_frames_inflight);
 
+        for _ in 0..max_frames_inflight {
             fences_inflight.push(unsafe {
                 device.logical_device.create_fence(
                     &vk::FenceCreateInfo::builder().flags(vk::FenceCreateFlags::SIGNALED),
@@ -308,569 +776,4148 @@ impl Renderer {
             instance,
             device: device.logical_device,
             physical_device: device.physical_device,
+            allocator,
+            swapchain_images,
             image_views,
 
             // Extensions
             debug_utils_loader: debug_ext_loader,
             debug_messenger: debug_ext_messenger,
+            debug_sink,
             surface_loader: surface_ext.loader,
             surface: surface_ext.surface,
             swapchain_loader: swapchain_ext.loader,
             swapchain: swapchain_ext.swapchain,
+            full_screen_exclusive_loader,
+            fullscreen_mode: FullscreenMode::Windowed,
+            #[cfg(feature = "winit")]
+            exclusive_monitor: None,
+            present_wait_loader,
+            present_id: 0,
+            pending_present: None,
 
             // Descriptors
             descriptor_set_layout: descriptor.set_layout,
             descriptor_pool: descriptor.pool,
             descriptor_sets: descriptor.sets,
+            texture_set_layout: descriptor.texture_set_layout,
+            texture_pool: descriptor.texture_pool,
 
             // Graphics Pipeline
             pipeline_layout: graphics_pipeline.layout,
-            render_pass: graphics_pipeline.render_pass,
+            render_pass,
             graphics_pipeline: graphics_pipeline.pipeline,
+            sprite_pipeline_layout: sprite_pipeline.layout,
+            sprite_pipeline: sprite_pipeline.pipeline,
+            mask_pipeline,
+            sdf_pipeline,
+            wireframe_pipeline,
+            custom_pipelines: Vec::new(),
+            effect_canvas: EffectCanvas::default(),
+            compute_pipelines: Vec::new(),
+            compute_buffers: Vec::new(),
+            compute_pools,
+            compute_dispatches: Vec::new(),
+            shader_watcher,
+            debug_wireframe: false,
+            debug_overlay: false,
             graphics_queue,
+            graphics_queue_index: device.graphics_queue_index,
             present_queue,
+            present_queue_index: device.present_queue_index,
+            upload_queues,
+            surface_extent,
             viewport,
             scissor,
-            push_constant_range,
+            present_mode,
+            surface_format,
+            dynamic_rendering,
+            msaa_samples,
+            clear_values: clear_values(options.clear_color, msaa_samples),
 
             // Buffers
-            frame_buffers: frame_buffer.buffers,
+            retired_swapchains: Vec::new(),
+            frame_buffers,
+            color_target,
+            stencil_target,
+            post_process: None,
+            #[cfg(feature = "egui")]
+            egui_renderer: None,
             command_pool: draw_command_buffer.pool,
             draw_command_buffers: draw_command_buffer.buffers,
+            parallel_recorder,
             vertex_buffer: vertex_buffer.buffer,
-            vertex_buffer_memory: vertex_buffer.buffer_memory,
+            vertex_buffer_allocation: vertex_buffer.buffer_allocation,
             index_buffer: index_buffer.buffer,
-            index_buffer_memory: index_buffer.buffer_memory,
+            index_buffer_allocation: index_buffer.buffer_allocation,
+            index_type,
+            stream_buffers,
+            instance_buffers,
             uniform_buffers: uniform_buffer.buffers,
-            uniform_buffers_memory: uniform_buffer.buffers_memory,
+            uniform_buffers_allocation: uniform_buffer.buffers_allocation,
             uniform_buffers_mapped: uniform_buffer.buffers_mapped,
             uniform_buffers_mem_req: uniform_buffer.buffers_mem_req,
+            uniform_stride,
+
+            // Textures
+            textures: Vec::new(),
+            texture_sets: Vec::new(),
+
+            // Fonts
+            fonts: Vec::new(),
+            font: None,
+
+            // Resource Loading
+            resource_loader,
+            pending_textures: HashMap::new(),
+            object_watcher,
 
             // Syncronization
             semaphores_acquire,
             semaphores_release,
             fences_inflight,
+            recorder: None,
 
             // Render Loop Data
+            max_frames_inflight,
             current_frame: 0,
-            scene: Scene::new(&window, ProjectionType::Orthographic),
+            created: Instant::now(),
+            cursor_position: glm::Vec2::zeros(),
+            scene: Scene::new(
+                surface_extent.width,
+                surface_extent.height,
+                ProjectionType::Orthographic,
+            ),
+            viewports: Vec::new(),
+            culling: true,
             object_pool,
+            rectangle_object,
+            circle_object,
+            stream_pool: ObjectPool::default(),
             draw_pool: Vec::new(),
-            render_stats: RenderStats::new(),
+            persistent_pool: PersistentPool::default(),
+            flipbooks: Vec::new(),
+            layers: Layers::default(),
+            pick_targets: Vec::new(),
+            pick_index: OnceCell::new(),
+            draw_params: DrawParams::default(),
+            clip_stack: Vec::new(),
+            mask_count: 0,
+            render_stats: RenderStats::new(options.frame_limit),
+            frame_pacer: FramePacer::new(options.frame_limit),
         })
     }
 
-    /* Swapchain */
+    /* Debug Messages */
 
-    /// Recreates the [`Swapchain`] based on the `new_size`
+    /// Routes the validation messages at or above `min_severity` into `callback`
     ///
-    /// Recration occurs only when `new_size` is valid
-    pub fn recreate_swapchain(&mut self, new_size: PhysicalSize<u32>) -> Result<()> {
-        // Window Minimized -> No Recreation
-        if new_size.height == 0 || new_size.width == 0 {
-            return Ok(());
-        }
+    /// Messages are only produced with the `render_dbg` feature, by default they are printed to
+    /// stdout (or forwarded to the `log` crate with the `log` feature)
+    pub fn set_debug_sink(
+        &mut self,
+        min_severity: DebugSeverity,
+        callback: impl Fn(&DebugMessage) + Send + Sync + 'static,
+    ) -> RendererResult<()> {
+        let mut sink = self
+            .debug_sink
+            .lock()
+            .map_err(|_| anyhow!("Debug Sink: Lock is poisoned"))?;
+
+        *sink = DebugSink {
+            min_severity,
+            callback: Box::new(callback),
+        };
 
-        // Cleanup Old Swapchain
-        unsafe {
-            self.device.device_wait_idle();
-            self.swapchain_loader
-                .destroy_swapchain(self.swapchain, None);
+        Ok(())
+    }
 
-            for iv in &self.image_views {
-                self.device.destroy_image_view(*iv, None);
-            }
+    /* Wireframe */
 
-            for fb in &self.frame_buffers {
-                self.device.destroy_framebuffer(*fb, None)
-            }
+    /// Draws every object with its triangle edges only, textures are ignored
+    ///
+    /// Fails when the device does not support non solid fill modes
+    pub fn set_debug_wireframe(&mut self, enabled: bool) -> RendererResult<()> {
+        if enabled && self.wireframe_pipeline.is_none() {
+            return Err(
+                anyhow!("Wireframe: The device does not support non solid fill modes").into(),
+            );
         }
 
-        // Adjust Dynamic State
-        self.viewport.height = new_size.height as f32;
-        self.viewport.width = new_size.width as f32;
+        self.debug_wireframe = enabled;
 
-        self.scissor.extent.height = new_size.height;
-        self.scissor.extent.width = new_size.width;
+        Ok(())
+    }
 
-        // Recreate Swapchain / ImageViews / FrameBuffers
-        self.swapchain = {
-            let (min_image_count, pre_transform) = {
-                let caps = unsafe {
-                    self.surface_loader
-                        .get_physical_device_surface_capabilities(
-                            self.physical_device,
-                            self.surface,
-                        )
-                }?;
-                let mut count = caps.min_image_count + 1;
+    /// Draws the [`DebugInfo`] of every frame in the top right corner, with a swatch of the
+    /// color of the hovered instance next to the cursor
+    ///
+    /// Also toggled by [`Renderer::DEBUG_OVERLAY_KEY`] in `handle_window_event`
+    pub fn set_debug_overlay(&mut self, enabled: bool) {
+        self.debug_overlay = enabled;
+    }
 
-                if caps.max_image_count > 0 && count > caps.max_image_count {
-                    count = caps.max_image_count;
-                }
+    /// Gives back the camera, the cursor and the instance under the cursor
+    ///
+    /// The hovered instance is looked up in the current `draw_pool`, so it is only found
+    /// before the draw request
+    pub fn debug_info(&self) -> DebugInfo {
+        let hovered = self.pick(self.cursor_position).and_then(|id| {
+            self.draw_pool
+                .get(id.0)
+                .map(|draw_instance| HoveredInstance {
+                    id,
+                    color: draw_instance.color,
+                    position: draw_instance.position.xy(),
+                    rotation: draw_instance.rotation,
+                    scale: draw_instance.scale.xy(),
+                    tag: draw_instance.tag,
+                })
+        });
 
-                (count, caps.current_transform)
-            };
+        DebugInfo {
+            camera_position: self.scene.position(),
+            zoom: self.scene.zoom_level(),
+            draw_pool_size: self.draw_pool.len(),
+            cursor_world: self.scene.screen_to_world(self.cursor_position),
+            hovered,
+        }
+    }
 
-            // TODO! -> This is too strict/error prone right now, better to supplement with queried data
-            // TODO! -> Check for defaults
-            let create_info = vk::SwapchainCreateInfoKHR::builder()
-                .surface(self.surface)
-                .min_image_count(min_image_count)
-                .image_format(vk::Format::B8G8R8A8_SRGB)
-                .image_color_space(vk::ColorSpaceKHR::SRGB_NONLINEAR)
-                .image_extent(self.scissor.extent)
-                .image_array_layers(1)
-                .image_usage(vk::ImageUsageFlags::COLOR_ATTACHMENT)
-                .pre_transform(pre_transform)
-                .composite_alpha(vk::CompositeAlphaFlagsKHR::OPAQUE)
-                .present_mode(vk::PresentModeKHR::MAILBOX)
-                .image_sharing_mode(vk::SharingMode::EXCLUSIVE)
-                .clipped(true);
-
-            // TODO! -> Fix me!
-            // let create_info = if !queue_family_indices.eq_indicies()? {
-            //     create_info
-            //         .image_sharing_mode(vk::SharingMode::CONCURRENT)
-            //         .queue_family_indices(queue_family_indices.to_vec()?)
-            //     //.queue_family_indices(&queue_family_indices.to_vec()?.as_slice());
-            // } else {
-            //     create_info.image_sharing_mode(vk::SharingMode::EXCLUSIVE)
-            // };
+    /* Viewports */
 
-            unsafe { self.swapchain_loader.create_swapchain(&create_info, None) }?
-            // TODO! -> STATUS_STACK_BUFFER_OVERRUN Error
-        };
+    /// Splits the window into `regions`, each drawing the whole draw pool with its own camera
+    ///
+    /// Without regions the whole window is drawn with `scene`, which also places the
+    /// [`AnchorType::Locked`] instances in every region
+    pub fn set_viewports(&mut self, regions: &[ViewportRegion]) -> RendererResult<()> {
+        if regions.len() > Self::MAX_VIEWPORTS {
+            return Err(anyhow!(
+                "Viewports: {} regions are more than the maximum of {}",
+                regions.len(),
+                Self::MAX_VIEWPORTS
+            )
+            .into());
+        }
 
-        let swapchain_images =
-            unsafe { self.swapchain_loader.get_swapchain_images(self.swapchain) }?;
+        self.viewports = regions.to_vec();
 
-        self.image_views = {
-            let subresource_range = vk::ImageSubresourceRange::builder()
-                .aspect_mask(vk::ImageAspectFlags::COLOR)
-                .level_count(1)
-                .layer_count(1)
-                .build();
+        Ok(())
+    }
 
-            let mut image_views = Vec::new();
-            for img in swapchain_images {
-                let create_info = vk::ImageViewCreateInfo::builder()
-                    .image(img)
-                    .view_type(vk::ImageViewType::TYPE_2D)
-                    .format(vk::Format::B8G8R8A8_SRGB)
-                    .subresource_range(subresource_range);
+    /* Culling */
 
-                image_views.push(unsafe { self.device.create_image_view(&create_info, None) }?);
+    /// Skips the world space instances whose bounding box is outside of the view of every
+    /// camera, enabled by default
+    ///
+    /// Custom pipelines moving vertices outside of the bounds of their object need it disabled
+    pub fn set_culling(&mut self, enabled: bool) {
+        self.culling = enabled;
+    }
+
+    /// Removes the instances of the `draw_pool` seen by none of the cameras of `regions`
+    fn cull_instances(&mut self, regions: &[(vk::Viewport, vk::Rect2D, CameraVP)]) {
+        if !self.culling {
+            self.render_stats.last_culled_instances = 0;
+            return;
+        }
+
+        let visible_bounds: Vec<WorldRect> = regions
+            .iter()
+            .map(|(_, _, camera_vp)| camera_vp.visible_bounds())
+            .collect();
+
+        let drawn = self.draw_pool.len();
+        let (object_pool, stream_pool, layers) =
+            (&self.object_pool, &self.stream_pool, &self.layers);
+        self.draw_pool.retain(|draw_instance| {
+            // Screen space instances are placed without the world cameras
+            if draw_instance.space == Space::Screen || layers.is_screen_space(draw_instance.layer) {
+                return true;
             }
-            image_views
-        };
 
-        self.frame_buffers = buffers::FrameBuffer::new(
-            &self.device,
-            &self.image_views,
-            &self.render_pass,
-            new_size.width,
-            new_size.height,
-        )?
-        .buffers;
+            let object_data = if draw_instance.streamed {
+                &stream_pool.pool[draw_instance.object.0]
+            } else {
+                &object_pool.pool[draw_instance.object.0]
+            };
 
-        Ok(())
+            let bounds = draw_instance.world_bounds(object_data);
+            visible_bounds
+                .iter()
+                .any(|visible_bounds| visible_bounds.intersects(&bounds))
+        });
+
+        self.render_stats.last_culled_instances = drawn - self.draw_pool.len();
     }
 
-    /* Drawing */
+    /* Post-Processing */
 
-    /// Submits multiple draw commands to graphics queue based on the current `draw_pool` in
+    /// Applies the `effects` in order on every frame, drawing the frame into an offscreen
+    /// target first
     ///
-    /// 1. Fill `draw_pool` with objects to draw
-    /// 2. Call `draw_request` function to submit draw
-    /// 3. The `draw_pool` are cleared after submission
-    pub fn draw_request(&mut self, window: &winit::window::Window) -> Result<()> {
-        // Window Minimized -> No Draw
-        if window.inner_size().height == 0 || window.inner_size().width == 0 {
+    /// Without effects the frame is drawn straight into the swapchain image again
+    pub fn set_post_effects(&mut self, effects: &[PostEffect]) -> RendererResult<()> {
+        unsafe { self.device.device_wait_idle() }?;
+
+        if effects.is_empty() {
+            if let Some(mut post_process) = self.post_process.take() {
+                post_process.destroy(&self.device, &mut self.allocator);
+            }
+
             return Ok(());
         }
 
-        /////////////////// STATISTICS DRAW ///////////////////
-        self.rectangle(
-            4.5,
-            1.75,
-            0.0,
-            -1.7,
-            0.85,
-            glm::vec3(0.5, 0.5, 0.5),
-            AnchorType::Locked,
-        )?;
-        self.text(
-            &self.render_stats.as_text(),
-            1.0,
-            -2.0,
-            1.0,
-            AnchorType::Locked,
-        )?;
+        if self.dynamic_rendering {
+            return Err(anyhow!(
+                "Post Process: Effects need the render passes, dynamic rendering is enabled"
+            )
+            .into());
+        }
 
-        /////////////////// DRAW REQUEST TIMER ///////////////////
-        self.render_stats.start_draw_request_timer();
+        if self.post_process.is_none() {
+            self.post_process = Some(PostProcess::new(
+                &self.device,
+                &mut self.allocator,
+                &self.texture_set_layout,
+                self.surface_format.format,
+                self.msaa_samples,
+                self.scissor.extent,
+                &self.image_views,
+                self.color_target.as_ref(),
+                &self.stencil_target,
+            )?);
+        }
 
-        // Drawing
-        unsafe {
-            self.device.wait_for_fences(
-                std::slice::from_ref(
-                    self.fences_inflight
-                        .get(self.current_frame)
-                        .context("Inflight Fence: Index out of bounds")?,
-                ),
-                true,
-                u64::MAX,
-            );
+        if let Some(post_process) = &mut self.post_process {
+            post_process.set_effects(
+                &self.device,
+                &self.texture_set_layout,
+                self.scissor.extent,
+                effects,
+            )?;
+        }
 
-            self.device.reset_fences(std::slice::from_ref(
-                &self.fences_inflight[self.current_frame],
-            ))?;
+        Ok(())
+    }
 
-            let image_index = self
-                .swapchain_loader
-                .acquire_next_image(
-                    self.swapchain,
-                    u64::MAX,
-                    *self
-                        .semaphores_acquire
-                        .get(self.current_frame)
-                        .context("Acquire Semaphore: Index out of bounds")?,
-                    vk::Fence::null(),
-                )?
-                .0;
+    /* Egui */
+
+    /// Gives back the renderer of the egui frames, drawn over the scene and its
+    /// post-processing, created on the first call
+    #[cfg(feature = "egui")]
+    pub fn egui(&mut self) -> RendererResult<&mut EguiRenderer> {
+        let egui_renderer = match self.egui_renderer.take() {
+            Some(egui_renderer) => egui_renderer,
+            None => EguiRenderer::new(
+                &self.device,
+                &mut self.allocator,
+                self.texture_set_layout,
+                self.surface_format.format,
+                self.scissor.extent,
+                &self.image_views,
+                self.max_frames_inflight,
+            )?,
+        };
 
-            self.device.reset_command_buffer(
-                *self
-                    .draw_command_buffers
-                    .get(self.current_frame)
-                    .context("Draw Command Buffer: Index out of bounds")?,
-                vk::CommandBufferResetFlags::empty(),
-            )?;
+        Ok(self.egui_renderer.insert(egui_renderer))
+    }
 
-            self.device.begin_command_buffer(
-                self.draw_command_buffers[self.current_frame],
-                &vk::CommandBufferBeginInfo::default(),
-            )?;
+    /// Gives back the viewport regions, eg. to move their cameras
+    pub fn viewports_mut(&mut self) -> &mut [ViewportRegion] {
+        &mut self.viewports
+    }
 
-            let render_pass_begin = vk::RenderPassBeginInfo::builder()
-                .render_pass(self.render_pass)
-                .framebuffer(
-                    *self
-                        .frame_buffers
-                        .get(image_index as usize)
-                        .context("Frame Buffer: Index out of bounds")?,
-                )
-                .render_area(self.scissor)
-                .clear_values(&Self::CLEAR_VALUES);
+    /// Gives back the viewport, scissor and camera of every region drawn in this frame,
+    /// updating the projection of the cameras to the aspect ratio of their region
+    fn viewport_regions(&mut self) -> Vec<(vk::Viewport, vk::Rect2D, CameraVP)> {
+        if self.viewports.is_empty() {
+            self.scene.update_projection();
 
-            self.device.cmd_begin_render_pass(
-                self.draw_command_buffers[self.current_frame],
-                &render_pass_begin,
-                vk::SubpassContents::INLINE,
-            );
+            return vec![(self.viewport, self.scissor, self.scene.camera_vp)];
+        }
 
-            self.device.cmd_bind_pipeline(
-                self.draw_command_buffers[self.current_frame],
-                vk::PipelineBindPoint::GRAPHICS,
-                self.graphics_pipeline,
-            );
+        let extent = self.scissor.extent;
+
+        self.viewports
+            .iter_mut()
+            .map(|region| {
+                let scissor = region.scissor(extent);
+                let viewport = vk::Viewport {
+                    x: scissor.offset.x as f32,
+                    y: scissor.offset.y as f32,
+                    width: scissor.extent.width as f32,
+                    height: scissor.extent.height as f32,
+                    min_depth: 0.0,
+                    max_depth: 1.0,
+                };
+
+                region
+                    .camera
+                    .update_projection_aspect(viewport.width / viewport.height.max(1.0));
+
+                (viewport, scissor, region.camera.camera_vp)
+            })
+            .collect()
+    }
 
-            self.device.cmd_bind_vertex_buffers(
-                self.draw_command_buffers[self.current_frame],
-                0,
-                &[self.vertex_buffer],
-                &[0],
-            );
+    /* Pipelines */
 
-            self.device.cmd_bind_index_buffer(
-                self.draw_command_buffers[self.current_frame],
-                self.index_buffer,
-                0,
-                vk::IndexType::UINT16,
-            );
+    /// Creates a custom pipeline from the SPIR-V shaders of `desc`
+    ///
+    /// Instances are drawn with it while it is set as `draw_params.pipeline`
+    pub fn create_pipeline(&mut self, desc: &PipelineDesc) -> RendererResult<PipelineHandle> {
+        let shaders = ShaderCode {
+            vert: ShaderCode::from_bytes(desc.vert)?,
+            frag: ShaderCode::from_bytes(desc.frag)?,
+        };
 
-            self.device.cmd_set_viewport(
-                self.draw_command_buffers[self.current_frame],
-                0,
-                std::slice::from_ref(&self.viewport),
-            );
+        let custom_pipeline = GraphicsPipeline::new(
+            &self.device,
+            &self.pipeline_target(),
+            &[self.descriptor_set_layout, self.texture_set_layout],
+            &self.viewport,
+            &self.scissor,
+            std::mem::size_of::<Vertex>() as u32,
+            &[],
+            &shaders,
+            desc.blend,
+            self.msaa_samples,
+            vk::PolygonMode::FILL,
+            desc.topology.as_vk(),
+        )?;
+
+        self.custom_pipelines.push(custom_pipeline);
+
+        Ok(PipelineHandle(self.custom_pipelines.len() - 1))
+    }
+
+    /* Fullscreen Effects */
+
+    /// Draws the SPIR-V `frag` shader over the whole window in this frame, before or after
+    /// the shapes depending on `order`
+    ///
+    /// The shader reads the `fragment_uv` input (0.0 - 1.0) at `location = 0` and the push
+    /// constant block `vec4 params; vec2 resolution; vec2 mouse; float time;` where `params`
+    /// are the `uniforms`, `mouse` is in window pixels like `gl_FragCoord` and `time` is in
+    /// seconds since the renderer was created. The output is alpha blended over the frame.
+    ///
+    /// The pipeline of a shader is created on its first use and kept, frames with effects
+    /// are recorded without the recording threads
+    pub fn fullscreen_effect(
+        &mut self,
+        frag: &[u8],
+        uniforms: glm::Vec4,
+        order: EffectOrder,
+    ) -> RendererResult<()> {
+        let constants = EffectConstants {
+            params: uniforms,
+            resolution: glm::vec2(
+                self.surface_extent.width as f32,
+                self.surface_extent.height as f32,
+            ),
+            mouse: self.cursor_position,
+            time: self.created.elapsed().as_secs_f32(),
+        };
+
+        let pipeline = self.effect_canvas.pipeline(
+            &self.device,
+            &self.pipeline_target(),
+            &self.viewport,
+            &self.scissor,
+            self.msaa_samples,
+            frag,
+        )?;
+        self.effect_canvas.queue(pipeline, constants, order);
+
+        Ok(())
+    }
+
+    /// Moves the cursor of the fullscreen effects, in window pixels from the top left
+    ///
+    /// Only needed when the window events are not passed to `handle_window_event`
+    pub fn set_cursor_position(&mut self, position: glm::Vec2) {
+        self.cursor_position = position;
+    }
+
+    /* Compute */
+
+    /// Creates a compute pipeline from the SPIR-V `code`, reading `binding_count` storage
+    /// buffers at `set = 0` and a push constant block of `push_constant_size` bytes
+    pub fn create_compute_pipeline(
+        &mut self,
+        code: &[u8],
+        binding_count: u32,
+        push_constant_size: u32,
+    ) -> RendererResult<ComputeHandle> {
+        let compute_pipeline =
+            ComputePipeline::new(&self.device, code, binding_count, push_constant_size)?;
+
+        self.compute_pipelines.push(compute_pipeline);
+
+        Ok(ComputeHandle(self.compute_pipelines.len() - 1))
+    }
+
+    /// Creates a host visible storage buffer of `size` bytes for the compute dispatches
+    pub fn create_compute_buffer(&mut self, size: u64) -> RendererResult<ComputeBufferHandle> {
+        let compute_buffer = InstanceBuffer::new(&self.device, &mut self.allocator, size)?;
+
+        self.compute_buffers.push(compute_buffer);
+
+        Ok(ComputeBufferHandle(self.compute_buffers.len() - 1))
+    }
+
+    /// Copies `data` to the start of the buffer, waiting for the GPU to stop using it
+    pub fn write_compute_buffer<T: bytemuck::Pod>(
+        &mut self,
+        handle: ComputeBufferHandle,
+        data: &[T],
+    ) -> RendererResult<()> {
+        let compute_buffer = self
+            .compute_buffers
+            .get(handle.0)
+            .ok_or_else(|| anyhow!("Compute: Unknown buffer {:?}", handle))?;
+
+        unsafe { self.device.device_wait_idle() }?;
+
+        Ok(compute_buffer.write(data)?)
+    }
+
+    /// Gives back the first `count` elements of the buffer, waiting for the GPU to finish
+    /// the submitted dispatches
+    pub fn read_compute_buffer<T: bytemuck::Pod>(
+        &self,
+        handle: ComputeBufferHandle,
+        count: usize,
+    ) -> RendererResult<Vec<T>> {
+        let compute_buffer = self
+            .compute_buffers
+            .get(handle.0)
+            .ok_or_else(|| anyhow!("Compute: Unknown buffer {:?}", handle))?;
+
+        unsafe { self.device.device_wait_idle() }?;
+
+        Ok(compute_buffer.read(count)?)
+    }
+
+    /// Queues a dispatch of `group_count` work groups with the storage buffer `bindings`
+    ///
+    /// Dispatches are recorded before the draws of the next `draw_request` in the order they
+    /// were queued, after the instance buffer of the frame is filled, so the vertex shaders
+    /// see their writes
+    pub fn dispatch_compute(
+        &mut self,
+        handle: ComputeHandle,
+        bindings: &[ComputeBinding],
+        push_constants: &[u8],
+        group_count: [u32; 3],
+    ) -> RendererResult<()> {
+        let compute_pipeline = self
+            .compute_pipelines
+            .get(handle.0)
+            .ok_or_else(|| anyhow!("Compute: Unknown pipeline {:?}", handle))?;
+
+        compute_pipeline.validate(bindings, push_constants)?;
+
+        if let Some(ComputeBinding::Buffer(buffer)) = bindings.iter().find(|binding| {
+            matches!(binding, ComputeBinding::Buffer(buffer) if buffer.0 >= self.compute_buffers.len())
+        }) {
+            return Err(anyhow!("Compute: Unknown buffer {:?}", buffer).into());
+        }
+
+        if self.compute_dispatches.len() >= compute::MAX_DISPATCHES as usize {
+            return Err(anyhow!(
+                "Compute: More than {} dispatches in a frame",
+                compute::MAX_DISPATCHES
+            )
+            .into());
+        }
+
+        self.compute_dispatches.push(ComputeDispatch {
+            pipeline: handle,
+            bindings: bindings.to_vec(),
+            push_constants: push_constants.to_vec(),
+            group_count,
+        });
+
+        Ok(())
+    }
+
+    /* Shader Hot Reload */
+
+    /// Reloads the built-in shaders from the hot reload directory and rebuilds their pipelines
+    ///
+    /// The old pipelines are kept when any of the shaders fails to load or compile
+    pub fn reload_shaders(&mut self) -> RendererResult<()> {
+        let shader_dir = &self
+            .shader_watcher
+            .as_ref()
+            .context("Shader: Hot reload is not enabled")?
+            .shader_dir;
+
+        let shape_shaders = ShaderCode::load_source(shader_dir, "shader")?;
+        let sprite_shaders = ShaderCode::load_source(shader_dir, "sprite")?;
+        let sdf_shaders = ShaderCode::load_source(shader_dir, "sdf")?;
+
+        let shape_set_layouts = [self.descriptor_set_layout];
+        let sprite_set_layouts = [self.descriptor_set_layout, self.texture_set_layout];
+
+        let mut pipeline_descs = vec![
+            (
+                &shape_set_layouts[..],
+                &shape_shaders,
+                false,
+                vk::PolygonMode::FILL,
+            ),
+            (
+                &sprite_set_layouts[..],
+                &sprite_shaders,
+                true,
+                vk::PolygonMode::FILL,
+            ),
+            (
+                &shape_set_layouts[..],
+                &sdf_shaders,
+                true,
+                vk::PolygonMode::FILL,
+            ),
+        ];
+        if self.wireframe_pipeline.is_some() {
+            pipeline_descs.push((
+                &shape_set_layouts[..],
+                &shape_shaders,
+                false,
+                vk::PolygonMode::LINE,
+            ));
+        }
+
+        let mask_pipeline = GraphicsPipeline::mask(
+            &self.device,
+            &self.pipeline_target(),
+            &shape_set_layouts,
+            &self.viewport,
+            &self.scissor,
+            &shape_shaders,
+            self.msaa_samples,
+        )?;
+
+        let mut pipelines = Vec::new();
+        for (set_layouts, shaders, blend_enable, polygon_mode) in pipeline_descs {
+            let pipeline = GraphicsPipeline::new(
+                &self.device,
+                &self.pipeline_target(),
+                set_layouts,
+                &self.viewport,
+                &self.scissor,
+                std::mem::size_of::<Vertex>() as u32,
+                &[],
+                shaders,
+                blend_enable,
+                self.msaa_samples,
+                polygon_mode,
+                vk::PrimitiveTopology::TRIANGLE_LIST,
+            );
+
+            match pipeline {
+                Ok(pipeline) => pipelines.push(pipeline),
+                Err(error) => {
+                    pipelines.iter().for_each(|p| p.destroy(&self.device));
+                    mask_pipeline.destroy(&self.device);
+                    return Err(error.into());
+                }
+            }
+        }
+
+        // Old pipelines could still be used by frames in flight
+        unsafe { self.device.device_wait_idle() }?;
+
+        std::mem::replace(&mut self.mask_pipeline, mask_pipeline).destroy(&self.device);
+
+        let mut pipelines = pipelines.into_iter();
+
+        if let Some(pipeline) = pipelines.next() {
+            unsafe {
+                self.device.destroy_pipeline(self.graphics_pipeline, None);
+                self.device
+                    .destroy_pipeline_layout(self.pipeline_layout, None);
+            }
+            self.graphics_pipeline = pipeline.pipeline;
+            self.pipeline_layout = pipeline.layout;
+        }
+
+        if let Some(pipeline) = pipelines.next() {
+            unsafe {
+                self.device.destroy_pipeline(self.sprite_pipeline, None);
+                self.device
+                    .destroy_pipeline_layout(self.sprite_pipeline_layout, None);
+            }
+            self.sprite_pipeline = pipeline.pipeline;
+            self.sprite_pipeline_layout = pipeline.layout;
+        }
+
+        if let Some(pipeline) = pipelines.next() {
+            std::mem::replace(&mut self.sdf_pipeline, pipeline).destroy(&self.device);
+        }
+
+        if let Some(pipeline) = pipelines.next() {
+            if let Some(wireframe_pipeline) = self.wireframe_pipeline.replace(pipeline) {
+                wireframe_pipeline.destroy(&self.device);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Rebuilds the built-in pipelines when the watched shader files changed
+    ///
+    /// Failures are sent to the debug sink, so a broken shader does not stop the render loop
+    fn hot_reload_shaders(&mut self) -> Result<()> {
+        let changed = match &mut self.shader_watcher {
+            Some(shader_watcher) => shader_watcher.poll(),
+            None => false,
+        };
+
+        if !changed {
+            return Ok(());
+        }
+
+        if let Err(error) = self.reload_shaders() {
+            let sink = self
+                .debug_sink
+                .lock()
+                .map_err(|_| anyhow!("Debug Sink: Lock is poisoned"))?;
+
+            if DebugSeverity::Error >= sink.min_severity {
+                (sink.callback)(&DebugMessage {
+                    severity: DebugSeverity::Error,
+                    message_type: "Shader Hot Reload".to_string(),
+                    id_name: String::new(),
+                    id_number: 0,
+                    message: format!("{:#}", error),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /* Swapchain */
+
+    /// Recreates the [`Swapchain`] based on the `new_size`, see [`Renderer::set_surface_extent`]
+    #[cfg(feature = "winit")]
+    pub fn recreate_swapchain(&mut self, new_size: PhysicalSize<u32>) -> RendererResult<()> {
+        self.set_surface_extent(new_size.width, new_size.height)
+    }
+
+    /// Sets the size of the window in pixels and recreates the [`Swapchain`] for it
+    ///
+    /// Call it whenever the window is resized, the camera projection, picking and the screen
+    /// positions of the `scene` are based on this size
+    pub fn set_surface_extent(&mut self, width: u32, height: u32) -> RendererResult<()> {
+        self.surface_extent = vk::Extent2D { width, height };
+        self.scene.surface_extent = self.surface_extent;
+
+        self.rebuild_swapchain()
+    }
+
+    /// Recreates the [`Swapchain`] for the `surface_extent`
+    ///
+    /// Recration occurs only when the extent is valid, the size is clamped to the extents
+    /// supported by the surface. The old swapchain is handed over to the new one and destroyed
+    /// after the frames in flight are done with it
+    fn rebuild_swapchain(&mut self) -> RendererResult<()> {
+        let new_size = self.surface_extent;
+
+        // Window Minimized -> No Recreation
+        if new_size.height == 0 || new_size.width == 0 {
+            return Ok(());
+        }
+
+        let caps = unsafe {
+            self.surface_loader
+                .get_physical_device_surface_capabilities(self.physical_device, self.surface)
+        }?;
+
+        let extent = builder::swapchain_extent(&caps, new_size.width, new_size.height);
+        if extent.height == 0 || extent.width == 0 {
+            return Ok(());
+        }
+
+        // Present ids belong to the old swapchain
+        self.pending_present = None;
+
+        let stencil_target = buffers::StencilTarget::new(
+            &self.device,
+            &mut self.allocator,
+            extent.width,
+            extent.height,
+            self.stencil_target.format,
+            self.msaa_samples,
+        )?;
+
+        // Retire Old Swapchain
+        let old_swapchain = self.swapchain;
+        self.retired_swapchains.push(RetiredSwapchain {
+            swapchain: old_swapchain,
+            image_views: std::mem::take(&mut self.image_views),
+            frame_buffers: std::mem::take(&mut self.frame_buffers),
+            color_target: self.color_target.take(),
+            stencil_target: std::mem::replace(&mut self.stencil_target, stencil_target),
+            semaphores_release: std::mem::take(&mut self.semaphores_release),
+            frames_left: (0..self.max_frames_inflight).collect(),
+        });
+
+        // Adjust Dynamic State
+        self.viewport.height = extent.height as f32;
+        self.viewport.width = extent.width as f32;
+
+        self.scissor.extent = extent;
+
+        let exclusive_loader = self
+            .full_screen_exclusive_loader
+            .as_ref()
+            .filter(|_| self.fullscreen_mode == FullscreenMode::Exclusive);
+
+        // Recreate Swapchain / ImageViews / FrameBuffers
+        self.swapchain = {
+            let min_image_count = builder::swapchain_image_count(&caps, self.max_frames_inflight);
+
+            // TODO! -> This is too strict/error prone right now, better to supplement with queried data
+            // TODO! -> Check for defaults
+            let create_info = vk::SwapchainCreateInfoKHR::builder()
+                .surface(self.surface)
+                .min_image_count(min_image_count)
+                .image_format(self.surface_format.format)
+                .image_color_space(self.surface_format.color_space)
+                .image_extent(self.scissor.extent)
+                .image_array_layers(1)
+                .image_usage(builder::swapchain_usage(&caps))
+                .pre_transform(caps.current_transform)
+                .composite_alpha(vk::CompositeAlphaFlagsKHR::OPAQUE)
+                .present_mode(self.present_mode)
+                .clipped(true)
+                .old_swapchain(old_swapchain);
+
+            // Images are used by both queue families when they are separate
+            let queue_family_indices = [self.graphics_queue_index, self.present_queue_index];
+            let create_info = if self.graphics_queue_index != self.present_queue_index {
+                create_info
+                    .image_sharing_mode(vk::SharingMode::CONCURRENT)
+                    .queue_family_indices(&queue_family_indices)
+            } else {
+                create_info.image_sharing_mode(vk::SharingMode::EXCLUSIVE)
+            };
+
+            // Exclusive Fullscreen: Acquired by the application on the window's monitor
+            let mut full_screen_exclusive = vk::SurfaceFullScreenExclusiveInfoEXT::builder()
+                .full_screen_exclusive(vk::FullScreenExclusiveEXT::APPLICATION_CONTROLLED);
+
+            #[cfg(all(target_os = "windows", feature = "winit"))]
+            let mut full_screen_exclusive_win32 = {
+                use winit::platform::windows::MonitorHandleExtWindows;
+
+                vk::SurfaceFullScreenExclusiveWin32InfoEXT::builder().hmonitor(
+                    self.exclusive_monitor
+                        .as_ref()
+                        .map_or(std::ptr::null_mut(), |monitor| {
+                            monitor.hmonitor() as vk::HMONITOR
+                        }),
+                )
+            };
+
+            let create_info = if exclusive_loader.is_some() {
+                let create_info = create_info.push_next(&mut full_screen_exclusive);
+
+                #[cfg(all(target_os = "windows", feature = "winit"))]
+                let create_info = create_info.push_next(&mut full_screen_exclusive_win32);
+
+                create_info
+            } else {
+                create_info
+            };
+
+            unsafe { self.swapchain_loader.create_swapchain(&create_info, None) }?
+            // TODO! -> STATUS_STACK_BUFFER_OVERRUN Error
+        };
+
+        // Presenting works without exclusive access too, eg. while the window is not focused
+        if let Some(loader) = exclusive_loader {
+            let _ = unsafe { loader.acquire_full_screen_exclusive_mode(self.swapchain) };
+        }
+
+        self.swapchain_images =
+            unsafe { self.swapchain_loader.get_swapchain_images(self.swapchain) }?;
+
+        self.image_views = {
+            let subresource_range = vk::ImageSubresourceRange::builder()
+                .aspect_mask(vk::ImageAspectFlags::COLOR)
+                .level_count(1)
+                .layer_count(1)
+                .build();
+
+            let mut image_views = Vec::new();
+            for img in &self.swapchain_images {
+                let create_info = vk::ImageViewCreateInfo::builder()
+                    .image(*img)
+                    .view_type(vk::ImageViewType::TYPE_2D)
+                    .format(self.surface_format.format)
+                    .subresource_range(subresource_range);
+
+                image_views.push(unsafe { self.device.create_image_view(&create_info, None) }?);
+            }
+            image_views
+        };
+
+        // The image count may change with the new swapchain
+        let sync_counts = SyncCounts::new(self.max_frames_inflight, self.image_views.len());
+        self.semaphores_release = create_semaphores(&self.device, sync_counts.release)?;
+
+        if self.msaa_samples != vk::SampleCountFlags::TYPE_1 {
+            self.color_target = Some(buffers::ColorTarget::new(
+                &self.device,
+                &mut self.allocator,
+                extent.width,
+                extent.height,
+                self.surface_format.format,
+                self.msaa_samples,
+            )?);
+        }
+
+        // Dynamic rendering draws into the image views, no framebuffers to rebuild
+        self.frame_buffers = if self.dynamic_rendering {
+            Vec::new()
+        } else {
+            buffers::FrameBuffer::new(
+                &self.device,
+                &self.image_views,
+                &self.render_pass,
+                extent.width,
+                extent.height,
+                self.color_target.as_ref(),
+                Some(&self.stencil_target),
+            )?
+            .buffers
+        };
+
+        // Offscreen targets are recreated in place, the frames in flight must be done with them
+        if self.post_process.is_some() {
+            unsafe {
+                self.device
+                    .wait_for_fences(&self.fences_inflight, true, u64::MAX)
+            }?;
+        }
+
+        if let Some(post_process) = &mut self.post_process {
+            post_process.recreate(
+                &self.device,
+                &mut self.allocator,
+                self.scissor.extent,
+                &self.image_views,
+                self.color_target.as_ref(),
+                &self.stencil_target,
+            )?;
+        }
+
+        // Old egui framebuffers are destroyed with the retired swapchain
+        #[cfg(feature = "egui")]
+        if let Some(egui_renderer) = &mut self.egui_renderer {
+            let old_frame_buffers =
+                egui_renderer.recreate(&self.device, self.scissor.extent, &self.image_views)?;
+
+            if let Some(retired) = self.retired_swapchains.last_mut() {
+                retired.frame_buffers.extend(old_frame_buffers);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Switches the `window` to `mode` and recreates the swapchain for its new size
+    ///
+    /// [`FullscreenMode::Exclusive`] picks the largest video mode with the highest refresh rate
+    /// of the current monitor
+    #[cfg(feature = "winit")]
+    pub fn set_fullscreen(
+        &mut self,
+        window: &winit::window::Window,
+        mode: FullscreenMode,
+    ) -> RendererResult<()> {
+        let monitor = window.current_monitor();
+
+        let fullscreen = match mode {
+            FullscreenMode::Windowed => None,
+            FullscreenMode::Borderless => Some(Fullscreen::Borderless(monitor.clone())),
+            FullscreenMode::Exclusive => {
+                let video_mode = monitor
+                    .as_ref()
+                    .and_then(|monitor| {
+                        monitor.video_modes().max_by_key(|video_mode| {
+                            let size = video_mode.size();
+                            (
+                                size.width * size.height,
+                                video_mode.refresh_rate_millihertz(),
+                                video_mode.bit_depth(),
+                            )
+                        })
+                    })
+                    .context("Fullscreen: No video mode found on the current monitor")?;
+
+                Some(Fullscreen::Exclusive(video_mode))
+            }
+        };
+
+        // Leaving exclusive mode -> Give the monitor back before the swapchain is retired
+        if self.fullscreen_mode == FullscreenMode::Exclusive && mode != FullscreenMode::Exclusive {
+            if let Some(loader) = &self.full_screen_exclusive_loader {
+                let _ = unsafe { loader.release_full_screen_exclusive_mode(self.swapchain) };
+            }
+        }
+
+        window.set_fullscreen(fullscreen);
+        self.fullscreen_mode = mode;
+        self.exclusive_monitor = monitor.filter(|_| mode == FullscreenMode::Exclusive);
+
+        // The resize event of the window recreates it again if the size is not applied yet
+        self.recreate_swapchain(window.inner_size())
+    }
+
+    pub fn fullscreen_mode(&self) -> FullscreenMode {
+        self.fullscreen_mode
+    }
+
+    /// Recreates the swapchain on the resize events of the window, gives back whether `event`
+    /// was consumed
+    ///
+    /// The next draw request uses the new extent and projection, so redrawing after every
+    /// consumed event keeps the content matching the window during a live resize. Cursor
+    /// movements are tracked for the fullscreen effects and the debug overlay without consuming
+    /// them, [`Renderer::DEBUG_OVERLAY_KEY`] toggles the debug overlay
+    #[cfg(feature = "winit")]
+    pub fn handle_window_event(&mut self, event: &WindowEvent) -> RendererResult<bool> {
+        match event {
+            WindowEvent::Resized(new_size) => self.recreate_swapchain(*new_size)?,
+            WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
+                self.recreate_swapchain(**new_inner_size)?
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                self.cursor_position = glm::vec2(position.x as f32, position.y as f32);
+                return Ok(false);
+            }
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        state: ElementState::Pressed,
+                        virtual_keycode: Some(Self::DEBUG_OVERLAY_KEY),
+                        ..
+                    },
+                ..
+            } => self.debug_overlay = !self.debug_overlay,
+            _ => return Ok(false),
+        }
+
+        Ok(true)
+    }
+
+    /// Moves the renderer to its own thread, drawing the frames sent by the returned
+    /// [`RenderHandle`], so a slow simulation step doesn't delay the presentation
+    ///
+    /// The resize events of the window must be forwarded with [`RenderHandle::resize`]
+    pub fn spawn_thread(self) -> RenderHandle {
+        RenderHandle::spawn(self)
+    }
+
+    /* Drawing */
+
+    /// Submits multiple draw commands to graphics queue based on the current `draw_pool` in
+    ///
+    /// 1. Fill `draw_pool` with objects to draw
+    /// 2. Call `draw_request` function to submit draw
+    /// 3. The `draw_pool` are cleared after submission
+    ///
+    /// An out of date or suboptimal swapchain is recreated based on the surface extent.
+    /// Instances pointing past their pools, eg. of a stale [`ObjectHandle`], are left out of
+    /// the frame and reported as an error after it is submitted
+    pub fn draw_request(&mut self) -> RendererResult<()> {
+        // Window Minimized -> No Draw
+        if self.surface_extent.height == 0 || self.surface_extent.width == 0 {
+            return Ok(());
+        }
+
+        // Replaced scene -> Same surface
+        self.scene.surface_extent = self.surface_extent;
+
+        // Development Mode -> Rebuild pipelines of changed shaders
+        self.hot_reload_shaders()?;
+
+        /////////////////// STATISTICS DRAW ///////////////////
+        if self.render_stats.overlay && !self.render_stats.turned_off {
+            let draw_params = std::mem::replace(
+                &mut self.draw_params,
+                DrawParams {
+                    layer: Self::STATS_LAYER,
+                    space: Space::Screen,
+                    ..DrawParams::default()
+                },
+            );
+
+            // Window pixels -> Same size at any zoom, placed from the top left corner
+            let top = self.surface_extent.height as f32;
+            self.rectangle(
+                0.9 * Self::STATS_SCALE,
+                0.35 * Self::STATS_SCALE,
+                0.0,
+                60.0,
+                top - 130.0,
+                glm::vec3(0.5, 0.5, 0.5),
+                AnchorType::Unlocked,
+            )?;
+            self.text(
+                &self.render_stats.as_text(),
+                6.0,
+                top - 100.0,
+                &TextParams {
+                    scale: Self::STATS_SCALE,
+                    ..TextParams::default()
+                },
+                AnchorType::Unlocked,
+            )?;
+
+            self.draw_params = draw_params;
+        }
+
+        // Flipbooks -> Frame of the elapsed time
+        self.advance_flipbooks()?;
+
+        // Retained instances are drawn with the ones pushed this frame
+        self.draw_pool
+            .extend(self.persistent_pool.instances().cloned());
+
+        // Stale Handles -> Left out instead of a panic while drawing, reported after the frame
+        let stale = self.draw_limits().retain_valid(&mut self.draw_pool);
+
+        // Debug Overlay -> Picks from the instances of the frame before pushing its own
+        if self.debug_overlay {
+            self.draw_debug_overlay()?;
+        }
+
+        // Hidden Layers -> No Draw
+        let layers = &self.layers;
+        self.draw_pool
+            .retain(|draw_instance| layers.is_visible(draw_instance.layer));
+
+        // Frame Limit -> Wait until the frame is due
+        self.render_stats.last_pacing_time = self.frame_pacer.wait().as_micros();
+
+        // Low Latency -> Wait until the previous frame is on the screen
+        self.wait_for_present()?;
+
+        /////////////////// DRAW REQUEST TIMER ///////////////////
+        self.render_stats.start_draw_request_timer();
+
+        // Drawing
+        let needs_recreate;
+        unsafe {
+            self.device.wait_for_fences(
+                std::slice::from_ref(
+                    self.fences_inflight
+                        .get(self.current_frame)
+                        .context("Inflight Fence: Index out of bounds")?,
+                ),
+                true,
+                u64::MAX,
+            );
+
+            // The frame of this fence is done with the retired swapchains
+            self.destroy_retired_swapchains();
+
+            let (image_index, suboptimal) = match self.swapchain_loader.acquire_next_image(
+                self.swapchain,
+                u64::MAX,
+                *self
+                    .semaphores_acquire
+                    .get(self.current_frame)
+                    .context("Acquire Semaphore: Index out of bounds")?,
+                vk::Fence::null(),
+            ) {
+                Ok(acquired) => acquired,
+                // Swapchain no longer matches the surface or lost exclusive fullscreen
+                // -> Recreate & Skip Frame
+                Err(
+                    vk::Result::ERROR_OUT_OF_DATE_KHR
+                    | vk::Result::ERROR_FULL_SCREEN_EXCLUSIVE_MODE_LOST_EXT,
+                ) => {
+                    self.render_stats.stop_draw_request_timer();
+                    self.draw_pool.clear();
+                    self.end_frame_masks();
+                    self.stream_pool.clear();
+                    self.effect_canvas.clear();
+
+                    self.rebuild_swapchain()?;
+                    return Ok(stale?);
+                }
+                Err(error) => return Err(error.into()),
+            };
+
+            // Reset only after acquiring, a skipped frame must leave the fence signaled
+            self.device.reset_fences(std::slice::from_ref(
+                &self.fences_inflight[self.current_frame],
+            ))?;
+
+            // Captured frame of this slot is read back
+            if let Some(recorder) = &mut self.recorder {
+                recorder.collect(self.current_frame)?;
+            }
+
+            // Streamed geometry & instances, the buffers of this frame are no longer used by the GPU
+            let stream_index_offset = self.upload_stream()?;
+
+            /////////////////// POOL CREATION TIMER START ///////////////////
+            self.render_stats.start_pool_creation_timer();
+
+            // Keep the submitted instances for picking, before the pool is sorted
+            self.pick_targets = self.collect_pick_targets();
+            self.pick_index = OnceCell::new();
+
+            // Viewport Regions: Draw pool is recorded once for each with its own camera
+            let regions = self.viewport_regions();
+
+            // Off-Screen Instances -> No Draw
+            self.cull_instances(&regions);
+
+            let draw_batches = self.upload_instances()?;
+
+            #[cfg(feature = "egui")]
+            if let Some(egui_renderer) = &mut self.egui_renderer {
+                egui_renderer.prepare(
+                    &self.device,
+                    &mut self.allocator,
+                    &self.upload_queues,
+                    self.current_frame,
+                    self.scissor.extent,
+                )?;
+            }
+
+            self.device.reset_command_buffer(
+                *self
+                    .draw_command_buffers
+                    .get(self.current_frame)
+                    .context("Draw Command Buffer: Index out of bounds")?,
+                vk::CommandBufferResetFlags::empty(),
+            )?;
+
+            self.device.begin_command_buffer(
+                self.draw_command_buffers[self.current_frame],
+                &vk::CommandBufferBeginInfo::default(),
+            )?;
+
+            // Compute -> Dispatches run before the render pass, on the filled instance buffer
+            compute::record_dispatches(
+                &self.device,
+                self.draw_command_buffers[self.current_frame],
+                self.compute_pools[self.current_frame],
+                &self.compute_pipelines,
+                &self.compute_buffers,
+                &self.instance_buffers[self.current_frame],
+                &self.compute_dispatches,
+            )?;
+            self.compute_dispatches.clear();
+
+            // Cameras of every region, the resting and pixel cameras follow the world cameras
+            let region_commands: Vec<RegionCommands> = regions
+                .iter()
+                .enumerate()
+                .map(|(region_index, (viewport, scissor, _))| RegionCommands {
+                    viewport: *viewport,
+                    scissor: *scissor,
+                    camera_offsets: [0, 1, 2].map(|camera_index| {
+                        ((camera_index * regions.len() + region_index) as u64 * self.uniform_stride)
+                            as u32
+                    }),
+                })
+                .collect();
+
+            // Large frames are recorded by the threads into secondary command buffers
+            let parallel = self
+                .parallel_recorder
+                .as_ref()
+                .is_some_and(|recorder| recorder.is_worth_it(draw_batches.len()))
+                && self.effect_canvas.is_empty();
+            let contents = if parallel {
+                vk::SubpassContents::SECONDARY_COMMAND_BUFFERS
+            } else {
+                vk::SubpassContents::INLINE
+            };
+
+            let secondary_target = if self.dynamic_rendering {
+                self.begin_rendering(image_index as usize, parallel)?;
+
+                SecondaryTarget::Dynamic {
+                    format: self.surface_format.format,
+                    stencil_format: self.stencil_target.format,
+                    samples: self.msaa_samples,
+                }
+            } else {
+                // Post-Processing -> The frame is drawn into the first offscreen target
+                let (render_pass, frame_buffer) = match &self.post_process {
+                    Some(post_process) => (
+                        post_process.scene_render_pass,
+                        post_process.scene_frame_buffer,
+                    ),
+                    None => (
+                        self.render_pass,
+                        *self
+                            .frame_buffers
+                            .get(image_index as usize)
+                            .context("Frame Buffer: Index out of bounds")?,
+                    ),
+                };
+
+                let render_pass_begin = vk::RenderPassBeginInfo::builder()
+                    .render_pass(render_pass)
+                    .framebuffer(frame_buffer)
+                    .render_area(self.scissor)
+                    .clear_values(&self.clear_values);
+
+                self.device.cmd_begin_render_pass(
+                    self.draw_command_buffers[self.current_frame],
+                    &render_pass_begin,
+                    contents,
+                );
+
+                SecondaryTarget::RenderPass {
+                    render_pass,
+                    frame_buffer,
+                }
+            };
+
+            let descriptor_set = *self
+                .descriptor_sets
+                .get(self.current_frame)
+                .context("Descriptor Sets: Index out of bounds")?;
+            let context = self.draw_context(descriptor_set, stream_index_offset);
+
+            self.effect_canvas.record(
+                &self.device,
+                self.draw_command_buffers[self.current_frame],
+                &self.viewport,
+                &self.scissor,
+                EffectOrder::Background,
+            );
+
+            match &self.parallel_recorder {
+                Some(recorder) if parallel => {
+                    let secondaries = recorder.record(
+                        self.current_frame,
+                        &context,
+                        secondary_target,
+                        &region_commands,
+                        &draw_batches,
+                    )?;
+
+                    self.device.cmd_execute_commands(
+                        self.draw_command_buffers[self.current_frame],
+                        &secondaries,
+                    );
+                }
+                _ => {
+                    let mut recorder = CommandRecorder {
+                        context: &context,
+                        command_buffer: self.draw_command_buffers[self.current_frame],
+                    };
+
+                    for region in &region_commands {
+                        recorder.draw_region(region, &draw_batches)?;
+                    }
+                }
+            }
+
+            self.effect_canvas.record(
+                &self.device,
+                self.draw_command_buffers[self.current_frame],
+                &self.viewport,
+                &self.scissor,
+                EffectOrder::Overlay,
+            );
+
+            /////////////////// POOL CREATION TIMER STOP ///////////////////
+            self.render_stats.stop_pool_creation_timer();
+
+            if self.dynamic_rendering {
+                self.end_rendering(image_index as usize);
+            } else {
+                self.device
+                    .cmd_end_render_pass(self.draw_command_buffers[self.current_frame]);
+            }
+
+            if let Some(post_process) = &self.post_process {
+                post_process.record(
+                    &self.device,
+                    self.draw_command_buffers[self.current_frame],
+                    image_index as usize,
+                    self.scissor,
+                    &self.clear_values,
+                )?;
+            }
+
+            // Egui -> Drawn over the finished frame
+            #[cfg(feature = "egui")]
+            if let Some(egui_renderer) = &self.egui_renderer {
+                egui_renderer.record(
+                    &self.device,
+                    self.draw_command_buffers[self.current_frame],
+                    image_index as usize,
+                    self.current_frame,
+                    self.scissor.extent,
+                )?;
+            }
+
+            // Recording -> The presentable image is copied after everything is drawn
+            if let Some(recorder) = &mut self.recorder {
+                recorder.record(
+                    &self.device,
+                    &mut self.allocator,
+                    self.draw_command_buffers[self.current_frame],
+                    *self
+                        .swapchain_images
+                        .get(image_index as usize)
+                        .context("Swapchain Image: Index out of bounds")?,
+                    self.scissor.extent,
+                    self.current_frame,
+                )?;
+            }
+
+            self.device
+                .end_command_buffer(self.draw_command_buffers[self.current_frame])?;
+
+            // Cameras are placed `uniform_stride` apart, matching the dynamic offsets
+            let mut uniform_align = util::Align::new(
+                *self
+                    .uniform_buffers_mapped
+                    .get(self.current_frame)
+                    .context("Uniform Buffers Mapped: Index out of bounds")?,
+                self.uniform_stride,
+                self.uniform_buffers_mem_req
+                    .get(self.current_frame)
+                    .context("Uniform Buffers Mem Req: Index out of bounds")?
+                    .size,
+            );
+
+            uniform_align.copy_from_slice(
+                &regions
+                    .iter()
+                    .map(|(_, _, camera_vp)| *camera_vp)
+                    .chain(regions.iter().map(|(_, _, camera_vp)| camera_vp.at_rest()))
+                    .chain(
+                        regions.iter().map(|(viewport, _, _)| {
+                            CameraVP::pixels(viewport.width, viewport.height)
+                        }),
+                    )
+                    .collect::<Vec<_>>(),
+            );
+
+            let submit_info = vk::SubmitInfo::builder()
+                .wait_dst_stage_mask(std::slice::from_ref(
+                    &vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+                ))
+                .wait_semaphores(std::slice::from_ref(
+                    &self.semaphores_acquire[self.current_frame],
+                ))
+                .command_buffers(std::slice::from_ref(
+                    &self.draw_command_buffers[self.current_frame],
+                ))
+                .signal_semaphores(std::slice::from_ref(release_semaphore(
+                    &self.semaphores_release,
+                    image_index,
+                )?));
+
+            self.device.queue_submit(
+                self.graphics_queue,
+                std::slice::from_ref(&submit_info),
+                self.fences_inflight[self.current_frame],
+            )?;
+
+            let present_info = vk::PresentInfoKHR::builder()
+                .wait_semaphores(std::slice::from_ref(release_semaphore(
+                    &self.semaphores_release,
+                    image_index,
+                )?))
+                .swapchains(std::slice::from_ref(&self.swapchain))
+                .image_indices(std::slice::from_ref(&image_index));
+
+            // Low Latency: The frame is waited on by its present id before the next one
+            let present_id = self.present_id + 1;
+            let mut present_id_info =
+                vk::PresentIdKHR::builder().present_ids(std::slice::from_ref(&present_id));
+            let present_info = if self.present_wait_loader.is_some() {
+                present_info.push_next(&mut present_id_info)
+            } else {
+                present_info
+            };
+
+            needs_recreate = match self
+                .swapchain_loader
+                .queue_present(self.present_queue, &present_info)
+            {
+                Ok(present_suboptimal) => {
+                    if self.present_wait_loader.is_some() {
+                        self.present_id = present_id;
+                        self.pending_present = Some((present_id, Instant::now()));
+                    }
+
+                    suboptimal || present_suboptimal
+                }
+                Err(
+                    vk::Result::ERROR_OUT_OF_DATE_KHR
+                    | vk::Result::ERROR_FULL_SCREEN_EXCLUSIVE_MODE_LOST_EXT,
+                ) => true,
+                Err(error) => return Err(error.into()),
+            };
+
+            let frame = (self.current_frame + 1) % self.max_frames_inflight;
+            self.current_frame = frame;
+        }
+
+        /////////////////// DRAW REQUEST TIMER START ///////////////////
+        self.render_stats.stop_draw_request_timer();
+
+        /////////////////// UPDATE STATISTICS ///////////////////
+        self.update_render_stats();
+
+        // Reset Draw Pool, Streamed Geometry, Masks & Effects
+        self.draw_pool.clear();
+        self.end_frame_masks();
+        self.stream_pool.clear();
+        self.effect_canvas.clear();
+
+        // Suboptimal/Out Of Date Swapchain -> Recreate for the next frame
+        if needs_recreate {
+            self.rebuild_swapchain()?;
+        }
+
+        Ok(stale?)
+    }
+
+    /// Sorts the `draw_pool` into batches of instances sharing the same object, layer,
+    /// pipeline and texture, and copies their [`DrawInstanceData`] into the instance buffer
+    /// of the current frame
+    ///
+    /// Gives back the ranges of the batches in the sorted `draw_pool`,
+    /// the buffer is replaced by a larger one when the instances do not fit
+    fn upload_instances(&mut self) -> Result<Vec<Range<usize>>> {
+        let draw_batches = sort_into_batches(&mut self.draw_pool);
+
+        let instance_data: Vec<DrawInstanceData> = self
+            .draw_pool
+            .iter()
+            .map(|draw_instance| {
+                let object_data = if draw_instance.streamed {
+                    &self.stream_pool.pool[draw_instance.object.0]
+                } else {
+                    &self.object_pool.pool[draw_instance.object.0]
+                };
+
+                DrawInstanceData::new(draw_instance, object_data)
+            })
+            .collect();
+
+        let instances_size = std::mem::size_of_val(instance_data.as_slice()) as u64;
+
+        let instance_buffer = &mut self.instance_buffers[self.current_frame];
+        if instances_size > instance_buffer.capacity {
+            let new_instance_buffer = InstanceBuffer::new(
+                &self.device,
+                &mut self.allocator,
+                instances_size.next_power_of_two(),
+            )?;
+
+            std::mem::replace(instance_buffer, new_instance_buffer)
+                .destroy(&self.device, &mut self.allocator);
+
+            // The descriptor set of this frame is not bound yet
+            descriptor::write_instance_buffer(
+                &self.device,
+                self.descriptor_sets[self.current_frame],
+                self.instance_buffers[self.current_frame].buffer,
+            );
+        }
+
+        self.instance_buffers[self.current_frame].write(&instance_data)?;
+        self.render_stats.last_draw_calls = draw_batches.len();
+
+        Ok(draw_batches)
+    }
+
+    /// Begins dynamic rendering into the swapchain image `image_index`, multisampled frames are
+    /// drawn into the color target and resolved into the image
+    ///
+    /// With `secondary` the draw commands are executed from secondary command buffers
+    fn begin_rendering(&self, image_index: usize, secondary: bool) -> Result<()> {
+        let command_buffer = self.draw_command_buffers[self.current_frame];
+        let image = *self
+            .swapchain_images
+            .get(image_index)
+            .context("Swapchain Image: Index out of bounds")?;
+        let image_view = self.image_views[image_index];
+
+        // Previous contents are cleared, the layouts can be discarded
+        transition_image(
+            &self.device,
+            command_buffer,
+            image,
+            vk::ImageLayout::UNDEFINED,
+            vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+        );
+        if let Some(color_target) = &self.color_target {
+            transition_image(
+                &self.device,
+                command_buffer,
+                color_target.image,
+                vk::ImageLayout::UNDEFINED,
+                vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+            );
+        }
+        transition_stencil_image(&self.device, command_buffer, &self.stencil_target);
+
+        let attachment = vk::RenderingAttachmentInfo::builder()
+            .image_layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL)
+            .load_op(vk::AttachmentLoadOp::CLEAR)
+            .clear_value(self.clear_values[0]);
+
+        let attachment = match &self.color_target {
+            Some(color_target) => attachment
+                .image_view(color_target.image_view)
+                .store_op(vk::AttachmentStoreOp::DONT_CARE)
+                .resolve_mode(vk::ResolveModeFlags::AVERAGE)
+                .resolve_image_view(image_view)
+                .resolve_image_layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL),
+            None => attachment
+                .image_view(image_view)
+                .store_op(vk::AttachmentStoreOp::STORE),
+        };
+
+        let stencil_attachment = vk::RenderingAttachmentInfo::builder()
+            .image_view(self.stencil_target.image_view)
+            .image_layout(vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL)
+            .load_op(vk::AttachmentLoadOp::CLEAR)
+            .store_op(vk::AttachmentStoreOp::DONT_CARE)
+            .clear_value(STENCIL_CLEAR_VALUE);
+
+        let rendering_info = vk::RenderingInfo::builder()
+            .flags(if secondary {
+                vk::RenderingFlags::CONTENTS_SECONDARY_COMMAND_BUFFERS
+            } else {
+                vk::RenderingFlags::empty()
+            })
+            .render_area(self.scissor)
+            .layer_count(1)
+            .color_attachments(std::slice::from_ref(&attachment))
+            .stencil_attachment(&stencil_attachment);
+
+        unsafe {
+            self.device
+                .cmd_begin_rendering(command_buffer, &rendering_info)
+        };
+
+        Ok(())
+    }
+
+    /// Ends dynamic rendering and prepares the swapchain image `image_index` for presenting
+    fn end_rendering(&self, image_index: usize) {
+        let command_buffer = self.draw_command_buffers[self.current_frame];
+
+        unsafe { self.device.cmd_end_rendering(command_buffer) };
+
+        transition_image(
+            &self.device,
+            command_buffer,
+            self.swapchain_images[image_index],
+            vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+            vk::ImageLayout::PRESENT_SRC_KHR,
+        );
+    }
+
+    /// Gives back what the draw commands of the current frame read, with the cameras of
+    /// `descriptor_set` and the streamed indices at `stream_index_offset`
+    fn draw_context(
+        &self,
+        descriptor_set: vk::DescriptorSet,
+        stream_index_offset: u64,
+    ) -> DrawContext<'_> {
+        DrawContext {
+            device: &self.device,
+            draw_pool: &self.draw_pool,
+            objects: &self.object_pool.pool,
+            stream_objects: &self.stream_pool.pool,
+            layers: &self.layers,
+            pipeline_layout: self.pipeline_layout,
+            sprite_pipeline_layout: self.sprite_pipeline_layout,
+            graphics_pipeline: self.graphics_pipeline,
+            sprite_pipeline: self.sprite_pipeline,
+            mask_pipeline: self.mask_pipeline.pipeline,
+            sdf_pipeline: self.sdf_pipeline.pipeline,
+            wireframe_pipeline: self
+                .wireframe_pipeline
+                .as_ref()
+                .filter(|_| self.debug_wireframe)
+                .map(|wireframe_pipeline| wireframe_pipeline.pipeline),
+            custom_pipelines: &self.custom_pipelines,
+            texture_sets: &self.texture_sets,
+            descriptor_set,
+            vertex_buffer: self.vertex_buffer,
+            index_buffer: self.index_buffer,
+            index_type: self.index_type,
+            stream_buffer: self.stream_buffers[self.current_frame].buffer,
+            stream_index_offset,
+            framebuffer_height: self.scissor.extent.height,
+        }
+    }
+
+    /// Copies the geometry streamed for this frame into the streaming buffer of the current frame,
+    /// gives back the offset of the indices in the buffer
+    ///
+    /// The buffer is replaced by a larger one when the geometry does not fit
+    fn upload_stream(&mut self) -> Result<u64> {
+        if self.stream_pool.pool.is_empty() {
+            return Ok(0);
+        }
+
+        let stream_size = (std::mem::size_of::<Vertex>() * self.stream_pool.vertices.len()
+            + std::mem::size_of::<u32>() * self.stream_pool.indices.len())
+            as u64;
+
+        let stream_buffer = &mut self.stream_buffers[self.current_frame];
+        if stream_size > stream_buffer.capacity {
+            let new_stream_buffer = StreamingBuffer::new(
+                &self.device,
+                &mut self.allocator,
+                stream_size.next_power_of_two(),
+            )?;
+
+            std::mem::replace(stream_buffer, new_stream_buffer)
+                .destroy(&self.device, &mut self.allocator);
+        }
+
+        self.stream_buffers[self.current_frame]
+            .write(&self.stream_pool.vertices, &self.stream_pool.indices)
+    }
+
+    /* Objects */
+
+    /// Gives back the instances of the current frame, instances pushed to it are checked
+    /// against the objects, textures and pipelines of the renderer
+    pub fn draw_list(&mut self) -> DrawList<'_> {
+        let limits = self.draw_limits();
+
+        DrawList::new(&mut self.draw_pool, limits)
+    }
+
+    /// Sizes of the pools the instances of the current frame may point into
+    fn draw_limits(&self) -> DrawLimits {
+        DrawLimits {
+            objects: self.object_pool.pool.len(),
+            streamed_objects: self.stream_pool.pool.len(),
+            textures: self.textures.len(),
+            pipelines: self.custom_pipelines.len(),
+        }
+    }
+
+    /// Creates and pushes geometry which is drawn only in the current frame
+    ///
+    /// `vertices` are in world space (or relative to the camera when [`AnchorType::Locked`]),
+    /// `indices` are local to `vertices` and form a triangle list
+    pub fn mesh(
+        &mut self,
+        vertices: &[Vertex],
+        indices: &[u32],
+        color: glm::Vec3,
+        anchor_type: AnchorType,
+    ) -> RendererResult<()> {
+        let handle = self.stream_pool.push_object("mesh", vertices, indices)?;
+
+        let anchor_type = self.resolve_anchor(anchor_type);
+        let (position, rotation) = self.scene.anchor(0.0, 0.0, 0.0, anchor_type);
+
+        self.draw_pool.push(ObjectInstance {
+            position,
+            rotation,
+            skew: glm::Vec2::zeros(),
+            scale: glm::vec3(1.0, 1.0, 1.0),
+            color,
+            object: handle,
+            texture: None,
+            uv_rect: None,
+            layer: self.draw_params.layer,
+            pipeline: self.draw_params.pipeline,
+            gradient: self.draw_params.gradient,
+            streamed: true,
+            space: self.draw_params.space,
+            clip: self.draw_params.clip,
+            mask: self.draw_params.mask,
+            pivot: Pivot::Center,
+            sdf: None,
+            tag: self.draw_params.tag,
+        });
+
+        Ok(())
+    }
+
+    /// Registers a custom mesh called `name` and gives back a [`ObjectHandle`] usable by `object`
+    ///
+    /// The vertex and index buffers are reallocated to hold the new object,
+    /// so this should not be called every frame
+    pub fn register_object(
+        &mut self,
+        name: &str,
+        vertices: &[Vertex],
+        indices: &[u32],
+    ) -> RendererResult<ObjectHandle> {
+        let handle = self.object_pool.push_object(name, vertices, indices)?;
+
+        self.upload_object_pool()?;
+
+        Ok(handle)
+    }
+
+    /// Loads every object of the .obj file at `obj_path` with the colors of the .mtl file of the
+    /// same name next to it, gives back their [`ObjectHandle`]s in file order
+    ///
+    /// Vertex colors are multiplied by the color given to `object`, white keeps them unchanged
+    pub fn load_object(
+        &mut self,
+        obj_path: impl AsRef<std::path::Path>,
+    ) -> RendererResult<Vec<ObjectHandle>> {
+        let object_pool = resources::load_obj_with_mtl(obj_path.as_ref())
+            .map_err(|error| RendererError::resource_load(obj_path.as_ref(), error))?;
+        let handles = self.object_pool.append(&object_pool)?;
+
+        self.upload_object_pool()?;
+        self.object_watcher
+            .watch(obj_path.as_ref(), true, handles.clone());
+
+        Ok(handles)
+    }
+
+    /// Checks the objects for broken index ranges, degenerate triangles and missing glyphs,
+    /// see [`PoolWarning`]
+    ///
+    /// The default objects are checked at creation too, their warnings are logged with the
+    /// `log` feature
+    pub fn validate_objects(&self) -> Vec<PoolWarning> {
+        self.object_pool.validate()
+    }
+
+    /// Gives back the first object named `name`, the error lists the names of every object
+    ///
+    /// Objects are named by their .obj `o` line up to the first `_`, the default shapes are
+    /// `rectangle` and `circle`
+    pub fn find_object(&self, name: &str) -> RendererResult<ObjectHandle> {
+        Ok(self.object_pool.lookup(name)?)
+    }
+
+    /// Parses the .obj files changed on disk since they were loaded again and uploads their
+    /// objects, gives back the handles of the reloaded objects
+    ///
+    /// Every .obj file loaded by [`Renderer::load_object`], in the background or as default
+    /// objects of the [`ResourceConfig`] is watched. The objects keep their handles in file
+    /// order, so instances drawn with them pick up the change. Call it once a frame while
+    /// editing the files, the files are checked at most 4 times a second
+    pub fn reload_changed_objects(&mut self) -> RendererResult<Vec<ObjectHandle>> {
+        let mut reloaded = Vec::new();
+        let mut result = Ok(());
+
+        for file in self.object_watcher.changed() {
+            let object_pool = if file.with_mtl {
+                resources::load_obj_with_mtl(&file.path)
+            } else {
+                let obj_dir = file.path.parent().unwrap_or(std::path::Path::new(""));
+                let obj_name = file
+                    .path
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                resources::load_obj_files_from(obj_dir, &[&obj_name])
+            };
+
+            // A broken file keeps its old objects until it is saved again
+            let handles = match object_pool
+                .and_then(|object_pool| self.object_pool.replace(&file.handles, &object_pool))
+            {
+                Ok(handles) => handles,
+                Err(error) => {
+                    result = Err(RendererError::resource_load(&file.path, error));
+                    continue;
+                }
+            };
+
+            self.object_watcher.set_handles(&file.path, handles.clone());
+            reloaded.extend(handles);
+        }
+
+        if !reloaded.is_empty() {
+            self.upload_object_pool()?;
+        }
+
+        result.map(|_| reloaded)
+    }
+
+    /// Loads the filled shapes of the .svg file at `svg_path` as objects, gives back their
+    /// [`ObjectHandle`]s in document order
+    ///
+    /// The drawing fits the size of the rectangle object, every object keeps its place in the
+    /// drawing, so drawing them at the same position puts the drawing together
+    pub fn load_svg(
+        &mut self,
+        svg_path: impl AsRef<std::path::Path>,
+    ) -> RendererResult<Vec<ObjectHandle>> {
+        let object_pool = svg::load_svg(svg_path.as_ref())
+            .map_err(|error| RendererError::resource_load(svg_path.as_ref(), error))?;
+        let handles = self.object_pool.append(&object_pool)?;
+
+        self.upload_object_pool()?;
+
+        Ok(handles)
+    }
+
+    /* Background Loading */
+
+    /// Loads the objects of the .obj file at `obj_path` like [`Renderer::load_object`] on a
+    /// background thread, their handles are given back by [`Renderer::poll_loads`]
+    pub fn load_object_async(&mut self, obj_path: impl AsRef<std::path::Path>) -> LoadId {
+        self.resource_loader
+            .request(LoadRequest::Objects(obj_path.as_ref().to_path_buf()))
+    }
+
+    /// Loads the .png image at `path` like [`Renderer::load_texture`] on a background thread
+    ///
+    /// The texture is a white placeholder until [`Renderer::poll_loads`] swaps in the image,
+    /// so the handle can be drawn right away
+    pub fn load_texture_async(
+        &mut self,
+        path: impl AsRef<std::path::Path>,
+    ) -> RendererResult<(LoadId, TextureHandle)> {
+        let texture = self.create_texture(1, 1, &[255; 4])?;
+        let id = self
+            .resource_loader
+            .request(LoadRequest::Texture(path.as_ref().to_path_buf()));
+        self.pending_textures.insert(id, texture);
+
+        Ok((id, texture))
+    }
+
+    /// Number of background loads not yet given back by [`Renderer::poll_loads`]
+    pub fn pending_loads(&self) -> usize {
+        self.resource_loader.pending()
+    }
+
+    /// Uploads the finished background loads without waiting for the rest, call it once a
+    /// frame while [`Renderer::pending_loads`] is not zero
+    ///
+    /// A failed texture keeps its placeholder
+    pub fn poll_loads(&mut self) -> Vec<LoadEvent> {
+        let finished = self.resource_loader.poll();
+        self.upload_loads(finished)
+    }
+
+    /// Waits for every background load and uploads them
+    pub fn wait_loads(&mut self) -> Vec<LoadEvent> {
+        let finished = self.resource_loader.wait();
+        self.upload_loads(finished)
+    }
+
+    fn upload_loads(
+        &mut self,
+        finished: Vec<(LoadId, std::path::PathBuf, Result<Loaded>)>,
+    ) -> Vec<LoadEvent> {
+        let mut events = Vec::with_capacity(finished.len());
+        let mut objects_changed = false;
+
+        for (id, path, result) in finished {
+            let texture = self.pending_textures.remove(&id);
+
+            let result = match result {
+                Ok(Loaded::Objects(object_pool)) => self
+                    .object_pool
+                    .append(&object_pool)
+                    .map(|handles| {
+                        objects_changed = true;
+                        self.object_watcher.watch(&path, true, handles.clone());
+                        LoadedResource::Objects(handles)
+                    })
+                    .map_err(RendererError::from),
+                Ok(Loaded::DefaultObjects(object_pool)) => self
+                    .replace_default_objects(object_pool)
+                    .and_then(|_| resources::default_object_files(&path))
+                    .map(|files| {
+                        objects_changed = true;
+                        for (file_path, handles) in files {
+                            self.object_watcher.watch(&file_path, false, handles);
+                        }
+                        LoadedResource::DefaultObjects
+                    })
+                    .map_err(|error| RendererError::resource_load(&path, error)),
+                Ok(Loaded::Texture(image)) => match texture {
+                    Some(texture) => self
+                        .replace_texture(texture, &image)
+                        .map(|_| LoadedResource::Texture(texture))
+                        .map_err(RendererError::from),
+                    None => Err(anyhow!("Texture: No placeholder for {}", path.display()).into()),
+                },
+                Err(error) => Err(RendererError::resource_load(&path, error)),
+            };
+
+            events.push(LoadEvent { id, result });
+        }
+
+        if objects_changed {
+            if let Err(error) = self.upload_object_pool() {
+                // The objects can't be drawn without their buffers
+                for event in &mut events {
+                    if matches!(
+                        event.result,
+                        Ok(LoadedResource::Objects(_) | LoadedResource::DefaultObjects)
+                    ) {
+                        event.result = Err(anyhow!("{:#}", error).into());
+                    }
+                }
+            }
+        }
+
+        events
+    }
+
+    /// Swaps the embedded default objects at the start of the pool for `object_pool`, the
+    /// objects registered since keep their handles
+    fn replace_default_objects(&mut self, object_pool: ObjectPool) -> Result<()> {
+        let default_count = object_pool.pool.len();
+        let embedded_count = resources::preload(&ResourceConfig::default())?.pool.len();
+        if default_count != embedded_count {
+            return Err(anyhow!(
+                "Object: Found {} default objects instead of {}",
+                default_count,
+                embedded_count
+            ));
+        }
+
+        let registered = ObjectPool {
+            indices: self.object_pool.indices.clone(),
+            vertices: self.object_pool.vertices.clone(),
+            pool: self.object_pool.pool[embedded_count..].to_vec(),
+            atlases: Vec::new(),
+        };
+
+        let mut object_pool = object_pool;
+        object_pool.append(&registered)?;
+        object_pool.atlases = std::mem::take(&mut self.object_pool.atlases);
+        self.object_pool = object_pool;
+
+        Ok(())
+    }
+
+    /// Replaces the pixels of the placeholder `texture`, the image may have another size
+    fn replace_texture(&mut self, texture: TextureHandle, image: &ImageData) -> Result<()> {
+        let new_texture = buffers::Texture::new(
+            &self.device,
+            &mut self.allocator,
+            &self.upload_queues,
+            image.width,
+            image.height,
+            &image.pixels,
+        )?;
+
+        // The placeholder could still be sampled by a frame in flight
+        unsafe { self.device.device_wait_idle() }?;
+
+        descriptor::write_texture_set(
+            &self.device,
+            self.texture_sets[texture.0],
+            &new_texture.image_view,
+            &new_texture.sampler,
+        );
+        let placeholder = std::mem::replace(&mut self.textures[texture.0], new_texture);
+        placeholder.destroy(&self.device, &mut self.allocator);
+
+        Ok(())
+    }
+
+    /// Marks the current frame as done for the retired swapchains, destroying the ones which
+    /// are no longer used by any frame in flight
+    fn destroy_retired_swapchains(&mut self) {
+        let current_frame = self.current_frame;
+        for retired in &mut self.retired_swapchains {
+            retired.frames_left.retain(|&frame| frame != current_frame);
+        }
+
+        let (done, retired_swapchains) = std::mem::take(&mut self.retired_swapchains)
+            .into_iter()
+            .partition(|retired| retired.frames_left.is_empty());
+        self.retired_swapchains = retired_swapchains;
+
+        for retired in done {
+            retired.destroy(&self.device, &self.swapchain_loader, &mut self.allocator);
+        }
+    }
+
+    /// Gives back what the graphics pipelines draw into
+    fn pipeline_target(&self) -> RenderTarget {
+        if self.dynamic_rendering {
+            RenderTarget::Dynamic {
+                format: self.surface_format.format,
+                stencil_format: self.stencil_target.format,
+            }
+        } else {
+            RenderTarget::StencilRenderPass(self.render_pass)
+        }
+    }
+
+    /// Recreates the vertex and index buffers from the current object pool
+    fn upload_object_pool(&mut self) -> Result<()> {
+        let vertices_size =
+            (std::mem::size_of::<Vertex>() * self.object_pool.vertices.len()) as u64;
+
+        let vertex_buffer = buffers::StorageBuffer::new(
+            &self.device,
+            &mut self.allocator,
+            &self.upload_queues,
+            vertices_size,
+            DataUsage::VERTEX,
+            &self.object_pool.vertices,
+            std::mem::align_of::<f32>() as u64,
+        )?;
+
+        let (index_buffer, index_type) = create_index_buffer(
+            &self.device,
+            &mut self.allocator,
+            &self.upload_queues,
+            &self.object_pool,
+        )?;
+
+        // Old buffers could still be in use by a frame in flight
+        unsafe {
+            self.device.device_wait_idle()?;
+
+            self.device.destroy_buffer(self.index_buffer, None);
+            self.device.destroy_buffer(self.vertex_buffer, None);
+        }
+        self.allocator.free(&self.index_buffer_allocation);
+        self.allocator.free(&self.vertex_buffer_allocation);
+
+        self.vertex_buffer = vertex_buffer.buffer;
+        self.vertex_buffer_allocation = vertex_buffer.buffer_allocation;
+        self.index_buffer = index_buffer.buffer;
+        self.index_buffer_allocation = index_buffer.buffer_allocation;
+        self.index_type = index_type;
+
+        Ok(())
+    }
+
+    /* Textures */
+
+    /// Loads a .png image from `path` and uploads it as a [`TextureHandle`] usable by `sprite`
+    pub fn load_texture(&mut self, path: &str) -> RendererResult<TextureHandle> {
+        let image =
+            resources::load_png(path).map_err(|error| RendererError::resource_load(path, error))?;
+
+        self.create_texture(image.width, image.height, &image.pixels)
+    }
+
+    /// Uploads `width` x `height` RGBA8 `pixels` as a [`TextureHandle`] usable by `sprite`
+    pub fn create_texture(
+        &mut self,
+        width: u32,
+        height: u32,
+        pixels: &[u8],
+    ) -> RendererResult<TextureHandle> {
+        if self.textures.len() >= Descriptor::MAX_TEXTURES as usize {
+            return Err(anyhow!("Texture limit of {} reached", Descriptor::MAX_TEXTURES).into());
+        }
+
+        let texture = buffers::Texture::new(
+            &self.device,
+            &mut self.allocator,
+            &self.upload_queues,
+            width,
+            height,
+            pixels,
+        )?;
+
+        let texture_set = descriptor::allocate_texture_set(
+            &self.device,
+            &self.texture_pool,
+            &self.texture_set_layout,
+            &texture.image_view,
+            &texture.sampler,
+        )?;
+
+        self.textures.push(texture);
+        self.texture_sets.push(texture_set);
+
+        Ok(TextureHandle(self.textures.len() - 1))
+    }
+
+    /// Loads multiple .png images from `paths` and packs them into a single atlas texture
+    ///
+    /// Each region is named after the file stem of its image (eg. `res/img/ball.png` -> `ball`)
+    pub fn load_atlas(&mut self, paths: &[&str]) -> RendererResult<AtlasHandle> {
+        let mut images = Vec::with_capacity(paths.len());
+
+        for path in paths {
+            let name = std::path::Path::new(path)
+                .file_stem()
+                .context("Atlas: Image path has no file name")?
+                .to_string_lossy()
+                .to_string();
+
+            let image = resources::load_png(path)
+                .map_err(|error| RendererError::resource_load(path, error))?;
+            images.push((name, image));
+        }
+
+        let (regions, image) = resources::pack_atlas(&images);
+        let texture = self.create_texture(image.width, image.height, &image.pixels)?;
+
+        self.object_pool.atlases.push(Atlas {
+            texture,
+            width: image.width,
+            height: image.height,
+            regions,
+        });
+
+        Ok(AtlasHandle(self.object_pool.atlases.len() - 1))
+    }
+
+    /// Gives back the id of the region called `name` inside `atlas`
+    pub fn atlas_region(&self, atlas: AtlasHandle, name: &str) -> RendererResult<AtlasRegionId> {
+        let region = self
+            .object_pool
+            .atlases
+            .get(atlas.0)
+            .context("Atlas: Index out of bounds")?
+            .region_index(name)
+            .with_context(|| format!("Atlas: No region called {}", name))?;
+
+        Ok(AtlasRegionId {
+            atlas: atlas.0,
+            region,
+        })
+    }
+
+    /* Fonts */
+
+    /// Loads a TrueType/OpenType font from `path`, its glyphs are rasterized with `px_size` pixel high lines
+    ///
+    /// The first loaded font draws every `text` until another one is set by `set_font`
+    pub fn load_font(&mut self, path: &str, px_size: f32) -> RendererResult<FontHandle> {
+        let data = std::fs::read(path)
+            .with_context(|| format!("Font: Can't read {}", path))
+            .map_err(|error| RendererError::resource_load(path, error))?;
+
+        let texture = self.create_texture(
+            Font::INITIAL_ATLAS_SIZE,
+            Font::INITIAL_ATLAS_SIZE,
+            &Font::empty_atlas(Font::INITIAL_ATLAS_SIZE),
+        )?;
+
+        let font = Font::new(data, px_size, texture)
+            .map_err(|error| RendererError::resource_load(path, error))?;
+        self.fonts.push(font);
+
+        Ok(FontHandle(self.fonts.len() - 1))
+    }
+
+    /// Sets the font used by `text`, `None` switches back to the first loaded font
+    ///
+    /// The built-in character objects are only drawn while no font is loaded
+    pub fn set_font(&mut self, font: Option<FontHandle>) -> RendererResult<()> {
+        if let Some(handle) = font {
+            if handle.0 >= self.fonts.len() {
+                return Err(anyhow!("Font: Unknown font handle {:?}", handle).into());
+            }
+        }
+
+        self.font = font;
+
+        Ok(())
+    }
+
+    /// Font drawing `text`, the one set by `set_font` or else the first loaded one
+    fn active_font(&self) -> Option<FontHandle> {
+        self.font
+            .or_else(|| (!self.fonts.is_empty()).then_some(FontHandle(0)))
+    }
+
+    /// Rasterizes the missing characters of `text` and uploads the atlas if it changed
+    ///
+    /// A grown atlas replaces the texture, the glyphs pushed before it grew are moved to
+    /// their regions in the new one
+    fn prepare_font(&mut self, handle: FontHandle, text: &str) -> Result<()> {
+        let font = &mut self.fonts[handle.0];
+        let old_size = font.atlas_size();
+        font.prepare(text)?;
+
+        if !font.dirty {
+            return Ok(());
+        }
+
+        if font.resized {
+            let texture = font.texture;
+            let image = ImageData {
+                width: font.atlas_size(),
+                height: font.atlas_size(),
+                pixels: font.pixels().to_vec(),
+            };
+            self.replace_texture(texture, &image)?;
+
+            let scale = old_size as f32 / image.width as f32;
+            for draw_instance in self
+                .draw_pool
+                .iter_mut()
+                .chain(self.persistent_pool.instances_mut())
+                .filter(|draw_instance| draw_instance.texture == Some(texture))
+            {
+                if let Some(uv_rect) = &mut draw_instance.uv_rect {
+                    *uv_rect *= scale;
+                }
+            }
+
+            let font = &mut self.fonts[handle.0];
+            font.resized = false;
+            font.dirty = false;
+
+            return Ok(());
+        }
+
+        // The atlas may be sampled by frames in flight
+        unsafe { self.device.device_wait_idle() }?;
+
+        self.textures[font.texture.0].update(
+            &self.device,
+            &mut self.allocator,
+            &self.upload_queues,
+            font.atlas_size(),
+            font.atlas_size(),
+            font.pixels(),
+        )?;
+
+        font.dirty = false;
+
+        Ok(())
+    }
+
+    /* Creating Draw Instances */
+
+    /// Creates and pushes a text object to draw
+    ///
+    /// The text is drawn with the font set by `set_font` or the first loaded one, the built-in
+    /// character objects which only cover ASCII letters are the fallback while no font is loaded
+    ///
+    /// `x` and `y` is the anchor point of the text, the alignment in `params` decides
+    /// which corner/edge of the text block is placed there
+    pub fn text(
+        &mut self,
+        text: &str,
+        x: f32,
+        y: f32,
+        params: &TextParams,
+        anchor_type: AnchorType,
+    ) -> RendererResult<()> {
+        self.rich_text(&[TextSpan::new(text, params)], x, y, params, anchor_type)
+    }
+
+    /// Creates and pushes a text object to draw, made from `spans` of different colors
+    ///
+    /// The spans are laid out as a single text with `params`, the cursor continues across
+    /// them and lines may break inside a span. The colors of `params` are not used
+    pub fn rich_text(
+        &mut self,
+        spans: &[TextSpan],
+        x: f32,
+        y: f32,
+        params: &TextParams,
+        anchor_type: AnchorType,
+    ) -> RendererResult<()> {
+        let text: String = spans.iter().map(|span| span.text).collect();
+        let span_ends = text::span_ends(spans);
+
+        if let Some(font) = self.active_font() {
+            return Ok(self.font_text(
+                font,
+                &text,
+                spans,
+                &span_ends,
+                x,
+                y,
+                params,
+                anchor_type,
+            )?);
+        }
+
+        let layer = self.draw_params.layer;
+        let pipeline = self.draw_params.pipeline;
+        let gradient = self.draw_params.gradient;
+        let space = self.draw_params.space;
+        let clip = self.draw_params.clip;
+        let mask = self.draw_params.mask;
+        let tag = self.draw_params.tag;
+        let anchor_type = self.resolve_anchor(anchor_type);
+        let pool = &self.object_pool.pool;
+        let placements = text::layout_text(&text, params, |c| {
+            let char_index = *resources::CHAR_OBJECT_POOL.get(c as usize)?;
+
+            match char_index {
+                // There are no corresponding character object / New lines are handled by the layout
+                255 | 253 => None,
+                // Space
+                254 => Some(text::Glyph {
+                    id: None,
+                    offset_x: 0.0,
+                    offset_y: 0.0,
+                    advance: text::SPACE_ADVANCE,
+                }),
+                _ => {
+                    let object_data = pool.get(char_index as usize)?;
+
+                    Some(text::Glyph {
+                        id: Some(char_index as usize),
+                        offset_x: -object_data.bounds_min.x,
+                        offset_y: 0.0,
+                        advance: object_data.width() + text::CHAR_SPACING,
+                    })
+                }
+            }
+        });
+
+        let scene = &self.scene;
+        let glyphs = placements
+            .into_iter()
+            .map(|placement| {
+                let span = &spans[text::span_at(&span_ends, placement.index)];
+                let (position, rotation) = scene.anchor(
+                    x + placement.position.x,
+                    y + placement.position.y,
+                    0.0,
+                    anchor_type,
+                );
+
+                let glyph = ObjectInstance {
+                    position,
+                    rotation,
+                    skew: glm::Vec2::zeros(),
+                    scale: glm::vec3(params.scale, params.scale, 0.0),
+                    color: span.color,
+                    object: ObjectHandle(placement.id),
+                    layer,
+                    pipeline,
+                    gradient,
+                    streamed: false,
+                    space,
+                    clip,
+                    mask,
+                    tag,
+                    ..ObjectInstance::default()
+                };
+
+                (glyph, span.outline)
+            })
+            .collect();
+
+        self.push_glyphs(glyphs, params.scale);
+
+        Ok(())
+    }
+
+    /// Pushes the characters of `text` as textured rectangles showing the glyphs of `font`
+    fn font_text(
+        &mut self,
+        font: FontHandle,
+        text: &str,
+        spans: &[TextSpan],
+        span_ends: &[usize],
+        x: f32,
+        y: f32,
+        params: &TextParams,
+        anchor_type: AnchorType,
+    ) -> Result<()> {
+        self.prepare_font(font, text)?;
+
+        let font = &self.fonts[font.0];
+        let placements = text::layout_text(text, params, |c| font.layout_glyph(c));
+
+        let rectangle_object = self.rectangle_object;
+        let layer = self.draw_params.layer;
+        let pipeline = self.draw_params.pipeline;
+        let gradient = self.draw_params.gradient;
+        let space = self.draw_params.space;
+        let clip = self.draw_params.clip;
+        let mask = self.draw_params.mask;
+        let tag = self.draw_params.tag;
+        let anchor_type = self.resolve_anchor(anchor_type);
+        let scene = &self.scene;
+
+        let glyphs = placements
+            .into_iter()
+            .filter_map(|placement| {
+                let span = &spans[text::span_at(span_ends, placement.index)];
+                let glyph = font.glyph(char::from_u32(placement.id as u32)?)?;
+                let (position, rotation) = scene.anchor(
+                    x + placement.position.x,
+                    y + placement.position.y,
+                    0.0,
+                    anchor_type,
+                );
+
+                let glyph = ObjectInstance {
+                    position,
+                    rotation,
+                    skew: glm::Vec2::zeros(),
+                    scale: glm::vec3(
+                        glyph.size.x * params.scale / shapes::RECTANGLE_SIZE,
+                        glyph.size.y * params.scale / shapes::RECTANGLE_SIZE,
+                        0.0,
+                    ),
+                    color: span.color,
+                    object: rectangle_object,
+                    texture: Some(font.texture),
+                    uv_rect: glyph.uv_rect,
+                    layer,
+                    pipeline,
+                    gradient,
+                    streamed: false,
+                    space,
+                    clip,
+                    mask,
+                    pivot: Pivot::Center,
+                    sdf: None,
+                    tag,
+                };
+
+                Some((glyph, span.outline))
+            })
+            .collect();
+
+        self.push_glyphs(glyphs, params.scale);
+
+        Ok(())
+    }
+
+    /// Pushes the `glyphs` of a text, glyphs with an outline color are preceded by copies in
+    /// that color, offset in eight directions
+    fn push_glyphs(&mut self, glyphs: Vec<(ObjectInstance, Option<glm::Vec3>)>, scale: f32) {
+        let offset = text::OUTLINE_WIDTH * scale;
+
+        for direction in 0..8 {
+            let angle = direction as f32 * std::f32::consts::FRAC_PI_4;
+            let shift = glm::vec3(angle.cos() * offset, angle.sin() * offset, 0.0);
+
+            self.draw_pool
+                .extend(glyphs.iter().filter_map(|(glyph, outline)| {
+                    Some(ObjectInstance {
+                        position: glyph.position + shift,
+                        color: (*outline)?,
+                        gradient: None,
+                        ..glyph.clone()
+                    })
+                }));
+        }
+
+        self.draw_pool
+            .extend(glyphs.into_iter().map(|(glyph, _)| glyph));
+    }
+
+    /// Pushes every visible node of `scene_graph` to draw, call it once per frame
+    ///
+    /// The world transforms of changed nodes are updated first
+    pub fn draw_scene(&mut self, scene_graph: &mut SceneGraph) -> RendererResult<()> {
+        scene_graph.update();
+
+        for (node, anchor_type) in scene_graph.visible_nodes() {
+            let Transform {
+                position,
+                rotation,
+                scale,
+            } = *node.world_transform();
+
+            match node.shape() {
+                Some(NodeShape::Circle { color }) => {
+                    self.circle(scale.x, position.x, position.y, *color, anchor_type)?
+                }
+                Some(NodeShape::Rectangle { color }) => self.rectangle(
+                    scale.x,
+                    scale.y,
+                    rotation,
+                    position.x,
+                    position.y,
+                    *color,
+                    anchor_type,
+                )?,
+                Some(NodeShape::Object { object, color }) => self.object(
+                    *object,
+                    scale.x,
+                    scale.y,
+                    rotation,
+                    position.x,
+                    position.y,
+                    *color,
+                    anchor_type,
+                )?,
+                Some(NodeShape::Sprite { texture }) => self.sprite(
+                    *texture,
+                    scale.x,
+                    scale.y,
+                    rotation,
+                    position.x,
+                    position.y,
+                    anchor_type,
+                )?,
+                Some(NodeShape::Text { text, params }) => {
+                    let params = TextParams {
+                        scale: params.scale * scale.x,
+                        ..*params
+                    };
+
+                    self.text(text, position.x, position.y, &params, anchor_type)?
+                }
+                None => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Pushes the objects of a recorded `command` to draw, calling its matching function
+    pub fn draw_command(&mut self, command: &DrawCommand) -> RendererResult<()> {
+        match command {
+            DrawCommand::Circle {
+                scale,
+                center_x,
+                center_y,
+                color,
+                anchor_type,
+            } => self.circle(*scale, *center_x, *center_y, *color, *anchor_type),
+            DrawCommand::Rectangle {
+                scale_x,
+                scale_y,
+                rotation,
+                center_x,
+                center_y,
+                color,
+                anchor_type,
+            } => self.rectangle(
+                *scale_x,
+                *scale_y,
+                *rotation,
+                *center_x,
+                *center_y,
+                *color,
+                *anchor_type,
+            ),
+            DrawCommand::Line {
+                from,
+                to,
+                thickness,
+                color,
+                anchor_type,
+            } => self.line(*from, *to, *thickness, *color, *anchor_type),
+            DrawCommand::Polyline {
+                points,
+                thickness,
+                color,
+                anchor_type,
+            } => self.polyline(points, *thickness, *color, *anchor_type),
+            DrawCommand::Object {
+                object,
+                scale_x,
+                scale_y,
+                rotation,
+                center_x,
+                center_y,
+                color,
+                anchor_type,
+            } => self.object(
+                *object,
+                *scale_x,
+                *scale_y,
+                *rotation,
+                *center_x,
+                *center_y,
+                *color,
+                *anchor_type,
+            ),
+            DrawCommand::Sprite {
+                texture,
+                scale_x,
+                scale_y,
+                rotation,
+                center_x,
+                center_y,
+                anchor_type,
+            } => self.sprite(
+                *texture,
+                *scale_x,
+                *scale_y,
+                *rotation,
+                *center_x,
+                *center_y,
+                *anchor_type,
+            ),
+            DrawCommand::Text {
+                text,
+                x,
+                y,
+                params,
+                anchor_type,
+            } => self.text(text, *x, *y, params, *anchor_type),
+            DrawCommand::UseLayer(name) => self.use_layer(name),
+            DrawCommand::PushClip(rect) => {
+                self.push_clip(*rect);
+                Ok(())
+            }
+            DrawCommand::PopClip => {
+                self.pop_clip();
+                Ok(())
+            }
+            DrawCommand::BeginMask => self.begin_mask(),
+            DrawCommand::EndMask => {
+                self.end_mask();
+                Ok(())
+            }
+            DrawCommand::ClearMask => {
+                self.clear_mask();
+                Ok(())
+            }
+        }
+    }
+
+    /// Creates and pushes a [`Shape`] of plain data to draw, eg. `(center, radius, color)`
+    pub fn add_shape(&mut self, shape: impl Shape, anchor_type: AnchorType) -> RendererResult<()> {
+        shape.draw(self, anchor_type)
+    }
+
+    /// Creates and pushes every [`Shape`] of `shapes` to draw, stops at the first error
+    ///
+    /// Same as [`Renderer::extend_shapes`]
+    pub fn add_shapes<S: Shape>(
+        &mut self,
+        shapes: impl IntoIterator<Item = S>,
+        anchor_type: AnchorType,
+    ) -> RendererResult<()> {
+        self.extend_shapes(shapes, anchor_type)
+    }
+
+    /// Creates and pushes every [`Shape`] of `shapes` to draw, stops at the first error
+    ///
+    /// Reserves room for the lower bound of the size hint of `shapes` up front, each shape is
+    /// still drawn like by [`Renderer::add_shape`]. Scenes pushing many shapes in separate calls
+    /// can reserve the same way with [`DrawList::reserve`], the capacity is kept across frames
+    pub fn extend_shapes<S: Shape>(
+        &mut self,
+        shapes: impl IntoIterator<Item = S>,
+        anchor_type: AnchorType,
+    ) -> RendererResult<()> {
+        let mut shapes = shapes.into_iter();
+        self.draw_pool.reserve(shapes.size_hint().0);
+
+        shapes.try_for_each(|shape| shape.draw(self, anchor_type))
+    }
+
+    /// Creates and pushes a circle object to draw
+    pub fn circle(
+        &mut self,
+        scale: f32,
+        center_x: f32,
+        center_y: f32,
+        color: glm::Vec3,
+        anchor_type: AnchorType,
+    ) -> RendererResult<()> {
+        let anchor_type = self.resolve_anchor(anchor_type);
+        let (position, rotation) = self.scene.anchor(center_x, center_y, 0.0, anchor_type);
+
+        // SDF -> Rectangle object rounded into a circle
+        let sdf = self.uses_sdf().then(|| SdfShape {
+            corner_radius: 0.5 * shapes::CIRCLE_SIZE * scale.abs(),
+            border: 0.0,
+        });
+
+        self.draw_pool.push(ObjectInstance {
+            position,
+            rotation, // <- Matters only if has a texture
+            skew: glm::Vec2::zeros(),
+            scale: glm::vec3(scale, scale, 0.0),
+            color,
+            object: match sdf {
+                Some(_) => self.rectangle_object,
+                None => self.circle_object,
+            },
+            texture: None,
+            uv_rect: None,
+            layer: self.draw_params.layer,
+            pipeline: self.draw_params.pipeline,
+            gradient: self.draw_params.gradient,
+            streamed: false,
+            space: self.draw_params.space,
+            clip: self.draw_params.clip,
+            mask: self.draw_params.mask,
+            pivot: self.draw_params.pivot,
+            sdf,
+            tag: self.draw_params.tag,
+        });
+
+        Ok(())
+    }
+
+    /// Creates and pushes a rectangle object to draw
+    pub fn rectangle(
+        &mut self,
+        scale_x: f32,
+        scale_y: f32,
+        rotation: f32,
+        center_x: f32,
+        center_y: f32,
+        color: glm::Vec3,
+        anchor_type: AnchorType,
+    ) -> RendererResult<()> {
+        let anchor_type = self.resolve_anchor(anchor_type);
+        let (position, rotation) = self.scene.anchor(center_x, center_y, rotation, anchor_type);
+
+        self.draw_pool.push(ObjectInstance {
+            position,
+            rotation,
+            skew: glm::Vec2::zeros(),
+            scale: glm::vec3(scale_x, scale_y, 0.0),
+            color,
+            object: self.rectangle_object,
+            texture: None,
+            uv_rect: None,
+            layer: self.draw_params.layer,
+            pipeline: self.draw_params.pipeline,
+            gradient: self.draw_params.gradient,
+            streamed: false,
+            space: self.draw_params.space,
+            clip: self.draw_params.clip,
+            mask: self.draw_params.mask,
+            pivot: self.draw_params.pivot,
+            sdf: None,
+            tag: self.draw_params.tag,
+        });
+
+        Ok(())
+    }
+
+    /// Creates and pushes a line object to draw between `from` and `to`
+    ///
+    /// Dashed and round capped with `draw_params.line_style`
+    pub fn line(
+        &mut self,
+        from: glm::Vec2,
+        to: glm::Vec2,
+        thickness: f32,
+        color: glm::Vec3,
+        anchor_type: AnchorType,
+    ) -> RendererResult<()> {
+        self.polyline(&[from, to], thickness, color, anchor_type)
+    }
+
+    /// Creates and pushes connected line objects to draw through `points`
+    ///
+    /// The dashes, caps and the joints between the segments follow `draw_params.line_style`,
+    /// the joints are filled with circles by default. Mitered joints are streamed
+    pub fn polyline(
+        &mut self,
+        points: &[glm::Vec2],
+        thickness: f32,
+        color: glm::Vec3,
+        anchor_type: AnchorType,
+    ) -> RendererResult<()> {
+        let style = self.draw_params.line_style;
+
+        // Placed by their center, independent of the pivot
+        let previous_pivot = std::mem::take(&mut self.draw_params.pivot);
+
+        let result = shapes::dashes(points, style.dash_length, style.gap_length)
+            .iter()
+            .try_for_each(|dash| self.dash(dash, thickness, color, &style, anchor_type));
+
+        self.draw_params.pivot = previous_pivot;
+        result
+    }
+
+    /// Creates and pushes the segments, joints and caps of a single dash through `points`
+    fn dash(
+        &mut self,
+        points: &[glm::Vec2],
+        thickness: f32,
+        color: glm::Vec3,
+        style: &LineStyle,
+        anchor_type: AnchorType,
+    ) -> RendererResult<()> {
+        for segment in points.windows(2) {
+            let quad = shapes::line_quad(segment[0], segment[1]);
+
+            self.rectangle(
+                quad.length / shapes::RECTANGLE_SIZE,
+                thickness / shapes::RECTANGLE_SIZE,
+                quad.rotation,
+                quad.center.x,
+                quad.center.y,
+                color,
+                anchor_type,
+            )?;
+        }
+
+        for corner in points.windows(3) {
+            match style.joint {
+                LineJoint::Round => self.circle(
+                    thickness / shapes::CIRCLE_SIZE,
+                    corner[1].x,
+                    corner[1].y,
+                    color,
+                    anchor_type,
+                )?,
+                LineJoint::Miter => {
+                    let wedge = shapes::miter_joint(corner[0], corner[1], corner[2], thickness);
+                    let indices = shapes::triangulate(&wedge);
+
+                    self.shape(&wedge, &indices, glm::Vec2::zeros(), color, anchor_type)?;
+                }
+            }
+        }
+
+        if style.cap == LineCap::Round {
+            for end in [points.first(), points.last()].into_iter().flatten() {
+                self.circle(
+                    thickness / shapes::CIRCLE_SIZE,
+                    end.x,
+                    end.y,
+                    color,
+                    anchor_type,
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Creates and pushes the lines of a grid with `spacing` to draw, covering `extent` around
+    /// the camera in every direction
+    ///
+    /// The lines stay on the multiples of `spacing` while the camera pans and are drawn in a
+    /// single instanced batch
+    pub fn draw_grid(&mut self, spacing: f32, color: glm::Vec3, extent: f32) -> RendererResult<()> {
+        if spacing <= 0.0 {
+            return Err(anyhow!("Grid: Spacing must be positive, got {}", spacing).into());
+        }
+
+        let center = self.scene.view_to_world(glm::vec2(0.0, 0.0));
+
+        for (from, to) in shapes::grid_lines(center, spacing, extent) {
+            self.line(
+                from,
+                to,
+                shapes::GRID_THICKNESS,
+                color,
+                AnchorType::Unlocked,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Creates and pushes the x (red) and y (green) axes starting at `origin` to draw
+    pub fn draw_axes(&mut self, origin: glm::Vec2, length: f32) -> RendererResult<()> {
+        for (direction, color) in [
+            (glm::vec2(length, 0.0), glm::vec3(1.0, 0.0, 0.0)),
+            (glm::vec2(0.0, length), glm::vec3(0.0, 1.0, 0.0)),
+        ] {
+            self.line(
+                origin,
+                origin + direction,
+                shapes::AXIS_THICKNESS,
+                color,
+                AnchorType::Unlocked,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Creates and pushes a polygon through `points` to draw, filled or as an outline
+    ///
+    /// Convex and concave outlines are triangulated on the CPU and streamed for the current frame
+    pub fn polygon(
+        &mut self,
+        points: &[glm::Vec2],
+        color: glm::Vec3,
+        filled: bool,
+        anchor_type: AnchorType,
+    ) -> RendererResult<()> {
+        if !filled {
+            return self.polygon_outline(points, shapes::OUTLINE_THICKNESS, color, anchor_type);
+        }
+
+        let indices = shapes::triangulate(points);
+
+        Ok(self.shape(points, &indices, glm::vec2(0.0, 0.0), color, anchor_type)?)
+    }
+
+    /// Creates and pushes the closed outline of a polygon through `points` to draw
+    pub fn polygon_outline(
+        &mut self,
+        points: &[glm::Vec2],
+        thickness: f32,
+        color: glm::Vec3,
+        anchor_type: AnchorType,
+    ) -> RendererResult<()> {
+        let (outline, indices) = shapes::polygon_outline(points, thickness);
+
+        Ok(self.shape(&outline, &indices, glm::vec2(0.0, 0.0), color, anchor_type)?)
+    }
+
+    /// Creates and pushes a ring of `thickness` inside the edge of a circle of `radius` to draw
+    ///
+    /// The ring is generated with `draw_params.segments`, so its radii are exact at any size
+    pub fn circle_border(
+        &mut self,
+        radius: f32,
+        thickness: f32,
+        center: glm::Vec2,
+        color: glm::Vec3,
+        anchor_type: AnchorType,
+    ) -> RendererResult<()> {
+        if self.uses_sdf() {
+            let shape = SdfShape {
+                corner_radius: radius,
+                border: thickness,
+            };
+            let size = glm::vec2(2.0 * radius, 2.0 * radius);
+
+            return self.sdf_shape(size, shape, center, color, anchor_type);
+        }
+
+        let (vertices, indices) =
+            shapes::generate::circle_border(radius, thickness, self.segments());
+
+        self.shape(&vertices, &indices, center, color, anchor_type)?;
+        self.set_pick_shape(PickShape::EllipseBorder(thickness));
+
+        Ok(())
+    }
+
+    /// Creates and pushes a rectangle of `size` with circular corners of `corner_radius` to draw
+    ///
+    /// The mesh is generated for `size`, so the corners stay circular at any aspect ratio
+    pub fn rounded_rectangle(
+        &mut self,
+        size: glm::Vec2,
+        corner_radius: f32,
+        center: glm::Vec2,
+        color: glm::Vec3,
+        anchor_type: AnchorType,
+    ) -> RendererResult<()> {
+        if self.uses_sdf() {
+            let shape = SdfShape {
+                corner_radius,
+                border: 0.0,
+            };
+
+            return self.sdf_shape(size, shape, center, color, anchor_type);
+        }
+
+        let (vertices, indices) =
+            shapes::generate::rounded_rectangle(size.x, size.y, corner_radius, self.segments());
+
+        Ok(self.shape(&vertices, &indices, center, color, anchor_type)?)
+    }
+
+    /// Creates and pushes a border of `thickness` inside the edge of a rounded rectangle to draw
+    ///
+    /// A `corner_radius` of `0.0` gives the border of a plain rectangle
+    pub fn rounded_rectangle_border(
+        &mut self,
+        size: glm::Vec2,
+        corner_radius: f32,
+        thickness: f32,
+        center: glm::Vec2,
+        color: glm::Vec3,
+        anchor_type: AnchorType,
+    ) -> RendererResult<()> {
+        if self.uses_sdf() {
+            let shape = SdfShape {
+                corner_radius,
+                border: thickness,
+            };
+
+            return self.sdf_shape(size, shape, center, color, anchor_type);
+        }
+
+        let (vertices, indices) = shapes::generate::rounded_rectangle_border(
+            size.x,
+            size.y,
+            corner_radius,
+            thickness,
+            self.segments(),
+        );
+
+        Ok(self.shape(&vertices, &indices, center, color, anchor_type)?)
+    }
+
+    /// Creates and pushes an ellipse of `radii` along the x and y axes to draw
+    ///
+    /// The mesh is generated for the `radii` with `draw_params.segments`, picking hits the
+    /// ellipse instead of its bounding box
+    pub fn ellipse(
+        &mut self,
+        radii: glm::Vec2,
+        center: glm::Vec2,
+        color: glm::Vec3,
+        anchor_type: AnchorType,
+    ) -> RendererResult<()> {
+        let (vertices, indices) = shapes::generate::ellipse(radii, self.segments());
+
+        self.shape(&vertices, &indices, center, color, anchor_type)?;
+        self.set_pick_shape(PickShape::Ellipse);
+
+        Ok(())
+    }
+
+    /// Creates and pushes a border of `thickness` inside the edge of an ellipse of `radii` to
+    /// draw, picking hits the border only
+    pub fn ellipse_border(
+        &mut self,
+        radii: glm::Vec2,
+        thickness: f32,
+        center: glm::Vec2,
+        color: glm::Vec3,
+        anchor_type: AnchorType,
+    ) -> RendererResult<()> {
+        let (vertices, indices) =
+            shapes::generate::ellipse_border(radii, thickness, self.segments());
+
+        self.shape(&vertices, &indices, center, color, anchor_type)?;
+        self.set_pick_shape(PickShape::EllipseBorder(thickness));
+
+        Ok(())
+    }
+
+    /// Creates and pushes a circle of `radius` filled with `fill` and its `border` to draw
+    ///
+    /// The border is drawn over the fill, both are centered regardless of `draw_params.pivot`
+    pub fn circle_outlined(
+        &mut self,
+        radius: f32,
+        center: glm::Vec2,
+        fill: glm::Vec3,
+        border: Border,
+        anchor_type: AnchorType,
+    ) -> RendererResult<()> {
+        let previous_pivot = std::mem::take(&mut self.draw_params.pivot);
+
+        let result = self
+            .circle(
+                2.0 * radius / shapes::CIRCLE_SIZE,
+                center.x,
+                center.y,
+                fill,
+                anchor_type,
+            )
+            .and_then(|()| {
+                self.circle_border(radius, border.width, center, border.color, anchor_type)
+            });
+
+        self.draw_params.pivot = previous_pivot;
+        result
+    }
+
+    /// Creates and pushes a rounded rectangle of `size` filled with `fill` and its `border` to
+    /// draw, see [`Renderer::circle_outlined`]
+    pub fn rounded_rectangle_outlined(
+        &mut self,
+        size: glm::Vec2,
+        corner_radius: f32,
+        center: glm::Vec2,
+        fill: glm::Vec3,
+        border: Border,
+        anchor_type: AnchorType,
+    ) -> RendererResult<()> {
+        let previous_pivot = std::mem::take(&mut self.draw_params.pivot);
+
+        let result = self
+            .rounded_rectangle(size, corner_radius, center, fill, anchor_type)
+            .and_then(|()| {
+                self.rounded_rectangle_border(
+                    size,
+                    corner_radius,
+                    border.width,
+                    center,
+                    border.color,
+                    anchor_type,
+                )
+            });
+
+        self.draw_params.pivot = previous_pivot;
+        result
+    }
+
+    /// Creates and pushes an ellipse of `radii` filled with `fill` and its `border` to draw,
+    /// see [`Renderer::circle_outlined`]
+    pub fn ellipse_outlined(
+        &mut self,
+        radii: glm::Vec2,
+        center: glm::Vec2,
+        fill: glm::Vec3,
+        border: Border,
+        anchor_type: AnchorType,
+    ) -> RendererResult<()> {
+        let previous_pivot = std::mem::take(&mut self.draw_params.pivot);
+
+        let result = self
+            .ellipse(radii, center, fill, anchor_type)
+            .and_then(|()| {
+                self.ellipse_border(radii, border.width, center, border.color, anchor_type)
+            });
+
+        self.draw_params.pivot = previous_pivot;
+        result
+    }
+
+    /// Creates and pushes a ring sector of `thickness` inside a circle of `radius` to draw,
+    /// between the `angles` in degrees
+    ///
+    /// Angles are counted counter-clockwise from the positive x axis, a decreasing range goes
+    /// clockwise. A `thickness` of `radius` gives a pie slice, eg. a progress ring filling
+    /// clockwise from the top is `arc(r, t, 90.0..90.0 - 360.0 * progress, ..)`
+    pub fn arc(
+        &mut self,
+        radius: f32,
+        thickness: f32,
+        angles: Range<f32>,
+        center: glm::Vec2,
+        color: glm::Vec3,
+        anchor_type: AnchorType,
+    ) -> RendererResult<()> {
+        let (vertices, indices) = shapes::generate::ring_sector(
+            radius,
+            thickness,
+            angles.start,
+            angles.end - angles.start,
+            self.segments(),
+        );
+
+        Ok(self.shape(&vertices, &indices, center, color, anchor_type)?)
+    }
+
+    /// Creates and pushes a capsule of `size` to draw, a rectangle with half circles on its
+    /// shorter sides
+    pub fn capsule(
+        &mut self,
+        size: glm::Vec2,
+        center: glm::Vec2,
+        color: glm::Vec3,
+        anchor_type: AnchorType,
+    ) -> RendererResult<()> {
+        if self.uses_sdf() {
+            let shape = SdfShape {
+                corner_radius: size.x.min(size.y) * 0.5,
+                border: 0.0,
+            };
+
+            return self.sdf_shape(size, shape, center, color, anchor_type);
+        }
+
+        let (vertices, indices) = shapes::generate::capsule(size.x, size.y, self.segments());
+
+        Ok(self.shape(&vertices, &indices, center, color, anchor_type)?)
+    }
+
+    /// Creates and pushes an isosceles triangle of `size` to draw, its tip is up at a
+    /// `rotation` of `0.0` degrees, eg. as a direction indicator
+    pub fn triangle(
+        &mut self,
+        size: glm::Vec2,
+        rotation: f32,
+        center: glm::Vec2,
+        color: glm::Vec3,
+        anchor_type: AnchorType,
+    ) -> RendererResult<()> {
+        let (mut vertices, indices) = shapes::generate::triangle(size.x, size.y);
+        vertices
+            .iter_mut()
+            .for_each(|vertex| *vertex = glm::rotate_vec2(vertex, rotation.to_radians()));
+
+        Ok(self.shape(&vertices, &indices, center, color, anchor_type)?)
+    }
+
+    /// `true` if the shapes are drawn by the SDF pipeline, see [`DrawParams::sdf`]
+    fn uses_sdf(&self) -> bool {
+        self.draw_params.sdf
+            && self.draw_params.pipeline.is_none()
+            && self
+                .draw_params
+                .mask
+                .is_none_or(|mask| mask.role != MaskRole::Shape)
+    }
+
+    /// Creates and pushes a rectangle of `size` drawn as the SDF `shape`
+    fn sdf_shape(
+        &mut self,
+        size: glm::Vec2,
+        shape: SdfShape,
+        center: glm::Vec2,
+        color: glm::Vec3,
+        anchor_type: AnchorType,
+    ) -> RendererResult<()> {
+        self.rectangle(
+            size.x / shapes::RECTANGLE_SIZE,
+            size.y / shapes::RECTANGLE_SIZE,
+            0.0,
+            center.x,
+            center.y,
+            color,
+            anchor_type,
+        )?;
+
+        if let Some(draw_instance) = self.draw_pool.last_mut() {
+            draw_instance.sdf = Some(shape);
+        }
+
+        Ok(())
+    }
+
+    /// Sets the [`PickShape`] of the object streamed for the last pushed instance
+    fn set_pick_shape(&mut self, pick_shape: PickShape) {
+        if let Some(draw_instance) = self.draw_pool.last().filter(|last| last.streamed) {
+            self.stream_pool.pool[draw_instance.object.0].pick_shape = pick_shape;
+        }
+    }
+
+    /// Number of segments of generated circles
+    fn segments(&self) -> u32 {
+        match self.draw_params.segments {
+            0 => shapes::generate::DEFAULT_SEGMENTS,
+            segments => segments,
+        }
+    }
+
+    /// Streams the 2D `points` moved by `offset` as a mesh, nothing is drawn without `indices`
+    fn shape(
+        &mut self,
+        points: &[glm::Vec2],
+        indices: &[u32],
+        offset: glm::Vec2,
+        color: glm::Vec3,
+        anchor_type: AnchorType,
+    ) -> Result<()> {
+        if indices.is_empty() {
+            return Ok(());
+        }
+
+        let vertices = points
+            .iter()
+            .map(|point| Vertex {
+                position: [point.x + offset.x, point.y + offset.y, 0.0],
+                color: [1.0, 1.0, 1.0],
+            })
+            .collect::<Vec<_>>();
+
+        Ok(self.mesh(&vertices, indices, color, anchor_type)?)
+    }
+
+    /// Creates and pushes a cubic bezier curve to draw, approximated by `resolution` line segments
+    pub fn bezier(
+        &mut self,
+        control_points: [glm::Vec2; 4],
+        resolution: usize,
+        thickness: f32,
+        color: glm::Vec3,
+        anchor_type: AnchorType,
+    ) -> RendererResult<()> {
+        let points = shapes::cubic_bezier(&control_points, resolution);
+
+        self.polyline(&points, thickness, color, anchor_type)
+    }
+
+    /// Creates and pushes an arrow from `from` pointing to `to` to draw
+    ///
+    /// The shaft follows `draw_params.line_style`, the head of `head_size` length and width
+    /// is streamed. Nothing is drawn when `from` and `to` are the same
+    pub fn arrow(
+        &mut self,
+        from: glm::Vec2,
+        to: glm::Vec2,
+        thickness: f32,
+        head_size: f32,
+        color: glm::Vec3,
+        anchor_type: AnchorType,
+    ) -> RendererResult<()> {
+        let Some(head) = shapes::arrow_head(from, to, head_size) else {
+            return Ok(());
+        };
+
+        if head.base != from {
+            self.line(from, head.base, thickness, color, anchor_type)?;
+        }
+
+        self.polygon(&[head.tip, head.left, head.right], color, true, anchor_type)
+    }
+
+    /// Creates and pushes the `text` of an annotation of `position` to draw
+    ///
+    /// Without a `leader` the text is placed at `position` as aligned by `params`. With a
+    /// `leader` offset a line of the text color leads from `position` to the text, which is
+    /// aligned away from the line, eg. an offset to the top right places its bottom left corner
+    pub fn annotate(
+        &mut self,
+        position: glm::Vec2,
+        text: &str,
+        leader: Option<glm::Vec2>,
+        params: &TextParams,
+        anchor_type: AnchorType,
+    ) -> RendererResult<()> {
+        let Some(offset) = leader else {
+            return self.text(text, position.x, position.y, params, anchor_type);
+        };
+
+        let end = position + offset;
+        self.line(
+            position,
+            end,
+            shapes::LEADER_THICKNESS,
+            params.color,
+            anchor_type,
+        )?;
+
+        let params = TextParams {
+            horizontal_align: if offset.x < 0.0 {
+                HorizontalAlign::Right
+            } else {
+                HorizontalAlign::Left
+            },
+            vertical_align: if offset.y < 0.0 {
+                VerticalAlign::Top
+            } else {
+                VerticalAlign::Bottom
+            },
+            ..*params
+        };
+
+        self.text(text, end.x, end.y, &params, anchor_type)
+    }
+
+    /// Creates and pushes a registered object to draw
+    pub fn object(
+        &mut self,
+        object: ObjectHandle,
+        scale_x: f32,
+        scale_y: f32,
+        rotation: f32,
+        center_x: f32,
+        center_y: f32,
+        color: glm::Vec3,
+        anchor_type: AnchorType,
+    ) -> RendererResult<()> {
+        if object.0 >= self.object_pool.pool.len() {
+            return Err(anyhow!("Object: Unknown object handle {:?}", object).into());
+        }
+
+        let anchor_type = self.resolve_anchor(anchor_type);
+        let (position, rotation) = self.scene.anchor(center_x, center_y, rotation, anchor_type);
+
+        self.draw_pool.push(ObjectInstance {
+            position,
+            rotation,
+            skew: glm::Vec2::zeros(),
+            scale: glm::vec3(scale_x, scale_y, 0.0),
+            color,
+            object,
+            texture: None,
+            uv_rect: None,
+            layer: self.draw_params.layer,
+            pipeline: self.draw_params.pipeline,
+            gradient: self.draw_params.gradient,
+            streamed: false,
+            space: self.draw_params.space,
+            clip: self.draw_params.clip,
+            mask: self.draw_params.mask,
+            pivot: self.draw_params.pivot,
+            sdf: None,
+            tag: self.draw_params.tag,
+        });
+
+        Ok(())
+    }
+
+    /// Creates and pushes a textured rectangle object to draw
+    pub fn sprite(
+        &mut self,
+        texture: TextureHandle,
+        scale_x: f32,
+        scale_y: f32,
+        rotation: f32,
+        center_x: f32,
+        center_y: f32,
+        anchor_type: AnchorType,
+    ) -> RendererResult<()> {
+        if texture.0 >= self.textures.len() {
+            return Err(anyhow!("Sprite: Unknown texture handle {:?}", texture).into());
+        }
+
+        self.push_sprite(
+            texture,
+            None,
+            scale_x,
+            scale_y,
+            rotation,
+            center_x,
+            center_y,
+            anchor_type,
+        );
+
+        Ok(())
+    }
+
+    /// Creates and pushes a textured rectangle object to draw, showing a single atlas `region`
+    pub fn sprite_region(
+        &mut self,
+        region: AtlasRegionId,
+        scale_x: f32,
+        scale_y: f32,
+        rotation: f32,
+        center_x: f32,
+        center_y: f32,
+        anchor_type: AnchorType,
+    ) -> RendererResult<()> {
+        let (texture, uv_rect) = self.object_pool.region_texture(region)?;
+
+        self.push_sprite(
+            texture,
+            Some(uv_rect),
+            scale_x,
+            scale_y,
+            rotation,
+            center_x,
+            center_y,
+            anchor_type,
+        );
+
+        Ok(())
+    }
+
+    /// Creates and pushes a textured rectangle of `size` to draw as nine slices
+    ///
+    /// The corners stay `border` sized showing `uv_border` (0.0 - 0.5) of each side of the texture,
+    /// so panels can be stretched to any size without distorting their frame
+    pub fn nine_slice(
+        &mut self,
+        texture: TextureHandle,
+        size: glm::Vec2,
+        border: f32,
+        uv_border: f32,
+        center: glm::Vec2,
+        anchor_type: AnchorType,
+    ) -> RendererResult<()> {
+        if texture.0 >= self.textures.len() {
+            return Err(anyhow!("Nine Slice: Unknown texture handle {:?}", texture).into());
+        }
+
+        for slice in shapes::nine_slice(size, border, uv_border) {
+            // Zero border -> Empty corners & edges
+            if slice.size.x <= 0.0 || slice.size.y <= 0.0 {
+                continue;
+            }
+
+            self.push_sprite(
+                texture,
+                Some(slice.uv_rect),
+                slice.size.x / shapes::RECTANGLE_SIZE,
+                slice.size.y / shapes::RECTANGLE_SIZE,
+                0.0,
+                center.x + slice.center.x,
+                center.y + slice.center.y,
+                anchor_type,
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Pushes a textured rectangle object to the draw pool
+    fn push_sprite(
+        &mut self,
+        texture: TextureHandle,
+        uv_rect: Option<glm::Vec4>,
+        scale_x: f32,
+        scale_y: f32,
+        rotation: f32,
+        center_x: f32,
+        center_y: f32,
+        anchor_type: AnchorType,
+    ) {
+        let anchor_type = self.resolve_anchor(anchor_type);
+        let (position, rotation) = self.scene.anchor(center_x, center_y, rotation, anchor_type);
 
-            self.device.cmd_set_scissor(
-                self.draw_command_buffers[self.current_frame],
-                0,
-                std::slice::from_ref(&self.scissor),
-            );
+        self.draw_pool.push(ObjectInstance {
+            position,
+            rotation,
+            skew: glm::Vec2::zeros(),
+            scale: glm::vec3(scale_x, scale_y, 0.0),
+            color: glm::vec3(1.0, 1.0, 1.0),
+            object: self.rectangle_object,
+            texture: Some(texture),
+            uv_rect,
+            layer: self.draw_params.layer,
+            pipeline: self.draw_params.pipeline,
+            gradient: self.draw_params.gradient,
+            streamed: false,
+            space: self.draw_params.space,
+            clip: self.draw_params.clip,
+            mask: self.draw_params.mask,
+            pivot: self.draw_params.pivot,
+            sdf: None,
+            tag: self.draw_params.tag,
+        });
+    }
 
-            let descriptor_set = self
-                .descriptor_sets
-                .get(self.current_frame)
-                .context("Descriptor Sets: Index out of bounds")?;
+    /* Layers */
 
-            self.device.cmd_bind_descriptor_sets(
-                self.draw_command_buffers[self.current_frame],
-                vk::PipelineBindPoint::GRAPHICS,
-                self.pipeline_layout,
-                0,
-                std::slice::from_ref(descriptor_set),
-                &[],
-            );
+    /// Adds a new named layer drawn at `order`, names and orders are unique
+    pub fn add_layer(&mut self, name: &str, order: i32) -> RendererResult<()> {
+        Ok(self.layers.add(name, order)?)
+    }
 
-            /////////////////// POOL CREATION TIMER START ///////////////////
-            self.render_stats.start_pool_creation_timer();
+    /// Pushes the following instances onto the layer called `name`,
+    /// same as setting `draw_params.layer` to the order of the layer
+    pub fn use_layer(&mut self, name: &str) -> RendererResult<()> {
+        self.draw_params.layer = self.layers.get(name)?.order;
 
-            self.draw_from_pool()?;
+        Ok(())
+    }
 
-            /////////////////// POOL CREATION TIMER STOP ///////////////////
-            self.render_stats.stop_pool_creation_timer();
+    /// Shows or hides the instances of the layer called `name`
+    pub fn set_layer_visible(&mut self, name: &str, visible: bool) -> RendererResult<()> {
+        self.layers.get_mut(name)?.visible = visible;
 
-            self.device
-                .cmd_end_render_pass(self.draw_command_buffers[self.current_frame]);
+        Ok(())
+    }
 
-            self.device
-                .end_command_buffer(self.draw_command_buffers[self.current_frame])?;
+    /// Locks the layer called `name` to the screen, its instances ignore the camera position
+    /// and rotation, like [`AnchorType::Locked`] instances of a camera at rest
+    ///
+    /// Should be set before pushing instances onto the layer, positions of already pushed
+    /// locked instances include the camera
+    pub fn set_layer_screen_space(&mut self, name: &str, screen_space: bool) -> RendererResult<()> {
+        self.layers.get_mut(name)?.screen_space = screen_space;
 
-            self.scene.update_projection(&window);
+        Ok(())
+    }
 
-            let mut uniform_align = util::Align::new(
-                *self
-                    .uniform_buffers_mapped
-                    .get(self.current_frame)
-                    .context("Uniform Buffers Mapped: Index out of bounds")?,
-                std::mem::align_of::<u16>() as u64,
-                self.uniform_buffers_mem_req
-                    .get(self.current_frame)
-                    .context("Uniform Buffers Mem Req: Index out of bounds")?
-                    .size,
-            );
+    /// Moves the layer called `name` to `order`, instances already on the layer are moved along
+    pub fn set_layer_order(&mut self, name: &str, order: i32) -> RendererResult<()> {
+        let previous_order = self.layers.set_order(name, order)?;
+
+        for draw_instance in self
+            .draw_pool
+            .iter_mut()
+            .chain(self.persistent_pool.instances_mut())
+            .filter(|draw_instance| draw_instance.layer == previous_order)
+        {
+            draw_instance.layer = order;
+        }
 
-            uniform_align.copy_from_slice(&std::slice::from_ref(&self.scene.camera_vp));
+        if self.draw_params.layer == previous_order {
+            self.draw_params.layer = order;
+        }
 
-            let submit_info = vk::SubmitInfo::builder()
-                .wait_dst_stage_mask(std::slice::from_ref(
-                    &vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
-                ))
-                .wait_semaphores(std::slice::from_ref(
-                    &self.semaphores_acquire[self.current_frame],
-                ))
-                .command_buffers(std::slice::from_ref(
-                    &self.draw_command_buffers[self.current_frame],
-                ))
-                .signal_semaphores(std::slice::from_ref(
-                    self.semaphores_release
-                        .get(self.current_frame)
-                        .context("Release Semaphores: Index out of bounds")?,
-                ));
+        Ok(())
+    }
 
-            self.device.queue_submit(
-                self.graphics_queue,
-                std::slice::from_ref(&submit_info),
-                self.fences_inflight[self.current_frame],
-            )?;
+    /// Gives back the named layers
+    pub fn layers(&self) -> &[Layer] {
+        self.layers.as_slice()
+    }
 
-            let present_info = vk::PresentInfoKHR::builder()
-                .wait_semaphores(std::slice::from_ref(
-                    &self.semaphores_release[self.current_frame],
-                ))
-                .swapchains(std::slice::from_ref(&self.swapchain))
-                .image_indices(std::slice::from_ref(&image_index));
+    /* Clipping */
 
-            self.swapchain_loader
-                .queue_present(self.present_queue, &present_info)?;
+    /// Clips the following instances to `rect` and the clip rectangles pushed before, eg. the
+    /// contents of a scrollable panel, until the matching [`Renderer::pop_clip`]
+    ///
+    /// `rect` is given in window pixels like [`Space::Screen`], the clipping is done by the
+    /// scissor of the draw batches and applies to every space
+    pub fn push_clip(&mut self, rect: ClipRect) {
+        let rect = match self.clip_stack.last() {
+            Some(outer) => outer.intersect(&rect),
+            None => rect,
+        };
 
-            let frame = (self.current_frame + 1) % Self::MAX_FRAMES_INFLIGHT;
-            self.current_frame = frame;
-        }
+        self.clip_stack.push(rect);
+        self.draw_params.clip = Some(rect);
+    }
 
-        /////////////////// DRAW REQUEST TIMER START ///////////////////
-        self.render_stats.stop_draw_request_timer();
+    /// Removes the last clip rectangle of [`Renderer::push_clip`], the previous one applies to
+    /// the following instances again
+    pub fn pop_clip(&mut self) -> Option<ClipRect> {
+        let rect = self.clip_stack.pop();
+        self.draw_params.clip = self.clip_stack.last().copied();
 
-        /////////////////// UPDATE STATISTICS ///////////////////
-        self.update_render_stats();
+        rect
+    }
 
-        // Reset Draw Pool
-        self.draw_pool.clear();
+    /* Masking */
+
+    /// Starts a new mask, the instances drawn until [`Renderer::end_mask`] are its shapes
+    ///
+    /// Shapes are only drawn into the stencil attachment, the instances after `end_mask` are
+    /// drawn inside of them until [`Renderer::clear_mask`], eg. sprites inside a rounded panel.
+    /// Masks last for the frame they are begun in, where shapes of masks overlap the later
+    /// mask replaces the earlier one
+    ///
+    /// Fails after 255 masks in a frame, the stencil values are 8 bit
+    pub fn begin_mask(&mut self) -> RendererResult<()> {
+        if self.mask_count == u8::MAX {
+            return Err(anyhow!("Mask: At most {} masks per frame", u8::MAX).into());
+        }
+
+        self.mask_count += 1;
+        self.draw_params.mask = Some(Mask {
+            id: self.mask_count,
+            role: MaskRole::Shape,
+        });
 
         Ok(())
     }
 
-    /// For each `draw_instance` in the [`Renderer`]'s `draw_pool`
-    /// * Creates an a transformation matrix based on the instance's position, rototation and scale
-    /// * Adds a push constant
-    /// * Adds an indexed draw command
-    ///
-    /// Used only internally by draw_request function!
-    fn draw_from_pool(&mut self) -> Result<()> {
-        let mut draw_instance_data = DrawInstanceData::new_empty();
-
-        for draw_instance in &self.draw_pool {
-            draw_instance_data.transform = glm::translate(
-                &glm::Mat4::identity(),
-                &draw_instance.position, // Object Position
-            ) * glm::rotate(
-                &glm::Mat4::identity(),
-                (draw_instance.rotation).to_radians(), // Rotation
-                &glm::vec3(0.0, 0.0, 1.0),             // Axis of Rotation
-            ) * glm::scale(
-                &glm::Mat4::identity(),
-                &draw_instance.scale, // Scale Factors
+    /// Ends the shapes of the mask of [`Renderer::begin_mask`], the following instances are
+    /// clipped by them
+    pub fn end_mask(&mut self) {
+        if let Some(mask) = &mut self.draw_params.mask {
+            mask.role = MaskRole::Clipped;
+        }
+    }
+
+    /// Draws the following instances without a mask
+    pub fn clear_mask(&mut self) {
+        self.draw_params.mask = None;
+    }
+
+    /// Masks of the submitted frame are done, their stencil values are free again
+    fn end_frame_masks(&mut self) {
+        self.mask_count = 0;
+        self.draw_params.mask = None;
+    }
+
+    /// Instances in [`Space::Screen`] or on screen space layers are placed as given,
+    /// their cameras do not move
+    fn resolve_anchor(&self, anchor_type: AnchorType) -> AnchorType {
+        if self.draw_params.space == Space::Screen
+            || self.layers.is_screen_space(self.draw_params.layer)
+        {
+            AnchorType::Unlocked
+        } else {
+            anchor_type
+        }
+    }
+
+    /* Persistent Instances */
+
+    /// Keeps the instances pushed by `draw` across frames and gives back their [`PersistentId`]
+    ///
+    /// Every draw function can be used inside `draw`, eg. `|renderer| renderer.rectangle(..)`.
+    /// Positions are kept as pushed, so [`AnchorType::Locked`] instances do not follow later
+    /// camera movement. Instances of streamed geometry (`mesh`) are rejected, register the
+    /// geometry with `register_object` instead. Masked instances are rejected too, masks only
+    /// last for a frame
+    pub fn add_persistent(
+        &mut self,
+        draw: impl FnOnce(&mut Renderer) -> RendererResult<()>,
+    ) -> RendererResult<PersistentId> {
+        let start = self.draw_pool.len();
+        let result = draw(self);
+        let instances: Vec<ObjectInstance> = self.draw_pool.drain(start..).collect();
+        result?;
+
+        if instances.iter().any(|instance| instance.streamed) {
+            return Err(
+                anyhow!("Persistent: Streamed geometry can not be kept across frames").into(),
             );
+        }
 
-            draw_instance_data.color = draw_instance.color;
+        if instances.iter().any(|instance| instance.mask.is_some()) {
+            return Err(anyhow!("Persistent: Masks can not be kept across frames").into());
+        }
 
-            unsafe {
-                self.device.cmd_push_constants(
-                    self.draw_command_buffers[self.current_frame],
-                    self.pipeline_layout,
-                    vk::ShaderStageFlags::VERTEX,
-                    0,
-                    &bytemuck::try_cast_slice(&draw_instance_data.as_slice())?,
-                );
+        Ok(self.persistent_pool.add(instances))
+    }
 
-                self.device.cmd_draw_indexed(
-                    self.draw_command_buffers[self.current_frame],
-                    self.object_pool.pool[draw_instance.object_index].index_count as u32,
-                    1,
-                    self.object_pool.pool[draw_instance.object_index].index_offset as u32,
-                    0,
-                    0,
-                );
-            }
+    /// Changes every instance kept under `id` with `update`
+    pub fn update(
+        &mut self,
+        id: PersistentId,
+        mut update: impl FnMut(&mut ObjectInstance),
+    ) -> RendererResult<()> {
+        self.persistent_pool
+            .get_mut(id)?
+            .iter_mut()
+            .for_each(&mut update);
+
+        Ok(())
+    }
+
+    /// Stops drawing the instances kept under `id`
+    pub fn remove(&mut self, id: PersistentId) -> RendererResult<()> {
+        self.persistent_pool.remove(id)?;
+        self.flipbooks.retain(|playback| playback.target != id);
+
+        Ok(())
+    }
+
+    /// Plays `flipbook` on the sprites kept under `id`, replacing the one already playing
+    ///
+    /// The texture and UV rectangle of every instance under `id` is set to the current frame
+    /// in each `draw_request`. A flipbook played once stops on its last frame
+    pub fn play_flipbook(&mut self, id: PersistentId, flipbook: Flipbook) -> RendererResult<()> {
+        if flipbook.frames.is_empty() {
+            return Err(anyhow!("Flipbook: No frames to play").into());
         }
 
+        for &frame in &flipbook.frames {
+            self.object_pool.region_texture(frame)?;
+        }
+
+        self.persistent_pool.get_mut(id)?;
+        self.flipbooks.retain(|playback| playback.target != id);
+        self.flipbooks.push(FlipbookPlayback {
+            target: id,
+            flipbook,
+            start: Instant::now(),
+        });
+
         Ok(())
     }
 
-    /* Creating Draw Instances */
+    /// Stops the flipbook of `id`, the sprites keep showing their current frame
+    pub fn stop_flipbook(&mut self, id: PersistentId) {
+        self.flipbooks.retain(|playback| playback.target != id);
+    }
 
-    /// Creates and pushes a text object to draw
-    pub fn text(
-        &mut self,
-        text: &str,
-        scale: f32,
-        top_left_x: f32,
-        top_left_y: f32,
-        anchor_type: AnchorType,
-    ) -> Result<()> {
-        // let scale = scale * self.scene.camera_zoom;
-        let pad_x = scale * 0.03;
-        let pad_y = scale * 0.05;
-
-        let anchor_position = match anchor_type {
-            AnchorType::Locked => glm::vec3(
-                top_left_x + self.scene.camera_pos.x + pad_x,
-                top_left_y + self.scene.camera_pos.y - pad_y,
-                0.0,
-            ),
-            AnchorType::Unlocked => glm::vec3(top_left_x + pad_x, top_left_y - pad_y, 0.0),
+    /// Sets the sprites of every flipbook to the frame of the elapsed time
+    ///
+    /// Flipbooks played once are dropped after their last frame is set
+    fn advance_flipbooks(&mut self) -> Result<()> {
+        let now = Instant::now();
+        let object_pool = &self.object_pool;
+        let persistent_pool = &mut self.persistent_pool;
+        let mut first_error = None;
+
+        self.flipbooks.retain(|playback| {
+            let elapsed = now.duration_since(playback.start).as_secs_f32();
+            let frame = playback
+                .flipbook
+                .frame(elapsed)
+                .context("Flipbook: No frames to play")
+                .and_then(|frame| object_pool.region_texture(frame));
+
+            let result = frame.and_then(|(texture, uv_rect)| {
+                persistent_pool
+                    .get_mut(playback.target)?
+                    .iter_mut()
+                    .for_each(|instance| {
+                        instance.texture = Some(texture);
+                        instance.uv_rect = Some(uv_rect);
+                    });
+                Ok(())
+            });
+
+            match result {
+                Ok(()) => !playback.flipbook.is_finished(elapsed),
+                Err(error) => {
+                    first_error.get_or_insert(error);
+                    false
+                }
+            }
+        });
+
+        match first_error {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    /* Picking */
+
+    /// Gives back the topmost draw instance under `screen_position` (in window pixels)
+    ///
+    /// Tests the current `draw_pool`, or the instances of the last submitted frame when it is
+    /// empty. The position is transformed with the camera of the main `scene`, instances are
+    /// tested against their rotated bounding box. Higher layers win, inside a layer the
+    /// instance pushed last. Hidden layers are skipped
+    pub fn pick(&self, screen_position: glm::Vec2) -> Option<DrawInstanceId> {
+        let world_position = self.scene.screen_to_world(screen_position);
+        let rest_position = Scene::unproject(
+            &self.scene.camera_vp.at_rest(),
+            screen_position,
+            self.surface_extent,
+        );
+        let pixel_position = glm::vec2(
+            screen_position.x,
+            self.surface_extent.height as f32 - screen_position.y,
+        );
+
+        // Submitted Frame -> Only the targets sharing a cell of the grid with the cursor
+        let current_targets;
+        let pick_targets: Vec<&PickTarget> = if self.draw_pool.is_empty() {
+            self.pick_index()
+                .candidates(world_position)
+                .map(|index| &self.pick_targets[index])
+                .collect()
+        } else {
+            current_targets = self.collect_pick_targets();
+            current_targets.iter().collect()
         };
 
-        let mut char_index;
-        let mut text_instance_pool = Vec::with_capacity(text.len());
-        let mut cursor_position = anchor_position;
+        pick_targets
+            .into_iter()
+            .filter(|pick_target| self.layers.is_visible(pick_target.layer))
+            .filter(|pick_target| {
+                pick_target.contains(match (pick_target.space, pick_target.screen_space) {
+                    (Space::Screen, _) => pixel_position,
+                    (Space::World, true) => rest_position,
+                    (Space::World, false) => world_position,
+                })
+            })
+            .max_by_key(|pick_target| (pick_target.layer, pick_target.id))
+            .map(|pick_target| pick_target.id)
+    }
 
-        for byte in text.bytes() {
-            char_index = resources::CHAR_OBJECT_POOL[byte as usize];
+    /// Gives back the world space draw instances of the last submitted frame whose bounding box
+    /// intersects `region`, eg. for box selection, ordered by their id
+    ///
+    /// Instances of hidden and screen space layers are skipped
+    pub fn pick_region(&self, region: WorldRect) -> Vec<DrawInstanceId> {
+        let mut ids: Vec<DrawInstanceId> = self
+            .pick_index()
+            .query(&region)
+            .into_iter()
+            .map(|index| &self.pick_targets[index])
+            .filter(|pick_target| self.layers.is_visible(pick_target.layer))
+            .map(|pick_target| pick_target.id)
+            .collect();
 
-            // There are no corresponding character object
-            if char_index == 255 {
-                continue;
-            };
+        ids.sort_unstable();
+        ids
+    }
 
-            // Move the cursor to the next line
-            if char_index == 253 {
-                cursor_position.x = anchor_position.x;
-                cursor_position.y -= pad_y;
-                continue;
-            };
+    /// Gives back the [`DrawParams::tag`] the instance of `id` was pushed with
+    ///
+    /// Looks up the current `draw_pool`, or the instances of the last submitted frame when it
+    /// is empty, the same as [`Renderer::pick`]
+    pub fn tag(&self, id: DrawInstanceId) -> Option<u32> {
+        if !self.draw_pool.is_empty() {
+            return self.draw_pool.get(id.0)?.tag;
+        }
 
-            // Add the current char to the draw pool
-            if char_index != 254 {
-                text_instance_pool.push(ObjectInstance {
-                    position: cursor_position,
-                    scale: glm::vec3(scale, scale, 0.0),
-                    object_index: char_index as usize,
-                    ..ObjectInstance::default()
-                });
-            }
+        let index = self
+            .pick_targets
+            .binary_search_by_key(&id, |pick_target| pick_target.id)
+            .ok()?;
+
+        self.pick_targets[index].tag
+    }
+
+    fn pick_index(&self) -> &PickIndex {
+        self.pick_index
+            .get_or_init(|| PickIndex::new(&self.pick_targets))
+    }
+
+    /// Gives back the [`PickTarget`] of every instance in the `draw_pool`, instances of stale
+    /// objects are skipped
+    fn collect_pick_targets(&self) -> Vec<PickTarget> {
+        self.draw_pool
+            .iter()
+            .enumerate()
+            .filter_map(|(index, draw_instance)| {
+                let object_data = if draw_instance.streamed {
+                    self.stream_pool.pool.get(draw_instance.object.0)
+                } else {
+                    self.object_pool.pool.get(draw_instance.object.0)
+                }?;
+
+                Some(PickTarget {
+                    screen_space: self.layers.is_screen_space(draw_instance.layer),
+                    ..PickTarget::new(DrawInstanceId(index), draw_instance, object_data)
+                })
+            })
+            .collect()
+    }
+
+    /* Recording */
+
+    /// Captures the next `frame_count` frames and encodes them into `path` on a background
+    /// thread, the format is picked from the path, see [`RecordFormat`]
+    ///
+    /// A finished recording is stopped first, the frames of the post-processing and the
+    /// egui pass are captured as presented
+    pub fn start_recording(
+        &mut self,
+        path: impl AsRef<Path>,
+        frame_count: usize,
+    ) -> RendererResult<()> {
+        if self.is_recording() {
+            return Err(anyhow!("Recorder: Already recording").into());
+        }
+
+        if self.recorder.is_some() {
+            self.stop_recording()?;
+        }
 
-            // Move the cursor by 1 character to right
-            cursor_position.x += pad_x;
+        let caps = unsafe {
+            self.surface_loader
+                .get_physical_device_surface_capabilities(self.physical_device, self.surface)
+        }?;
+        if !builder::swapchain_usage(&caps).contains(vk::ImageUsageFlags::TRANSFER_SRC) {
+            return Err(anyhow!("Recorder: The swapchain images can't be copied").into());
         }
 
-        self.draw_pool.extend(text_instance_pool);
+        self.recorder = Some(Recorder::start(
+            path.as_ref(),
+            frame_count,
+            self.surface_format.format,
+            self.max_frames_inflight,
+        )?);
 
         Ok(())
     }
 
-    /// Creates and pushes a circle object to draw
-    pub fn circle(
-        &mut self,
-        scale: f32,
-        center_x: f32,
-        center_y: f32,
-        color: glm::Vec3,
-        anchor_type: AnchorType,
-    ) -> Result<()> {
-        let anchor_position = match anchor_type {
-            AnchorType::Locked => glm::vec3(
-                center_x + self.scene.camera_pos.x,
-                center_y + self.scene.camera_pos.y,
-                0.0,
-            ),
-            AnchorType::Unlocked => glm::vec3(center_x, center_y, 0.0),
+    /// Gives back true while the frames of the recording are captured
+    pub fn is_recording(&self) -> bool {
+        self.recorder
+            .as_ref()
+            .is_some_and(|recorder| recorder.is_capturing())
+    }
+
+    /// Stops capturing and waits until the recording is written, gives back the number of
+    /// frames in it
+    pub fn stop_recording(&mut self) -> RendererResult<usize> {
+        let Some(recorder) = self.recorder.take() else {
+            return Ok(0);
         };
 
-        self.draw_pool.push(ObjectInstance {
-            position: anchor_position,
-            rotation: 0.0, // <- Matters only if has a texture
-            scale: glm::vec3(scale, scale, 0.0),
-            color,
-            object_index: self.object_pool.pool.len() - 1,
-        });
+        unsafe { self.device.device_wait_idle() }?;
 
-        Ok(())
+        Ok(recorder.stop(&self.device, &mut self.allocator)?)
     }
 
-    /// Creates and pushes a rectangle object to draw
-    pub fn rectangle(
-        &mut self,
-        scale_x: f32,
-        scale_y: f32,
-        rotation: f32,
-        center_x: f32,
-        center_y: f32,
-        color: glm::Vec3,
-        anchor_type: AnchorType,
-    ) -> Result<()> {
-        let anchor_position = match anchor_type {
-            AnchorType::Locked => glm::vec3(
-                center_x + self.scene.camera_pos.x,
-                center_y + self.scene.camera_pos.y,
-                0.0,
-            ),
-            AnchorType::Unlocked => glm::vec3(center_x, center_y, 0.0),
+    /* Frame Pacing */
+
+    /// Waits until the last presented frame is shown and records its presentation in the render
+    /// statistics, only with [`RendererBuilder::low_latency`]
+    fn wait_for_present(&mut self) -> Result<()> {
+        let (Some(loader), Some((present_id, presented))) =
+            (&self.present_wait_loader, self.pending_present.take())
+        else {
+            return Ok(());
         };
 
-        self.draw_pool.push(ObjectInstance {
-            position: anchor_position,
-            rotation: rotation,
-            scale: glm::vec3(scale_x, scale_y, 0.0),
-            color,
-            object_index: self.object_pool.pool.len() - 2,
-        });
+        match unsafe {
+            loader.wait_for_present(self.swapchain, present_id, Self::PRESENT_WAIT_TIMEOUT)
+        } {
+            Ok(()) | Err(vk::Result::SUBOPTIMAL_KHR) => {
+                let now = Instant::now();
+                self.render_stats.last_present = Some(now);
+                self.render_stats.last_present_latency = (now - presented).as_micros();
+            }
+            // Not shown in time, eg. the window is hidden -> Draw without a timestamp
+            Err(vk::Result::TIMEOUT | vk::Result::ERROR_OUT_OF_DATE_KHR) => (),
+            Err(error) => return Err(error.into()),
+        }
 
         Ok(())
     }
 
+    /// Limits the frame rate, draw requests wait until their frame is due, `None` draws as fast
+    /// as the present mode allows
+    pub fn set_frame_limit(&mut self, limit: Option<FrameLimit>) {
+        self.frame_pacer.set_limit(limit);
+        self.render_stats.target_frame_time = self.frame_pacer.target();
+    }
+
     /* Render Statistics */
 
+    /// Gives back the render statistics of the last frames
+    pub fn stats(&self) -> &RenderStats {
+        &self.render_stats
+    }
+
+    /// Enables measuring the render statistics, disabled stats keep their last values
+    pub fn set_stats_enabled(&mut self, enabled: bool) {
+        self.render_stats.turned_off = !enabled;
+    }
+
+    /// Draws the render statistics in the top left corner while they are enabled,
+    /// apps can turn it off to draw their own
+    pub fn set_stats_overlay(&mut self, enabled: bool) {
+        self.render_stats.overlay = enabled;
+    }
+
+    /// Pushes the text of the [`DebugInfo`] and the swatch of the hovered color
+    fn draw_debug_overlay(&mut self) -> RendererResult<()> {
+        let debug_info = self.debug_info();
+
+        let draw_params = std::mem::replace(
+            &mut self.draw_params,
+            DrawParams {
+                layer: Self::STATS_LAYER,
+                space: Space::Screen,
+                ..DrawParams::default()
+            },
+        );
+
+        // Window pixels from the bottom left, the cursor is tracked from the top left
+        let right = self.surface_extent.width as f32;
+        let top = self.surface_extent.height as f32;
+        let result = self
+            .text(
+                &debug_info.as_text(),
+                right - 6.0,
+                top - 6.0,
+                &TextParams {
+                    scale: Self::STATS_SCALE,
+                    horizontal_align: HorizontalAlign::Right,
+                    outline: Some(glm::vec3(1.0, 1.0, 1.0)),
+                    ..TextParams::default()
+                },
+                AnchorType::Unlocked,
+            )
+            .and_then(|()| {
+                let Some(hovered) = debug_info.hovered else {
+                    return Ok(());
+                };
+
+                let swatch = Self::DEBUG_SWATCH_SIZE / shapes::RECTANGLE_SIZE;
+                self.rectangle(
+                    swatch,
+                    swatch,
+                    0.0,
+                    self.cursor_position.x + Self::DEBUG_SWATCH_SIZE,
+                    top - self.cursor_position.y - Self::DEBUG_SWATCH_SIZE,
+                    hovered.color,
+                    AnchorType::Unlocked,
+                )
+            });
+
+        self.draw_params = draw_params;
+
+        result
+    }
+
     /// Updates the render statistics structure based on the time elapsed
     fn update_render_stats(&mut self) -> () {
         if self.render_stats.turned_off {
             return;
         }
 
+        // Update Frame Time
+        let frame_time = self.render_stats.frame_instant.elapsed();
+        self.render_stats.last_frame_time = frame_time.as_micros();
+        self.render_stats.frame_instant = Instant::now();
+        self.render_stats.push_frame_time(frame_time);
+
         // Update Frame Counter
         if self.render_stats.fps_instant.elapsed() >= Duration::from_secs(1) {
             self.render_stats.frames_per_sec = self.render_stats.frame_counter;
@@ -897,11 +4944,30 @@ impl Drop for Renderer {
         unsafe {
             self.device.device_wait_idle();
 
-            // Buffers: Index & Vertex
+            // Recording: Written before the buffers are gone
+            if let Some(recorder) = self.recorder.take() {
+                let _ = recorder.stop(&self.device, &mut self.allocator);
+            }
+
+            // Textures
+            self.textures
+                .iter()
+                .for_each(|texture| texture.destroy(&self.device, &mut self.allocator));
+
+            // Buffers: Compute, Instance, Streaming, Index & Vertex
+            self.compute_buffers.iter().for_each(|compute_buffer| {
+                compute_buffer.destroy(&self.device, &mut self.allocator)
+            });
+            self.instance_buffers.iter().for_each(|instance_buffer| {
+                instance_buffer.destroy(&self.device, &mut self.allocator)
+            });
+            self.stream_buffers
+                .iter()
+                .for_each(|stream_buffer| stream_buffer.destroy(&self.device, &mut self.allocator));
             self.device.destroy_buffer(self.index_buffer, None);
-            self.device.free_memory(self.index_buffer_memory, None);
             self.device.destroy_buffer(self.vertex_buffer, None);
-            self.device.free_memory(self.vertex_buffer_memory, None);
+            self.allocator.free(&self.index_buffer_allocation);
+            self.allocator.free(&self.vertex_buffer_allocation);
 
             // Syncronisation
             self.semaphores_acquire.clone().into_iter().for_each(|s| {
@@ -914,31 +4980,78 @@ impl Drop for Renderer {
                 self.device.destroy_fence(f, None);
             });
 
-            // Command Pool
+            // Command Pools
             self.device.destroy_command_pool(self.command_pool, None);
+            if let Some(parallel_recorder) = &mut self.parallel_recorder {
+                parallel_recorder.destroy();
+            }
+
+            // Retired Swapchains
+            std::mem::take(&mut self.retired_swapchains)
+                .into_iter()
+                .for_each(|retired| {
+                    retired.destroy(&self.device, &self.swapchain_loader, &mut self.allocator)
+                });
 
             // Buffers: Frame & Uniform
             self.frame_buffers
                 .clone()
                 .into_iter()
                 .for_each(|fb| self.device.destroy_framebuffer(fb, None));
+            if let Some(color_target) = &self.color_target {
+                color_target.destroy(&self.device, &mut self.allocator);
+            }
+            self.stencil_target
+                .destroy(&self.device, &mut self.allocator);
+            if let Some(post_process) = &mut self.post_process {
+                post_process.destroy(&self.device, &mut self.allocator);
+            }
+            #[cfg(feature = "egui")]
+            if let Some(egui_renderer) = &mut self.egui_renderer {
+                egui_renderer.destroy(&self.device, &mut self.allocator);
+            }
             self.uniform_buffers
                 .clone()
                 .into_iter()
                 .for_each(|b| self.device.destroy_buffer(b, None));
-            self.uniform_buffers_memory
-                .clone()
-                .into_iter()
-                .for_each(|dm| self.device.free_memory(dm, None));
+            self.uniform_buffers_allocation
+                .iter()
+                .for_each(|allocation| self.allocator.free(allocation));
 
             // Descriptors & Pipeline
             self.device
                 .destroy_descriptor_pool(self.descriptor_pool, None);
             self.device
                 .destroy_descriptor_set_layout(self.descriptor_set_layout, None);
+            self.device.destroy_descriptor_pool(self.texture_pool, None);
+            self.device
+                .destroy_descriptor_set_layout(self.texture_set_layout, None);
             self.device.destroy_pipeline(self.graphics_pipeline, None);
             self.device
                 .destroy_pipeline_layout(self.pipeline_layout, None);
+            self.device.destroy_pipeline(self.sprite_pipeline, None);
+            self.device
+                .destroy_pipeline_layout(self.sprite_pipeline_layout, None);
+            self.mask_pipeline.destroy(&self.device);
+            self.sdf_pipeline.destroy(&self.device);
+            if let Some(wireframe_pipeline) = &self.wireframe_pipeline {
+                self.device
+                    .destroy_pipeline(wireframe_pipeline.pipeline, None);
+                self.device
+                    .destroy_pipeline_layout(wireframe_pipeline.layout, None);
+            }
+            for custom_pipeline in &self.custom_pipelines {
+                self.device.destroy_pipeline(custom_pipeline.pipeline, None);
+                self.device
+                    .destroy_pipeline_layout(custom_pipeline.layout, None);
+            }
+            self.effect_canvas.destroy(&self.device);
+            self.compute_pipelines
+                .iter()
+                .for_each(|compute_pipeline| compute_pipeline.destroy(&self.device));
+            self.compute_pools
+                .iter()
+                .for_each(|pool| self.device.destroy_descriptor_pool(*pool, None));
             self.device.destroy_render_pass(self.render_pass, None);
             self.image_views
                 .clone() // TODO! -> Potential fix here, but cloning Handles should be OK
@@ -950,6 +5063,9 @@ impl Drop for Renderer {
                 .destroy_swapchain(self.swapchain, None);
             self.surface_loader.destroy_surface(self.surface, None);
 
+            // Memory: Every block of the allocator
+            self.allocator.destroy(&self.device);
+
             // Device
             self.device.destroy_device(None);
 
@@ -969,7 +5085,8 @@ impl Drop for Renderer {
 /// Cretes a Vulkan Instance using the given `entry` and `window`
 pub fn create_instance(
     entry: &ash::Entry,
-    window: &winit::window::Window,
+    window: &impl HasRawDisplayHandle,
+    portability_enumeration: bool,
 ) -> Result<ash::Instance> {
     /* Application Data */
     let api_version = match entry.try_enumerate_instance_version()? {
@@ -990,12 +5107,37 @@ pub fn create_instance(
 
     enabled_extension_names.push(khr::Surface::name().as_ptr());
 
+    let available_extensions = entry.enumerate_instance_extension_properties(None)?;
+    let is_available = |name: &CStr| {
+        available_extensions
+            .iter()
+            .any(|ep| unsafe { CStr::from_ptr(ep.extension_name.as_ptr()) } == name)
+    };
+
+    // HDR color spaces of the surface formats, see `ColorMode`
+    if is_available(vk::ExtSwapchainColorspaceFn::name()) {
+        enabled_extension_names.push(vk::ExtSwapchainColorspaceFn::name().as_ptr());
+    }
+
+    // Dependency of VK_EXT_full_screen_exclusive, see `FullscreenMode`
+    if cfg!(target_os = "windows") && is_available(vk::KhrGetSurfaceCapabilities2Fn::name()) {
+        enabled_extension_names.push(vk::KhrGetSurfaceCapabilities2Fn::name().as_ptr());
+    }
+
     #[cfg(feature = "render_dbg")]
     enabled_extension_names.push(ext::DebugUtils::name().as_ptr());
 
+    let create_flags = if portability_enumeration {
+        enabled_extension_names.push(vk::KhrPortabilityEnumerationFn::name().as_ptr());
+        vk::InstanceCreateFlags::ENUMERATE_PORTABILITY_KHR
+    } else {
+        vk::InstanceCreateFlags::empty()
+    };
+
     let create_info = vk::InstanceCreateInfo::builder()
         .application_info(&application_info)
-        .enabled_extension_names(&enabled_extension_names);
+        .enabled_extension_names(&enabled_extension_names)
+        .flags(create_flags);
 
     /* Layers */
     #[cfg(feature = "render_dbg")]
@@ -1038,28 +5180,36 @@ struct Device {
     memory_properties: vk::PhysicalDeviceMemoryProperties,
     graphics_queue_index: u32,
     present_queue_index: u32,
-    // transfer_queue_index: u32,
+    supported_sample_counts: vk::SampleCountFlags,
+    /// Format of the stencil attachment of the masks
+    stencil_format: vk::Format,
+    wireframe_supported: bool,
+    dynamic_rendering_supported: bool,
+    full_screen_exclusive_supported: bool,
+    present_wait_supported: bool,
+    min_uniform_buffer_offset_alignment: u64,
+    /// Dedicated transfer family running the staging copies of the uploads
+    transfer_queue_index: Option<u32>,
 }
 
 impl Device {
     // TODO! -> This is too strict right now, better to rank surface properties
     // TODO! -> Capability Support: image count + image extent
 
-    /// Creates a new device using the given `instance` and `surface_ext
-    fn new(instance: &ash::Instance, surface_ext: &SurfaceExtension) -> Result<Self> {
+    /// Creates a new device using the given `instance` and `surface_ext`
+    ///
+    /// Out of the suitable physical devices the one matching `gpu_preference` the best is picked
+    fn new(
+        instance: &ash::Instance,
+        surface_ext: &SurfaceExtension,
+        gpu_preference: &GpuPreference,
+    ) -> Result<Self> {
         /*Find Physical Device*/
-        let mut physical_device = None;
-        let mut graphics_queue_index = None;
-        let mut present_queue_index = None;
-        // let mut transfer_queue_index = None;
+        let mut candidates = Vec::new();
 
         for pd in unsafe { instance.enumerate_physical_devices() }? {
             /* Device Properties */
-            if !(unsafe { instance.get_physical_device_properties(pd) }.device_type
-                == vk::PhysicalDeviceType::DISCRETE_GPU)
-            {
-                continue;
-            }
+            let properties = unsafe { instance.get_physical_device_properties(pd) };
 
             /* Device Features */
             // unsafe { instance.get_physical_device_features(*pd) }
@@ -1081,87 +5231,122 @@ impl Device {
                     .get_physical_device_surface_formats(pd, surface_ext.surface)
             }?
             .into_iter()
-            .any(|f| {
-                f.format == vk::Format::B8G8R8A8_SRGB
-                    && f.color_space == vk::ColorSpaceKHR::SRGB_NONLINEAR
-            })) {
-                continue;
-            }
-
-            /* Surface Present Modes */
-            if !(unsafe {
-                surface_ext
-                    .loader
-                    .get_physical_device_surface_present_modes(pd, surface_ext.surface)
-            }?
-            .into_iter()
-            .any(|pm| pm == vk::PresentModeKHR::MAILBOX))
+            .any(|f| builder::surface_format(ColorMode::Srgb, &[f]).is_some()))
             {
                 continue;
             }
 
             /* Queue Family Indices */
-            graphics_queue_index = None;
-            present_queue_index = None;
-            if !(unsafe { instance.get_physical_device_queue_family_properties(pd) }
-                .into_iter()
-                .enumerate()
-                .any(|(i, qf)| {
-                    let index = i as u32;
-
-                    if graphics_queue_index.is_none()
-                        && qf.queue_flags.contains(vk::QueueFlags::GRAPHICS)
-                    {
-                        graphics_queue_index = Some(index);
-                    }
-
-                    if present_queue_index.is_none()
-                        && unsafe {
-                            surface_ext.loader.get_physical_device_surface_support(
-                                pd,
-                                index,
-                                surface_ext.surface,
-                            )
-                        }
-                        .unwrap_or(false)
-                    {
-                        // Present Index must be different than Graphics Index
-                        if let Some(graphics_queue_index) = graphics_queue_index {
-                            if graphics_queue_index != index {
-                                present_queue_index = Some(index);
-                            }
-                        }
+            let queue_families =
+                unsafe { instance.get_physical_device_queue_family_properties(pd) };
+
+            let graphics_support: Vec<bool> = queue_families
+                .iter()
+                .map(|qf| qf.queue_flags.contains(vk::QueueFlags::GRAPHICS))
+                .collect();
+
+            let present_support: Vec<bool> = (0..queue_families.len() as u32)
+                .map(|index| {
+                    unsafe {
+                        surface_ext.loader.get_physical_device_surface_support(
+                            pd,
+                            index,
+                            surface_ext.surface,
+                        )
                     }
+                    .unwrap_or(false)
+                })
+                .collect();
 
-                    // if transfer_queue_index.is_none()
-                    //     && qf.queue_flags.contains(vk::QueueFlags::TRANSFER)
-                    // {
-                    //     // Compute Index must be different than Graphics & Present Index
-                    //     if let (Some(graphics_queue_index), Some(present_queue_index)) =
-                    //         (graphics_queue_index, present_queue_index)
-                    //     {
-                    //         if graphics_queue_index != index && present_queue_index != index {
-                    //             transfer_queue_index = Some(index);
-                    //         }
-                    //     }
-                    // }
-
-                    graphics_queue_index.is_some() && present_queue_index.is_some()
-                    // && transfer_queue_index.is_some()
-                }))
-            {
+            let Some((graphics_queue_index, present_queue_index)) =
+                select_queue_families(&graphics_support, &present_support)
+            else {
                 continue;
-            }
+            };
 
-            physical_device = Some(pd);
-            break;
+            let transfer_queue_index = select_transfer_family(&queue_families);
+
+            candidates.push((
+                pd,
+                properties,
+                graphics_queue_index,
+                present_queue_index,
+                transfer_queue_index,
+            ));
         }
 
-        let physical_device =
-            physical_device.context("Could not find a proper physical device!")?;
-        let graphics_queue_index = graphics_queue_index.unwrap();
-        let present_queue_index = present_queue_index.unwrap();
-        // let transfer_queue_index = transfer_queue_index.unwrap();
+        /* Rank Physical Devices */
+        let (
+            physical_device,
+            properties,
+            graphics_queue_index,
+            present_queue_index,
+            transfer_queue_index,
+        ) = select_physical_device(candidates, gpu_preference, |(_, properties, _, _, _)| {
+            properties
+        })
+        .ok_or_else(|| {
+            RendererError::DeviceSelection(match gpu_preference {
+                GpuPreference::Name(name) => {
+                    format!("Could not find a proper physical device called {}!", name)
+                }
+                _ => "Could not find a proper physical device!".to_string(),
+            })
+        })?;
+
+        // The stencil attachment of the masks is multisampled like the color attachment
+        let supported_sample_counts = properties.limits.framebuffer_color_sample_counts
+            & properties.limits.framebuffer_stencil_sample_counts;
+        let min_uniform_buffer_offset_alignment =
+            properties.limits.min_uniform_buffer_offset_alignment;
+
+        // Vulkan guarantees a depth stencil attachment format with a stencil aspect
+        let stencil_format = builder::stencil_format(|format| {
+            unsafe { instance.get_physical_device_format_properties(physical_device, format) }
+                .optimal_tiling_features
+                .contains(vk::FormatFeatureFlags::DEPTH_STENCIL_ATTACHMENT)
+        })
+        .ok_or_else(|| anyhow!("Device: No stencil attachment format"))?;
+
+        /* Physical Device Features */
+        let wireframe_supported = unsafe { instance.get_physical_device_features(physical_device) }
+            .fill_mode_non_solid
+            == vk::TRUE;
+
+        let available_extensions =
+            unsafe { instance.enumerate_device_extension_properties(physical_device) }?;
+        let is_available = |name: &CStr| {
+            available_extensions
+                .iter()
+                .any(|ep| unsafe { CStr::from_ptr(ep.extension_name.as_ptr()) } == name)
+        };
+
+        let dynamic_rendering_supported = {
+            let mut vulkan_13_features = vk::PhysicalDeviceVulkan13Features::default();
+            let mut features =
+                vk::PhysicalDeviceFeatures2::builder().push_next(&mut vulkan_13_features);
+            unsafe { instance.get_physical_device_features2(physical_device, &mut features) };
+
+            vulkan_13_features.dynamic_rendering == vk::TRUE
+        };
+
+        // Exclusive fullscreen is only controlled by the application on Windows
+        let full_screen_exclusive_supported =
+            cfg!(target_os = "windows") && is_available(ext::FullScreenExclusive::name());
+
+        // Presentation timing, see `RendererBuilder::low_latency`
+        let present_wait_supported =
+            is_available(vk::KhrPresentIdFn::name()) && is_available(khr::PresentWait::name()) && {
+                let mut present_id_features = vk::PhysicalDevicePresentIdFeaturesKHR::default();
+                let mut present_wait_features = vk::PhysicalDevicePresentWaitFeaturesKHR::default();
+                let mut features = vk::PhysicalDeviceFeatures2::builder()
+                    .push_next(&mut present_id_features)
+                    .push_next(&mut present_wait_features);
+                unsafe { instance.get_physical_device_features2(physical_device, &mut features) };
+
+                present_id_features.present_id == vk::TRUE
+                    && present_wait_features.present_wait == vk::TRUE
+            };
 
         /* Physical Device Memory Properties */
         let memory_properties =
@@ -1171,29 +5356,74 @@ impl Device {
         let logical_device = {
             let queue_priority = [1.0];
 
-            let queue_create_infos = vec![
+            let mut queue_create_infos = vec![
                 // Graphics Queue
                 vk::DeviceQueueCreateInfo::builder()
                     .queue_family_index(graphics_queue_index)
                     .queue_priorities(&queue_priority)
                     .build(),
-                // Present Queue
-                vk::DeviceQueueCreateInfo::builder()
-                    .queue_family_index(present_queue_index)
-                    .queue_priorities(&queue_priority)
-                    .build(),
-                // Transfer Queue
-                // vk::DeviceQueueCreateInfo::builder()
-                //     .queue_family_index(transfer_queue_index)
-                //     .queue_priorities(&queue_priority)
-                //     .build(),
             ];
 
-            let extension_names = [khr::Swapchain::name().as_ptr()];
+            // Transfer Queue: Only a dedicated family is selected
+            if let Some(transfer_queue_index) = transfer_queue_index {
+                queue_create_infos.push(
+                    vk::DeviceQueueCreateInfo::builder()
+                        .queue_family_index(transfer_queue_index)
+                        .queue_priorities(&queue_priority)
+                        .build(),
+                );
+            }
+
+            // Present Queue: A shared family must be requested only once
+            if present_queue_index != graphics_queue_index {
+                queue_create_infos.push(
+                    vk::DeviceQueueCreateInfo::builder()
+                        .queue_family_index(present_queue_index)
+                        .queue_priorities(&queue_priority)
+                        .build(),
+                );
+            }
+
+            let mut extension_names = vec![khr::Swapchain::name().as_ptr()];
+
+            // Portability implementations must enable the subset extension
+            if is_available(vk::KhrPortabilitySubsetFn::name()) {
+                extension_names.push(vk::KhrPortabilitySubsetFn::name().as_ptr());
+            }
+
+            if full_screen_exclusive_supported {
+                extension_names.push(ext::FullScreenExclusive::name().as_ptr());
+            }
+
+            if present_wait_supported {
+                extension_names.push(vk::KhrPresentIdFn::name().as_ptr());
+                extension_names.push(khr::PresentWait::name().as_ptr());
+            }
+
+            let enabled_features =
+                vk::PhysicalDeviceFeatures::builder().fill_mode_non_solid(wireframe_supported);
+
+            let mut vulkan_13_features = vk::PhysicalDeviceVulkan13Features::builder()
+                .dynamic_rendering(dynamic_rendering_supported);
+
+            let mut present_id_features =
+                vk::PhysicalDevicePresentIdFeaturesKHR::builder().present_id(true);
+            let mut present_wait_features =
+                vk::PhysicalDevicePresentWaitFeaturesKHR::builder().present_wait(true);
 
             let create_info = vk::DeviceCreateInfo::builder()
                 .queue_create_infos(&queue_create_infos)
-                .enabled_extension_names(&extension_names);
+                .enabled_extension_names(&extension_names)
+                .enabled_features(&enabled_features)
+                .push_next(&mut vulkan_13_features);
+
+            let create_info = if present_wait_supported {
+                create_info
+                    .push_next(&mut present_id_features)
+                    .push_next(&mut present_wait_features)
+            } else {
+                create_info
+            };
 
             unsafe { instance.create_device(physical_device, &create_info, None) }?
         };
@@ -1204,8 +5434,350 @@ impl Device {
             memory_properties,
             graphics_queue_index,
             present_queue_index,
-            // transfer_queue_index,
+            supported_sample_counts,
+            stencil_format,
+            wireframe_supported,
+            dynamic_rendering_supported,
+            full_screen_exclusive_supported,
+            present_wait_supported,
+            min_uniform_buffer_offset_alignment,
+            transfer_queue_index,
+        })
+    }
+}
+
+/// Picks the suitable physical device matching `gpu_preference` the best
+///
+/// A name is matched case insensitive, otherwise the preferred device type comes first, then
+/// the other GPUs and any other device last
+fn select_physical_device<T>(
+    candidates: Vec<T>,
+    gpu_preference: &GpuPreference,
+    properties: impl Fn(&T) -> &vk::PhysicalDeviceProperties,
+) -> Option<T> {
+    let rank = |device_type: vk::PhysicalDeviceType| -> u32 {
+        let preferred = match gpu_preference {
+            GpuPreference::Integrated => vk::PhysicalDeviceType::INTEGRATED_GPU,
+            _ => vk::PhysicalDeviceType::DISCRETE_GPU,
+        };
+
+        match device_type {
+            t if t == preferred => 0,
+            vk::PhysicalDeviceType::DISCRETE_GPU | vk::PhysicalDeviceType::INTEGRATED_GPU => 1,
+            vk::PhysicalDeviceType::VIRTUAL_GPU => 2,
+            _ => 3,
+        }
+    };
+
+    match gpu_preference {
+        GpuPreference::Name(name) => candidates.into_iter().find(|candidate| {
+            unsafe { CStr::from_ptr(properties(candidate).device_name.as_ptr()) }
+                .to_string_lossy()
+                .to_lowercase()
+                .contains(&name.to_lowercase())
+        }),
+        _ => candidates
+            .into_iter()
+            .min_by_key(|candidate| rank(properties(candidate).device_type)),
+    }
+}
+
+/// Picks the graphics and present queue family indices out of the supported families
+///
+/// A single family supporting both is preferred, otherwise the first graphics and
+/// the first present family is used
+fn select_queue_families(
+    graphics_support: &[bool],
+    present_support: &[bool],
+) -> Option<(u32, u32)> {
+    let is_supported =
+        |support: &[bool], index: usize| support.get(index).copied().unwrap_or(false);
+
+    if let Some(index) = (0..graphics_support.len()).find(|&index| {
+        is_supported(graphics_support, index) && is_supported(present_support, index)
+    }) {
+        return Some((index as u32, index as u32));
+    }
+
+    let graphics_index = (0..graphics_support.len()).find(|&index| graphics_support[index])?;
+    let present_index = (0..present_support.len()).find(|&index| present_support[index])?;
+
+    Some((graphics_index as u32, present_index as u32))
+}
+
+/// Picks a dedicated transfer queue family, one without graphics support
+///
+/// Families without compute support are preferred, as those are usually the DMA engines.
+/// Only families copying images of any size are considered
+fn select_transfer_family(queue_families: &[vk::QueueFamilyProperties]) -> Option<u32> {
+    let is_dedicated = |qf: &vk::QueueFamilyProperties| {
+        qf.queue_count > 0
+            && qf.queue_flags.contains(vk::QueueFlags::TRANSFER)
+            && !qf.queue_flags.contains(vk::QueueFlags::GRAPHICS)
+            && qf.min_image_transfer_granularity
+                == (vk::Extent3D {
+                    width: 1,
+                    height: 1,
+                    depth: 1,
+                })
+    };
+
+    let index = queue_families
+        .iter()
+        .position(|qf| is_dedicated(qf) && !qf.queue_flags.contains(vk::QueueFlags::COMPUTE))
+        .or_else(|| queue_families.iter().position(is_dedicated))?;
+
+    Some(index as u32)
+}
+
+/// Swapchain replaced by `recreate_swapchain` with the resources of its images
+struct RetiredSwapchain {
+    swapchain: vk::SwapchainKHR,
+    image_views: Vec<vk::ImageView>,
+    frame_buffers: Vec<vk::Framebuffer>,
+    color_target: Option<ColorTarget>,
+    stencil_target: StencilTarget,
+    semaphores_release: Vec<vk::Semaphore>,
+    /// Frames in flight whose fences are left to wait for before it can be destroyed
+    frames_left: Vec<usize>,
+}
+
+impl RetiredSwapchain {
+    fn destroy(
+        self,
+        logical_device: &ash::Device,
+        swapchain_loader: &khr::Swapchain,
+        allocator: &mut MemoryAllocator,
+    ) {
+        unsafe {
+            for fb in self.frame_buffers {
+                logical_device.destroy_framebuffer(fb, None);
+            }
+
+            for iv in self.image_views {
+                logical_device.destroy_image_view(iv, None);
+            }
+
+            for semaphore in self.semaphores_release {
+                logical_device.destroy_semaphore(semaphore, None);
+            }
+
+            swapchain_loader.destroy_swapchain(self.swapchain, None);
+        }
+
+        if let Some(color_target) = self.color_target {
+            color_target.destroy(logical_device, allocator);
+        }
+        self.stencil_target.destroy(logical_device, allocator);
+    }
+}
+
+/// Sorts the `draw_pool` by layer into batches of consecutive instances sharing the same
+/// object, pipeline and texture, gives back the ranges of the batches
+///
+/// Stable sort, instances on the same layer keep their submission order
+fn sort_into_batches(draw_pool: &mut [ObjectInstance]) -> Vec<Range<usize>> {
+    draw_pool.sort_by_key(ObjectInstance::draw_order);
+
+    let mut draw_batches: Vec<Range<usize>> = Vec::new();
+    for (index, draw_instance) in draw_pool.iter().enumerate() {
+        match draw_batches.last_mut() {
+            Some(batch) if draw_pool[batch.start].batch_key() == draw_instance.batch_key() => {
+                batch.end = index + 1
+            }
+            _ => draw_batches.push(index..index + 1),
+        }
+    }
+
+    draw_batches
+}
+
+/// Records a layout transition of the color `image` around the color attachment output stage,
+/// the stage waiting on the acquired swapchain image
+fn transition_image(
+    logical_device: &ash::Device,
+    command_buffer: vk::CommandBuffer,
+    image: vk::Image,
+    old_layout: vk::ImageLayout,
+    new_layout: vk::ImageLayout,
+) {
+    let (src_stage, src_access, dst_stage, dst_access) =
+        if new_layout == vk::ImageLayout::PRESENT_SRC_KHR {
+            (
+                vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+                vk::AccessFlags::COLOR_ATTACHMENT_WRITE,
+                vk::PipelineStageFlags::BOTTOM_OF_PIPE,
+                vk::AccessFlags::empty(),
+            )
+        } else {
+            (
+                vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+                vk::AccessFlags::empty(),
+                vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+                vk::AccessFlags::COLOR_ATTACHMENT_WRITE,
+            )
+        };
+
+    let barrier = vk::ImageMemoryBarrier::builder()
+        .src_access_mask(src_access)
+        .dst_access_mask(dst_access)
+        .old_layout(old_layout)
+        .new_layout(new_layout)
+        .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+        .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+        .image(image)
+        .subresource_range(
+            vk::ImageSubresourceRange::builder()
+                .aspect_mask(vk::ImageAspectFlags::COLOR)
+                .level_count(1)
+                .layer_count(1)
+                .build(),
+        );
+
+    unsafe {
+        logical_device.cmd_pipeline_barrier(
+            command_buffer,
+            src_stage,
+            dst_stage,
+            vk::DependencyFlags::empty(),
+            &[],
+            &[],
+            std::slice::from_ref(&barrier),
+        )
+    };
+}
+
+/// Records the transition of the stencil attachment of the masks for dynamic rendering, its
+/// content is discarded after the previous frame's fragment tests
+fn transition_stencil_image(
+    logical_device: &ash::Device,
+    command_buffer: vk::CommandBuffer,
+    stencil_target: &StencilTarget,
+) {
+    let barrier = vk::ImageMemoryBarrier::builder()
+        .src_access_mask(vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE)
+        .dst_access_mask(
+            vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_READ
+                | vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE,
+        )
+        .old_layout(vk::ImageLayout::UNDEFINED)
+        .new_layout(vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL)
+        .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+        .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+        .image(stencil_target.image)
+        .subresource_range(
+            vk::ImageSubresourceRange::builder()
+                .aspect_mask(StencilTarget::aspect_mask(stencil_target.format))
+                .level_count(1)
+                .layer_count(1)
+                .build(),
+        );
+
+    unsafe {
+        logical_device.cmd_pipeline_barrier(
+            command_buffer,
+            vk::PipelineStageFlags::LATE_FRAGMENT_TESTS,
+            vk::PipelineStageFlags::EARLY_FRAGMENT_TESTS,
+            vk::DependencyFlags::empty(),
+            &[],
+            &[],
+            std::slice::from_ref(&barrier),
+        )
+    };
+}
+
+/// Stencil value the masks are cleared to, no mask matches it
+const STENCIL_CLEAR_VALUE: vk::ClearValue = vk::ClearValue {
+    depth_stencil: vk::ClearDepthStencilValue {
+        depth: 1.0,
+        stencil: 0,
+    },
+};
+
+/// Gives back the clear values of the main pass attachments, the multisampled color,
+/// the resolve and the stencil attachment
+fn clear_values(clear_color: [f32; 4], samples: vk::SampleCountFlags) -> Vec<vk::ClearValue> {
+    let color = vk::ClearValue {
+        color: vk::ClearColorValue {
+            float32: clear_color,
+        },
+    };
+
+    if samples == vk::SampleCountFlags::TYPE_1 {
+        vec![color, STENCIL_CLEAR_VALUE]
+    } else {
+        vec![color, color, STENCIL_CLEAR_VALUE]
+    }
+}
+
+/// Number of syncronization objects, acquire semaphores & fences for each frame in flight and
+/// release semaphores for each swapchain image
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct SyncCounts {
+    acquire: usize,
+    release: usize,
+}
+
+impl SyncCounts {
+    fn new(max_frames_inflight: usize, image_count: usize) -> Self {
+        Self {
+            acquire: max_frames_inflight,
+            release: image_count,
+        }
+    }
+}
+
+/// Release semaphore signaled by the rendering to the swapchain image `image_index`
+fn release_semaphore<T>(semaphores_release: &[T], image_index: u32) -> Result<&T> {
+    semaphores_release
+        .get(image_index as usize)
+        .context("Release Semaphores: Index out of bounds")
+}
+
+/// Creates `count` binary semaphores
+fn create_semaphores(logical_device: &ash::Device, count: usize) -> Result<Vec<vk::Semaphore>> {
+    (0..count)
+        .map(|_| {
+            Ok(unsafe {
+                logical_device.create_semaphore(&vk::SemaphoreCreateInfo::default(), None)
+            }?)
         })
+        .collect()
+}
+
+/// Uploads the indices of `object_pool` as 16 bit while every vertex is addressable by it,
+/// otherwise as 32 bit
+fn create_index_buffer(
+    logical_device: &ash::Device,
+    allocator: &mut MemoryAllocator,
+    queues: &UploadQueues,
+    object_pool: &ObjectPool,
+) -> Result<(buffers::StorageBuffer, vk::IndexType)> {
+    match object_pool.indices_u16() {
+        Some(indices) => Ok((
+            buffers::StorageBuffer::new(
+                logical_device,
+                allocator,
+                queues,
+                (std::mem::size_of::<u16>() * indices.len()) as u64,
+                DataUsage::INDEX,
+                &indices,
+                std::mem::align_of::<u16>() as u64,
+            )?,
+            vk::IndexType::UINT16,
+        )),
+        None => Ok((
+            buffers::StorageBuffer::new(
+                logical_device,
+                allocator,
+                queues,
+                (std::mem::size_of::<u32>() * object_pool.indices.len()) as u64,
+                DataUsage::INDEX,
+                &object_pool.indices,
+                std::mem::align_of::<u32>() as u64,
+            )?,
+            vk::IndexType::UINT32,
+        )),
     }
 }
 
@@ -1213,31 +5785,56 @@ impl Device {
 //=== Render Statistics
 //==================================================
 
-struct RenderStats {
+/// Timings and counters of the last frames, see `Renderer::stats`
+pub struct RenderStats {
     turned_off: bool,
+    overlay: bool,
     frames_per_sec: u32,
+    last_frame_time: u128,
+    /// Ring buffer of the last frame times, oldest first
+    frame_times: VecDeque<Duration>,
     last_draw_request_time: u128,
     last_draw_pool_creation_time: u128,
     last_draw_pool_elements: usize,
     last_draw_pool_vertices: usize,
+    last_draw_calls: usize,
+    last_culled_instances: usize,
+    last_pacing_time: u128,
+    target_frame_time: Option<Duration>,
+    last_present: Option<Instant>,
+    last_present_latency: u128,
     frame_counter: u32,
     fps_instant: Instant,
+    frame_instant: Instant,
     draw_request_instant: Instant,
     pool_creation_instant: Instant,
 }
 
 impl RenderStats {
+    /// Number of frame times kept for the percentiles and the sparkline
+    pub const FRAME_HISTORY: usize = 240;
+
     /// Creates a new render statistics
-    fn new() -> Self {
+    fn new(frame_limit: Option<FrameLimit>) -> Self {
         Self {
             turned_off: false,
+            overlay: true,
             frames_per_sec: 0,
+            last_frame_time: 0,
+            frame_times: VecDeque::with_capacity(Self::FRAME_HISTORY),
             last_draw_request_time: 0,
             last_draw_pool_creation_time: 0,
             last_draw_pool_elements: 0,
             last_draw_pool_vertices: 0,
+            last_draw_calls: 0,
+            last_culled_instances: 0,
+            last_pacing_time: 0,
+            target_frame_time: frame_limit.map(|limit| limit.frame_time()),
+            last_present: None,
+            last_present_latency: 0,
             frame_counter: 0,
             fps_instant: Instant::now(),
+            frame_instant: Instant::now(),
             draw_request_instant: Instant::now(),
             pool_creation_instant: Instant::now(),
         }
@@ -1276,56 +5873,554 @@ impl RenderStats {
             return;
         }
 
-        self.last_draw_pool_creation_time = self.pool_creation_instant.elapsed().as_micros();
+        self.last_draw_pool_creation_time = self.pool_creation_instant.elapsed().as_micros();
+    }
+
+    /// Frames drawn in the last second
+    pub fn fps(&self) -> u32 {
+        self.frames_per_sec
+    }
+
+    /// Time between the last two draw requests
+    pub fn frame_time(&self) -> Duration {
+        Duration::from_micros(self.last_frame_time as u64)
+    }
+
+    /// Frame times of the last [`RenderStats::FRAME_HISTORY`] frames, oldest first
+    pub fn frame_time_history(&self) -> impl Iterator<Item = Duration> + '_ {
+        self.frame_times.iter().copied()
+    }
+
+    /// Gives back the `percentile` (0.0 - 100.0) of the frame time history,
+    /// eg. the p99 is the frame time only 1% of the frames are slower than
+    pub fn frame_time_percentile(&self, percentile: f32) -> Duration {
+        let mut frame_times: Vec<Duration> = self.frame_times.iter().copied().collect();
+        frame_times.sort_unstable();
+
+        if frame_times.is_empty() {
+            return Duration::ZERO;
+        }
+
+        // Nearest rank
+        let rank =
+            (percentile.clamp(0.0, 100.0) / 100.0 * frame_times.len() as f32).ceil() as usize;
+        frame_times[rank.clamp(1, frame_times.len()) - 1]
+    }
+
+    /// Draws the last `width` frame times as a line of bar characters scaled to the slowest one,
+    /// so stutters stand out as spikes
+    pub fn frame_time_sparkline(&self, width: usize) -> String {
+        const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+        let skip = self.frame_times.len().saturating_sub(width);
+        let max = self
+            .frame_times
+            .iter()
+            .skip(skip)
+            .max()
+            .copied()
+            .unwrap_or_default();
+
+        self.frame_times
+            .iter()
+            .skip(skip)
+            .map(|frame_time| {
+                let level = if max.is_zero() {
+                    0
+                } else {
+                    (frame_time.as_secs_f32() / max.as_secs_f32() * (BARS.len() - 1) as f32).round()
+                        as usize
+                };
+
+                BARS[level.min(BARS.len() - 1)]
+            })
+            .collect()
+    }
+
+    /// Adds a frame time to the history, dropping the oldest one when it is full
+    fn push_frame_time(&mut self, frame_time: Duration) {
+        if self.frame_times.len() == Self::FRAME_HISTORY {
+            self.frame_times.pop_front();
+        }
+
+        self.frame_times.push_back(frame_time);
+    }
+
+    /// Frame time of the frame limit, compared to the actual [`RenderStats::frame_time`]
+    pub fn target_frame_time(&self) -> Option<Duration> {
+        self.target_frame_time
+    }
+
+    /// Time the frame limiter waited before the last draw request
+    pub fn pacing_time(&self) -> Duration {
+        Duration::from_micros(self.last_pacing_time as u64)
+    }
+
+    /// When the last waited frame was shown, only with [`RendererBuilder::low_latency`]
+    pub fn present_time(&self) -> Option<Instant> {
+        self.last_present
+    }
+
+    /// Time from presenting the last waited frame until it was shown
+    pub fn present_latency(&self) -> Duration {
+        Duration::from_micros(self.last_present_latency as u64)
+    }
+
+    /// Time spent in the last draw request, including the wait for its frame
+    pub fn draw_request_time(&self) -> Duration {
+        Duration::from_micros(self.last_draw_request_time as u64)
+    }
+
+    /// Time spent sorting, uploading and recording the draw pool in the last draw request
+    pub fn pool_creation_time(&self) -> Duration {
+        Duration::from_micros(self.last_draw_pool_creation_time as u64)
+    }
+
+    /// Number of instances drawn in the last frame
+    pub fn draw_count(&self) -> usize {
+        self.last_draw_pool_elements
+    }
+
+    /// Number of draw calls recorded in the last frame
+    pub fn draw_calls(&self) -> usize {
+        self.last_draw_calls
+    }
+
+    /// Number of instances skipped in the last frame, they were outside of the view
+    pub fn culled_count(&self) -> usize {
+        self.last_culled_instances
+    }
+
+    /// Number of vertices in the object pool
+    pub fn vertex_count(&self) -> usize {
+        self.last_draw_pool_vertices
+    }
+
+    /// Gives back the current stats as a [`String`]
+    pub fn as_text(&self) -> String {
+        format!("[Statistics]\nfps: {}\nrequest time: {} us\npool creation time:{}\nelements:{}\nvertices:{}\ndraw calls:{}", 
+        self.frames_per_sec,
+        self.last_draw_request_time,
+        self.last_draw_pool_creation_time,
+        self.last_draw_pool_elements,
+        self.last_draw_pool_vertices,
+        self.last_draw_calls)
+    }
+}
+
+//==================================================
+//=== Debug Overlay
+//==================================================
+
+/// State shown by the debug overlay, see [`Renderer::debug_info`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DebugInfo {
+    pub camera_position: glm::Vec2,
+    pub zoom: f32,
+    pub draw_pool_size: usize,
+    /// World position under the cursor
+    pub cursor_world: glm::Vec2,
+    /// Topmost instance under the cursor, see [`Renderer::pick`]
+    pub hovered: Option<HoveredInstance>,
+}
+
+/// Color and transform of the instance under the cursor
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HoveredInstance {
+    pub id: DrawInstanceId,
+    pub color: glm::Vec3,
+    pub position: glm::Vec2,
+    pub rotation: f32,
+    pub scale: glm::Vec2,
+    pub tag: Option<u32>,
+}
+
+impl DebugInfo {
+    /// Gives back the debug info as a [`String`]
+    pub fn as_text(&self) -> String {
+        let mut text = format!(
+            "[Debug]\ncamera: {:.2}, {:.2}\nzoom: {:.2}\nelements: {}\ncursor: {:.2}, {:.2}",
+            self.camera_position.x,
+            self.camera_position.y,
+            self.zoom,
+            self.draw_pool_size,
+            self.cursor_world.x,
+            self.cursor_world.y,
+        );
+
+        match &self.hovered {
+            Some(hovered) => text.push_str(&format!(
+                "\nhovered: #{}\ncolor: {:.2}, {:.2}, {:.2}\nposition: {:.2}, {:.2}\nrotation: {:.1}\nscale: {:.2}, {:.2}",
+                hovered.id.0,
+                hovered.color.x,
+                hovered.color.y,
+                hovered.color.z,
+                hovered.position.x,
+                hovered.position.y,
+                hovered.rotation,
+                hovered.scale.x,
+                hovered.scale.y,
+            )),
+            None => text.push_str("\nhovered: -"),
+        }
+
+        if let Some(tag) = self.hovered.and_then(|hovered| hovered.tag) {
+            text.push_str(&format!("\ntag: {}", tag));
+        }
+
+        text
+    }
+}
+
+//==================================================
+//=== Draw Instance
+//==================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AnchorType {
+    Locked,
+    Unlocked,
+}
+
+/// Coordinate space of the positions and sizes of draw instances
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Space {
+    /// World units seen through the camera, placed with the [`AnchorType`]
+    #[default]
+    World,
+    /// Window (or viewport region) pixels from the bottom left corner, the y axis points up like
+    /// in world space
+    ///
+    /// The camera is bypassed, so sizes stay the same at any zoom, a unit of the objects is
+    /// a pixel (eg. a rectangle of scale 100.0 is 20 pixels wide). The [`AnchorType`] is ignored
+    Screen,
+}
+
+/// Parameters applied to every draw instance created after they are set
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DrawParams {
+    /// Instances on higher layers are drawn above lower ones, independent of call order
+    pub layer: i32,
+    /// Custom pipeline the instances are drawn with, `None` uses the built-in ones
+    pub pipeline: Option<PipelineHandle>,
+    /// Blends the color of the instances into a secondary color, `None` fills with a flat color
+    pub gradient: Option<Gradient>,
+    /// Number of segments of generated circles and rounded corners, `0` uses a default count
+    pub segments: u32,
+    pub space: Space,
+    /// Window pixels the instances are clipped to, set by [`Renderer::push_clip`]
+    pub clip: Option<ClipRect>,
+    /// Stencil mask the instances are drawn into or clipped by, set by [`Renderer::begin_mask`]
+    pub mask: Option<Mask>,
+    /// Point of the objects placed at the position of the instances, they rotate and scale
+    /// around it, eg. [`Pivot::Left`] for a bar growing to the right. Text is not affected
+    pub pivot: Pivot,
+    /// Dash pattern, caps and joints of lines, polylines and bezier curves, solid by default
+    pub line_style: LineStyle,
+    /// Number given back for the instances by [`Renderer::tag`] and the debug overlay, eg. to
+    /// map picked instances to the entities of the application
+    pub tag: Option<u32>,
+    /// Draws circles, rounded rectangles and their borders as single quads with the edge
+    /// evaluated in the fragment shader, anti-aliased and crisp at any zoom
+    ///
+    /// Custom pipelines and the shapes of masks keep the generated meshes
+    pub sdf: bool,
+}
+
+/// Rounded rectangle drawn by the SDF pipeline on the rectangle object, see [`DrawParams::sdf`]
+///
+/// The size comes from the scale of the instance, the lengths are in world units
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SdfShape {
+    /// Radius of the corners, half of the shorter side gives a circle or a capsule
+    pub corner_radius: f32,
+    /// Thickness of the border inside the edge, `0.0` fills the shape
+    pub border: f32,
+}
+
+impl SdfShape {
+    /// Signed distance of `position` to the edge of the shape of `half_size`, negative inside
+    ///
+    /// Matches the fragment shader of the SDF pipeline
+    pub fn distance(&self, position: glm::Vec2, half_size: glm::Vec2) -> f32 {
+        let radius = self.corner_radius.clamp(0.0, half_size.x.min(half_size.y));
+        let q = position.abs() - half_size + glm::vec2(radius, radius);
+        let distance =
+            glm::vec2(q.x.max(0.0), q.y.max(0.0)).norm() + q.x.max(q.y).min(0.0) - radius;
+
+        if self.border > 0.0 {
+            distance.max(-(distance + self.border))
+        } else {
+            distance
+        }
+    }
+}
+
+/// Stencil mask of an instance, see [`Renderer::begin_mask`]
+///
+/// Shapes sort before the instances they clip
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Mask {
+    /// Stencil value of the mask, unique in a frame
+    pub id: u8,
+    pub role: MaskRole,
+}
+
+/// Part an instance plays in its [`Mask`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MaskRole {
+    /// Drawn into the stencil attachment only, not visible
+    Shape,
+    /// Drawn only inside the shapes of the mask
+    Clipped,
+}
+
+/// Rectangle of window pixels from the bottom left corner like [`Space::Screen`], drawing
+/// outside of it is clipped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClipRect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl ClipRect {
+    /// Creates a new [`ClipRect`] of `width` x `height` pixels with its bottom left corner at
+    /// `x`, `y`
+    pub fn new(x: i32, y: i32, width: u32, height: u32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Gives back the overlap of the rectangles, it is empty when they don't overlap
+    pub fn intersect(&self, other: &ClipRect) -> ClipRect {
+        let min_x = self.x.max(other.x);
+        let min_y = self.y.max(other.y);
+        let max_x = (self.x as i64 + self.width as i64).min(other.x as i64 + other.width as i64);
+        let max_y = (self.y as i64 + self.height as i64).min(other.y as i64 + other.height as i64);
+
+        ClipRect {
+            x: min_x,
+            y: min_y,
+            width: (max_x - min_x as i64).max(0) as u32,
+            height: (max_y - min_y as i64).max(0) as u32,
+        }
+    }
+
+    /// Gives back the part of `scissor` inside the rectangle, the framebuffer is
+    /// `framebuffer_height` pixels high and its y axis points down
+    pub(crate) fn scissor(&self, scissor: vk::Rect2D, framebuffer_height: u32) -> vk::Rect2D {
+        let top = framebuffer_height as i64 - (self.y as i64 + self.height as i64);
+
+        let min_x = (self.x as i64).max(scissor.offset.x as i64);
+        let min_y = top.max(scissor.offset.y as i64);
+        let max_x = (self.x as i64 + self.width as i64)
+            .min(scissor.offset.x as i64 + scissor.extent.width as i64);
+        let max_y =
+            (top + self.height as i64).min(scissor.offset.y as i64 + scissor.extent.height as i64);
+
+        vk::Rect2D {
+            offset: vk::Offset2D {
+                x: min_x.max(0) as i32,
+                y: min_y.max(0) as i32,
+            },
+            extent: vk::Extent2D {
+                width: (max_x - min_x.max(0)).max(0) as u32,
+                height: (max_y - min_y.max(0)).max(0) as u32,
+            },
+        }
+    }
+}
+
+/// Fill going from the color of an instance to a secondary `color` across the object
+///
+/// The gradient spans the bounding box of the object and rotates with it
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Gradient {
+    /// Changes along `direction`, eg. `(0.0, 1.0)` reaches `color` at the top of the object
+    Linear {
+        color: glm::Vec3,
+        direction: glm::Vec2,
+    },
+    /// Changes from the center of the object, reaching `color` at its furthest edge
+    Radial { color: glm::Vec3 },
+}
+
+impl Gradient {
+    /// Mode read by the fragment shaders, 0 is reserved for flat fills
+    fn mode(&self) -> f32 {
+        match self {
+            Gradient::Linear { .. } => 1.0,
+            Gradient::Radial { .. } => 2.0,
+        }
+    }
+
+    fn color(&self) -> glm::Vec3 {
+        match self {
+            Gradient::Linear { color, .. } | Gradient::Radial { color } => *color,
+        }
     }
 
-    /// Gives back the current stats as a [`String`]
-    fn as_text(&self) -> String {
-        format!("[Statistics]\nfps: {}\nrequest time: {} us\npool creation time:{}\nelements:{}\nvertices:{}", 
-        self.frames_per_sec,
-        self.last_draw_request_time,
-        self.last_draw_pool_creation_time,
-        self.last_draw_pool_elements,
-        self.last_draw_pool_vertices)
+    /// Start and end point of the gradient in the local space of `object_data`
+    fn points(&self, object_data: &ObjectData) -> glm::Vec4 {
+        let center = (object_data.bounds_min + object_data.bounds_max) * 0.5;
+        let half_size = (object_data.bounds_max - object_data.bounds_min) * 0.5;
+
+        let end = match self {
+            Gradient::Linear { direction, .. } => {
+                let direction = direction
+                    .try_normalize(f32::EPSILON)
+                    .unwrap_or(glm::vec2(1.0, 0.0));
+                let extent = direction.x.abs() * half_size.x + direction.y.abs() * half_size.y;
+
+                return glm::vec4(
+                    center.x - direction.x * extent,
+                    center.y - direction.y * extent,
+                    center.x + direction.x * extent,
+                    center.y + direction.y * extent,
+                );
+            }
+            Gradient::Radial { .. } => center + glm::vec2(half_size.x.max(half_size.y), 0.0),
+        };
+
+        glm::vec4(center.x, center.y, end.x, end.y)
     }
 }
 
-//==================================================
-//=== Draw Instance
-//==================================================
+/// Point of an object its instances are placed at and rotate and scale around
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Pivot {
+    /// Origin of the object, the center of the built-in shapes
+    #[default]
+    Center,
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+    /// Offset from the origin in the units of the unscaled object
+    Offset(glm::Vec2),
+}
 
-pub enum AnchorType {
-    Locked,
-    Unlocked,
+impl Pivot {
+    /// Gives back the point in the local space of `object_data`, the edges are the ones of
+    /// its bounding box
+    pub(crate) fn point(&self, object_data: &ObjectData) -> glm::Vec2 {
+        let (min, max) = (object_data.bounds_min, object_data.bounds_max);
+
+        match self {
+            Pivot::Center => glm::Vec2::zeros(),
+            Pivot::TopLeft => glm::vec2(min.x, max.y),
+            Pivot::Top => glm::vec2(0.0, max.y),
+            Pivot::TopRight => max,
+            Pivot::Left => glm::vec2(min.x, 0.0),
+            Pivot::Right => glm::vec2(max.x, 0.0),
+            Pivot::BottomLeft => min,
+            Pivot::Bottom => glm::vec2(0.0, min.y),
+            Pivot::BottomRight => glm::vec2(max.x, min.y),
+            Pivot::Offset(offset) => *offset,
+        }
+    }
 }
 
+/// Data of a single instance, read by the shaders from the instance buffer
+///
+/// Field order and padding follows the std430 `ModelData` struct of the instance storage buffer,
+/// the fields are plain float arrays so the whole struct can be copied as bytes
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable)]
+#[repr(C)]
 pub struct DrawInstanceData {
-    transform: glm::Mat4,
-    color: glm::Vec3,
+    /// Column major model matrix
+    transform: [[f32; 4]; 4],
+    color: [f32; 3],
+    /// Pads `color` to the 16 byte alignment of the following `vec4`
+    _padding: f32,
+    /// Atlas region of textures, the half size, corner radius and border of [`SdfShape`]s
+    uv_rect: [f32; 4],
+    /// Secondary color and the gradient mode in `w`
+    gradient_color: [f32; 4],
+    /// Start and end point of the gradient
+    gradient_points: [f32; 4],
 }
 
 impl DrawInstanceData {
-    /// Creates a new empty [`DrawInstanceData`]
-    pub fn new_empty() -> Self {
+    /// Creates the [`DrawInstanceData`] of `draw_instance`, the gradient spans `object_data`
+    pub fn new(draw_instance: &ObjectInstance, object_data: &ObjectData) -> Self {
+        // Rotation, skew and scale around the pivot, placed at the object position
+        let linear = draw_instance.linear();
+        let offset = draw_instance.position.xy() - linear * draw_instance.pivot.point(object_data);
+
+        let transform = glm::mat4(
+            linear.m11,
+            linear.m12,
+            0.0,
+            offset.x,
+            linear.m21,
+            linear.m22,
+            0.0,
+            offset.y,
+            0.0,
+            0.0,
+            draw_instance.scale.z,
+            draw_instance.position.z,
+            0.0,
+            0.0,
+            0.0,
+            1.0,
+        );
+
+        let (gradient_color, gradient_points) = match &draw_instance.gradient {
+            Some(gradient) => (
+                glm::vec4(
+                    gradient.color().x,
+                    gradient.color().y,
+                    gradient.color().z,
+                    gradient.mode(),
+                ),
+                gradient.points(object_data),
+            ),
+            None => (glm::Vec4::zeros(), glm::Vec4::zeros()),
+        };
+
         Self {
-            transform: glm::Mat4::zeros(),
-            color: glm::Vec3::zeros(),
+            transform: transform.into(),
+            color: draw_instance.color.into(),
+            _padding: 0.0,
+            uv_rect: match draw_instance.sdf {
+                Some(sdf) => {
+                    let half_size = draw_instance.sdf_half_size();
+                    glm::vec4(half_size.x, half_size.y, sdf.corner_radius, sdf.border)
+                }
+                None => draw_instance
+                    .uv_rect
+                    .unwrap_or(glm::vec4(0.0, 0.0, 1.0, 1.0)),
+            }
+            .into(),
+            gradient_color: gradient_color.into(),
+            gradient_points: gradient_points.into(),
         }
     }
 
-    /// Gives back the [`DrawInstanceData`] as a slice
-    ///
-    /// # Safety
-    ///
-    /// This is safe to call, since the safety conditions
-    /// of the`std::slice::from_raw_parts` function are met.
-    pub fn as_slice(&self) -> &[f32] {
-        unsafe {
-            std::slice::from_raw_parts(
-                self.transform.as_ptr(),
-                self.transform.len() + self.color.len(),
-            )
-        }
+    /// Creates a new empty [`DrawInstanceData`]
+    pub fn new_empty() -> Self {
+        Zeroable::zeroed()
     }
 }
 
@@ -1333,31 +6428,69 @@ impl DrawInstanceData {
 //=== Render Loop
 //==================================================
 
+#[derive(Clone)]
 pub struct Scene {
     camera_zoom: f32,
     camera_pos: glm::Vec3,
+    /// Roll of the camera around the view axis in radians
+    camera_rotation: f32,
     camera_vp: CameraVP,
     projection: ProjectionType,
+    /// Size of the window in pixels, the screen positions are relative to it
+    surface_extent: vk::Extent2D,
 }
 
 impl Scene {
-    /// Creates a new [`Scene`] based on the current windows size
-    pub fn new(window: &winit::window::Window, projection_type: ProjectionType) -> Self {
-        let aspect = (window.inner_size().width / window.inner_size().height) as f32;
+    /// Creates a new [`Scene`] for a window of `width` x `height` pixels
+    pub fn new(width: u32, height: u32, projection_type: ProjectionType) -> Self {
+        let aspect = (width / height.max(1)) as f32;
         let camera_pos = glm::vec3(0.0, 0.0, 2.0);
         let camera_vp = CameraVP::new(&camera_pos, &projection_type, aspect);
 
         Self {
             camera_zoom: 1.0,
             camera_pos,
+            camera_rotation: 0.0,
             camera_vp,
             projection: projection_type,
+            surface_extent: vk::Extent2D { width, height },
         }
     }
 
     /// Change the current zoom level with the value of `delta`
     pub fn zoom(&mut self, delta: f32) -> () {
         self.camera_zoom = f32::clamp(self.camera_zoom + delta, 0.1, 2.0);
+
+        self.update_view();
+    }
+
+    /// Changes the zoom level with the value of `delta` around `screen_position` (in window
+    /// pixels), the world point under it stays in place
+    pub fn zoom_at(&mut self, screen_position: glm::Vec2, delta: f32) {
+        let before = Self::unproject(&self.camera_vp, screen_position, self.surface_extent);
+        self.zoom(delta);
+        let after = Self::unproject(&self.camera_vp, screen_position, self.surface_extent);
+
+        // Moving the camera shifts every point of the z = 0 plane by the same offset
+        self.set_position(self.position() + before - after);
+    }
+
+    /// Gives back the zoom level, 1.0 shows the scene at its default size
+    pub fn zoom_level(&self) -> f32 {
+        self.camera_zoom
+    }
+
+    /// Gives back the world position the camera is looking at
+    pub fn position(&self) -> glm::Vec2 {
+        self.camera_pos.xy()
+    }
+
+    /// Moves the camera to look at the world `position`
+    pub fn set_position(&mut self, position: glm::Vec2) {
+        self.camera_pos.x = position.x;
+        self.camera_pos.y = position.y;
+
+        self.update_view();
     }
 
     /// Pan the camera on the X and Y axis
@@ -1368,24 +6501,124 @@ impl Scene {
             self.camera_pos.z,
         );
 
-        self.camera_vp.view = glm::look_at(
-            &self.camera_pos,                                      // Camera Position
-            &glm::vec3(self.camera_pos.x, self.camera_pos.y, 0.0), // Camera Target
-            &glm::vec3(0.0, 1.0, 0.0),
+        self.update_view();
+    }
+
+    /// Sets the roll of the camera, a positive angle rotates the scene clockwise on screen
+    pub fn set_rotation(&mut self, radians: f32) {
+        self.camera_rotation = radians;
+
+        self.update_view();
+    }
+
+    /// Gives back the roll of the camera in radians
+    pub fn rotation(&self) -> f32 {
+        self.camera_rotation
+    }
+
+    /// Rebuilds the view matrix from the camera position, rotation and zoom
+    ///
+    /// The zoom scales the view instead of the projection, so screen space layers keep their size
+    fn update_view(&mut self) {
+        let zoom = glm::scaling(&glm::vec3(self.camera_zoom, self.camera_zoom, 1.0));
+
+        self.camera_vp.view = zoom
+            * glm::look_at(
+                &self.camera_pos,                                      // Camera Position
+                &glm::vec3(self.camera_pos.x, self.camera_pos.y, 0.0), // Camera Target
+                &glm::vec3(-self.camera_rotation.sin(), self.camera_rotation.cos(), 0.0), // Up Axis
+            );
+    }
+
+    /// Transforms a position relative to the view into world space
+    pub fn view_to_world(&self, position: glm::Vec2) -> glm::Vec2 {
+        glm::vec2(self.camera_pos.x, self.camera_pos.y)
+            + glm::rotate_vec2(&position, self.camera_rotation)
+    }
+
+    /// Transforms a world position into a position relative to the view, the inverse of
+    /// [`Scene::view_to_world`]
+    pub fn world_to_view(&self, position: glm::Vec2) -> glm::Vec2 {
+        glm::rotate_vec2(
+            &(position - glm::vec2(self.camera_pos.x, self.camera_pos.y)),
+            -self.camera_rotation,
+        )
+    }
+
+    /// Gives back the world position and rotation (in degrees) of an instance anchored with `anchor_type`
+    ///
+    /// Locked instances are placed relative to the view, they follow the camera movement and rotation
+    fn anchor(&self, x: f32, y: f32, rotation: f32, anchor_type: AnchorType) -> (glm::Vec3, f32) {
+        match anchor_type {
+            AnchorType::Locked => {
+                let position = self.view_to_world(glm::vec2(x, y));
+
+                (
+                    glm::vec3(position.x, position.y, 0.0),
+                    rotation + self.camera_rotation.to_degrees(),
+                )
+            }
+            AnchorType::Unlocked => (glm::vec3(x, y, 0.0), rotation),
+        }
+    }
+
+    /// Transforms a position in window pixels into a world position on the z = 0 plane
+    pub fn screen_to_world(&self, screen_position: glm::Vec2) -> glm::Vec2 {
+        Self::unproject(&self.camera_vp, screen_position, self.surface_extent)
+    }
+
+    /// Transforms a position in pixels of a window of `window_size` into a position on the
+    /// z = 0 plane seen by `camera_vp`
+    fn unproject(
+        camera_vp: &CameraVP,
+        screen_position: glm::Vec2,
+        window_size: vk::Extent2D,
+    ) -> glm::Vec2 {
+        let ndc = glm::vec2(
+            2.0 * screen_position.x / window_size.width as f32 - 1.0,
+            2.0 * screen_position.y / window_size.height as f32 - 1.0,
         );
+
+        camera_vp.unproject(ndc)
+    }
+
+    /// Gives back the part of the z = 0 plane seen by the camera
+    ///
+    /// The projection is updated to the window (or viewport region) at every draw request,
+    /// the bounds of a new [`Scene`] are only exact after the first one
+    pub fn visible_bounds(&self) -> WorldRect {
+        self.camera_vp.visible_bounds()
+    }
+
+    /// Transforms a world position on the z = 0 plane into window pixels
+    pub fn world_to_screen(&self, world_position: glm::Vec2) -> glm::Vec2 {
+        let window_size = self.surface_extent;
+        let clip = self.camera_vp.projection
+            * self.camera_vp.view
+            * glm::vec4(world_position.x, world_position.y, 0.0, 1.0);
+
+        glm::vec2(
+            (clip.x / clip.w + 1.0) * 0.5 * window_size.width as f32,
+            (clip.y / clip.w + 1.0) * 0.5 * window_size.height as f32,
+        )
     }
 
     /// Updates the projection matrix of the camera
     ///
     /// If the camera is fix then we do not need to call this function
-    pub fn update_projection(&mut self, window: &winit::window::Window) -> () {
+    pub fn update_projection(&mut self) -> () {
+        self.update_projection_aspect(
+            (self.surface_extent.width as f32) / (self.surface_extent.height as f32),
+        );
+    }
+
+    /// Updates the projection matrix of the camera for a view with `viewport_aspect` ratio
+    fn update_projection_aspect(&mut self, viewport_aspect: f32) {
         //let n = 2.0 * self.camera_zoom;
 
         let target_width = 4.0;
         let target_height = 3.0;
         let target_aspect = target_width / target_height;
-        let viewport_aspect =
-            (window.inner_size().width as f32) / (window.inner_size().height as f32);
 
         match self.projection {
             ProjectionType::Orthographic => {
@@ -1419,6 +6652,86 @@ impl Scene {
     }
 }
 
+/// Axis aligned rectangle in world units
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WorldRect {
+    pub min: glm::Vec2,
+    pub max: glm::Vec2,
+}
+
+impl WorldRect {
+    /// Creates a new [`WorldRect`] from its bottom left and top right corner
+    pub fn new(min: glm::Vec2, max: glm::Vec2) -> Self {
+        Self { min, max }
+    }
+
+    /// Gives back the smallest [`WorldRect`] containing every point of `points`
+    pub fn around(points: &[glm::Vec2]) -> Self {
+        let min = points
+            .iter()
+            .fold(glm::vec2(f32::INFINITY, f32::INFINITY), |min, point| {
+                glm::min2(&min, point)
+            });
+        let max = points.iter().fold(
+            glm::vec2(f32::NEG_INFINITY, f32::NEG_INFINITY),
+            |max, point| glm::max2(&max, point),
+        );
+
+        Self { min, max }
+    }
+
+    pub fn contains(&self, point: glm::Vec2) -> bool {
+        point.x >= self.min.x
+            && point.x <= self.max.x
+            && point.y >= self.min.y
+            && point.y <= self.max.y
+    }
+
+    /// Rectangles touching at their edges also intersect
+    pub fn intersects(&self, other: &WorldRect) -> bool {
+        self.min.x <= other.max.x
+            && other.min.x <= self.max.x
+            && self.min.y <= other.max.y
+            && other.min.y <= self.max.y
+    }
+}
+
+/// Part of the window drawn with its own camera, see `Renderer::set_viewports`
+#[derive(Clone)]
+pub struct ViewportRegion {
+    /// Left, top, width and height as fractions (0.0 - 1.0) of the window
+    pub rect: glm::Vec4,
+    pub camera: Scene,
+}
+
+impl ViewportRegion {
+    /// Creates a new [`ViewportRegion`] covering `rect` of the window, drawn with `camera`
+    pub fn new(rect: glm::Vec4, camera: Scene) -> Self {
+        Self { rect, camera }
+    }
+
+    /// Gives back the pixels of the region in a window of `extent`, clamped to the window
+    fn scissor(&self, extent: vk::Extent2D) -> vk::Rect2D {
+        let (width, height) = (extent.width as f32, extent.height as f32);
+
+        let left = (self.rect.x.clamp(0.0, 1.0) * width).round();
+        let top = (self.rect.y.clamp(0.0, 1.0) * height).round();
+        let right = ((self.rect.x + self.rect.z).clamp(0.0, 1.0) * width).round();
+        let bottom = ((self.rect.y + self.rect.w).clamp(0.0, 1.0) * height).round();
+
+        vk::Rect2D {
+            offset: vk::Offset2D {
+                x: left as i32,
+                y: top as i32,
+            },
+            extent: vk::Extent2D {
+                width: (right - left).max(1.0) as u32,
+                height: (bottom - top).max(1.0) as u32,
+            },
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct CameraVP {
     view: glm::Mat4,
@@ -1448,9 +6761,553 @@ impl CameraVP {
             projection,
         }
     }
+
+    /// Creates a new [`CameraVP`] showing `width` x `height` pixels, the origin is at the
+    /// bottom left corner
+    fn pixels(width: f32, height: f32) -> Self {
+        Self {
+            view: glm::Mat4::identity(),
+            // Bottom and top are swapped, the clip space y axis points down
+            projection: glm::ortho(0.0, width, height, 0.0, -100.0, 100.0),
+        }
+    }
+
+    /// Transforms a position in normalized device coordinates into a position on the z = 0 plane
+    fn unproject(&self, ndc: glm::Vec2) -> glm::Vec2 {
+        let inverse = glm::inverse(&(self.projection * self.view));
+        let unproject = |depth: f32| -> glm::Vec3 {
+            let world = inverse * glm::vec4(ndc.x, ndc.y, depth, 1.0);
+            world.xyz() / world.w
+        };
+
+        // Intersect the ray between the near and far plane with z = 0
+        let near = unproject(0.0);
+        let far = unproject(1.0);
+        let t = if (far.z - near.z).abs() > f32::EPSILON {
+            -near.z / (far.z - near.z)
+        } else {
+            0.0
+        };
+
+        glm::lerp(&near, &far, t).xy()
+    }
+
+    /// Gives back the bounding box of the z = 0 plane seen through the corners of the view
+    fn visible_bounds(&self) -> WorldRect {
+        let corners = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+            .map(|(x, y)| self.unproject(glm::vec2(x, y)));
+
+        WorldRect::around(&corners)
+    }
+
+    /// Gives back the camera with the same projection placed where a new [`Scene`] starts,
+    /// used by screen space layers
+    fn at_rest(&self) -> Self {
+        let position = glm::vec3(0.0, 0.0, 2.0);
+
+        Self {
+            view: glm::look_at(
+                &position,                               // Camera Position
+                &glm::vec3(position.x, position.y, 0.0), // Camera Target
+                &glm::vec3(0.0, 1.0, 0.0),               // Up Axis
+            ),
+            projection: self.projection,
+        }
+    }
 }
 
+#[derive(Debug, Clone, Copy)]
 pub enum ProjectionType {
     Orthographic,
     Perspective,
 }
+
+//==================================================
+//=== Unit Testing
+//==================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_queue_families() {
+        // Shared family is preferred even if separate ones come first
+        assert_eq!(
+            select_queue_families(&[true, false, true], &[false, true, true]),
+            Some((2, 2))
+        );
+
+        // Separate families
+        assert_eq!(
+            select_queue_families(&[true, false], &[false, true]),
+            Some((0, 1))
+        );
+
+        // No present support
+        assert_eq!(select_queue_families(&[true, true], &[false, false]), None);
+    }
+
+    #[test]
+    fn test_select_transfer_family() {
+        let family = |queue_flags: vk::QueueFlags, granularity: u32| vk::QueueFamilyProperties {
+            queue_flags,
+            queue_count: 1,
+            min_image_transfer_granularity: vk::Extent3D {
+                width: granularity,
+                height: granularity,
+                depth: 1,
+            },
+            ..Default::default()
+        };
+        let graphics = family(
+            vk::QueueFlags::GRAPHICS | vk::QueueFlags::COMPUTE | vk::QueueFlags::TRANSFER,
+            1,
+        );
+        let compute = family(vk::QueueFlags::COMPUTE | vk::QueueFlags::TRANSFER, 1);
+        let transfer = family(vk::QueueFlags::TRANSFER, 1);
+
+        // Transfer only family is preferred over async compute
+        assert_eq!(
+            select_transfer_family(&[graphics, compute, transfer]),
+            Some(2)
+        );
+        assert_eq!(select_transfer_family(&[graphics, compute]), Some(1));
+
+        // Graphics families and coarse image copies are not dedicated
+        assert_eq!(select_transfer_family(&[graphics]), None);
+        assert_eq!(
+            select_transfer_family(&[graphics, family(vk::QueueFlags::TRANSFER, 8)]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_gradient_points() {
+        let object_data = ObjectData {
+            bounds_min: glm::vec2(-0.1, -0.2),
+            bounds_max: glm::vec2(0.3, 0.2),
+            ..ObjectData::default()
+        };
+
+        // Linear gradient spans the bounding box along its direction
+        let linear = Gradient::Linear {
+            color: glm::Vec3::zeros(),
+            direction: glm::vec2(0.0, 2.0),
+        };
+        assert!(glm::vec4(0.1, -0.2, 0.1, 0.2).relative_eq(
+            &linear.points(&object_data),
+            f32::EPSILON,
+            1e-6
+        ));
+
+        // Radial gradient reaches the furthest edge from the center
+        let radial = Gradient::Radial {
+            color: glm::Vec3::zeros(),
+        };
+        assert!(glm::vec4(0.1, 0.0, 0.3, 0.0).relative_eq(
+            &radial.points(&object_data),
+            f32::EPSILON,
+            1e-6
+        ));
+    }
+
+    #[test]
+    fn test_pivot() {
+        let object_data = ObjectData {
+            bounds_min: glm::vec2(-0.1, -0.1),
+            bounds_max: glm::vec2(0.1, 0.1),
+            ..ObjectData::default()
+        };
+
+        // Bar growing to the right of its position
+        let bar = ObjectInstance {
+            position: glm::vec3(1.0, 0.0, 0.0),
+            scale: glm::vec3(3.0, 1.0, 1.0),
+            pivot: Pivot::Left,
+            ..ObjectInstance::default()
+        };
+        let transform = glm::Mat4::from(DrawInstanceData::new(&bar, &object_data).transform);
+        assert!(
+            ((transform * glm::vec4(-0.1, 0.0, 0.0, 1.0)).xy() - glm::vec2(1.0, 0.0)).norm() < 1e-5
+        );
+        let bounds = bar.world_bounds(&object_data);
+        assert!((bounds.min - glm::vec2(1.0, -0.1)).norm() < 1e-5);
+        assert!((bounds.max - glm::vec2(1.6, 0.1)).norm() < 1e-5);
+
+        // Clock hand turning around its bottom
+        let hand = ObjectInstance {
+            rotation: 90.0,
+            scale: glm::vec3(1.0, 1.0, 1.0),
+            pivot: Pivot::Bottom,
+            ..ObjectInstance::default()
+        };
+        let transform = glm::Mat4::from(DrawInstanceData::new(&hand, &object_data).transform);
+        assert!(
+            ((transform * glm::vec4(0.0, 0.1, 0.0, 1.0)).xy() - glm::vec2(-0.2, 0.0)).norm() < 1e-5
+        );
+        assert!(
+            Pivot::Offset(glm::vec2(0.1, 0.0)).point(&object_data)
+                == Pivot::Right.point(&object_data)
+        );
+    }
+
+    #[test]
+    fn test_draw_instance_layout() {
+        let draw_instance = ObjectInstance {
+            position: glm::vec3(1.0, 2.0, 0.0),
+            scale: glm::vec3(1.0, 1.0, 1.0),
+            color: glm::vec3(0.5, 0.25, 1.0),
+            ..ObjectInstance::default()
+        };
+        let data = DrawInstanceData::new(&draw_instance, &ObjectData::default());
+
+        // Floats line up with the std430 `ModelData` of the shaders
+        assert_eq!(std::mem::size_of::<DrawInstanceData>(), 128);
+        let floats: [f32; 32] = bytemuck::cast(data);
+        assert_eq!(floats[12..14], [1.0, 2.0]);
+        assert_eq!(floats[16..20], [0.5, 0.25, 1.0, 0.0]);
+        assert_eq!(floats[20..24], [0.0, 0.0, 1.0, 1.0]);
+        assert_eq!(floats[24..], [0.0; 8]);
+    }
+
+    #[test]
+    fn test_viewport_region_scissor() {
+        let extent = vk::Extent2D {
+            width: 800,
+            height: 600,
+        };
+
+        let rect = |x, y, w, h| {
+            let region = ViewportRegion {
+                rect: glm::vec4(x, y, w, h),
+                camera: Scene {
+                    camera_zoom: 1.0,
+                    camera_pos: glm::vec3(0.0, 0.0, 2.0),
+                    camera_rotation: 0.0,
+                    camera_vp: CameraVP::new(
+                        &glm::vec3(0.0, 0.0, 2.0),
+                        &ProjectionType::Orthographic,
+                        1.0,
+                    ),
+                    projection: ProjectionType::Orthographic,
+                    surface_extent: extent,
+                },
+            };
+
+            let scissor = region.scissor(extent);
+            (
+                scissor.offset.x,
+                scissor.offset.y,
+                scissor.extent.width,
+                scissor.extent.height,
+            )
+        };
+
+        // Right half of a split screen
+        assert_eq!(rect(0.5, 0.0, 0.5, 1.0), (400, 0, 400, 600));
+
+        // Regions outside of the window are clamped
+        assert_eq!(rect(0.75, 0.5, 0.5, 0.75), (600, 300, 200, 300));
+    }
+
+    #[test]
+    fn test_clip_rect() {
+        let panel = ClipRect::new(100, 50, 200, 100);
+
+        // Nested clips -> Overlap, disjoint ones are empty
+        assert_eq!(
+            panel.intersect(&ClipRect::new(250, 0, 100, 100)),
+            ClipRect::new(250, 50, 50, 50)
+        );
+        assert_eq!(panel.intersect(&ClipRect::new(0, 0, 50, 50)).width, 0);
+
+        // Bottom left origin -> Top left origin of a 600 pixel high window
+        let window = vk::Rect2D {
+            offset: vk::Offset2D { x: 0, y: 0 },
+            extent: vk::Extent2D {
+                width: 800,
+                height: 600,
+            },
+        };
+        let scissor = panel.scissor(window, 600);
+        assert_eq!((scissor.offset.x, scissor.offset.y), (100, 450));
+        assert_eq!((scissor.extent.width, scissor.extent.height), (200, 100));
+
+        // Partly outside of the region -> Clamped to it
+        let scissor = ClipRect::new(-50, 550, 100, 100).scissor(window, 600);
+        assert_eq!((scissor.offset.x, scissor.offset.y), (0, 0));
+        assert_eq!((scissor.extent.width, scissor.extent.height), (50, 50));
+    }
+
+    #[test]
+    fn test_zoom_at() {
+        let window_size = vk::Extent2D {
+            width: 800,
+            height: 600,
+        };
+        let cursor = glm::vec2(600.0, 150.0);
+
+        for projection in [ProjectionType::Orthographic, ProjectionType::Perspective] {
+            let camera_pos = glm::vec3(0.3, -0.2, 2.0);
+            let mut scene = Scene {
+                camera_zoom: 1.0,
+                camera_pos,
+                camera_rotation: 0.5,
+                camera_vp: CameraVP::new(&camera_pos, &projection, 4.0 / 3.0),
+                projection,
+                surface_extent: window_size,
+            };
+            scene.update_projection_aspect(4.0 / 3.0);
+            scene.update_view();
+
+            // World point under the cursor stays in place while zooming in and out
+            let grabbed = Scene::unproject(&scene.camera_vp, cursor, window_size);
+            scene.zoom_at(cursor, 0.5);
+            assert_eq!(scene.zoom_level(), 1.5);
+            let zoomed_in = Scene::unproject(&scene.camera_vp, cursor, window_size);
+            scene.zoom_at(cursor, -1.0);
+            let zoomed_out = Scene::unproject(&scene.camera_vp, cursor, window_size);
+
+            assert!((zoomed_in - grabbed).norm() < 1e-4);
+            assert!((zoomed_out - grabbed).norm() < 1e-4);
+            assert!((scene.position() - camera_pos.xy()).norm() > 1e-2);
+        }
+    }
+
+    #[test]
+    fn test_visible_bounds() {
+        let camera_pos = glm::vec3(1.0, 0.0, 2.0);
+        let mut scene = Scene {
+            camera_zoom: 2.0,
+            camera_pos,
+            camera_rotation: 0.0,
+            camera_vp: CameraVP::new(&camera_pos, &ProjectionType::Orthographic, 1.0),
+            projection: ProjectionType::Orthographic,
+            surface_extent: vk::Extent2D::default(),
+        };
+        scene.update_projection_aspect(4.0 / 3.0);
+        scene.update_view();
+
+        // 4 x 3 units at a zoom level of 1.0
+        let visible_bounds = scene.visible_bounds();
+        assert!((visible_bounds.min - glm::vec2(0.0, -0.75)).norm() < 1e-5);
+        assert!((visible_bounds.max - glm::vec2(2.0, 0.75)).norm() < 1e-5);
+
+        // Rotated instance reaching into the view from outside of it
+        let object_data = ObjectData {
+            bounds_min: glm::vec2(-0.1, -0.5),
+            bounds_max: glm::vec2(0.1, 0.5),
+            ..ObjectData::default()
+        };
+        let mut draw_instance = ObjectInstance {
+            position: glm::vec3(-0.4, 0.0, 0.0),
+            scale: glm::vec3(1.0, 1.0, 1.0),
+            ..ObjectInstance::default()
+        };
+        assert!(!visible_bounds.intersects(&draw_instance.world_bounds(&object_data)));
+
+        draw_instance.rotation = 90.0;
+        let bounds = draw_instance.world_bounds(&object_data);
+        assert!((bounds.max - glm::vec2(0.1, 0.1)).norm() < 1e-5);
+        assert!(visible_bounds.intersects(&bounds));
+        assert!(bounds.contains(glm::vec2(-0.8, 0.0)));
+    }
+
+    #[test]
+    fn test_pixel_camera() {
+        let camera_vp = CameraVP::pixels(800.0, 600.0);
+        let clip = |x, y| camera_vp.projection * camera_vp.view * glm::vec4(x, y, 0.0, 1.0);
+
+        // Bottom left pixel -> Bottom left corner, the clip space y axis points down
+        assert!((clip(0.0, 0.0).xy() - glm::vec2(-1.0, 1.0)).norm() < 1e-5);
+        assert!((clip(800.0, 600.0).xy() - glm::vec2(1.0, -1.0)).norm() < 1e-5);
+        assert!(clip(400.0, 300.0).xy().norm() < 1e-5);
+    }
+
+    #[test]
+    fn test_outlined_order() {
+        // Fill on the circle object, border streamed afterwards -> Border drawn over the fill
+        let fill = ObjectInstance {
+            object: ObjectHandle(1),
+            ..ObjectInstance::default()
+        };
+        let border = ObjectInstance {
+            streamed: true,
+            ..ObjectInstance::default()
+        };
+        let mut draw_pool = [fill, border];
+        assert_eq!(sort_into_batches(&mut draw_pool), vec![0..1, 1..2]);
+        assert!(!draw_pool[0].streamed && draw_pool[1].streamed);
+
+        // Both on the SDF rectangle -> Same batch, drawn in the order they were pushed
+        let sdf = SdfShape {
+            corner_radius: 0.1,
+            border: 0.0,
+        };
+        let fill = ObjectInstance {
+            sdf: Some(sdf),
+            ..ObjectInstance::default()
+        };
+        let border = ObjectInstance {
+            sdf: Some(SdfShape {
+                border: 0.02,
+                ..sdf
+            }),
+            ..ObjectInstance::default()
+        };
+        assert_eq!(fill.batch_key(), border.batch_key());
+    }
+
+    #[test]
+    fn test_debug_info_text() {
+        let mut debug_info = DebugInfo {
+            camera_position: glm::vec2(1.0, -2.0),
+            zoom: 1.5,
+            draw_pool_size: 3,
+            cursor_world: glm::vec2(0.25, 0.5),
+            hovered: None,
+        };
+        assert_eq!(
+            debug_info.as_text(),
+            "[Debug]\ncamera: 1.00, -2.00\nzoom: 1.50\nelements: 3\ncursor: 0.25, 0.50\nhovered: -"
+        );
+
+        debug_info.hovered = Some(HoveredInstance {
+            id: DrawInstanceId(2),
+            color: glm::vec3(1.0, 0.5, 0.0),
+            position: glm::vec2(0.25, 0.5),
+            rotation: 45.0,
+            scale: glm::vec2(2.0, 1.0),
+            tag: None,
+        });
+        let text = debug_info.as_text();
+        assert!(text.ends_with(
+            "hovered: #2\ncolor: 1.00, 0.50, 0.00\nposition: 0.25, 0.50\nrotation: 45.0\nscale: 2.00, 1.00"
+        ));
+
+        // Tagged -> The tag of the application last
+        if let Some(hovered) = &mut debug_info.hovered {
+            hovered.tag = Some(7);
+        }
+        assert!(debug_info.as_text().ends_with("scale: 2.00, 1.00\ntag: 7"));
+    }
+
+    #[test]
+    fn test_frame_time_percentile() {
+        let mut render_stats = RenderStats::new(None);
+        assert_eq!(render_stats.frame_time_percentile(50.0), Duration::ZERO);
+
+        for millis in 1..=100 {
+            render_stats.push_frame_time(Duration::from_millis(millis));
+        }
+
+        assert_eq!(
+            render_stats.frame_time_percentile(50.0),
+            Duration::from_millis(50)
+        );
+        assert_eq!(
+            render_stats.frame_time_percentile(95.0),
+            Duration::from_millis(95)
+        );
+        assert_eq!(
+            render_stats.frame_time_percentile(99.0),
+            Duration::from_millis(99)
+        );
+
+        // Only the last frames are kept, a single stutter shows up as a spike
+        for _ in 0..RenderStats::FRAME_HISTORY {
+            render_stats.push_frame_time(Duration::from_millis(1));
+        }
+        render_stats.push_frame_time(Duration::from_millis(16));
+
+        assert_eq!(
+            render_stats.frame_time_history().count(),
+            RenderStats::FRAME_HISTORY
+        );
+        assert_eq!(render_stats.frame_time_sparkline(3), "▁▁█");
+    }
+
+    #[test]
+    fn test_sync_counts() {
+        // Fewer, equal and more swapchain images than frames in flight
+        for (max_frames_inflight, image_count) in [(2, 1), (2, 2), (2, 3), (1, 4)] {
+            let sync_counts = SyncCounts::new(max_frames_inflight, image_count);
+            assert_eq!(sync_counts.acquire, max_frames_inflight);
+            assert_eq!(sync_counts.release, image_count);
+
+            // Every image has its own release semaphore
+            let semaphores_release = (0..sync_counts.release).collect::<Vec<_>>();
+            for image_index in 0..image_count as u32 {
+                assert_eq!(
+                    release_semaphore(&semaphores_release, image_index).unwrap(),
+                    &(image_index as usize)
+                );
+            }
+            assert!(release_semaphore(&semaphores_release, image_count as u32).is_err());
+        }
+
+        // A recreated swapchain with more images only changes the release semaphores
+        assert_eq!(
+            SyncCounts::new(2, 5),
+            SyncCounts {
+                release: 5,
+                ..SyncCounts::new(2, 3)
+            }
+        );
+    }
+
+    /// Renders more swapchain images than frames in flight with synchronization validation,
+    /// needs a display and a Vulkan device with the validation layers
+    #[test]
+    #[ignore]
+    #[cfg(all(feature = "winit", feature = "render_dbg", feature = "sync_validation"))]
+    fn test_sync_validation() {
+        #[cfg(target_os = "windows")]
+        use winit::platform::windows::EventLoopBuilderExtWindows;
+        #[cfg(target_os = "linux")]
+        use winit::platform::x11::EventLoopBuilderExtX11;
+        use winit::{dpi::PhysicalSize, event_loop::EventLoopBuilder, window::WindowBuilder};
+
+        // Tests are not run on the main thread
+        let mut event_loop_builder = EventLoopBuilder::new();
+        #[cfg(any(target_os = "linux", target_os = "windows"))]
+        event_loop_builder.with_any_thread(true);
+        let event_loop = event_loop_builder.build();
+        let window = WindowBuilder::new()
+            .with_inner_size(PhysicalSize::new(320, 240))
+            .build(&event_loop)
+            .unwrap();
+
+        let mut renderer = Renderer::builder()
+            .max_frames_inflight(1)
+            .build(&window)
+            .unwrap();
+        assert!(renderer.swapchain_images.len() > renderer.max_frames_inflight);
+
+        let errors = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink_errors = errors.clone();
+        renderer
+            .set_debug_sink(DebugSeverity::Error, move |message| {
+                sink_errors.lock().unwrap().push(message.message.clone())
+            })
+            .unwrap();
+
+        // Every image is acquired and released again a few times
+        for frame in 0..renderer.swapchain_images.len() * 4 {
+            renderer
+                .circle(
+                    1.0,
+                    frame as f32 * 0.01,
+                    0.0,
+                    glm::vec3(1.0, 0.0, 0.0),
+                    AnchorType::Unlocked,
+                )
+                .unwrap();
+            renderer.draw_request().unwrap();
+        }
+        unsafe { renderer.device.device_wait_idle() }.unwrap();
+
+        assert_eq!(*errors.lock().unwrap(), Vec::<String>::new());
+    }
+}
@@ -0,0 +1,217 @@
+// std
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread::JoinHandle;
+
+// extern
+use anyhow::{anyhow, Result};
+
+// intern
+use crate::resources::{self, ImageData, ObjectPool};
+use crate::{ObjectHandle, RendererResult, TextureHandle};
+
+//==================================================
+//=== Resource Loader
+//==================================================
+
+/// Id of a background load, given back by the `load_*_async` functions of the
+/// [`crate::Renderer`] and found again in its [`LoadEvent`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LoadId(u64);
+
+/// Finished background load, given back by [`crate::Renderer::poll_loads`]
+#[derive(Debug)]
+pub struct LoadEvent {
+    pub id: LoadId,
+    pub result: RendererResult<LoadedResource>,
+}
+
+/// Resource of a finished background load, already uploaded and ready to draw
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoadedResource {
+    /// Objects of an .obj file in file order
+    Objects(Vec<ObjectHandle>),
+    /// Texture which replaced its placeholder, the handle is unchanged
+    Texture(TextureHandle),
+    /// Default objects of the [`crate::ResourceConfig`], which replaced the embedded ones
+    DefaultObjects,
+}
+
+/// File to parse on the loader thread
+#[derive(Debug, Clone)]
+pub(crate) enum LoadRequest {
+    Objects(PathBuf),
+    Texture(PathBuf),
+    DefaultObjects(PathBuf),
+}
+
+/// Parsed file, uploaded by the renderer
+pub(crate) enum Loaded {
+    Objects(ObjectPool),
+    Texture(ImageData),
+    DefaultObjects(ObjectPool),
+}
+
+impl LoadRequest {
+    fn path(&self) -> &Path {
+        match self {
+            LoadRequest::Objects(path)
+            | LoadRequest::Texture(path)
+            | LoadRequest::DefaultObjects(path) => path,
+        }
+    }
+
+    /// Reads and parses the file, this is the slow part done on the loader thread
+    fn load(&self) -> Result<Loaded> {
+        match self {
+            LoadRequest::Objects(path) => resources::load_obj_with_mtl(path).map(Loaded::Objects),
+            LoadRequest::Texture(path) => {
+                let path = path
+                    .to_str()
+                    .ok_or_else(|| anyhow!("Texture: Path {} is not UTF-8", path.display()))?;
+                resources::load_png(path).map(Loaded::Texture)
+            }
+            LoadRequest::DefaultObjects(obj_dir) => {
+                resources::preload(&resources::ResourceConfig {
+                    obj_dir: Some(obj_dir.clone()),
+                    ..Default::default()
+                })
+                .map(Loaded::DefaultObjects)
+            }
+        }
+    }
+}
+
+/// Reads and parses resource files on a background thread, the GPU upload is left to the
+/// renderer when it polls the results
+///
+/// The thread is started by the first request and stopped when the loader is dropped
+#[derive(Debug, Default)]
+pub(crate) struct ResourceLoader {
+    thread: Option<LoaderThread>,
+    next_id: u64,
+    pending: usize,
+}
+
+#[derive(Debug)]
+struct LoaderThread {
+    requests: mpsc::Sender<(LoadId, LoadRequest)>,
+    results: mpsc::Receiver<(LoadId, PathBuf, Result<Loaded>)>,
+    handle: JoinHandle<()>,
+}
+
+impl ResourceLoader {
+    /// Queues the `request` and gives back its id
+    pub fn request(&mut self, request: LoadRequest) -> LoadId {
+        let id = LoadId(self.next_id);
+        self.next_id += 1;
+        self.pending += 1;
+
+        let thread = self.thread.get_or_insert_with(LoaderThread::spawn);
+        // The thread only stops when the sender is dropped
+        let _ = thread.requests.send((id, request));
+
+        id
+    }
+
+    /// Number of requests not yet given back by [`ResourceLoader::poll`]
+    pub fn pending(&self) -> usize {
+        self.pending
+    }
+
+    /// Gives back the finished requests without waiting, with the path of their file
+    pub fn poll(&mut self) -> Vec<(LoadId, PathBuf, Result<Loaded>)> {
+        let Some(thread) = &self.thread else {
+            return Vec::new();
+        };
+
+        let finished: Vec<_> = thread.results.try_iter().collect();
+        self.pending -= finished.len();
+
+        finished
+    }
+
+    /// Waits for every pending request and gives them back
+    pub fn wait(&mut self) -> Vec<(LoadId, PathBuf, Result<Loaded>)> {
+        let Some(thread) = &self.thread else {
+            return Vec::new();
+        };
+
+        let finished: Vec<_> = thread.results.iter().take(self.pending).collect();
+        self.pending -= finished.len();
+
+        finished
+    }
+}
+
+impl LoaderThread {
+    fn spawn() -> Self {
+        let (requests, request_receiver) = mpsc::channel::<(LoadId, LoadRequest)>();
+        let (result_sender, results) = mpsc::channel();
+
+        let handle = std::thread::Builder::new()
+            .name("lavapond-loader".to_string())
+            .spawn(move || {
+                for (id, request) in request_receiver {
+                    let result = request.load();
+                    if result_sender
+                        .send((id, request.path().to_path_buf(), result))
+                        .is_err()
+                    {
+                        break;
+                    }
+                }
+            })
+            .expect("Loader: Can't spawn the loader thread");
+
+        Self {
+            requests,
+            results,
+            handle,
+        }
+    }
+}
+
+impl Drop for ResourceLoader {
+    fn drop(&mut self) {
+        if let Some(thread) = self.thread.take() {
+            // Closing the channel ends the thread after its current file
+            drop(thread.requests);
+            let _ = thread.handle.join();
+        }
+    }
+}
+
+//==================================================
+//=== Unit Testing
+//==================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resource_loader() {
+        let mut loader = ResourceLoader::default();
+        assert!(loader.poll().is_empty());
+
+        let rectangle = loader.request(LoadRequest::Objects("res/obj/rectangle.obj".into()));
+        let missing = loader.request(LoadRequest::Texture("res/img/missing.png".into()));
+        let defaults = loader.request(LoadRequest::DefaultObjects("res/obj".into()));
+        assert_eq!(loader.pending(), 3);
+
+        let finished = loader.wait();
+        assert_eq!(loader.pending(), 0);
+
+        // Requests are handled in order
+        let ids: Vec<LoadId> = finished.iter().map(|(id, _, _)| *id).collect();
+        assert_eq!(ids, [rectangle, missing, defaults]);
+
+        assert!(matches!(&finished[0].2, Ok(Loaded::Objects(pool)) if pool.pool.len() == 1));
+        assert!(finished[1].2.is_err());
+        assert_eq!(finished[1].1, Path::new("res/img/missing.png"));
+        assert!(
+            matches!(&finished[2].2, Ok(Loaded::DefaultObjects(pool)) if pool.pool.len() == 54)
+        );
+    }
+}
@@ -0,0 +1,194 @@
+// std
+#[cfg(feature = "winit")]
+use std::time::Instant;
+
+// extern
+#[cfg(feature = "winit")]
+use anyhow::Result;
+#[cfg(feature = "winit")]
+use winit::{
+    event::{Event, WindowEvent},
+    event_loop::EventLoop,
+    platform::run_return::EventLoopExtRunReturn,
+    window::Window,
+};
+
+// intern
+#[cfg(feature = "winit")]
+use crate::Renderer;
+
+//==================================================
+//=== Fixed Timestep
+//==================================================
+
+/// Accumulator splitting the elapsed time into updates of the same length
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FixedTimestep {
+    dt: f32,
+    accumulator: f32,
+    max_updates: usize,
+}
+
+impl FixedTimestep {
+    /// Creates a new [`FixedTimestep`] running `updates_per_sec` updates every second
+    pub fn new(updates_per_sec: f32) -> Self {
+        Self {
+            dt: 1.0 / updates_per_sec.max(f32::EPSILON),
+            accumulator: 0.0,
+            max_updates: 8,
+        }
+    }
+
+    /// Sets the most updates run for a single frame, the rest of a long frame is dropped
+    /// instead of falling further behind
+    pub fn with_max_updates(mut self, max_updates: usize) -> Self {
+        self.max_updates = max_updates.max(1);
+        self
+    }
+
+    /// Length of an update in seconds
+    pub fn dt(&self) -> f32 {
+        self.dt
+    }
+
+    /// Adds `elapsed` seconds and gives back the number of updates to run
+    pub fn advance(&mut self, elapsed: f32) -> usize {
+        self.accumulator += elapsed.max(0.0);
+
+        let updates = ((self.accumulator / self.dt) as usize).min(self.max_updates);
+        self.accumulator -= updates as f32 * self.dt;
+
+        if updates == self.max_updates {
+            self.accumulator = self.accumulator.min(self.dt);
+        }
+
+        updates
+    }
+
+    /// Fraction of an update (0.0 - 1.0) left in the accumulator, eg. to interpolate
+    /// between the last two states while rendering
+    pub fn alpha(&self) -> f32 {
+        (self.accumulator / self.dt).clamp(0.0, 1.0)
+    }
+}
+
+//==================================================
+//=== Loop Runner
+//==================================================
+
+/// Callbacks of an application driven by a [`LoopRunner`]
+#[cfg(feature = "winit")]
+pub trait LoopHandler {
+    /// Advances the state by the fixed `dt` seconds
+    fn update(&mut self, dt: f32) -> Result<()>;
+
+    /// Fills the draw pool of the `renderer`, the draw request is submitted by the runner
+    ///
+    /// `alpha` is the fraction of an update passed since the last one
+    fn render(&mut self, renderer: &mut Renderer, alpha: f32) -> Result<()>;
+
+    /// Window events besides closing and resizing, which are handled by the runner
+    fn event(
+        &mut self,
+        _event: &WindowEvent,
+        _window: &Window,
+        _renderer: &mut Renderer,
+    ) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Fixed update, variable render loop on top of the winit event loop
+#[cfg(feature = "winit")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LoopRunner {
+    timestep: FixedTimestep,
+}
+
+#[cfg(feature = "winit")]
+impl LoopRunner {
+    /// Creates a new [`LoopRunner`] updating with `timestep`
+    pub fn new(timestep: FixedTimestep) -> Self {
+        Self { timestep }
+    }
+
+    /// Runs the event loop until the window is closed or a callback fails
+    ///
+    /// Every frame the pending updates are run, followed by a single render and draw request.
+    /// Resize events are handled by the renderer and redrawn right away
+    pub fn run(
+        mut self,
+        event_loop: &mut EventLoop<()>,
+        window: &Window,
+        renderer: &mut Renderer,
+        handler: &mut impl LoopHandler,
+    ) -> Result<()> {
+        let mut result = Ok(());
+        let mut last_frame = Instant::now();
+
+        event_loop.run_return(|event, _, control_flow| {
+            control_flow.set_poll();
+
+            let frame_result = match event {
+                Event::MainEventsCleared => {
+                    let now = Instant::now();
+                    let updates = self.timestep.advance((now - last_frame).as_secs_f32());
+                    last_frame = now;
+
+                    window.request_redraw();
+                    (0..updates).try_for_each(|_| handler.update(self.timestep.dt()))
+                }
+                Event::RedrawRequested(window_id) if window_id == window.id() => handler
+                    .render(renderer, self.timestep.alpha())
+                    .and_then(|_| renderer.draw_request().map_err(anyhow::Error::from)),
+                Event::WindowEvent { event, window_id } if window_id == window.id() => {
+                    match renderer.handle_window_event(&event) {
+                        // Redraw every intermediate size of a live resize
+                        Ok(true) => {
+                            window.request_redraw();
+                            Ok(())
+                        }
+                        Ok(false) if event == WindowEvent::CloseRequested => {
+                            control_flow.set_exit();
+                            Ok(())
+                        }
+                        Ok(false) => handler.event(&event, window, renderer),
+                        Err(err) => Err(err.into()),
+                    }
+                }
+                _ => Ok(()),
+            };
+
+            if let Err(err) = frame_result {
+                result = Err(err);
+                control_flow.set_exit();
+            }
+        });
+
+        result
+    }
+}
+
+//==================================================
+//=== Unit Testing
+//==================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixed_timestep() {
+        let mut timestep = FixedTimestep::new(100.0).with_max_updates(4);
+
+        // Remainder is carried over to the next frame
+        assert_eq!(timestep.advance(0.025), 2);
+        assert!((timestep.alpha() - 0.5).abs() < 1e-4);
+        assert_eq!(timestep.advance(0.007), 1);
+        assert!((timestep.alpha() - 0.2).abs() < 1e-4);
+
+        // Long frames are capped and their rest is dropped
+        assert_eq!(timestep.advance(1.0), 4);
+        assert!(timestep.alpha() <= 1.0);
+    }
+}
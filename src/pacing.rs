@@ -0,0 +1,126 @@
+// std
+use std::time::{Duration, Instant};
+
+//==================================================
+//=== Frame Limit
+//==================================================
+
+/// Target of the frame limiter, see `Renderer::set_frame_limit`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FrameLimit {
+    /// Frames per second
+    Fps(f32),
+    /// Time between the starts of two frames
+    FrameTime(Duration),
+}
+
+impl FrameLimit {
+    /// Time between the starts of two frames
+    pub fn frame_time(&self) -> Duration {
+        match self {
+            FrameLimit::Fps(fps) => Duration::from_secs_f32(1.0 / fps.max(f32::EPSILON)),
+            FrameLimit::FrameTime(frame_time) => *frame_time,
+        }
+    }
+}
+
+//==================================================
+//=== Frame Pacer
+//==================================================
+
+/// Waits at the start of every draw request until the next frame is due
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct FramePacer {
+    target: Option<Duration>,
+    /// Start of the next frame, `None` before the first frame of the target
+    deadline: Option<Instant>,
+}
+
+impl FramePacer {
+    /// Rest of the wait spent spinning, sleeps of the OS overshoot by up to about a millisecond
+    const SPIN_MARGIN: Duration = Duration::from_micros(1500);
+
+    pub fn new(limit: Option<FrameLimit>) -> Self {
+        Self {
+            target: limit.map(|limit| limit.frame_time()),
+            deadline: None,
+        }
+    }
+
+    pub fn target(&self) -> Option<Duration> {
+        self.target
+    }
+
+    pub fn set_limit(&mut self, limit: Option<FrameLimit>) {
+        *self = Self::new(limit);
+    }
+
+    /// Waits until the next frame is due and gives back the time spent waiting
+    pub fn wait(&mut self) -> Duration {
+        let Some(target) = self.target else {
+            return Duration::ZERO;
+        };
+
+        let start = Instant::now();
+        if let Some(deadline) = self.deadline {
+            sleep_until(deadline);
+        }
+
+        let now = Instant::now();
+        self.deadline = Some(next_deadline(self.deadline.unwrap_or(now), now, target));
+
+        now - start
+    }
+}
+
+/// Gives back the start of the frame after the one due at `deadline`
+///
+/// Frames late by less than the `target` keep the schedule, so the average frame time stays on
+/// target. Later ones restart it from `now` instead of rushing through the missed frames
+fn next_deadline(deadline: Instant, now: Instant, target: Duration) -> Instant {
+    if now >= deadline + target {
+        now + target
+    } else {
+        deadline + target
+    }
+}
+
+/// Sleeps most of the time until `deadline` and spins the rest for precision
+fn sleep_until(deadline: Instant) {
+    let remaining = deadline.saturating_duration_since(Instant::now());
+    if remaining > FramePacer::SPIN_MARGIN {
+        std::thread::sleep(remaining - FramePacer::SPIN_MARGIN);
+    }
+
+    while Instant::now() < deadline {
+        std::hint::spin_loop();
+    }
+}
+
+//==================================================
+//=== Unit Testing
+//==================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_deadline() {
+        let target = FrameLimit::Fps(100.0).frame_time();
+        assert_eq!(target, Duration::from_millis(10));
+
+        let start = Instant::now();
+
+        // Slightly late -> Same schedule
+        let deadline = next_deadline(start, start + Duration::from_millis(2), target);
+        assert_eq!(deadline, start + target);
+
+        // Missed a whole frame -> Restarts from now
+        let now = start + Duration::from_millis(25);
+        assert_eq!(next_deadline(start, now, target), now + target);
+
+        // No limit -> No wait
+        assert_eq!(FramePacer::new(None).wait(), Duration::ZERO);
+    }
+}
@@ -0,0 +1,584 @@
+// std
+#[cfg(feature = "serde")]
+use std::path::Path;
+
+// extern
+extern crate nalgebra_glm as glm;
+#[cfg(feature = "serde")]
+use anyhow::{Context, Result};
+
+// intern
+use crate::spatial::SpatialGrid;
+use crate::WorldRect;
+
+//==================================================
+//=== Physics System
+//==================================================
+
+/// Handle of a [`Body`] inside a [`PhysicsSystem`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BodyId(pub(crate) usize);
+
+/// Shape of a [`Body`] used for the collision detection, centered on the body position
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Collider {
+    Circle {
+        radius: f32,
+    },
+    /// Axis aligned box, boxes only collide with circles
+    Aabb {
+        half_size: glm::Vec2,
+    },
+}
+
+/// Simulated body of a [`PhysicsSystem`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Body {
+    pub position: glm::Vec2,
+    pub velocity: glm::Vec2,
+    pub collider: Collider,
+    /// Static bodies with zero inverse mass are neither moved by gravity nor by collisions
+    pub inverse_mass: f32,
+    /// Bounciness, 0.0 stops along the contact normal, 1.0 keeps the whole speed
+    pub restitution: f32,
+    /// Sum of the forces applied during the next `step`, see [`PhysicsSystem::apply_force`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub force: glm::Vec2,
+}
+
+impl Body {
+    /// Creates a new moving circle with a mass proportional to its area
+    pub fn circle(radius: f32, position: glm::Vec2, velocity: glm::Vec2) -> Self {
+        Self {
+            position,
+            velocity,
+            collider: Collider::Circle { radius },
+            inverse_mass: 1.0 / (radius * radius).max(f32::EPSILON),
+            restitution: 1.0,
+            force: glm::vec2(0.0, 0.0),
+        }
+    }
+
+    /// Creates a new static box, eg. a wall or an obstacle
+    pub fn aabb(half_size: glm::Vec2, position: glm::Vec2) -> Self {
+        Self {
+            position,
+            velocity: glm::vec2(0.0, 0.0),
+            collider: Collider::Aabb { half_size },
+            inverse_mass: 0.0,
+            restitution: 1.0,
+            force: glm::vec2(0.0, 0.0),
+        }
+    }
+
+    /// Sets the bounciness of the body
+    pub fn with_restitution(mut self, restitution: f32) -> Self {
+        self.restitution = restitution;
+        self
+    }
+
+    /// Sets the mass of the body, an infinite or non positive `mass` makes it static
+    pub fn with_mass(mut self, mass: f32) -> Self {
+        self.inverse_mass = match mass > 0.0 {
+            true => 1.0 / mass,
+            false => 0.0,
+        };
+        self
+    }
+
+    /// Mass of the body, infinite for static bodies
+    pub fn mass(&self) -> f32 {
+        match self.inverse_mass > 0.0 {
+            true => 1.0 / self.inverse_mass,
+            false => f32::INFINITY,
+        }
+    }
+
+    /// Half of the width and height of the collider
+    pub fn half_extents(&self) -> glm::Vec2 {
+        match self.collider {
+            Collider::Circle { radius } => glm::vec2(radius, radius),
+            Collider::Aabb { half_size } => half_size,
+        }
+    }
+
+    /// Bounding box of the collider in world units
+    pub fn bounds(&self) -> WorldRect {
+        let half_extents = self.half_extents();
+        WorldRect::new(self.position - half_extents, self.position + half_extents)
+    }
+}
+
+/// Contact of two colliders, `normal` points from the first one towards the second one
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Contact {
+    normal: glm::Vec2,
+    depth: f32,
+}
+
+/// Fixed timestep simulation of circles and boxes with gravity, collisions and restitution
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PhysicsSystem {
+    bodies: Vec<Body>,
+    /// Acceleration of the moving bodies
+    pub gravity: glm::Vec2,
+    /// Bodies are kept inside the `(min, max)` corners when set
+    pub bounds: Option<(glm::Vec2, glm::Vec2)>,
+    timestep: f32,
+    accumulator: f32,
+    paused: bool,
+}
+
+impl Default for PhysicsSystem {
+    fn default() -> Self {
+        Self {
+            bodies: Vec::new(),
+            gravity: glm::vec2(0.0, 0.0),
+            bounds: None,
+            timestep: Self::DEFAULT_TIMESTEP,
+            accumulator: 0.0,
+            paused: false,
+        }
+    }
+}
+
+impl PhysicsSystem {
+    /// Simulation step of 120 Hz in seconds
+    pub const DEFAULT_TIMESTEP: f32 = 1.0 / 120.0;
+
+    /// Most steps simulated by a single `step`, the rest of a long frame is dropped
+    /// instead of falling further behind
+    const MAX_STEPS: usize = 8;
+
+    /// Creates a new empty running [`PhysicsSystem`] without gravity
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the duration of a simulation step in seconds
+    pub fn with_timestep(mut self, timestep: f32) -> Self {
+        self.timestep = timestep.max(f32::EPSILON);
+        self
+    }
+
+    pub fn add(&mut self, body: Body) -> BodyId {
+        self.bodies.push(body);
+        BodyId(self.bodies.len() - 1)
+    }
+
+    pub fn body(&self, id: BodyId) -> Option<&Body> {
+        self.bodies.get(id.0)
+    }
+
+    pub fn body_mut(&mut self, id: BodyId) -> Option<&mut Body> {
+        self.bodies.get_mut(id.0)
+    }
+
+    pub fn bodies(&self) -> &[Body] {
+        &self.bodies
+    }
+
+    /// Adds `force` to the body of `id` for every simulation step of the next `step` that
+    /// simulates any, unknown ids and static bodies are ignored
+    pub fn apply_force(&mut self, id: BodyId, force: glm::Vec2) {
+        if let Some(body) = self.bodies.get_mut(id.0) {
+            body.force += force;
+        }
+    }
+
+    /// Changes the velocity of the body of `id` by `impulse` at once, scaled by its inverse
+    /// mass, unknown ids and static bodies are ignored
+    pub fn apply_impulse(&mut self, id: BodyId, impulse: glm::Vec2) {
+        if let Some(body) = self.bodies.get_mut(id.0) {
+            body.velocity += impulse * body.inverse_mass;
+        }
+    }
+
+    /// Removes every body
+    pub fn clear(&mut self) {
+        self.bodies.clear();
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Paused systems ignore the elapsed time
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+        self.accumulator = 0.0;
+    }
+
+    /// Switches between the paused and running state
+    pub fn switch_state(&mut self) {
+        self.set_paused(!self.paused);
+    }
+
+    /// Advances the simulation by `dt` seconds in fixed steps, the remainder is carried
+    /// over to the next call
+    ///
+    /// Gives back the number of simulated steps
+    pub fn step(&mut self, dt: f32) -> usize {
+        if self.paused {
+            return 0;
+        }
+
+        self.accumulator += dt.max(0.0);
+
+        let mut steps = 0;
+        while self.accumulator >= self.timestep && steps < Self::MAX_STEPS {
+            self.fixed_step();
+            self.accumulator -= self.timestep;
+            steps += 1;
+        }
+
+        if steps == Self::MAX_STEPS {
+            self.accumulator = self.accumulator.min(self.timestep);
+        }
+
+        // Applied Forces -> Kept until a step is simulated
+        if steps > 0 {
+            self.bodies
+                .iter_mut()
+                .for_each(|body| body.force = glm::vec2(0.0, 0.0));
+        }
+
+        steps
+    }
+
+    /// Writes the bodies and settings into the JSON file at `path`, eg. to author a level
+    #[cfg(feature = "serde")]
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let file = std::fs::File::create(path)
+            .with_context(|| format!("Physics: Can't write {}", path.display()))?;
+
+        serde_json::to_writer_pretty(std::io::BufWriter::new(file), self)
+            .with_context(|| format!("Physics: Can't write {}", path.display()))
+    }
+
+    /// Reads a [`PhysicsSystem`] written by [`PhysicsSystem::save`] from the JSON file at `path`
+    #[cfg(feature = "serde")]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = std::fs::File::open(path)
+            .with_context(|| format!("Physics: Can't read {}", path.display()))?;
+
+        serde_json::from_reader(std::io::BufReader::new(file))
+            .with_context(|| format!("Physics: Can't parse {}", path.display()))
+    }
+
+    /// Simulates a single step of `timestep` seconds
+    fn fixed_step(&mut self) {
+        let dt = self.timestep;
+
+        /* Integration */
+
+        // Semi-implicit Euler: The position moves with the new velocity
+        for body in self
+            .bodies
+            .iter_mut()
+            .filter(|body| body.inverse_mass > 0.0)
+        {
+            body.velocity += (self.gravity + body.force * body.inverse_mass) * dt;
+            body.position += body.velocity * dt;
+        }
+
+        /* Collisions */
+
+        // Broadphase: Only bodies sharing a cell of the grid are tested, in index order
+        let broadphase = self.broadphase();
+        for a in 0..self.bodies.len() {
+            let mut candidates = broadphase.query(&self.bodies[a].bounds());
+            candidates.retain(|&b| b > a);
+            candidates.sort_unstable();
+
+            for b in candidates {
+                let (first, second) = self.bodies.split_at_mut(b);
+                let (body_a, body_b) = (&mut first[a], &mut second[0]);
+
+                if let Some(contact) = collide(body_a, body_b) {
+                    resolve(body_a, body_b, contact);
+                }
+            }
+        }
+
+        /* Bounds */
+
+        if let Some((min, max)) = self.bounds {
+            for body in self
+                .bodies
+                .iter_mut()
+                .filter(|body| body.inverse_mass > 0.0)
+            {
+                let half_extents = body.half_extents();
+
+                for axis in 0..2 {
+                    let low = min[axis] + half_extents[axis];
+                    let high = max[axis] - half_extents[axis];
+
+                    if body.position[axis] < low {
+                        body.position[axis] = low;
+                        body.velocity[axis] = body.velocity[axis].abs() * body.restitution;
+                    } else if body.position[axis] > high {
+                        body.position[axis] = high;
+                        body.velocity[axis] = -body.velocity[axis].abs() * body.restitution;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Gives back a grid of the bodies with cells of the largest circle, walls and other large
+    /// boxes span many cells
+    fn broadphase(&self) -> SpatialGrid<usize> {
+        let cell_size = self
+            .bodies
+            .iter()
+            .filter_map(|body| match body.collider {
+                Collider::Circle { radius } => Some(radius * 2.0),
+                Collider::Aabb { .. } => None,
+            })
+            .fold(0.0, f32::max);
+
+        let mut grid = SpatialGrid::new(cell_size);
+        for (index, body) in self.bodies.iter().enumerate() {
+            grid.insert(index, body.bounds());
+        }
+
+        grid
+    }
+}
+
+/// Gives back the contact of the two bodies, boxes do not collide with each other
+fn collide(a: &Body, b: &Body) -> Option<Contact> {
+    match (a.collider, b.collider) {
+        (Collider::Circle { radius: radius_a }, Collider::Circle { radius: radius_b }) => {
+            circle_circle(a.position, radius_a, b.position, radius_b)
+        }
+        (Collider::Circle { radius }, Collider::Aabb { half_size }) => {
+            circle_aabb(a.position, radius, b.position, half_size)
+        }
+        (Collider::Aabb { half_size }, Collider::Circle { radius }) => {
+            circle_aabb(b.position, radius, a.position, half_size).map(|contact| Contact {
+                normal: -contact.normal,
+                ..contact
+            })
+        }
+        (Collider::Aabb { .. }, Collider::Aabb { .. }) => None,
+    }
+}
+
+fn circle_circle(
+    center_a: glm::Vec2,
+    radius_a: f32,
+    center_b: glm::Vec2,
+    radius_b: f32,
+) -> Option<Contact> {
+    let delta = center_b - center_a;
+    let distance = delta.norm();
+    let depth = radius_a + radius_b - distance;
+
+    if depth <= 0.0 {
+        return None;
+    }
+
+    // Same Center -> Any direction separates them
+    let normal = if distance > f32::EPSILON {
+        delta / distance
+    } else {
+        glm::vec2(1.0, 0.0)
+    };
+
+    Some(Contact { normal, depth })
+}
+
+fn circle_aabb(
+    center: glm::Vec2,
+    radius: f32,
+    box_center: glm::Vec2,
+    half_size: glm::Vec2,
+) -> Option<Contact> {
+    let offset = center - box_center;
+    let closest = glm::clamp_vec(&offset, &-half_size, &half_size);
+    let inside = closest == offset;
+
+    // Center Inside -> Pushed out through the closest side
+    if inside {
+        let overlap = half_size - offset.abs();
+        let normal = if overlap.x < overlap.y {
+            glm::vec2(-offset.x.signum(), 0.0)
+        } else {
+            glm::vec2(0.0, -offset.y.signum())
+        };
+
+        return Some(Contact {
+            normal,
+            depth: overlap.x.min(overlap.y) + radius,
+        });
+    }
+
+    let delta = closest - offset;
+    let distance = delta.norm();
+
+    if distance >= radius {
+        return None;
+    }
+
+    Some(Contact {
+        normal: delta / distance,
+        depth: radius - distance,
+    })
+}
+
+/// Separates the bodies and applies the impulse of the collision
+fn resolve(a: &mut Body, b: &mut Body, contact: Contact) {
+    let inverse_mass_sum = a.inverse_mass + b.inverse_mass;
+    if inverse_mass_sum <= 0.0 {
+        return;
+    }
+
+    // Positional Correction
+    let correction = contact.normal * (contact.depth / inverse_mass_sum);
+    a.position -= correction * a.inverse_mass;
+    b.position += correction * b.inverse_mass;
+
+    // Separating -> No impulse
+    let normal_velocity = (b.velocity - a.velocity).dot(&contact.normal);
+    if normal_velocity >= 0.0 {
+        return;
+    }
+
+    let restitution = a.restitution.min(b.restitution);
+    let impulse = contact.normal * (-(1.0 + restitution) * normal_velocity / inverse_mass_sum);
+
+    a.velocity -= impulse * a.inverse_mass;
+    b.velocity += impulse * b.inverse_mass;
+}
+
+//==================================================
+//=== Unit Testing
+//==================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_physics_collisions() {
+        // Equal circles swap their velocities in an elastic head-on collision
+        let mut physics_system = PhysicsSystem::new();
+        let a = physics_system.add(Body::circle(
+            0.1,
+            glm::vec2(-0.15, 0.0),
+            glm::vec2(1.0, 0.0),
+        ));
+        let b = physics_system.add(Body::circle(
+            0.1,
+            glm::vec2(0.15, 0.0),
+            glm::vec2(-1.0, 0.0),
+        ));
+
+        assert_eq!(
+            physics_system.step(PhysicsSystem::DEFAULT_TIMESTEP * 7.5),
+            7
+        );
+        assert!((physics_system.body(a).unwrap().velocity.x + 1.0).abs() < 1e-5);
+        assert!((physics_system.body(b).unwrap().velocity.x - 1.0).abs() < 1e-5);
+
+        // Circle falling on a static box stops with zero restitution
+        let mut physics_system = PhysicsSystem::new();
+        physics_system.gravity = glm::vec2(0.0, -10.0);
+        physics_system.add(Body::aabb(glm::vec2(1.0, 0.1), glm::vec2(0.0, -0.1)));
+        let ball = physics_system
+            .add(Body::circle(0.1, glm::vec2(0.0, 0.5), glm::vec2(0.0, 0.0)).with_restitution(0.0));
+
+        for _ in 0..240 {
+            physics_system.step(PhysicsSystem::DEFAULT_TIMESTEP);
+        }
+
+        let ball = physics_system.body(ball).unwrap();
+        assert!((ball.position.y - 0.1).abs() < 0.01);
+        assert!(ball.velocity.y.abs() < 0.1);
+    }
+
+    #[test]
+    fn test_physics_forces() {
+        let mut physics_system = PhysicsSystem::new().with_timestep(0.5);
+        let ball = physics_system
+            .add(Body::circle(0.1, glm::vec2(0.0, 0.0), glm::vec2(0.0, 0.0)).with_mass(2.0));
+        let wall = physics_system.add(Body::aabb(glm::vec2(0.1, 0.1), glm::vec2(5.0, 0.0)));
+        assert_eq!(physics_system.body(wall).unwrap().mass(), f32::INFINITY);
+
+        // Impulse -> Velocity changes at once by impulse / mass, static bodies stay
+        physics_system.apply_impulse(ball, glm::vec2(1.0, 0.0));
+        physics_system.apply_impulse(wall, glm::vec2(1.0, 0.0));
+        assert_eq!(
+            physics_system.body(ball).unwrap().velocity,
+            glm::vec2(0.5, 0.0)
+        );
+        assert_eq!(
+            physics_system.body(wall).unwrap().velocity,
+            glm::vec2(0.0, 0.0)
+        );
+
+        // Force -> Kept while no step is simulated, then accelerates every step of the call
+        physics_system.apply_force(ball, glm::vec2(0.0, 4.0));
+        assert_eq!(physics_system.step(0.25), 0);
+        assert_eq!(physics_system.step(0.75), 2);
+
+        let body = physics_system.body(ball).unwrap();
+        assert_eq!(body.velocity, glm::vec2(0.5, 2.0));
+        assert_eq!(body.position, glm::vec2(0.5, 1.5));
+        assert_eq!(body.force, glm::vec2(0.0, 0.0));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_physics_save_load() {
+        let dir = std::env::temp_dir().join(format!("lavapond_physics_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("level.json");
+
+        let mut physics_system = PhysicsSystem::new().with_timestep(0.25);
+        physics_system.gravity = glm::vec2(0.0, -9.81);
+        physics_system.bounds = Some((glm::vec2(-1.0, -1.0), glm::vec2(1.0, 1.0)));
+        let ball = physics_system
+            .add(Body::circle(0.1, glm::vec2(0.0, 0.5), glm::vec2(1.0, 0.0)).with_restitution(0.5));
+        physics_system.add(Body::aabb(glm::vec2(1.0, 0.1), glm::vec2(0.0, -0.1)));
+        physics_system.apply_force(ball, glm::vec2(2.0, 0.0));
+        physics_system.set_paused(true);
+
+        // JSON round trip -> Same bodies and settings
+        physics_system.save(&path).unwrap();
+        let loaded = PhysicsSystem::load(&path).unwrap();
+        assert_eq!(loaded.bodies(), physics_system.bodies());
+        assert_eq!(format!("{:?}", loaded), format!("{:?}", physics_system));
+
+        // Bodies saved before the applied forces -> No force
+        let mut json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        json["bodies"][0].as_object_mut().unwrap().remove("force");
+        std::fs::write(&path, json.to_string()).unwrap();
+        let loaded = PhysicsSystem::load(&path).unwrap();
+        assert_eq!(loaded.body(ball).unwrap().force, glm::vec2(0.0, 0.0));
+
+        // Missing or malformed files -> Error instead of a panic
+        assert!(PhysicsSystem::load(dir.join("missing.json")).is_err());
+        for json in [
+            "",
+            "{",
+            r#"{"bodies": "ball"}"#,
+            r#"{"bodies": [{"position": [0.0]}]}"#,
+        ] {
+            std::fs::write(&path, json).unwrap();
+            assert!(PhysicsSystem::load(&path).is_err(), "{}", json);
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
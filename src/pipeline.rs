@@ -1,37 +1,441 @@
 // std
-use std::ffi::CStr;
+use std::{
+    ffi::CStr,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
 
 // extern
 use anyhow::{Context, Ok, Result};
-use ash::vk;
+use ash::{util, vk};
+
+//==================================================
+//=== Shaders
+//==================================================
+
+/// SPIR-V shaders compiled by the build script, embedded by name
+const EMBEDDED_SHADERS: [(&str, &[u8], &[u8]); 3] = [
+    (
+        "shader",
+        include_bytes!(concat!(env!("OUT_DIR"), "/shader.vert.spv")),
+        include_bytes!(concat!(env!("OUT_DIR"), "/shader.frag.spv")),
+    ),
+    (
+        "sprite",
+        include_bytes!(concat!(env!("OUT_DIR"), "/sprite.vert.spv")),
+        include_bytes!(concat!(env!("OUT_DIR"), "/sprite.frag.spv")),
+    ),
+    (
+        "sdf",
+        include_bytes!(concat!(env!("OUT_DIR"), "/sdf.vert.spv")),
+        include_bytes!(concat!(env!("OUT_DIR"), "/sdf.frag.spv")),
+    ),
+];
+
+/// Vertex shader of the post-processing passes, drawing a full-screen triangle
+const EMBEDDED_POST_VERT: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/post.vert.spv"));
+
+/// Fragment shaders of the built-in post-processing effects, embedded by name
+const EMBEDDED_POST_EFFECTS: [(&str, &[u8]); 3] = [
+    (
+        "vignette",
+        include_bytes!(concat!(env!("OUT_DIR"), "/vignette.frag.spv")),
+    ),
+    (
+        "bloom",
+        include_bytes!(concat!(env!("OUT_DIR"), "/bloom.frag.spv")),
+    ),
+    (
+        "gamma",
+        include_bytes!(concat!(env!("OUT_DIR"), "/gamma.frag.spv")),
+    ),
+];
+
+/// SPIR-V code of a vertex and a fragment shader
+pub struct ShaderCode {
+    pub vert: Vec<u32>,
+    pub frag: Vec<u32>,
+}
+
+impl ShaderCode {
+    /// Loads the shaders called `shader_name`
+    ///
+    /// Without `shader_dir` the embedded shaders are used, otherwise they are read from
+    /// `{shader_dir}/{shader_name}.vert.spv` and `{shader_dir}/{shader_name}.frag.spv`
+    pub fn load(shader_dir: Option<&Path>, shader_name: &str) -> Result<Self> {
+        match shader_dir {
+            Some(shader_dir) => {
+                let read = |stage: &str| -> Result<Vec<u32>> {
+                    let path = shader_dir.join(format!("{}.{}.spv", shader_name, stage));
+                    let code = std::fs::read(&path)
+                        .with_context(|| format!("Shader: Can't read {}", path.display()))?;
+
+                    Self::from_bytes(&code)
+                };
+
+                Ok(Self {
+                    vert: read("vert")?,
+                    frag: read("frag")?,
+                })
+            }
+            None => {
+                let (_, vert, frag) = EMBEDDED_SHADERS
+                    .iter()
+                    .find(|(name, _, _)| *name == shader_name)
+                    .with_context(|| {
+                        format!("Shader: No embedded shader called {}", shader_name)
+                    })?;
+
+                Ok(Self {
+                    vert: Self::from_bytes(vert)?,
+                    frag: Self::from_bytes(frag)?,
+                })
+            }
+        }
+    }
+
+    /// Loads the embedded post-processing effect called `effect_name`
+    pub fn post_effect(effect_name: &str) -> Result<Self> {
+        let (_, frag) = EMBEDDED_POST_EFFECTS
+            .iter()
+            .find(|(name, _)| *name == effect_name)
+            .with_context(|| format!("Shader: No embedded post effect called {}", effect_name))?;
+
+        Self::post_fragment(frag)
+    }
+
+    /// Pairs the SPIR-V `frag` shader with the full-screen vertex shader of the post-processing
+    pub fn post_fragment(frag: &[u8]) -> Result<Self> {
+        Ok(Self {
+            vert: Self::from_bytes(EMBEDDED_POST_VERT)?,
+            frag: Self::from_bytes(frag)?,
+        })
+    }
+
+    /// Converts SPIR-V bytes into words, the bytes do not have to be aligned
+    pub fn from_bytes(code: &[u8]) -> Result<Vec<u32>> {
+        Ok(util::read_spv(&mut std::io::Cursor::new(code)).context("Shader: Invalid SPIR-V")?)
+    }
+}
+
+//==================================================
+//=== Shader Hot Reload
+//==================================================
+
+/// Names of the shaders used by the built-in pipelines
+pub const BUILTIN_SHADERS: [&str; 2] = ["shader", "sprite"];
+
+impl ShaderCode {
+    /// Loads the shaders called `shader_name` from the files of a development `shader_dir`
+    ///
+    /// A stage is read from `{shader_name}.{stage}.spv` if it exists, otherwise the GLSL source
+    /// `{shader_name}.{stage}` is compiled with `glslc`, which has to be on the `PATH`
+    pub fn load_source(shader_dir: &Path, shader_name: &str) -> Result<Self> {
+        let load = |stage: &str| -> Result<Vec<u32>> {
+            let spirv_path = shader_dir.join(format!("{}.{}.spv", shader_name, stage));
+            if spirv_path.is_file() {
+                let code = std::fs::read(&spirv_path)
+                    .with_context(|| format!("Shader: Can't read {}", spirv_path.display()))?;
+
+                return Self::from_bytes(&code);
+            }
+
+            let glsl_path = shader_dir.join(format!("{}.{}", shader_name, stage));
+            let output = std::process::Command::new("glslc")
+                .arg(&glsl_path)
+                .args(["-o", "-"])
+                .output()
+                .context("Shader: Can't run glslc")?;
+
+            if !output.status.success() {
+                return Err(anyhow::anyhow!(
+                    "Shader: Can't compile {}\n{}",
+                    glsl_path.display(),
+                    String::from_utf8_lossy(&output.stderr)
+                ));
+            }
+
+            Self::from_bytes(&output.stdout)
+        };
+
+        Ok(Self {
+            vert: load("vert")?,
+            frag: load("frag")?,
+        })
+    }
+}
+
+/// Polls the files of the built-in shaders in a development directory for changes
+pub struct ShaderWatcher {
+    pub shader_dir: PathBuf,
+    modified: Vec<Option<SystemTime>>,
+    last_poll: Instant,
+}
+
+impl ShaderWatcher {
+    /// Minimum time between two checks of the files
+    const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+    /// Creates a new [`ShaderWatcher`], the current state of the files counts as unchanged
+    pub fn new(shader_dir: PathBuf) -> Self {
+        let mut watcher = Self {
+            shader_dir,
+            modified: Vec::new(),
+            last_poll: Instant::now(),
+        };
+        watcher.modified = watcher.modified_times();
+
+        watcher
+    }
+
+    /// Gives back true when any of the watched files changed since the last change
+    pub fn poll(&mut self) -> bool {
+        if self.last_poll.elapsed() < Self::POLL_INTERVAL {
+            return false;
+        }
+        self.last_poll = Instant::now();
+
+        let modified = self.modified_times();
+        if modified == self.modified {
+            return false;
+        }
+
+        self.modified = modified;
+        true
+    }
+
+    /// Modification time of every GLSL and SPIR-V file of the built-in shaders
+    fn modified_times(&self) -> Vec<Option<SystemTime>> {
+        BUILTIN_SHADERS
+            .iter()
+            .flat_map(|name| {
+                ["vert", "frag"].into_iter().flat_map(move |stage| {
+                    [
+                        format!("{}.{}", name, stage),
+                        format!("{}.{}.spv", name, stage),
+                    ]
+                })
+            })
+            .map(|file_name| {
+                std::fs::metadata(self.shader_dir.join(file_name))
+                    .and_then(|metadata| metadata.modified())
+                    .ok()
+            })
+            .collect()
+    }
+}
 
 //==================================================
 //=== Graphics Pipeline
 //==================================================
 
+/// Index of a custom pipeline created by `Renderer::create_pipeline`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PipelineHandle(pub(crate) usize);
+
+/// How the indices of an object are assembled into primitives
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Topology {
+    TriangleList,
+    TriangleStrip,
+    LineList,
+    LineStrip,
+    PointList,
+}
+
+impl Topology {
+    pub(crate) fn as_vk(&self) -> vk::PrimitiveTopology {
+        match self {
+            Topology::TriangleList => vk::PrimitiveTopology::TRIANGLE_LIST,
+            Topology::TriangleStrip => vk::PrimitiveTopology::TRIANGLE_STRIP,
+            Topology::LineList => vk::PrimitiveTopology::LINE_LIST,
+            Topology::LineStrip => vk::PrimitiveTopology::LINE_STRIP,
+            Topology::PointList => vk::PrimitiveTopology::POINT_LIST,
+        }
+    }
+}
+
+/// Description of a custom pipeline with user supplied SPIR-V shaders
+///
+/// The shaders get the same vertex input, uniform buffer (set 0), instance storage buffer
+/// (set 0, binding 1, indexed by `gl_InstanceIndex`) and texture (set 1) as the built-in sprite shaders
+#[derive(Debug, Clone, Copy)]
+pub struct PipelineDesc<'a> {
+    /// SPIR-V code of the vertex shader
+    pub vert: &'a [u8],
+    /// SPIR-V code of the fragment shader
+    pub frag: &'a [u8],
+    /// Enables alpha blending
+    pub blend: bool,
+    pub topology: Topology,
+}
+
 pub struct GraphicsPipeline {
     pub layout: vk::PipelineLayout,
-    pub render_pass: vk::RenderPass,
     pub pipeline: vk::Pipeline,
 }
 
+/// Attachments a [`GraphicsPipeline`] draws into
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderTarget {
+    /// First subpass of a render pass
+    RenderPass(vk::RenderPass),
+    /// First subpass of a render pass with the stencil attachment of the masks
+    StencilRenderPass(vk::RenderPass),
+    /// Color and stencil attachment of the formats, drawn with dynamic rendering
+    Dynamic {
+        format: vk::Format,
+        stencil_format: vk::Format,
+    },
+}
+
+impl RenderTarget {
+    fn has_stencil(&self) -> bool {
+        !matches!(self, RenderTarget::RenderPass(_))
+    }
+}
+
+/// Stencil state of a [`GraphicsPipeline`] drawing into a target with a stencil attachment,
+/// see `Renderer::begin_mask`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum StencilMode {
+    /// Draws where the stencil value matches the reference, the dynamic compare mask of
+    /// unmasked instances is zero
+    Test,
+    /// Writes the reference into the stencil attachment without drawing any color
+    Write,
+}
+
+/// Vertex input and fixed function states of a [`GraphicsPipeline`]
+pub(crate) struct PipelineStates<'a> {
+    pub viewport: vk::Viewport,
+    pub scissor: vk::Rect2D,
+    /// Zero Stride -> No vertex input, the vertices are generated in the shader
+    pub vertex_stride: u32,
+    pub vertex_attributes: &'a [vk::VertexInputAttributeDescription],
+    pub blend: vk::PipelineColorBlendAttachmentState,
+    pub cull_mode: vk::CullModeFlags,
+    pub samples: vk::SampleCountFlags,
+    pub polygon_mode: vk::PolygonMode,
+    pub topology: vk::PrimitiveTopology,
+    /// Ignored by targets without a stencil attachment
+    pub stencil: StencilMode,
+}
+
+/// Position and color attributes of the [`crate::Vertex`] of the objects
+fn object_vertex_attributes() -> [vk::VertexInputAttributeDescription; 2] {
+    [
+        vk::VertexInputAttributeDescription::builder()
+            .binding(0)
+            .location(0)
+            .format(vk::Format::R32G32B32_SFLOAT)
+            .offset(0)
+            .build(),
+        vk::VertexInputAttributeDescription::builder()
+            .binding(0)
+            .location(1)
+            .format(vk::Format::R32G32B32_SFLOAT)
+            .offset((std::mem::size_of::<[f32; 3]>()) as u32)
+            .build(),
+    ]
+}
+
 impl GraphicsPipeline {
-    /// Creates a new [`GraphicsPipeline`]
+    /// Creates a new [`GraphicsPipeline`] using the vertex and fragment `shaders`
     pub fn new(
         logical_device: &ash::Device,
-        descriptor_set_layout: &vk::DescriptorSetLayout,
+        target: &RenderTarget,
+        descriptor_set_layouts: &[vk::DescriptorSetLayout],
         viewport: &vk::Viewport,
         scissor: &vk::Rect2D,
         vertex_stride: u32,
-        push_constant_ranges: &vk::PushConstantRange,
+        push_constant_ranges: &[vk::PushConstantRange],
+        shaders: &ShaderCode,
+        blend_enable: bool,
+        samples: vk::SampleCountFlags,
+        polygon_mode: vk::PolygonMode,
+        topology: vk::PrimitiveTopology,
+    ) -> Result<Self> {
+        let vertex_attributes = object_vertex_attributes();
+
+        let blend = vk::PipelineColorBlendAttachmentState::builder()
+            .color_write_mask(vk::ColorComponentFlags::RGBA)
+            .blend_enable(blend_enable)
+            .src_color_blend_factor(vk::BlendFactor::SRC_ALPHA)
+            .dst_color_blend_factor(vk::BlendFactor::ONE_MINUS_SRC_ALPHA)
+            .color_blend_op(vk::BlendOp::ADD)
+            .src_alpha_blend_factor(vk::BlendFactor::ONE)
+            .dst_alpha_blend_factor(vk::BlendFactor::ZERO)
+            .alpha_blend_op(vk::BlendOp::ADD)
+            .build();
+
+        Self::with_states(
+            logical_device,
+            target,
+            descriptor_set_layouts,
+            push_constant_ranges,
+            shaders,
+            &PipelineStates {
+                viewport: *viewport,
+                scissor: *scissor,
+                vertex_stride,
+                vertex_attributes: &vertex_attributes,
+                blend,
+                cull_mode: vk::CullModeFlags::BACK,
+                samples,
+                polygon_mode,
+                topology,
+                stencil: StencilMode::Test,
+            },
+        )
+    }
+
+    /// Creates a new [`GraphicsPipeline`] drawing the objects with the `shaders` into the
+    /// stencil attachment of the masks only
+    pub(crate) fn mask(
+        logical_device: &ash::Device,
+        target: &RenderTarget,
+        descriptor_set_layouts: &[vk::DescriptorSetLayout],
+        viewport: &vk::Viewport,
+        scissor: &vk::Rect2D,
+        shaders: &ShaderCode,
+        samples: vk::SampleCountFlags,
+    ) -> Result<Self> {
+        Self::with_states(
+            logical_device,
+            target,
+            descriptor_set_layouts,
+            &[],
+            shaders,
+            &PipelineStates {
+                viewport: *viewport,
+                scissor: *scissor,
+                vertex_stride: std::mem::size_of::<crate::Vertex>() as u32,
+                vertex_attributes: &object_vertex_attributes(),
+                blend: vk::PipelineColorBlendAttachmentState::default(),
+                cull_mode: vk::CullModeFlags::BACK,
+                samples,
+                polygon_mode: vk::PolygonMode::FILL,
+                topology: vk::PrimitiveTopology::TRIANGLE_LIST,
+                stencil: StencilMode::Write,
+            },
+        )
+    }
+
+    /// Creates a new [`GraphicsPipeline`] with its own vertex input and fixed function `states`
+    pub(crate) fn with_states(
+        logical_device: &ash::Device,
+        target: &RenderTarget,
+        descriptor_set_layouts: &[vk::DescriptorSetLayout],
+        push_constant_ranges: &[vk::PushConstantRange],
+        shaders: &ShaderCode,
+        states: &PipelineStates,
     ) -> Result<Self> {
         /* Pipeline Stages */
 
         let shader_mod_vert = {
-            let code = std::fs::read("res/shaders/spirv/shader.vert.spv")?;
-
-            let create_info = vk::ShaderModuleCreateInfo::builder()
-                .code(bytemuck::try_cast_slice(code.as_slice())?);
+            let create_info = vk::ShaderModuleCreateInfo::builder().code(&shaders.vert);
 
             unsafe { logical_device.create_shader_module(&create_info, None) }?
         };
@@ -43,10 +447,7 @@ impl GraphicsPipeline {
             .build();
 
         let shader_mod_frag = {
-            let code = std::fs::read("res/shaders/spirv/shader.frag.spv")?;
-
-            let create_info = vk::ShaderModuleCreateInfo::builder()
-                .code(bytemuck::try_cast_slice(code.as_slice())?);
+            let create_info = vk::ShaderModuleCreateInfo::builder().code(&shaders.frag);
 
             unsafe { logical_device.create_shader_module(&create_info, None) }?
         };
@@ -61,47 +462,47 @@ impl GraphicsPipeline {
 
         /* Pipeline States */
 
-        let dynamic_state = vk::PipelineDynamicStateCreateInfo::builder()
-            .dynamic_states(&[vk::DynamicState::VIEWPORT, vk::DynamicState::SCISSOR]);
+        let dynamic_states = [
+            vk::DynamicState::VIEWPORT,
+            vk::DynamicState::SCISSOR,
+            vk::DynamicState::STENCIL_COMPARE_MASK,
+            vk::DynamicState::STENCIL_REFERENCE,
+        ];
+        let dynamic_state =
+            vk::PipelineDynamicStateCreateInfo::builder().dynamic_states(if target.has_stencil() {
+                &dynamic_states
+            } else {
+                &dynamic_states[..2]
+            });
 
         let input_assembly_state = vk::PipelineInputAssemblyStateCreateInfo::builder()
-            .topology(vk::PrimitiveTopology::TRIANGLE_LIST)
+            .topology(states.topology)
             .primitive_restart_enable(false);
 
         let vertex_binding_descriptions = vk::VertexInputBindingDescription::builder()
             .binding(0)
-            .stride(vertex_stride)
+            .stride(states.vertex_stride)
             .input_rate(vk::VertexInputRate::VERTEX);
 
-        let vertex_attribute_descriptions = [
-            vk::VertexInputAttributeDescription::builder()
-                .binding(0)
-                .location(0)
-                .format(vk::Format::R32G32B32_SFLOAT)
-                .offset(0)
-                .build(),
-            vk::VertexInputAttributeDescription::builder()
-                .binding(0)
-                .location(1)
-                .format(vk::Format::R32G32B32_SFLOAT)
-                .offset((std::mem::size_of::<[f32; 3]>()) as u32)
-                .build(),
-        ];
-
-        let vertex_input_state = vk::PipelineVertexInputStateCreateInfo::builder()
-            .vertex_binding_descriptions(std::slice::from_ref(&vertex_binding_descriptions))
-            .vertex_attribute_descriptions(&vertex_attribute_descriptions);
+        // Zero Stride -> No vertex input, the vertices are generated in the shader
+        let vertex_input_state = if states.vertex_stride == 0 {
+            vk::PipelineVertexInputStateCreateInfo::builder()
+        } else {
+            vk::PipelineVertexInputStateCreateInfo::builder()
+                .vertex_binding_descriptions(std::slice::from_ref(&vertex_binding_descriptions))
+                .vertex_attribute_descriptions(states.vertex_attributes)
+        };
 
         let viewport_state = vk::PipelineViewportStateCreateInfo::builder()
-            .viewports(std::slice::from_ref(&viewport))
-            .scissors(std::slice::from_ref(&scissor));
+            .viewports(std::slice::from_ref(&states.viewport))
+            .scissors(std::slice::from_ref(&states.scissor));
 
         let rasterization_state = vk::PipelineRasterizationStateCreateInfo::builder()
             .depth_clamp_enable(false)
             .rasterizer_discard_enable(false)
-            .polygon_mode(vk::PolygonMode::FILL)
+            .polygon_mode(states.polygon_mode)
             .line_width(1.0)
-            .cull_mode(vk::CullModeFlags::BACK)
+            .cull_mode(states.cull_mode)
             .front_face(vk::FrontFace::COUNTER_CLOCKWISE)
             .depth_bias_enable(false)
             .depth_bias_constant_factor(0.0)
@@ -110,71 +511,67 @@ impl GraphicsPipeline {
 
         let multisample_state = vk::PipelineMultisampleStateCreateInfo::builder()
             .sample_shading_enable(false)
-            .rasterization_samples(vk::SampleCountFlags::TYPE_1)
+            .rasterization_samples(states.samples)
             .min_sample_shading(1.0);
 
-        let color_blend_attachment_state = vk::PipelineColorBlendAttachmentState::builder()
-            .color_write_mask(vk::ColorComponentFlags::RGBA)
-            .blend_enable(false)
-            .src_color_blend_factor(vk::BlendFactor::ONE)
-            .dst_color_blend_factor(vk::BlendFactor::ZERO)
-            .color_blend_op(vk::BlendOp::ADD)
-            .src_alpha_blend_factor(vk::BlendFactor::ONE)
-            .dst_alpha_blend_factor(vk::BlendFactor::ZERO)
-            .alpha_blend_op(vk::BlendOp::ADD);
+        // Masks: Shapes write their stencil reference, clipped instances draw where it matches
+        let stencil_op_state = match states.stencil {
+            StencilMode::Test => vk::StencilOpState::builder()
+                .fail_op(vk::StencilOp::KEEP)
+                .pass_op(vk::StencilOp::KEEP)
+                .depth_fail_op(vk::StencilOp::KEEP)
+                .compare_op(vk::CompareOp::EQUAL)
+                .build(),
+            StencilMode::Write => vk::StencilOpState::builder()
+                .fail_op(vk::StencilOp::KEEP)
+                .pass_op(vk::StencilOp::REPLACE)
+                .depth_fail_op(vk::StencilOp::KEEP)
+                .compare_op(vk::CompareOp::ALWAYS)
+                .write_mask(u32::MAX)
+                .build(),
+        };
+
+        let depth_stencil_state = vk::PipelineDepthStencilStateCreateInfo::builder()
+            .depth_test_enable(false)
+            .depth_write_enable(false)
+            .stencil_test_enable(true)
+            .front(stencil_op_state)
+            .back(stencil_op_state);
+
+        let blend = match states.stencil {
+            StencilMode::Write if target.has_stencil() => vk::PipelineColorBlendAttachmentState {
+                color_write_mask: vk::ColorComponentFlags::empty(),
+                ..states.blend
+            },
+            _ => states.blend,
+        };
 
         let color_blend_state = vk::PipelineColorBlendStateCreateInfo::builder()
             .logic_op_enable(false)
             .logic_op(vk::LogicOp::COPY)
-            .attachments(std::slice::from_ref(&color_blend_attachment_state));
-
-        /* Render- & Subpasses */
-
-        let color_attachment = vk::AttachmentDescription::builder()
-            .format(vk::Format::B8G8R8A8_SRGB)
-            .samples(vk::SampleCountFlags::TYPE_1)
-            .load_op(vk::AttachmentLoadOp::CLEAR)
-            .store_op(vk::AttachmentStoreOp::STORE)
-            .stencil_load_op(vk::AttachmentLoadOp::DONT_CARE)
-            .stencil_store_op(vk::AttachmentStoreOp::DONT_CARE)
-            .initial_layout(vk::ImageLayout::UNDEFINED)
-            .final_layout(vk::ImageLayout::PRESENT_SRC_KHR);
-
-        let color_attachment_ref = vk::AttachmentReference::builder()
-            .attachment(0) // <- Index of attachment descriptor
-            .layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL);
-
-        let subpass = vk::SubpassDescription::builder()
-            .pipeline_bind_point(vk::PipelineBindPoint::GRAPHICS)
-            .color_attachments(std::slice::from_ref(&color_attachment_ref));
-
-        let subpass_dependency = vk::SubpassDependency::builder()
-            .src_subpass(vk::SUBPASS_EXTERNAL)
-            .dst_subpass(0)
-            .src_stage_mask(vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT)
-            .dst_stage_mask(vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT)
-            .src_access_mask(vk::AccessFlags::empty())
-            .dst_access_mask(vk::AccessFlags::COLOR_ATTACHMENT_WRITE);
-
-        let render_pass = {
-            let create_info = vk::RenderPassCreateInfo::builder()
-                .attachments(std::slice::from_ref(&color_attachment))
-                .subpasses(std::slice::from_ref(&subpass))
-                .dependencies(std::slice::from_ref(&subpass_dependency));
-
-            unsafe { logical_device.create_render_pass(&create_info, None)? }
-        };
+            .attachments(std::slice::from_ref(&blend));
 
         /* Pipeline Finalization */
 
         let layout = {
             let create_info = vk::PipelineLayoutCreateInfo::builder()
-                .set_layouts(std::slice::from_ref(&descriptor_set_layout))
-                .push_constant_ranges(std::slice::from_ref(&push_constant_ranges));
+                .set_layouts(descriptor_set_layouts)
+                .push_constant_ranges(push_constant_ranges);
 
             unsafe { logical_device.create_pipeline_layout(&create_info, None) }?
         };
 
+        let (color_formats, stencil_format) = match target {
+            RenderTarget::Dynamic {
+                format,
+                stencil_format,
+            } => (vec![*format], *stencil_format),
+            _ => (Vec::new(), vk::Format::UNDEFINED),
+        };
+        let mut rendering_info = vk::PipelineRenderingCreateInfo::builder()
+            .color_attachment_formats(&color_formats)
+            .stencil_attachment_format(stencil_format);
+
         let pipeline = {
             let create_info = vk::GraphicsPipelineCreateInfo::builder()
                 .stages(&shader_stages)
@@ -183,12 +580,23 @@ impl GraphicsPipeline {
                 .viewport_state(&viewport_state)
                 .rasterization_state(&rasterization_state)
                 .multisample_state(&multisample_state)
-                //.depth_stencil_state(depth_stencil_state)
                 .color_blend_state(&color_blend_state)
                 .dynamic_state(&dynamic_state)
-                .layout(layout)
-                .render_pass(render_pass)
-                .subpass(0);
+                .layout(layout);
+
+            let create_info = if target.has_stencil() {
+                create_info.depth_stencil_state(&depth_stencil_state)
+            } else {
+                create_info
+            };
+
+            let create_info = match target {
+                RenderTarget::RenderPass(render_pass)
+                | RenderTarget::StencilRenderPass(render_pass) => {
+                    create_info.render_pass(*render_pass).subpass(0)
+                }
+                RenderTarget::Dynamic { .. } => create_info.push_next(&mut rendering_info),
+            };
 
             unsafe {
                 logical_device.create_graphics_pipelines(
@@ -213,10 +621,160 @@ impl GraphicsPipeline {
             logical_device.destroy_shader_module(shader_mod_vert, None);
         };
 
-        Ok(Self {
-            layout,
-            render_pass,
-            pipeline,
+        Ok(Self { layout, pipeline })
+    }
+
+    /// Destroys the pipeline and its layout, the device must not use them anymore
+    pub fn destroy(&self, logical_device: &ash::Device) {
+        unsafe {
+            logical_device.destroy_pipeline(self.pipeline, None);
+            logical_device.destroy_pipeline_layout(self.layout, None);
+        }
+    }
+}
+
+//==================================================
+//=== Render Pass
+//==================================================
+
+/// Creates the render pass shared by every [`GraphicsPipeline`]
+///
+/// With multiple `samples` the frame is drawn into a multisampled color target,
+/// which is resolved into the swapchain image at the end of the render pass
+///
+/// The image is left in `final_layout`, eg. `SHADER_READ_ONLY_OPTIMAL` for the offscreen
+/// targets sampled by the post-processing
+///
+/// With a `stencil_format` the stencil attachment of the masks follows the color attachments,
+/// cleared at the start of the pass
+pub fn create_render_pass(
+    logical_device: &ash::Device,
+    format: vk::Format,
+    samples: vk::SampleCountFlags,
+    final_layout: vk::ImageLayout,
+    stencil_format: Option<vk::Format>,
+) -> Result<vk::RenderPass> {
+    let multisampled = samples != vk::SampleCountFlags::TYPE_1;
+
+    let color_attachment = vk::AttachmentDescription::builder()
+        .format(format)
+        .samples(samples)
+        .load_op(vk::AttachmentLoadOp::CLEAR)
+        .store_op(if multisampled {
+            vk::AttachmentStoreOp::DONT_CARE
+        } else {
+            vk::AttachmentStoreOp::STORE
         })
+        .stencil_load_op(vk::AttachmentLoadOp::DONT_CARE)
+        .stencil_store_op(vk::AttachmentStoreOp::DONT_CARE)
+        .initial_layout(vk::ImageLayout::UNDEFINED)
+        .final_layout(if multisampled {
+            vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL
+        } else {
+            final_layout
+        })
+        .build();
+
+    let resolve_attachment = vk::AttachmentDescription::builder()
+        .format(format)
+        .samples(vk::SampleCountFlags::TYPE_1)
+        .load_op(vk::AttachmentLoadOp::DONT_CARE)
+        .store_op(vk::AttachmentStoreOp::STORE)
+        .stencil_load_op(vk::AttachmentLoadOp::DONT_CARE)
+        .stencil_store_op(vk::AttachmentStoreOp::DONT_CARE)
+        .initial_layout(vk::ImageLayout::UNDEFINED)
+        .final_layout(final_layout)
+        .build();
+
+    let color_attachment_ref = vk::AttachmentReference::builder()
+        .attachment(0) // <- Index of attachment descriptor
+        .layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL);
+
+    let resolve_attachment_ref = vk::AttachmentReference::builder()
+        .attachment(1)
+        .layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL);
+
+    let mut attachments = vec![color_attachment];
+    if multisampled {
+        attachments.push(resolve_attachment);
+    }
+
+    let stencil_attachment_ref = vk::AttachmentReference::builder()
+        .attachment(attachments.len() as u32)
+        .layout(vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL);
+
+    if let Some(stencil_format) = stencil_format {
+        attachments.push(
+            vk::AttachmentDescription::builder()
+                .format(stencil_format)
+                .samples(samples)
+                .load_op(vk::AttachmentLoadOp::DONT_CARE)
+                .store_op(vk::AttachmentStoreOp::DONT_CARE)
+                .stencil_load_op(vk::AttachmentLoadOp::CLEAR)
+                .stencil_store_op(vk::AttachmentStoreOp::DONT_CARE)
+                .initial_layout(vk::ImageLayout::UNDEFINED)
+                .final_layout(vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL)
+                .build(),
+        );
     }
+
+    let subpass = vk::SubpassDescription::builder()
+        .pipeline_bind_point(vk::PipelineBindPoint::GRAPHICS)
+        .color_attachments(std::slice::from_ref(&color_attachment_ref));
+
+    let subpass = if multisampled {
+        subpass.resolve_attachments(std::slice::from_ref(&resolve_attachment_ref))
+    } else {
+        subpass
+    };
+
+    let subpass = if stencil_format.is_some() {
+        subpass.depth_stencil_attachment(&stencil_attachment_ref)
+    } else {
+        subpass
+    };
+
+    // Offscreen targets may still be sampled by the post-processing of the previous frame,
+    // the stencil attachment may still be tested by the previous frame
+    let subpass_dependencies = [
+        vk::SubpassDependency::builder()
+            .src_subpass(vk::SUBPASS_EXTERNAL)
+            .dst_subpass(0)
+            .src_stage_mask(
+                vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT
+                    | vk::PipelineStageFlags::FRAGMENT_SHADER
+                    | vk::PipelineStageFlags::LATE_FRAGMENT_TESTS,
+            )
+            .dst_stage_mask(
+                vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT
+                    | vk::PipelineStageFlags::EARLY_FRAGMENT_TESTS,
+            )
+            .src_access_mask(vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE)
+            .dst_access_mask(
+                vk::AccessFlags::COLOR_ATTACHMENT_WRITE
+                    | vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE,
+            )
+            .build(),
+        // Sampled Image -> The next pass reads it only after the writes
+        vk::SubpassDependency::builder()
+            .src_subpass(0)
+            .dst_subpass(vk::SUBPASS_EXTERNAL)
+            .src_stage_mask(vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT)
+            .dst_stage_mask(vk::PipelineStageFlags::FRAGMENT_SHADER)
+            .src_access_mask(vk::AccessFlags::COLOR_ATTACHMENT_WRITE)
+            .dst_access_mask(vk::AccessFlags::SHADER_READ)
+            .build(),
+    ];
+    let dependency_count = if final_layout == vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL {
+        2
+    } else {
+        1
+    };
+
+    let create_info = vk::RenderPassCreateInfo::builder()
+        .attachments(&attachments)
+        .subpasses(std::slice::from_ref(&subpass))
+        .dependencies(&subpass_dependencies[..dependency_count]);
+
+    Ok(unsafe { logical_device.create_render_pass(&create_info, None) }?)
 }
@@ -0,0 +1,394 @@
+// extern
+extern crate nalgebra_glm as glm;
+use anyhow::Result;
+
+// intern
+use crate::shapes::CIRCLE_SIZE;
+use crate::text::{HorizontalAlign, TextParams, VerticalAlign};
+use crate::ui::UiRect;
+use crate::{AnchorType, Renderer};
+
+//==================================================
+//=== Plot
+//==================================================
+
+/// Colors and sizes of a [`Plot`]
+#[derive(Debug, Clone)]
+pub struct PlotStyle {
+    pub axis_color: glm::Vec3,
+    pub grid_color: glm::Vec3,
+    /// Series without a color of their own cycle through these
+    pub series_colors: Vec<glm::Vec3>,
+    /// Thickness of the axes and line series
+    pub thickness: f32,
+    /// Diameter of the points of scatter series
+    pub point_size: f32,
+    /// Length of the tick marks outside of the axes
+    pub tick_length: f32,
+    pub text_params: TextParams,
+}
+
+impl Default for PlotStyle {
+    fn default() -> Self {
+        Self {
+            axis_color: glm::vec3(0.8, 0.8, 0.8),
+            grid_color: glm::vec3(0.3, 0.3, 0.3),
+            series_colors: vec![
+                glm::vec3(0.3, 0.6, 1.0),
+                glm::vec3(1.0, 0.5, 0.2),
+                glm::vec3(0.4, 0.8, 0.3),
+                glm::vec3(0.9, 0.3, 0.4),
+            ],
+            thickness: 0.01,
+            point_size: 0.03,
+            tick_length: 0.03,
+            text_params: TextParams {
+                scale: 0.5,
+                color: glm::vec3(0.8, 0.8, 0.8),
+                ..TextParams::default()
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SeriesKind {
+    Line,
+    Scatter,
+}
+
+#[derive(Debug, Clone)]
+struct Series {
+    kind: SeriesKind,
+    points: Vec<glm::Vec2>,
+    color: Option<glm::Vec3>,
+}
+
+/// Chart with axes, tick marks, grid lines and line or scatter series, drawn with the shapes
+/// and text of the [`Renderer`]
+///
+/// The data ranges are fitted to the series unless they are set
+#[derive(Debug, Clone)]
+pub struct Plot {
+    pub style: PlotStyle,
+    /// Area of the data, the tick labels are placed outside of it
+    rect: UiRect,
+    anchor_type: AnchorType,
+    x_range: Option<(f32, f32)>,
+    y_range: Option<(f32, f32)>,
+    ticks: usize,
+    grid: bool,
+    series: Vec<Series>,
+}
+
+impl Plot {
+    /// Creates a new empty [`Plot`] inside `rect` with 5 ticks per axis and grid lines
+    pub fn new(rect: UiRect) -> Self {
+        Self {
+            style: PlotStyle::default(),
+            rect,
+            anchor_type: AnchorType::Unlocked,
+            x_range: None,
+            y_range: None,
+            ticks: 5,
+            grid: true,
+            series: Vec::new(),
+        }
+    }
+
+    /// Places the plot relative to the view instead of the world with [`AnchorType::Locked`]
+    pub fn with_anchor(mut self, anchor_type: AnchorType) -> Self {
+        self.anchor_type = anchor_type;
+        self
+    }
+
+    pub fn with_x_range(mut self, min: f32, max: f32) -> Self {
+        self.x_range = Some((min, max));
+        self
+    }
+
+    pub fn with_y_range(mut self, min: f32, max: f32) -> Self {
+        self.y_range = Some((min, max));
+        self
+    }
+
+    /// Sets the number of intervals between the tick marks of each axis
+    pub fn with_ticks(mut self, ticks: usize) -> Self {
+        self.ticks = ticks.max(1);
+        self
+    }
+
+    pub fn with_grid(mut self, grid: bool) -> Self {
+        self.grid = grid;
+        self
+    }
+
+    /// Adds a series of `points` connected by lines
+    pub fn line_series(self, points: &[(f32, f32)]) -> Self {
+        self.push_series(SeriesKind::Line, points)
+    }
+
+    /// Adds a series of separate `points`
+    pub fn scatter_series(self, points: &[(f32, f32)]) -> Self {
+        self.push_series(SeriesKind::Scatter, points)
+    }
+
+    /// Sets the color of the last added series
+    pub fn with_color(mut self, color: glm::Vec3) -> Self {
+        if let Some(series) = self.series.last_mut() {
+            series.color = Some(color);
+        }
+        self
+    }
+
+    /// Pushes the grid, axes, tick labels and series of the plot to draw
+    pub fn draw(&self, renderer: &mut Renderer) -> Result<()> {
+        let x_range = self.x_range.unwrap_or_else(|| self.fit(|p| p.x));
+        let y_range = self.y_range.unwrap_or_else(|| self.fit(|p| p.y));
+
+        let min = self.rect.center - self.rect.size * 0.5;
+        let max = self.rect.center + self.rect.size * 0.5;
+        let to_rect = |point: glm::Vec2| map_point(point, x_range, y_range, min, self.rect.size);
+
+        /* Grid & Ticks */
+
+        let labels = TextParams {
+            horizontal_align: HorizontalAlign::Center,
+            vertical_align: VerticalAlign::Top,
+            ..self.style.text_params
+        };
+
+        let x_ticks = ticks(x_range, self.ticks);
+        for &value in &x_ticks {
+            let x = to_rect(glm::vec2(value, y_range.0)).x;
+
+            if self.grid {
+                self.line(
+                    renderer,
+                    glm::vec2(x, min.y),
+                    glm::vec2(x, max.y),
+                    self.style.grid_color,
+                )?;
+            }
+
+            self.line(
+                renderer,
+                glm::vec2(x, min.y),
+                glm::vec2(x, min.y - self.style.tick_length),
+                self.style.axis_color,
+            )?;
+            renderer.text(
+                &format_tick(value, &x_ticks),
+                x,
+                min.y - self.style.tick_length * 1.5,
+                &labels,
+                self.anchor_type,
+            )?;
+        }
+
+        let labels = TextParams {
+            horizontal_align: HorizontalAlign::Right,
+            vertical_align: VerticalAlign::Center,
+            ..labels
+        };
+
+        let y_ticks = ticks(y_range, self.ticks);
+        for &value in &y_ticks {
+            let y = to_rect(glm::vec2(x_range.0, value)).y;
+
+            if self.grid {
+                self.line(
+                    renderer,
+                    glm::vec2(min.x, y),
+                    glm::vec2(max.x, y),
+                    self.style.grid_color,
+                )?;
+            }
+
+            self.line(
+                renderer,
+                glm::vec2(min.x, y),
+                glm::vec2(min.x - self.style.tick_length, y),
+                self.style.axis_color,
+            )?;
+            renderer.text(
+                &format_tick(value, &y_ticks),
+                min.x - self.style.tick_length * 1.5,
+                y,
+                &labels,
+                self.anchor_type,
+            )?;
+        }
+
+        /* Axes */
+
+        self.line(
+            renderer,
+            glm::vec2(min.x, min.y),
+            glm::vec2(max.x, min.y),
+            self.style.axis_color,
+        )?;
+        self.line(
+            renderer,
+            glm::vec2(min.x, min.y),
+            glm::vec2(min.x, max.y),
+            self.style.axis_color,
+        )?;
+
+        /* Series */
+
+        for (index, series) in self.series.iter().enumerate() {
+            let color = series.color.unwrap_or_else(|| {
+                self.style.series_colors[index % self.style.series_colors.len().max(1)]
+            });
+            let points: Vec<glm::Vec2> = series.points.iter().map(|&p| to_rect(p)).collect();
+
+            match series.kind {
+                SeriesKind::Line => {
+                    renderer.polyline(&points, self.style.thickness, color, self.anchor_type)?
+                }
+                SeriesKind::Scatter => {
+                    // Markers are placed by their center, independent of the pivot
+                    let previous_pivot = std::mem::take(&mut renderer.draw_params.pivot);
+
+                    let result = points.iter().try_for_each(|point| {
+                        renderer.circle(
+                            self.style.point_size / CIRCLE_SIZE,
+                            point.x,
+                            point.y,
+                            color,
+                            self.anchor_type,
+                        )
+                    });
+
+                    renderer.draw_params.pivot = previous_pivot;
+                    result?
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn push_series(mut self, kind: SeriesKind, points: &[(f32, f32)]) -> Self {
+        self.series.push(Series {
+            kind,
+            points: points.iter().map(|&(x, y)| glm::vec2(x, y)).collect(),
+            color: None,
+        });
+        self
+    }
+
+    /// Gives back the range of a coordinate of every point, ranges without an extent are widened
+    fn fit(&self, coordinate: impl Fn(&glm::Vec2) -> f32) -> (f32, f32) {
+        let (min, max) = self
+            .series
+            .iter()
+            .flat_map(|series| series.points.iter().map(&coordinate))
+            .fold((f32::MAX, f32::MIN), |(min, max), value| {
+                (min.min(value), max.max(value))
+            });
+
+        if min > max {
+            (0.0, 1.0)
+        } else if max - min <= f32::EPSILON {
+            (min - 0.5, max + 0.5)
+        } else {
+            (min, max)
+        }
+    }
+
+    fn line(
+        &self,
+        renderer: &mut Renderer,
+        from: glm::Vec2,
+        to: glm::Vec2,
+        color: glm::Vec3,
+    ) -> Result<()> {
+        Ok(renderer.line(
+            from,
+            to,
+            self.style.thickness * 0.5,
+            color,
+            self.anchor_type,
+        )?)
+    }
+}
+
+/// Maps a data `point` inside the ranges onto the area starting at the `min` corner
+fn map_point(
+    point: glm::Vec2,
+    x_range: (f32, f32),
+    y_range: (f32, f32),
+    min: glm::Vec2,
+    size: glm::Vec2,
+) -> glm::Vec2 {
+    let fraction = glm::vec2(
+        (point.x - x_range.0) / (x_range.1 - x_range.0),
+        (point.y - y_range.0) / (y_range.1 - y_range.0),
+    );
+
+    min + fraction.component_mul(&size)
+}
+
+/// Gives back `count` + 1 evenly spaced values from the start to the end of `range`
+fn ticks(range: (f32, f32), count: usize) -> Vec<f32> {
+    (0..=count)
+        .map(|i| range.0 + (range.1 - range.0) * i as f32 / count as f32)
+        .collect()
+}
+
+/// Formats a tick value with the decimals needed to tell the `ticks` apart
+fn format_tick(value: f32, ticks: &[f32]) -> String {
+    let step = match ticks {
+        [first, second, ..] => (second - first).abs(),
+        _ => 1.0,
+    };
+
+    // Fewest decimals showing the step as a whole number, eg. 1 for 2.5
+    let decimals = (0..6)
+        .find(|&decimals| {
+            let scaled = step * 10_f32.powi(decimals);
+            (scaled - scaled.round()).abs() < 1e-3 * scaled.max(1.0)
+        })
+        .unwrap_or(6) as usize;
+
+    format!("{:.*}", decimals, value)
+}
+
+//==================================================
+//=== Unit Testing
+//==================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plot_layout() {
+        let plot = Plot::new(UiRect::new(0.0, 0.0, 2.0, 1.0))
+            .line_series(&[(0.0, -1.0), (10.0, 1.0)])
+            .scatter_series(&[(5.0, 0.0)]);
+
+        // Ranges are fitted to every series
+        let x_range = plot.fit(|p| p.x);
+        let y_range = plot.fit(|p| p.y);
+        assert_eq!((x_range, y_range), ((0.0, 10.0), (-1.0, 1.0)));
+
+        let min = glm::vec2(-1.0, -0.5);
+        let size = glm::vec2(2.0, 1.0);
+        assert_eq!(
+            map_point(glm::vec2(5.0, 0.0), x_range, y_range, min, size),
+            glm::vec2(0.0, 0.0)
+        );
+        assert_eq!(
+            map_point(glm::vec2(10.0, 1.0), x_range, y_range, min, size),
+            glm::vec2(1.0, 0.5)
+        );
+
+        let x_ticks = ticks(x_range, 4);
+        assert_eq!(x_ticks, [0.0, 2.5, 5.0, 7.5, 10.0]);
+        assert_eq!(format_tick(7.5, &x_ticks), "7.5");
+        assert_eq!(format_tick(10.0, &ticks((0.0, 100.0), 4)), "10");
+    }
+}
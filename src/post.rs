@@ -0,0 +1,578 @@
+// extern
+extern crate nalgebra_glm as glm;
+use anyhow::{anyhow, Result};
+use ash::vk;
+
+// intern
+use crate::allocator::{Allocation, MemoryAllocator};
+use crate::buffers::{ColorTarget, FrameBuffer, StencilTarget};
+use crate::descriptor;
+use crate::pipeline::{self, GraphicsPipeline, RenderTarget, ShaderCode};
+
+//==================================================
+//=== Post Effect
+//==================================================
+
+/// Full-screen effect applied on the finished frame, set with `Renderer::set_post_effects`
+#[derive(Debug, Clone, PartialEq)]
+pub enum PostEffect {
+    /// Darkens the frame towards the corners, starting at `radius` (0.0 center, 1.0 corner)
+    Vignette { strength: f32, radius: f32 },
+    /// Adds a blurred glow around the pixels brighter than `threshold`
+    Bloom { threshold: f32, intensity: f32 },
+    /// Gamma correction, above 1.0 brightens the frame
+    Gamma { gamma: f32 },
+    /// User SPIR-V fragment shader
+    ///
+    /// The frame is sampled from `set = 0, binding = 0`, the push constant block holds the
+    /// `params` followed by the texel size (`vec4 params; vec2 texel_size;`)
+    Custom { frag: Vec<u8>, params: glm::Vec4 },
+}
+
+impl PostEffect {
+    fn shaders(&self) -> Result<ShaderCode> {
+        match self {
+            PostEffect::Vignette { .. } => ShaderCode::post_effect("vignette"),
+            PostEffect::Bloom { .. } => ShaderCode::post_effect("bloom"),
+            PostEffect::Gamma { .. } => ShaderCode::post_effect("gamma"),
+            PostEffect::Custom { frag, .. } => ShaderCode::post_fragment(frag),
+        }
+    }
+
+    fn params(&self) -> glm::Vec4 {
+        match self {
+            PostEffect::Vignette { strength, radius } => glm::vec4(*strength, *radius, 0.0, 0.0),
+            PostEffect::Bloom {
+                threshold,
+                intensity,
+            } => glm::vec4(*threshold, *intensity, 0.0, 0.0),
+            PostEffect::Gamma { gamma } => glm::vec4(*gamma, 0.0, 0.0, 0.0),
+            PostEffect::Custom { params, .. } => *params,
+        }
+    }
+}
+
+/// Push constant block of the post-processing shaders
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+struct PostParams {
+    params: glm::Vec4,
+    texel_size: glm::Vec2,
+}
+
+//==================================================
+//=== Post Process
+//==================================================
+
+/// Sampled color image the frame is drawn into before the post-processing
+struct PostTarget {
+    image: vk::Image,
+    allocation: Allocation,
+    image_view: vk::ImageView,
+    /// Framebuffer of the passes drawing into the target
+    frame_buffer: vk::Framebuffer,
+}
+
+/// Offscreen targets, render passes and pipelines of the post-processing
+///
+/// The frame is drawn into the first target, then every effect reads one target and writes
+/// the other, the last effect writes the swapchain image
+pub(crate) struct PostProcess {
+    /// Main pass of the frame, compatible with the pipelines of the [`crate::Renderer`]
+    pub scene_render_pass: vk::RenderPass,
+    /// Main pass framebuffer drawing into the first target
+    pub scene_frame_buffer: vk::Framebuffer,
+    target_render_pass: vk::RenderPass,
+    present_render_pass: vk::RenderPass,
+    /// Swapchain format, also used by the offscreen targets
+    format: vk::Format,
+    present_frame_buffers: Vec<vk::Framebuffer>,
+    targets: Vec<PostTarget>,
+    sampler: vk::Sampler,
+    set_pool: vk::DescriptorPool,
+    target_sets: Vec<vk::DescriptorSet>,
+    effects: Vec<PostEffect>,
+    pipelines: Vec<GraphicsPipeline>,
+}
+
+impl PostProcess {
+    /// Number of offscreen targets the effects alternate between
+    const TARGET_COUNT: usize = 2;
+
+    /// Creates a new [`PostProcess`] without effects for the swapchain `image_views`
+    ///
+    /// The main pass draws with `samples`, resolving into the first target through the `color_target`,
+    /// masking with the `stencil_target`
+    pub fn new(
+        logical_device: &ash::Device,
+        allocator: &mut MemoryAllocator,
+        texture_set_layout: &vk::DescriptorSetLayout,
+        format: vk::Format,
+        samples: vk::SampleCountFlags,
+        extent: vk::Extent2D,
+        image_views: &Vec<vk::ImageView>,
+        color_target: Option<&ColorTarget>,
+        stencil_target: &StencilTarget,
+    ) -> Result<Self> {
+        let scene_render_pass = pipeline::create_render_pass(
+            logical_device,
+            format,
+            samples,
+            vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+            Some(stencil_target.format),
+        )?;
+        let target_render_pass = pipeline::create_render_pass(
+            logical_device,
+            format,
+            vk::SampleCountFlags::TYPE_1,
+            vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+            None,
+        )?;
+        let present_render_pass = pipeline::create_render_pass(
+            logical_device,
+            format,
+            vk::SampleCountFlags::TYPE_1,
+            vk::ImageLayout::PRESENT_SRC_KHR,
+            None,
+        )?;
+
+        let sampler = {
+            let create_info = vk::SamplerCreateInfo::builder()
+                .mag_filter(vk::Filter::LINEAR)
+                .min_filter(vk::Filter::LINEAR)
+                .mipmap_mode(vk::SamplerMipmapMode::NEAREST)
+                .address_mode_u(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+                .address_mode_v(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+                .address_mode_w(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+                .max_lod(0.0)
+                .border_color(vk::BorderColor::INT_OPAQUE_BLACK);
+
+            unsafe { logical_device.create_sampler(&create_info, None) }?
+        };
+
+        // Target sets come from their own pool, they are rewritten instead of reallocated
+        let set_pool = {
+            let pool_size = vk::DescriptorPoolSize::builder()
+                .ty(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+                .descriptor_count(Self::TARGET_COUNT as u32);
+
+            let create_info = vk::DescriptorPoolCreateInfo::builder()
+                .pool_sizes(std::slice::from_ref(&pool_size))
+                .max_sets(Self::TARGET_COUNT as u32);
+
+            unsafe { logical_device.create_descriptor_pool(&create_info, None) }?
+        };
+
+        let mut post_process = Self {
+            scene_render_pass,
+            scene_frame_buffer: vk::Framebuffer::null(),
+            target_render_pass,
+            present_render_pass,
+            format,
+            present_frame_buffers: Vec::new(),
+            targets: Vec::new(),
+            sampler,
+            set_pool,
+            target_sets: Vec::new(),
+            effects: Vec::new(),
+            pipelines: Vec::new(),
+        };
+
+        post_process.create_targets(
+            logical_device,
+            allocator,
+            extent,
+            image_views,
+            color_target,
+            stencil_target,
+        )?;
+
+        for target in &post_process.targets {
+            post_process
+                .target_sets
+                .push(descriptor::allocate_texture_set(
+                    logical_device,
+                    &set_pool,
+                    texture_set_layout,
+                    &target.image_view,
+                    &sampler,
+                )?);
+        }
+
+        Ok(post_process)
+    }
+
+    /// Replaces the effects, the device must not use the old pipelines anymore
+    pub fn set_effects(
+        &mut self,
+        logical_device: &ash::Device,
+        texture_set_layout: &vk::DescriptorSetLayout,
+        extent: vk::Extent2D,
+        effects: &[PostEffect],
+    ) -> Result<()> {
+        let viewport = vk::Viewport::builder()
+            .width(extent.width as f32)
+            .height(extent.height as f32)
+            .max_depth(1.0)
+            .build();
+        let scissor = vk::Rect2D::builder().extent(extent).build();
+
+        let push_constant_range = vk::PushConstantRange::builder()
+            .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+            .size(std::mem::size_of::<PostParams>() as u32)
+            .build();
+
+        let mut pipelines = Vec::new();
+        for effect in effects {
+            let pipeline = effect.shaders().and_then(|shaders| {
+                // Target and present passes are compatible, one pipeline is used for both
+                GraphicsPipeline::new(
+                    logical_device,
+                    &RenderTarget::RenderPass(self.present_render_pass),
+                    std::slice::from_ref(texture_set_layout),
+                    &viewport,
+                    &scissor,
+                    0,
+                    std::slice::from_ref(&push_constant_range),
+                    &shaders,
+                    false,
+                    vk::SampleCountFlags::TYPE_1,
+                    vk::PolygonMode::FILL,
+                    vk::PrimitiveTopology::TRIANGLE_LIST,
+                )
+            });
+
+            match pipeline {
+                Ok(pipeline) => pipelines.push(pipeline),
+                Err(err) => {
+                    pipelines
+                        .iter()
+                        .for_each(|pipeline| pipeline.destroy(logical_device));
+                    return Err(anyhow!("Post Effects: {:?} failed, {}", effect, err));
+                }
+            }
+        }
+
+        self.pipelines
+            .drain(..)
+            .for_each(|pipeline| pipeline.destroy(logical_device));
+
+        self.pipelines = pipelines;
+        self.effects = effects.to_vec();
+
+        Ok(())
+    }
+
+    /// Recreates the targets and framebuffers for the new swapchain `image_views`
+    pub fn recreate(
+        &mut self,
+        logical_device: &ash::Device,
+        allocator: &mut MemoryAllocator,
+        extent: vk::Extent2D,
+        image_views: &Vec<vk::ImageView>,
+        color_target: Option<&ColorTarget>,
+        stencil_target: &StencilTarget,
+    ) -> Result<()> {
+        self.destroy_targets(logical_device, allocator);
+        self.create_targets(
+            logical_device,
+            allocator,
+            extent,
+            image_views,
+            color_target,
+            stencil_target,
+        )?;
+
+        for (set, target) in self.target_sets.iter().zip(&self.targets) {
+            descriptor::write_texture_set(logical_device, *set, &target.image_view, &self.sampler);
+        }
+
+        Ok(())
+    }
+
+    /// Records the effect passes after the main pass, ending with the swapchain image of `image_index`
+    pub fn record(
+        &self,
+        logical_device: &ash::Device,
+        command_buffer: vk::CommandBuffer,
+        image_index: usize,
+        scissor: vk::Rect2D,
+        clear_values: &[vk::ClearValue],
+    ) -> Result<()> {
+        let viewport = vk::Viewport::builder()
+            .width(scissor.extent.width as f32)
+            .height(scissor.extent.height as f32)
+            .max_depth(1.0)
+            .build();
+        let texel_size = glm::vec2(
+            1.0 / scissor.extent.width.max(1) as f32,
+            1.0 / scissor.extent.height.max(1) as f32,
+        );
+
+        let passes = pass_targets(self.pipelines.len());
+        for ((pipeline, effect), (source, destination)) in
+            self.pipelines.iter().zip(&self.effects).zip(passes)
+        {
+            let (render_pass, frame_buffer) = match destination {
+                Some(destination) => (
+                    self.target_render_pass,
+                    self.targets[destination].frame_buffer,
+                ),
+                None => (
+                    self.present_render_pass,
+                    *self
+                        .present_frame_buffers
+                        .get(image_index)
+                        .ok_or_else(|| anyhow!("Post Effects: Frame buffer index out of bounds"))?,
+                ),
+            };
+
+            let render_pass_begin = vk::RenderPassBeginInfo::builder()
+                .render_pass(render_pass)
+                .framebuffer(frame_buffer)
+                .render_area(scissor)
+                .clear_values(&clear_values[..1]);
+
+            let post_params = PostParams {
+                params: effect.params(),
+                texel_size,
+            };
+
+            unsafe {
+                logical_device.cmd_begin_render_pass(
+                    command_buffer,
+                    &render_pass_begin,
+                    vk::SubpassContents::INLINE,
+                );
+
+                logical_device.cmd_bind_pipeline(
+                    command_buffer,
+                    vk::PipelineBindPoint::GRAPHICS,
+                    pipeline.pipeline,
+                );
+
+                logical_device.cmd_set_viewport(command_buffer, 0, std::slice::from_ref(&viewport));
+                logical_device.cmd_set_scissor(command_buffer, 0, std::slice::from_ref(&scissor));
+
+                logical_device.cmd_bind_descriptor_sets(
+                    command_buffer,
+                    vk::PipelineBindPoint::GRAPHICS,
+                    pipeline.layout,
+                    0,
+                    std::slice::from_ref(&self.target_sets[source]),
+                    &[],
+                );
+
+                logical_device.cmd_push_constants(
+                    command_buffer,
+                    pipeline.layout,
+                    vk::ShaderStageFlags::FRAGMENT,
+                    0,
+                    std::slice::from_raw_parts(
+                        (&post_params as *const PostParams).cast::<u8>(),
+                        std::mem::size_of::<PostParams>(),
+                    ),
+                );
+
+                // Full-screen triangle
+                logical_device.cmd_draw(command_buffer, 3, 1, 0, 0);
+
+                logical_device.cmd_end_render_pass(command_buffer);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Destroys every resource, the device must not use them anymore
+    pub fn destroy(&mut self, logical_device: &ash::Device, allocator: &mut MemoryAllocator) {
+        self.destroy_targets(logical_device, allocator);
+
+        self.pipelines
+            .drain(..)
+            .for_each(|pipeline| pipeline.destroy(logical_device));
+
+        unsafe {
+            logical_device.destroy_descriptor_pool(self.set_pool, None);
+            logical_device.destroy_sampler(self.sampler, None);
+            logical_device.destroy_render_pass(self.scene_render_pass, None);
+            logical_device.destroy_render_pass(self.target_render_pass, None);
+            logical_device.destroy_render_pass(self.present_render_pass, None);
+        }
+    }
+
+    fn create_targets(
+        &mut self,
+        logical_device: &ash::Device,
+        allocator: &mut MemoryAllocator,
+        extent: vk::Extent2D,
+        image_views: &Vec<vk::ImageView>,
+        color_target: Option<&ColorTarget>,
+        stencil_target: &StencilTarget,
+    ) -> Result<()> {
+        for _ in 0..Self::TARGET_COUNT {
+            let target = PostTarget::new(
+                logical_device,
+                allocator,
+                &self.target_render_pass,
+                self.format,
+                extent,
+            )?;
+            self.targets.push(target);
+        }
+
+        // Scene Pass -> Multisampled frames are resolved into the first target
+        self.scene_frame_buffer = FrameBuffer::new(
+            logical_device,
+            &vec![self.targets[0].image_view],
+            &self.scene_render_pass,
+            extent.width,
+            extent.height,
+            color_target,
+            Some(stencil_target),
+        )?
+        .buffers[0];
+
+        self.present_frame_buffers = FrameBuffer::new(
+            logical_device,
+            image_views,
+            &self.present_render_pass,
+            extent.width,
+            extent.height,
+            None,
+            None,
+        )?
+        .buffers;
+
+        Ok(())
+    }
+
+    fn destroy_targets(&mut self, logical_device: &ash::Device, allocator: &mut MemoryAllocator) {
+        unsafe {
+            logical_device.destroy_framebuffer(self.scene_frame_buffer, None);
+            self.present_frame_buffers
+                .drain(..)
+                .for_each(|fb| logical_device.destroy_framebuffer(fb, None));
+        }
+
+        self.targets
+            .drain(..)
+            .for_each(|target| target.destroy(logical_device, allocator));
+    }
+}
+
+impl PostTarget {
+    fn new(
+        logical_device: &ash::Device,
+        allocator: &mut MemoryAllocator,
+        render_pass: &vk::RenderPass,
+        format: vk::Format,
+        extent: vk::Extent2D,
+    ) -> Result<Self> {
+        let image = {
+            let create_info = vk::ImageCreateInfo::builder()
+                .image_type(vk::ImageType::TYPE_2D)
+                .format(format)
+                .extent(vk::Extent3D {
+                    width: extent.width,
+                    height: extent.height,
+                    depth: 1,
+                })
+                .mip_levels(1)
+                .array_layers(1)
+                .samples(vk::SampleCountFlags::TYPE_1)
+                .tiling(vk::ImageTiling::OPTIMAL)
+                .usage(vk::ImageUsageFlags::COLOR_ATTACHMENT | vk::ImageUsageFlags::SAMPLED)
+                .sharing_mode(vk::SharingMode::EXCLUSIVE)
+                .initial_layout(vk::ImageLayout::UNDEFINED);
+
+            unsafe { logical_device.create_image(&create_info, None) }?
+        };
+
+        let image_mem_requirements = unsafe { logical_device.get_image_memory_requirements(image) };
+
+        let allocation = allocator.allocate(
+            logical_device,
+            image_mem_requirements,
+            vk::MemoryPropertyFlags::DEVICE_LOCAL,
+            false,
+        )?;
+
+        unsafe { logical_device.bind_image_memory(image, allocation.memory, allocation.offset) }?;
+
+        let image_view = {
+            let subresource_range = vk::ImageSubresourceRange::builder()
+                .aspect_mask(vk::ImageAspectFlags::COLOR)
+                .level_count(1)
+                .layer_count(1)
+                .build();
+
+            let create_info = vk::ImageViewCreateInfo::builder()
+                .image(image)
+                .view_type(vk::ImageViewType::TYPE_2D)
+                .format(format)
+                .subresource_range(subresource_range);
+
+            unsafe { logical_device.create_image_view(&create_info, None) }?
+        };
+
+        let frame_buffer = FrameBuffer::new(
+            logical_device,
+            &vec![image_view],
+            render_pass,
+            extent.width,
+            extent.height,
+            None,
+            None,
+        )?
+        .buffers[0];
+
+        Ok(Self {
+            image,
+            allocation,
+            image_view,
+            frame_buffer,
+        })
+    }
+
+    fn destroy(&self, logical_device: &ash::Device, allocator: &mut MemoryAllocator) {
+        unsafe {
+            logical_device.destroy_framebuffer(self.frame_buffer, None);
+            logical_device.destroy_image_view(self.image_view, None);
+            logical_device.destroy_image(self.image, None);
+        }
+        allocator.free(&self.allocation);
+    }
+}
+
+/// Gives back the `(source, destination)` targets of the effect passes, the last pass has no
+/// destination target as it writes the swapchain image
+fn pass_targets(effect_count: usize) -> Vec<(usize, Option<usize>)> {
+    (0..effect_count)
+        .map(|pass| {
+            let source = pass % PostProcess::TARGET_COUNT;
+            let destination =
+                (pass + 1 < effect_count).then_some((pass + 1) % PostProcess::TARGET_COUNT);
+
+            (source, destination)
+        })
+        .collect()
+}
+
+//==================================================
+//=== Unit Testing
+//==================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pass_targets() {
+        assert!(pass_targets(0).is_empty());
+        assert_eq!(pass_targets(1), vec![(0, None)]);
+
+        // Effects alternate between the targets, the last one presents
+        assert_eq!(pass_targets(3), vec![(0, Some(1)), (1, Some(0)), (0, None)]);
+    }
+}
@@ -0,0 +1,715 @@
+// std
+use std::ops::Range;
+use std::path::Path;
+
+// extern
+extern crate nalgebra_glm as glm;
+use anyhow::{anyhow, Context, Result};
+use ash::vk;
+
+// intern
+use crate::layers::Layers;
+use crate::recording::{camera_index, DrawBackend};
+use crate::resources::{self, ObjectInstance, ObjectPool};
+use crate::shapes;
+use crate::{CameraVP, DrawInstanceData, Mask, MaskRole, SdfShape};
+
+//==================================================
+//=== Raster Image
+//==================================================
+
+/// RGBA8 image drawn by the [`SoftwareBackend`], holding the pixels an sRGB swapchain image
+/// would hold
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RasterImage {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl RasterImage {
+    /// Creates a new [`RasterImage`] cleared to the linear `clear_color`
+    pub fn new(width: u32, height: u32, clear_color: glm::Vec4) -> Self {
+        let clear = [
+            srgb_from_linear(clear_color.x),
+            srgb_from_linear(clear_color.y),
+            srgb_from_linear(clear_color.z),
+            (clear_color.w.clamp(0.0, 1.0) * 255.0).round() as u8,
+        ];
+
+        Self {
+            width,
+            height,
+            pixels: clear.repeat((width * height) as usize),
+        }
+    }
+
+    /// Gives back the RGBA8 pixel at `x`, `y` from the top left corner
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let start = ((y * self.width + x) * 4) as usize;
+        [
+            self.pixels[start],
+            self.pixels[start + 1],
+            self.pixels[start + 2],
+            self.pixels[start + 3],
+        ]
+    }
+
+    /// Writes the linear `color` as an opaque pixel
+    fn set_pixel(&mut self, x: u32, y: u32, color: glm::Vec3) {
+        let start = ((y * self.width + x) * 4) as usize;
+        self.pixels[start..start + 4].copy_from_slice(&[
+            srgb_from_linear(color.x),
+            srgb_from_linear(color.y),
+            srgb_from_linear(color.z),
+            u8::MAX,
+        ]);
+    }
+
+    /// Number of pixels with a channel differing more than `tolerance` from `other`
+    pub fn diff(&self, other: &RasterImage, tolerance: u8) -> Result<usize> {
+        if (self.width, self.height) != (other.width, other.height) {
+            return Err(anyhow!(
+                "Raster Image: {}x{} can't be compared with {}x{}",
+                self.width,
+                self.height,
+                other.width,
+                other.height
+            ));
+        }
+
+        Ok(self
+            .pixels
+            .chunks_exact(4)
+            .zip(other.pixels.chunks_exact(4))
+            .filter(|(a, b)| a.iter().zip(*b).any(|(a, b)| a.abs_diff(*b) > tolerance))
+            .count())
+    }
+
+    /// Loads a .png file as a [`RasterImage`]
+    pub fn load_png(path: &Path) -> Result<Self> {
+        let path = path
+            .to_str()
+            .with_context(|| format!("Raster Image: Path {} is not UTF-8", path.display()))?;
+        let image = resources::load_png(path)?;
+
+        Ok(Self {
+            width: image.width,
+            height: image.height,
+            pixels: image.pixels,
+        })
+    }
+
+    /// Writes the image into a .png file
+    pub fn save_png(&self, path: &Path) -> Result<()> {
+        let file = std::fs::File::create(path)
+            .with_context(|| format!("Raster Image: Can't create {}", path.display()))?;
+
+        let mut encoder = png::Encoder::new(std::io::BufWriter::new(file), self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.pixels)?;
+        writer.finish()?;
+
+        Ok(())
+    }
+}
+
+/// Encodes a linear color channel like an sRGB attachment
+fn srgb_from_linear(value: f32) -> u8 {
+    let value = value.clamp(0.0, 1.0);
+    let srgb = if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    };
+
+    (srgb * 255.0).round() as u8
+}
+
+//==================================================
+//=== Software Backend
+//==================================================
+
+/// Subpixel precision of the rasterizer, like the 8 bits of most GPUs
+const SUBPIXELS: i64 = 256;
+const MAX_SUBPIXELS: i64 = 1 << 28;
+
+/// Viewport region drawn by the [`SoftwareBackend`]
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RasterRegion {
+    pub viewport: vk::Viewport,
+    pub scissor: vk::Rect2D,
+    /// World, resting and pixel cameras, see [`camera_index`]
+    pub cameras: [CameraVP; 3],
+}
+
+/// Vertex after the vertex shader of the default pipeline
+#[derive(Debug, Clone, Copy)]
+struct RasterVertex {
+    /// Framebuffer position on the subpixel grid
+    position: [i64; 2],
+    color: glm::Vec3,
+    gradient_color: glm::Vec3,
+    /// Object space position, the gradients are placed by it
+    local: glm::Vec2,
+}
+
+/// CPU reference of the default pipeline, rasterizes the sorted draw pool of a frame into a
+/// [`RasterImage`] so tests can check the drawn pixels without a GPU
+///
+/// The result only depends on the input: vertices are snapped to a subpixel grid, pixel centers
+/// are sampled with a top-left fill rule, back faces are culled like by the pipelines and the
+/// attributes are interpolated linearly, which is exact for the orthographic cameras. Textures
+/// and custom pipelines are drawn with the flat shading of the default pipeline, masks are
+/// tested like by the stencil attachment.
+/// SDF shapes fill the pixels with their center inside the edge, without anti-aliasing
+pub(crate) struct SoftwareBackend<'a> {
+    /// Sorted draw pool, see `sort_into_batches`
+    pub draw_pool: &'a [ObjectInstance],
+    pub object_pool: &'a ObjectPool,
+    /// Geometry streamed for the frame
+    pub stream_pool: &'a ObjectPool,
+    pub layers: &'a Layers,
+    pub image: RasterImage,
+    /// Stencil value of each pixel of the `image`, cleared to zero
+    pub stencil: Vec<u8>,
+}
+
+impl DrawBackend for SoftwareBackend<'_> {
+    type Region = RasterRegion;
+
+    fn draw_region(&mut self, region: &RasterRegion, draw_batches: &[Range<usize>]) -> Result<()> {
+        for draw_batch in draw_batches {
+            for draw_instance in &self.draw_pool[draw_batch.clone()] {
+                self.draw_instance(region, draw_instance)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl SoftwareBackend<'_> {
+    /// Runs the vertex shader on the triangles of `draw_instance` and rasterizes them
+    fn draw_instance(
+        &mut self,
+        region: &RasterRegion,
+        draw_instance: &ObjectInstance,
+    ) -> Result<()> {
+        let pool = match draw_instance.streamed {
+            true => self.stream_pool,
+            false => self.object_pool,
+        };
+        let object_data = pool
+            .pool
+            .get(draw_instance.object.0)
+            .context("Software Backend: Object index out of bounds")?;
+
+        let data = DrawInstanceData::new(draw_instance, object_data);
+        let camera = &region.cameras[camera_index(self.layers, draw_instance)];
+        let mvp = camera.projection * camera.view * glm::Mat4::from(data.transform);
+        let scissor = match draw_instance.clip {
+            Some(clip) => clip.scissor(region.scissor, self.image.height),
+            None => region.scissor,
+        };
+
+        let model_color = glm::Vec3::from(data.color);
+        let gradient_color = glm::vec3(
+            data.gradient_color[0],
+            data.gradient_color[1],
+            data.gradient_color[2],
+        );
+        let gradient = (
+            glm::Vec4::from(data.gradient_points),
+            data.gradient_color[3],
+        );
+
+        let sdf = draw_instance
+            .sdf
+            .map(|shape| (shape, draw_instance.sdf_half_size()));
+
+        let indices = pool
+            .indices
+            .get(object_data.index_offset..object_data.index_offset + object_data.index_count)
+            .context("Software Backend: Index range out of bounds")?;
+
+        for triangle in indices.chunks_exact(3) {
+            let mut vertices = [RasterVertex {
+                position: [0; 2],
+                color: glm::Vec3::zeros(),
+                gradient_color: glm::Vec3::zeros(),
+                local: glm::Vec2::zeros(),
+            }; 3];
+
+            for (raster_vertex, index) in vertices.iter_mut().zip(triangle) {
+                let vertex = pool
+                    .vertices
+                    .get(*index as usize)
+                    .context("Software Backend: Vertex index out of bounds")?;
+                let position = glm::Vec3::from(vertex.position);
+                let vertex_color = glm::Vec3::from(vertex.color);
+
+                let clip = mvp * glm::vec4(position.x, position.y, position.z, 1.0);
+                let ndc = clip.xy() / clip.w;
+
+                *raster_vertex = RasterVertex {
+                    position: [
+                        snap(region.viewport.x + (ndc.x + 1.0) * 0.5 * region.viewport.width),
+                        snap(region.viewport.y + (ndc.y + 1.0) * 0.5 * region.viewport.height),
+                    ],
+                    color: vertex_color.component_mul(&model_color),
+                    gradient_color: vertex_color.component_mul(&gradient_color),
+                    local: position.xy(),
+                };
+            }
+
+            self.rasterize(scissor, draw_instance.mask, vertices, gradient, sdf);
+        }
+
+        Ok(())
+    }
+
+    /// Fills the pixels of a front facing triangle inside the `scissor` and the edge of the `sdf`
+    /// shape of the given half size, the shapes of a `mask` only write its id into the stencil
+    /// values
+    fn rasterize(
+        &mut self,
+        scissor: vk::Rect2D,
+        mask: Option<Mask>,
+        mut vertices: [RasterVertex; 3],
+        (gradient_points, gradient_mode): (glm::Vec4, f32),
+        sdf: Option<(SdfShape, glm::Vec2)>,
+    ) {
+        // Counter-clockwise on the screen is front facing, the y axis points down
+        let area = edge(
+            vertices[0].position,
+            vertices[1].position,
+            vertices[2].position,
+        );
+        if area >= 0 {
+            return;
+        }
+        vertices.swap(1, 2);
+        let area = -area as f32;
+
+        let [a, b, c] = vertices.map(|vertex| vertex.position);
+
+        // Pixels touched by the triangle, inside the scissor and the image
+        let pixel_range = |min: i64, max: i64, offset: i32, extent: u32, size: u32| {
+            let start = (min / SUBPIXELS).max(offset.max(0) as i64);
+            let end = (max / SUBPIXELS + 1)
+                .min(offset.max(0) as i64 + extent as i64)
+                .min(size as i64);
+            start.max(0) as u32..end.max(0) as u32
+        };
+        let columns = pixel_range(
+            a[0].min(b[0]).min(c[0]),
+            a[0].max(b[0]).max(c[0]),
+            scissor.offset.x,
+            scissor.extent.width,
+            self.image.width,
+        );
+        let rows = pixel_range(
+            a[1].min(b[1]).min(c[1]),
+            a[1].max(b[1]).max(c[1]),
+            scissor.offset.y,
+            scissor.extent.height,
+            self.image.height,
+        );
+
+        let edges = [(b, c), (c, a), (a, b)];
+
+        for y in rows {
+            for x in columns.clone() {
+                let center = [
+                    x as i64 * SUBPIXELS + SUBPIXELS / 2,
+                    y as i64 * SUBPIXELS + SUBPIXELS / 2,
+                ];
+
+                let weights = edges.map(|(from, to)| edge(from, to, center));
+                let inside = weights.iter().zip(edges).all(|(weight, (from, to))| {
+                    *weight > 0 || (*weight == 0 && is_top_left(from, to))
+                });
+                if !inside {
+                    continue;
+                }
+
+                let weights = weights.map(|weight| weight as f32 / area);
+                let interpolate = |attribute: fn(&RasterVertex) -> glm::Vec3| {
+                    attribute(&vertices[0]) * weights[0]
+                        + attribute(&vertices[1]) * weights[1]
+                        + attribute(&vertices[2]) * weights[2]
+                };
+                let local = interpolate(|vertex| glm::vec3(vertex.local.x, vertex.local.y, 0.0));
+
+                // SDF: Rectangle object spans [-0.1, 0.1] -> [-half size, half size]
+                if let Some((shape, half_size)) = sdf {
+                    let position =
+                        (local.xy() / (0.5 * shapes::RECTANGLE_SIZE)).component_mul(&half_size);
+                    if shape.distance(position, half_size) > 0.0 {
+                        continue;
+                    }
+                }
+
+                let stencil = &mut self.stencil[(y * self.image.width + x) as usize];
+                match mask {
+                    Some(Mask {
+                        id,
+                        role: MaskRole::Shape,
+                    }) => {
+                        *stencil = id;
+                        continue;
+                    }
+                    Some(Mask {
+                        id,
+                        role: MaskRole::Clipped,
+                    }) if *stencil != id => continue,
+                    _ => {}
+                }
+
+                let color = interpolate(|vertex| vertex.color);
+                let gradient_color = interpolate(|vertex| vertex.gradient_color);
+
+                let color = shade_gradient(
+                    color,
+                    gradient_color,
+                    local.xy(),
+                    gradient_points,
+                    gradient_mode,
+                );
+                self.image.set_pixel(x, y, color);
+            }
+        }
+    }
+}
+
+/// Snaps a framebuffer coordinate in pixels to the subpixel grid
+fn snap(value: f32) -> i64 {
+    // Far outside coordinates are clamped, the edge functions can't overflow
+    ((value * SUBPIXELS as f32).round() as i64).clamp(-MAX_SUBPIXELS, MAX_SUBPIXELS)
+}
+
+/// Twice the signed area of the triangle `from`, `to`, `point`, exact on the subpixel grid
+fn edge(from: [i64; 2], to: [i64; 2], point: [i64; 2]) -> i64 {
+    (to[0] - from[0]) * (point[1] - from[1]) - (to[1] - from[1]) * (point[0] - from[0])
+}
+
+/// Pixels exactly on an edge are filled by only one of the two triangles sharing it
+fn is_top_left(from: [i64; 2], to: [i64; 2]) -> bool {
+    let delta = [to[0] - from[0], to[1] - from[1]];
+    delta[1] < 0 || (delta[1] == 0 && delta[0] > 0)
+}
+
+/// Gradient of the fragment shader: 0 -> Flat, 1 -> Linear, 2 -> Radial
+fn shade_gradient(
+    color: glm::Vec3,
+    gradient_color: glm::Vec3,
+    position: glm::Vec2,
+    points: glm::Vec4,
+    mode: f32,
+) -> glm::Vec3 {
+    let start = points.xy();
+    let delta = glm::vec2(points.z, points.w) - start;
+
+    let t = if mode > 1.5 {
+        (position - start).norm() / delta.norm().max(0.0001)
+    } else if mode > 0.5 {
+        (position - start).dot(&delta) / delta.dot(&delta).max(0.0001)
+    } else {
+        return color;
+    };
+
+    glm::lerp(&color, &gradient_color, t.clamp(0.0, 1.0))
+}
+
+//==================================================
+//=== Unit Testing
+//==================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::ResourceConfig;
+    use crate::{sort_into_batches, ClipRect, Gradient, ProjectionType};
+
+    const SIZE: u32 = 32;
+
+    /// Draws the `draw_pool` with the default objects and an orthographic camera
+    fn render(draw_pool: &mut [ObjectInstance], object_pool: &ObjectPool) -> RasterImage {
+        let draw_batches = sort_into_batches(draw_pool);
+        let camera = CameraVP::new(
+            &glm::vec3(0.0, 0.0, 2.0),
+            &ProjectionType::Orthographic,
+            1.0,
+        );
+        let region = RasterRegion {
+            viewport: vk::Viewport::builder()
+                .width(SIZE as f32)
+                .height(SIZE as f32)
+                .max_depth(1.0)
+                .build(),
+            scissor: vk::Rect2D::builder()
+                .extent(vk::Extent2D {
+                    width: SIZE,
+                    height: SIZE,
+                })
+                .build(),
+            cameras: [
+                camera,
+                camera.at_rest(),
+                CameraVP::pixels(SIZE as f32, SIZE as f32),
+            ],
+        };
+
+        let stream_pool = ObjectPool::default();
+        let layers = Layers::default();
+        let mut backend = SoftwareBackend {
+            draw_pool,
+            object_pool,
+            stream_pool: &stream_pool,
+            layers: &layers,
+            image: RasterImage::new(SIZE, SIZE, glm::vec4(0.0, 0.0, 0.0, 1.0)),
+            stencil: vec![0; (SIZE * SIZE) as usize],
+        };
+        backend.draw_region(&region, &draw_batches).unwrap();
+
+        backend.image
+    }
+
+    fn instance(
+        object_pool: &ObjectPool,
+        name: &str,
+        scale: f32,
+        color: glm::Vec3,
+    ) -> ObjectInstance {
+        ObjectInstance {
+            scale: glm::vec3(scale, scale, 0.0),
+            color,
+            object: object_pool.lookup(name).unwrap(),
+            ..ObjectInstance::default()
+        }
+    }
+
+    #[test]
+    fn test_software_backend() {
+        let object_pool = resources::preload(&ResourceConfig::default()).unwrap();
+        let red = glm::vec3(1.0, 0.0, 0.0);
+        let blue = glm::vec3(0.0, 0.0, 1.0);
+
+        // Pushed first, but drawn over the red rectangle by its layer
+        let mut draw_pool = vec![
+            ObjectInstance {
+                layer: 1,
+                ..instance(&object_pool, "rectangle", 2.5, blue)
+            },
+            instance(&object_pool, "rectangle", 5.0, red),
+        ];
+        let image = render(&mut draw_pool, &object_pool);
+
+        // Rectangle of 0.2 x 0.2 scaled by 5 -> Half of the view
+        let red_pixels = image
+            .pixels
+            .chunks_exact(4)
+            .filter(|pixel| *pixel == [255, 0, 0, 255])
+            .count();
+        let blue_pixels = image
+            .pixels
+            .chunks_exact(4)
+            .filter(|pixel| *pixel == [0, 0, 255, 255])
+            .count();
+        assert_eq!(red_pixels + blue_pixels, 16 * 16);
+        assert_eq!(blue_pixels, 8 * 8);
+        assert_eq!(image.pixel(16, 16), [0, 0, 255, 255]);
+        assert_eq!(image.pixel(9, 9), [255, 0, 0, 255]);
+        assert_eq!(image.pixel(7, 7), [0, 0, 0, 255]);
+
+        // Same input -> Same pixels
+        assert_eq!(render(&mut draw_pool, &object_pool), image);
+    }
+
+    #[test]
+    fn test_submission_order() {
+        let object_pool = resources::preload(&ResourceConfig::default()).unwrap();
+        let red = glm::vec3(1.0, 0.0, 0.0);
+        let green = glm::vec3(0.0, 1.0, 0.0);
+
+        // Same layer -> The later instance is drawn over the earlier one, whatever its object
+        let mut draw_pool = vec![
+            instance(&object_pool, "circle", 4.0, red),
+            instance(&object_pool, "rectangle", 2.0, green),
+        ];
+        let image = render(&mut draw_pool, &object_pool);
+        assert_eq!(image.pixel(16, 16), [0, 255, 0, 255]);
+
+        let mut draw_pool = vec![
+            instance(&object_pool, "rectangle", 2.0, green),
+            instance(&object_pool, "circle", 4.0, red),
+        ];
+        let image = render(&mut draw_pool, &object_pool);
+        assert_eq!(image.pixel(16, 16), [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_clipping() {
+        let object_pool = resources::preload(&ResourceConfig::default()).unwrap();
+        let red = glm::vec3(1.0, 0.0, 0.0);
+
+        // Bottom 12 rows of the window -> Lower 4 rows of the rectangle
+        let mut draw_pool = vec![
+            ObjectInstance {
+                clip: Some(ClipRect::new(0, 0, SIZE, 12)),
+                ..instance(&object_pool, "rectangle", 5.0, red)
+            },
+            ObjectInstance {
+                position: glm::vec3(0.0, 0.5, 0.0),
+                clip: Some(ClipRect::new(0, 0, SIZE, 12)),
+                ..instance(&object_pool, "circle", 1.0, red)
+            },
+        ];
+        let image = render(&mut draw_pool, &object_pool);
+
+        let red_rows: Vec<u32> = (0..SIZE)
+            .filter(|y| image.pixel(16, *y) == [255, 0, 0, 255])
+            .collect();
+        assert_eq!(red_rows, (20..24).collect::<Vec<_>>());
+        assert_eq!(
+            image
+                .diff(
+                    &RasterImage::new(SIZE, SIZE, glm::vec4(0.0, 0.0, 0.0, 1.0)),
+                    0
+                )
+                .unwrap(),
+            16 * 4
+        );
+    }
+
+    #[test]
+    fn test_masking() {
+        let object_pool = resources::preload(&ResourceConfig::default()).unwrap();
+        let red = glm::vec3(1.0, 0.0, 0.0);
+        let green = glm::vec3(0.0, 1.0, 0.0);
+        let shape = |id| {
+            Some(Mask {
+                id,
+                role: MaskRole::Shape,
+            })
+        };
+        let clipped = |id| {
+            Some(Mask {
+                id,
+                role: MaskRole::Clipped,
+            })
+        };
+
+        // Clipped instances pushed before the shapes on a lower layer are still masked, the
+        // second mask replaces the first one where they overlap
+        let mut draw_pool = vec![
+            ObjectInstance {
+                mask: clipped(1),
+                ..instance(&object_pool, "rectangle", 5.0, red)
+            },
+            ObjectInstance {
+                mask: clipped(2),
+                layer: -1,
+                ..instance(&object_pool, "rectangle", 5.0, green)
+            },
+            ObjectInstance {
+                layer: 1,
+                position: glm::vec3(-0.25, 0.0, 0.0),
+                mask: shape(1),
+                ..instance(&object_pool, "rectangle", 2.5, glm::vec3(1.0, 1.0, 1.0))
+            },
+            ObjectInstance {
+                layer: 1,
+                mask: shape(2),
+                ..instance(&object_pool, "rectangle", 2.5, glm::vec3(1.0, 1.0, 1.0))
+            },
+        ];
+        let image = render(&mut draw_pool, &object_pool);
+
+        // Shapes of 8 x 8 pixels overlapping by 4 columns, no white pixel of the shapes
+        let red_columns: Vec<u32> = (0..SIZE)
+            .filter(|x| image.pixel(*x, 16) == [255, 0, 0, 255])
+            .collect();
+        let green_columns: Vec<u32> = (0..SIZE)
+            .filter(|x| image.pixel(*x, 16) == [0, 255, 0, 255])
+            .collect();
+        assert_eq!(red_columns, (8..12).collect::<Vec<_>>());
+        assert_eq!(green_columns, (12..20).collect::<Vec<_>>());
+        assert_eq!(image.pixel(16, 16), [0, 255, 0, 255]);
+        assert_eq!(image.pixel(4, 16), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_sdf_shapes() {
+        let object_pool = resources::preload(&ResourceConfig::default()).unwrap();
+        let red = glm::vec3(1.0, 0.0, 0.0);
+
+        // Rectangle covering the view rounded into a circle of radius 1.0
+        let circle = ObjectInstance {
+            sdf: Some(SdfShape {
+                corner_radius: 1.0,
+                border: 0.0,
+            }),
+            ..instance(&object_pool, "rectangle", 10.0, red)
+        };
+        let image = render(&mut [circle.clone()], &object_pool);
+        assert_eq!(image.pixel(16, 16), [255, 0, 0, 255]);
+        assert_eq!(image.pixel(16, 0), [255, 0, 0, 255]);
+        assert_eq!(image.pixel(1, 1), [0, 0, 0, 255]);
+        assert_eq!(image.pixel(30, 30), [0, 0, 0, 255]);
+
+        // Border -> Only the ring inside the edge
+        let ring = ObjectInstance {
+            sdf: Some(SdfShape {
+                corner_radius: 1.0,
+                border: 0.25,
+            }),
+            ..circle
+        };
+        let image = render(&mut [ring], &object_pool);
+        assert_eq!(image.pixel(16, 2), [255, 0, 0, 255]);
+        assert_eq!(image.pixel(16, 16), [0, 0, 0, 255]);
+        assert_eq!(image.pixel(1, 1), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_golden_image() {
+        let object_pool = resources::preload(&ResourceConfig::default()).unwrap();
+
+        let mut draw_pool = vec![
+            ObjectInstance {
+                gradient: Some(Gradient::Linear {
+                    color: glm::vec3(0.0, 0.0, 1.0),
+                    direction: glm::vec2(1.0, 0.0),
+                }),
+                ..instance(&object_pool, "circle", 4.0, glm::vec3(1.0, 1.0, 0.0))
+            },
+            ObjectInstance {
+                position: glm::vec3(0.5, -0.5, 0.0),
+                rotation: 30.0,
+                layer: 1,
+                ..instance(&object_pool, "rectangle", 2.0, glm::vec3(0.0, 1.0, 0.0))
+            },
+        ];
+        let image = render(&mut draw_pool, &object_pool);
+
+        // The golden image is only written when the env var is set
+        let golden_path = Path::new("res/golden/shapes.png");
+        if std::env::var_os("LAVAPOND_UPDATE_GOLDEN").is_some() {
+            std::fs::create_dir_all(golden_path.parent().unwrap()).unwrap();
+            image.save_png(golden_path).unwrap();
+        }
+        assert!(
+            golden_path.exists(),
+            "Missing {}, regenerate it with LAVAPOND_UPDATE_GOLDEN=1 cargo test test_golden_image",
+            golden_path.display()
+        );
+
+        let golden = RasterImage::load_png(golden_path).unwrap();
+        assert_eq!(image.diff(&golden, 1).unwrap(), 0);
+    }
+}
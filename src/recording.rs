@@ -0,0 +1,711 @@
+// std
+use std::ops::Range;
+use std::sync::mpsc;
+use std::thread::JoinHandle;
+
+// extern
+use anyhow::{Context, Result};
+use ash::vk;
+
+// intern
+use crate::layers::Layers;
+use crate::pipeline::{GraphicsPipeline, PipelineHandle};
+use crate::resources::{ObjectData, ObjectInstance};
+use crate::{ClipRect, Space, TextureHandle};
+
+//==================================================
+//=== Draw Backend
+//==================================================
+
+/// Destination of the sorted draw pool of a frame, the Vulkan command buffers or the
+/// [`crate::raster::SoftwareBackend`] of the tests
+pub(crate) trait DrawBackend {
+    /// Viewport, scissor and cameras of a viewport region
+    type Region;
+
+    /// Draws the `draw_batches` of the draw pool into `region`, in order
+    fn draw_region(&mut self, region: &Self::Region, draw_batches: &[Range<usize>]) -> Result<()>;
+}
+
+/// Index of the camera drawing `draw_instance`, the world camera, the camera at rest of
+/// screen space layers or the camera of the window pixels of [`Space::Screen`]
+pub(crate) fn camera_index(layers: &Layers, draw_instance: &ObjectInstance) -> usize {
+    match draw_instance.space {
+        Space::Screen => 2,
+        Space::World if layers.is_screen_space(draw_instance.layer) => 1,
+        Space::World => 0,
+    }
+}
+
+//==================================================
+//=== Draw Context
+//==================================================
+
+/// Everything read by the draw commands of a frame, shared by the recording threads
+pub(crate) struct DrawContext<'a> {
+    pub device: &'a ash::Device,
+    pub draw_pool: &'a [ObjectInstance],
+    pub objects: &'a [ObjectData],
+    pub stream_objects: &'a [ObjectData],
+    pub layers: &'a Layers,
+    pub pipeline_layout: vk::PipelineLayout,
+    pub sprite_pipeline_layout: vk::PipelineLayout,
+    pub graphics_pipeline: vk::Pipeline,
+    pub sprite_pipeline: vk::Pipeline,
+    /// Draws the shapes of the masks into the stencil attachment
+    pub mask_pipeline: vk::Pipeline,
+    /// Draws the instances with an [`crate::SdfShape`]
+    pub sdf_pipeline: vk::Pipeline,
+    /// Draws every instance except the shapes of the masks when set
+    pub wireframe_pipeline: Option<vk::Pipeline>,
+    pub custom_pipelines: &'a [GraphicsPipeline],
+    pub texture_sets: &'a [vk::DescriptorSet],
+    pub descriptor_set: vk::DescriptorSet,
+    pub vertex_buffer: vk::Buffer,
+    pub index_buffer: vk::Buffer,
+    pub index_type: vk::IndexType,
+    /// Streamed vertices, followed by their indices at `stream_index_offset`
+    pub stream_buffer: vk::Buffer,
+    pub stream_index_offset: u64,
+    /// Height of the drawn image, the clip rectangles are placed from its bottom
+    pub framebuffer_height: u32,
+}
+
+/// Viewport region of the frame as recorded, its cameras are at the dynamic offsets of the
+/// uniform buffer
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RegionCommands {
+    pub viewport: vk::Viewport,
+    pub scissor: vk::Rect2D,
+    /// Offset of the world, resting and pixel cameras of the region, see `camera_index`
+    pub camera_offsets: [u32; 3],
+}
+
+impl DrawContext<'_> {
+    /// Binds the default pipeline, the buffers and the world camera of `region`, secondary
+    /// command buffers don't inherit any of them
+    pub fn begin_region(&self, command_buffer: vk::CommandBuffer, region: &RegionCommands) {
+        unsafe {
+            self.device.cmd_bind_pipeline(
+                command_buffer,
+                vk::PipelineBindPoint::GRAPHICS,
+                self.graphics_pipeline,
+            );
+
+            self.device
+                .cmd_bind_vertex_buffers(command_buffer, 0, &[self.vertex_buffer], &[0]);
+
+            self.device.cmd_bind_index_buffer(
+                command_buffer,
+                self.index_buffer,
+                0,
+                self.index_type,
+            );
+
+            self.device
+                .cmd_set_viewport(command_buffer, 0, std::slice::from_ref(&region.viewport));
+
+            self.device
+                .cmd_set_scissor(command_buffer, 0, std::slice::from_ref(&region.scissor));
+
+            // Unmasked: A zero compare mask passes the stencil test everywhere
+            self.device.cmd_set_stencil_compare_mask(
+                command_buffer,
+                vk::StencilFaceFlags::FRONT_AND_BACK,
+                0,
+            );
+            self.device.cmd_set_stencil_reference(
+                command_buffer,
+                vk::StencilFaceFlags::FRONT_AND_BACK,
+                0,
+            );
+
+            self.device.cmd_bind_descriptor_sets(
+                command_buffer,
+                vk::PipelineBindPoint::GRAPHICS,
+                self.pipeline_layout,
+                0,
+                std::slice::from_ref(&self.descriptor_set),
+                &region.camera_offsets[..1],
+            );
+        }
+    }
+
+    /// For each batch of `draw_batches` in the `draw_pool`
+    /// * Binds the pipeline, texture and buffers of the batch when they change
+    /// * Binds the camera of `descriptor_set` at the world, resting or pixel offset of
+    ///   `region` when it changes, see `camera_index`
+    /// * Sets the scissor of `region` inside the clip rectangle of the batch when it changes
+    /// * Sets the stencil reference and compare mask of the mask of the batch when it changes
+    /// * Adds an instanced indexed draw command, the shaders read the instance data from the
+    ///   instance buffer with `gl_InstanceIndex`
+    ///
+    /// Expects the state of [`DrawContext::begin_region`]
+    pub fn record_batches(
+        &self,
+        command_buffer: vk::CommandBuffer,
+        draw_batches: &[Range<usize>],
+        region: &RegionCommands,
+    ) -> Result<()> {
+        let mut bound_state: Option<(Option<PipelineHandle>, Option<TextureHandle>, bool, bool)> =
+            None;
+        // Object pool buffers, camera, scissor and stencil state of `begin_region`
+        let mut bound_stream = false;
+        let mut bound_camera = 0;
+        let mut bound_clip: Option<ClipRect> = None;
+        let mut bound_mask: Option<u8> = None;
+
+        for draw_batch in draw_batches {
+            let draw_instance = &self.draw_pool[draw_batch.start];
+
+            // Screen Space -> Camera at rest or window pixels
+            let camera_index = camera_index(self.layers, draw_instance);
+            if bound_camera != camera_index {
+                unsafe {
+                    self.device.cmd_bind_descriptor_sets(
+                        command_buffer,
+                        vk::PipelineBindPoint::GRAPHICS,
+                        self.pipeline_layout,
+                        0,
+                        std::slice::from_ref(&self.descriptor_set),
+                        &region.camera_offsets[camera_index..=camera_index],
+                    )
+                };
+
+                // Texture sets are bound again after the camera changed
+                bound_state = None;
+                bound_camera = camera_index;
+            }
+
+            // Clipped -> Scissor of the region inside the clip rectangle
+            if bound_clip != draw_instance.clip {
+                let scissor = match draw_instance.clip {
+                    Some(clip) => clip.scissor(region.scissor, self.framebuffer_height),
+                    None => region.scissor,
+                };
+
+                unsafe {
+                    self.device
+                        .cmd_set_scissor(command_buffer, 0, std::slice::from_ref(&scissor))
+                };
+
+                bound_clip = draw_instance.clip;
+            }
+
+            // Masked -> Shapes write the id of the mask, clipped instances test for it
+            let mask = draw_instance.mask.map(|mask| mask.id);
+            if bound_mask != mask {
+                unsafe {
+                    self.device.cmd_set_stencil_compare_mask(
+                        command_buffer,
+                        vk::StencilFaceFlags::FRONT_AND_BACK,
+                        if mask.is_some() { u32::MAX } else { 0 },
+                    );
+                    self.device.cmd_set_stencil_reference(
+                        command_buffer,
+                        vk::StencilFaceFlags::FRONT_AND_BACK,
+                        mask.unwrap_or(0) as u32,
+                    );
+                }
+
+                bound_mask = mask;
+            }
+
+            // Switch pipelines and textures only when they change
+            let state = (
+                draw_instance.pipeline,
+                draw_instance.texture,
+                draw_instance.is_mask_shape(),
+                draw_instance.sdf.is_some(),
+            );
+            if bound_state != Some(state) {
+                let pipeline = match (state, self.wireframe_pipeline) {
+                    ((_, _, true, _), _) => self.mask_pipeline,
+                    // Wireframe: Every other instance is drawn by the same pipeline
+                    (_, Some(wireframe_pipeline)) => wireframe_pipeline,
+                    ((Some(pipeline), _, _, _), None) => {
+                        self.custom_pipelines
+                            .get(pipeline.0)
+                            .context("Custom Pipelines: Index out of bounds")?
+                            .pipeline
+                    }
+                    ((None, Some(_), _, _), None) => self.sprite_pipeline,
+                    ((None, None, _, true), None) => self.sdf_pipeline,
+                    ((None, None, _, false), None) => self.graphics_pipeline,
+                };
+
+                unsafe {
+                    self.device.cmd_bind_pipeline(
+                        command_buffer,
+                        vk::PipelineBindPoint::GRAPHICS,
+                        pipeline,
+                    );
+
+                    // Custom pipelines share the set layouts of the sprite pipeline
+                    if let Some(texture) = draw_instance.texture {
+                        self.device.cmd_bind_descriptor_sets(
+                            command_buffer,
+                            vk::PipelineBindPoint::GRAPHICS,
+                            self.sprite_pipeline_layout,
+                            1,
+                            std::slice::from_ref(
+                                self.texture_sets
+                                    .get(texture.0)
+                                    .context("Texture Sets: Index out of bounds")?,
+                            ),
+                            &[],
+                        );
+                    }
+                }
+
+                bound_state = Some(state);
+            }
+
+            // Switch between the object pool and the streamed geometry buffers
+            if bound_stream != draw_instance.streamed {
+                let (vertex_buffer, index_buffer, index_offset, index_type) =
+                    if draw_instance.streamed {
+                        (
+                            self.stream_buffer,
+                            self.stream_buffer,
+                            self.stream_index_offset,
+                            vk::IndexType::UINT32,
+                        )
+                    } else {
+                        (self.vertex_buffer, self.index_buffer, 0, self.index_type)
+                    };
+
+                unsafe {
+                    self.device
+                        .cmd_bind_vertex_buffers(command_buffer, 0, &[vertex_buffer], &[0]);
+
+                    self.device.cmd_bind_index_buffer(
+                        command_buffer,
+                        index_buffer,
+                        index_offset,
+                        index_type,
+                    );
+                }
+
+                bound_stream = draw_instance.streamed;
+            }
+
+            let object_data = if draw_instance.streamed {
+                &self.stream_objects[draw_instance.object.0]
+            } else {
+                &self.objects[draw_instance.object.0]
+            };
+
+            unsafe {
+                self.device.cmd_draw_indexed(
+                    command_buffer,
+                    object_data.index_count as u32,
+                    draw_batch.len() as u32,
+                    object_data.index_offset as u32,
+                    0,
+                    draw_batch.start as u32,
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Records the draw commands of a frame into a command buffer
+pub(crate) struct CommandRecorder<'a> {
+    pub context: &'a DrawContext<'a>,
+    pub command_buffer: vk::CommandBuffer,
+}
+
+impl DrawBackend for CommandRecorder<'_> {
+    type Region = RegionCommands;
+
+    fn draw_region(
+        &mut self,
+        region: &RegionCommands,
+        draw_batches: &[Range<usize>],
+    ) -> Result<()> {
+        self.context.begin_region(self.command_buffer, region);
+        self.context
+            .record_batches(self.command_buffer, draw_batches, region)
+    }
+}
+
+//==================================================
+//=== Parallel Recorder
+//==================================================
+
+/// Attachments the secondary command buffers draw into
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum SecondaryTarget {
+    /// First subpass of the render pass, drawing into the frame buffer
+    RenderPass {
+        render_pass: vk::RenderPass,
+        frame_buffer: vk::Framebuffer,
+    },
+    /// Color and stencil attachment drawn with dynamic rendering
+    Dynamic {
+        format: vk::Format,
+        stencil_format: vk::Format,
+        samples: vk::SampleCountFlags,
+    },
+}
+
+/// Records the draw batches of a frame on several threads into secondary command buffers,
+/// which are executed in order by the primary command buffer
+///
+/// The threads live as long as the recorder, each owns a command pool for each frame in flight,
+/// reset when the frame comes around again
+pub(crate) struct ParallelRecorder {
+    threads: Vec<RecordingThread>,
+}
+
+/// Long-lived recording thread, waiting for the draw batches of the next frame
+struct RecordingThread {
+    jobs: mpsc::Sender<RecordJob>,
+    handle: JoinHandle<()>,
+    /// Secondary command buffers of the thread, for each frame in flight, one per region
+    buffers: Vec<Vec<vk::CommandBuffer>>,
+}
+
+/// Share of the draw batches of a frame sent to a recording thread
+///
+/// Points into the borrows of [`ParallelRecorder::record`], which waits for the result of
+/// every job before returning
+struct RecordJob {
+    frame: usize,
+    context: *const DrawContext<'static>,
+    target: SecondaryTarget,
+    regions: *const [RegionCommands],
+    draw_batches: *const [Range<usize>],
+    result: mpsc::Sender<Result<()>>,
+}
+
+// The pointed data is shared between the threads like the borrows of a scoped thread
+unsafe impl Send for RecordJob {}
+
+impl ParallelRecorder {
+    /// Fewest draw batches recorded by a thread, smaller frames are recorded by the primary
+    /// command buffer alone
+    pub const MIN_BATCHES_PER_THREAD: usize = 64;
+
+    /// Spawns `thread_count` recording threads, with the command pools and secondary command
+    /// buffers of `frame_count` frames of at most `region_count` viewport regions
+    pub fn new(
+        device: &ash::Device,
+        queue_family_index: u32,
+        thread_count: usize,
+        frame_count: usize,
+        region_count: usize,
+    ) -> Result<Self> {
+        let mut recorder = Self {
+            threads: Vec::new(),
+        };
+
+        for index in 0..thread_count {
+            match RecordingThread::spawn(
+                device,
+                queue_family_index,
+                index,
+                frame_count,
+                region_count,
+            ) {
+                Ok(thread) => recorder.threads.push(thread),
+                Err(error) => {
+                    recorder.destroy();
+                    return Err(error);
+                }
+            }
+        }
+
+        Ok(recorder)
+    }
+
+    pub fn thread_count(&self) -> usize {
+        self.threads.len()
+    }
+
+    /// Whether `batch_count` draw batches are worth splitting among the threads
+    pub fn is_worth_it(&self, batch_count: usize) -> bool {
+        self.thread_count() > 1 && batch_count >= 2 * Self::MIN_BATCHES_PER_THREAD
+    }
+
+    /// Records the `draw_batches` of every region of the frame `frame`, each thread records its
+    /// share of the batches for every region
+    ///
+    /// Gives back the secondary command buffers in the order they have to be executed
+    pub fn record(
+        &self,
+        frame: usize,
+        context: &DrawContext,
+        target: SecondaryTarget,
+        regions: &[RegionCommands],
+        draw_batches: &[Range<usize>],
+    ) -> Result<Vec<vk::CommandBuffer>> {
+        let chunks = split_batches(draw_batches, self.thread_count());
+
+        let region_capacity = self
+            .threads
+            .first()
+            .map_or(0, |thread| thread.buffers[frame].len());
+        if regions.len() > region_capacity {
+            return Err(anyhow::anyhow!(
+                "Recording: {} regions, the secondary command buffers fit {}",
+                regions.len(),
+                region_capacity
+            ));
+        }
+
+        let (result_sender, results) = mpsc::channel();
+        let mut sent = 0;
+        for (thread, chunk) in self.threads.iter().zip(&chunks) {
+            let job = RecordJob {
+                frame,
+                context: (context as *const DrawContext).cast(),
+                target,
+                regions,
+                draw_batches: *chunk,
+                result: result_sender.clone(),
+            };
+
+            // A thread only stops receiving after it panicked
+            if thread.jobs.send(job).is_err() {
+                break;
+            }
+            sent += 1;
+        }
+        drop(result_sender);
+
+        // Every sent job is done before the borrows end, the results only stop early once
+        // every job has been dropped
+        let finished: Vec<Result<()>> = results.iter().take(sent).collect();
+        if finished.len() < chunks.len() {
+            return Err(anyhow::anyhow!("Recording: Thread panicked"));
+        }
+        finished.into_iter().collect::<Result<()>>()?;
+
+        // Regions in order, the batches of a region in order
+        Ok((0..regions.len())
+            .flat_map(|region| {
+                self.threads[..chunks.len()]
+                    .iter()
+                    .map(move |thread| thread.buffers[frame][region])
+            })
+            .collect())
+    }
+
+    /// Stops the threads, which destroy their command pools, freeing their command buffers
+    pub fn destroy(&mut self) {
+        for thread in self.threads.drain(..) {
+            // Closing the channel ends the thread
+            drop(thread.jobs);
+            let _ = thread.handle.join();
+        }
+    }
+}
+
+impl Drop for ParallelRecorder {
+    fn drop(&mut self) {
+        self.destroy();
+    }
+}
+
+impl RecordingThread {
+    /// Creates the command pools of the thread, for `frame_count` frames of at most
+    /// `region_count` viewport regions, and starts waiting for jobs
+    fn spawn(
+        device: &ash::Device,
+        queue_family_index: u32,
+        index: usize,
+        frame_count: usize,
+        region_count: usize,
+    ) -> Result<Self> {
+        let mut pools = Vec::new();
+        let mut buffers = Vec::new();
+        let destroy_pools = |pools: &[vk::CommandPool]| {
+            for pool in pools {
+                unsafe { device.destroy_command_pool(*pool, None) };
+            }
+        };
+
+        for _ in 0..frame_count {
+            let create_info = vk::CommandPoolCreateInfo::builder()
+                .flags(vk::CommandPoolCreateFlags::TRANSIENT)
+                .queue_family_index(queue_family_index);
+
+            let pool = match unsafe { device.create_command_pool(&create_info, None) } {
+                Ok(pool) => pool,
+                Err(error) => {
+                    destroy_pools(&pools);
+                    return Err(error.into());
+                }
+            };
+            pools.push(pool);
+
+            let allocate_info = vk::CommandBufferAllocateInfo::builder()
+                .command_pool(pool)
+                .level(vk::CommandBufferLevel::SECONDARY)
+                .command_buffer_count(region_count as u32);
+
+            match unsafe { device.allocate_command_buffers(&allocate_info) } {
+                Ok(frame_buffers) => buffers.push(frame_buffers),
+                Err(error) => {
+                    destroy_pools(&pools);
+                    return Err(error.into());
+                }
+            }
+        }
+
+        let (jobs, job_receiver) = mpsc::channel::<RecordJob>();
+        let thread_device = device.clone();
+        let thread_pools = pools.clone();
+        let thread_buffers = buffers.clone();
+
+        let handle = std::thread::Builder::new()
+            .name(format!("lavapond-recorder-{index}"))
+            .spawn(move || {
+                for job in job_receiver {
+                    // The borrows of the job outlive it, see `ParallelRecorder::record`
+                    let (context, regions, draw_batches) =
+                        unsafe { (&*job.context, &*job.regions, &*job.draw_batches) };
+
+                    let result = record_chunk(
+                        context,
+                        job.target,
+                        thread_pools[job.frame],
+                        &thread_buffers[job.frame],
+                        regions,
+                        draw_batches,
+                    );
+                    let _ = job.result.send(result);
+                }
+
+                for pool in thread_pools {
+                    unsafe { thread_device.destroy_command_pool(pool, None) };
+                }
+            });
+
+        match handle {
+            Ok(handle) => Ok(Self {
+                jobs,
+                handle,
+                buffers,
+            }),
+            Err(error) => {
+                destroy_pools(&pools);
+                Err(error).context("Recording: Can't spawn a recording thread")
+            }
+        }
+    }
+}
+
+/// Splits `draw_batches` into at most `thread_count` runs of consecutive batches, each with at
+/// least [`ParallelRecorder::MIN_BATCHES_PER_THREAD`] batches but the last
+fn split_batches(draw_batches: &[Range<usize>], thread_count: usize) -> Vec<&[Range<usize>]> {
+    let chunk_size = draw_batches
+        .len()
+        .div_ceil(thread_count.max(1))
+        .max(ParallelRecorder::MIN_BATCHES_PER_THREAD);
+
+    draw_batches.chunks(chunk_size).collect()
+}
+
+/// Records the `draw_batches` of every region into the secondary `buffers` of a thread
+fn record_chunk(
+    context: &DrawContext,
+    target: SecondaryTarget,
+    pool: vk::CommandPool,
+    buffers: &[vk::CommandBuffer],
+    regions: &[RegionCommands],
+    draw_batches: &[Range<usize>],
+) -> Result<()> {
+    // The frame of the pool is done, see the in flight fences
+    unsafe {
+        context
+            .device
+            .reset_command_pool(pool, vk::CommandPoolResetFlags::empty())
+    }?;
+
+    let color_formats;
+    let mut rendering_info = vk::CommandBufferInheritanceRenderingInfo::builder();
+    let mut inheritance_info = vk::CommandBufferInheritanceInfo::builder();
+    match target {
+        SecondaryTarget::RenderPass {
+            render_pass,
+            frame_buffer,
+        } => {
+            inheritance_info = inheritance_info
+                .render_pass(render_pass)
+                .subpass(0)
+                .framebuffer(frame_buffer);
+        }
+        SecondaryTarget::Dynamic {
+            format,
+            stencil_format,
+            samples,
+        } => {
+            color_formats = [format];
+            rendering_info = rendering_info
+                .color_attachment_formats(&color_formats)
+                .stencil_attachment_format(stencil_format)
+                .rasterization_samples(samples);
+            inheritance_info = inheritance_info.push_next(&mut rendering_info);
+        }
+    }
+
+    let begin_info = vk::CommandBufferBeginInfo::builder()
+        .flags(
+            vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT
+                | vk::CommandBufferUsageFlags::RENDER_PASS_CONTINUE,
+        )
+        .inheritance_info(&inheritance_info);
+
+    for (region, command_buffer) in regions.iter().zip(buffers) {
+        unsafe {
+            context
+                .device
+                .begin_command_buffer(*command_buffer, &begin_info)
+        }?;
+
+        CommandRecorder {
+            context,
+            command_buffer: *command_buffer,
+        }
+        .draw_region(region, draw_batches)?;
+
+        unsafe { context.device.end_command_buffer(*command_buffer) }?;
+    }
+
+    Ok(())
+}
+
+//==================================================
+//=== Unit Testing
+//==================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_batches() {
+        let draw_batches: Vec<Range<usize>> = (0..1000).map(|i| i..i + 1).collect();
+
+        // Consecutive runs keep the draw order when executed one after the other
+        let chunks = split_batches(&draw_batches, 3);
+        assert_eq!(
+            chunks.iter().map(|chunk| chunk.len()).collect::<Vec<_>>(),
+            [334, 334, 332]
+        );
+        assert_eq!(chunks.concat(), draw_batches);
+
+        // Small frames use fewer threads
+        let chunks = split_batches(&draw_batches[..100], 8);
+        assert_eq!(
+            chunks.iter().map(|chunk| chunk.len()).collect::<Vec<_>>(),
+            [64, 36]
+        );
+        assert!(split_batches(&[], 4).is_empty());
+    }
+}
@@ -0,0 +1,339 @@
+// std
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::JoinHandle;
+
+// extern
+
+// intern
+use crate::{
+    AnchorType, ClipRect, ObjectHandle, Renderer, RendererError, RendererResult, TextParams,
+    TextureHandle,
+};
+
+//==================================================
+//=== Draw Command
+//==================================================
+
+/// Drawing call recorded on the application thread and replayed on the renderer thread,
+/// see [`Renderer::draw_command`] for the matching functions
+#[derive(Debug, Clone)]
+pub enum DrawCommand {
+    Circle {
+        scale: f32,
+        center_x: f32,
+        center_y: f32,
+        color: glm::Vec3,
+        anchor_type: AnchorType,
+    },
+    Rectangle {
+        scale_x: f32,
+        scale_y: f32,
+        rotation: f32,
+        center_x: f32,
+        center_y: f32,
+        color: glm::Vec3,
+        anchor_type: AnchorType,
+    },
+    Line {
+        from: glm::Vec2,
+        to: glm::Vec2,
+        thickness: f32,
+        color: glm::Vec3,
+        anchor_type: AnchorType,
+    },
+    Polyline {
+        points: Vec<glm::Vec2>,
+        thickness: f32,
+        color: glm::Vec3,
+        anchor_type: AnchorType,
+    },
+    Object {
+        object: ObjectHandle,
+        scale_x: f32,
+        scale_y: f32,
+        rotation: f32,
+        center_x: f32,
+        center_y: f32,
+        color: glm::Vec3,
+        anchor_type: AnchorType,
+    },
+    Sprite {
+        texture: TextureHandle,
+        scale_x: f32,
+        scale_y: f32,
+        rotation: f32,
+        center_x: f32,
+        center_y: f32,
+        anchor_type: AnchorType,
+    },
+    Text {
+        text: String,
+        x: f32,
+        y: f32,
+        params: TextParams,
+        anchor_type: AnchorType,
+    },
+    /// Following commands are drawn on the layer called so
+    UseLayer(String),
+    /// Following commands are clipped to the rectangle, see [`Renderer::push_clip`]
+    PushClip(ClipRect),
+    PopClip,
+    /// Following commands are the shapes of a new mask, see [`Renderer::begin_mask`]
+    BeginMask,
+    /// Following commands are drawn inside the shapes of the mask
+    EndMask,
+    ClearMask,
+}
+
+//==================================================
+//=== Render Handle
+//==================================================
+
+/// Work run on the renderer thread before the next frame
+type RenderTask = Box<dyn FnOnce(&mut Renderer) -> RendererResult<()> + Send>;
+
+/// Frames and requests handed over between the application and the renderer thread
+#[derive(Default)]
+struct Mailbox {
+    /// Latest submitted frame, replaced when the renderer thread is behind
+    frame: Option<Vec<DrawCommand>>,
+    /// Drawn frame given back to the application to be filled again
+    spare: Option<Vec<DrawCommand>>,
+    tasks: Vec<RenderTask>,
+    /// Width and height of the window in pixels
+    resize: Option<(u32, u32)>,
+    /// Errors of the renderer thread, given back by the next submit
+    errors: Vec<RendererError>,
+    closed: bool,
+}
+
+impl Mailbox {
+    /// Hands over `frame` and gives back the empty buffer of the next one
+    ///
+    /// A frame not yet taken by the renderer thread is dropped and its buffer reused
+    fn submit(&mut self, frame: Vec<DrawCommand>) -> Vec<DrawCommand> {
+        let mut next = match self.frame.replace(frame) {
+            Some(dropped) => dropped,
+            None => self.spare.take().unwrap_or_default(),
+        };
+        next.clear();
+
+        next
+    }
+
+    fn has_work(&self) -> bool {
+        self.frame.is_some() || !self.tasks.is_empty() || self.resize.is_some()
+    }
+}
+
+#[derive(Default)]
+struct Shared {
+    mailbox: Mutex<Mailbox>,
+    wake: Condvar,
+}
+
+impl Shared {
+    fn mailbox(&self) -> std::sync::MutexGuard<'_, Mailbox> {
+        // A panic of the other thread leaves the mailbox consistent
+        self.mailbox
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Application side of a [`Renderer`] running on its own thread, created by
+/// [`Renderer::spawn_thread`]
+///
+/// Draw commands are collected with [`RenderHandle::shape`] and handed over as a frame with
+/// [`RenderHandle::submit`], which never waits for the GPU. While the renderer thread draws
+/// a frame the next one is filled, a frame submitted before the previous was taken replaces it
+pub struct RenderHandle {
+    commands: Vec<DrawCommand>,
+    shared: Arc<Shared>,
+    thread: Option<JoinHandle<Renderer>>,
+}
+
+impl RenderHandle {
+    pub(crate) fn spawn(mut renderer: Renderer) -> Self {
+        let shared = Arc::new(Shared::default());
+        let thread_shared = Arc::clone(&shared);
+
+        let thread = std::thread::Builder::new()
+            .name("lavapond-renderer".to_string())
+            .spawn(move || {
+                render_loop(&mut renderer, &thread_shared);
+                renderer
+            })
+            .expect("Renderer: Can't spawn the renderer thread");
+
+        Self {
+            commands: Vec::new(),
+            shared,
+            thread: Some(thread),
+        }
+    }
+
+    /// Adds `command` to the frame being filled
+    pub fn shape(&mut self, command: DrawCommand) -> &mut Self {
+        self.commands.push(command);
+        self
+    }
+
+    /// Commands of the frame being filled
+    pub fn commands(&self) -> &[DrawCommand] {
+        &self.commands
+    }
+
+    /// Hands over the filled frame to the renderer thread and starts a new one
+    ///
+    /// Fails with the first error the renderer thread ran into since the last submit
+    pub fn submit(&mut self) -> RendererResult<()> {
+        let mut mailbox = self.shared.mailbox();
+
+        let frame = std::mem::take(&mut self.commands);
+        self.commands = mailbox.submit(frame);
+        let mut errors = std::mem::take(&mut mailbox.errors);
+
+        drop(mailbox);
+        self.shared.wake.notify_one();
+
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(errors.swap_remove(0)),
+        }
+    }
+
+    /// Sets the surface extent to `width` x `height` before the next frame, call it on the
+    /// resize events of the window
+    pub fn resize(&self, width: u32, height: u32) {
+        self.shared.mailbox().resize = Some((width, height));
+        self.shared.wake.notify_one();
+    }
+
+    /// Runs `task` on the renderer thread before the next frame, eg. to load resources
+    ///
+    /// Its error is given back by the next submit
+    pub fn run(&self, task: impl FnOnce(&mut Renderer) -> RendererResult<()> + Send + 'static) {
+        self.shared.mailbox().tasks.push(Box::new(task));
+        self.shared.wake.notify_one();
+    }
+
+    /// Stops the renderer thread after its current frame and gives back the [`Renderer`]
+    pub fn stop(mut self) -> Renderer {
+        match self.join() {
+            Some(Ok(renderer)) => renderer,
+            Some(Err(panic)) => std::panic::resume_unwind(panic),
+            None => unreachable!("Renderer: The thread is only joined once"),
+        }
+    }
+
+    fn join(&mut self) -> Option<std::thread::Result<Renderer>> {
+        let thread = self.thread.take()?;
+
+        self.shared.mailbox().closed = true;
+        self.shared.wake.notify_one();
+
+        Some(thread.join())
+    }
+}
+
+impl Drop for RenderHandle {
+    fn drop(&mut self) {
+        // The renderer is destroyed after its thread stopped
+        let _ = self.join();
+    }
+}
+
+/// Draws the submitted frames until the [`RenderHandle`] is stopped
+fn render_loop(renderer: &mut Renderer, shared: &Shared) {
+    loop {
+        let (frame, tasks, resize) = {
+            let mut mailbox = shared.mailbox();
+            while !mailbox.has_work() && !mailbox.closed {
+                mailbox = shared
+                    .wake
+                    .wait(mailbox)
+                    .unwrap_or_else(|poisoned| poisoned.into_inner());
+            }
+
+            if mailbox.closed {
+                return;
+            }
+
+            (
+                mailbox.frame.take(),
+                std::mem::take(&mut mailbox.tasks),
+                mailbox.resize.take(),
+            )
+        };
+
+        let mut errors = Vec::new();
+
+        if let Some((width, height)) = resize {
+            errors.extend(renderer.set_surface_extent(width, height).err());
+        }
+
+        for task in tasks {
+            errors.extend(task(renderer).err());
+        }
+
+        let frame = frame.map(|mut frame| {
+            let drawn = frame
+                .iter()
+                .try_for_each(|command| renderer.draw_command(command))
+                .and_then(|_| renderer.draw_request());
+            errors.extend(drawn.err());
+
+            frame.clear();
+            frame
+        });
+
+        let mut mailbox = shared.mailbox();
+        mailbox.errors.extend(errors);
+        if frame.is_some() {
+            mailbox.spare = frame;
+        }
+    }
+}
+
+//==================================================
+//=== Unit Testing
+//==================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn circle(scale: f32) -> DrawCommand {
+        DrawCommand::Circle {
+            scale,
+            center_x: 0.0,
+            center_y: 0.0,
+            color: glm::vec3(1.0, 1.0, 1.0),
+            anchor_type: AnchorType::Locked,
+        }
+    }
+
+    #[test]
+    fn test_mailbox() {
+        let mut mailbox = Mailbox::default();
+        assert!(!mailbox.has_work());
+
+        let next = mailbox.submit(vec![circle(1.0)]);
+        assert!(next.is_empty());
+        assert!(mailbox.has_work());
+
+        // Frame not taken yet -> Replaced, its buffer is reused
+        let next = mailbox.submit(vec![circle(2.0), circle(3.0)]);
+        assert!(next.is_empty() && next.capacity() >= 1);
+
+        let frame = mailbox.frame.take().unwrap();
+        assert!(matches!(frame[..], [DrawCommand::Circle { scale, .. }, _] if scale == 2.0));
+        assert!(!mailbox.has_work());
+
+        // Drawn frame -> Given back as the next buffer
+        mailbox.spare = Some(Vec::with_capacity(8));
+        let next = mailbox.submit(frame);
+        assert!(next.is_empty() && next.capacity() >= 8);
+    }
+}
@@ -1,11 +1,17 @@
 #![allow(dead_code)]
 
 // std
-use std::io::BufRead;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 // extern
 extern crate nalgebra_glm as glm;
-use anyhow::{Ok, Result};
+use anyhow::{Context, Ok, Result};
+
+// intern
+use crate::pipeline::PipelineHandle;
+use crate::spatial::SpatialGrid;
+use crate::{ClipRect, Gradient, Mask, MaskRole, Pivot, SdfShape, Space, WorldRect};
 
 //==================================================
 //=== Object
@@ -15,20 +21,735 @@ const COLOR_WHITE: [f32; 3] = [1.0, 1.0, 1.0];
 const COLOR_GRAY: [f32; 3] = [0.5, 0.5, 0.5];
 const COLOR_BLACK: [f32; 3] = [0.0, 0.0, 0.0];
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct ObjectPool {
-    pub indices: Vec<u16>,
+    pub indices: Vec<u32>,
     pub vertices: Vec<Vertex>,
     pub pool: Vec<ObjectData>,
+    pub atlases: Vec<Atlas>,
+}
+
+/// Index of an object of the renderer, see [`crate::Renderer::register_object`] and
+/// [`crate::Renderer::find_object`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ObjectHandle(pub(crate) usize);
+
+/// Problem of an object found by [`crate::Renderer::validate_objects`], usually a broken .obj file
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum PoolWarning {
+    /// Index range of the object reaches past the indices of the pool
+    #[error("Object {object}: Indices {start}..{end} out of bounds for {index_count} indices")]
+    RangeOutOfBounds {
+        object: String,
+        start: usize,
+        end: usize,
+        index_count: usize,
+    },
+    /// Index count of the object is not a multiple of 3
+    #[error("Object {object}: Index count {index_count} is not a multiple of 3")]
+    IncompleteTriangle { object: String, index_count: usize },
+    /// Index of the object points past the vertices of the pool
+    #[error("Object {object}: Index {index} out of bounds for {vertex_count} vertices")]
+    IndexOutOfBounds {
+        object: String,
+        index: u32,
+        vertex_count: usize,
+    },
+    /// Triangle of the object has no area, it repeats a vertex or its vertices are on a line
+    #[error("Object {object}: Triangle {triangle} has no area")]
+    DegenerateTriangle { object: String, triangle: usize },
+    /// Character of the text objects maps to an object which is not a glyph
+    #[error("Character {character:?}: Object {object_index} is not a glyph")]
+    MissingGlyph {
+        character: char,
+        object_index: usize,
+    },
+}
+
+impl ObjectPool {
+    /// Gives back the texture of the atlas of `region` and the UV rectangle of the region
+    pub fn region_texture(&self, region: AtlasRegionId) -> Result<(TextureHandle, glm::Vec4)> {
+        let atlas = self
+            .atlases
+            .get(region.atlas)
+            .context("Atlas: Index out of bounds")?;
+
+        let uv_rect = atlas
+            .uv_rect(region.region)
+            .context("Atlas: Region index out of bounds")?;
+
+        Ok((atlas.texture, uv_rect))
+    }
+
+    /// Checks the pool for broken objects and gives back every problem found
+    ///
+    /// Index ranges and indices are checked against the pool, the triangles for their area and
+    /// the text characters for their glyph objects, an empty list means the pool is sound
+    pub fn validate(&self) -> Vec<PoolWarning> {
+        let mut warnings = Vec::new();
+
+        for object_data in &self.pool {
+            let object = || object_data.name.clone();
+            let (start, end) = (
+                object_data.index_offset,
+                object_data.index_offset + object_data.index_count,
+            );
+
+            let Some(indices) = self.indices.get(start..end) else {
+                warnings.push(PoolWarning::RangeOutOfBounds {
+                    object: object(),
+                    start,
+                    end,
+                    index_count: self.indices.len(),
+                });
+                continue;
+            };
+
+            if indices.len() % 3 != 0 {
+                warnings.push(PoolWarning::IncompleteTriangle {
+                    object: object(),
+                    index_count: indices.len(),
+                });
+            }
+
+            if let Some(&index) = indices
+                .iter()
+                .find(|&&index| index as usize >= self.vertices.len())
+            {
+                warnings.push(PoolWarning::IndexOutOfBounds {
+                    object: object(),
+                    index,
+                    vertex_count: self.vertices.len(),
+                });
+                continue;
+            }
+
+            for (triangle, corners) in indices.chunks_exact(3).enumerate() {
+                let [a, b, c] = [corners[0], corners[1], corners[2]]
+                    .map(|index| glm::Vec3::from(self.vertices[index as usize].position));
+
+                if (b - a).cross(&(c - a)).norm() <= f32::EPSILON * f32::EPSILON {
+                    warnings.push(PoolWarning::DegenerateTriangle {
+                        object: object(),
+                        triangle,
+                    });
+                }
+            }
+        }
+
+        for (character, &object_index) in CHAR_OBJECT_POOL.iter().enumerate() {
+            // No object: Unused, new line and space
+            if matches!(object_index, 253..=255) {
+                continue;
+            }
+
+            let object_index = object_index as usize;
+            let is_glyph = self
+                .pool
+                .get(object_index)
+                .is_some_and(|object_data| !SHAPE_OBJECTS.contains(&object_data.name.as_str()));
+            if !is_glyph {
+                warnings.push(PoolWarning::MissingGlyph {
+                    character: character as u8 as char,
+                    object_index,
+                });
+            }
+        }
+
+        warnings
+    }
+
+    /// Removes every object from the pool
+    pub fn clear(&mut self) {
+        self.indices.clear();
+        self.vertices.clear();
+        self.pool.clear();
+        self.atlases.clear();
+    }
+
+    /// Appends a new object to the pool from its own `vertices` and `indices`
+    ///
+    /// `indices` are local to `vertices`, the offset into the pool is added here
+    pub fn push_object(
+        &mut self,
+        name: &str,
+        vertices: &[Vertex],
+        indices: &[u32],
+    ) -> Result<ObjectHandle> {
+        if indices.len() % 3 != 0 {
+            return Err(anyhow::anyhow!(
+                "Object {}: Index count must be a multiple of 3",
+                name
+            ));
+        }
+
+        if let Some(index) = indices.iter().find(|&&i| i as usize >= vertices.len()) {
+            return Err(anyhow::anyhow!(
+                "Object {}: Index {} out of bounds for {} vertices",
+                name,
+                index,
+                vertices.len()
+            ));
+        }
+
+        let vertex_offset = self.vertices.len();
+        if vertex_offset + vertices.len() > u32::MAX as usize {
+            return Err(anyhow::anyhow!(
+                "Object {}: Object pool can not hold more than {} vertices",
+                name,
+                u32::MAX
+            ));
+        }
+
+        self.pool.push(ObjectData {
+            name: name.to_string(),
+            index_count: indices.len(),
+            index_offset: self.indices.len(),
+            ..ObjectData::default()
+        });
+        self.vertices.extend_from_slice(vertices);
+        self.indices
+            .extend(indices.iter().map(|i| i + vertex_offset as u32));
+
+        let last = self.pool.len() - 1;
+        update_bounds(&mut self.pool[last..], &self.vertices, &self.indices);
+
+        Ok(ObjectHandle(self.pool.len() - 1))
+    }
+
+    /// Gives back the first object named `name`
+    ///
+    /// Objects are named by their .obj `o` line up to the first `_`, the default shapes by
+    /// [`SHAPE_OBJECTS`] and the glyphs of the default font by their character
+    pub fn get(&self, name: &str) -> Option<ObjectHandle> {
+        self.pool
+            .iter()
+            .position(|object_data| object_data.name == name)
+            .map(ObjectHandle)
+    }
+
+    /// Gives back the first object named `name` like [`ObjectPool::get`], the error lists the
+    /// names of every object
+    pub fn lookup(&self, name: &str) -> Result<ObjectHandle> {
+        self.get(name).with_context(|| {
+            let mut names: Vec<&str> = Vec::new();
+            for object_data in &self.pool {
+                if !names.contains(&object_data.name.as_str()) {
+                    names.push(&object_data.name);
+                }
+            }
+
+            format!(
+                "Object: No object named \"{}\", the objects are: {}",
+                name,
+                names.join(", ")
+            )
+        })
+    }
+
+    /// Gives back the indices as 16 bit while every vertex is addressable by it
+    ///
+    /// Larger pools have to be drawn with 32 bit indices
+    pub fn indices_u16(&self) -> Option<Vec<u16>> {
+        if self.vertices.len() > u16::MAX as usize + 1 {
+            return None;
+        }
+
+        Some(self.indices.iter().map(|&index| index as u16).collect())
+    }
+
+    /// Appends every object of `other` to the pool with their names and vertex colors
+    ///
+    /// Only the vertices used by an object are copied
+    pub fn append(&mut self, other: &ObjectPool) -> Result<Vec<ObjectHandle>> {
+        let mut handles = Vec::new();
+
+        for (index, object_data) in other.pool.iter().enumerate() {
+            let (vertices, indices) = other.object_geometry(index)?;
+            handles.push(self.push_object(&object_data.name, &vertices, &indices)?);
+        }
+
+        Ok(handles)
+    }
+
+    /// Replaces the objects of `handles` with the objects of `other` in order, every other
+    /// object keeps its handle and geometry
+    ///
+    /// Extra objects of `other` are appended with new handles, the objects of `handles` missing
+    /// from `other` are left empty. Gives back the handles of every object of `other`
+    pub fn replace(
+        &mut self,
+        handles: &[ObjectHandle],
+        other: &ObjectPool,
+    ) -> Result<Vec<ObjectHandle>> {
+        if let Some(handle) = handles.iter().find(|handle| handle.0 >= self.pool.len()) {
+            return Err(anyhow::anyhow!(
+                "Object: Handle {} out of bounds for {} objects",
+                handle.0,
+                self.pool.len()
+            ));
+        }
+
+        let replacements: HashMap<usize, usize> = handles
+            .iter()
+            .enumerate()
+            .map(|(i, handle)| (handle.0, i))
+            .collect();
+
+        let mut object_pool = ObjectPool {
+            atlases: std::mem::take(&mut self.atlases),
+            ..ObjectPool::default()
+        };
+
+        for (index, object_data) in self.pool.iter().enumerate() {
+            match replacements.get(&index) {
+                Some(&other_index) if other_index < other.pool.len() => {
+                    let (vertices, indices) = other.object_geometry(other_index)?;
+                    object_pool.push_object(&other.pool[other_index].name, &vertices, &indices)?;
+                }
+                Some(_) => {
+                    object_pool.push_object(&object_data.name, &[], &[])?;
+                }
+                None => {
+                    let (vertices, indices) = self.object_geometry(index)?;
+                    object_pool.push_object(&object_data.name, &vertices, &indices)?;
+                }
+            }
+        }
+
+        let mut replaced: Vec<ObjectHandle> =
+            handles.iter().take(other.pool.len()).copied().collect();
+        for (index, object_data) in other.pool.iter().enumerate().skip(handles.len()) {
+            let (vertices, indices) = other.object_geometry(index)?;
+            replaced.push(object_pool.push_object(&object_data.name, &vertices, &indices)?);
+        }
+
+        *self = object_pool;
+
+        Ok(replaced)
+    }
+
+    /// Gives back the vertices used by the object at `index` and its indices local to them
+    fn object_geometry(&self, index: usize) -> Result<(Vec<Vertex>, Vec<u32>)> {
+        let object_data = &self.pool[index];
+        let object_indices = &self.indices
+            [object_data.index_offset..object_data.index_offset + object_data.index_count];
+
+        let mut local_indices = HashMap::new();
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+
+        for &index in object_indices {
+            let local_index = match local_indices.get(&index) {
+                Some(&local_index) => local_index,
+                None => {
+                    let vertex = self.vertices.get(index as usize).with_context(|| {
+                        format!(
+                            "Object {}: Index {} out of bounds for {} vertices",
+                            object_data.name,
+                            index,
+                            self.vertices.len()
+                        )
+                    })?;
+
+                    vertices.push(*vertex);
+                    local_indices.insert(index, (vertices.len() - 1) as u32);
+                    (vertices.len() - 1) as u32
+                }
+            };
+
+            indices.push(local_index);
+        }
+
+        Ok((vertices, indices))
+    }
 }
 
 #[derive(Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ObjectInstance {
     pub position: glm::Vec3,
     pub rotation: f32,
+    /// Shear angles in degrees applied before the rotation, `x` slants the vertical edges and
+    /// `y` the horizontal ones
+    pub skew: glm::Vec2,
     pub scale: glm::Vec3,
     pub color: glm::Vec3,
-    pub object_index: usize,
+    /// Object drawn by the instance, checked against the pool when the frame is submitted
+    pub object: ObjectHandle,
+    pub texture: Option<TextureHandle>,
+    pub uv_rect: Option<glm::Vec4>,
+    pub layer: i32,
+    pub pipeline: Option<PipelineHandle>,
+    pub gradient: Option<Gradient>,
+    /// `object` points into the geometry streamed for the current frame
+    pub streamed: bool,
+    pub space: Space,
+    /// Window pixels the instance is clipped to, see [`crate::Renderer::push_clip`]
+    pub clip: Option<ClipRect>,
+    /// Stencil mask the instance is drawn into or clipped by, see
+    /// [`crate::Renderer::begin_mask`]
+    pub mask: Option<Mask>,
+    /// Point of the object placed at `position`, see [`crate::DrawParams::pivot`]
+    pub pivot: Pivot,
+    /// Shape evaluated on the rectangle object by the SDF pipeline, see
+    /// [`crate::DrawParams::sdf`]
+    pub sdf: Option<SdfShape>,
+    /// Number of the application, see [`crate::DrawParams::tag`]
+    pub tag: Option<u32>,
+}
+
+/// Draw state of an instance, see [`ObjectInstance::batch_key`]
+type BatchKey = (
+    u16,
+    i32,
+    Space,
+    Option<Mask>,
+    Option<ClipRect>,
+    Option<usize>,
+    Option<usize>,
+    bool,
+    bool,
+    usize,
+);
+
+impl ObjectInstance {
+    /// Creates a new [`ObjectInstance`] placed by the 2D affine `matrix`, see
+    /// [`ObjectInstance::set_matrix`]
+    pub fn from_matrix(matrix: &glm::Mat3) -> Self {
+        let mut instance = ObjectInstance {
+            scale: glm::vec3(1.0, 1.0, 1.0),
+            ..ObjectInstance::default()
+        };
+        instance.set_matrix(matrix);
+
+        instance
+    }
+
+    /// Places the pivot of the object by the 2D affine `matrix`, eg. for squash and stretch
+    ///
+    /// The matrix is decomposed into position, rotation, a horizontal skew and scale, its last
+    /// row is ignored. Matrices without area lose their skew
+    pub fn set_matrix(&mut self, matrix: &glm::Mat3) {
+        let rotation = matrix.m21.atan2(matrix.m11);
+        let linear =
+            glm::rotation2d(-rotation).fixed_view::<2, 2>(0, 0) * matrix.fixed_view::<2, 2>(0, 0);
+
+        // Upper triangular -> Scale on the diagonal, skew above it
+        let scale = glm::vec2(linear.m11, linear.m22);
+        let skew_x = if scale.y.abs() > f32::EPSILON {
+            (linear.m12 / scale.y).atan()
+        } else {
+            0.0
+        };
+
+        self.position = glm::vec3(matrix.m13, matrix.m23, self.position.z);
+        self.rotation = rotation.to_degrees();
+        self.skew = glm::vec2(skew_x.to_degrees(), 0.0);
+        self.scale = glm::vec3(scale.x, scale.y, self.scale.z);
+    }
+
+    /// Gives back the 2D affine matrix placing the pivot of the object
+    pub fn matrix(&self) -> glm::Mat3 {
+        let linear = self.linear();
+
+        glm::mat3(
+            linear.m11,
+            linear.m12,
+            self.position.x,
+            linear.m21,
+            linear.m22,
+            self.position.y,
+            0.0,
+            0.0,
+            1.0,
+        )
+    }
+
+    /// Rotation, skew and scale of the instance in the xy plane
+    pub(crate) fn linear(&self) -> glm::Mat2 {
+        let (sin, cos) = self.rotation.to_radians().sin_cos();
+        let rotation = glm::mat2(cos, -sin, sin, cos);
+        let skew = glm::mat2(
+            1.0,
+            self.skew.x.to_radians().tan(),
+            self.skew.y.to_radians().tan(),
+            1.0,
+        );
+
+        rotation * skew * glm::diagonal2x2(&self.scale.xy())
+    }
+
+    /// Sort key of the instance, instances with the same key keep their submission order
+    ///
+    /// Shapes of masks come before every layer in the order of their masks, so they are in
+    /// the stencil attachment before the instances they clip
+    pub(crate) fn draw_order(&self) -> (u16, i32) {
+        let mask_order = match self.mask {
+            Some(Mask {
+                id,
+                role: MaskRole::Shape,
+            }) => id as u16,
+            _ => u16::MAX,
+        };
+
+        (mask_order, self.layer)
+    }
+
+    /// Consecutive instances with the same key are drawn by a single instanced draw call
+    pub(crate) fn batch_key(&self) -> BatchKey {
+        let (mask_order, layer) = self.draw_order();
+
+        (
+            mask_order,
+            layer,
+            self.space,
+            self.mask,
+            self.clip,
+            self.pipeline.map(|pipeline| pipeline.0),
+            self.texture.map(|texture| texture.0),
+            self.sdf.is_some(),
+            self.streamed,
+            self.object.0,
+        )
+    }
+
+    /// Half size of the [`SdfShape`] of the instance in world units, drawn on the rectangle
+    /// object
+    pub(crate) fn sdf_half_size(&self) -> glm::Vec2 {
+        self.scale.xy().abs() * (crate::shapes::RECTANGLE_SIZE * 0.5)
+    }
+
+    /// The instance is only drawn into the stencil attachment of its mask
+    pub(crate) fn is_mask_shape(&self) -> bool {
+        self.mask.is_some_and(|mask| mask.role == MaskRole::Shape)
+    }
+
+    /// Gives back the world space bounding box of the instance drawing `object_data`
+    pub(crate) fn world_bounds(&self, object_data: &ObjectData) -> WorldRect {
+        let pivot = self.pivot.point(object_data);
+
+        placed_bounds(
+            self.position.xy(),
+            &self.linear(),
+            object_data.bounds_min - pivot,
+            object_data.bounds_max - pivot,
+        )
+    }
+}
+
+/// Index of a draw instance in the order it was pushed into the draw pool of its frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DrawInstanceId(pub usize);
+
+/// World space placement and bounds of a draw instance, kept for picking after the draw
+/// pool and the streamed objects are cleared
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PickTarget {
+    pub id: DrawInstanceId,
+    pub layer: i32,
+    pub position: glm::Vec2,
+    /// Rotation, skew and scale, see [`ObjectInstance::linear`]
+    pub linear: glm::Mat2,
+    pub bounds_min: glm::Vec2,
+    pub bounds_max: glm::Vec2,
+    pub pick_shape: PickShape,
+    pub space: Space,
+    /// Placed on a screen space layer
+    pub screen_space: bool,
+    pub tag: Option<u32>,
+}
+
+impl PickTarget {
+    pub fn new(
+        id: DrawInstanceId,
+        draw_instance: &ObjectInstance,
+        object_data: &ObjectData,
+    ) -> Self {
+        // Bounds relative to the pivot, placed at the position
+        let pivot = draw_instance.pivot.point(object_data);
+
+        Self {
+            id,
+            layer: draw_instance.layer,
+            position: draw_instance.position.xy(),
+            linear: draw_instance.linear(),
+            bounds_min: object_data.bounds_min - pivot,
+            bounds_max: object_data.bounds_max - pivot,
+            pick_shape: object_data.pick_shape,
+            space: draw_instance.space,
+            screen_space: false,
+            tag: draw_instance.tag,
+        }
+    }
+
+    pub fn world_bounds(&self) -> WorldRect {
+        placed_bounds(
+            self.position,
+            &self.linear,
+            self.bounds_min,
+            self.bounds_max,
+        )
+    }
+
+    /// Checks whether the [`PickShape`] of the instance contains the `world_position`
+    pub fn contains(&self, world_position: glm::Vec2) -> bool {
+        let Some(inverse) = self.linear.try_inverse() else {
+            return false;
+        };
+
+        let local = inverse * (world_position - self.position);
+
+        local.x >= self.bounds_min.x
+            && local.x <= self.bounds_max.x
+            && local.y >= self.bounds_min.y
+            && local.y <= self.bounds_max.y
+            && self
+                .pick_shape
+                .contains(self.bounds_min, self.bounds_max, local)
+    }
+}
+
+/// Pick targets of a frame, the world space ones sorted into a [`SpatialGrid`]
+pub(crate) struct PickIndex {
+    grid: SpatialGrid<usize>,
+    /// Targets placed without the world camera, tested by every pick
+    screen: Vec<usize>,
+}
+
+impl PickIndex {
+    pub fn new(pick_targets: &[PickTarget]) -> Self {
+        let (world, screen): (Vec<usize>, Vec<usize>) =
+            (0..pick_targets.len()).partition(|&index| {
+                pick_targets[index].space == Space::World && !pick_targets[index].screen_space
+            });
+
+        // Cells of the average target size
+        let bounds: Vec<WorldRect> = world
+            .iter()
+            .map(|&index| pick_targets[index].world_bounds())
+            .collect();
+        let average_size = bounds
+            .iter()
+            .map(|bounds| (bounds.max - bounds.min).max())
+            .sum::<f32>()
+            / bounds.len().max(1) as f32;
+
+        let mut grid = SpatialGrid::new(average_size);
+        for (index, bounds) in world.into_iter().zip(bounds) {
+            grid.insert(index, bounds);
+        }
+
+        Self { grid, screen }
+    }
+
+    /// Indices of the targets which may contain `world_position`
+    pub fn candidates(&self, world_position: glm::Vec2) -> impl Iterator<Item = usize> + '_ {
+        self.grid
+            .query_point(world_position)
+            .into_iter()
+            .chain(self.screen.iter().copied())
+    }
+
+    /// Indices of the world space targets intersecting `region`
+    pub fn query(&self, region: &WorldRect) -> Vec<usize> {
+        self.grid.query(region)
+    }
+}
+
+/// Gives back the world space bounding box of `bounds_min` and `bounds_max` transformed by
+/// `linear` and moved to `position`
+fn placed_bounds(
+    position: glm::Vec2,
+    linear: &glm::Mat2,
+    bounds_min: glm::Vec2,
+    bounds_max: glm::Vec2,
+) -> WorldRect {
+    let corners = [
+        bounds_min,
+        glm::vec2(bounds_max.x, bounds_min.y),
+        bounds_max,
+        glm::vec2(bounds_min.x, bounds_max.y),
+    ]
+    .map(|corner| position + linear * corner);
+
+    WorldRect::around(&corners)
+}
+
+/// Handle of instances kept by the renderer across frames
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PersistentId(pub(crate) usize);
+
+/// Retained draw instances, appended to the draw pool of every frame until removed
+///
+/// Slots of removed instances are not reused, so a stale [`PersistentId`] never points to
+/// other instances
+#[derive(Clone, Default)]
+pub(crate) struct PersistentPool {
+    slots: Vec<Option<Vec<ObjectInstance>>>,
+}
+
+impl PersistentPool {
+    pub fn add(&mut self, instances: Vec<ObjectInstance>) -> PersistentId {
+        self.slots.push(Some(instances));
+        PersistentId(self.slots.len() - 1)
+    }
+
+    pub fn get_mut(&mut self, id: PersistentId) -> Result<&mut Vec<ObjectInstance>> {
+        self.slots
+            .get_mut(id.0)
+            .and_then(Option::as_mut)
+            .with_context(|| format!("Persistent: Unknown instance {:?}", id))
+    }
+
+    pub fn remove(&mut self, id: PersistentId) -> Result<Vec<ObjectInstance>> {
+        self.slots
+            .get_mut(id.0)
+            .and_then(Option::take)
+            .with_context(|| format!("Persistent: Unknown instance {:?}", id))
+    }
+
+    /// Every kept instance in the order they were added
+    pub fn instances(&self) -> impl Iterator<Item = &ObjectInstance> {
+        self.slots.iter().flatten().flatten()
+    }
+
+    pub fn instances_mut(&mut self) -> impl Iterator<Item = &mut ObjectInstance> {
+        self.slots.iter_mut().flatten().flatten()
+    }
+}
+
+/// Area of an object hit by picking, see [`crate::Renderer::pick`]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum PickShape {
+    /// The whole bounding box
+    #[default]
+    Bounds,
+    /// Ellipse filling the bounding box
+    Ellipse,
+    /// Border of the ellipse filling the bounding box, of the given thickness
+    EllipseBorder(f32),
+}
+
+impl PickShape {
+    /// Checks whether the `point` inside the bounding box of `min` and `max` hits the shape
+    pub fn contains(&self, min: glm::Vec2, max: glm::Vec2, point: glm::Vec2) -> bool {
+        let center = (min + max) * 0.5;
+        let inside = |half_size: glm::Vec2| {
+            let normalized = (point - center).component_div(&half_size);
+            half_size.x > 0.0 && half_size.y > 0.0 && normalized.norm_squared() <= 1.0
+        };
+
+        match *self {
+            PickShape::Bounds => true,
+            PickShape::Ellipse => inside((max - min) * 0.5),
+            PickShape::EllipseBorder(thickness) => {
+                let half_size = (max - min) * 0.5;
+                inside(half_size) && !inside(half_size.add_scalar(-thickness))
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -36,6 +757,47 @@ pub struct ObjectData {
     pub name: String,
     pub index_count: usize,
     pub index_offset: usize,
+    pub bounds_min: glm::Vec2,
+    pub bounds_max: glm::Vec2,
+    pub pick_shape: PickShape,
+}
+
+impl ObjectData {
+    /// Width of the object's bounding box
+    pub fn width(&self) -> f32 {
+        self.bounds_max.x - self.bounds_min.x
+    }
+
+    /// Height of the object's bounding box
+    pub fn height(&self) -> f32 {
+        self.bounds_max.y - self.bounds_min.y
+    }
+}
+
+/// Calculates the bounding box of every object in the `pool` based on its indexed vertices
+fn update_bounds(pool: &mut [ObjectData], vertices: &[Vertex], indices: &[u32]) {
+    for object_data in pool {
+        let object_indices =
+            &indices[object_data.index_offset..object_data.index_offset + object_data.index_count];
+
+        let mut bounds_min = glm::vec2(f32::MAX, f32::MAX);
+        let mut bounds_max = glm::vec2(f32::MIN, f32::MIN);
+
+        for &index in object_indices {
+            let position = vertices[index as usize].position;
+            bounds_min = glm::min2(&bounds_min, &glm::vec2(position[0], position[1]));
+            bounds_max = glm::max2(&bounds_max, &glm::vec2(position[0], position[1]));
+        }
+
+        // Objects without any faces have no extent
+        if object_indices.is_empty() {
+            bounds_min = glm::Vec2::zeros();
+            bounds_max = glm::Vec2::zeros();
+        }
+
+        object_data.bounds_min = bounds_min;
+        object_data.bounds_max = bounds_max;
+    }
 }
 
 #[derive(Debug, Clone, Copy, Default)]
@@ -44,255 +806,397 @@ pub struct Vertex {
     pub color: [f32; 3],
 }
 
-#[derive(Clone, Default)]
-struct VertexColor {
-    pub name: String,
-    pub color: [f32; 3],
+/// Default objects of the [`ObjectPool`], embedded into the binary
+const EMBEDDED_OBJECTS: [(&str, &str); 3] = [
+    ("chars", include_str!("../res/obj/chars.obj")),
+    ("rectangle", include_str!("../res/obj/rectangle.obj")),
+    ("circle", include_str!("../res/obj/circle.obj")),
+];
+
+/// Decides where the default resources are loaded from
+#[derive(Debug, Clone, Default)]
+pub struct ResourceConfig {
+    /// Loads the default objects as `{name}.obj` from `obj_dir`
+    /// instead of the ones embedded into the binary
+    pub obj_dir: Option<PathBuf>,
+    /// Loads the objects of `obj_dir` on a background thread, the embedded objects are drawn
+    /// until they are ready, see [`crate::Renderer::poll_loads`]
+    pub load_in_background: bool,
 }
 
+/// Default objects holding a single shape, named after their file instead of their first
+/// character, see [`ObjectPool::get`]
+pub const SHAPE_OBJECTS: [&str; 2] = ["rectangle", "circle"];
+
 /// Preload Object Pool
-pub fn preload() -> Result<ObjectPool> {
-    load_obj_files(&["chars", "rectangle", "circle"])
+pub fn preload(config: &ResourceConfig) -> Result<ObjectPool> {
+    if let (Some(obj_dir), false) = (&config.obj_dir, config.load_in_background) {
+        return load_obj_files_from(obj_dir, &EMBEDDED_OBJECTS.map(|(name, _)| name));
+    }
+
+    let mut object_pool = ObjectPool::default();
+    for (obj_name, source) in EMBEDDED_OBJECTS {
+        parse_obj(
+            source,
+            &format!("embedded {}.obj", obj_name),
+            None,
+            first_char_name,
+            &mut object_pool,
+        )?;
+        name_shape_objects(&mut object_pool, obj_name);
+    }
+
+    Ok(object_pool)
+}
+
+/// Names the object of the shape file `obj_name` just parsed into `object_pool` after the file
+fn name_shape_objects(object_pool: &mut ObjectPool, obj_name: &str) {
+    if SHAPE_OBJECTS.contains(&obj_name) {
+        if let Some(object_data) = object_pool.pool.last_mut() {
+            object_data.name = obj_name.to_string();
+            if obj_name == "circle" {
+                object_data.pick_shape = PickShape::Ellipse;
+            }
+        }
+    }
+}
+
+/// Paths of the default objects in `obj_dir` with the handles of their objects in the pool
+/// loaded by [`preload`]
+pub fn default_object_files(obj_dir: &Path) -> Result<Vec<(PathBuf, Vec<ObjectHandle>)>> {
+    let mut files = Vec::new();
+    let mut offset = 0;
+
+    for (obj_name, _) in EMBEDDED_OBJECTS {
+        let object_count = load_obj_files_from(obj_dir, &[obj_name])?.pool.len();
+        files.push((
+            obj_dir.join(format!("{}.obj", obj_name)),
+            (offset..offset + object_count).map(ObjectHandle).collect(),
+        ));
+        offset += object_count;
+    }
+
+    Ok(files)
 }
 
 /// Load .obj file without .mtl file
 pub fn load_obj_files(obj_names: &[&str]) -> Result<ObjectPool> {
-    let mut curr_line;
+    load_obj_files_from(Path::new("res/obj"), obj_names)
+}
 
-    /* 1. Load Vertices/Indices & Fill Object Pool*/
+/// Load .obj file without .mtl file from `obj_dir`
+pub fn load_obj_files_from(obj_dir: &Path, obj_names: &[&str]) -> Result<ObjectPool> {
+    let mut object_pool = ObjectPool::default();
 
-    let mut vertices = Vec::new();
-    let mut indices = Vec::new();
-    let mut pool = Vec::new();
+    for obj_name in obj_names {
+        let path = obj_dir.join(format!("{}.obj", obj_name));
+        let source = std::fs::read_to_string(&path)
+            .with_context(|| format!("Object {}: Can't read {}", obj_name, path.display()))?;
+        parse_obj(
+            &source,
+            &path.display().to_string(),
+            None,
+            first_char_name,
+            &mut object_pool,
+        )?;
+        name_shape_objects(&mut object_pool, obj_name);
+    }
 
-    let mut vertex = Vertex {
-        color: COLOR_WHITE,
-        ..Vertex::default()
-    };
-    let mut index;
-    let mut object_index_offset = 0;
-    let mut object_data = ObjectData::default();
+    Ok(object_pool)
+}
 
-    for obj_name in obj_names {
-        let path = format!("res/obj/{}.obj", obj_name);
-        let file = std::fs::File::open(path)?;
-        for line in std::io::BufReader::new(file).lines() {
-            curr_line = line?;
-
-            if let Some(text) = curr_line.get(..2) {
-                match text {
-                    "o " => {
-                        //"o Test_Cube.001" -> "X_Cube.001"
-                        if let Some(object_text) = curr_line.split(' ').next_back() {
-                            //"X_Cube.001" -> "X"
-                            if let Some(object_name) = object_text.chars().next() {
-                                // First Object -> Skip Save
-                                if object_data.name.len() == 0 {
-                                    object_data.name = object_name.to_string();
-                                    continue;
-                                }
-
-                                // Save
-                                pool.push(object_data.clone());
-                                object_data.name = object_name.to_string();
-                                object_data.index_offset += object_data.index_count;
-                                object_data.index_count = 0;
-                            }
-                        }
-                    }
-                    "v " => {
-                        //"v 0.000000 0.000000 -7.000000" -> [0.0, 0.0, -7.0]
-                        for (i, value) in curr_line.split(' ').enumerate() {
-                            if i == 0 {
-                                continue;
-                            }
+/// Parse the contents of .obj files without .mtl file
+pub fn parse_obj_sources(sources: &[&str]) -> Result<ObjectPool> {
+    let mut object_pool = ObjectPool::default();
+
+    for (i, source) in sources.iter().enumerate() {
+        parse_obj(
+            source,
+            &format!("source {}", i),
+            None,
+            first_char_name,
+            &mut object_pool,
+        )?;
+    }
 
-                            if i > 3 {
-                                break;
-                            }
+    Ok(object_pool)
+}
 
-                            vertex.position[i - 1] = value.parse::<f32>()?;
-                        }
+/// Load a single .obj file with the .mtl file of the same name next to it
+///
+/// The diffuse colors of the materials are stored as vertex colors
+pub fn load_obj_with_mtl(obj_path: &Path) -> Result<ObjectPool> {
+    /* 1. Load Colors */
 
-                        vertices.push(vertex);
-                    }
-                    "f " => {
-                        //"f 18 7 1" -> [18, 7, 1]
-                        for (i, value) in curr_line.split(' ').enumerate() {
-                            if i == 0 {
-                                continue;
-                            }
+    let mtl_path = obj_path.with_extension("mtl");
+    let mtl_source = std::fs::read_to_string(&mtl_path)
+        .with_context(|| format!("Object: Can't read {}", mtl_path.display()))?;
+    let materials = parse_mtl(&mtl_source, &mtl_path.display().to_string())?;
 
-                            if i > 3 {
-                                break;
-                            }
+    /* 2. Load Vertices/Indices & Fill Object Pool*/
 
-                            index = value.parse::<u16>()? - 1;
+    let source = std::fs::read_to_string(obj_path)
+        .with_context(|| format!("Object: Can't read {}", obj_path.display()))?;
+    let mut object_pool = ObjectPool::default();
+    parse_obj(
+        &source,
+        &obj_path.display().to_string(),
+        Some(&materials),
+        prefix_name,
+        &mut object_pool,
+    )?;
+
+    Ok(object_pool)
+}
 
-                            indices.push(object_index_offset as u16 + index);
-                        }
+/// "X_Cube.001" -> "X", names of the embedded objects
+fn first_char_name(object_text: &str) -> String {
+    object_text
+        .chars()
+        .next()
+        .map(String::from)
+        .unwrap_or_default()
+}
 
-                        object_data.index_count += 3;
-                    }
-                    _ => (),
-                }
-            }
-        }
+/// "Test_Cube.001" -> "Test"
+fn prefix_name(object_text: &str) -> String {
+    object_text
+        .split('_')
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Object of an .obj file while it is parsed
+struct ObjObject {
+    name: String,
+    vertices: Vec<Vertex>,
+    indices: Vec<u32>,
+    /// Index of a position and color in `vertices`, faces of different materials don't share
+    /// their vertices
+    lookup: HashMap<(usize, [u32; 3]), u32>,
+}
 
-        object_index_offset = vertices.len();
+impl ObjObject {
+    fn new(name: String) -> Self {
+        Self {
+            name,
+            vertices: Vec::new(),
+            indices: Vec::new(),
+            lookup: HashMap::new(),
+        }
     }
 
-    // Save Last Object
-    pool.push(object_data);
+    fn push_index(&mut self, position_index: usize, position: [f32; 3], color: [f32; 3]) {
+        let key = (position_index, color.map(f32::to_bits));
+        let vertices = &mut self.vertices;
+        let index = *self.lookup.entry(key).or_insert_with(|| {
+            vertices.push(Vertex { position, color });
+            vertices.len() as u32 - 1
+        });
 
-    Ok(ObjectPool {
-        indices,
-        vertices,
-        pool,
-    })
+        self.indices.push(index);
+    }
 }
 
-/// Load a single .obj file with .mtl file
-pub fn load_obj_with_mtl(obj_name: &str) -> Result<ObjectPool> {
-    let mut curr_line;
+/// Parses the objects of a single .obj `source` into `object_pool`, `origin` names the source
+/// in the errors, which point to the line and column
+///
+/// * Faces of any vertex count are triangulated as fans
+/// * `v/vt/vn`, `v//vn` and negative (relative) indices are supported, texture coordinates and
+///   normals are skipped
+/// * Faces are colored by their `usemtl` material, `materials` of `None` keeps them white,
+///   `v x y z r g b` vertex colors replace the material
+fn parse_obj(
+    source: &str,
+    origin: &str,
+    materials: Option<&HashMap<String, [f32; 3]>>,
+    object_name: fn(&str) -> String,
+    object_pool: &mut ObjectPool,
+) -> Result<()> {
+    let mut positions: Vec<([f32; 3], Option<[f32; 3]>)> = Vec::new();
+    let mut objects: Vec<ObjObject> = Vec::new();
+    let mut color = COLOR_WHITE;
+
+    for (line_index, line) in source.lines().enumerate() {
+        let error = |column: usize, message: String| {
+            anyhow::anyhow!(
+                "Object: {}:{}:{}: {}",
+                origin,
+                line_index + 1,
+                column,
+                message
+            )
+        };
+
+        let tokens = obj_tokens(line);
+        let Some(&(_, keyword)) = tokens.first() else {
+            continue;
+        };
+        // Rest of the line after the keyword, names may contain spaces
+        let rest = || line.trim_start()[keyword.len()..].trim();
+
+        match keyword {
+            "o" => objects.push(ObjObject::new(object_name(rest()))),
+            "v" => {
+                //"v 0.000000 0.000000 -7.000000" -> [0.0, 0.0, -7.0]
+                let mut values = Vec::new();
+                for &(column, value) in &tokens[1..] {
+                    values.push(value.parse::<f32>().map_err(|_| {
+                        error(column, format!("Invalid vertex coordinate \"{}\"", value))
+                    })?);
+                }
 
-    /* 1. Load Colors */
+                let position = match values.len() {
+                    3 | 4 | 6 => [values[0], values[1], values[2]],
+                    _ => {
+                        return Err(error(
+                            1,
+                            format!("Vertex needs 3 coordinates, found {}", values.len()),
+                        ))
+                    }
+                };
+                let vertex_color = (values.len() == 6).then(|| [values[3], values[4], values[5]]);
 
-    let mut color_pool = Vec::new();
+                positions.push((position, vertex_color));
+            }
+            "f" => {
+                //"f 18/3/1 7 -1" -> [17, 6, last]
+                let mut face = Vec::new();
+                for &(column, value) in &tokens[1..] {
+                    let index_text = value.split('/').next().unwrap_or_default();
+                    let index = index_text.parse::<i64>().map_err(|_| {
+                        error(column, format!("Invalid vertex index \"{}\"", value))
+                    })?;
+
+                    // Negative indices count back from the last vertex
+                    let position_index = match index {
+                        1.. => index - 1,
+                        ..=-1 => positions.len() as i64 + index,
+                        0 => -1,
+                    };
+                    if position_index < 0 || position_index >= positions.len() as i64 {
+                        return Err(error(
+                            column,
+                            format!(
+                                "Vertex index {} out of bounds for {} vertices",
+                                index,
+                                positions.len()
+                            ),
+                        ));
+                    }
 
-    let mut vertex_color = VertexColor::default();
+                    face.push(position_index as usize);
+                }
 
-    let path = format!("res/obj/{}.mtl", obj_name);
-    let file = std::fs::File::open(path)?;
-    for line in std::io::BufReader::new(file).lines() {
-        curr_line = line?;
-
-        if let Some(text) = curr_line.get(..2) {
-            match text {
-                "ne" => {
-                    //newmtl Name -> Name
-                    if let Some(color_name) = curr_line.split(' ').next_back() {
-                        vertex_color.name = color_name.to_string();
-                    }
+                if face.len() < 3 {
+                    return Err(error(
+                        1,
+                        format!("Face needs 3 vertices, found {}", face.len()),
+                    ));
+                }
+
+                // Faces before the first object
+                if objects.is_empty() {
+                    objects.push(ObjObject::new(String::new()));
                 }
-                "Kd" => {
-                    //Kd 0.8 0.8 0.8 -> [0.8, 0.8, 0.8]
-                    for (i, value) in curr_line.split(' ').enumerate() {
-                        if i == 0 {
-                            continue;
-                        }
-
-                        if i > 3 {
-                            break;
-                        }
-
-                        vertex_color.color[i - 1] = value.parse::<f32>()?;
+                let object = objects.last_mut().unwrap();
+
+                for i in 1..face.len() - 1 {
+                    for position_index in [face[0], face[i], face[i + 1]] {
+                        let (position, vertex_color) = positions[position_index];
+                        object.push_index(position_index, position, vertex_color.unwrap_or(color));
                     }
                 }
-                "Ks" => {
-                    //Ks should come after Kd
-                    color_pool.push(vertex_color.clone());
+            }
+            "usemtl" => {
+                //"usemtl MaterialName" -> MaterialName
+                if let Some(materials) = materials {
+                    color = *materials.get(rest()).ok_or_else(|| {
+                        error(
+                            tokens[1..].first().map_or(1, |token| token.0),
+                            format!("Unknown material \"{}\"", rest()),
+                        )
+                    })?;
                 }
-                _ => (),
             }
+            // Texture coordinates, normals, groups, smoothing and material libraries
+            _ => (),
         }
     }
 
-    /* 2. Load Vertices/Indices & Fill Object Pool*/
+    for object in objects {
+        object_pool.push_object(&object.name, &object.vertices, &object.indices)?;
+    }
 
-    let mut vertices = Vec::new();
-    let mut indices = Vec::new();
-    let mut pool = Vec::new();
+    Ok(())
+}
 
-    let mut vertex = Vertex::default();
-    let mut index;
-    let mut object_data = ObjectData::default();
+/// Parses the diffuse colors of the materials of an .mtl `source` by their names
+fn parse_mtl(source: &str, origin: &str) -> Result<HashMap<String, [f32; 3]>> {
+    let mut materials = HashMap::new();
+    let mut name = None;
 
-    let path = format!("res/obj/{}.obj", obj_name);
-    let file = std::fs::File::open(path)?;
-    for line in std::io::BufReader::new(file).lines() {
-        curr_line = line?;
-
-        if let Some(text) = curr_line.get(..2) {
-            match text {
-                "o " => {
-                    //"o Test_Cube.001" -> "Test_Cube.001"
-                    if let Some(object_text) = curr_line.split(' ').next_back() {
-                        //"Test_Cube.001" -> "Test"
-                        if let Some(object_name) = object_text.split("_").next() {
-                            // First Object -> Skip Save
-                            if object_data.name.len() == 0 {
-                                object_data.name = object_name.to_string();
-                                continue;
-                            }
-
-                            // Save
-                            pool.push(object_data.clone());
-                            object_data.name = object_name.to_string();
-                            object_data.index_offset += object_data.index_count;
-                            object_data.index_count = 0;
-                        }
-                    }
-                }
-                "v " => {
-                    //"v 0.000000 0.000000 -7.000000" -> [0.0, 0.0, -7.0]
-                    for (i, value) in curr_line.split(' ').enumerate() {
-                        if i == 0 {
-                            continue;
-                        }
-
-                        if i > 3 {
-                            break;
-                        }
-
-                        vertex.position[i - 1] = value.parse::<f32>()?;
-                    }
+    for (line_index, line) in source.lines().enumerate() {
+        let tokens = obj_tokens(line);
 
-                    vertices.push(vertex);
-                }
-                "us" => {
-                    //"usemtl MaterialName" -> MaterialName
-                    if let Some(color_name) = curr_line.split(' ').next_back() {
-                        for color in &color_pool {
-                            if color.name == color_name {
-                                vertex_color.color = color.color;
-                            }
-                        }
-                    }
+        match tokens.first().map(|&(_, keyword)| keyword) {
+            Some("newmtl") => {
+                //"newmtl Name" -> Name
+                let material = line.trim_start()["newmtl".len()..].trim().to_string();
+                materials.insert(material.clone(), COLOR_WHITE);
+                name = Some(material);
+            }
+            Some("Kd") => {
+                //"Kd 0.8 0.8 0.8" -> [0.8, 0.8, 0.8]
+                let mut color = [0.0; 3];
+                for (i, channel) in color.iter_mut().enumerate() {
+                    let (column, value) =
+                        tokens.get(i + 1).copied().unwrap_or((line.len() + 1, ""));
+                    *channel = value.parse().map_err(|_| {
+                        anyhow::anyhow!(
+                            "Object: {}:{}:{}: Invalid diffuse color \"{}\"",
+                            origin,
+                            line_index + 1,
+                            column,
+                            value
+                        )
+                    })?;
                 }
-                "f " => {
-                    //"f 18 7 1" -> [18, 7, 1]
-                    for (i, value) in curr_line.split(' ').enumerate() {
-                        if i == 0 {
-                            continue;
-                        }
 
-                        if i > 3 {
-                            break;
-                        }
+                if let Some(name) = &name {
+                    materials.insert(name.clone(), color);
+                }
+            }
+            _ => (),
+        }
+    }
 
-                        index = value.parse::<u16>()? - 1;
+    Ok(materials)
+}
 
-                        vertices[index as usize].color = vertex_color.color;
+/// Splits an .obj or .mtl line into its tokens with their 1 based columns, comment lines have
+/// none
+fn obj_tokens(line: &str) -> Vec<(usize, &str)> {
+    if line.trim_start().starts_with('#') {
+        return Vec::new();
+    }
 
-                        indices.push(index);
-                    }
+    let mut tokens = Vec::new();
+    let mut start = None;
 
-                    object_data.index_count += 3;
-                }
-                _ => (),
+    for (i, c) in line.char_indices().chain([(line.len(), ' ')]) {
+        match (start, c.is_whitespace()) {
+            (None, false) => start = Some(i),
+            (Some(token_start), true) => {
+                tokens.push((token_start + 1, &line[token_start..i]));
+                start = None;
             }
+            _ => (),
         }
     }
 
-    // Save Last Object
-    pool.push(object_data);
-
-    Ok(ObjectPool {
-        indices,
-        vertices,
-        pool,
-    })
+    tokens
 }
 
 //==================================================
@@ -434,6 +1338,176 @@ pub const CHAR_OBJECT_POOL: [u8; 255] = [
     255, 255, 255, 255, 255, 255, 255, 255,
 ];
 
+//==================================================
+//=== Texture
+//==================================================
+
+/// Index of a texture loaded by the renderer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TextureHandle(pub(crate) usize);
+
+#[derive(Debug, Clone, Default)]
+pub struct ImageData {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+/// Load a .png file as RGBA8 pixels
+pub fn load_png(path: &str) -> Result<ImageData> {
+    let file = std::fs::File::open(path)?;
+
+    let mut decoder = png::Decoder::new(file);
+    decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+
+    let mut reader = decoder.read_info()?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer)?;
+    let buffer = &buffer[..info.buffer_size()];
+
+    //Expand every color type to RGBA
+    let pixels = match info.color_type {
+        png::ColorType::Rgba => buffer.to_vec(),
+        png::ColorType::Rgb => buffer
+            .chunks_exact(3)
+            .flat_map(|p| [p[0], p[1], p[2], 255])
+            .collect(),
+        png::ColorType::GrayscaleAlpha => buffer
+            .chunks_exact(2)
+            .flat_map(|p| [p[0], p[0], p[0], p[1]])
+            .collect(),
+        png::ColorType::Grayscale => buffer.iter().flat_map(|&p| [p, p, p, 255]).collect(),
+        png::ColorType::Indexed => {
+            return Err(anyhow::anyhow!(
+                "Indexed .png should be expanded by the decoder"
+            ))
+        }
+    };
+
+    Ok(ImageData {
+        width: info.width,
+        height: info.height,
+        pixels,
+    })
+}
+
+//==================================================
+//=== Atlas
+//==================================================
+
+/// Index of an atlas loaded by [`crate::Renderer::load_atlas`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AtlasHandle(pub(crate) usize);
+
+/// Index of a region inside an atlas, see [`crate::Renderer::atlas_region`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AtlasRegionId {
+    pub(crate) atlas: usize,
+    pub(crate) region: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AtlasRegion {
+    pub name: String,
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct Atlas {
+    pub texture: TextureHandle,
+    pub width: u32,
+    pub height: u32,
+    pub regions: Vec<AtlasRegion>,
+}
+
+impl Atlas {
+    /// Gives back the index of the region called `name`
+    pub fn region_index(&self, name: &str) -> Option<usize> {
+        self.regions.iter().position(|r| r.name == name)
+    }
+
+    /// Gives back the UV rectangle of the region as (offset x, offset y, scale x, scale y)
+    pub fn uv_rect(&self, region_index: usize) -> Option<glm::Vec4> {
+        let region = self.regions.get(region_index)?;
+
+        Some(glm::vec4(
+            region.x as f32 / self.width as f32,
+            region.y as f32 / self.height as f32,
+            region.width as f32 / self.width as f32,
+            region.height as f32 / self.height as f32,
+        ))
+    }
+}
+
+/// Packs named images into a single RGBA8 image using rows (shelves) sorted by height
+///
+/// Gives back the packed regions and the atlas image, the texture itself is uploaded by the renderer
+pub fn pack_atlas(images: &[(String, ImageData)]) -> (Vec<AtlasRegion>, ImageData) {
+    const PADDING: u32 = 1;
+
+    let area: u32 = images
+        .iter()
+        .map(|(_, img)| (img.width + PADDING) * (img.height + PADDING))
+        .sum();
+    let max_width = images.iter().map(|(_, img)| img.width).max().unwrap_or(1);
+    let width = u32::max(max_width, (area as f32).sqrt().ceil() as u32).next_power_of_two();
+
+    // Tallest images first keeps the rows tight
+    let mut order: Vec<usize> = (0..images.len()).collect();
+    order.sort_by(|&a, &b| images[b].1.height.cmp(&images[a].1.height));
+
+    let mut regions = vec![AtlasRegion::default(); images.len()];
+    let (mut cursor_x, mut cursor_y, mut row_height) = (0, 0, 0);
+
+    for i in order {
+        let (name, img) = &images[i];
+
+        // Move to the next row
+        if cursor_x + img.width > width {
+            cursor_x = 0;
+            cursor_y += row_height + PADDING;
+            row_height = 0;
+        }
+
+        regions[i] = AtlasRegion {
+            name: name.clone(),
+            x: cursor_x,
+            y: cursor_y,
+            width: img.width,
+            height: img.height,
+        };
+
+        cursor_x += img.width + PADDING;
+        row_height = u32::max(row_height, img.height);
+    }
+
+    let height = (cursor_y + row_height).max(1);
+    let mut pixels = vec![0; (width * height * 4) as usize];
+
+    for (region, (_, img)) in regions.iter().zip(images) {
+        for row in 0..img.height {
+            let src = (row * img.width * 4) as usize;
+            let dst = (((region.y + row) * width + region.x) * 4) as usize;
+            let len = (img.width * 4) as usize;
+
+            pixels[dst..dst + len].copy_from_slice(&img.pixels[src..src + len]);
+        }
+    }
+
+    (
+        regions,
+        ImageData {
+            width,
+            height,
+            pixels,
+        },
+    )
+}
+
 //==================================================
 //=== Shapes
 //==================================================
@@ -454,4 +1528,364 @@ mod tests {
 
         dbg!(obj);
     }
+
+    #[test]
+    fn test_preload_embedded() {
+        let object_pool = preload(&ResourceConfig::default()).unwrap();
+
+        assert_eq!(object_pool.pool.len(), 54);
+        assert_eq!(object_pool.pool[52].name, "rectangle");
+        assert_eq!(object_pool.pool[53].name, "circle");
+    }
+
+    #[test]
+    fn test_object_lookup() {
+        let object_pool = preload(&ResourceConfig::default()).unwrap();
+
+        assert_eq!(object_pool.get("circle"), Some(ObjectHandle(53)));
+        assert_eq!(object_pool.get("A"), Some(ObjectHandle(26)));
+        assert_eq!(object_pool.get("triangle"), None);
+
+        let error = object_pool.lookup("triangle").unwrap_err().to_string();
+        assert!(error.starts_with("Object: No object named \"triangle\", the objects are: $, !"));
+        assert!(error.ends_with("Z, rectangle, circle"));
+    }
+
+    #[test]
+    fn test_load_obj_with_mtl() {
+        let mut object_pool = preload(&ResourceConfig::default()).unwrap();
+        let rectangle = load_obj_with_mtl(Path::new("res/obj/rectangle.obj")).unwrap();
+
+        let handles = object_pool.append(&rectangle).unwrap();
+        assert_eq!(handles, vec![ObjectHandle(54)]);
+
+        // Diffuse color of the material is kept as vertex color
+        let object_data = &object_pool.pool[54];
+        for &index in &object_pool.indices[object_data.index_offset..][..object_data.index_count] {
+            assert_eq!(object_pool.vertices[index as usize].color, [0.8, 0.8, 0.8]);
+        }
+        assert_eq!(object_data.width(), 0.2);
+    }
+
+    #[test]
+    fn test_parse_obj() {
+        let source = "# Quad and a triangle with texture coordinates and normals
+o Quad_Plane
+v -1.0 -1.0 0.0
+vt 0.0 0.0
+v 1.0 -1.0 0.0
+vn 0.0 0.0 1.0
+v 1.0 1.0 0.0
+v -1.0 1.0 0.0 1.0 0.0 0.0
+usemtl Blue
+f 1/1/1 2//1 3/2 4
+o Triangle_Plane
+v 2.0 0.0 0.0
+usemtl Green
+f -3 -2 -1
+";
+        let materials = HashMap::from([
+            ("Blue".to_string(), [0.0, 0.0, 1.0]),
+            ("Green".to_string(), [0.0, 1.0, 0.0]),
+        ]);
+
+        let mut object_pool = ObjectPool::default();
+        parse_obj(
+            source,
+            "test",
+            Some(&materials),
+            prefix_name,
+            &mut object_pool,
+        )
+        .unwrap();
+
+        // Quad -> 2 triangles, the vertex color replaces the material
+        let quad = &object_pool.pool[0];
+        assert_eq!(quad.name, "Quad");
+        assert_eq!(quad.index_count, 6);
+        assert_eq!(quad.width(), 2.0);
+        let colors: Vec<[f32; 3]> = object_pool.indices[..6]
+            .iter()
+            .map(|&index| object_pool.vertices[index as usize].color)
+            .collect();
+        assert_eq!(colors.iter().filter(|&&c| c == [1.0, 0.0, 0.0]).count(), 1);
+        assert_eq!(colors.iter().filter(|&&c| c == [0.0, 0.0, 1.0]).count(), 5);
+
+        // Negative indices of the triangle, sharing positions with the quad
+        let triangle = &object_pool.pool[1];
+        assert_eq!(triangle.index_count, 3);
+        assert_eq!(triangle.bounds_min, glm::vec2(-1.0, 0.0));
+        assert_eq!(triangle.bounds_max, glm::vec2(2.0, 1.0));
+        assert_eq!(object_pool.vertices.len(), 7);
+
+        // Errors point to the line and column
+        let error = parse_obj(
+            "v 0 0 0\nf 1 1 4\n",
+            "test",
+            None,
+            prefix_name,
+            &mut object_pool,
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Object: test:2:7: Vertex index 4 out of bounds for 1 vertices"
+        );
+        let error = parse_obj(
+            "v 0 0 0\nf 1 x 1\n",
+            "test",
+            None,
+            prefix_name,
+            &mut object_pool,
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Object: test:2:5: Invalid vertex index \"x\""
+        );
+    }
+
+    #[test]
+    fn test_replace_objects() {
+        let mut object_pool = preload(&ResourceConfig::default()).unwrap();
+        let rectangle = load_obj_with_mtl(Path::new("res/obj/rectangle.obj")).unwrap();
+        let handles = object_pool.append(&rectangle).unwrap();
+        let circle_indices = object_pool.pool[53].index_count;
+
+        // Rectangle -> 2 circles, the second one is appended
+        let circles = parse_obj_sources(&[
+            include_str!("../res/obj/circle.obj"),
+            include_str!("../res/obj/circle.obj"),
+        ])
+        .unwrap();
+        let replaced = object_pool.replace(&handles, &circles).unwrap();
+        assert_eq!(replaced, vec![ObjectHandle(54), ObjectHandle(55)]);
+        assert_eq!(object_pool.pool[54].index_count, circle_indices);
+        assert_eq!(object_pool.pool[55].name, "C");
+        assert_eq!(object_pool.pool[52].name, "rectangle");
+        assert_eq!(object_pool.pool[52].width(), 0.2);
+
+        // Missing objects are emptied
+        let replaced = object_pool
+            .replace(&replaced, &ObjectPool::default())
+            .unwrap();
+        assert!(replaced.is_empty());
+        assert_eq!(object_pool.pool.len(), 56);
+        assert_eq!(object_pool.pool[55].index_count, 0);
+
+        assert!(object_pool.replace(&[ObjectHandle(56)], &circles).is_err());
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut object_pool = preload(&ResourceConfig::default()).unwrap();
+        assert_eq!(object_pool.validate(), vec![]);
+
+        let vertices = [
+            Vertex::default(),
+            Vertex {
+                position: [1.0, 0.0, 0.0],
+                ..Vertex::default()
+            },
+            Vertex {
+                position: [2.0, 0.0, 0.0],
+                ..Vertex::default()
+            },
+        ];
+        object_pool
+            .push_object("line", &vertices, &[0, 1, 2, 0, 0, 1])
+            .unwrap();
+        object_pool.indices.push(u32::MAX);
+        object_pool.pool.push(ObjectData {
+            name: "broken".to_string(),
+            index_count: 1,
+            index_offset: object_pool.indices.len() - 1,
+            ..ObjectData::default()
+        });
+
+        assert_eq!(
+            object_pool.validate(),
+            vec![
+                PoolWarning::DegenerateTriangle {
+                    object: "line".to_string(),
+                    triangle: 0,
+                },
+                PoolWarning::DegenerateTriangle {
+                    object: "line".to_string(),
+                    triangle: 1,
+                },
+                PoolWarning::IncompleteTriangle {
+                    object: "broken".to_string(),
+                    index_count: 1,
+                },
+                PoolWarning::IndexOutOfBounds {
+                    object: "broken".to_string(),
+                    index: u32::MAX,
+                    vertex_count: object_pool.vertices.len(),
+                },
+            ]
+        );
+
+        // Glyphs missing from the pool
+        object_pool.pool.truncate(10);
+        assert!(object_pool.validate().contains(&PoolWarning::MissingGlyph {
+            character: 'A',
+            object_index: 26,
+        }));
+    }
+
+    #[test]
+    fn test_indices_u16() {
+        let mut object_pool = preload(&ResourceConfig::default()).unwrap();
+        assert!(object_pool.indices_u16().is_some());
+
+        // Vertices beyond the 16 bit range promote the pool to 32 bit indices
+        let vertices = vec![Vertex::default(); u16::MAX as usize + 1];
+        let handle = object_pool
+            .push_object("large", &vertices, &[0, 1, u16::MAX as u32])
+            .unwrap();
+        assert!(object_pool.indices_u16().is_none());
+
+        let object_data = &object_pool.pool[handle.0];
+        let last_index = object_pool.indices[object_data.index_offset + 2] as usize;
+        assert_eq!(last_index, object_pool.vertices.len() - 1);
+    }
+
+    #[test]
+    fn test_pack_atlas() {
+        let image = |width, height, value| ImageData {
+            width,
+            height,
+            pixels: vec![value; (width * height * 4) as usize],
+        };
+
+        let (regions, atlas) = pack_atlas(&[
+            ("small".to_string(), image(2, 2, 1)),
+            ("tall".to_string(), image(3, 5, 2)),
+            ("wide".to_string(), image(6, 1, 3)),
+        ]);
+
+        // Regions must stay inside the atlas and must not overlap
+        for (i, a) in regions.iter().enumerate() {
+            assert!(a.x + a.width <= atlas.width && a.y + a.height <= atlas.height);
+
+            for b in regions.iter().skip(i + 1) {
+                let overlap_x = a.x < b.x + b.width && b.x < a.x + a.width;
+                let overlap_y = a.y < b.y + b.height && b.y < a.y + a.height;
+                assert!(!(overlap_x && overlap_y), "{} overlaps {}", a.name, b.name);
+            }
+        }
+
+        // Pixels are copied into their region
+        let tall = &regions[1];
+        let index = ((tall.y * atlas.width + tall.x) * 4) as usize;
+        assert_eq!(tall.name, "tall");
+        assert_eq!(atlas.pixels[index], 2);
+    }
+
+    #[test]
+    fn test_pick_target() {
+        let object_data = ObjectData {
+            bounds_min: glm::vec2(-0.1, -0.1),
+            bounds_max: glm::vec2(0.1, 0.1),
+            ..ObjectData::default()
+        };
+        let draw_instance = ObjectInstance {
+            position: glm::vec3(1.0, 0.0, 0.0),
+            rotation: 90.0,
+            scale: glm::vec3(4.0, 1.0, 0.0),
+            ..ObjectInstance::default()
+        };
+
+        // Rotated by 90 degrees -> The long side points along the y axis
+        let pick_target = PickTarget::new(DrawInstanceId(0), &draw_instance, &object_data);
+        assert!(pick_target.contains(glm::vec2(1.0, 0.35)));
+        assert!(!pick_target.contains(glm::vec2(1.35, 0.0)));
+
+        // Ellipse -> The corners of the bounding box are missed
+        let ellipse = ObjectData {
+            pick_shape: PickShape::Ellipse,
+            ..object_data.clone()
+        };
+        let pick_target = PickTarget::new(DrawInstanceId(0), &draw_instance, &ellipse);
+        assert!(pick_target.contains(glm::vec2(1.0, 0.35)));
+        assert!(pick_target.contains(glm::vec2(1.08, 0.0)));
+        assert!(!pick_target.contains(glm::vec2(1.09, 0.35)));
+
+        // Ellipse border -> The middle is missed
+        let border = ObjectData {
+            pick_shape: PickShape::EllipseBorder(0.05),
+            ..object_data
+        };
+        let pick_target = PickTarget::new(DrawInstanceId(0), &draw_instance, &border);
+        assert!(pick_target.contains(glm::vec2(1.0, 0.38)));
+        assert!(!pick_target.contains(glm::vec2(1.0, 0.0)));
+    }
+
+    #[test]
+    fn test_instance_matrix() {
+        let draw_instance = ObjectInstance {
+            position: glm::vec3(1.0, -2.0, 0.0),
+            rotation: 30.0,
+            skew: glm::vec2(20.0, 0.0),
+            scale: glm::vec3(2.0, 0.5, 1.0),
+            ..ObjectInstance::default()
+        };
+
+        // Decomposed into the same placement
+        let decomposed = ObjectInstance::from_matrix(&draw_instance.matrix());
+        assert!((decomposed.position - draw_instance.position).norm() < 1e-5);
+        assert!((decomposed.rotation - 30.0).abs() < 1e-3);
+        assert!((decomposed.skew - draw_instance.skew).norm() < 1e-3);
+        assert!((decomposed.scale - draw_instance.scale).norm() < 1e-5);
+
+        // Vertical skew -> Same matrix through a horizontal one
+        let squashed = ObjectInstance {
+            skew: glm::vec2(0.0, 45.0),
+            scale: glm::vec3(1.0, 1.0, 1.0),
+            ..ObjectInstance::default()
+        };
+        let matrix = squashed.matrix();
+        assert!((ObjectInstance::from_matrix(&matrix).matrix() - matrix).norm() < 1e-5);
+
+        // Skewed along x -> The top edge is shifted to the right
+        let object_data = ObjectData {
+            bounds_min: glm::vec2(-0.1, -0.1),
+            bounds_max: glm::vec2(0.1, 0.1),
+            ..ObjectData::default()
+        };
+        let skewed = ObjectInstance {
+            skew: glm::vec2(45.0, 0.0),
+            scale: glm::vec3(1.0, 1.0, 1.0),
+            ..ObjectInstance::default()
+        };
+        let pick_target = PickTarget::new(DrawInstanceId(0), &skewed, &object_data);
+        assert!(pick_target.contains(glm::vec2(0.15, 0.09)));
+        assert!(!pick_target.contains(glm::vec2(-0.15, 0.09)));
+        assert!((skewed.world_bounds(&object_data).max - glm::vec2(0.2, 0.1)).norm() < 1e-5);
+    }
+
+    #[test]
+    fn test_persistent_pool() {
+        let mut persistent_pool = PersistentPool::default();
+        let first = persistent_pool.add(vec![ObjectInstance::default(); 2]);
+        let second = persistent_pool.add(vec![ObjectInstance {
+            layer: 1,
+            ..ObjectInstance::default()
+        }]);
+
+        for instance in persistent_pool.get_mut(first).unwrap() {
+            instance.layer = 2;
+        }
+
+        let layers: Vec<i32> = persistent_pool.instances().map(|i| i.layer).collect();
+        assert_eq!(layers, [2, 2, 1]);
+
+        // Removed ids stay invalid, even after new instances are added
+        assert_eq!(persistent_pool.remove(first).unwrap().len(), 2);
+        persistent_pool.add(vec![ObjectInstance::default()]);
+        assert!(persistent_pool.remove(first).is_err());
+        assert!(persistent_pool.get_mut(second).is_ok());
+        assert_eq!(persistent_pool.instances().count(), 2);
+    }
 }
@@ -0,0 +1,473 @@
+// std
+#[cfg(feature = "serde")]
+use std::path::Path;
+
+// extern
+extern crate nalgebra_glm as glm;
+#[cfg(feature = "serde")]
+use anyhow::Context;
+use anyhow::{anyhow, Result};
+
+// intern
+use crate::resources::{ObjectHandle, TextureHandle};
+use crate::text::TextParams;
+use crate::AnchorType;
+
+//==================================================
+//=== Scene Graph
+//==================================================
+
+/// Handle of a [`SceneNode`] inside a [`SceneGraph`], handles of removed nodes are reused
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeId(pub(crate) usize);
+
+/// Position, rotation (degrees) and scale of a node relative to its parent
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Transform {
+    pub position: glm::Vec2,
+    pub rotation: f32,
+    pub scale: glm::Vec2,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self {
+            position: glm::vec2(0.0, 0.0),
+            rotation: 0.0,
+            scale: glm::vec2(1.0, 1.0),
+        }
+    }
+}
+
+impl Transform {
+    /// Creates a new [`Transform`] at `position` without rotation and scaling
+    pub fn from_position(x: f32, y: f32) -> Self {
+        Self {
+            position: glm::vec2(x, y),
+            ..Self::default()
+        }
+    }
+
+    /// Applies `self` on top of the `parent` transform
+    ///
+    /// Scaling happens along the axes of the node, so rotated children of a non-uniformly
+    /// scaled parent are not sheared
+    pub fn then(&self, parent: &Transform) -> Transform {
+        let (sin, cos) = parent.rotation.to_radians().sin_cos();
+        let offset = self.position.component_mul(&parent.scale);
+
+        Transform {
+            position: parent.position
+                + glm::vec2(
+                    offset.x * cos - offset.y * sin,
+                    offset.x * sin + offset.y * cos,
+                ),
+            rotation: parent.rotation + self.rotation,
+            scale: self.scale.component_mul(&parent.scale),
+        }
+    }
+}
+
+/// What a [`SceneNode`] draws, the node transform scales the drawn object
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NodeShape {
+    Circle {
+        color: glm::Vec3,
+    },
+    Rectangle {
+        color: glm::Vec3,
+    },
+    Object {
+        object: ObjectHandle,
+        color: glm::Vec3,
+    },
+    Sprite {
+        texture: TextureHandle,
+    },
+    /// Text is scaled by the x scale of the node and is never rotated
+    Text {
+        text: String,
+        params: TextParams,
+    },
+}
+
+/// Persistent node of a [`SceneGraph`], nodes without a shape only group their children
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SceneNode {
+    pub(crate) transform: Transform,
+    pub(crate) shape: Option<NodeShape>,
+    pub(crate) anchor_type: AnchorType,
+    pub(crate) visible: bool,
+    pub(crate) parent: Option<NodeId>,
+    pub(crate) children: Vec<NodeId>,
+    /// Transform relative to the world, valid while the node is not dirty
+    pub(crate) world_transform: Transform,
+    pub(crate) dirty: bool,
+}
+
+impl SceneNode {
+    /// Creates a new visible [`SceneNode`] moving with the camera
+    pub fn new(transform: Transform, shape: Option<NodeShape>) -> Self {
+        Self {
+            transform,
+            shape,
+            anchor_type: AnchorType::Unlocked,
+            visible: true,
+            parent: None,
+            children: Vec::new(),
+            world_transform: transform,
+            dirty: true,
+        }
+    }
+
+    /// Sets how the node is anchored to the camera, children follow their root
+    pub fn with_anchor(mut self, anchor_type: AnchorType) -> Self {
+        self.anchor_type = anchor_type;
+        self
+    }
+
+    pub fn transform(&self) -> &Transform {
+        &self.transform
+    }
+
+    pub fn shape(&self) -> Option<&NodeShape> {
+        self.shape.as_ref()
+    }
+
+    pub fn children(&self) -> &[NodeId] {
+        &self.children
+    }
+
+    /// Transform relative to the world as of the last [`SceneGraph::update`]
+    pub fn world_transform(&self) -> &Transform {
+        &self.world_transform
+    }
+}
+
+/// Retained nodes drawn with `Renderer::draw_scene` every frame
+///
+/// World transforms are cached and only recomputed for changed nodes and their children
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SceneGraph {
+    nodes: Vec<Option<SceneNode>>,
+    roots: Vec<NodeId>,
+}
+
+impl SceneGraph {
+    /// Creates a new empty [`SceneGraph`]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `node` below `parent`, or as a root without one
+    pub fn insert(&mut self, parent: Option<NodeId>, mut node: SceneNode) -> Result<NodeId> {
+        if let Some(parent) = parent {
+            self.node(parent)
+                .ok_or_else(|| anyhow!("Scene: Unknown parent node {:?}", parent))?;
+        }
+
+        node.parent = parent;
+        node.children.clear();
+        node.dirty = true;
+
+        let id = match self.nodes.iter().position(Option::is_none) {
+            Some(index) => {
+                self.nodes[index] = Some(node);
+                NodeId(index)
+            }
+            None => {
+                self.nodes.push(Some(node));
+                NodeId(self.nodes.len() - 1)
+            }
+        };
+
+        match parent.and_then(|parent| self.node_mut(parent)) {
+            Some(parent) => parent.children.push(id),
+            None => self.roots.push(id),
+        }
+
+        Ok(id)
+    }
+
+    /// Removes the node and all of its children
+    pub fn remove(&mut self, id: NodeId) {
+        let Some(node) = self.nodes.get_mut(id.0).and_then(Option::take) else {
+            return;
+        };
+
+        match node.parent.and_then(|parent| self.node_mut(parent)) {
+            Some(parent) => parent.children.retain(|&child| child != id),
+            None => self.roots.retain(|&root| root != id),
+        }
+
+        let mut stack = node.children;
+        while let Some(child) = stack.pop() {
+            if let Some(child) = self.nodes.get_mut(child.0).and_then(Option::take) {
+                stack.extend(child.children);
+            }
+        }
+    }
+
+    /// Removes every node
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.roots.clear();
+    }
+
+    pub fn node(&self, id: NodeId) -> Option<&SceneNode> {
+        self.nodes.get(id.0).and_then(Option::as_ref)
+    }
+
+    fn node_mut(&mut self, id: NodeId) -> Option<&mut SceneNode> {
+        self.nodes.get_mut(id.0).and_then(Option::as_mut)
+    }
+
+    /// Sets the transform of the node relative to its parent
+    pub fn set_transform(&mut self, id: NodeId, transform: Transform) {
+        if let Some(node) = self.node_mut(id) {
+            node.transform = transform;
+            node.dirty = true;
+        }
+    }
+
+    pub fn set_shape(&mut self, id: NodeId, shape: Option<NodeShape>) {
+        if let Some(node) = self.node_mut(id) {
+            node.shape = shape;
+        }
+    }
+
+    /// Hidden nodes are not drawn together with their children
+    pub fn set_visible(&mut self, id: NodeId, visible: bool) {
+        if let Some(node) = self.node_mut(id) {
+            node.visible = visible;
+        }
+    }
+
+    /// Recomputes the world transforms of changed nodes and their children
+    pub fn update(&mut self) {
+        let mut stack: Vec<(NodeId, Transform, bool)> = self
+            .roots
+            .iter()
+            .rev()
+            .map(|&root| (root, Transform::default(), false))
+            .collect();
+
+        while let Some((id, parent_transform, parent_dirty)) = stack.pop() {
+            let Some(node) = self.node_mut(id) else {
+                continue;
+            };
+
+            let dirty = node.dirty || parent_dirty;
+            if dirty {
+                node.world_transform = node.transform.then(&parent_transform);
+                node.dirty = false;
+            }
+
+            let world_transform = node.world_transform;
+            stack.extend(
+                node.children
+                    .iter()
+                    .rev()
+                    .map(|&child| (child, world_transform, dirty)),
+            );
+        }
+    }
+
+    /// Writes the nodes into the JSON file at `path`
+    ///
+    /// Object, texture and font handles are saved as they are, they are only valid for a
+    /// renderer loading its resources in the same order
+    #[cfg(feature = "serde")]
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let file = std::fs::File::create(path)
+            .with_context(|| format!("Scene: Can't write {}", path.display()))?;
+
+        serde_json::to_writer_pretty(std::io::BufWriter::new(file), self)
+            .with_context(|| format!("Scene: Can't write {}", path.display()))
+    }
+
+    /// Reads a [`SceneGraph`] written by [`SceneGraph::save`] from the JSON file at `path`
+    ///
+    /// Fails when the roots and children point to missing nodes or form a cycle
+    #[cfg(feature = "serde")]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = std::fs::File::open(path)
+            .with_context(|| format!("Scene: Can't read {}", path.display()))?;
+
+        let scene_graph: Self = serde_json::from_reader(std::io::BufReader::new(file))
+            .with_context(|| format!("Scene: Can't parse {}", path.display()))?;
+        scene_graph
+            .validate()
+            .with_context(|| format!("Scene: Can't parse {}", path.display()))?;
+
+        Ok(scene_graph)
+    }
+
+    /// Checks that every node below the roots exists and is reached only once
+    #[cfg(feature = "serde")]
+    fn validate(&self) -> Result<()> {
+        let mut reached = vec![false; self.nodes.len()];
+        let mut stack = self.roots.clone();
+
+        while let Some(id) = stack.pop() {
+            let node = self
+                .node(id)
+                .ok_or_else(|| anyhow!("Scene: Unknown node {:?}", id))?;
+
+            if std::mem::replace(&mut reached[id.0], true) {
+                return Err(anyhow!("Scene: Node {:?} is reached more than once", id));
+            }
+
+            stack.extend(&node.children);
+        }
+
+        Ok(())
+    }
+
+    /// Visible nodes with a shape in drawing order (parents before their children),
+    /// with the anchor of their root
+    pub(crate) fn visible_nodes(&self) -> Vec<(&SceneNode, AnchorType)> {
+        let mut visible_nodes = Vec::new();
+        let mut stack: Vec<(NodeId, Option<AnchorType>)> =
+            self.roots.iter().rev().map(|&root| (root, None)).collect();
+
+        while let Some((id, anchor_type)) = stack.pop() {
+            let Some(node) = self.node(id).filter(|node| node.visible) else {
+                continue;
+            };
+
+            let anchor_type = anchor_type.unwrap_or(node.anchor_type);
+            if node.shape.is_some() {
+                visible_nodes.push((node, anchor_type));
+            }
+
+            stack.extend(
+                node.children
+                    .iter()
+                    .rev()
+                    .map(|&child| (child, Some(anchor_type))),
+            );
+        }
+
+        visible_nodes
+    }
+}
+
+//==================================================
+//=== Unit Testing
+//==================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scene_graph_update() {
+        let mut scene_graph = SceneGraph::new();
+
+        let parent = scene_graph
+            .insert(
+                None,
+                SceneNode::new(Transform::from_position(1.0, 0.0), None),
+            )
+            .unwrap();
+        let child = scene_graph
+            .insert(
+                Some(parent),
+                SceneNode::new(Transform::from_position(1.0, 0.0), None),
+            )
+            .unwrap();
+
+        scene_graph.update();
+        let world = scene_graph.node(child).unwrap().world_transform().position;
+        assert!((world - glm::vec2(2.0, 0.0)).norm() < 1e-6);
+
+        // Rotating the parent moves the child along
+        scene_graph.set_transform(
+            parent,
+            Transform {
+                rotation: 90.0,
+                ..Transform::from_position(1.0, 0.0)
+            },
+        );
+        scene_graph.update();
+        let world = scene_graph.node(child).unwrap().world_transform();
+        assert!((world.position - glm::vec2(1.0, 1.0)).norm() < 1e-6);
+        assert_eq!(world.rotation, 90.0);
+
+        // Removing the parent removes the child too
+        scene_graph.remove(parent);
+        assert!(scene_graph.node(child).is_none());
+        assert!(scene_graph.visible_nodes().is_empty());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_scene_graph_save_load() {
+        let dir = std::env::temp_dir().join(format!("lavapond_scene_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("scene.json");
+
+        let mut scene_graph = SceneGraph::new();
+        let parent = scene_graph
+            .insert(
+                None,
+                SceneNode::new(
+                    Transform::from_position(1.0, 0.0),
+                    Some(NodeShape::Circle {
+                        color: glm::vec3(1.0, 0.0, 0.0),
+                    }),
+                )
+                .with_anchor(AnchorType::Locked),
+            )
+            .unwrap();
+        let child = scene_graph
+            .insert(
+                Some(parent),
+                SceneNode::new(
+                    Transform {
+                        rotation: 45.0,
+                        ..Transform::default()
+                    },
+                    Some(NodeShape::Text {
+                        text: "Árvíz".to_string(),
+                        params: TextParams::default(),
+                    }),
+                ),
+            )
+            .unwrap();
+        scene_graph.update();
+
+        // JSON round trip -> Same nodes
+        scene_graph.save(&path).unwrap();
+        let loaded = SceneGraph::load(&path).unwrap();
+        assert_eq!(format!("{:?}", loaded), format!("{:?}", scene_graph));
+
+        // Missing or malformed files -> Error instead of a panic
+        assert!(SceneGraph::load(dir.join("missing.json")).is_err());
+        for json in ["", "{", "[1, 2]", r#"{"nodes": 3, "roots": []}"#] {
+            std::fs::write(&path, json).unwrap();
+            assert!(SceneGraph::load(&path).is_err(), "{}", json);
+        }
+
+        // Unknown root or a cycle -> Error instead of a hanging update
+        let mut unknown_root = scene_graph.clone();
+        unknown_root.roots.push(NodeId(7));
+        let mut cycle = scene_graph.clone();
+        cycle.node_mut(child).unwrap().children.push(parent);
+
+        for broken in [unknown_root, cycle] {
+            broken.save(&path).unwrap();
+            assert!(SceneGraph::load(&path).is_err());
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
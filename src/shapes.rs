@@ -0,0 +1,726 @@
+// extern
+extern crate nalgebra_glm as glm;
+
+// intern
+use crate::{AnchorType, Renderer, RendererResult, WorldRect};
+pub mod generate;
+
+//==================================================
+//=== Line
+//==================================================
+
+/// Side length of the rectangle object at scale 1.0
+pub const RECTANGLE_SIZE: f32 = 0.2;
+
+/// Diameter of the circle object at scale 1.0
+pub const CIRCLE_SIZE: f32 = 0.2;
+
+/// Rectangle covering a line segment
+#[derive(Debug, Clone, Copy)]
+pub struct LineQuad {
+    pub center: glm::Vec2,
+    pub length: f32,
+    /// Rotation in degrees
+    pub rotation: f32,
+}
+
+/// Computes the rectangle covering the segment between `from` and `to`
+pub fn line_quad(from: glm::Vec2, to: glm::Vec2) -> LineQuad {
+    let direction = to - from;
+
+    LineQuad {
+        center: (from + to) * 0.5,
+        length: direction.norm(),
+        rotation: direction.y.atan2(direction.x).to_degrees(),
+    }
+}
+
+/// Ends of the dashes of a line, see [`LineStyle`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LineCap {
+    /// Cut off at the end points
+    #[default]
+    Butt,
+    /// Half circle around the end points
+    Round,
+}
+
+/// Corners between the segments of a polyline, see [`LineStyle`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LineJoint {
+    /// Filled with a circle
+    #[default]
+    Round,
+    /// Outer edges extended until they meet, sharp corners are beveled
+    Miter,
+}
+
+/// Dash pattern, ends and corners of lines, see `DrawParams::line_style`
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LineStyle {
+    /// Length of the dashes, `0.0` draws a solid line
+    pub dash_length: f32,
+    /// Length of the gaps between the dashes, `0.0` draws a solid line
+    pub gap_length: f32,
+    pub cap: LineCap,
+    pub joint: LineJoint,
+}
+
+impl LineStyle {
+    /// Creates a new [`LineStyle`] of dashes separated by gaps with butt caps and round joints
+    pub fn dashed(dash_length: f32, gap_length: f32) -> Self {
+        Self {
+            dash_length,
+            gap_length,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_cap(mut self, cap: LineCap) -> Self {
+        self.cap = cap;
+        self
+    }
+
+    pub fn with_joint(mut self, joint: LineJoint) -> Self {
+        self.joint = joint;
+        self
+    }
+}
+
+/// Miter length of sharp corners in half thicknesses, longer miters are beveled
+pub const MITER_LIMIT: f32 = 4.0;
+
+/// Splits the polyline through `points` into dashes of `dash_length` separated by `gap_length`
+///
+/// The pattern continues around the corners. Without a dash or gap length the whole polyline
+/// is a single dash
+pub fn dashes(points: &[glm::Vec2], dash_length: f32, gap_length: f32) -> Vec<Vec<glm::Vec2>> {
+    if points.len() < 2 {
+        return Vec::new();
+    }
+
+    if dash_length <= 0.0 || gap_length <= 0.0 {
+        return vec![points.to_vec()];
+    }
+
+    let mut dashes = Vec::new();
+    let mut dash = vec![points[0]];
+    let mut drawing = true;
+    let mut remaining = dash_length;
+
+    for segment in points.windows(2) {
+        let (from, to) = (segment[0], segment[1]);
+        let length = (to - from).norm();
+        let direction = (to - from) / length;
+        let mut travelled = 0.0;
+
+        // Pattern switches inside the segment
+        while length - travelled > remaining {
+            travelled += remaining;
+            let point = from + direction * travelled;
+
+            if drawing {
+                if dash.last() != Some(&point) {
+                    dash.push(point);
+                }
+                dashes.push(std::mem::take(&mut dash));
+                remaining = gap_length;
+            } else {
+                dash.push(point);
+                remaining = dash_length;
+            }
+            drawing = !drawing;
+        }
+
+        remaining -= length - travelled;
+        if drawing {
+            dash.push(to);
+        }
+    }
+
+    if dash.len() >= 2 {
+        dashes.push(dash);
+    }
+
+    dashes
+}
+
+/// Builds the outline of the wedge filling the outer side of the corner at `joint` between
+/// the lines of `thickness` coming from `prev` and going to `next`
+///
+/// Gives back the joint, the outer corners of the two lines and the miter point between them,
+/// without the miter point when it is longer than [`MITER_LIMIT`]. Straight joints give back
+/// no outline
+pub fn miter_joint(
+    prev: glm::Vec2,
+    joint: glm::Vec2,
+    next: glm::Vec2,
+    thickness: f32,
+) -> Vec<glm::Vec2> {
+    let (Some(incoming), Some(outgoing)) = (
+        (joint - prev).try_normalize(f32::EPSILON),
+        (next - joint).try_normalize(f32::EPSILON),
+    ) else {
+        return Vec::new();
+    };
+
+    let turn = cross_2d(incoming, outgoing);
+    if turn.abs() <= f32::EPSILON {
+        return Vec::new();
+    }
+
+    // Left turn -> Outer side on the right of the lines
+    let outward_normal =
+        |direction: glm::Vec2| glm::vec2(direction.y, -direction.x) * turn.signum();
+    let (incoming_normal, outgoing_normal) = (outward_normal(incoming), outward_normal(outgoing));
+
+    let half_thickness = thickness * 0.5;
+    let incoming_corner = joint + incoming_normal * half_thickness;
+    let outgoing_corner = joint + outgoing_normal * half_thickness;
+
+    let miter = (incoming_normal + outgoing_normal)
+        .try_normalize(f32::EPSILON)
+        .unwrap_or(incoming_normal);
+    let miter_length = half_thickness / miter.dot(&incoming_normal).max(f32::EPSILON);
+
+    if miter_length > half_thickness * MITER_LIMIT {
+        vec![joint, incoming_corner, outgoing_corner]
+    } else {
+        vec![
+            joint,
+            incoming_corner,
+            joint + miter * miter_length,
+            outgoing_corner,
+        ]
+    }
+}
+
+//==================================================
+//=== Border
+//==================================================
+
+/// Outline drawn over the edge of a filled shape, see `Renderer::circle_outlined`
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Border {
+    pub color: glm::Vec3,
+    /// Thickness inside the edge of the shape
+    pub width: f32,
+}
+
+impl Border {
+    /// Creates a new [`Border`] of `width` inside the edge
+    pub fn new(color: glm::Vec3, width: f32) -> Self {
+        Self { color, width }
+    }
+}
+
+//==================================================
+//=== Arrow
+//==================================================
+
+/// Thickness of the leader lines drawn by `Renderer::annotate`
+pub const LEADER_THICKNESS: f32 = 0.005;
+
+/// Triangle at the end of an arrow
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ArrowHead {
+    pub tip: glm::Vec2,
+    pub left: glm::Vec2,
+    pub right: glm::Vec2,
+    /// Middle of the back edge, where the shaft ends
+    pub base: glm::Vec2,
+}
+
+/// Computes the head of `head_size` length and width at `to` of the arrow from `from`
+///
+/// Arrows shorter than `head_size` are all head, zero length arrows have none
+pub fn arrow_head(from: glm::Vec2, to: glm::Vec2, head_size: f32) -> Option<ArrowHead> {
+    let length = (to - from).norm();
+    let direction = (to - from).try_normalize(f32::EPSILON)?;
+    let normal = glm::vec2(-direction.y, direction.x);
+
+    let head_length = head_size.min(length);
+    let base = to - direction * head_length;
+    let half_width = normal * (head_size * 0.5);
+
+    Some(ArrowHead {
+        tip: to,
+        left: base + half_width,
+        right: base - half_width,
+        base,
+    })
+}
+
+//==================================================
+//=== Bezier
+//==================================================
+
+/// Evaluates the cubic bezier curve of `control_points` at `t` (0.0 - 1.0)
+pub fn cubic_bezier_point(control_points: &[glm::Vec2; 4], t: f32) -> glm::Vec2 {
+    let [p0, p1, p2, p3] = control_points;
+    let u = 1.0 - t;
+
+    p0 * (u * u * u) + p1 * (3.0 * u * u * t) + p2 * (3.0 * u * t * t) + p3 * (t * t * t)
+}
+
+/// Tessellates the cubic bezier curve of `control_points` into `resolution` segments
+pub fn cubic_bezier(control_points: &[glm::Vec2; 4], resolution: usize) -> Vec<glm::Vec2> {
+    let resolution = resolution.max(1);
+
+    (0..=resolution)
+        .map(|i| cubic_bezier_point(control_points, i as f32 / resolution as f32))
+        .collect()
+}
+
+//==================================================
+//=== Polygon
+//==================================================
+
+/// Thickness of polygon outlines drawn without an explicit thickness
+pub const OUTLINE_THICKNESS: f32 = 0.01;
+
+/// Signed area of the outline, positive for counter-clockwise `points`
+pub fn signed_area(points: &[glm::Vec2]) -> f32 {
+    let mut area = 0.0;
+
+    for (i, a) in points.iter().enumerate() {
+        let b = points[(i + 1) % points.len()];
+        area += a.x * b.y - b.x * a.y;
+    }
+
+    area * 0.5
+}
+
+/// Triangulates a simple (convex or concave) outline with ear clipping
+///
+/// Gives back counter-clockwise triangles as indices into `points` in either winding order,
+/// self intersecting outlines are only partially triangulated
+pub fn triangulate(points: &[glm::Vec2]) -> Vec<u32> {
+    let mut indices = Vec::new();
+
+    if points.len() < 3 {
+        return indices;
+    }
+
+    let mut remaining: Vec<usize> = (0..points.len()).collect();
+    if signed_area(points) < 0.0 {
+        remaining.reverse();
+    }
+
+    let mut i = 0;
+    let mut attempts = 0;
+
+    while remaining.len() > 3 {
+        let n = remaining.len();
+        let (prev, curr, next) = (
+            remaining[(i + n - 1) % n],
+            remaining[i % n],
+            remaining[(i + 1) % n],
+        );
+
+        let (a, b, c) = (points[prev], points[curr], points[next]);
+        let cross = cross_2d(b - a, c - b);
+
+        if cross.abs() <= f32::EPSILON {
+            // Collinear -> Vertex adds no area
+            remaining.remove(i % n);
+            attempts = 0;
+        } else if cross > 0.0
+            && !remaining
+                .iter()
+                .filter(|&&j| j != prev && j != curr && j != next)
+                .any(|&j| in_triangle(points[j], a, b, c))
+        {
+            // Ear -> Clip
+            indices.extend([prev as u32, curr as u32, next as u32]);
+            remaining.remove(i % n);
+            attempts = 0;
+        } else {
+            i += 1;
+            attempts += 1;
+
+            // No ear left (self intersecting outline)
+            if attempts > n {
+                return indices;
+            }
+        }
+
+        i %= remaining.len();
+    }
+
+    let (a, b, c) = (remaining[0], remaining[1], remaining[2]);
+    if cross_2d(points[b] - points[a], points[c] - points[b]) > f32::EPSILON {
+        indices.extend([a as u32, b as u32, c as u32]);
+    }
+
+    indices
+}
+
+/// Builds a closed ring of `thickness` around the outline with mitered corners
+///
+/// Gives back the vertices of the ring and its counter-clockwise triangle indices
+pub fn polygon_outline(points: &[glm::Vec2], thickness: f32) -> (Vec<glm::Vec2>, Vec<u32>) {
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+
+    if points.len() < 2 {
+        return (vertices, indices);
+    }
+
+    let mut points = points.to_vec();
+    if signed_area(&points) < 0.0 {
+        points.reverse();
+    }
+
+    let n = points.len();
+    let half_thickness = thickness * 0.5;
+    let outward_normal = |from: glm::Vec2, to: glm::Vec2| {
+        let direction = (to - from)
+            .try_normalize(f32::EPSILON)
+            .unwrap_or(glm::vec2(1.0, 0.0));
+        glm::vec2(direction.y, -direction.x)
+    };
+
+    // Inner & outer vertex of each corner
+    for i in 0..n {
+        let prev = points[(i + n - 1) % n];
+        let curr = points[i];
+        let next = points[(i + 1) % n];
+
+        let prev_normal = outward_normal(prev, curr);
+        let next_normal = outward_normal(curr, next);
+        let miter = (prev_normal + next_normal)
+            .try_normalize(f32::EPSILON)
+            .unwrap_or(next_normal);
+
+        // Sharp corners would produce long spikes -> Limit miter length
+        let miter_length =
+            (half_thickness / miter.dot(&next_normal).max(0.25)).min(thickness * 2.0);
+
+        vertices.push(curr - miter * miter_length);
+        vertices.push(curr + miter * miter_length);
+    }
+
+    for i in 0..n {
+        let inner = (2 * i) as u32;
+        let outer = inner + 1;
+        let next_inner = (2 * ((i + 1) % n)) as u32;
+        let next_outer = next_inner + 1;
+
+        indices.extend([inner, outer, next_outer, inner, next_outer, next_inner]);
+    }
+
+    (vertices, indices)
+}
+
+fn cross_2d(a: glm::Vec2, b: glm::Vec2) -> f32 {
+    a.x * b.y - a.y * b.x
+}
+
+/// Checks if `p` is inside or on the edge of the counter-clockwise triangle `a` `b` `c`
+fn in_triangle(p: glm::Vec2, a: glm::Vec2, b: glm::Vec2, c: glm::Vec2) -> bool {
+    cross_2d(b - a, p - a) >= 0.0 && cross_2d(c - b, p - b) >= 0.0 && cross_2d(a - c, p - c) >= 0.0
+}
+
+//==================================================
+//=== Nine Slice
+//==================================================
+
+/// Part of a nine-slice rectangle
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Slice {
+    /// Offset from the center of the whole rectangle
+    pub center: glm::Vec2,
+    pub size: glm::Vec2,
+    /// Part of the image shown by the slice as offset (xy) and size (zw), top left is `(0.0, 0.0)`
+    pub uv_rect: glm::Vec4,
+}
+
+/// Splits a rectangle of `size` into a grid of 3 x 3 slices, top left first
+///
+/// The corners keep their `border` size and show `uv_border` of the image on each side,
+/// only the edges and the center stretch
+pub fn nine_slice(size: glm::Vec2, border: f32, uv_border: f32) -> [Slice; 9] {
+    let border = border.clamp(0.0, size.x.min(size.y) * 0.5);
+    let uv_border = uv_border.clamp(0.0, 0.5);
+
+    let (half_x, half_y) = (size.x * 0.5, size.y * 0.5);
+    let x = [-half_x, -half_x + border, half_x - border, half_x];
+    let y = [half_y, half_y - border, -half_y + border, -half_y];
+    let uv = [0.0, uv_border, 1.0 - uv_border, 1.0];
+
+    std::array::from_fn(|i| {
+        let (column, row) = (i % 3, i / 3);
+
+        Slice {
+            center: glm::vec2(
+                (x[column] + x[column + 1]) * 0.5,
+                (y[row] + y[row + 1]) * 0.5,
+            ),
+            size: glm::vec2(x[column + 1] - x[column], y[row] - y[row + 1]),
+            uv_rect: glm::vec4(
+                uv[column],
+                uv[row],
+                uv[column + 1] - uv[column],
+                uv[row + 1] - uv[row],
+            ),
+        }
+    })
+}
+
+//==================================================
+//=== Grid
+//==================================================
+
+/// Thickness of the lines drawn by `Renderer::draw_grid`
+pub const GRID_THICKNESS: f32 = 0.005;
+
+/// Thickness of the lines drawn by `Renderer::draw_axes`
+pub const AXIS_THICKNESS: f32 = 0.01;
+
+/// Gives back the endpoints of the grid lines on the multiples of `spacing` within `extent`
+/// of `center`, vertical lines first
+pub fn grid_lines(center: glm::Vec2, spacing: f32, extent: f32) -> Vec<(glm::Vec2, glm::Vec2)> {
+    if spacing <= 0.0 || extent <= 0.0 {
+        return Vec::new();
+    }
+
+    let (min, max) = (center.add_scalar(-extent), center.add_scalar(extent));
+    let multiples = |min: f32, max: f32| {
+        ((min / spacing).ceil() as i64..=(max / spacing).floor() as i64)
+            .map(move |i| i as f32 * spacing)
+    };
+
+    let vertical = multiples(min.x, max.x).map(|x| (glm::vec2(x, min.y), glm::vec2(x, max.y)));
+    let horizontal = multiples(min.y, max.y).map(|y| (glm::vec2(min.x, y), glm::vec2(max.x, y)));
+
+    vertical.chain(horizontal).collect()
+}
+
+//==================================================
+//=== Shape
+//==================================================
+
+/// Plain data drawn as a shape by [`Renderer::add_shape`]
+///
+/// - `(center, radius, color)` is a circle
+/// - `(rect, color)` is a rectangle covering the [`WorldRect`]
+/// - `(from, to, thickness, color)` is a line
+/// - `(points, color)` is a filled polygon
+pub trait Shape {
+    /// Creates and pushes the shape to draw
+    fn draw(&self, renderer: &mut Renderer, anchor_type: AnchorType) -> RendererResult<()>;
+}
+
+impl<S: Shape + ?Sized> Shape for &S {
+    fn draw(&self, renderer: &mut Renderer, anchor_type: AnchorType) -> RendererResult<()> {
+        (**self).draw(renderer, anchor_type)
+    }
+}
+
+impl Shape for (glm::Vec2, f32, glm::Vec3) {
+    fn draw(&self, renderer: &mut Renderer, anchor_type: AnchorType) -> RendererResult<()> {
+        let (center, radius, color) = *self;
+
+        renderer.circle(
+            2.0 * radius / CIRCLE_SIZE,
+            center.x,
+            center.y,
+            color,
+            anchor_type,
+        )
+    }
+}
+
+impl Shape for (WorldRect, glm::Vec3) {
+    fn draw(&self, renderer: &mut Renderer, anchor_type: AnchorType) -> RendererResult<()> {
+        let (rect, color) = *self;
+        let (center, size) = ((rect.min + rect.max) * 0.5, rect.max - rect.min);
+
+        renderer.rectangle(
+            size.x / RECTANGLE_SIZE,
+            size.y / RECTANGLE_SIZE,
+            0.0,
+            center.x,
+            center.y,
+            color,
+            anchor_type,
+        )
+    }
+}
+
+impl Shape for (glm::Vec2, glm::Vec2, f32, glm::Vec3) {
+    fn draw(&self, renderer: &mut Renderer, anchor_type: AnchorType) -> RendererResult<()> {
+        let (from, to, thickness, color) = *self;
+
+        renderer.line(from, to, thickness, color, anchor_type)
+    }
+}
+
+impl Shape for (&[glm::Vec2], glm::Vec3) {
+    fn draw(&self, renderer: &mut Renderer, anchor_type: AnchorType) -> RendererResult<()> {
+        renderer.polygon(self.0, self.1, true, anchor_type)
+    }
+}
+
+impl Shape for (Vec<glm::Vec2>, glm::Vec3) {
+    fn draw(&self, renderer: &mut Renderer, anchor_type: AnchorType) -> RendererResult<()> {
+        renderer.polygon(&self.0, self.1, true, anchor_type)
+    }
+}
+
+//==================================================
+//=== Unit Testing
+//==================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cubic_bezier() {
+        let control_points = [
+            glm::vec2(0.0, 0.0),
+            glm::vec2(0.0, 1.0),
+            glm::vec2(1.0, 1.0),
+            glm::vec2(1.0, 0.0),
+        ];
+
+        let points = cubic_bezier(&control_points, 4);
+
+        assert_eq!(points.len(), 5);
+        assert_eq!(points[0], control_points[0]);
+        assert_eq!(points[4], control_points[3]);
+        assert!((points[2] - glm::vec2(0.5, 0.75)).norm() < 1e-6);
+    }
+
+    #[test]
+    fn test_dashes() {
+        let points = [
+            glm::vec2(0.0, 0.0),
+            glm::vec2(3.0, 0.0),
+            glm::vec2(3.0, 3.0),
+        ];
+
+        // Solid -> Whole polyline
+        assert_eq!(dashes(&points, 0.0, 1.0), vec![points.to_vec()]);
+        assert!(dashes(&points[..1], 1.0, 1.0).is_empty());
+
+        // Dashes of 2.0 and gaps of 0.5 -> The second dash goes around the corner
+        let dashed = dashes(&points, 2.0, 0.5);
+        assert_eq!(
+            dashed,
+            vec![
+                vec![glm::vec2(0.0, 0.0), glm::vec2(2.0, 0.0)],
+                vec![
+                    glm::vec2(2.5, 0.0),
+                    glm::vec2(3.0, 0.0),
+                    glm::vec2(3.0, 1.5)
+                ],
+                vec![glm::vec2(3.0, 2.0), glm::vec2(3.0, 3.0)],
+            ]
+        );
+
+        // Dash ending on the corner -> No zero length segment
+        let dashed = dashes(&points, 3.0, 1.0);
+        assert_eq!(dashed[0], vec![glm::vec2(0.0, 0.0), glm::vec2(3.0, 0.0)]);
+    }
+
+    #[test]
+    fn test_arrow_head() {
+        let head = arrow_head(glm::vec2(0.0, 0.0), glm::vec2(2.0, 0.0), 0.5).unwrap();
+        assert_eq!(head.tip, glm::vec2(2.0, 0.0));
+        assert_eq!(head.base, glm::vec2(1.5, 0.0));
+        assert_eq!(head.left, glm::vec2(1.5, 0.25));
+        assert_eq!(head.right, glm::vec2(1.5, -0.25));
+
+        // Shorter than the head -> Head from the start, no length -> No head
+        let head = arrow_head(glm::vec2(0.0, 0.0), glm::vec2(0.0, 0.2), 0.5).unwrap();
+        assert_eq!(head.base, glm::vec2(0.0, 0.0));
+        assert!(arrow_head(glm::vec2(1.0, 1.0), glm::vec2(1.0, 1.0), 0.5).is_none());
+    }
+
+    #[test]
+    fn test_miter_joint() {
+        let prev = glm::vec2(-1.0, 0.0);
+        let joint = glm::vec2(0.0, 0.0);
+
+        // Left turn -> Square corner on the bottom right
+        let wedge = miter_joint(prev, joint, glm::vec2(0.0, 1.0), 0.2);
+        assert_eq!(wedge.len(), 4);
+        assert!((wedge[1] - glm::vec2(0.0, -0.1)).norm() < 1e-6);
+        assert!((wedge[2] - glm::vec2(0.1, -0.1)).norm() < 1e-6);
+        assert!((wedge[3] - glm::vec2(0.1, 0.0)).norm() < 1e-6);
+
+        // Turning back -> Beveled, straight -> Nothing to fill
+        assert_eq!(
+            miter_joint(prev, joint, glm::vec2(-1.0, 0.05), 0.2).len(),
+            3
+        );
+        assert!(miter_joint(prev, joint, glm::vec2(1.0, 0.0), 0.2).is_empty());
+    }
+
+    #[test]
+    fn test_triangulate() {
+        // Concave L shape in clockwise order
+        let points = [
+            glm::vec2(0.0, 0.0),
+            glm::vec2(0.0, 2.0),
+            glm::vec2(1.0, 2.0),
+            glm::vec2(1.0, 1.0),
+            glm::vec2(2.0, 1.0),
+            glm::vec2(2.0, 0.0),
+        ];
+
+        let indices = triangulate(&points);
+        assert_eq!(indices.len(), 12);
+
+        // Triangles are counter-clockwise and cover the whole outline
+        let mut area = 0.0;
+        for triangle in indices.chunks(3) {
+            let triangle_area = signed_area(&[
+                points[triangle[0] as usize],
+                points[triangle[1] as usize],
+                points[triangle[2] as usize],
+            ]);
+
+            assert!(triangle_area > 0.0);
+            area += triangle_area;
+        }
+        assert!((area - signed_area(&points).abs()).abs() < 1e-6);
+        assert!((area - 3.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_nine_slice() {
+        let slices = nine_slice(glm::vec2(4.0, 2.0), 0.5, 0.25);
+
+        // Corners keep their size in a non-uniform rectangle
+        assert_eq!(slices[0].size, glm::vec2(0.5, 0.5));
+        assert_eq!(slices[8].size, glm::vec2(0.5, 0.5));
+        assert_eq!(slices[0].center, glm::vec2(-1.75, 0.75));
+        assert_eq!(slices[0].uv_rect, glm::vec4(0.0, 0.0, 0.25, 0.25));
+
+        // Center stretches, showing the middle of the image
+        assert_eq!(slices[4].size, glm::vec2(3.0, 1.0));
+        assert_eq!(slices[4].center, glm::vec2(0.0, 0.0));
+        assert_eq!(slices[4].uv_rect, glm::vec4(0.25, 0.25, 0.5, 0.5));
+    }
+
+    #[test]
+    fn test_grid_lines() {
+        let lines = grid_lines(glm::vec2(0.3, 0.0), 0.5, 1.0);
+
+        // Lines stay on the multiples of the spacing when the center moves
+        let xs: Vec<f32> = lines.iter().take(4).map(|(from, _)| from.x).collect();
+        assert_eq!(xs, [-0.5, 0.0, 0.5, 1.0]);
+        assert_eq!(lines.len(), 4 + 5);
+        assert_eq!(lines[4], (glm::vec2(-0.7, -1.0), glm::vec2(1.3, -1.0)));
+
+        assert!(grid_lines(glm::vec2(0.0, 0.0), 0.0, 1.0).is_empty());
+    }
+}
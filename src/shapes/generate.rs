@@ -0,0 +1,283 @@
+// extern
+extern crate nalgebra_glm as glm;
+
+//==================================================
+//=== Shape Generation
+//==================================================
+
+/// Number of segments of a full circle when no count is requested
+pub const DEFAULT_SEGMENTS: u32 = 32;
+
+/// Ring of `thickness` inside the edge of a circle of `radius` around the origin
+///
+/// Gives back the vertices and their counter-clockwise triangle indices
+pub fn circle_border(radius: f32, thickness: f32, segments: u32) -> (Vec<glm::Vec2>, Vec<u32>) {
+    let inner_radius = (radius - thickness).max(0.0);
+
+    ring(
+        ellipse_outline(glm::vec2(radius, radius), segments),
+        ellipse_outline(glm::vec2(inner_radius, inner_radius), segments),
+        true,
+    )
+}
+
+/// Filled ellipse of `radii` along the x and y axes around the origin
+pub fn ellipse(radii: glm::Vec2, segments: u32) -> (Vec<glm::Vec2>, Vec<u32>) {
+    fan(ellipse_outline(radii, segments))
+}
+
+/// Border of `thickness` inside the edge of an ellipse of `radii`
+///
+/// The inner radii are shorter by `thickness`, so the border is exact at the ends of the axes
+pub fn ellipse_border(
+    radii: glm::Vec2,
+    thickness: f32,
+    segments: u32,
+) -> (Vec<glm::Vec2>, Vec<u32>) {
+    let inner_radii = radii.map(|radius| (radius - thickness).max(0.0));
+
+    ring(
+        ellipse_outline(radii, segments),
+        ellipse_outline(inner_radii, segments),
+        true,
+    )
+}
+
+/// Part of a ring of `thickness` inside a circle of `radius`, from `start_angle` sweeping
+/// `sweep` degrees counter-clockwise, eg. a progress ring
+///
+/// A negative `sweep` goes clockwise, a `thickness` of `radius` gives a pie slice. The arc
+/// gets its share of `segments`, at least one
+pub fn ring_sector(
+    radius: f32,
+    thickness: f32,
+    start_angle: f32,
+    sweep: f32,
+    segments: u32,
+) -> (Vec<glm::Vec2>, Vec<u32>) {
+    let inner_radius = (radius - thickness).max(0.0);
+    let sweep = sweep.clamp(-360.0, 360.0);
+
+    // Clockwise -> The same arc from its other end
+    let start_angle = start_angle + sweep.min(0.0);
+    let sweep = sweep.abs();
+
+    let arc_segments = ((segments.max(3) as f32 * sweep / 360.0).ceil() as u32).max(1);
+    let arc = |radius: f32| -> Vec<glm::Vec2> {
+        (0..=arc_segments)
+            .map(|i| {
+                let angle = (start_angle + sweep * i as f32 / arc_segments as f32).to_radians();
+                glm::vec2(angle.cos(), angle.sin()) * radius
+            })
+            .collect()
+    };
+
+    ring(arc(radius), arc(inner_radius), false)
+}
+
+/// Isosceles triangle of `width` x `height` around the origin with its tip up
+pub fn triangle(width: f32, height: f32) -> (Vec<glm::Vec2>, Vec<u32>) {
+    let (half_x, half_y) = (width * 0.5, height * 0.5);
+
+    (
+        vec![
+            glm::vec2(-half_x, -half_y),
+            glm::vec2(half_x, -half_y),
+            glm::vec2(0.0, half_y),
+        ],
+        vec![0, 1, 2],
+    )
+}
+
+/// Rectangle of `width` x `height` around the origin with half circles on its shorter sides
+pub fn capsule(width: f32, height: f32, segments: u32) -> (Vec<glm::Vec2>, Vec<u32>) {
+    rounded_rectangle(width, height, width.min(height) * 0.5, segments)
+}
+
+/// Filled rectangle of `width` x `height` around the origin with circular corners
+///
+/// `corner_radius` is clamped to half of the shorter side, each corner is made of a quarter of `segments`
+pub fn rounded_rectangle(
+    width: f32,
+    height: f32,
+    corner_radius: f32,
+    segments: u32,
+) -> (Vec<glm::Vec2>, Vec<u32>) {
+    fan(rounded_rectangle_outline(
+        width,
+        height,
+        corner_radius,
+        segments,
+    ))
+}
+
+/// Border of `thickness` inside the edge of a rounded rectangle of `width` x `height`
+///
+/// The inner corners keep the same center as the outer ones, so the border is even all around
+pub fn rounded_rectangle_border(
+    width: f32,
+    height: f32,
+    corner_radius: f32,
+    thickness: f32,
+    segments: u32,
+) -> (Vec<glm::Vec2>, Vec<u32>) {
+    let corner_radius = corner_radius.clamp(0.0, width.min(height) * 0.5);
+    let thickness = thickness.clamp(0.0, width.min(height) * 0.5);
+
+    ring(
+        rounded_rectangle_outline(width, height, corner_radius, segments),
+        rounded_rectangle_outline(
+            width - thickness * 2.0,
+            height - thickness * 2.0,
+            (corner_radius - thickness).max(0.0),
+            segments,
+        ),
+        true,
+    )
+}
+
+/// Counter-clockwise points of an ellipse, at least 3
+fn ellipse_outline(radii: glm::Vec2, segments: u32) -> Vec<glm::Vec2> {
+    let segments = segments.max(3);
+
+    (0..segments)
+        .map(|i| {
+            let angle = i as f32 / segments as f32 * std::f32::consts::TAU;
+            glm::vec2(angle.cos(), angle.sin()).component_mul(&radii)
+        })
+        .collect()
+}
+
+/// Counter-clockwise points of a rounded rectangle, the same count for any `corner_radius`
+fn rounded_rectangle_outline(
+    width: f32,
+    height: f32,
+    corner_radius: f32,
+    segments: u32,
+) -> Vec<glm::Vec2> {
+    let corner_radius = corner_radius.clamp(0.0, width.min(height) * 0.5);
+    let corner_segments = segments.div_ceil(4).max(1);
+
+    let half_x = width * 0.5 - corner_radius;
+    let half_y = height * 0.5 - corner_radius;
+
+    // Top right, top left, bottom left, bottom right
+    [
+        (half_x, half_y),
+        (-half_x, half_y),
+        (-half_x, -half_y),
+        (half_x, -half_y),
+    ]
+    .into_iter()
+    .enumerate()
+    .flat_map(|(corner, (center_x, center_y))| {
+        (0..=corner_segments).map(move |i| {
+            let angle =
+                (corner as f32 + i as f32 / corner_segments as f32) * std::f32::consts::FRAC_PI_2;
+            glm::vec2(center_x, center_y) + glm::vec2(angle.cos(), angle.sin()) * corner_radius
+        })
+    })
+    .collect()
+}
+
+/// Triangle fan from the center of a convex counter-clockwise `outline`
+fn fan(outline: Vec<glm::Vec2>) -> (Vec<glm::Vec2>, Vec<u32>) {
+    let n = outline.len() as u32;
+
+    let mut vertices = Vec::with_capacity(outline.len() + 1);
+    vertices.push(glm::vec2(0.0, 0.0));
+    vertices.extend(outline);
+
+    let indices = (0..n).flat_map(|i| [0, i + 1, (i + 1) % n + 1]).collect();
+
+    (vertices, indices)
+}
+
+/// Triangles between two counter-clockwise outlines with the same number of points, the last
+/// points are connected to the first ones when `closed`
+fn ring(outer: Vec<glm::Vec2>, inner: Vec<glm::Vec2>, closed: bool) -> (Vec<glm::Vec2>, Vec<u32>) {
+    let n = outer.len();
+    let quads = if closed { n } else { n.saturating_sub(1) };
+
+    let vertices = inner
+        .into_iter()
+        .zip(outer)
+        .flat_map(|(inner, outer)| [inner, outer])
+        .collect();
+
+    let indices = (0..quads)
+        .flat_map(|i| {
+            let inner = (2 * i) as u32;
+            let outer = inner + 1;
+            let next_inner = (2 * ((i + 1) % n)) as u32;
+            let next_outer = next_inner + 1;
+
+            [inner, outer, next_outer, inner, next_outer, next_inner]
+        })
+        .collect();
+
+    (vertices, indices)
+}
+
+//==================================================
+//=== Unit Testing
+//==================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_circle_border() {
+        let (vertices, indices) = circle_border(1.0, 0.25, 16);
+        assert_eq!(vertices.len(), 32);
+        assert_eq!(indices.len(), 16 * 6);
+
+        // Exact radii instead of a scaled ring
+        for pair in vertices.chunks(2) {
+            assert!((pair[0].norm() - 0.75).abs() < 1e-6);
+            assert!((pair[1].norm() - 1.0).abs() < 1e-6);
+        }
+
+        // Every triangle is counter-clockwise
+        for triangle in indices.chunks(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| vertices[triangle[i] as usize]);
+            let (ab, ac) = (b - a, c - a);
+            assert!(ab.x * ac.y - ab.y * ac.x > 0.0);
+        }
+    }
+
+    #[test]
+    fn test_ellipse_border() {
+        let (vertices, indices) = ellipse_border(glm::vec2(2.0, 1.0), 0.25, 16);
+        assert_eq!(vertices.len(), 32);
+        assert_eq!(indices.len(), 16 * 6);
+
+        // Inner and outer points at the ends of both axes
+        assert!((vertices[0] - glm::vec2(1.75, 0.0)).norm() < 1e-6);
+        assert!((vertices[1] - glm::vec2(2.0, 0.0)).norm() < 1e-6);
+        assert!((vertices[8] - glm::vec2(0.0, 0.75)).norm() < 1e-6);
+        assert!((vertices[9] - glm::vec2(0.0, 1.0)).norm() < 1e-6);
+    }
+
+    #[test]
+    fn test_ring_sector() {
+        // Quarter of 16 segments -> 4 quads between 5 point pairs
+        let (vertices, indices) = ring_sector(1.0, 0.5, 90.0, 90.0, 16);
+        assert_eq!(vertices.len(), 10);
+        assert_eq!(indices.len(), 4 * 6);
+        assert!((vertices[1] - glm::vec2(0.0, 1.0)).norm() < 1e-6);
+        assert!((vertices[9] - glm::vec2(-1.0, 0.0)).norm() < 1e-6);
+
+        // Clockwise sweep -> Same arc, still counter-clockwise triangles
+        let (clockwise, indices) = ring_sector(1.0, 0.5, 180.0, -90.0, 16);
+        for (a, b) in vertices.iter().zip(&clockwise) {
+            assert!((a - b).norm() < 1e-6);
+        }
+        for triangle in indices.chunks(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| clockwise[triangle[i] as usize]);
+            let (ab, ac) = (b - a, c - a);
+            assert!(ab.x * ac.y - ab.y * ac.x > 0.0);
+        }
+    }
+}
@@ -0,0 +1,363 @@
+// std
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+// extern
+extern crate nalgebra_glm as glm;
+
+// intern
+use crate::WorldRect;
+
+//==================================================
+//=== Spatial Grid
+//==================================================
+
+/// Uniform grid of ids and their bounding boxes, answering region, point and ray queries
+/// without testing every id
+///
+/// Boxes are kept in every cell they overlap, so the cell size should be around the size of
+/// a typical box. Boxes covering too many cells are kept aside and tested by every query
+#[derive(Debug, Clone)]
+pub struct SpatialGrid<T> {
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<T>>,
+    bounds: HashMap<T, WorldRect>,
+    oversized: Vec<T>,
+}
+
+impl<T: Copy + Eq + Hash> SpatialGrid<T> {
+    /// Most cells a single box is inserted into
+    const MAX_CELLS: i64 = 1024;
+
+    /// Creates a new empty [`SpatialGrid`] with square cells of `cell_size` world units
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size: if cell_size.is_finite() && cell_size > 0.0 {
+                cell_size
+            } else {
+                1.0
+            },
+            cells: HashMap::new(),
+            bounds: HashMap::new(),
+            oversized: Vec::new(),
+        }
+    }
+
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    pub fn len(&self) -> usize {
+        self.bounds.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bounds.is_empty()
+    }
+
+    /// Adds `id` with its `bounds`, an id already in the grid is moved
+    pub fn insert(&mut self, id: T, bounds: WorldRect) {
+        self.remove(id);
+
+        match self.cell_range(&bounds) {
+            Some((min, max)) => {
+                for x in min.0..=max.0 {
+                    for y in min.1..=max.1 {
+                        self.cells.entry((x, y)).or_default().push(id);
+                    }
+                }
+            }
+            None => self.oversized.push(id),
+        }
+
+        self.bounds.insert(id, bounds);
+    }
+
+    /// Removes `id` and gives back its bounds, `None` if it was not in the grid
+    pub fn remove(&mut self, id: T) -> Option<WorldRect> {
+        let bounds = self.bounds.remove(&id)?;
+
+        match self.cell_range(&bounds) {
+            Some((min, max)) => {
+                for x in min.0..=max.0 {
+                    for y in min.1..=max.1 {
+                        if let Some(cell) = self.cells.get_mut(&(x, y)) {
+                            cell.retain(|other| *other != id);
+
+                            if cell.is_empty() {
+                                self.cells.remove(&(x, y));
+                            }
+                        }
+                    }
+                }
+            }
+            None => self.oversized.retain(|other| *other != id),
+        }
+
+        Some(bounds)
+    }
+
+    pub fn bounds(&self, id: T) -> Option<WorldRect> {
+        self.bounds.get(&id).copied()
+    }
+
+    /// Removes every id, keeping the cell size
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.bounds.clear();
+        self.oversized.clear();
+    }
+
+    /// Gives back the ids whose bounds intersect `region`, each only once
+    pub fn query(&self, region: &WorldRect) -> Vec<T> {
+        let mut found = HashSet::new();
+        let mut ids = Vec::new();
+
+        let mut test = |id: &T| {
+            if !found.contains(id) && self.bounds[id].intersects(region) {
+                found.insert(*id);
+                ids.push(*id);
+            }
+        };
+
+        match self.cell_range(region) {
+            Some((min, max)) => {
+                for x in min.0..=max.0 {
+                    for y in min.1..=max.1 {
+                        self.cells
+                            .get(&(x, y))
+                            .into_iter()
+                            .flatten()
+                            .for_each(&mut test);
+                    }
+                }
+            }
+            // Region larger than the grid can walk -> Test every id
+            None => self.bounds.keys().for_each(&mut test),
+        }
+
+        self.oversized.iter().for_each(test);
+
+        ids
+    }
+
+    /// Gives back the ids whose bounds contain `point`
+    pub fn query_point(&self, point: glm::Vec2) -> Vec<T> {
+        self.query(&WorldRect::new(point, point))
+    }
+
+    /// Gives back the first id hit by the ray from `origin` towards `direction` and the distance
+    /// to its bounds, boxes containing the `origin` are hit at a distance of 0.0
+    ///
+    /// The cells are walked along the ray until a hit or `max_distance`
+    pub fn raycast(
+        &self,
+        origin: glm::Vec2,
+        direction: glm::Vec2,
+        max_distance: f32,
+    ) -> Option<(T, f32)> {
+        let direction = direction.try_normalize(f32::EPSILON)?;
+
+        let mut closest: Option<(T, f32)> = None;
+        let test = |ids: &[T], closest: &mut Option<(T, f32)>| {
+            for id in ids {
+                if let Some(distance) = ray_distance(origin, direction, &self.bounds[id]) {
+                    if distance <= max_distance && closest.is_none_or(|(_, other)| distance < other)
+                    {
+                        *closest = Some((*id, distance));
+                    }
+                }
+            }
+        };
+
+        test(&self.oversized, &mut closest);
+
+        let Some((first, last)) = self.occupied_cells() else {
+            return closest;
+        };
+
+        // Amanatides & Woo traversal, `next` is the distance to the next cell border on each axis
+        let mut cell = self.cell(origin);
+        let step = direction.map(|d| (d > 0.0) as i32 - (d < 0.0) as i32);
+        let delta = direction.map(|d| self.cell_size / d.abs());
+        let mut next = glm::vec2(
+            axis_border(origin.x, direction.x, cell.0, self.cell_size),
+            axis_border(origin.y, direction.y, cell.1, self.cell_size),
+        );
+
+        let mut distance = 0.0;
+        while distance <= max_distance {
+            // Left the occupied cells for good
+            let leaving = |cell: i32, first: i32, last: i32, step: i32| {
+                (cell < first && step <= 0) || (cell > last && step >= 0)
+            };
+            if leaving(cell.0, first.0, last.0, step.x) || leaving(cell.1, first.1, last.1, step.y)
+            {
+                break;
+            }
+
+            if let Some(ids) = self.cells.get(&cell) {
+                test(ids, &mut closest);
+            }
+
+            // Boxes of the later cells are farther than the end of this one
+            let cell_end = next.x.min(next.y);
+            if closest.is_some_and(|(_, hit)| hit <= cell_end) {
+                break;
+            }
+
+            if next.x < next.y {
+                cell.0 += step.x;
+                next.x += delta.x;
+            } else {
+                cell.1 += step.y;
+                next.y += delta.y;
+            }
+            distance = cell_end;
+        }
+
+        closest
+    }
+
+    /// Gives back the lowest and highest cell coordinates holding an id
+    fn occupied_cells(&self) -> Option<((i32, i32), (i32, i32))> {
+        self.cells.keys().fold(None, |range, &(x, y)| match range {
+            Some(((min_x, min_y), (max_x, max_y))) => {
+                Some(((x.min(min_x), y.min(min_y)), (x.max(max_x), y.max(max_y))))
+            }
+            None => Some(((x, y), (x, y))),
+        })
+    }
+
+    fn cell(&self, point: glm::Vec2) -> (i32, i32) {
+        (
+            (point.x / self.cell_size).floor() as i32,
+            (point.y / self.cell_size).floor() as i32,
+        )
+    }
+
+    /// Gives back the first and last cell overlapped by `rect`, `None` when it covers more
+    /// than [`SpatialGrid::MAX_CELLS`] cells or is not finite
+    fn cell_range(&self, rect: &WorldRect) -> Option<((i32, i32), (i32, i32))> {
+        if !(rect.min.x.is_finite()
+            && rect.min.y.is_finite()
+            && rect.max.x.is_finite()
+            && rect.max.y.is_finite())
+        {
+            return None;
+        }
+
+        let (min, max) = (self.cell(rect.min), self.cell(rect.max));
+        let cells = (max.0 as i64 - min.0 as i64 + 1) * (max.1 as i64 - min.1 as i64 + 1);
+
+        (cells <= Self::MAX_CELLS).then_some((min, max))
+    }
+}
+
+/// Distance along an axis to the first cell border crossed from `origin` in `cell`
+fn axis_border(origin: f32, direction: f32, cell: i32, cell_size: f32) -> f32 {
+    if direction > 0.0 {
+        ((cell + 1) as f32 * cell_size - origin) / direction
+    } else if direction < 0.0 {
+        (cell as f32 * cell_size - origin) / direction
+    } else {
+        f32::INFINITY
+    }
+}
+
+/// Distance from `origin` along the normalized `direction` to `rect`, slab test
+fn ray_distance(origin: glm::Vec2, direction: glm::Vec2, rect: &WorldRect) -> Option<f32> {
+    let mut near = 0.0f32;
+    let mut far = f32::INFINITY;
+
+    for axis in 0..2 {
+        if direction[axis] == 0.0 {
+            if origin[axis] < rect.min[axis] || origin[axis] > rect.max[axis] {
+                return None;
+            }
+            continue;
+        }
+
+        let first = (rect.min[axis] - origin[axis]) / direction[axis];
+        let second = (rect.max[axis] - origin[axis]) / direction[axis];
+        near = near.max(first.min(second));
+        far = far.min(first.max(second));
+    }
+
+    (near <= far).then_some(near)
+}
+
+//==================================================
+//=== Unit Testing
+//==================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(min_x: f32, min_y: f32, max_x: f32, max_y: f32) -> WorldRect {
+        WorldRect::new(glm::vec2(min_x, min_y), glm::vec2(max_x, max_y))
+    }
+
+    #[test]
+    fn test_spatial_grid_query() {
+        let mut grid = SpatialGrid::new(1.0);
+        grid.insert(0, rect(0.2, 0.2, 0.4, 0.4));
+        grid.insert(1, rect(-3.0, -0.5, 3.0, 0.5));
+        grid.insert(2, rect(5.0, 5.0, 5.5, 5.5));
+        grid.insert(3, rect(f32::NEG_INFINITY, -10.0, f32::INFINITY, -9.0));
+
+        // Boxes spanning many cells are found once
+        let mut ids = grid.query(&rect(-4.0, -1.0, 1.0, 1.0));
+        ids.sort();
+        assert_eq!(ids, vec![0, 1]);
+        assert_eq!(grid.query_point(glm::vec2(5.2, 5.2)), vec![2]);
+        assert_eq!(grid.query_point(glm::vec2(1e6, -9.5)), vec![3]);
+
+        // Moved and removed ids
+        grid.insert(2, rect(0.3, 0.3, 0.5, 0.5));
+        assert!(grid.query_point(glm::vec2(5.2, 5.2)).is_empty());
+        assert_eq!(grid.remove(1), Some(rect(-3.0, -0.5, 3.0, 0.5)));
+        assert_eq!(grid.remove(1), None);
+        let mut ids = grid.query(&rect(-4.0, -1.0, 1.0, 1.0));
+        ids.sort();
+        assert_eq!(ids, vec![0, 2]);
+        assert_eq!(grid.len(), 3);
+    }
+
+    #[test]
+    fn test_spatial_grid_raycast() {
+        let mut grid = SpatialGrid::new(0.5);
+        grid.insert("far", rect(4.0, -1.0, 4.5, 1.0));
+        grid.insert("near", rect(2.0, -0.1, 2.2, 0.1));
+        grid.insert("above", rect(1.0, 1.0, 1.2, 1.2));
+
+        let (id, distance) = grid
+            .raycast(glm::vec2(0.1, 0.0), glm::vec2(1.0, 0.0), 10.0)
+            .unwrap();
+        assert_eq!(id, "near");
+        assert!((distance - 1.9).abs() < 1e-5);
+
+        // Diagonal ray through a corner cell
+        let (id, _) = grid
+            .raycast(glm::vec2(0.0, 0.0), glm::vec2(1.0, 1.05), 10.0)
+            .unwrap();
+        assert_eq!(id, "above");
+
+        // Out of range and backwards rays
+        assert_eq!(
+            grid.raycast(glm::vec2(0.1, 0.0), glm::vec2(1.0, 0.0), 1.0),
+            None
+        );
+        assert_eq!(
+            grid.raycast(glm::vec2(0.1, 0.0), glm::vec2(-1.0, 0.0), f32::INFINITY),
+            None
+        );
+
+        // Started inside of a box
+        assert_eq!(
+            grid.raycast(glm::vec2(4.2, 0.0), glm::vec2(0.0, 1.0), 10.0),
+            Some(("far", 0.0))
+        );
+    }
+}
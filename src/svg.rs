@@ -0,0 +1,766 @@
+// std
+use std::path::Path;
+
+// extern
+extern crate nalgebra_glm as glm;
+use anyhow::{anyhow, Context, Result};
+
+// intern
+use crate::resources::{ObjectPool, Vertex};
+use crate::shapes::{self, RECTANGLE_SIZE};
+
+//==================================================
+//=== SVG Import
+//==================================================
+
+/// Segments of a tessellated curve and of a full circle
+const CURVE_SEGMENTS: usize = 16;
+const CIRCLE_SEGMENTS: usize = 48;
+
+/// Loads the filled shapes of the .svg file at `path`, see [`parse_svg`]
+pub fn load_svg(path: &Path) -> Result<ObjectPool> {
+    let source = std::fs::read_to_string(path)
+        .with_context(|| format!("SVG: Can't read {}", path.display()))?;
+    let name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    parse_svg(&source, &name)
+}
+
+/// Tessellates the filled `rect`, `circle`, `ellipse`, `polygon` and `path` elements of an SVG
+/// document into an object each, colored by their fill
+///
+/// * Paths support lines and cubic and quadratic beziers, arcs are replaced by a line to their
+///   end point. Every subpath is filled on its own, so holes are filled too
+/// * Fills are read from the `fill` attribute or `style`, inherited from the groups, as hex,
+///   `rgb()` or a few named colors. Strokes, gradients and opacity are ignored
+/// * `translate`, `scale`, `rotate` and `matrix` transforms are applied
+///
+/// The drawing is centered on the view box and scaled to fit the rectangle object, with the
+/// y axis pointing up. Objects are named by their `id`, or `{name}_{index}` without one
+pub fn parse_svg(source: &str, name: &str) -> Result<ObjectPool> {
+    let mut object_pool = ObjectPool::default();
+    let mut shapes: Vec<(String, glm::Vec3, Vec<Vec<glm::Vec2>>)> = Vec::new();
+    let mut view_box = None;
+
+    // Style of the open groups, the root style is the SVG default
+    let mut stack = vec![Style {
+        fill: Some(glm::vec3(0.0, 0.0, 0.0)),
+        transform: glm::Mat3::identity(),
+    }];
+
+    for tag in tags(source)? {
+        if tag.closing {
+            if tag.name == "g" || tag.name == "svg" {
+                if stack.len() == 1 {
+                    return Err(anyhow!("SVG: Unexpected closing tag </{}>", tag.name));
+                }
+                stack.pop();
+            }
+            continue;
+        }
+
+        let style = stack.last().unwrap().apply(&tag)?;
+
+        match tag.name {
+            "svg" if view_box.is_none() => view_box = document_view_box(&tag)?,
+            "rect" | "circle" | "ellipse" | "polygon" | "path" => {
+                if let Some(fill) = style.fill {
+                    let outlines = outlines(&tag)?
+                        .into_iter()
+                        .map(|outline| {
+                            outline
+                                .iter()
+                                .map(|point| {
+                                    (style.transform * glm::vec3(point.x, point.y, 1.0)).xy()
+                                })
+                                .collect()
+                        })
+                        .collect();
+
+                    let object_name = match tag.attribute("id") {
+                        Some(id) => id.to_string(),
+                        None => format!("{}_{}", name, shapes.len()),
+                    };
+                    shapes.push((object_name, fill, outlines));
+                }
+            }
+            _ => (),
+        }
+
+        if (tag.name == "g" || tag.name == "svg") && !tag.self_closing {
+            stack.push(style);
+        }
+    }
+
+    // Without a view box the shapes are fitted
+    let (min, size) = match view_box {
+        Some(view_box) => view_box,
+        None => {
+            let points: Vec<glm::Vec2> = shapes
+                .iter()
+                .flat_map(|(_, _, outlines)| outlines.iter().flatten().copied())
+                .collect();
+            let min = points
+                .iter()
+                .fold(glm::vec2(f32::MAX, f32::MAX), |min, point| {
+                    glm::min2(&min, point)
+                });
+            let max = points
+                .iter()
+                .fold(glm::vec2(f32::MIN, f32::MIN), |max, point| {
+                    glm::max2(&max, point)
+                });
+
+            (min, max - min)
+        }
+    };
+    let center = min + size * 0.5;
+    let scale = RECTANGLE_SIZE / size.max().max(f32::EPSILON);
+
+    for (object_name, fill, outlines) in shapes {
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+
+        for outline in outlines {
+            // Flipped before the triangulation, which keeps the winding order
+            let outline: Vec<glm::Vec2> = outline
+                .iter()
+                .map(|point| glm::vec2(point.x - center.x, center.y - point.y) * scale)
+                .collect();
+
+            let offset = vertices.len() as u32;
+            indices.extend(
+                shapes::triangulate(&outline)
+                    .iter()
+                    .map(|index| index + offset),
+            );
+            vertices.extend(outline.iter().map(|point| Vertex {
+                position: [point.x, point.y, 0.0],
+                color: fill.into(),
+            }));
+        }
+
+        if !indices.is_empty() {
+            object_pool.push_object(&object_name, &vertices, &indices)?;
+        }
+    }
+
+    Ok(object_pool)
+}
+
+/// Fill and transform of an element, inherited by the children of groups
+#[derive(Debug, Clone, Copy)]
+struct Style {
+    /// `None` is not filled
+    fill: Option<glm::Vec3>,
+    transform: glm::Mat3,
+}
+
+impl Style {
+    /// Gives back the style of `tag` inside an element of this style
+    fn apply(&self, tag: &Tag) -> Result<Style> {
+        let mut style = *self;
+
+        let fill = tag.attribute("fill").or_else(|| {
+            tag.attribute("style").and_then(|style| {
+                style.split(';').find_map(|declaration| {
+                    let (property, value) = declaration.split_once(':')?;
+                    (property.trim() == "fill").then_some(value.trim())
+                })
+            })
+        });
+
+        if let Some(fill) = fill {
+            style.fill = parse_color(fill)?;
+        }
+
+        if let Some(transform) = tag.attribute("transform") {
+            style.transform = self.transform * parse_transform(transform)?;
+        }
+
+        Ok(style)
+    }
+}
+
+/// Gives back the corner and size of the `viewBox`, or of the `width` and `height` of the
+/// `svg` element
+fn document_view_box(tag: &Tag) -> Result<Option<(glm::Vec2, glm::Vec2)>> {
+    if let Some(view_box) = tag.attribute("viewBox") {
+        let values = numbers(view_box)?;
+        if values.len() != 4 {
+            return Err(anyhow!("SVG: Invalid viewBox \"{}\"", view_box));
+        }
+
+        return Ok(Some((
+            glm::vec2(values[0], values[1]),
+            glm::vec2(values[2], values[3]),
+        )));
+    }
+
+    match (tag.length("width")?, tag.length("height")?) {
+        (Some(width), Some(height)) => Ok(Some((glm::Vec2::zeros(), glm::vec2(width, height)))),
+        _ => Ok(None),
+    }
+}
+
+/// Outlines of a shape element in document units
+fn outlines(tag: &Tag) -> Result<Vec<Vec<glm::Vec2>>> {
+    let length = |name: &str| -> Result<f32> { Ok(tag.length(name)?.unwrap_or(0.0)) };
+
+    let outline = match tag.name {
+        "rect" => {
+            let (x, y) = (length("x")?, length("y")?);
+            let (width, height) = (length("width")?, length("height")?);
+
+            vec![
+                glm::vec2(x, y),
+                glm::vec2(x + width, y),
+                glm::vec2(x + width, y + height),
+                glm::vec2(x, y + height),
+            ]
+        }
+        "circle" | "ellipse" => {
+            let center = glm::vec2(length("cx")?, length("cy")?);
+            let radius = match tag.name {
+                "circle" => glm::vec2(length("r")?, length("r")?),
+                _ => glm::vec2(length("rx")?, length("ry")?),
+            };
+
+            (0..CIRCLE_SEGMENTS)
+                .map(|i| {
+                    let angle = i as f32 / CIRCLE_SEGMENTS as f32 * std::f32::consts::TAU;
+                    center + glm::vec2(angle.cos(), angle.sin()).component_mul(&radius)
+                })
+                .collect()
+        }
+        "polygon" => numbers(tag.attribute("points").unwrap_or(""))?
+            .chunks_exact(2)
+            .map(|point| glm::vec2(point[0], point[1]))
+            .collect(),
+        "path" => return parse_path(tag.attribute("d").unwrap_or("")),
+        _ => Vec::new(),
+    };
+
+    Ok(vec![outline])
+}
+
+/// Flattens the path data `d` into an outline for each subpath
+fn parse_path(d: &str) -> Result<Vec<Vec<glm::Vec2>>> {
+    let mut outlines = Vec::new();
+    let mut outline: Vec<glm::Vec2> = Vec::new();
+    let mut tokens = PathTokens {
+        source: d,
+        position: 0,
+    };
+
+    let mut current = glm::Vec2::zeros();
+    let mut start = glm::Vec2::zeros();
+    // Second control point of the last curve, mirrored by the smooth curves
+    let mut last_control: Option<(char, glm::Vec2)> = None;
+    let mut command = None;
+
+    let finish = |outline: &mut Vec<glm::Vec2>, outlines: &mut Vec<Vec<glm::Vec2>>| {
+        // Closing point repeating the start adds no area
+        if outline.len() > 1 && outline.first() == outline.last() {
+            outline.pop();
+        }
+        if outline.len() >= 3 {
+            outlines.push(std::mem::take(outline));
+        }
+        outline.clear();
+    };
+
+    while let Some(token) = tokens.next_command() {
+        let command_char = match token {
+            Some(command_char) => command_char,
+            // Numbers after a command repeat it, a move continues as a line
+            None => match command {
+                Some('M') => 'L',
+                Some('m') => 'l',
+                Some(command_char) => command_char,
+                None => return Err(anyhow!("SVG: Path data \"{}\" starts with a number", d)),
+            },
+        };
+        command = Some(command_char);
+
+        let relative = command_char.is_ascii_lowercase();
+        let origin = if relative {
+            current
+        } else {
+            glm::Vec2::zeros()
+        };
+        let mut point = |tokens: &mut PathTokens| -> Result<glm::Vec2> {
+            Ok(origin + glm::vec2(tokens.number()?, tokens.number()?))
+        };
+
+        let mut control = None;
+        match command_char.to_ascii_uppercase() {
+            'M' => {
+                finish(&mut outline, &mut outlines);
+                current = point(&mut tokens)?;
+                start = current;
+                outline.push(current);
+            }
+            'L' => {
+                current = point(&mut tokens)?;
+                outline.push(current);
+            }
+            'H' => {
+                current.x = origin.x + tokens.number()?;
+                outline.push(current);
+            }
+            'V' => {
+                current.y = origin.y + tokens.number()?;
+                outline.push(current);
+            }
+            'C' | 'S' => {
+                let first = match command_char.to_ascii_uppercase() {
+                    'C' => point(&mut tokens)?,
+                    _ => mirrored(last_control, &['C', 'S'], current),
+                };
+                let second = point(&mut tokens)?;
+                let end = point(&mut tokens)?;
+
+                outline.extend(
+                    shapes::cubic_bezier(&[current, first, second, end], CURVE_SEGMENTS)
+                        .into_iter()
+                        .skip(1),
+                );
+                control = Some(('C', second));
+                current = end;
+            }
+            'Q' | 'T' => {
+                let quadratic = match command_char.to_ascii_uppercase() {
+                    'Q' => point(&mut tokens)?,
+                    _ => mirrored(last_control, &['Q'], current),
+                };
+                let end = point(&mut tokens)?;
+
+                // Quadratic curve as a cubic one
+                let first = current + (quadratic - current) * (2.0 / 3.0);
+                let second = end + (quadratic - end) * (2.0 / 3.0);
+                outline.extend(
+                    shapes::cubic_bezier(&[current, first, second, end], CURVE_SEGMENTS)
+                        .into_iter()
+                        .skip(1),
+                );
+                control = Some(('Q', quadratic));
+                current = end;
+            }
+            'A' => {
+                // Radii, rotation and flags -> Straight line to the end point
+                for _ in 0..5 {
+                    tokens.number()?;
+                }
+                current = point(&mut tokens)?;
+                outline.push(current);
+            }
+            'Z' => {
+                finish(&mut outline, &mut outlines);
+                current = start;
+                command = None;
+            }
+            _ => return Err(anyhow!("SVG: Unknown path command '{}'", command_char)),
+        }
+        last_control = control;
+    }
+
+    finish(&mut outline, &mut outlines);
+
+    Ok(outlines)
+}
+
+/// Reflection of the last control point around `current` when the last curve was one of
+/// `kinds`, otherwise `current`
+fn mirrored(
+    last_control: Option<(char, glm::Vec2)>,
+    kinds: &[char],
+    current: glm::Vec2,
+) -> glm::Vec2 {
+    match last_control {
+        Some((kind, control)) if kinds.contains(&kind) => current * 2.0 - control,
+        _ => current,
+    }
+}
+
+/// Commands and numbers of path data
+struct PathTokens<'a> {
+    source: &'a str,
+    position: usize,
+}
+
+impl PathTokens<'_> {
+    /// Gives back the next command letter, `Some(None)` when a number follows instead
+    fn next_command(&mut self) -> Option<Option<char>> {
+        self.skip_separators();
+
+        let next = self.source[self.position..].chars().next()?;
+        if next.is_ascii_alphabetic() && next != 'e' && next != 'E' {
+            self.position += 1;
+            Some(Some(next))
+        } else {
+            Some(None)
+        }
+    }
+
+    fn number(&mut self) -> Result<f32> {
+        self.skip_separators();
+
+        let rest = &self.source[self.position..];
+        let length = number_length(rest);
+        let number = rest[..length]
+            .parse()
+            .map_err(|_| anyhow!("SVG: Expected a number at \"{}\"", rest))?;
+
+        self.position += length;
+        Ok(number)
+    }
+
+    fn skip_separators(&mut self) {
+        let rest = &self.source[self.position..];
+        self.position += rest.len() - rest.trim_start_matches([' ', ',', '\t', '\n', '\r']).len();
+    }
+}
+
+/// Length of the number at the start of `text`, eg. `1.5` of `1.5.5` or `-2e3` of `-2e3-1`
+fn number_length(text: &str) -> usize {
+    let bytes = text.as_bytes();
+    let mut length = 0;
+    let mut dot = false;
+    let mut exponent = false;
+
+    while length < bytes.len() {
+        match bytes[length] {
+            b'+' | b'-' if length == 0 || matches!(bytes[length - 1], b'e' | b'E') => (),
+            b'0'..=b'9' => (),
+            b'.' if !dot && !exponent => dot = true,
+            b'e' | b'E' if !exponent && length > 0 => exponent = true,
+            _ => break,
+        }
+        length += 1;
+    }
+
+    length
+}
+
+/// Numbers separated by whitespace or commas
+fn numbers(text: &str) -> Result<Vec<f32>> {
+    let mut tokens = PathTokens {
+        source: text,
+        position: 0,
+    };
+    let mut numbers = Vec::new();
+
+    while tokens.next_command() == Some(None) {
+        numbers.push(tokens.number()?);
+    }
+
+    Ok(numbers)
+}
+
+/// Parses `#rgb`, `#rrggbb`, `rgb(r, g, b)` and a few named colors, `none` is no fill
+fn parse_color(text: &str) -> Result<Option<glm::Vec3>> {
+    let text = text.trim();
+    let invalid = || anyhow!("SVG: Unsupported color \"{}\"", text);
+
+    if let Some(hex) = text.strip_prefix('#') {
+        let channel = |digits: &str| u8::from_str_radix(digits, 16).map_err(|_| invalid());
+
+        let rgb = match hex.len() {
+            3 => {
+                let digit = |i: usize| channel(&hex[i..i + 1]).map(|value| value * 17);
+                [digit(0)?, digit(1)?, digit(2)?]
+            }
+            6 => [
+                channel(&hex[0..2])?,
+                channel(&hex[2..4])?,
+                channel(&hex[4..6])?,
+            ],
+            _ => return Err(invalid()),
+        };
+
+        return Ok(Some(
+            glm::vec3(rgb[0] as f32, rgb[1] as f32, rgb[2] as f32) / 255.0,
+        ));
+    }
+
+    if let Some(channels) = text
+        .strip_prefix("rgb(")
+        .and_then(|text| text.strip_suffix(')'))
+    {
+        let channels = numbers(channels)?;
+        if channels.len() != 3 {
+            return Err(invalid());
+        }
+
+        return Ok(Some(
+            glm::vec3(channels[0], channels[1], channels[2]) / 255.0,
+        ));
+    }
+
+    let color = match text {
+        "none" | "transparent" => return Ok(None),
+        "black" => glm::vec3(0.0, 0.0, 0.0),
+        "white" => glm::vec3(1.0, 1.0, 1.0),
+        "gray" | "grey" => glm::vec3(0.5, 0.5, 0.5),
+        "red" => glm::vec3(1.0, 0.0, 0.0),
+        "green" => glm::vec3(0.0, 0.5, 0.0),
+        "lime" => glm::vec3(0.0, 1.0, 0.0),
+        "blue" => glm::vec3(0.0, 0.0, 1.0),
+        "yellow" => glm::vec3(1.0, 1.0, 0.0),
+        "orange" => glm::vec3(1.0, 0.65, 0.0),
+        _ => return Err(invalid()),
+    };
+
+    Ok(Some(color))
+}
+
+/// Parses a list of `translate`, `scale`, `rotate` and `matrix` transforms into a matrix
+fn parse_transform(text: &str) -> Result<glm::Mat3> {
+    let mut transform = glm::Mat3::identity();
+    let mut rest = text.trim();
+
+    while !rest.is_empty() {
+        let (function, after) = rest
+            .split_once('(')
+            .ok_or_else(|| anyhow!("SVG: Invalid transform \"{}\"", text))?;
+        let (arguments, after) = after
+            .split_once(')')
+            .ok_or_else(|| anyhow!("SVG: Invalid transform \"{}\"", text))?;
+        let values = numbers(arguments)?;
+        let value = |i: usize| values.get(i).copied();
+
+        let matrix = match (function.trim(), values.len()) {
+            ("translate", 1 | 2) => {
+                glm::translation2d(&glm::vec2(values[0], value(1).unwrap_or(0.0)))
+            }
+            ("scale", 1 | 2) => {
+                glm::scaling2d(&glm::vec2(values[0], value(1).unwrap_or(values[0])))
+            }
+            ("rotate", 1 | 3) => {
+                let pivot = glm::vec2(value(1).unwrap_or(0.0), value(2).unwrap_or(0.0));
+                glm::translation2d(&pivot)
+                    * glm::rotation2d(values[0].to_radians())
+                    * glm::translation2d(&-pivot)
+            }
+            ("matrix", 6) => glm::mat3(
+                values[0], values[2], values[4], values[1], values[3], values[5], 0.0, 0.0, 1.0,
+            ),
+            _ => return Err(anyhow!("SVG: Unsupported transform \"{}\"", text)),
+        };
+
+        transform *= matrix;
+        rest = after.trim_start_matches([' ', ',']).trim();
+    }
+
+    Ok(transform)
+}
+
+//==================================================
+//=== Tags
+//==================================================
+
+/// Start, end or empty element tag of the document
+#[derive(Debug, Clone)]
+struct Tag<'a> {
+    name: &'a str,
+    attributes: Vec<(&'a str, &'a str)>,
+    closing: bool,
+    self_closing: bool,
+}
+
+impl<'a> Tag<'a> {
+    fn attribute(&self, name: &str) -> Option<&'a str> {
+        self.attributes
+            .iter()
+            .find(|(attribute, _)| *attribute == name)
+            .map(|(_, value)| *value)
+    }
+
+    /// Length attribute in user units, the `px` suffix is allowed
+    fn length(&self, name: &str) -> Result<Option<f32>> {
+        self.attribute(name)
+            .map(|value| {
+                value
+                    .trim()
+                    .trim_end_matches("px")
+                    .parse()
+                    .map_err(|_| anyhow!("SVG: Invalid {} \"{}\"", name, value))
+            })
+            .transpose()
+    }
+}
+
+/// Splits the document into its tags, skipping text, comments and declarations
+fn tags(source: &str) -> Result<Vec<Tag<'_>>> {
+    let mut tags = Vec::new();
+    let mut rest = source;
+
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+
+        let skip_until = if rest.starts_with("!--") {
+            Some("-->")
+        } else if rest.starts_with("![CDATA[") {
+            Some("]]>")
+        } else if rest.starts_with('?') || rest.starts_with('!') {
+            Some(">")
+        } else {
+            None
+        };
+
+        if let Some(end_marker) = skip_until {
+            let end = rest
+                .find(end_marker)
+                .ok_or_else(|| anyhow!("SVG: Missing \"{}\"", end_marker))?;
+            rest = &rest[end + end_marker.len()..];
+            continue;
+        }
+
+        let end = tag_end(rest).ok_or_else(|| anyhow!("SVG: Unclosed tag"))?;
+        let content = &rest[..end];
+        rest = &rest[end + 1..];
+
+        let closing = content.starts_with('/');
+        let self_closing = content.ends_with('/');
+        let content = content.trim_start_matches('/').trim_end_matches('/');
+
+        let name_end = content
+            .find(|c: char| c.is_whitespace())
+            .unwrap_or(content.len());
+        let name = &content[..name_end];
+
+        // Namespaced elements like `svg:path` of some editors
+        let name = name.rsplit(':').next().unwrap_or(name);
+
+        tags.push(Tag {
+            name,
+            attributes: attributes(&content[name_end..])?,
+            closing,
+            self_closing,
+        });
+    }
+
+    Ok(tags)
+}
+
+/// Position of the `>` ending the tag, skipping the ones inside of quoted values
+fn tag_end(text: &str) -> Option<usize> {
+    let mut quote = None;
+
+    for (i, c) in text.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(open), _) if open == c => quote = None,
+            (None, '>') => return Some(i),
+            _ => (),
+        }
+    }
+
+    None
+}
+
+/// Parses `name="value"` pairs
+fn attributes(mut text: &str) -> Result<Vec<(&str, &str)>> {
+    let mut attributes = Vec::new();
+
+    loop {
+        text = text.trim_start();
+        if text.is_empty() {
+            return Ok(attributes);
+        }
+
+        let (name, after) = text
+            .split_once('=')
+            .ok_or_else(|| anyhow!("SVG: Attribute without value \"{}\"", text))?;
+        let after = after.trim_start();
+        let quote = after
+            .chars()
+            .next()
+            .filter(|c| *c == '"' || *c == '\'')
+            .ok_or_else(|| anyhow!("SVG: Unquoted attribute {}", name.trim()))?;
+        let value_end = after[1..]
+            .find(quote)
+            .ok_or_else(|| anyhow!("SVG: Unclosed attribute {}", name.trim()))?;
+
+        let name = name.trim();
+        attributes.push((
+            name.rsplit(':').next().unwrap_or(name),
+            &after[1..1 + value_end],
+        ));
+        text = &after[value_end + 2..];
+    }
+}
+
+//==================================================
+//=== Unit Testing
+//==================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_svg() {
+        let source = r##"<?xml version="1.0"?>
+            <!-- Inkscape -->
+            <svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 100 100">
+                <rect id="background" width="100" height="100" fill="#fff"/>
+                <g style="fill:rgb(255, 0, 0)" transform="translate(50 50)">
+                    <circle r="10"/>
+                    <path d="M-10-10h20v20H-10z m30,0 l5 0 l0 5z" fill="none"/>
+                    <path d="M0 0 C10 0 10 10 0 10 S-10 0 0 0"/>
+                </g>
+                <polygon points="0,0 10,0 0,10" fill="#00F"/>
+            </svg>"##;
+
+        let object_pool = parse_svg(source, "test").unwrap();
+        let names: Vec<&str> = object_pool
+            .pool
+            .iter()
+            .map(|object_data| object_data.name.as_str())
+            .collect();
+        assert_eq!(names, ["background", "test_1", "test_2", "test_3"]);
+
+        // View box -> Size of the rectangle object, y axis up
+        let background = &object_pool.pool[0];
+        assert!((background.bounds_min - glm::vec2(-0.1, -0.1)).norm() < 1e-5);
+        assert!((background.bounds_max - glm::vec2(0.1, 0.1)).norm() < 1e-5);
+        assert_eq!(object_pool.vertices[0].color, [1.0, 1.0, 1.0]);
+
+        // Inherited fill and transform
+        let circle = &object_pool.pool[1];
+        assert!(((circle.bounds_min + circle.bounds_max) * 0.5).norm() < 1e-5);
+        assert!((circle.width() - 0.04).abs() < 1e-4);
+        let circle_vertex = object_pool.vertices[object_pool.indices[circle.index_offset] as usize];
+        assert_eq!(circle_vertex.color, [1.0, 0.0, 0.0]);
+
+        // Upper left corner of the document is at the top
+        let polygon = &object_pool.pool[3];
+        assert!((polygon.bounds_max - glm::vec2(-0.08, 0.1)).norm() < 1e-5);
+    }
+
+    #[test]
+    fn test_parse_path() {
+        let outlines = parse_path("M0,0L1-1.5.5 1e1 10,0zm2 2h1v1").unwrap();
+        assert_eq!(outlines.len(), 2);
+        assert_eq!(
+            outlines[0],
+            vec![
+                glm::vec2(0.0, 0.0),
+                glm::vec2(1.0, -1.5),
+                glm::vec2(0.5, 10.0),
+                glm::vec2(10.0, 0.0),
+            ]
+        );
+        assert_eq!(outlines[1][0], glm::vec2(2.0, 2.0));
+        assert_eq!(outlines[1][2], glm::vec2(3.0, 3.0));
+
+        assert!(parse_path("0 0").is_err());
+        assert!(parse_color("#12").is_err());
+        assert_eq!(parse_color("none").unwrap(), None);
+    }
+}
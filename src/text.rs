@@ -0,0 +1,310 @@
+// extern
+extern crate nalgebra_glm as glm;
+
+//==================================================
+//=== Text Parameters
+//==================================================
+
+/// Height of a line at scale 1.0
+pub const LINE_HEIGHT: f32 = 0.05;
+
+/// Gap between two characters at scale 1.0
+pub const CHAR_SPACING: f32 = 0.005;
+
+/// Advance of a space character at scale 1.0
+pub const SPACE_ADVANCE: f32 = 0.03;
+
+/// Distance of the outline copies from the glyphs at scale 1.0
+pub const OUTLINE_WIDTH: f32 = 0.004;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HorizontalAlign {
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum VerticalAlign {
+    Top,
+    Center,
+    Bottom,
+}
+
+/// Layout and style parameters of a text
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TextParams {
+    pub scale: f32,
+    pub horizontal_align: HorizontalAlign,
+    pub vertical_align: VerticalAlign,
+    /// Multiplier of the default line height
+    pub line_spacing: f32,
+    /// Lines are wrapped at word borders when they would be wider than this
+    pub max_width: Option<f32>,
+    /// Multiplies the colors of the glyphs
+    pub color: glm::Vec3,
+    /// Extra distance between two characters at scale 1.0
+    pub letter_spacing: f32,
+    /// Color of an outline drawn behind the glyphs, `None` draws no outline
+    pub outline: Option<glm::Vec3>,
+}
+
+impl Default for TextParams {
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            horizontal_align: HorizontalAlign::Left,
+            vertical_align: VerticalAlign::Top,
+            line_spacing: 1.0,
+            max_width: None,
+            color: glm::vec3(0.0, 0.0, 0.0),
+            letter_spacing: 0.0,
+            outline: None,
+        }
+    }
+}
+
+/// Part of a rich text drawn with its own colors, the layout is shared by every span
+#[derive(Debug, Clone, Copy)]
+pub struct TextSpan<'a> {
+    pub text: &'a str,
+    /// Multiplies the colors of the glyphs
+    pub color: glm::Vec3,
+    /// Color of an outline drawn behind the glyphs, `None` draws no outline
+    pub outline: Option<glm::Vec3>,
+}
+
+impl<'a> TextSpan<'a> {
+    /// Creates a new [`TextSpan`] of `text` with the colors of `params`
+    pub fn new(text: &'a str, params: &TextParams) -> Self {
+        Self {
+            text,
+            color: params.color,
+            outline: params.outline,
+        }
+    }
+}
+
+/// Gives back the character index where each of the `spans` ends in their joined text
+pub fn span_ends(spans: &[TextSpan]) -> Vec<usize> {
+    spans
+        .iter()
+        .scan(0, |end, span| {
+            *end += span.text.chars().count();
+            Some(*end)
+        })
+        .collect()
+}
+
+/// Gives back the index of the span containing the character at `index`
+pub fn span_at(span_ends: &[usize], index: usize) -> usize {
+    span_ends
+        .partition_point(|&end| end <= index)
+        .min(span_ends.len().saturating_sub(1))
+}
+
+//==================================================
+//=== Text Layout
+//==================================================
+
+/// Metrics of a single character at scale 1.0
+#[derive(Debug, Clone, Copy)]
+pub struct Glyph {
+    /// Identifier of the drawn glyph (eg. object index), `None` for invisible characters like space
+    pub id: Option<usize>,
+    /// Horizontal distance between the cursor and the glyph center
+    pub offset_x: f32,
+    /// Vertical distance between the baseline and the glyph center
+    pub offset_y: f32,
+    /// Horizontal distance to the next character
+    pub advance: f32,
+}
+
+/// A character placed by [`layout_text`]
+#[derive(Debug, Clone, Copy)]
+pub struct GlyphPlacement {
+    pub id: usize,
+    /// Index of the character in the text
+    pub index: usize,
+    /// Glyph center, relative to the text anchor
+    pub position: glm::Vec2,
+}
+
+/// Places the characters of `text` relative to the anchor point based on `params`
+///
+/// `glyph` gives back the metrics of a character, characters without metrics are skipped
+pub fn layout_text(
+    text: &str,
+    params: &TextParams,
+    glyph: impl Fn(char) -> Option<Glyph>,
+) -> Vec<GlyphPlacement> {
+    let scale = params.scale;
+    let line_height = LINE_HEIGHT * params.line_spacing * scale;
+    let advance = |g: &Glyph| (g.advance + params.letter_spacing) * scale;
+
+    /* 1. Break Text Into Lines */
+
+    let word_width =
+        |word: &str| -> f32 { word.chars().filter_map(&glyph).map(|g| advance(&g)).sum() };
+    let space_width = glyph(' ').map_or((SPACE_ADVANCE + params.letter_spacing) * scale, |g| {
+        advance(&g)
+    });
+
+    // Lines with the index of their first character in the text
+    let mut lines: Vec<(usize, String)> = Vec::new();
+    let mut paragraph_start = 0;
+
+    for paragraph in text.split('\n') {
+        let word_starts = paragraph_start;
+        paragraph_start += paragraph.chars().count() + 1;
+
+        let max_width = match params.max_width {
+            Some(max_width) => max_width,
+            None => {
+                lines.push((word_starts, paragraph.to_string()));
+                continue;
+            }
+        };
+
+        let mut line = String::new();
+        let mut line_start = word_starts;
+        let mut line_width = 0.0;
+        let mut word_start = word_starts;
+
+        for word in paragraph.split(' ') {
+            let width = word_width(word);
+
+            if !line.is_empty() && line_width + space_width + width > max_width {
+                lines.push((line_start, std::mem::take(&mut line)));
+                line_width = 0.0;
+            }
+
+            if line.is_empty() {
+                line_start = word_start;
+            } else {
+                line.push(' ');
+                line_width += space_width;
+            }
+
+            line.push_str(word);
+            line_width += width;
+            word_start += word.chars().count() + 1;
+        }
+
+        lines.push((line_start, line));
+    }
+
+    /* 2. Place Characters */
+
+    let text_height = lines.len() as f32 * line_height;
+    let offset_y = match params.vertical_align {
+        VerticalAlign::Top => 0.0,
+        VerticalAlign::Center => text_height * 0.5,
+        VerticalAlign::Bottom => text_height,
+    };
+
+    let mut placements = Vec::with_capacity(text.len());
+
+    for (line_index, (line_start, line)) in lines.iter().enumerate() {
+        let offset_x = match params.horizontal_align {
+            HorizontalAlign::Left => 0.0,
+            HorizontalAlign::Center => -word_width(line) * 0.5,
+            HorizontalAlign::Right => -word_width(line),
+        };
+
+        let baseline = offset_y - (line_index + 1) as f32 * line_height;
+        let mut cursor = offset_x;
+
+        for (char_offset, c) in line.chars().enumerate() {
+            let Some(glyph) = glyph(c) else {
+                continue;
+            };
+
+            if let Some(id) = glyph.id {
+                placements.push(GlyphPlacement {
+                    id,
+                    index: line_start + char_offset,
+                    position: glm::vec2(
+                        cursor + glyph.offset_x * scale,
+                        baseline + glyph.offset_y * scale,
+                    ),
+                });
+            }
+
+            cursor += advance(&glyph);
+        }
+    }
+
+    placements
+}
+
+//==================================================
+//=== Unit Testing
+//==================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glyph(c: char) -> Option<Glyph> {
+        match c {
+            ' ' => Some(Glyph {
+                id: None,
+                offset_x: 0.0,
+                offset_y: 0.0,
+                advance: 1.0,
+            }),
+            'a'..='z' => Some(Glyph {
+                id: Some(c as usize),
+                offset_x: 0.5,
+                offset_y: 0.0,
+                advance: 1.0,
+            }),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_layout_text() {
+        let params = TextParams {
+            horizontal_align: HorizontalAlign::Center,
+            max_width: Some(5.0),
+            ..TextParams::default()
+        };
+
+        let placements = layout_text("abc def ghi", &params, glyph);
+
+        // "abc def" is 7 wide -> every word is placed on its own line
+        assert_eq!(placements.len(), 9);
+        assert_eq!(placements[0].position, glm::vec2(-1.0, -LINE_HEIGHT));
+        assert_eq!(placements[3].position, glm::vec2(-1.0, -LINE_HEIGHT * 2.0));
+        assert_eq!(placements[8].position, glm::vec2(1.0, -LINE_HEIGHT * 3.0));
+
+        // Characters keep their index in the text across wrapped lines
+        assert_eq!(placements[3].index, 4);
+        assert_eq!(placements[8].index, 10);
+
+        let spans = [
+            TextSpan::new("abc ", &params),
+            TextSpan::new("", &params),
+            TextSpan::new("def ghi", &params),
+        ];
+        let ends = span_ends(&spans);
+        assert_eq!(span_at(&ends, 3), 0);
+        assert_eq!(span_at(&ends, 4), 2);
+        assert_eq!(span_at(&ends, 42), 2);
+
+        // Letter spacing widens the words -> "ab" is 3 wide
+        let params = TextParams {
+            letter_spacing: 0.5,
+            ..TextParams::default()
+        };
+
+        let placements = layout_text("ab ab", &params, glyph);
+        assert_eq!(placements[1].position, glm::vec2(2.0, -LINE_HEIGHT));
+        assert_eq!(placements[2].position, glm::vec2(5.0, -LINE_HEIGHT));
+    }
+}
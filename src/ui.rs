@@ -0,0 +1,824 @@
+// std
+#[cfg(feature = "winit")]
+use std::collections::HashSet;
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    ops::RangeInclusive,
+    time::{Duration, Instant},
+};
+
+// extern
+extern crate nalgebra_glm as glm;
+use anyhow::Result;
+#[cfg(feature = "winit")]
+use winit::event::{
+    ElementState, Ime, KeyboardInput, MouseButton, MouseScrollDelta, VirtualKeyCode, WindowEvent,
+};
+
+// intern
+use crate::shapes::RECTANGLE_SIZE;
+use crate::text::{HorizontalAlign, TextParams, VerticalAlign};
+use crate::{AnchorType, Renderer};
+
+//==================================================
+//=== Inputs
+//==================================================
+
+/// Mouse, keyboard, text and gamepad input of a frame, the mouse is used by the [`Ui`] widgets for hit
+/// testing
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Inputs {
+    /// Cursor position in window pixels
+    pub mouse_position: glm::Vec2,
+    /// Left mouse button is held down
+    pub mouse_down: bool,
+    /// Left mouse button went down since the last frame
+    pub mouse_pressed: bool,
+    /// Left mouse button went up since the last frame
+    pub mouse_released: bool,
+    /// Left mouse button went down a second time close to the last press since the last frame
+    pub mouse_double_clicked: bool,
+    pub gamepad: Gamepad,
+    /// Wheel movement since the last frame in lines
+    scroll: glm::Vec2,
+    modifiers: Modifiers,
+    /// Keys held down
+    #[cfg(feature = "winit")]
+    keys: HashSet<VirtualKeyCode>,
+    /// Time and position of the last press, the start of a double click
+    last_press: Option<(Instant, glm::Vec2)>,
+    /// Characters typed since the last frame
+    text: String,
+    /// Text the IME is composing, not typed yet
+    preedit: Option<String>,
+}
+
+impl Inputs {
+    /// Longest time between the presses of a double click
+    pub const DOUBLE_CLICK_TIME: Duration = Duration::from_millis(500);
+
+    /// Farthest the cursor can move between the presses of a double click in pixels
+    pub const DOUBLE_CLICK_DISTANCE: f32 = 4.0;
+
+    /// Pixels of a scrolled line, touchpads scroll by pixels instead of lines
+    #[cfg(feature = "winit")]
+    const SCROLL_LINE_PIXELS: f32 = 20.0;
+
+    /// Updates the state from an event of any windowing backend
+    pub fn handle_input_event(&mut self, event: &InputEvent) {
+        match event {
+            InputEvent::CursorMoved(position) => self.mouse_position = *position,
+            InputEvent::MouseButton(true) => self.press(Instant::now()),
+            InputEvent::MouseButton(false) => {
+                self.mouse_down = false;
+                self.mouse_released = true;
+            }
+            InputEvent::Scroll(delta) => self.scroll += delta,
+            InputEvent::Modifiers(modifiers) => self.modifiers = *modifiers,
+            InputEvent::FocusLost => {
+                #[cfg(feature = "winit")]
+                self.keys.clear();
+            }
+            // Control characters like backspace are left to the key events
+            InputEvent::Text(text) => self
+                .text
+                .extend(text.chars().filter(|character| !character.is_control())),
+            InputEvent::Preedit(text) => {
+                self.preedit = text.clone().filter(|text| !text.is_empty())
+            }
+        }
+    }
+
+    /// Updates the state from a window event
+    #[cfg(feature = "winit")]
+    pub fn handle_event(&mut self, event: &WindowEvent) {
+        let event = match event {
+            WindowEvent::CursorMoved { position, .. } => {
+                InputEvent::CursorMoved(glm::vec2(position.x as f32, position.y as f32))
+            }
+            WindowEvent::MouseInput {
+                button: MouseButton::Left,
+                state,
+                ..
+            } => InputEvent::MouseButton(*state == ElementState::Pressed),
+            WindowEvent::MouseWheel { delta, .. } => InputEvent::Scroll(Self::scroll_lines(delta)),
+            WindowEvent::ModifiersChanged(modifiers) => InputEvent::Modifiers(Modifiers {
+                ctrl: modifiers.ctrl(),
+                shift: modifiers.shift(),
+                alt: modifiers.alt(),
+                logo: modifiers.logo(),
+            }),
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        virtual_keycode: Some(key),
+                        state,
+                        ..
+                    },
+                ..
+            } => {
+                match state {
+                    ElementState::Pressed => self.keys.insert(*key),
+                    ElementState::Released => self.keys.remove(key),
+                };
+                return;
+            }
+            // Releases are not reported to unfocused windows
+            WindowEvent::Focused(false) => InputEvent::FocusLost,
+            WindowEvent::ReceivedCharacter(character) => InputEvent::Text(character.to_string()),
+            WindowEvent::Ime(Ime::Commit(text)) => {
+                self.handle_input_event(&InputEvent::Preedit(None));
+                InputEvent::Text(text.clone())
+            }
+            WindowEvent::Ime(Ime::Preedit(text, _)) => InputEvent::Preedit(Some(text.clone())),
+            WindowEvent::Ime(Ime::Disabled) => InputEvent::Preedit(None),
+            _ => return,
+        };
+
+        self.handle_input_event(&event);
+    }
+
+    /// Takes the characters typed since the last frame, including shifted ones and the text
+    /// committed by an IME
+    ///
+    /// Composed text only arrives with `Window::set_ime_allowed` enabled. Text which is not taken
+    /// is dropped at the end of the frame
+    pub fn take_text(&mut self) -> String {
+        std::mem::take(&mut self.text)
+    }
+
+    /// Text the IME is composing, eg. to show it underlined at the cursor of a text box
+    pub fn preedit(&self) -> Option<&str> {
+        self.preedit.as_deref()
+    }
+
+    /// Wheel movement since the last frame in lines, positive y scrolls up and positive x right
+    pub fn scroll_delta(&self) -> glm::Vec2 {
+        self.scroll
+    }
+
+    /// Control key is held, or Command on macOS
+    pub fn ctrl(&self) -> bool {
+        if cfg!(target_os = "macos") {
+            self.modifiers.logo
+        } else {
+            self.modifiers.ctrl
+        }
+    }
+
+    pub fn shift(&self) -> bool {
+        self.modifiers.shift
+    }
+
+    pub fn alt(&self) -> bool {
+        self.modifiers.alt
+    }
+
+    /// Key is held down
+    #[cfg(feature = "winit")]
+    pub fn key_held(&self, key: VirtualKeyCode) -> bool {
+        self.keys.contains(&key)
+    }
+
+    fn press(&mut self, now: Instant) {
+        self.mouse_down = true;
+        self.mouse_pressed = true;
+
+        // Third press starts a new double click instead of finishing another one
+        let double_clicked = self.last_press.is_some_and(|(time, position)| {
+            now.duration_since(time) <= Self::DOUBLE_CLICK_TIME
+                && glm::distance(&position, &self.mouse_position) <= Self::DOUBLE_CLICK_DISTANCE
+        });
+
+        self.mouse_double_clicked |= double_clicked;
+        self.last_press = if double_clicked {
+            None
+        } else {
+            Some((now, self.mouse_position))
+        };
+    }
+
+    /// Wheel movement of `delta` in lines
+    #[cfg(feature = "winit")]
+    fn scroll_lines(delta: &MouseScrollDelta) -> glm::Vec2 {
+        match delta {
+            MouseScrollDelta::LineDelta(x, y) => glm::vec2(*x, *y),
+            MouseScrollDelta::PixelDelta(position) => {
+                glm::vec2(position.x as f32, position.y as f32) / Self::SCROLL_LINE_PIXELS
+            }
+        }
+    }
+
+    /// Updates the gamepad state from an event of the gamepad backend
+    pub fn handle_gamepad_event(&mut self, event: &GamepadEvent) {
+        self.gamepad.handle_event(event);
+    }
+
+    /// Clears the per frame button changes, called after the widgets of the frame
+    pub fn end_frame(&mut self) {
+        self.mouse_pressed = false;
+        self.mouse_released = false;
+        self.mouse_double_clicked = false;
+        self.scroll = glm::Vec2::zeros();
+        self.text.clear();
+        self.gamepad.end_frame();
+    }
+}
+
+/// Input of a windowing backend, translated by the application for [`Inputs::handle_input_event`]
+///
+/// With the `winit` feature [`Inputs::handle_event`] translates the window events of winit
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputEvent {
+    /// Cursor position in window pixels
+    CursorMoved(glm::Vec2),
+    /// Left mouse button went down (`true`) or up
+    MouseButton(bool),
+    /// Wheel movement in lines, positive y scrolls up and positive x right
+    Scroll(glm::Vec2),
+    Modifiers(Modifiers),
+    /// Held keys are released, their releases are not reported to unfocused windows
+    FocusLost,
+    /// Typed or IME committed text, control characters are dropped
+    Text(String),
+    /// Text the IME is composing, `None` when composing stopped
+    Preedit(Option<String>),
+}
+
+/// Modifier keys held down
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Modifiers {
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+    /// Windows or Command key
+    pub logo: bool,
+}
+
+//==================================================
+//=== Gamepad
+//==================================================
+
+/// Buttons of a gamepad in the standard layout, eg. `South` is A on Xbox and Cross on
+/// PlayStation controllers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GamepadButton {
+    South,
+    East,
+    North,
+    West,
+    LeftBumper,
+    RightBumper,
+    LeftStick,
+    RightStick,
+    Select,
+    Start,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+impl GamepadButton {
+    const COUNT: usize = 14;
+}
+
+/// Axes of a gamepad, sticks range from -1.0 to 1.0 with up and right being positive,
+/// triggers from 0.0 to 1.0
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GamepadAxis {
+    LeftStickX,
+    LeftStickY,
+    RightStickX,
+    RightStickY,
+    LeftTrigger,
+    RightTrigger,
+}
+
+impl GamepadAxis {
+    const COUNT: usize = 6;
+}
+
+/// Change of a gamepad, translated from the events of a gamepad backend like gilrs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GamepadEvent {
+    ButtonPressed(GamepadButton),
+    ButtonReleased(GamepadButton),
+    AxisChanged(GamepadAxis, f32),
+    Connected,
+    /// Releases every button and centers the axes
+    Disconnected,
+}
+
+/// Gamepad state of a frame, buttons are held or just pressed or released like the mouse button
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Gamepad {
+    pub connected: bool,
+    held: [bool; GamepadButton::COUNT],
+    pressed: [bool; GamepadButton::COUNT],
+    released: [bool; GamepadButton::COUNT],
+    axes: [f32; GamepadAxis::COUNT],
+}
+
+impl Gamepad {
+    /// Stick positions closer to the center are read as centered, worn sticks rarely rest at 0.0
+    pub const DEAD_ZONE: f32 = 0.15;
+
+    pub fn handle_event(&mut self, event: &GamepadEvent) {
+        match *event {
+            GamepadEvent::ButtonPressed(button) => {
+                self.held[button as usize] = true;
+                self.pressed[button as usize] = true;
+            }
+            GamepadEvent::ButtonReleased(button) => {
+                self.held[button as usize] = false;
+                self.released[button as usize] = true;
+            }
+            GamepadEvent::AxisChanged(axis, value) => {
+                let range = match axis {
+                    GamepadAxis::LeftTrigger | GamepadAxis::RightTrigger => 0.0..=1.0,
+                    _ => -1.0..=1.0,
+                };
+                self.axes[axis as usize] = value.clamp(*range.start(), *range.end());
+            }
+            GamepadEvent::Connected => self.connected = true,
+            GamepadEvent::Disconnected => *self = Self::default(),
+        }
+    }
+
+    /// Button is held down
+    pub fn is_held(&self, button: GamepadButton) -> bool {
+        self.held[button as usize]
+    }
+
+    /// Button went down since the last frame
+    pub fn just_pressed(&self, button: GamepadButton) -> bool {
+        self.pressed[button as usize]
+    }
+
+    /// Button went up since the last frame
+    pub fn just_released(&self, button: GamepadButton) -> bool {
+        self.released[button as usize]
+    }
+
+    /// Raw value of the `axis`
+    pub fn axis(&self, axis: GamepadAxis) -> f32 {
+        self.axes[axis as usize]
+    }
+
+    /// Position of the left stick with the [`Gamepad::DEAD_ZONE`] applied
+    pub fn left_stick(&self) -> glm::Vec2 {
+        self.stick(GamepadAxis::LeftStickX, GamepadAxis::LeftStickY)
+    }
+
+    /// Position of the right stick with the [`Gamepad::DEAD_ZONE`] applied
+    pub fn right_stick(&self) -> glm::Vec2 {
+        self.stick(GamepadAxis::RightStickX, GamepadAxis::RightStickY)
+    }
+
+    /// Clears the per frame button changes
+    pub fn end_frame(&mut self) {
+        self.pressed = [false; GamepadButton::COUNT];
+        self.released = [false; GamepadButton::COUNT];
+    }
+
+    /// Radial dead zone, the rest of the range is rescaled to start from 0.0
+    fn stick(&self, x: GamepadAxis, y: GamepadAxis) -> glm::Vec2 {
+        let position = glm::vec2(self.axis(x), self.axis(y));
+        let length = position.norm();
+
+        if length <= Self::DEAD_ZONE {
+            return glm::Vec2::zeros();
+        }
+
+        let scaled = ((length - Self::DEAD_ZONE) / (1.0 - Self::DEAD_ZONE)).min(1.0);
+        position * (scaled / length)
+    }
+}
+
+//==================================================
+//=== Ui
+//==================================================
+
+/// Rectangle given by its center and size, eg. of a widget relative to the view like
+/// [`AnchorType::Locked`] instances or the area of a [`crate::Plot`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UiRect {
+    pub center: glm::Vec2,
+    pub size: glm::Vec2,
+}
+
+impl UiRect {
+    /// Creates a new [`UiRect`] of `width` and `height` around the center
+    pub fn new(center_x: f32, center_y: f32, width: f32, height: f32) -> Self {
+        Self {
+            center: glm::vec2(center_x, center_y),
+            size: glm::vec2(width, height),
+        }
+    }
+
+    pub fn contains(&self, point: glm::Vec2) -> bool {
+        let offset = (point - self.center).abs() * 2.0;
+        offset.x <= self.size.x && offset.y <= self.size.y
+    }
+}
+
+/// Interaction with a widget in the current frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct WidgetState {
+    /// Cursor is above the widget
+    pub hovered: bool,
+    /// Widget is held down, eg. a dragged slider
+    pub active: bool,
+    /// Mouse button was pressed and released above the widget
+    pub clicked: bool,
+    /// Value of the widget changed
+    pub changed: bool,
+}
+
+/// Colors and text of the [`Ui`] widgets
+#[derive(Debug, Clone, Copy)]
+pub struct UiStyle {
+    pub color: glm::Vec3,
+    pub hover_color: glm::Vec3,
+    pub active_color: glm::Vec3,
+    /// Checkbox mark and slider fill
+    pub accent_color: glm::Vec3,
+    pub text_params: TextParams,
+    /// Widget backgrounds are drawn on this layer, their fill and text on the next two
+    pub layer: i32,
+}
+
+impl Default for UiStyle {
+    fn default() -> Self {
+        Self {
+            color: glm::vec3(0.25, 0.25, 0.25),
+            hover_color: glm::vec3(0.35, 0.35, 0.35),
+            active_color: glm::vec3(0.15, 0.15, 0.15),
+            accent_color: glm::vec3(0.3, 0.6, 1.0),
+            text_params: TextParams {
+                scale: 0.5,
+                ..TextParams::default()
+            },
+            layer: 1000,
+        }
+    }
+}
+
+/// Immediate mode widgets, drawn with the shapes and text of the [`Renderer`]
+///
+/// Widgets are identified by their label, which must be unique inside a frame
+#[derive(Debug, Clone, Default)]
+pub struct Ui {
+    pub style: UiStyle,
+    inputs: Inputs,
+    /// Cursor position relative to the view
+    cursor: glm::Vec2,
+    /// Widget held down since the mouse button was pressed above it
+    active: Option<u64>,
+}
+
+impl Ui {
+    /// Creates a new [`Ui`] with the default style
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts a frame of widgets with the `inputs`, the cursor is placed with the scene camera
+    pub fn begin(&mut self, inputs: &Inputs, renderer: &Renderer) {
+        let world = renderer.scene.screen_to_world(inputs.mouse_position);
+
+        self.cursor = renderer.scene.world_to_view(world);
+        self.inputs = inputs.clone();
+
+        if !inputs.mouse_down && !inputs.mouse_released {
+            self.active = None;
+        }
+    }
+
+    /// Draws a text centered on `position`
+    pub fn label(
+        &mut self,
+        renderer: &mut Renderer,
+        text: &str,
+        position: glm::Vec2,
+    ) -> Result<()> {
+        self.draw_text(renderer, text, position, HorizontalAlign::Center)
+    }
+
+    /// Button, `clicked` when released above it after being pressed above it
+    pub fn button(
+        &mut self,
+        renderer: &mut Renderer,
+        label: &str,
+        rect: UiRect,
+    ) -> Result<WidgetState> {
+        let state = self.interact(widget_id(label), rect.contains(self.cursor));
+
+        self.draw_rect(renderer, rect, self.background(&state), self.style.layer)?;
+        self.draw_text(renderer, label, rect.center, HorizontalAlign::Center)?;
+
+        Ok(state)
+    }
+
+    /// Box toggling `value` when clicked, followed by the `label` on its right
+    pub fn checkbox(
+        &mut self,
+        renderer: &mut Renderer,
+        label: &str,
+        rect: UiRect,
+        value: &mut bool,
+    ) -> Result<WidgetState> {
+        let mut state = self.interact(widget_id(label), rect.contains(self.cursor));
+
+        if state.clicked {
+            *value = !*value;
+            state.changed = true;
+        }
+
+        self.draw_rect(renderer, rect, self.background(&state), self.style.layer)?;
+        if *value {
+            let mark = UiRect {
+                size: rect.size * 0.6,
+                ..rect
+            };
+            self.draw_rect(
+                renderer,
+                mark,
+                self.style.accent_color,
+                self.style.layer + 1,
+            )?;
+        }
+
+        let text_position = glm::vec2(rect.center.x + rect.size.x, rect.center.y);
+        self.draw_text(renderer, label, text_position, HorizontalAlign::Left)?;
+
+        Ok(state)
+    }
+
+    /// Horizontal slider setting `value` inside `range` while it is dragged
+    pub fn slider(
+        &mut self,
+        renderer: &mut Renderer,
+        label: &str,
+        rect: UiRect,
+        value: &mut f32,
+        range: RangeInclusive<f32>,
+    ) -> Result<WidgetState> {
+        let mut state = self.interact(widget_id(label), rect.contains(self.cursor));
+
+        if state.active {
+            let new_value = slider_value(&rect, self.cursor.x, &range);
+            state.changed = new_value != *value;
+            *value = new_value;
+        }
+
+        let (min, max) = (*range.start(), *range.end());
+        let fraction = if max > min {
+            ((*value - min) / (max - min)).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        let fill = UiRect {
+            center: glm::vec2(
+                rect.center.x - rect.size.x * 0.5 * (1.0 - fraction),
+                rect.center.y,
+            ),
+            size: glm::vec2(rect.size.x * fraction, rect.size.y),
+        };
+
+        self.draw_rect(renderer, rect, self.background(&state), self.style.layer)?;
+        self.draw_rect(
+            renderer,
+            fill,
+            self.style.accent_color,
+            self.style.layer + 1,
+        )?;
+        self.draw_text(
+            renderer,
+            &format!("{}: {:.2}", label, value),
+            rect.center,
+            HorizontalAlign::Center,
+        )?;
+
+        Ok(state)
+    }
+
+    /// Updates the active widget and gives back the interaction state of widget `id`
+    fn interact(&mut self, id: u64, hovered: bool) -> WidgetState {
+        if hovered && self.inputs.mouse_pressed {
+            self.active = Some(id);
+        }
+
+        let held = self.active == Some(id);
+
+        WidgetState {
+            hovered,
+            active: held && self.inputs.mouse_down,
+            clicked: held && hovered && self.inputs.mouse_released,
+            changed: false,
+        }
+    }
+
+    fn background(&self, state: &WidgetState) -> glm::Vec3 {
+        if state.active {
+            self.style.active_color
+        } else if state.hovered {
+            self.style.hover_color
+        } else {
+            self.style.color
+        }
+    }
+
+    fn draw_rect(
+        &self,
+        renderer: &mut Renderer,
+        rect: UiRect,
+        color: glm::Vec3,
+        layer: i32,
+    ) -> Result<()> {
+        let previous_layer = std::mem::replace(&mut renderer.draw_params.layer, layer);
+        let previous_pivot = std::mem::take(&mut renderer.draw_params.pivot);
+
+        let result = renderer.rectangle(
+            rect.size.x / RECTANGLE_SIZE,
+            rect.size.y / RECTANGLE_SIZE,
+            0.0,
+            rect.center.x,
+            rect.center.y,
+            color,
+            AnchorType::Locked,
+        );
+
+        renderer.draw_params.layer = previous_layer;
+        renderer.draw_params.pivot = previous_pivot;
+        Ok(result?)
+    }
+
+    /// Draws a text vertically centered on `position`, above the widget rectangles
+    fn draw_text(
+        &self,
+        renderer: &mut Renderer,
+        text: &str,
+        position: glm::Vec2,
+        horizontal_align: HorizontalAlign,
+    ) -> Result<()> {
+        let previous_layer =
+            std::mem::replace(&mut renderer.draw_params.layer, self.style.layer + 2);
+
+        let params = TextParams {
+            horizontal_align,
+            vertical_align: VerticalAlign::Center,
+            ..self.style.text_params
+        };
+        let result = renderer.text(text, position.x, position.y, &params, AnchorType::Locked);
+
+        renderer.draw_params.layer = previous_layer;
+        Ok(result?)
+    }
+}
+
+/// Identifier of a widget, based on its label
+fn widget_id(label: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    label.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Gives back the value of a slider in `rect` at the horizontal cursor position `x`
+fn slider_value(rect: &UiRect, x: f32, range: &RangeInclusive<f32>) -> f32 {
+    let left = rect.center.x - rect.size.x * 0.5;
+    let fraction = if rect.size.x > 0.0 {
+        ((x - left) / rect.size.x).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+
+    range.start() + (range.end() - range.start()) * fraction
+}
+
+//==================================================
+//=== Unit Testing
+//==================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ui_interaction() {
+        let mut ui = Ui::new();
+        let rect = UiRect::new(0.0, 0.0, 0.4, 0.2);
+
+        // Pressed above the widget -> Active, released above it -> Clicked
+        ui.inputs = Inputs {
+            mouse_down: true,
+            mouse_pressed: true,
+            ..Inputs::default()
+        };
+        let state = ui.interact(widget_id("button"), rect.contains(glm::vec2(0.1, 0.05)));
+        assert!(state.active && !state.clicked);
+
+        ui.inputs = Inputs {
+            mouse_released: true,
+            ..Inputs::default()
+        };
+        let state = ui.interact(widget_id("button"), true);
+        assert!(state.clicked && !state.active);
+
+        // Released above a widget which was not pressed
+        let state = ui.interact(widget_id("other"), true);
+        assert!(!state.clicked);
+
+        assert!(!rect.contains(glm::vec2(0.3, 0.0)));
+        assert_eq!(slider_value(&rect, 0.0, &(0.0..=10.0)), 5.0);
+        assert_eq!(slider_value(&rect, 1.0, &(0.0..=10.0)), 10.0);
+    }
+
+    #[test]
+    fn test_double_click_and_scroll() {
+        let mut inputs = Inputs::default();
+        let start = Instant::now();
+
+        inputs.press(start);
+        assert!(!inputs.mouse_double_clicked);
+        inputs.press(start + Duration::from_millis(200));
+        assert!(inputs.mouse_double_clicked);
+
+        // Too slow or too far
+        inputs.end_frame();
+        inputs.press(start + Duration::from_secs(1));
+        inputs.press(start + Duration::from_secs(2));
+        assert!(!inputs.mouse_double_clicked);
+        inputs.mouse_position = glm::vec2(50.0, 0.0);
+        inputs.press(start + Duration::from_millis(2100));
+        assert!(!inputs.mouse_double_clicked);
+
+        inputs.handle_input_event(&InputEvent::Scroll(glm::vec2(0.0, 1.0)));
+        inputs.handle_input_event(&InputEvent::Scroll(glm::vec2(0.0, 2.0)));
+        assert_eq!(inputs.scroll_delta(), glm::vec2(0.0, 3.0));
+        inputs.end_frame();
+        assert_eq!(inputs.scroll_delta(), glm::Vec2::zeros());
+    }
+
+    #[test]
+    fn test_text_input() {
+        let mut inputs = Inputs::default();
+
+        // Control characters are left out, the preedit is replaced until it is committed
+        inputs.handle_input_event(&InputEvent::Text("Hi\u{8}!".to_string()));
+        inputs.handle_input_event(&InputEvent::Preedit(Some("k".to_string())));
+        assert_eq!(inputs.preedit(), Some("k"));
+        inputs.handle_input_event(&InputEvent::Preedit(None));
+        inputs.handle_input_event(&InputEvent::Text("é".to_string()));
+        assert_eq!(inputs.preedit(), None);
+        assert_eq!(inputs.take_text(), "Hi!é");
+        assert_eq!(inputs.take_text(), "");
+    }
+
+    #[cfg(feature = "winit")]
+    #[test]
+    fn test_winit_input() {
+        let mut inputs = Inputs::default();
+
+        inputs.handle_event(&WindowEvent::MouseWheel {
+            device_id: unsafe { winit::event::DeviceId::dummy() },
+            delta: MouseScrollDelta::PixelDelta(winit::dpi::PhysicalPosition::new(0.0, 40.0)),
+            phase: winit::event::TouchPhase::Moved,
+            #[allow(deprecated)]
+            modifiers: Default::default(),
+        });
+        assert_eq!(inputs.scroll_delta(), glm::vec2(0.0, 2.0));
+
+        for character in ['H', 'i', '\u{8}', '!'] {
+            inputs.handle_event(&WindowEvent::ReceivedCharacter(character));
+        }
+        inputs.handle_event(&WindowEvent::Ime(Ime::Preedit("k".to_string(), None)));
+        assert_eq!(inputs.preedit(), Some("k"));
+        inputs.handle_event(&WindowEvent::Ime(Ime::Commit("é".to_string())));
+        assert_eq!(inputs.preedit(), None);
+        assert_eq!(inputs.take_text(), "Hi!é");
+    }
+
+    #[test]
+    fn test_gamepad_state() {
+        let mut inputs = Inputs::default();
+        inputs.handle_gamepad_event(&GamepadEvent::ButtonPressed(GamepadButton::South));
+        inputs.handle_gamepad_event(&GamepadEvent::AxisChanged(GamepadAxis::LeftStickX, 0.1));
+        inputs.handle_gamepad_event(&GamepadEvent::AxisChanged(GamepadAxis::RightStickY, 2.0));
+
+        assert!(inputs.gamepad.just_pressed(GamepadButton::South));
+        assert_eq!(inputs.gamepad.left_stick(), glm::Vec2::zeros());
+        assert_eq!(inputs.gamepad.right_stick(), glm::vec2(0.0, 1.0));
+
+        // Held over frames, pressed only in the first one
+        inputs.end_frame();
+        assert!(inputs.gamepad.is_held(GamepadButton::South));
+        assert!(!inputs.gamepad.just_pressed(GamepadButton::South));
+
+        inputs.handle_gamepad_event(&GamepadEvent::Disconnected);
+        assert!(!inputs.gamepad.is_held(GamepadButton::South));
+    }
+}
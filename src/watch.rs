@@ -0,0 +1,141 @@
+// std
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+// intern
+use crate::ObjectHandle;
+
+//==================================================
+//=== Object Watcher
+//==================================================
+
+/// .obj file loaded from disk, parsed again when it changes
+#[derive(Debug, Clone)]
+pub(crate) struct WatchedFile {
+    pub path: PathBuf,
+    /// Colored by the .mtl file of the same name, which is watched too
+    pub with_mtl: bool,
+    /// Objects of the file in file order
+    pub handles: Vec<ObjectHandle>,
+    modified: Option<SystemTime>,
+}
+
+/// Polls the modification times of the loaded .obj files
+#[derive(Debug, Default)]
+pub(crate) struct ObjectWatcher {
+    files: Vec<WatchedFile>,
+    last_check: Option<Instant>,
+}
+
+impl ObjectWatcher {
+    /// Shortest time between two checks of the files
+    const CHECK_INTERVAL: Duration = Duration::from_millis(250);
+
+    /// Starts watching the file at `path` holding the objects of `handles`, a file already
+    /// watched gets the new handles
+    pub fn watch(&mut self, path: &Path, with_mtl: bool, handles: Vec<ObjectHandle>) {
+        let modified = modified(path, with_mtl);
+
+        match self.files.iter_mut().find(|file| file.path == path) {
+            Some(file) => {
+                file.with_mtl = with_mtl;
+                file.handles = handles;
+                file.modified = modified;
+            }
+            None => self.files.push(WatchedFile {
+                path: path.to_path_buf(),
+                with_mtl,
+                handles,
+                modified,
+            }),
+        }
+    }
+
+    /// Sets the handles of the watched file at `path` after it was reloaded
+    pub fn set_handles(&mut self, path: &Path, handles: Vec<ObjectHandle>) {
+        if let Some(file) = self.files.iter_mut().find(|file| file.path == path) {
+            file.handles = handles;
+        }
+    }
+
+    /// Gives back the files modified since the last check, checks at most every
+    /// [`ObjectWatcher::CHECK_INTERVAL`]
+    ///
+    /// A file being removed or written is only reported when it is readable again
+    pub fn changed(&mut self) -> Vec<WatchedFile> {
+        if self
+            .last_check
+            .is_some_and(|last_check| last_check.elapsed() < Self::CHECK_INTERVAL)
+        {
+            return Vec::new();
+        }
+        self.last_check = Some(Instant::now());
+
+        let mut changed = Vec::new();
+        for file in &mut self.files {
+            let modified = modified(&file.path, file.with_mtl);
+
+            if modified.is_some() && modified != file.modified {
+                file.modified = modified;
+                changed.push(file.clone());
+            }
+        }
+
+        changed
+    }
+}
+
+/// Latest modification time of the .obj file at `path` and its .mtl file
+fn modified(path: &Path, with_mtl: bool) -> Option<SystemTime> {
+    let modified = |path: &Path| {
+        std::fs::metadata(path)
+            .and_then(|meta| meta.modified())
+            .ok()
+    };
+
+    let obj_modified = modified(path)?;
+    if !with_mtl {
+        return Some(obj_modified);
+    }
+
+    Some(obj_modified.max(modified(&path.with_extension("mtl"))?))
+}
+
+//==================================================
+//=== Unit Testing
+//==================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_object_watcher() {
+        let dir = std::env::temp_dir().join(format!("lavapond_watch_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("shape.obj");
+        std::fs::write(&path, "o S\n").unwrap();
+
+        let mut watcher = ObjectWatcher::default();
+        watcher.watch(&path, false, vec![ObjectHandle(3)]);
+        assert!(watcher.changed().is_empty());
+
+        // Checked again only after the interval
+        let file = std::fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(10))
+            .unwrap();
+        assert!(watcher.changed().is_empty());
+
+        watcher.last_check = None;
+        let changed = watcher.changed();
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].handles, vec![ObjectHandle(3)]);
+
+        // Removed files are not reported
+        std::fs::remove_file(&path).unwrap();
+        watcher.last_check = None;
+        assert!(watcher.changed().is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}